@@ -140,7 +140,7 @@ fn main() {
 
     let set = Arc::new(simple_descriptor_set!(pipeline.clone(), 0, {
         tex: (texture.clone(), sampler.clone())
-    }));
+    }).unwrap());
 
     let framebuffers = images.iter().map(|image| {
         Arc::new(vulkano::framebuffer::Framebuffer::start(renderpass.clone())
@@ -160,7 +160,7 @@ fn main() {
             .unwrap()
             //.clear_color_image(&texture, [0.0, 1.0, 0.0, 1.0])
             .begin_render_pass(
-                framebuffers[image_num].clone(), false,
+                framebuffers[image_num].clone(), vulkano::command_buffer::SubpassContents::Inline,
                 vec![[0.0, 0.0, 1.0, 1.0].into()]).unwrap()
             .draw(pipeline.clone(), vulkano::command_buffer::DynamicState::none(), vertex_buffer.clone(),
                   set.clone(), ()).unwrap()
@@ -169,7 +169,7 @@ fn main() {
 
         let future = previous_frame_end.join(future)
             .then_execute(queue.clone(), cb).unwrap()
-            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num)
+            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num).unwrap()
             .then_signal_fence_and_flush().unwrap();
         previous_frame_end = Box::new(future) as Box<_>;
 
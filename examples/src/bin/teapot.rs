@@ -139,7 +139,7 @@ fn main() {
 
     let set = Arc::new(simple_descriptor_set!(pipeline.clone(), 0, {
         uniforms: uniform_buffer.clone()
-    }));
+    }).unwrap());
 
     let framebuffers = images.iter().map(|image| {
         Arc::new(vulkano::framebuffer::Framebuffer::start(renderpass.clone())
@@ -169,7 +169,7 @@ fn main() {
 
         let command_buffer = vulkano::command_buffer::AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
             .begin_render_pass(
-                framebuffers[image_num].clone(), false,
+                framebuffers[image_num].clone(), vulkano::command_buffer::SubpassContents::Inline,
                 vec![
                     [0.0, 0.0, 1.0, 1.0].into(),
                     1f32.into()
@@ -183,7 +183,7 @@ fn main() {
         
         let future = previous_frame.join(acquire_future)
             .then_execute(queue.clone(), command_buffer).unwrap()
-            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num)
+            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num).unwrap()
             .then_signal_fence_and_flush().unwrap();
         previous_frame = Box::new(future) as Box<_>;
 
@@ -40,6 +40,7 @@ use vulkano::buffer::CpuAccessibleBuffer;
 use vulkano::command_buffer::AutoCommandBufferBuilder;
 use vulkano::command_buffer::CommandBufferBuilder;
 use vulkano::command_buffer::DynamicState;
+use vulkano::command_buffer::SubpassContents;
 use vulkano::device::Device;
 use vulkano::framebuffer::Framebuffer;
 use vulkano::framebuffer::Subpass;
@@ -343,14 +344,14 @@ void main() {
         // Note that we have to pass a queue family when we create the command buffer. The command
         // buffer will only be executable on that given queue family.
         let command_buffer = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
-            // Before we can draw, we have to *enter a render pass*. There are two methods to do
-            // this: `draw_inline` and `draw_secondary`. The latter is a bit more advanced and is
-            // not covered here.
+            // Before we can draw, we have to *enter a render pass*. The second parameter tells
+            // whether the first subpass contains inline draw commands or executes secondary
+            // command buffers. The latter is a bit more advanced and is not covered here.
             //
             // The third parameter builds the list of values to clear the attachments with. The API
             // is similar to the list of attachments when building the framebuffers, except that
             // only the attachments that use `load: Clear` appear in the list.
-            .begin_render_pass(framebuffers[image_num].clone(), false,
+            .begin_render_pass(framebuffers[image_num].clone(), SubpassContents::Inline,
                                vec![[0.0, 0.0, 1.0, 1.0].into()])
             .unwrap()
 
@@ -361,9 +362,8 @@ void main() {
             .draw(pipeline.clone(), DynamicState::none(), vertex_buffer.clone(), (), ())
             .unwrap()
 
-            // We leave the render pass by calling `draw_end`. Note that if we had multiple
-            // subpasses we could have called `next_inline` (or `next_secondary`) to jump to the
-            // next subpass.
+            // We leave the render pass by calling `end_render_pass`. Note that if we had multiple
+            // subpasses we could have called `next_subpass` to jump to the next subpass.
             .end_render_pass()
             .unwrap()
 
@@ -379,7 +379,7 @@ void main() {
             // This function does not actually present the image immediately. Instead it submits a
             // present command at the end of the queue. This means that it will only be presented once
             // the GPU has finished executing the command buffer that draws the triangle.
-            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num)
+            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num).unwrap()
             .then_signal_fence_and_flush().unwrap();
         previous_frame_end = Box::new(future) as Box<_>;
 
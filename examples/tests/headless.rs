@@ -0,0 +1,164 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+// Renders a triangle without any window, surface or swapchain, then reads back the result.
+
+#[macro_use]
+extern crate vulkano;
+#[macro_use]
+extern crate vulkano_shader_derive;
+
+use vulkano::buffer::BufferUsage;
+use vulkano::buffer::CpuAccessibleBuffer;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::command_buffer::CommandBufferBuilder;
+use vulkano::command_buffer::DynamicState;
+use vulkano::command_buffer::SubpassContents;
+use vulkano::device::Device;
+use vulkano::device::DeviceExtensions;
+use vulkano::format::Format;
+use vulkano::framebuffer::Framebuffer;
+use vulkano::framebuffer::Subpass;
+use vulkano::image::OffscreenFrame;
+use vulkano::instance::Instance;
+use vulkano::instance::InstanceExtensions;
+use vulkano::instance::PhysicalDevice;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::sync::GpuFuture;
+
+use std::iter;
+use std::sync::Arc;
+
+mod vs {
+    #[derive(VulkanoShader)]
+    #[ty = "vertex"]
+    #[src = "
+#version 450
+
+layout(location = 0) in vec2 position;
+
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+"]
+    struct Dummy;
+}
+
+mod fs {
+    #[derive(VulkanoShader)]
+    #[ty = "fragment"]
+    #[src = "
+#version 450
+
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    f_color = vec4(1.0, 0.0, 0.0, 1.0);
+}
+"]
+    struct Dummy;
+}
+
+#[test]
+fn triangle_without_surface() {
+    // No surface extension is enabled on the instance, and no swapchain extension on the device.
+    let instance = match Instance::new(None, &InstanceExtensions::none(), None) {
+        Ok(i) => i,
+        Err(_) => return
+    };
+
+    let physical = match PhysicalDevice::enumerate(&instance).next() {
+        Some(p) => p,
+        None => return
+    };
+
+    let queue = match physical.queue_families().find(|q| q.supports_graphics()) {
+        Some(q) => q,
+        None => return
+    };
+
+    let (device, mut queues) = Device::new(&physical, physical.supported_features(),
+                                           &DeviceExtensions::none(),
+                                           [(queue, 0.5)].iter().cloned()).unwrap();
+    let queue = queues.next().unwrap();
+
+    let frame = OffscreenFrame::new(queue.clone(), [64, 64], Format::R8G8B8A8Unorm).unwrap();
+
+    let vertex_buffer = {
+        #[derive(Debug, Clone)]
+        struct Vertex { position: [f32; 2] }
+        impl_vertex!(Vertex, position);
+
+        CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(), Some(queue.family()), [
+            Vertex { position: [-0.5, -0.5] },
+            Vertex { position: [0.5, -0.5] },
+            Vertex { position: [0.0, 0.5] }
+        ].iter().cloned()).unwrap()
+    };
+
+    let vs = vs::Shader::load(&device).unwrap();
+    let fs = fs::Shader::load(&device).unwrap();
+
+    let render_pass = Arc::new(single_pass_renderpass!(device.clone(),
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: Format::R8G8B8A8Unorm,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    ).unwrap());
+
+    let pipeline = Arc::new(GraphicsPipeline::start()
+        .vertex_input_single_buffer()
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_list()
+        .viewports(iter::once(Viewport {
+            origin: [0.0, 0.0],
+            depth_range: 0.0 .. 1.0,
+            dimensions: [64.0, 64.0],
+        }))
+        .fragment_shader(fs.main_entry_point(), ())
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build(device.clone())
+        .unwrap());
+
+    let framebuffer = Arc::new(Framebuffer::start(render_pass.clone())
+        .add(frame.image().clone()).unwrap()
+        .build().unwrap());
+
+    let command_buffer = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+        .begin_render_pass(framebuffer.clone(), SubpassContents::Inline,
+                           vec![[0.0, 0.0, 1.0, 1.0].into()])
+        .unwrap()
+        .draw(pipeline.clone(), DynamicState::none(), vertex_buffer.clone(), (), ())
+        .unwrap()
+        .end_render_pass()
+        .unwrap()
+        .build().unwrap();
+
+    let future = vulkano::sync::now(device.clone())
+        .then_execute(queue.clone(), command_buffer).unwrap();
+
+    let data = frame.read(future).unwrap();
+    assert_eq!(data.len(), 64 * 64 * 4);
+
+    let pixel = |x: usize, y: usize| &data[(y * 64 + x) * 4 .. (y * 64 + x + 1) * 4];
+    // The center of the image is covered by the triangle.
+    assert_eq!(pixel(32, 32), &[255, 0, 0, 255]);
+    // The corners keep the clear value.
+    assert_eq!(pixel(0, 0), &[0, 0, 255, 255]);
+    assert_eq!(pixel(63, 63), &[0, 0, 255, 255]);
+}
@@ -19,6 +19,7 @@ use std::os::raw::c_char;
 use std::os::raw::c_void;
 use std::os::raw::c_ulong;
 use std::os::raw::c_double;
+use std::os::raw::c_int;
 
 pub type Flags = u32;
 pub type Bool32 = u32;
@@ -57,6 +58,7 @@ pub type DisplayKHR = u64;
 pub type DisplayModeKHR = u64;
 pub type DebugReportCallbackEXT = u64;
 pub type DescriptorUpdateTemplateKHR = u64;
+pub type SamplerYcbcrConversionKHR = u64;
 
 pub const LOD_CLAMP_NONE: f32 = 1000.0;
 pub const REMAINING_MIP_LEVELS: u32 = 0xffffffff;
@@ -95,6 +97,7 @@ pub const ERROR_FEATURE_NOT_PRESENT: u32 = -8i32 as u32;
 pub const ERROR_INCOMPATIBLE_DRIVER: u32 = -9i32 as u32;
 pub const ERROR_TOO_MANY_OBJECTS: u32 = -10i32 as u32;
 pub const ERROR_FORMAT_NOT_SUPPORTED: u32 = -11i32 as u32;
+pub const ERROR_FRAGMENTED_POOL: u32 = -12i32 as u32;
 pub const ERROR_SURFACE_LOST_KHR: u32 = -1000000000i32 as u32;
 pub const ERROR_NATIVE_WINDOW_IN_USE_KHR: u32 = -1000000001i32 as u32;
 pub const SUBOPTIMAL_KHR: u32 = 1000001003;
@@ -102,6 +105,7 @@ pub const ERROR_OUT_OF_DATE_KHR: u32 = -1000001004i32 as u32;
 pub const ERROR_INCOMPATIBLE_DISPLAY_KHR: u32 = -1000003001i32 as u32;
 pub const ERROR_VALIDATION_FAILED_EXT: u32 = -1000011001i32 as u32;
 pub const ERROR_OUT_OF_POOL_MEMORY_KHR: u32 = -1000069000i32 as u32;
+pub const ERROR_INVALID_EXTERNAL_HANDLE_KHR: u32 = -1000072003i32 as u32;
 
 pub type StructureType = u32;
 pub const STRUCTURE_TYPE_APPLICATION_INFO: u32 = 0;
@@ -167,6 +171,7 @@ pub const STRUCTURE_TYPE_WIN32_SURFACE_CREATE_INFO_KHR: u32 = 1000009000;
 pub const STRUCTURE_TYPE_DEBUG_REPORT_CREATE_INFO_EXT: u32 = 1000011000;
 pub const STRUCTURE_TYPE_IOS_SURFACE_CREATE_INFO_MVK: u32 = 1000000000 + (52 * 1000);
 pub const STRUCTURE_TYPE_MACOS_SURFACE_CREATE_INFO_MVK: u32 = 1000000000 + (53 * 1000);
+pub const STRUCTURE_TYPE_METAL_SURFACE_CREATE_INFO_EXT: u32 = 1000217000;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_FEATURES_2_KHR: u32 = 1000059000;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2_KHR: u32 = 1000059001;
 pub const STRUCTURE_TYPE_FORMAT_PROPERTIES_2_KHR: u32 = 1000059002;
@@ -177,8 +182,49 @@ pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_MEMORY_PROPERTIES_2_KHR: u32 = 10000590
 pub const STRUCTURE_TYPE_SPARSE_IMAGE_FORMAT_PROPERTIES_2_KHR: u32 = 1000059007;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SPARSE_IMAGE_FORMAT_INFO_2_KHR: u32 = 1000059008;
 pub const STRUCTURE_TYPE_VI_SURFACE_CREATE_INFO_NN: u32 = 1000062000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_IMAGE_FORMAT_INFO_KHR: u32 = 1000071000;
+pub const STRUCTURE_TYPE_EXTERNAL_IMAGE_FORMAT_PROPERTIES_KHR: u32 = 1000071001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_BUFFER_INFO_KHR: u32 = 1000071002;
+pub const STRUCTURE_TYPE_EXTERNAL_BUFFER_PROPERTIES_KHR: u32 = 1000071003;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_ID_PROPERTIES_KHR: u32 = 1000071004;
+pub const STRUCTURE_TYPE_EXTERNAL_MEMORY_BUFFER_CREATE_INFO_KHR: u32 = 1000072000;
+pub const STRUCTURE_TYPE_EXTERNAL_MEMORY_IMAGE_CREATE_INFO_KHR: u32 = 1000072001;
+pub const STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO_KHR: u32 = 1000072002;
+pub const STRUCTURE_TYPE_IMPORT_MEMORY_WIN32_HANDLE_INFO_KHR: u32 = 1000073000;
+pub const STRUCTURE_TYPE_EXPORT_MEMORY_WIN32_HANDLE_INFO_KHR: u32 = 1000073001;
+pub const STRUCTURE_TYPE_MEMORY_WIN32_HANDLE_PROPERTIES_KHR: u32 = 1000073002;
+pub const STRUCTURE_TYPE_MEMORY_GET_WIN32_HANDLE_INFO_KHR: u32 = 1000073003;
+pub const STRUCTURE_TYPE_IMPORT_MEMORY_FD_INFO_KHR: u32 = 1000074000;
+pub const STRUCTURE_TYPE_MEMORY_FD_PROPERTIES_KHR: u32 = 1000074001;
+pub const STRUCTURE_TYPE_MEMORY_GET_FD_INFO_KHR: u32 = 1000074002;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO_KHR: u32 = 1000076000;
+pub const STRUCTURE_TYPE_EXTERNAL_SEMAPHORE_PROPERTIES_KHR: u32 = 1000076001;
+pub const STRUCTURE_TYPE_EXPORT_SEMAPHORE_CREATE_INFO_KHR: u32 = 1000077000;
+pub const STRUCTURE_TYPE_IMPORT_SEMAPHORE_WIN32_HANDLE_INFO_KHR: u32 = 1000078000;
+pub const STRUCTURE_TYPE_EXPORT_SEMAPHORE_WIN32_HANDLE_INFO_KHR: u32 = 1000078001;
+pub const STRUCTURE_TYPE_D3D12_FENCE_SUBMIT_INFO_KHR: u32 = 1000078002;
+pub const STRUCTURE_TYPE_SEMAPHORE_GET_WIN32_HANDLE_INFO_KHR: u32 = 1000078003;
+pub const STRUCTURE_TYPE_IMPORT_SEMAPHORE_FD_INFO_KHR: u32 = 1000079000;
+pub const STRUCTURE_TYPE_SEMAPHORE_GET_FD_INFO_KHR: u32 = 1000079001;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_PUSH_DESCRIPTOR_PROPERTIES_KHR: u32 = 1000080000;
 pub const STRUCTURE_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_CREATE_INFO_KHR: u32 = 1000085000;
+pub const STRUCTURE_TYPE_SAMPLER_YCBCR_CONVERSION_CREATE_INFO_KHR: u32 = 1000156000;
+pub const STRUCTURE_TYPE_SAMPLER_YCBCR_CONVERSION_INFO_KHR: u32 = 1000156001;
+pub const STRUCTURE_TYPE_BIND_IMAGE_PLANE_MEMORY_INFO_KHR: u32 = 1000156002;
+pub const STRUCTURE_TYPE_IMAGE_PLANE_MEMORY_REQUIREMENTS_INFO_KHR: u32 = 1000156003;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SAMPLER_YCBCR_CONVERSION_FEATURES_KHR: u32 = 1000156004;
+pub const STRUCTURE_TYPE_SAMPLER_YCBCR_CONVERSION_IMAGE_FORMAT_PROPERTIES_KHR: u32 = 1000156005;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_INDEX_TYPE_UINT8_FEATURES_EXT: u32 = 1000265000;
+pub const STRUCTURE_TYPE_COMMAND_BUFFER_INHERITANCE_CONDITIONAL_RENDERING_INFO_EXT: u32 = 1000081000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_CONDITIONAL_RENDERING_FEATURES_EXT: u32 = 1000081001;
+pub const STRUCTURE_TYPE_CONDITIONAL_RENDERING_BEGIN_INFO_EXT: u32 = 1000081002;
+pub const STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT: u32 = 1000128002;
+pub const STRUCTURE_TYPE_RENDER_PASS_MULTIVIEW_CREATE_INFO_KHR: u32 = 1000053000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_FEATURES_KHR: u32 = 1000053001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_PROPERTIES_KHR: u32 = 1000053002;
+pub const STRUCTURE_TYPE_MEMORY_ALLOCATE_FLAGS_INFO_KHR: u32 = 1000060000;
+pub const STRUCTURE_TYPE_BUFFER_DEVICE_ADDRESS_INFO_KHR: u32 = 1000244001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_BUFFER_DEVICE_ADDRESS_FEATURES_KHR: u32 = 1000257000;
 
 pub type SystemAllocationScope = u32;
 pub const SYSTEM_ALLOCATION_SCOPE_COMMAND: u32 = 0;
@@ -376,6 +422,40 @@ pub const FORMAT_ASTC_12x10_UNORM_BLOCK: u32 = 181;
 pub const FORMAT_ASTC_12x10_SRGB_BLOCK: u32 = 182;
 pub const FORMAT_ASTC_12x12_UNORM_BLOCK: u32 = 183;
 pub const FORMAT_ASTC_12x12_SRGB_BLOCK: u32 = 184;
+pub const FORMAT_G8B8G8R8_422_UNORM_KHR: u32 = 1000156000;
+pub const FORMAT_B8G8R8G8_422_UNORM_KHR: u32 = 1000156001;
+pub const FORMAT_G8_B8_R8_3PLANE_420_UNORM_KHR: u32 = 1000156002;
+pub const FORMAT_G8_B8R8_2PLANE_420_UNORM_KHR: u32 = 1000156003;
+pub const FORMAT_G8_B8_R8_3PLANE_422_UNORM_KHR: u32 = 1000156004;
+pub const FORMAT_G8_B8R8_2PLANE_422_UNORM_KHR: u32 = 1000156005;
+pub const FORMAT_G8_B8_R8_3PLANE_444_UNORM_KHR: u32 = 1000156006;
+pub const FORMAT_R10X6_UNORM_PACK16_KHR: u32 = 1000156007;
+pub const FORMAT_R10X6G10X6_UNORM_2PACK16_KHR: u32 = 1000156008;
+pub const FORMAT_R10X6G10X6B10X6A10X6_UNORM_4PACK16_KHR: u32 = 1000156009;
+pub const FORMAT_G10X6B10X6G10X6R10X6_422_UNORM_4PACK16_KHR: u32 = 1000156010;
+pub const FORMAT_B10X6G10X6R10X6G10X6_422_UNORM_4PACK16_KHR: u32 = 1000156011;
+pub const FORMAT_G10X6_B10X6_R10X6_3PLANE_420_UNORM_3PACK16_KHR: u32 = 1000156012;
+pub const FORMAT_G10X6_B10X6R10X6_2PLANE_420_UNORM_3PACK16_KHR: u32 = 1000156013;
+pub const FORMAT_G10X6_B10X6_R10X6_3PLANE_422_UNORM_3PACK16_KHR: u32 = 1000156014;
+pub const FORMAT_G10X6_B10X6R10X6_2PLANE_422_UNORM_3PACK16_KHR: u32 = 1000156015;
+pub const FORMAT_G10X6_B10X6_R10X6_3PLANE_444_UNORM_3PACK16_KHR: u32 = 1000156016;
+pub const FORMAT_R12X4_UNORM_PACK16_KHR: u32 = 1000156017;
+pub const FORMAT_R12X4G12X4_UNORM_2PACK16_KHR: u32 = 1000156018;
+pub const FORMAT_R12X4G12X4B12X4A12X4_UNORM_4PACK16_KHR: u32 = 1000156019;
+pub const FORMAT_G12X4B12X4G12X4R12X4_422_UNORM_4PACK16_KHR: u32 = 1000156020;
+pub const FORMAT_B12X4G12X4R12X4G12X4_422_UNORM_4PACK16_KHR: u32 = 1000156021;
+pub const FORMAT_G12X4_B12X4_R12X4_3PLANE_420_UNORM_3PACK16_KHR: u32 = 1000156022;
+pub const FORMAT_G12X4_B12X4R12X4_2PLANE_420_UNORM_3PACK16_KHR: u32 = 1000156023;
+pub const FORMAT_G12X4_B12X4_R12X4_3PLANE_422_UNORM_3PACK16_KHR: u32 = 1000156024;
+pub const FORMAT_G12X4_B12X4R12X4_2PLANE_422_UNORM_3PACK16_KHR: u32 = 1000156025;
+pub const FORMAT_G12X4_B12X4_R12X4_3PLANE_444_UNORM_3PACK16_KHR: u32 = 1000156026;
+pub const FORMAT_G16B16G16R16_422_UNORM_KHR: u32 = 1000156027;
+pub const FORMAT_B16G16R16G16_422_UNORM_KHR: u32 = 1000156028;
+pub const FORMAT_G16_B16_R16_3PLANE_420_UNORM_KHR: u32 = 1000156029;
+pub const FORMAT_G16_B16R16_2PLANE_420_UNORM_KHR: u32 = 1000156030;
+pub const FORMAT_G16_B16_R16_3PLANE_422_UNORM_KHR: u32 = 1000156031;
+pub const FORMAT_G16_B16R16_2PLANE_422_UNORM_KHR: u32 = 1000156032;
+pub const FORMAT_G16_B16_R16_3PLANE_444_UNORM_KHR: u32 = 1000156033;
 
 pub type ImageType = u32;
 pub const IMAGE_TYPE_1D: u32 = 0;
@@ -539,6 +619,21 @@ pub type Filter = u32;
 pub const FILTER_NEAREST: u32 = 0;
 pub const FILTER_LINEAR: u32 = 1;
 
+pub type SamplerYcbcrModelConversionKHR = u32;
+pub const SAMPLER_YCBCR_MODEL_CONVERSION_RGB_IDENTITY_KHR: u32 = 0;
+pub const SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_IDENTITY_KHR: u32 = 1;
+pub const SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_709_KHR: u32 = 2;
+pub const SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_601_KHR: u32 = 3;
+pub const SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_2020_KHR: u32 = 4;
+
+pub type SamplerYcbcrRangeKHR = u32;
+pub const SAMPLER_YCBCR_RANGE_ITU_FULL_KHR: u32 = 0;
+pub const SAMPLER_YCBCR_RANGE_ITU_NARROW_KHR: u32 = 1;
+
+pub type ChromaLocationKHR = u32;
+pub const CHROMA_LOCATION_COSITED_EVEN_KHR: u32 = 0;
+pub const CHROMA_LOCATION_MIDPOINT_KHR: u32 = 1;
+
 pub type SamplerMipmapMode = u32;
 pub const SAMPLER_MIPMAP_MODE_NEAREST: u32 = 0;
 pub const SAMPLER_MIPMAP_MODE_LINEAR: u32 = 1;
@@ -591,6 +686,7 @@ pub const COMMAND_BUFFER_LEVEL_SECONDARY: u32 = 1;
 pub type IndexType = u32;
 pub const INDEX_TYPE_UINT16: u32 = 0;
 pub const INDEX_TYPE_UINT32: u32 = 1;
+pub const INDEX_TYPE_UINT8_EXT: u32 = 1000265000;
 
 pub type SubpassContents = u32;
 pub const SUBPASS_CONTENTS_INLINE: u32 = 0;
@@ -614,6 +710,13 @@ pub const FORMAT_FEATURE_BLIT_DST_BIT: u32 = 0x00000800;
 pub const FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR_BIT: u32 = 0x00001000;
 pub const FORMAT_FEATURE_TRANSFER_SRC_BIT_KHR: u32 = 0x00004000;
 pub const FORMAT_FEATURE_TRANSFER_DST_BIT_KHR: u32 = 0x00008000;
+pub const FORMAT_FEATURE_MIDPOINT_CHROMA_SAMPLES_BIT_KHR: u32 = 0x00020000;
+pub const FORMAT_FEATURE_SAMPLED_IMAGE_YCBCR_CONVERSION_LINEAR_FILTER_BIT_KHR: u32 = 0x00040000;
+pub const FORMAT_FEATURE_SAMPLED_IMAGE_YCBCR_CONVERSION_SEPARATE_RECONSTRUCTION_FILTER_BIT_KHR: u32 = 0x00080000;
+pub const FORMAT_FEATURE_SAMPLED_IMAGE_YCBCR_CONVERSION_CHROMA_RECONSTRUCTION_EXPLICIT_BIT_KHR: u32 = 0x00100000;
+pub const FORMAT_FEATURE_SAMPLED_IMAGE_YCBCR_CONVERSION_CHROMA_RECONSTRUCTION_EXPLICIT_FORCEABLE_BIT_KHR: u32 = 0x00200000;
+pub const FORMAT_FEATURE_DISJOINT_BIT_KHR: u32 = 0x00400000;
+pub const FORMAT_FEATURE_COSITED_CHROMA_SAMPLES_BIT_KHR: u32 = 0x00800000;
 pub type FormatFeatureFlags = Flags;
 
 
@@ -636,6 +739,7 @@ pub const IMAGE_CREATE_SPARSE_ALIASED_BIT: u32 = 0x00000004;
 pub const IMAGE_CREATE_MUTABLE_FORMAT_BIT: u32 = 0x00000008;
 pub const IMAGE_CREATE_CUBE_COMPATIBLE_BIT: u32 = 0x00000010;
 pub const IMAGE_CREATE_2D_ARRAY_COMPATIBLE_BIT_KHR: u32 = 0x00000020;
+pub const IMAGE_CREATE_DISJOINT_BIT_KHR: u32 = 0x00000200;
 pub type ImageCreateFlags = Flags;
 
 
@@ -692,6 +796,7 @@ pub const PIPELINE_STAGE_BOTTOM_OF_PIPE_BIT: u32 = 0x00002000;
 pub const PIPELINE_STAGE_HOST_BIT: u32 = 0x00004000;
 pub const PIPELINE_STAGE_ALL_GRAPHICS_BIT: u32 = 0x00008000;
 pub const PIPELINE_STAGE_ALL_COMMANDS_BIT: u32 = 0x00010000;
+pub const PIPELINE_STAGE_CONDITIONAL_RENDERING_BIT_EXT: u32 = 0x00040000;
 pub type PipelineStageFlags = Flags;
 pub type MemoryMapFlags = Flags;
 
@@ -701,6 +806,9 @@ pub const IMAGE_ASPECT_COLOR_BIT: u32 = 0x00000001;
 pub const IMAGE_ASPECT_DEPTH_BIT: u32 = 0x00000002;
 pub const IMAGE_ASPECT_STENCIL_BIT: u32 = 0x00000004;
 pub const IMAGE_ASPECT_METADATA_BIT: u32 = 0x00000008;
+pub const IMAGE_ASPECT_PLANE_0_BIT_KHR: u32 = 0x00000010;
+pub const IMAGE_ASPECT_PLANE_1_BIT_KHR: u32 = 0x00000020;
+pub const IMAGE_ASPECT_PLANE_2_BIT_KHR: u32 = 0x00000040;
 pub type ImageAspectFlags = Flags;
 
 
@@ -764,7 +872,42 @@ pub const BUFFER_USAGE_STORAGE_BUFFER_BIT: u32 = 0x00000020;
 pub const BUFFER_USAGE_INDEX_BUFFER_BIT: u32 = 0x00000040;
 pub const BUFFER_USAGE_VERTEX_BUFFER_BIT: u32 = 0x00000080;
 pub const BUFFER_USAGE_INDIRECT_BUFFER_BIT: u32 = 0x00000100;
+pub const BUFFER_USAGE_CONDITIONAL_RENDERING_BIT_EXT: u32 = 0x00000200;
+pub const BUFFER_USAGE_SHADER_DEVICE_ADDRESS_BIT_KHR: u32 = 0x00020000;
 pub type BufferUsageFlags = Flags;
+
+pub type ExternalMemoryHandleTypeFlagBitsKHR = u32;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR: u32 = 0x00000004;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_BIT_KHR: u32 = 0x00000008;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_KMT_BIT_KHR: u32 = 0x00000010;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_HEAP_BIT_KHR: u32 = 0x00000020;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_RESOURCE_BIT_KHR: u32 = 0x00000040;
+pub type ExternalMemoryHandleTypeFlagsKHR = Flags;
+
+pub type ExternalMemoryFeatureFlagBitsKHR = u32;
+pub const EXTERNAL_MEMORY_FEATURE_DEDICATED_ONLY_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_MEMORY_FEATURE_EXPORTABLE_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_MEMORY_FEATURE_IMPORTABLE_BIT_KHR: u32 = 0x00000004;
+pub type ExternalMemoryFeatureFlagsKHR = Flags;
+
+pub type ExternalSemaphoreHandleTypeFlagBitsKHR = u32;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR: u32 = 0x00000004;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR: u32 = 0x00000008;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR: u32 = 0x00000010;
+pub type ExternalSemaphoreHandleTypeFlagsKHR = Flags;
+
+pub type ExternalSemaphoreFeatureFlagBitsKHR = u32;
+pub const EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR: u32 = 0x00000002;
+pub type ExternalSemaphoreFeatureFlagsKHR = Flags;
+
+pub type SemaphoreImportFlagBitsKHR = u32;
+pub const SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR: u32 = 0x00000001;
+pub type SemaphoreImportFlagsKHR = Flags;
 pub type BufferViewCreateFlags = Flags;
 pub type ImageViewCreateFlags = Flags;
 pub type ShaderModuleCreateFlags = Flags;
@@ -775,6 +918,7 @@ pub type PipelineCreateFlagBits = u32;
 pub const PIPELINE_CREATE_DISABLE_OPTIMIZATION_BIT: u32 = 0x00000001;
 pub const PIPELINE_CREATE_ALLOW_DERIVATIVES_BIT: u32 = 0x00000002;
 pub const PIPELINE_CREATE_DERIVATIVE_BIT: u32 = 0x00000004;
+pub const PIPELINE_CREATE_DISPATCH_BASE_KHR: u32 = 0x00000010;
 pub type PipelineCreateFlags = Flags;
 pub type PipelineShaderStageCreateFlags = Flags;
 
@@ -851,14 +995,24 @@ pub const ACCESS_HOST_READ_BIT: u32 = 0x00002000;
 pub const ACCESS_HOST_WRITE_BIT: u32 = 0x00004000;
 pub const ACCESS_MEMORY_READ_BIT: u32 = 0x00008000;
 pub const ACCESS_MEMORY_WRITE_BIT: u32 = 0x00010000;
+pub const ACCESS_CONDITIONAL_RENDERING_READ_BIT_EXT: u32 = 0x00100000;
 pub type AccessFlags = Flags;
 
 
 pub type DependencyFlagBits = u32;
 pub const DEPENDENCY_BY_REGION_BIT: u32 = 0x00000001;
+pub const DEPENDENCY_VIEW_LOCAL_BIT_KHR: u32 = 0x00000002;
 pub type DependencyFlags = Flags;
 
 
+pub type MemoryAllocateFlagBitsKHR = u32;
+pub const MEMORY_ALLOCATE_DEVICE_MASK_BIT_KHR: u32 = 0x00000001;
+pub const MEMORY_ALLOCATE_DEVICE_ADDRESS_BIT_KHR: u32 = 0x00000002;
+pub type MemoryAllocateFlagsKHR = Flags;
+
+pub type DeviceAddress = u64;
+
+
 pub type CommandPoolCreateFlagBits = u32;
 pub const COMMAND_POOL_CREATE_TRANSIENT_BIT: u32 = 0x00000001;
 pub const COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER_BIT: u32 = 0x00000002;
@@ -896,6 +1050,10 @@ pub const STENCIL_FACE_BACK_BIT: u32 = 0x00000002;
 pub const STENCIL_FRONT_AND_BACK: u32 = 0x3;
 pub type StencilFaceFlags = Flags;
 
+pub type ConditionalRenderingFlagBitsEXT = u32;
+pub const CONDITIONAL_RENDERING_INVERTED_BIT_EXT: u32 = 0x00000001;
+pub type ConditionalRenderingFlagsEXT = Flags;
+
 
 pub type DisplayPlaneAlphaFlagBitsKHR = u32;
 pub const DISPLAY_PLANE_ALPHA_OPAQUE_BIT_KHR: u32 = 0x00000001;
@@ -910,18 +1068,22 @@ pub type ColorSpaceKHR = u32;
 #[deprecated = "Renamed to COLOR_SPACE_SRGB_NONLINEAR_KHR"]
 pub const COLORSPACE_SRGB_NONLINEAR_KHR: u32 = 0;
 pub const COLOR_SPACE_SRGB_NONLINEAR_KHR: u32 = 0;
-pub const COLOR_SPACE_DISPLAY_P3_LINEAR_EXT: u32 = 1000104001;
-pub const COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT: u32 = 1000104002;
-pub const COLOR_SPACE_SCRGB_LINEAR_EXT: u32 = 1000104003;
-pub const COLOR_SPACE_SCRGB_NONLINEAR_EXT: u32 = 1000104004;
-pub const COLOR_SPACE_DCI_P3_LINEAR_EXT: u32 = 1000104005;
-pub const COLOR_SPACE_DCI_P3_NONLINEAR_EXT: u32 = 1000104006;
-pub const COLOR_SPACE_BT709_LINEAR_EXT: u32 = 1000104007;
-pub const COLOR_SPACE_BT709_NONLINEAR_EXT: u32 = 1000104008;
-pub const COLOR_SPACE_BT2020_LINEAR_EXT: u32 = 1000104009;
-pub const COLOR_SPACE_BT2020_NONLINEAR_EXT: u32 = 1000104010;
+pub const COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT: u32 = 1000104001;
+pub const COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT: u32 = 1000104002;
+pub const COLOR_SPACE_DCI_P3_LINEAR_EXT: u32 = 1000104003;
+pub const COLOR_SPACE_DCI_P3_NONLINEAR_EXT: u32 = 1000104004;
+pub const COLOR_SPACE_BT709_LINEAR_EXT: u32 = 1000104005;
+pub const COLOR_SPACE_BT709_NONLINEAR_EXT: u32 = 1000104006;
+pub const COLOR_SPACE_BT2020_LINEAR_EXT: u32 = 1000104007;
+pub const COLOR_SPACE_HDR10_ST2084_EXT: u32 = 1000104008;
+pub const COLOR_SPACE_DOLBYVISION_EXT: u32 = 1000104009;
+pub const COLOR_SPACE_HDR10_HLG_EXT: u32 = 1000104010;
 pub const COLOR_SPACE_ADOBERGB_LINEAR_EXT: u32 = 1000104011;
 pub const COLOR_SPACE_ADOBERGB_NONLINEAR_EXT: u32 = 1000104012;
+pub const COLOR_SPACE_PASS_THROUGH_EXT: u32 = 1000104013;
+pub const COLOR_SPACE_EXTENDED_SRGB_NONLINEAR_EXT: u32 = 1000104014;
+#[deprecated = "Renamed to COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT"]
+pub const COLOR_SPACE_SCRGB_LINEAR_EXT: u32 = COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT;
 
 pub type PresentModeKHR = u32;
 pub const PRESENT_MODE_IMMEDIATE_KHR: u32 = 0;
@@ -995,6 +1157,8 @@ pub type MacOSSurfaceCreateFlagsMVK = u32;
 
 pub type IOSSurfaceCreateFlagsMVK = u32;
 
+pub type MetalSurfaceCreateFlagsEXT = u32;
+
 pub type DescriptorSetLayoutCreateFlagBits = u32;
 pub const DESCRIPTOR_SET_LAYOUT_CREATE_PUSH_DESCRIPTOR_BIT_KHR: u32 = 0x00000001;
 
@@ -2433,6 +2597,14 @@ pub struct MacOSSurfaceCreateInfoMVK {
 	pub pView: *const c_void,
 }
 
+#[repr(C)]
+pub struct MetalSurfaceCreateInfoEXT {
+	pub sType: StructureType,
+	pub pNext: *const c_void,
+	pub flags: MetalSurfaceCreateFlagsEXT,
+	pub pLayer: *const c_void,
+}
+
 #[repr(C)]
 pub struct MVKDeviceConfiguration {
     pub supportDisplayContentsScale: Bool32,
@@ -2574,6 +2746,270 @@ pub struct DescriptorUpdateTemplateCreateInfoKHR {
     pub set: u32,
 }
 
+#[repr(C)]
+pub struct SamplerYcbcrConversionCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub format: Format,
+    pub ycbcrModel: SamplerYcbcrModelConversionKHR,
+    pub ycbcrRange: SamplerYcbcrRangeKHR,
+    pub components: ComponentMapping,
+    pub xChromaOffset: ChromaLocationKHR,
+    pub yChromaOffset: ChromaLocationKHR,
+    pub chromaFilter: Filter,
+    pub forceExplicitReconstruction: Bool32,
+}
+
+#[repr(C)]
+pub struct SamplerYcbcrConversionInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub conversion: SamplerYcbcrConversionKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceSamplerYcbcrConversionFeaturesKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub samplerYcbcrConversion: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceIndexTypeUint8FeaturesEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub indexTypeUint8: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceConditionalRenderingFeaturesEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub conditionalRendering: Bool32,
+    pub inheritedConditionalRendering: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceMultiviewFeaturesKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub multiview: Bool32,
+    pub multiviewGeometryShader: Bool32,
+    pub multiviewTessellationShader: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceMultiviewPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub maxMultiviewViewCount: u32,
+    pub maxMultiviewInstanceIndex: u32,
+}
+
+#[repr(C)]
+pub struct RenderPassMultiviewCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub subpassCount: u32,
+    pub pViewMasks: *const u32,
+    pub dependencyCount: u32,
+    pub pViewOffsets: *const i32,
+    pub correlationMaskCount: u32,
+    pub pCorrelationMasks: *const u32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceBufferDeviceAddressFeaturesKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub bufferDeviceAddress: Bool32,
+    pub bufferDeviceAddressCaptureReplay: Bool32,
+    pub bufferDeviceAddressMultiDevice: Bool32,
+}
+
+#[repr(C)]
+pub struct BufferDeviceAddressInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub buffer: Buffer,
+}
+
+#[repr(C)]
+pub struct MemoryAllocateFlagsInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: MemoryAllocateFlagsKHR,
+    pub deviceMask: u32,
+}
+
+#[repr(C)]
+pub struct ConditionalRenderingBeginInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub buffer: Buffer,
+    pub offset: DeviceSize,
+    pub flags: ConditionalRenderingFlagsEXT,
+}
+
+#[repr(C)]
+pub struct DebugUtilsLabelEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub pLabelName: *const c_char,
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+pub struct ExternalMemoryPropertiesKHR {
+    pub externalMemoryFeatures: ExternalMemoryFeatureFlagsKHR,
+    pub exportFromImportedHandleTypes: ExternalMemoryHandleTypeFlagsKHR,
+    pub compatibleHandleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalBufferInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: BufferCreateFlags,
+    pub usage: BufferUsageFlags,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalBufferPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub externalMemoryProperties: ExternalMemoryPropertiesKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalImageFormatInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalImageFormatPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub externalMemoryProperties: ExternalMemoryPropertiesKHR,
+}
+
+#[repr(C)]
+pub struct ExternalMemoryBufferCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalMemoryImageCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExportMemoryAllocateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ImportMemoryFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+    pub fd: c_int,
+}
+
+#[repr(C)]
+pub struct MemoryGetFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub memory: DeviceMemory,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ImportMemoryWin32HandleInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+    pub handle: *mut c_void,
+    pub name: *const u16,
+}
+
+#[repr(C)]
+pub struct MemoryGetWin32HandleInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub memory: DeviceMemory,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalSemaphoreInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalSemaphorePropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub exportFromImportedHandleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+    pub compatibleHandleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+    pub externalSemaphoreFeatures: ExternalSemaphoreFeatureFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExportSemaphoreCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ImportSemaphoreFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub flags: SemaphoreImportFlagsKHR,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+    pub fd: c_int,
+}
+
+#[repr(C)]
+pub struct SemaphoreGetFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ImportSemaphoreWin32HandleInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub flags: SemaphoreImportFlagsKHR,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+    pub handle: *mut c_void,
+    pub name: *const u16,
+}
+
+#[repr(C)]
+pub struct SemaphoreGetWin32HandleInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
 
 macro_rules! ptrs {
     ($struct_name:ident, { $($name:ident => ($($param_n:ident: $param_ty:ty),*) -> $ret:ty,)+ }) => (
@@ -2672,6 +3108,7 @@ ptrs!(InstancePointers, {
     DebugReportMessageEXT => (instance: Instance, flags: DebugReportFlagsEXT, objectType: DebugReportObjectTypeEXT, object: u64, location: usize, messageCode: i32, pLayerPrefix: *const c_char, pMessage: *const c_char) -> (),
     CreateIOSSurfaceMVK => (instance: Instance, pCreateInfo: *const IOSSurfaceCreateInfoMVK, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
     CreateMacOSSurfaceMVK => (instance: Instance, pCreateInfo: *const MacOSSurfaceCreateInfoMVK, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
+    CreateMetalSurfaceEXT => (instance: Instance, pCreateInfo: *const MetalSurfaceCreateInfoEXT, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
     ActivateMoltenVKLicenseMVK => (licenseID: *const c_char, licenseKey: *const c_char, acceptLicenseTermsAndConditions: Bool32) -> Result,
     ActivateMoltenVKLicensesMVK => () -> Result,
     GetMoltenVKDeviceConfigurationMVK => (device: Device, pConfiguration: *mut MVKDeviceConfiguration) -> Result,
@@ -2686,6 +3123,8 @@ ptrs!(InstancePointers, {
     GetPhysicalDeviceQueueFamilyProperties2KHR => (physicalDevice: PhysicalDevice, pQueueFamilyPropertiesCount: *mut u32, pQueueFamilyProperties: *mut QueueFamilyProperties2KHR) -> (),
     GetPhysicalDeviceMemoryProperties2KHR => (physicalDevice: PhysicalDevice, pMemoryProperties: *mut PhysicalDeviceMemoryProperties2KHR) -> (),
     GetPhysicalDeviceSparseImageFormatProperties2KHR => (physicalDevice: PhysicalDevice, pFormatInfo: *const PhysicalDeviceSparseImageFormatInfo2KHR, pPropertyCount: *mut u32, pProperties: *mut SparseImageFormatProperties2KHR) -> (),
+    GetPhysicalDeviceExternalBufferPropertiesKHR => (physicalDevice: PhysicalDevice, pExternalBufferInfo: *const PhysicalDeviceExternalBufferInfoKHR, pExternalBufferProperties: *mut ExternalBufferPropertiesKHR) -> (),
+    GetPhysicalDeviceExternalSemaphorePropertiesKHR => (physicalDevice: PhysicalDevice, pExternalSemaphoreInfo: *const PhysicalDeviceExternalSemaphoreInfoKHR, pExternalSemaphoreProperties: *mut ExternalSemaphorePropertiesKHR) -> (),
 });
 
 ptrs!(DevicePointers, {
@@ -2785,6 +3224,7 @@ ptrs!(DevicePointers, {
     CmdDrawIndexedIndirect => (commandBuffer: CommandBuffer, buffer: Buffer, offset: DeviceSize, drawCount: u32, stride: u32) -> (),
     CmdDispatch => (commandBuffer: CommandBuffer, x: u32, y: u32, z: u32) -> (),
     CmdDispatchIndirect => (commandBuffer: CommandBuffer, buffer: Buffer, offset: DeviceSize) -> (),
+    CmdDispatchBaseKHR => (commandBuffer: CommandBuffer, baseGroupX: u32, baseGroupY: u32, baseGroupZ: u32, groupCountX: u32, groupCountY: u32, groupCountZ: u32) -> (),
     CmdCopyBuffer => (commandBuffer: CommandBuffer, srcBuffer: Buffer, dstBuffer: Buffer, regionCount: u32, pRegions: *const BufferCopy) -> (),
     CmdCopyImage => (commandBuffer: CommandBuffer, srcImage: Image, srcImageLayout: ImageLayout, dstImage: Image, dstImageLayout: ImageLayout, regionCount: u32, pRegions: *const ImageCopy) -> (),
     CmdBlitImage => (commandBuffer: CommandBuffer, srcImage: Image, srcImageLayout: ImageLayout, dstImage: Image, dstImageLayout: ImageLayout, regionCount: u32, pRegions: *const ImageBlit, filter: Filter) -> (),
@@ -2810,6 +3250,11 @@ ptrs!(DevicePointers, {
     CmdNextSubpass => (commandBuffer: CommandBuffer, contents: SubpassContents) -> (),
     CmdEndRenderPass => (commandBuffer: CommandBuffer) -> (),
     CmdExecuteCommands => (commandBuffer: CommandBuffer, commandBufferCount: u32, pCommandBuffers: *const CommandBuffer) -> (),
+    CmdBeginConditionalRenderingEXT => (commandBuffer: CommandBuffer, pConditionalRenderingBegin: *const ConditionalRenderingBeginInfoEXT) -> (),
+    CmdEndConditionalRenderingEXT => (commandBuffer: CommandBuffer) -> (),
+    CmdBeginDebugUtilsLabelEXT => (commandBuffer: CommandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
+    CmdEndDebugUtilsLabelEXT => (commandBuffer: CommandBuffer) -> (),
+    CmdInsertDebugUtilsLabelEXT => (commandBuffer: CommandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
     CreateSwapchainKHR => (device: Device, pCreateInfo: *const SwapchainCreateInfoKHR, pAllocator: *const AllocationCallbacks, pSwapchain: *mut SwapchainKHR) -> Result,
     DestroySwapchainKHR => (device: Device, swapchain: SwapchainKHR, pAllocator: *const AllocationCallbacks) -> (),
     GetSwapchainImagesKHR => (device: Device, swapchain: SwapchainKHR, pSwapchainImageCount: *mut u32, pSwapchainImages: *mut Image) -> Result,
@@ -2821,4 +3266,13 @@ ptrs!(DevicePointers, {
     DestroyDescriptorUpdateTemplateKHR => (device: Device, descriptorUpdateTemplate: DescriptorUpdateTemplateKHR, pAllocator: *const AllocationCallbacks) -> (),
     UpdateDescriptorSetWithTemplateKHR => (device: Device, descriptorSet: DescriptorSet, descriptorUpdateTemplate: DescriptorUpdateTemplateKHR, pData: *const c_void) -> (),
     CmdPushDescriptorSetWithTemplateKHR => (commandBuffer: CommandBuffer, descriptorUpdateTemplate: DescriptorUpdateTemplateKHR, layout: PipelineLayout, set: u32, pData: *const c_void) -> (),
+    CreateSamplerYcbcrConversionKHR => (device: Device, pCreateInfo: *const SamplerYcbcrConversionCreateInfoKHR, pAllocator: *const AllocationCallbacks, pYcbcrConversion: *mut SamplerYcbcrConversionKHR) -> Result,
+    DestroySamplerYcbcrConversionKHR => (device: Device, ycbcrConversion: SamplerYcbcrConversionKHR, pAllocator: *const AllocationCallbacks) -> (),
+    GetMemoryFdKHR => (device: Device, pGetFdInfo: *const MemoryGetFdInfoKHR, pFd: *mut c_int) -> Result,
+    GetBufferDeviceAddressKHR => (device: Device, pInfo: *const BufferDeviceAddressInfoKHR) -> DeviceAddress,
+    GetMemoryWin32HandleKHR => (device: Device, pGetWin32HandleInfo: *const MemoryGetWin32HandleInfoKHR, pHandle: *mut *mut c_void) -> Result,
+    ImportSemaphoreFdKHR => (device: Device, pImportSemaphoreFdInfo: *const ImportSemaphoreFdInfoKHR) -> Result,
+    GetSemaphoreFdKHR => (device: Device, pGetFdInfo: *const SemaphoreGetFdInfoKHR, pFd: *mut c_int) -> Result,
+    ImportSemaphoreWin32HandleKHR => (device: Device, pImportSemaphoreWin32HandleInfo: *const ImportSemaphoreWin32HandleInfoKHR) -> Result,
+    GetSemaphoreWin32HandleKHR => (device: Device, pGetWin32HandleInfo: *const SemaphoreGetWin32HandleInfoKHR, pHandle: *mut *mut c_void) -> Result,
 });
@@ -64,6 +64,7 @@ pub fn write_descriptor_sets(doc: &parse::Spirv) -> String {
     }
 
     // Looping to find all the push constant structs.
+    let mut push_constants_offset = None;
     let mut push_constants_size = 0;
     for instruction in doc.instructions.iter() {
         let type_id = match instruction {
@@ -73,10 +74,26 @@ pub fn write_descriptor_sets(doc: &parse::Spirv) -> String {
             _ => continue
         };
 
+        // The size returned by `type_from_id` includes the padding before the first member, so
+        // it is the offset of the end of the block.
         let (_, size, _) = ::structs::type_from_id(doc, type_id);
         let size = size.expect("Found runtime-sized push constants");
         push_constants_size = cmp::max(push_constants_size, size);
+
+        // The start of the block is the lowest `Offset` decoration of its members.
+        let offset = doc.instructions.iter().filter_map(|i| {
+            match *i {
+                parse::Instruction::MemberDecorate { target_id,
+                                                   decoration: enums::Decoration::DecorationOffset,
+                                                   ref params, .. } if target_id == type_id => {
+                    Some(params[0] as usize)
+                },
+                _ => None
+            }
+        }).min().unwrap_or(0);
+        push_constants_offset = Some(cmp::min(push_constants_offset.unwrap_or(offset), offset));
     }
+    let push_constants_offset = push_constants_offset.unwrap_or(0);
 
     // Writing the body of the `descriptor` method.
     let descriptor_body = descriptors.iter().map(|d| {
@@ -120,11 +137,11 @@ pub fn write_descriptor_sets(doc: &parse::Spirv) -> String {
     let push_constants_range_body = format!(r#"
         if num != 0 || {pc_size} == 0 {{ return None; }}
         Some(PipelineLayoutDescPcRange {{
-            offset: 0,                      // FIXME: not necessarily true
+            offset: {pc_offset},
             size: {pc_size},
-            stages: ShaderStages::all(),     // FIXME: wrong
+            stages: self.0.clone(),
         }})
-    "#, pc_size = push_constants_size);
+    "#, pc_offset = push_constants_offset, pc_size = push_constants_size - push_constants_offset);
 
     format!(r#"
         #[derive(Debug, Clone)]
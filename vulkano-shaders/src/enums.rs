@@ -381,6 +381,7 @@ enumeration! {
         BuiltInSubgroupLocalInvocationId = 41,
         BuiltInVertexIndex = 42,
         BuiltInInstanceIndex = 43,
+        BuiltInViewIndex = 4440,
     } BuiltIn;
 
     typedef enum SelectionControlShift_ {
@@ -546,5 +547,6 @@ enumeration! {
         CapabilityStorageImageReadWithoutFormat = 55,
         CapabilityStorageImageWriteWithoutFormat = 56,
         CapabilityMultiViewport = 57,
+        CapabilityMultiView = 4439,
     } Capability;
 }
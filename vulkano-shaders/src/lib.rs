@@ -441,5 +441,6 @@ fn capability_name(cap: &enums::Capability) -> Option<&'static str> {
         enums::Capability::CapabilityStorageImageReadWithoutFormat => Some("shader_storage_image_read_without_format"),
         enums::Capability::CapabilityStorageImageWriteWithoutFormat => Some("shader_storage_image_write_without_format"),
         enums::Capability::CapabilityMultiViewport => Some("multi_viewport"),
+        enums::Capability::CapabilityMultiView => Some("multiview"),
     }
 }
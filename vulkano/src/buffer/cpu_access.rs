@@ -12,14 +12,33 @@
 //! The `CpuAccessibleBuffer` is a basic general-purpose buffer. It can be used in any situation
 //! but may not perform as well as other buffer types.
 //! 
-//! Each access from the CPU or from the GPU locks the whole buffer for either reading or writing.
-//! You can read the buffer multiple times simultaneously. Trying to read and write simultaneously,
-//! or write and write simultaneously will block.
+//! Each access from the CPU locks the whole buffer for either reading or writing. You can read the
+//! buffer multiple times simultaneously. Trying to read and write simultaneously, or write and
+//! write simultaneously will fail.
+//!
+//! The `read_back` function can be used to copy the content of any buffer to a
+//! `CpuAccessibleBuffer` and read it from the CPU in one step. A `ReadBackPool` does the same but
+//! keeps its staging buffer between calls, so that reading back regularly doesn't allocate memory
+//! each time.
+//!
+//! # Upload and download buffers
+//!
+//! The `*_with_hint` constructors accept a `CpuAccessHint` that describes how the CPU is going
+//! to access the buffer, and choose the memory type accordingly. Buffers that the CPU fills for
+//! the GPU are faster with host-coherent and uncached (write-combined) memory, while buffers that
+//! the CPU reads back are much faster with host-cached memory. If no memory type has the
+//! preferred properties, any host-visible memory type is used instead.
+//!
+//! The locks returned by `read()` and `write()` take care of invalidating and flushing the
+//! mapped memory if the memory type that was chosen isn't host-coherent.
 
+use std::error;
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::ops::Range;
 use std::ptr;
 use std::sync::Arc;
 use std::sync::RwLock;
@@ -37,12 +56,24 @@ use buffer::traits::BufferInner;
 use buffer::traits::Buffer;
 use buffer::traits::TypedBuffer;
 use buffer::traits::TypedBufferAccess;
+use command_buffer::AutoCommandBufferBuilder;
+use command_buffer::CommandBuffer;
+use command_buffer::CommandBufferBuilder;
+use command_buffer::CommandBufferBuilderError;
+use command_buffer::CommandBufferExecError;
+use command_buffer::cb::AddCommand;
+use command_buffer::commands_raw::CmdCopyBuffer;
+use command_buffer::commands_raw::CmdCopyBufferError;
 use device::Device;
 use device::DeviceOwned;
 use device::Queue;
+use instance::MemoryType;
 use instance::QueueFamily;
 use memory::Content;
 use memory::CpuAccess as MemCpuAccess;
+use memory::MemoryAllocError;
+use memory::MemoryPreference;
+use memory::MemoryProperties;
 use memory::pool::AllocLayout;
 use memory::pool::MemoryPool;
 use memory::pool::MemoryPoolAlloc;
@@ -50,6 +81,8 @@ use memory::pool::StdMemoryPool;
 use sync::AccessError;
 use sync::Sharing;
 use sync::AccessFlagBits;
+use sync::FlushError;
+use sync::GpuFuture;
 use sync::PipelineStages;
 
 use OomError;
@@ -88,14 +121,38 @@ impl<T> CpuAccessibleBuffer<T> {
     }
 
     /// Builds a new buffer with some data in it. Only allowed for sized data.
+    #[inline]
     pub fn from_data<'a, I>(device: Arc<Device>, usage: BufferUsage, queue_families: I, data: T)
                             -> Result<Arc<CpuAccessibleBuffer<T>>, OomError>
         where I: IntoIterator<Item = QueueFamily<'a>>,
               T: Content + 'static,
+    {
+        CpuAccessibleBuffer::from_data_impl(device, usage, queue_families, data,
+                                            MemoryPreference::host_visible())
+    }
+
+    /// Same as `from_data`, but chooses the memory type according to `hint`.
+    #[inline]
+    pub fn from_data_with_hint<'a, I>(device: Arc<Device>, usage: BufferUsage,
+                                      queue_families: I, data: T, hint: CpuAccessHint)
+                                      -> Result<Arc<CpuAccessibleBuffer<T>>, OomError>
+        where I: IntoIterator<Item = QueueFamily<'a>>,
+              T: Content + 'static,
+    {
+        CpuAccessibleBuffer::from_data_impl(device, usage, queue_families, data,
+                                            hint.memory_preference())
+    }
+
+    fn from_data_impl<'a, I>(device: Arc<Device>, usage: BufferUsage, queue_families: I, data: T,
+                             preference: MemoryPreference)
+                             -> Result<Arc<CpuAccessibleBuffer<T>>, OomError>
+        where I: IntoIterator<Item = QueueFamily<'a>>,
+              T: Content + 'static,
     {
         unsafe {
             let uninitialized = try!(
-                CpuAccessibleBuffer::raw(device, mem::size_of::<T>(), usage, queue_families)
+                CpuAccessibleBuffer::raw_host_visible(device, mem::size_of::<T>(), usage,
+                                                      queue_families, preference)
             );
 
             // Note that we are in panic-unsafety land here. However a panic should never ever
@@ -124,15 +181,50 @@ impl<T> CpuAccessibleBuffer<T> {
 impl<T> CpuAccessibleBuffer<[T]> {
     /// Builds a new buffer that contains an array `T`. The initial data comes from an iterator
     /// that produces that list of Ts.
+    ///
+    /// The length of the buffer is queried from the iterator, and the elements are written
+    /// directly in the mapped memory as they are produced. No intermediate copy of the data is
+    /// made on the host. Use `from_iter_buffered` if the length is not known in advance.
+    ///
+    /// If the iterator produces more elements than returned by its `len()` method, the extra
+    /// elements are ignored. If it produces less, the end of the buffer is left uninitialized.
+    #[inline]
     pub fn from_iter<'a, I, Q>(device: Arc<Device>, usage: BufferUsage, queue_families: Q, data: I)
                                -> Result<Arc<CpuAccessibleBuffer<[T]>>, OomError>
         where I: ExactSizeIterator<Item = T>,
               T: Content + 'static,
               Q: IntoIterator<Item = QueueFamily<'a>>
     {
+        CpuAccessibleBuffer::from_iter_impl(device, usage, queue_families, data,
+                                            MemoryPreference::host_visible())
+    }
+
+    /// Same as `from_iter`, but chooses the memory type according to `hint`.
+    #[inline]
+    pub fn from_iter_with_hint<'a, I, Q>(device: Arc<Device>, usage: BufferUsage,
+                                         queue_families: Q, data: I, hint: CpuAccessHint)
+                                         -> Result<Arc<CpuAccessibleBuffer<[T]>>, OomError>
+        where I: ExactSizeIterator<Item = T>,
+              T: Content + 'static,
+              Q: IntoIterator<Item = QueueFamily<'a>>
+    {
+        CpuAccessibleBuffer::from_iter_impl(device, usage, queue_families, data,
+                                            hint.memory_preference())
+    }
+
+    fn from_iter_impl<'a, I, Q>(device: Arc<Device>, usage: BufferUsage, queue_families: Q,
+                                data: I, preference: MemoryPreference)
+                                -> Result<Arc<CpuAccessibleBuffer<[T]>>, OomError>
+        where I: ExactSizeIterator<Item = T>,
+              T: Content + 'static,
+              Q: IntoIterator<Item = QueueFamily<'a>>
+    {
+        let len = data.len();
+
         unsafe {
-            let uninitialized = try!(
-                CpuAccessibleBuffer::uninitialized_array(device, data.len(), usage, queue_families)
+            let uninitialized: Arc<CpuAccessibleBuffer<[T]>> = try!(
+                CpuAccessibleBuffer::raw_host_visible(device, len * mem::size_of::<T>(), usage,
+                                                      queue_families, preference)
             );
 
             // Note that we are in panic-unsafety land here. However a panic should never ever
@@ -151,6 +243,22 @@ impl<T> CpuAccessibleBuffer<[T]> {
         }
     }
 
+    /// Same as `from_iter`, but accepts iterators whose length is not known in advance.
+    ///
+    /// The elements are first collected in a `Vec` and then copied to the buffer, which means
+    /// that the host temporarily holds the data twice. Prefer `from_iter` when you can.
+    #[inline]
+    pub fn from_iter_buffered<'a, I, Q>(device: Arc<Device>, usage: BufferUsage,
+                                        queue_families: Q, data: I)
+                                        -> Result<Arc<CpuAccessibleBuffer<[T]>>, OomError>
+        where I: IntoIterator<Item = T>,
+              T: Content + 'static,
+              Q: IntoIterator<Item = QueueFamily<'a>>
+    {
+        let data: Vec<T> = data.into_iter().collect();
+        CpuAccessibleBuffer::from_iter(device, usage, queue_families, data.into_iter())
+    }
+
     /// Deprecated. Use `uninitialized_array` or `from_iter` instead.
     // TODO: remove
     #[inline]
@@ -173,6 +281,50 @@ impl<T> CpuAccessibleBuffer<[T]> {
     {
         CpuAccessibleBuffer::raw(device, len * mem::size_of::<T>(), usage, queue_families)
     }
+
+    /// Same as `uninitialized_array`, but chooses the memory type according to `hint`.
+    ///
+    /// This is typically used with `CpuAccessHint::Download` to create the destination of a
+    /// `read_back`.
+    #[inline]
+    pub unsafe fn uninitialized_array_with_hint<'a, I>(device: Arc<Device>, len: usize,
+                                                       usage: BufferUsage, queue_families: I,
+                                                       hint: CpuAccessHint)
+                                                       -> Result<Arc<CpuAccessibleBuffer<[T]>>,
+                                                                 OomError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        CpuAccessibleBuffer::raw_with_hint(device, len * mem::size_of::<T>(), usage,
+                                           queue_families, hint)
+    }
+}
+
+impl CpuAccessibleBuffer<[u8]> {
+    /// Builds a new buffer that contains a copy of `data`, for when the layout of the elements is
+    /// only known at runtime.
+    ///
+    /// `stride` is the size in bytes of one element. Returns an error if `stride` is 0 or if the
+    /// length of `data` is not a multiple of `stride`.
+    pub fn from_data_slice<'a, I>(device: Arc<Device>, usage: BufferUsage, queue_families: I,
+                                  data: &[u8], stride: usize)
+                                  -> Result<Arc<CpuAccessibleBuffer<[u8]>>, FromDataSliceError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        check_data_slice(data.len(), stride)?;
+
+        unsafe {
+            let uninitialized = try!(
+                CpuAccessibleBuffer::uninitialized_array(device, data.len(), usage, queue_families)
+            );
+
+            {
+                let mut mapping = uninitialized.write().unwrap();
+                mapping.copy_from_slice(data);
+            }
+
+            Ok(uninitialized)
+        }
+    }
 }
 
 impl<T: ?Sized> CpuAccessibleBuffer<T> {
@@ -182,10 +334,70 @@ impl<T: ?Sized> CpuAccessibleBuffer<T> {
     ///
     /// You must ensure that the size that you pass is correct for `T`.
     ///
+    #[inline]
     pub unsafe fn raw<'a, I>(device: Arc<Device>, size: usize, usage: BufferUsage, queue_families: I)
                              -> Result<Arc<CpuAccessibleBuffer<T>>, OomError>
         where I: IntoIterator<Item = QueueFamily<'a>>
     {
+        CpuAccessibleBuffer::raw_host_visible(device, size, usage, queue_families,
+                                              MemoryPreference::host_visible())
+    }
+
+    /// Same as `raw`, but chooses the memory type according to `hint`.
+    ///
+    /// # Safety
+    ///
+    /// You must ensure that the size that you pass is correct for `T`.
+    ///
+    #[inline]
+    pub unsafe fn raw_with_hint<'a, I>(device: Arc<Device>, size: usize, usage: BufferUsage,
+                                       queue_families: I, hint: CpuAccessHint)
+                                       -> Result<Arc<CpuAccessibleBuffer<T>>, OomError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        CpuAccessibleBuffer::raw_host_visible(device, size, usage, queue_families,
+                                              hint.memory_preference())
+    }
+
+    // Same as `raw_with_memory_preference`, for preferences that only require host-visible
+    // memory.
+    unsafe fn raw_host_visible<'a, I>(device: Arc<Device>, size: usize, usage: BufferUsage,
+                                      queue_families: I, preference: MemoryPreference)
+                                      -> Result<Arc<CpuAccessibleBuffer<T>>, OomError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        debug_assert!(MemoryProperties { host_visible: true, .. MemoryProperties::none() }
+                          .contains(&preference.required));
+
+        match CpuAccessibleBuffer::raw_with_memory_preference(device, size, usage, queue_families,
+                                                              preference)
+        {
+            Ok(b) => Ok(b),
+            Err(MemoryAllocError::OomError(err)) => Err(err),
+            // Vk specs guarantee that a host-visible memory type is always available for buffers.
+            Err(MemoryAllocError::NoSuitableMemoryType) => unreachable!(),
+        }
+    }
+
+    /// Same as `raw`, but allocates the buffer from the memory type that best matches
+    /// `preference`.
+    ///
+    /// The memory type is always required to be host-visible, even if `preference` doesn't say
+    /// so. For example you can prefer host-cached memory for a buffer that you read back often.
+    ///
+    /// # Safety
+    ///
+    /// You must ensure that the size that you pass is correct for `T`.
+    ///
+    pub unsafe fn raw_with_memory_preference<'a, I>(device: Arc<Device>, size: usize,
+                                                    usage: BufferUsage, queue_families: I,
+                                                    mut preference: MemoryPreference)
+                                                    -> Result<Arc<CpuAccessibleBuffer<T>>,
+                                                              MemoryAllocError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        preference.required.host_visible = true;
+
         let queue_families = queue_families.into_iter().map(|f| f.id())
                                            .collect::<SmallVec<[u32; 4]>>();
 
@@ -198,16 +410,16 @@ impl<T: ?Sized> CpuAccessibleBuffer<T> {
 
             match UnsafeBuffer::new(device.clone(), size, usage, sharing, SparseLevel::none()) {
                 Ok(b) => b,
-                Err(BufferCreationError::OomError(err)) => return Err(err),
+                Err(BufferCreationError::OomError(err)) => return Err(err.into()),
                 Err(_) => unreachable!()        // We don't use sparse binding, therefore the other
                                                 // errors can't happen
             }
         };
 
-        let mem_ty = device.physical_device().memory_types()
-                           .filter(|t| (mem_reqs.memory_type_bits & (1 << t.id())) != 0)
-                           .filter(|t| t.is_host_visible())
-                           .next().unwrap();    // Vk specs guarantee that this can't fail
+        let mem_ty = match preference.choose(device.physical_device(), mem_reqs.memory_type_bits) {
+            Some(t) => t,
+            None => return Err(MemoryAllocError::NoSuitableMemoryType),
+        };
 
         let mem = try!(MemoryPool::alloc(&Device::standard_pool(&device), mem_ty,
                                          mem_reqs.size, mem_reqs.alignment, AllocLayout::Linear));
@@ -240,17 +452,39 @@ impl<T: ?Sized, A> CpuAccessibleBuffer<T, A> where A: MemoryPool {
             self.device().physical_device().queue_family_by_id(num).unwrap()
         }).collect()
     }
+
+    /// Returns the memory type that the buffer was allocated from.
+    #[inline]
+    pub fn memory_type(&self) -> MemoryType {
+        self.memory.memory().memory_type()
+    }
+}
+
+impl<T, A> CpuAccessibleBuffer<T, A> where T: Content + Copy + 'static, A: MemoryPool {
+    /// Reads the content of the buffer with a volatile read, without locking it.
+    ///
+    /// Used to look at a value that the GPU may be writing at the same time, for example the
+    /// counter of a `ProgressCounter`. The memory must be host-coherent.
+    #[inline]
+    pub(crate) unsafe fn read_volatile(&self) -> T {
+        let offset = self.memory.offset();
+        let range = offset .. offset + self.inner.size();
+        let access = self.memory.mapped_memory().unwrap().read::<T>(range);
+        ptr::read_volatile(access.as_ptr())
+    }
 }
 
 impl<T: ?Sized, A> CpuAccessibleBuffer<T, A> where T: Content + 'static, A: MemoryPool {
-    /// Locks the buffer in order to write its content.
+    /// Locks the buffer in order to read its content.
     ///
-    /// If the buffer is currently in use by the GPU, this function will block until either the
-    /// buffer is available or the timeout is reached. A value of `0` for the timeout is valid and
-    /// means that the function should never block.
+    /// The lock is shared: any number of `ReadLock`s can be alive at the same time, possibly from
+    /// multiple threads. Locking the buffer with `write()` fails as long as at least one of them
+    /// is alive, and calling `read()` fails while a `WriteLock` is alive.
     ///
-    /// After this function successfully locks the buffer, any attempt to submit a command buffer
-    /// that uses it will block until you unlock it.
+    /// This only synchronizes the CPU accesses with each other. You must still make sure that the
+    /// GPU isn't writing the buffer at the same time, which is normally done by waiting on the
+    /// future of the command buffer that writes it. GPU writes are always tracked as exclusive
+    /// accesses by the command buffer that contains them.
     #[inline]
     pub fn read(&self) -> Result<ReadLock<T>, TryLockError<RwLockReadGuard<()>>> {
         let lock = try!(self.access.try_read());
@@ -259,19 +493,18 @@ impl<T: ?Sized, A> CpuAccessibleBuffer<T, A> where T: Content + 'static, A: Memo
         let range = offset .. offset + self.inner.size();
 
         Ok(ReadLock {
-            inner: unsafe { self.memory.mapped_memory().unwrap().read_write(range) },
+            inner: unsafe { self.memory.mapped_memory().unwrap().read(range) },
             lock: lock,
         })
     }
 
     /// Locks the buffer in order to write its content.
     ///
-    /// If the buffer is currently in use by the GPU, this function will block until either the
-    /// buffer is available or the timeout is reached. A value of `0` for the timeout is valid and
-    /// means that the function should never block.
+    /// The lock is exclusive: this function fails if a `ReadLock` or another `WriteLock` is
+    /// alive.
     ///
-    /// After this function successfully locks the buffer, any attempt to submit a command buffer
-    /// that uses it will block until you unlock it.
+    /// As with `read()`, you must still make sure that the GPU isn't accessing the buffer at the
+    /// same time.
     #[inline]
     pub fn write(&self) -> Result<WriteLock<T>, TryLockError<RwLockWriteGuard<()>>> {
         let lock = try!(self.access.try_write());
@@ -286,6 +519,346 @@ impl<T: ?Sized, A> CpuAccessibleBuffer<T, A> where T: Content + 'static, A: Memo
     }
 }
 
+impl<T, A> CpuAccessibleBuffer<[T], A> where T: 'static, A: MemoryPool {
+    // Locks a range of elements of the buffer in order to write them.
+    //
+    // Contrary to `write()`, only this range is mapped and flushed, and the buffer is locked in
+    // shared mode so that multiple ranges can be written at the same time. Locking the whole
+    // buffer with `write()` fails while the returned lock is alive. This is used by the
+    // sub-allocators of the `buffer` module, which never give out the same range twice.
+    //
+    // # Safety
+    //
+    // Nothing else must access this range, either the CPU through another lock or the GPU, as
+    // long as the returned lock is alive.
+    //
+    // # Panic
+    //
+    // - Panics if the range is out of bounds of the buffer.
+    //
+    pub(crate) unsafe fn write_range(&self, range: Range<usize>)
+                                     -> Result<RangeWriteLock<[T]>,
+                                               TryLockError<RwLockReadGuard<()>>>
+    {
+        let element_size = mem::size_of::<T>();
+        assert!(range.start <= range.end && range.end * element_size <= self.inner.size());
+
+        let lock = try!(self.access.try_read());
+
+        let offset = self.memory.offset();
+        let range = offset + range.start * element_size .. offset + range.end * element_size;
+
+        Ok(RangeWriteLock {
+            inner: self.memory.mapped_memory().unwrap().read_write(range),
+            lock: lock,
+        })
+    }
+}
+
+/// Describes how the CPU is going to access a `CpuAccessibleBuffer`.
+///
+/// See the documentation of the `cpu_access` module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CpuAccessHint {
+    /// The CPU writes the buffer and the GPU reads it, for example a staging buffer or a buffer
+    /// of vertices. Prefers host-coherent memory, which is usually uncached and write-combined.
+    Upload,
+    /// The GPU writes the buffer and the CPU reads it, for example the destination of a
+    /// `read_back`. Prefers host-cached memory.
+    Download,
+}
+
+impl CpuAccessHint {
+    /// Returns the memory preference that corresponds to this hint.
+    pub fn memory_preference(&self) -> MemoryPreference {
+        let preferred = match *self {
+            // The specs require the memory types to be ordered so that a type whose properties
+            // are a subset of another's comes first. On equal score a coherent and uncached type
+            // is therefore chosen over a coherent and cached one.
+            CpuAccessHint::Upload => {
+                MemoryProperties { host_coherent: true, .. MemoryProperties::none() }
+            },
+            CpuAccessHint::Download => {
+                MemoryProperties { host_cached: true, .. MemoryProperties::none() }
+            },
+        };
+
+        MemoryPreference {
+            required: MemoryProperties { host_visible: true, .. MemoryProperties::none() },
+            preferred: preferred,
+        }
+    }
+}
+
+/// Copies the content of `source` to `destination`, waits until the copy is finished, then locks
+/// `destination` for reading.
+///
+/// This is a shortcut for building a command buffer that contains the copy, executing it on
+/// `queue`, signalling a fence and waiting for it. The current thread is blocked until the GPU
+/// has finished the copy. This is mostly useful for debugging or for reading back the result of
+/// a computation.
+///
+/// No memory is allocated for the destination by this function. If you read back data regularly,
+/// keep `destination` alive and pass it again to reuse it as a staging buffer. Creating it with
+/// `CpuAccessHint::Download` makes reading it faster on hardware that has host-cached memory.
+///
+/// If the two buffers don't have the same size, only the smallest of the two sizes is copied.
+///
+/// # Panic
+///
+/// - Panics if the source or the destination was not created with the same device as `queue`.
+///
+pub fn read_back<'a, S, T>(source: S, destination: &'a Arc<CpuAccessibleBuffer<T>>,
+                           queue: Arc<Queue>) -> Result<ReadLock<'a, T>, ReadBackError>
+    where S: Buffer,
+          T: ?Sized + Content + Send + Sync + 'static,
+          AutoCommandBufferBuilder: AddCommand<CmdCopyBuffer<S::Access, Arc<CpuAccessibleBuffer<T>>>,
+                                               Out = AutoCommandBufferBuilder>
+{
+    let command_buffer = AutoCommandBufferBuilder::new(queue.device().clone(), queue.family())?
+        .copy_buffer(source, destination.clone())?
+        .build()?;
+
+    let future = command_buffer.execute(queue)?.then_signal_fence_and_flush()?;
+    future.wait(None)?;
+
+    match destination.read() {
+        Ok(lock) => Ok(lock),
+        Err(_) => Err(ReadBackError::DestinationLocked),
+    }
+}
+
+/// Staging buffer that is reused between read-backs.
+///
+/// Each call to `read_back` copies the source to the staging buffer of the pool, which is only
+/// replaced by a larger one when the source doesn't fit in it. The staging buffer is created
+/// with `CpuAccessHint::Download` and can only be used by the queue family of the latest
+/// read-back, so using queues of a different family also replaces it.
+#[derive(Debug, Default)]
+pub struct ReadBackPool {
+    // The staging buffer and the id of the queue family it was created for.
+    buffer: Option<(Arc<CpuAccessibleBuffer<[u8]>>, u32)>,
+}
+
+impl ReadBackPool {
+    /// Builds a new pool. No memory is allocated until the first read-back.
+    #[inline]
+    pub fn new() -> ReadBackPool {
+        ReadBackPool {
+            buffer: None,
+        }
+    }
+
+    /// Copies the content of `source` to the staging buffer, waits until the copy is finished,
+    /// then locks the staging buffer for reading.
+    ///
+    /// Same as the `read_back` function, except that the destination is the staging buffer of
+    /// the pool. The returned lock only covers the first `source.size()` bytes of the staging
+    /// buffer.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the source was not created with the same device as `queue`.
+    ///
+    pub fn read_back<'a, S>(&'a mut self, source: S, queue: Arc<Queue>)
+                            -> Result<ReadLock<'a, [u8]>, ReadBackError>
+        where S: Buffer,
+              AutoCommandBufferBuilder: AddCommand<CmdCopyBuffer<S::Access,
+                                                                 Arc<CpuAccessibleBuffer<[u8]>>>,
+                                                   Out = AutoCommandBufferBuilder>
+    {
+        let size = source.size();
+
+        let reuse = match self.buffer {
+            Some((ref buffer, family)) => {
+                BufferAccess::size(&**buffer) >= size && family == queue.family().id() &&
+                &**buffer.device() as *const Device == &**queue.device() as *const Device
+            },
+            None => false,
+        };
+
+        if !reuse {
+            let buffer = unsafe {
+                let usage = BufferUsage::transfer_dest();
+                try!(CpuAccessibleBuffer::uninitialized_array_with_hint(queue.device().clone(),
+                                                                        size, usage,
+                                                                        Some(queue.family()),
+                                                                        CpuAccessHint::Download))
+            };
+            self.buffer = Some((buffer, queue.family().id()));
+        }
+
+        let destination = &self.buffer.as_ref().unwrap().0;
+        let lock = try!(read_back(source, destination, queue));
+        Ok(lock.map(|data| &data[.. size]))
+    }
+}
+
+// Checks that a slice of `len` bytes is made of elements of `stride` bytes.
+pub(crate) fn check_data_slice(len: usize, stride: usize) -> Result<(), FromDataSliceError> {
+    if stride == 0 {
+        return Err(FromDataSliceError::ZeroStride);
+    }
+
+    if len == 0 {
+        return Err(FromDataSliceError::EmptyData);
+    }
+
+    if len % stride != 0 {
+        return Err(FromDataSliceError::LengthNotMultipleOfStride {
+            len: len,
+            stride: stride,
+        });
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when building a buffer with `from_data_slice`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FromDataSliceError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The stride is 0.
+    ZeroStride,
+
+    /// The slice of data is empty.
+    EmptyData,
+
+    /// The length of the slice of data is not a multiple of the stride.
+    LengthNotMultipleOfStride {
+        /// Length of the slice in bytes.
+        len: usize,
+        /// Stride that was passed.
+        stride: usize,
+    },
+}
+
+impl error::Error for FromDataSliceError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            FromDataSliceError::OomError(_) => "not enough memory",
+            FromDataSliceError::ZeroStride => "the stride is 0",
+            FromDataSliceError::EmptyData => "the slice of data is empty",
+            FromDataSliceError::LengthNotMultipleOfStride { .. } => {
+                "the length of the slice of data is not a multiple of the stride"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            FromDataSliceError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FromDataSliceError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for FromDataSliceError {
+    #[inline]
+    fn from(err: OomError) -> FromDataSliceError {
+        FromDataSliceError::OomError(err)
+    }
+}
+
+/// Error that can happen when calling `read_back`.
+#[derive(Debug, Clone)]
+pub enum ReadBackError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// Error while adding the copy command to the command buffer.
+    CopyError(CommandBufferBuilderError<CmdCopyBufferError>),
+
+    /// Error while executing the command buffer.
+    ExecError(CommandBufferExecError),
+
+    /// Error while submitting the command buffer or waiting for it.
+    FlushError(FlushError),
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The destination buffer is locked by the CPU and can't be read.
+    DestinationLocked,
+}
+
+impl error::Error for ReadBackError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ReadBackError::OomError(_) => "not enough memory",
+            ReadBackError::CopyError(_) => "error while adding the copy command",
+            ReadBackError::ExecError(_) => "error while executing the command buffer",
+            ReadBackError::FlushError(_) => "error while submitting the command buffer",
+            ReadBackError::DeviceLost => "the connection to the device has been lost",
+            ReadBackError::DestinationLocked => {
+                "the destination buffer is locked by the CPU and can't be read"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ReadBackError::OomError(ref err) => Some(err),
+            ReadBackError::CopyError(ref err) => Some(err),
+            ReadBackError::ExecError(ref err) => Some(err),
+            ReadBackError::FlushError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ReadBackError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ReadBackError {
+    #[inline]
+    fn from(err: OomError) -> ReadBackError {
+        ReadBackError::OomError(err)
+    }
+}
+
+impl From<CommandBufferBuilderError<CmdCopyBufferError>> for ReadBackError {
+    #[inline]
+    fn from(err: CommandBufferBuilderError<CmdCopyBufferError>) -> ReadBackError {
+        ReadBackError::CopyError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for ReadBackError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> ReadBackError {
+        ReadBackError::ExecError(err)
+    }
+}
+
+impl From<FlushError> for ReadBackError {
+    #[inline]
+    fn from(err: FlushError) -> ReadBackError {
+        match err {
+            FlushError::OomError(err) => ReadBackError::OomError(err),
+            FlushError::DeviceLost => ReadBackError::DeviceLost,
+            err => ReadBackError::FlushError(err),
+        }
+    }
+}
+
 // FIXME: wrong
 unsafe impl<T: ?Sized, A> Buffer for Arc<CpuAccessibleBuffer<T, A>>
     where T: 'static + Send + Sync, A: MemoryPool
@@ -366,10 +939,10 @@ pub struct CpuAccessibleBufferFinished {
     write: bool,
 }
 
-/// Object that can be used to read or write the content of a `CpuAccessBuffer`.
+/// Object that can be used to read the content of a `CpuAccessBuffer`.
 ///
-/// Note that this object holds a rwlock read guard on the chunk. If another thread tries to access
-/// this buffer's content or tries to submit a GPU command that uses this buffer, it will block.
+/// Note that this object holds a rwlock read guard on the chunk. Other `ReadLock`s can be created
+/// at the same time, but trying to write the buffer's content will fail until it is destroyed.
 pub struct ReadLock<'a, T: ?Sized + 'a> {
     inner: MemCpuAccess<'a, T>,
     lock: RwLockReadGuard<'a, ()>,
@@ -379,10 +952,10 @@ impl<'a, T: ?Sized + 'a> ReadLock<'a, T> {
     /// Makes a new `ReadLock` to access a sub-part of the current `ReadLock`.
     #[inline]
     pub fn map<U: ?Sized + 'a, F>(self, f: F) -> ReadLock<'a, U>
-        where F: FnOnce(&mut T) -> &mut U
+        where F: FnOnce(&T) -> &U
     {
         ReadLock {
-            inner: self.inner.map(|ptr| unsafe { f(&mut *ptr) as *mut _ }),
+            inner: self.inner.map(|ptr| unsafe { f(&*ptr) as *const U as *mut U }),
             lock: self.lock,
         }
     }
@@ -434,3 +1007,229 @@ impl<'a, T: ?Sized + 'a> DerefMut for WriteLock<'a, T> {
         self.inner.deref_mut()
     }
 }
+
+// Object that can be used to write a range of a `CpuAccessibleBuffer`. Returned by
+// `write_range()`.
+//
+// Holds a rwlock read guard on the buffer, as the range is only written by the owner of the lock.
+pub(crate) struct RangeWriteLock<'a, T: ?Sized + 'a> {
+    inner: MemCpuAccess<'a, T>,
+    lock: RwLockReadGuard<'a, ()>,
+}
+
+impl<'a, T: ?Sized + 'a> Deref for RangeWriteLock<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.inner.deref()
+    }
+}
+
+impl<'a, T: ?Sized + 'a> DerefMut for RangeWriteLock<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        self.inner.deref_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferAccess;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use buffer::cpu_access::CpuAccessHint;
+    use buffer::cpu_access::FromDataSliceError;
+    use buffer::cpu_access::ReadBackPool;
+    use buffer::cpu_access::check_data_slice;
+    use buffer::cpu_access::read_back;
+    use std::alloc::GlobalAlloc;
+    use std::alloc::Layout;
+    use std::alloc::System;
+    use std::cell::Cell;
+    use std::sync::Arc;
+
+    // Allocator that counts the number of bytes allocated by each thread. Used to check that
+    // `from_iter` doesn't make an intermediate copy of the data.
+    struct CountingAllocator;
+
+    thread_local!(static ALLOCATED: Cell<usize> = Cell::new(0));
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            // `try_with` fails while the thread is being destroyed.
+            let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + layout.size()));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocated_bytes() -> usize {
+        ALLOCATED.with(|allocated| allocated.get())
+    }
+
+    #[test]
+    fn multiple_readers() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 12u32).unwrap();
+
+        {
+            let a = buffer.read().unwrap();
+            let b = buffer.read().unwrap();
+            assert_eq!(*a, 12);
+            assert_eq!(*b, 12);
+            assert!(buffer.write().is_err());
+        }
+
+        {
+            let _w = buffer.write().unwrap();
+            assert!(buffer.read().is_err());
+        }
+
+        assert!(buffer.write().is_ok());
+    }
+
+    #[test]
+    fn write_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0 .. 8u32).unwrap();
+
+        unsafe {
+            let mut a = buffer.write_range(2 .. 4).unwrap();
+            let mut b = buffer.write_range(6 .. 8).unwrap();
+            assert_eq!(&*a, &[2, 3]);
+            a.copy_from_slice(&[20, 30]);
+            b[1] = 70;
+            assert!(buffer.write().is_err());
+        }
+
+        assert_eq!(&*buffer.read().unwrap(), &[0, 1, 20, 30, 4, 5, 6, 70]);
+    }
+
+    #[test]
+    fn data_slice_stride() {
+        assert_eq!(check_data_slice(24, 12), Ok(()));
+        assert_eq!(check_data_slice(24, 0), Err(FromDataSliceError::ZeroStride));
+        assert_eq!(check_data_slice(0, 12), Err(FromDataSliceError::EmptyData));
+        assert_eq!(check_data_slice(20, 12),
+                   Err(FromDataSliceError::LengthNotMultipleOfStride { len: 20, stride: 12 }));
+    }
+
+    #[test]
+    fn from_data_slice() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let buffer = CpuAccessibleBuffer::from_data_slice(device, BufferUsage::all(),
+                                                          Some(queue.family()), &data, 4).unwrap();
+        assert_eq!(buffer.size(), 8);
+        assert_eq!(&*buffer.read().unwrap(), &data[..]);
+    }
+
+    #[test]
+    fn from_iter_large() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        const LEN: u32 = 1 << 20;
+        let before = allocated_bytes();
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. LEN).map(|n| n.wrapping_mul(7))).unwrap();
+
+        // The data is 4 MiB. Only small bookkeeping allocations are allowed on the host.
+        assert!(allocated_bytes() - before < (LEN as usize * 4) / 4);
+
+        let content = buffer.read().unwrap();
+        assert_eq!(content.len(), LEN as usize);
+        assert!(content.iter().enumerate().all(|(n, &v)| v == (n as u32).wrapping_mul(7)));
+    }
+
+    #[test]
+    fn from_iter_buffered() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let data = (0 .. 100u32).filter(|n| n % 3 == 0);
+        let buffer = CpuAccessibleBuffer::from_iter_buffered(device, BufferUsage::all(),
+                                                             Some(queue.family()), data).unwrap();
+
+        let expected: Vec<u32> = (0 .. 100).filter(|n| n % 3 == 0).collect();
+        assert_eq!(&*buffer.read().unwrap(), &expected[..]);
+    }
+
+    #[test]
+    fn hint_memory_types() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let upload = CpuAccessibleBuffer::from_iter_with_hint(device.clone(), BufferUsage::all(),
+                                                              Some(queue.family()), 0 .. 64u32,
+                                                              CpuAccessHint::Upload).unwrap();
+        assert!(upload.memory_type().is_host_visible());
+        assert!(upload.memory_type().is_host_coherent());
+
+        let download = unsafe {
+            CpuAccessibleBuffer::<[u32]>::uninitialized_array_with_hint(device.clone(), 64,
+                                                                        BufferUsage::all(),
+                                                                        Some(queue.family()),
+                                                                        CpuAccessHint::Download)
+                .unwrap()
+        };
+        assert!(download.memory_type().is_host_visible());
+        let has_cached = device.physical_device().memory_types()
+                               .any(|t| t.is_host_visible() && t.is_host_cached());
+        assert_eq!(download.memory_type().is_host_cached(), has_cached);
+
+        // The locks work regardless of whether the memory is coherent.
+        download.write().unwrap().copy_from_slice(&*upload.read().unwrap());
+        assert_eq!(&*download.read().unwrap(), &*upload.read().unwrap());
+    }
+
+    #[test]
+    fn read_back_copies() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let source = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), 0 .. 32u32).unwrap();
+        let destination = unsafe {
+            CpuAccessibleBuffer::<[u32]>::uninitialized_array(device.clone(), 32,
+                                                              BufferUsage::all(),
+                                                              Some(queue.family())).unwrap()
+        };
+
+        let data = read_back(source, &destination, queue).unwrap();
+        assert_eq!(&*data, &(0 .. 32).collect::<Vec<u32>>()[..]);
+    }
+
+    #[test]
+    fn read_back_pool_reuses_buffer() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let large = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                   Some(queue.family()), 0 .. 64u8).unwrap();
+        let small = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                   Some(queue.family()), 100 .. 116u8).unwrap();
+
+        let mut pool = ReadBackPool::new();
+        assert_eq!(&*pool.read_back(large.clone(), queue.clone()).unwrap(),
+                   &(0 .. 64).collect::<Vec<u8>>()[..]);
+        let first = pool.buffer.as_ref().unwrap().0.clone();
+
+        assert_eq!(&*pool.read_back(small, queue.clone()).unwrap(),
+                   &(100 .. 116).collect::<Vec<u8>>()[..]);
+        assert!(Arc::ptr_eq(&first, &pool.buffer.as_ref().unwrap().0));
+
+        let larger = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), 0 .. 128u8).unwrap();
+        assert_eq!(pool.read_back(larger, queue).unwrap().len(), 128);
+        assert!(!Arc::ptr_eq(&first, &pool.buffer.as_ref().unwrap().0));
+    }
+}
@@ -218,11 +218,28 @@ impl<T, A> CpuBufferPool<T, A> where A: MemoryPool {
     ///
     /// > **Note**: You can think of it like a `Vec`. If you insert an element and the `Vec` is not
     /// > large enough, a new chunk of memory is automatically allocated.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if allocating the new buffer fails. Use `try_allocate` to handle this case.
+    ///
     pub fn next(&self, data: T) -> CpuBufferPoolSubbuffer<T, A> {
+        match self.try_allocate(data) {
+            Ok(n) => n,
+            Err(err) => panic!("failed to allocate a new buffer in the pool: {}", err),
+        }
+    }
+
+    /// Same as `next`, but returns an error instead of panicking if there is not enough memory to
+    /// allocate a new buffer.
+    ///
+    /// The pool is left unchanged in case of an error, so the caller can free some memory and
+    /// try again.
+    pub fn try_allocate(&self, data: T) -> Result<CpuBufferPoolSubbuffer<T, A>, OomError> {
         let mut mutex = self.current_buffer.lock().unwrap();
 
         let data = match self.try_next_impl(&mut mutex, data) {
-            Ok(n) => return n,
+            Ok(n) => return Ok(n),
             Err(d) => d,
         };
 
@@ -231,10 +248,10 @@ impl<T, A> CpuBufferPool<T, A> where A: MemoryPool {
             None => 3,
         };
 
-        self.reset_buf(&mut mutex, next_capacity).unwrap();        /* FIXME: error */
+        try!(self.reset_buf(&mut mutex, next_capacity));
 
         match self.try_next_impl(&mut mutex, data) {
-            Ok(n) => n,
+            Ok(n) => Ok(n),
             Err(_) => unreachable!()
         }
     }
@@ -531,6 +548,17 @@ mod tests {
         assert!(pool.capacity() > first_cap);
     }
 
+    #[test]
+    fn try_allocate() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pool = CpuBufferPool::upload(device);
+        assert_eq!(pool.capacity(), 0);
+
+        let _subbuffer = pool.try_allocate(12u32).unwrap();
+        assert!(pool.capacity() >= 1);
+    }
+
     #[test]
     fn reuse_subbuffers() {
         let (device, _) = gfx_dev_and_queue!();
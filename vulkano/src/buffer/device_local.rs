@@ -31,7 +31,10 @@ use buffer::traits::TypedBufferAccess;
 use device::Device;
 use device::DeviceOwned;
 use device::Queue;
+use instance::MemoryType;
 use instance::QueueFamily;
+use memory::MemoryAllocError;
+use memory::MemoryPreference;
 use memory::pool::AllocLayout;
 use memory::pool::MemoryPool;
 use memory::pool::MemoryPoolAlloc;
@@ -97,6 +100,30 @@ impl<T: ?Sized> DeviceLocalBuffer<T> {
     pub unsafe fn raw<'a, I>(device: Arc<Device>, size: usize, usage: BufferUsage, queue_families: I)
                              -> Result<Arc<DeviceLocalBuffer<T>>, OomError>
         where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        match DeviceLocalBuffer::raw_with_memory_preference(device, size, usage, queue_families,
+                                                            MemoryPreference::device_local())
+        {
+            Ok(b) => Ok(b),
+            Err(MemoryAllocError::OomError(err)) => Err(err),
+            // Nothing is required, therefore all the memory types are suitable.
+            Err(MemoryAllocError::NoSuitableMemoryType) => unreachable!(),
+        }
+    }
+
+    /// Same as `raw`, but allocates the buffer from the memory type that best matches
+    /// `preference`.
+    ///
+    /// # Safety
+    ///
+    /// You must ensure that the size that you pass is correct for `T`.
+    ///
+    pub unsafe fn raw_with_memory_preference<'a, I>(device: Arc<Device>, size: usize,
+                                                    usage: BufferUsage, queue_families: I,
+                                                    preference: MemoryPreference)
+                                                    -> Result<Arc<DeviceLocalBuffer<T>>,
+                                                              MemoryAllocError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
     {
         let queue_families = queue_families.into_iter().map(|f| f.id())
                                            .collect::<SmallVec<[u32; 4]>>();
@@ -110,19 +137,15 @@ impl<T: ?Sized> DeviceLocalBuffer<T> {
 
             match UnsafeBuffer::new(device.clone(), size, usage, sharing, SparseLevel::none()) {
                 Ok(b) => b,
-                Err(BufferCreationError::OomError(err)) => return Err(err),
+                Err(BufferCreationError::OomError(err)) => return Err(err.into()),
                 Err(_) => unreachable!()        // We don't use sparse binding, therefore the other
                                                 // errors can't happen
             }
         };
 
-        let mem_ty = {
-            let device_local = device.physical_device().memory_types()
-                                     .filter(|t| (mem_reqs.memory_type_bits & (1 << t.id())) != 0)
-                                     .filter(|t| t.is_device_local());
-            let any = device.physical_device().memory_types()
-                            .filter(|t| (mem_reqs.memory_type_bits & (1 << t.id())) != 0);
-            device_local.chain(any).next().unwrap()
+        let mem_ty = match preference.choose(device.physical_device(), mem_reqs.memory_type_bits) {
+            Some(t) => t,
+            None => return Err(MemoryAllocError::NoSuitableMemoryType),
         };
 
         let mem = try!(MemoryPool::alloc(&Device::standard_pool(&device), mem_ty,
@@ -155,6 +178,12 @@ impl<T: ?Sized, A> DeviceLocalBuffer<T, A> where A: MemoryPool {
             self.device().physical_device().queue_family_by_id(num).unwrap()
         }).collect()
     }
+
+    /// Returns the memory type that the buffer was allocated from.
+    #[inline]
+    pub fn memory_type(&self) -> MemoryType {
+        self.memory.memory().memory_type()
+    }
 }
 
 /// Access to a device local buffer.
@@ -0,0 +1,285 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::Arc;
+use std::u32;
+
+use buffer::traits::BufferAccess;
+use buffer::traits::BufferInner;
+use buffer::traits::TypedBufferAccess;
+use device::Device;
+use device::DeviceOwned;
+use device::Queue;
+use sync::AccessError;
+
+/// Region of a buffer that is accessed through a dynamic uniform or storage buffer descriptor.
+///
+/// A dynamic descriptor always covers the same number of bytes, but the position of this range
+/// within the buffer is chosen when the descriptor set is bound, by passing a dynamic offset.
+/// A `DynamicSubbuffer` holds the whole buffer, the offset of the region and its size.
+///
+/// When written into a descriptor set, a `DynamicSubbuffer` describes the range of `size` bytes
+/// at the start of the buffer. The value returned by `dynamic_offset()` must then be passed when
+/// binding the set, for example with `DynamicOffsets`.
+///
+/// > **Note**: Since the range written in the descriptor doesn't include the offset, a
+/// > `DynamicSubbuffer` must only be used with dynamic descriptors. Use a `BufferSlice` for
+/// > everything else.
+pub struct DynamicSubbuffer<T: ?Sized, B> {
+    marker: PhantomData<Box<T>>,
+    // The whole buffer.
+    buffer: B,
+    // Offset of the region within the buffer.
+    offset: usize,
+    // Size of the region in bytes.
+    size: usize,
+}
+
+impl<T, B> DynamicSubbuffer<T, B> where B: BufferAccess {
+    /// Builds a `DynamicSubbuffer` for an object of type `T` that starts at `offset` bytes within
+    /// `buffer`.
+    ///
+    /// The offset must be a multiple of the `min_uniform_buffer_offset_alignment` and
+    /// `min_storage_buffer_offset_alignment` limits.
+    #[inline]
+    pub fn new(buffer: B, offset: usize) -> Result<DynamicSubbuffer<T, B>, DynamicSubbufferError> {
+        DynamicSubbuffer::with_size(buffer, offset, mem::size_of::<T>())
+    }
+}
+
+impl<T: ?Sized, B> DynamicSubbuffer<T, B> where B: BufferAccess {
+    /// Same as `new`, but for a region of `size` bytes.
+    pub fn with_size(buffer: B, offset: usize, size: usize)
+                     -> Result<DynamicSubbuffer<T, B>, DynamicSubbufferError>
+    {
+        if size == 0 {
+            return Err(DynamicSubbufferError::EmptyRange);
+        }
+
+        if offset.checked_add(size).map(|end| end > buffer.size()).unwrap_or(true) {
+            return Err(DynamicSubbufferError::OutOfRange);
+        }
+
+        if offset > u32::MAX as usize {
+            return Err(DynamicSubbufferError::OffsetTooLarge);
+        }
+
+        let alignment = required_alignment(buffer.device());
+        if offset % alignment != 0 {
+            return Err(DynamicSubbufferError::UnalignedOffset { alignment: alignment });
+        }
+
+        Ok(DynamicSubbuffer {
+            marker: PhantomData,
+            buffer: buffer,
+            offset: offset,
+            size: size,
+        })
+    }
+}
+
+impl<T: ?Sized, B> DynamicSubbuffer<T, B> {
+    /// Returns the whole buffer.
+    #[inline]
+    pub fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Returns the offset of the region within the buffer.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the dynamic offset to pass when binding the descriptor set that contains this
+    /// subbuffer.
+    #[inline]
+    pub fn dynamic_offset(&self) -> u32 {
+        self.offset as u32
+    }
+}
+
+impl<T: ?Sized, B> Clone for DynamicSubbuffer<T, B> where B: Clone {
+    #[inline]
+    fn clone(&self) -> DynamicSubbuffer<T, B> {
+        DynamicSubbuffer {
+            marker: PhantomData,
+            buffer: self.buffer.clone(),
+            offset: self.offset,
+            size: self.size,
+        }
+    }
+}
+
+impl<T: ?Sized, B> fmt::Debug for DynamicSubbuffer<T, B> where B: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("DynamicSubbuffer")
+           .field("buffer", &self.buffer)
+           .field("offset", &self.offset)
+           .field("size", &self.size)
+           .finish()
+    }
+}
+
+unsafe impl<T: ?Sized, B> BufferAccess for DynamicSubbuffer<T, B> where B: BufferAccess {
+    #[inline]
+    fn inner(&self) -> BufferInner {
+        // The offset is not included, as it is passed at bind time.
+        self.buffer.inner()
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn conflicts_buffer(&self, self_offset: usize, self_size: usize,
+                        other: &BufferAccess, other_offset: usize, other_size: usize) -> bool
+    {
+        self.buffer.conflicts_buffer(self.offset + self_offset, self_size, other, other_offset,
+                                     other_size)
+    }
+
+    #[inline]
+    fn conflict_key(&self, self_offset: usize, self_size: usize) -> u64 {
+        self.buffer.conflict_key(self.offset + self_offset, self_size)
+    }
+
+    #[inline]
+    fn try_gpu_lock(&self, exclusive_access: bool, queue: &Queue) -> Result<(), AccessError> {
+        self.buffer.try_gpu_lock(exclusive_access, queue)
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        self.buffer.increase_gpu_lock()
+    }
+}
+
+unsafe impl<T: ?Sized, B> TypedBufferAccess for DynamicSubbuffer<T, B> where B: BufferAccess {
+    type Content = T;
+}
+
+unsafe impl<T: ?Sized, B> DeviceOwned for DynamicSubbuffer<T, B> where B: DeviceOwned {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.buffer.device()
+    }
+}
+
+// Returns the alignment that the dynamic offsets must respect on `device`.
+#[inline]
+fn required_alignment(device: &Device) -> usize {
+    let limits = device.physical_device().limits();
+    cmp::max(limits.min_uniform_buffer_offset_alignment(),
+             limits.min_storage_buffer_offset_alignment()) as usize
+}
+
+/// Error that can happen when creating a `DynamicSubbuffer`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DynamicSubbufferError {
+    /// The size of the region is 0.
+    EmptyRange,
+    /// The region doesn't fit in the buffer.
+    OutOfRange,
+    /// The offset doesn't fit in the 32 bits of a dynamic offset.
+    OffsetTooLarge,
+    /// The offset is not a multiple of the alignment required for dynamic offsets.
+    UnalignedOffset {
+        /// The required alignment.
+        alignment: usize,
+    },
+}
+
+impl error::Error for DynamicSubbufferError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DynamicSubbufferError::EmptyRange => "the size of the region is 0",
+            DynamicSubbufferError::OutOfRange => "the region doesn't fit in the buffer",
+            DynamicSubbufferError::OffsetTooLarge => {
+                "the offset doesn't fit in the 32 bits of a dynamic offset"
+            },
+            DynamicSubbufferError::UnalignedOffset { .. } => {
+                "the offset is not a multiple of the alignment required for dynamic offsets"
+            },
+        }
+    }
+}
+
+impl fmt::Display for DynamicSubbufferError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferAccess;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use buffer::DynamicSubbuffer;
+    use buffer::DynamicSubbufferError;
+
+    #[test]
+    fn aligned_offset() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 4096).map(|_| 0u8)).unwrap();
+
+        let alignment = super::required_alignment(&device);
+        let sub = DynamicSubbuffer::<[f32; 4], _>::new(buffer.clone(), alignment).unwrap();
+        assert_eq!(sub.dynamic_offset() as usize, alignment);
+        assert_eq!(sub.size(), 16);
+        assert_eq!(sub.inner().offset, buffer.inner().offset);
+    }
+
+    #[test]
+    fn unaligned_offset() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 4096).map(|_| 0u8)).unwrap();
+
+        let alignment = super::required_alignment(&device);
+        if alignment == 1 {
+            return;
+        }
+
+        match DynamicSubbuffer::<u32, _>::new(buffer, 1) {
+            Err(DynamicSubbufferError::UnalignedOffset { alignment: a }) => {
+                assert_eq!(a, alignment)
+            },
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        match DynamicSubbuffer::<[u32; 2], _>::new(buffer, 0) {
+            Err(DynamicSubbufferError::OutOfRange) => (),
+            _ => panic!()
+        }
+    }
+}
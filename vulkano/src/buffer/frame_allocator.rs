@@ -0,0 +1,344 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Bump allocator for data that only lives for one frame.
+//!
+//! Uniforms and other small data that change at each draw are usually best stored in one large
+//! buffer. A `FrameAllocator` writes each piece of data right after the previous one, and
+//! returns the offset where it was written. The offset is aligned to the
+//! `min_uniform_buffer_offset_alignment` and `min_storage_buffer_offset_alignment` limits, which
+//! means that it can be used as the dynamic offset of a uniform or storage buffer descriptor. This
+//! way all the draws can share the same descriptor set.
+//!
+//! At the start of each frame, call `reset()` to reuse the buffer from the beginning. Since the
+//! allocator doesn't know when the GPU has finished reading the data of the previous frame, you
+//! should create one allocator for each frame in flight, for example with the help of a
+//! `FramesInFlight`.
+//!
+//! ```
+//! use vulkano::buffer::FrameAllocator;
+//!
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! let mut allocator = FrameAllocator::new(device.clone(), 64 * 1024).unwrap();
+//!
+//! loop {
+//!     allocator.reset();
+//!
+//!     for draw in 0 .. 100 {
+//!         let (slice, dynamic_offset) = allocator.allocate([draw as f32; 4]).unwrap();
+//!         // Draw with `DynamicOffsets::new(set.clone(), Some(dynamic_offset))` as the sets.
+//!     }
+//!     # break;
+//! }
+//! ```
+
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::iter;
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use buffer::BufferSlice;
+use buffer::BufferUsage;
+use buffer::CpuAccessibleBuffer;
+use buffer::DynamicSubbuffer;
+use device::Device;
+use device::DeviceOwned;
+use memory::MemoryAllocError;
+use memory::MemoryPreference;
+use memory::MemoryProperties;
+
+use OomError;
+
+/// Slice of a `FrameAllocator`.
+pub type FrameAllocatorSlice<T> = BufferSlice<T, Arc<CpuAccessibleBuffer<[u8]>>>;
+
+/// Dynamic subbuffer of a `FrameAllocator`.
+pub type FrameAllocatorDynamic<T> = DynamicSubbuffer<T, Arc<CpuAccessibleBuffer<[u8]>>>;
+
+/// Bump allocator for uniform and storage data that only lives for one frame.
+///
+/// See the documentation of the `frame_allocator` module.
+#[derive(Debug)]
+pub struct FrameAllocator {
+    // Buffer that holds the data.
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+    // Offset where the next allocation starts, before alignment.
+    offset: usize,
+    // Alignment of each allocation.
+    alignment: usize,
+}
+
+impl FrameAllocator {
+    /// Builds a new `FrameAllocator` of `capacity` bytes.
+    ///
+    /// The buffer can be used as a uniform buffer and as a storage buffer. It is allocated from
+    /// device-local memory if there is a memory type that is both host-visible and device-local.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `capacity` is 0.
+    ///
+    pub fn new(device: Arc<Device>, capacity: usize) -> Result<FrameAllocator, OomError> {
+        assert!(capacity >= 1);
+
+        let alignment = {
+            let limits = device.physical_device().limits();
+            cmp::max(limits.min_uniform_buffer_offset_alignment(),
+                     limits.min_storage_buffer_offset_alignment()) as usize
+        };
+
+        let usage = BufferUsage {
+            uniform_buffer: true,
+            storage_buffer: true,
+            .. BufferUsage::none()
+        };
+
+        let preference = MemoryPreference {
+            required: MemoryProperties { host_visible: true, .. MemoryProperties::none() },
+            preferred: MemoryProperties { device_local: true, .. MemoryProperties::none() },
+        };
+
+        let buffer = unsafe {
+            match CpuAccessibleBuffer::raw_with_memory_preference(device, capacity, usage,
+                                                                  iter::empty(), preference)
+            {
+                Ok(b) => b,
+                Err(MemoryAllocError::OomError(err)) => return Err(err),
+                // Vk specs guarantee that a host-visible memory type is always available for
+                // buffers.
+                Err(MemoryAllocError::NoSuitableMemoryType) => unreachable!(),
+            }
+        };
+
+        Ok(FrameAllocator {
+            buffer: buffer,
+            offset: 0,
+            alignment: alignment,
+        })
+    }
+
+    /// Returns the size of the buffer in bytes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.size()
+    }
+
+    /// Returns the alignment of the dynamic offsets returned by `allocate()`.
+    #[inline]
+    pub fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    /// Returns the number of bytes that have been allocated since the last reset, including
+    /// padding.
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the buffer that the allocations are made from.
+    ///
+    /// This is the buffer that must be put in the descriptor set. The range of the descriptor
+    /// must start at 0 and have the size of one allocation.
+    ///
+    /// Allocating panics if the buffer is locked with `CpuAccessibleBuffer::write()` at the time.
+    #[inline]
+    pub fn buffer(&self) -> &Arc<CpuAccessibleBuffer<[u8]>> {
+        &self.buffer
+    }
+
+    /// Writes `data` to the buffer, and returns the slice that contains it and its offset within
+    /// the buffer.
+    ///
+    /// The offset is aligned so that it can be used as a dynamic offset for a uniform or storage
+    /// buffer descriptor.
+    pub fn allocate<T>(&mut self, data: T)
+                       -> Result<(FrameAllocatorSlice<T>, u32), FrameAllocatorError>
+        where T: Copy + Send + Sync + 'static
+    {
+        let offset = self.write(data)?;
+        let size = mem::size_of::<T>();
+
+        let slice = BufferSlice::from_typed_buffer_access(self.buffer.clone())
+            .slice(offset .. offset + size).unwrap();
+        Ok((unsafe { slice.reinterpret() }, offset as u32))
+    }
+
+    /// Same as `allocate()`, but returns a `DynamicSubbuffer` that can be written directly into
+    /// a dynamic uniform or storage buffer descriptor.
+    ///
+    /// Returns `FrameAllocatorError::ZeroSized` if `T` is zero-sized, as a descriptor can't
+    /// point to an empty range.
+    pub fn allocate_dynamic<T>(&mut self, data: T)
+                               -> Result<FrameAllocatorDynamic<T>, FrameAllocatorError>
+        where T: Copy + Send + Sync + 'static
+    {
+        if mem::size_of::<T>() == 0 {
+            return Err(FrameAllocatorError::ZeroSized);
+        }
+
+        let offset = self.write(data)?;
+        // The offset is aligned, in range, and the size isn't 0, so this can't fail.
+        Ok(DynamicSubbuffer::new(self.buffer.clone(), offset).unwrap())
+    }
+
+    // Writes `data` at the next aligned offset and returns this offset.
+    fn write<T>(&mut self, data: T) -> Result<usize, FrameAllocatorError>
+        where T: Copy + Send + Sync + 'static
+    {
+        let size = mem::size_of::<T>();
+        let alignment = cmp::max(self.alignment, mem::align_of::<T>());
+        let offset = (self.offset + alignment - 1) / alignment * alignment;
+
+        if offset + size > self.capacity() {
+            return Err(FrameAllocatorError::OutOfSpace);
+        }
+
+        unsafe {
+            // Only us write to the buffer, and the GPU can't be using this range, as noted in the
+            // documentation of `reset()`. Only the range of the allocation is mapped and flushed.
+            let mut mapping = self.buffer.write_range(offset .. offset + size).unwrap();
+            ptr::copy_nonoverlapping(&data as *const T as *const u8, mapping.as_mut_ptr(), size);
+        }
+
+        self.offset = offset + size;
+        Ok(offset)
+    }
+
+    /// Rewinds the allocator, so that the next allocation starts at the beginning of the buffer.
+    ///
+    /// You must make sure that the GPU has finished reading the data that was allocated before,
+    /// otherwise it will be overwritten while being read.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+}
+
+unsafe impl DeviceOwned for FrameAllocator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.buffer.device()
+    }
+}
+
+/// Error that can happen when allocating from a `FrameAllocator`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameAllocatorError {
+    /// There is not enough space left in the buffer. Use a larger capacity or reset the
+    /// allocator more often.
+    OutOfSpace,
+
+    /// The type of the data is zero-sized, and a `DynamicSubbuffer` can't be empty.
+    ZeroSized,
+}
+
+impl error::Error for FrameAllocatorError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            FrameAllocatorError::OutOfSpace => "there is not enough space left in the buffer",
+            FrameAllocatorError::ZeroSized => "the type of the data is zero-sized",
+        }
+    }
+}
+
+impl fmt::Display for FrameAllocatorError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferAccess;
+    use buffer::FrameAllocator;
+    use buffer::frame_allocator::FrameAllocatorError;
+
+    #[test]
+    fn aligned_offsets() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut allocator = FrameAllocator::new(device, 4096).unwrap();
+        let alignment = allocator.alignment();
+
+        let (a, offset_a) = allocator.allocate(1u8).unwrap();
+        let (b, offset_b) = allocator.allocate([2.0f32; 4]).unwrap();
+
+        assert_eq!(offset_a, 0);
+        assert_eq!(a.offset(), 0);
+        assert_eq!(offset_b as usize % alignment, 0);
+        assert!(offset_b > offset_a);
+        assert_eq!(b.offset(), offset_b as usize);
+        assert_eq!(b.size(), 16);
+
+        let content = allocator.buffer().read().unwrap();
+        assert_eq!(content[0], 1);
+    }
+
+    #[test]
+    fn allocate_dynamic() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut allocator = FrameAllocator::new(device, 4096).unwrap();
+        allocator.allocate(1u8).unwrap();
+        let sub = allocator.allocate_dynamic([2.0f32; 4]).unwrap();
+
+        assert_eq!(sub.dynamic_offset() as usize % allocator.alignment(), 0);
+        assert!(sub.dynamic_offset() > 0);
+        assert_eq!(sub.size(), 16);
+        assert_eq!(sub.inner().offset, 0);
+    }
+
+    #[test]
+    fn allocate_dynamic_zero_sized() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut allocator = FrameAllocator::new(device, 4096).unwrap();
+
+        match allocator.allocate_dynamic(()) {
+            Err(FrameAllocatorError::ZeroSized) => (),
+            _ => panic!()
+        };
+
+        assert_eq!(allocator.used(), 0);
+    }
+
+    #[test]
+    fn reset() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut allocator = FrameAllocator::new(device, 4096).unwrap();
+        allocator.allocate(0u32).unwrap();
+        allocator.allocate(0u32).unwrap();
+        assert!(allocator.used() > 4);
+
+        allocator.reset();
+        assert_eq!(allocator.used(), 0);
+        assert_eq!(allocator.allocate(0u32).unwrap().1, 0);
+    }
+
+    #[test]
+    fn out_of_space() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut allocator = FrameAllocator::new(device, 16).unwrap();
+        allocator.allocate([0u8; 12]).unwrap();
+
+        match allocator.allocate([0u8; 12]) {
+            Err(FrameAllocatorError::OutOfSpace) => (),
+            _ => panic!()
+        };
+    }
+}
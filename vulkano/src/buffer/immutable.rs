@@ -29,6 +29,7 @@ use std::sync::atomic::Ordering;
 use smallvec::SmallVec;
 
 use buffer::CpuAccessibleBuffer;
+use buffer::cpu_access::FromDataSliceError;
 use buffer::sys::BufferCreationError;
 use buffer::sys::SparseLevel;
 use buffer::sys::UnsafeBuffer;
@@ -229,6 +230,29 @@ impl<T> ImmutableBuffer<[T]> {
     }
 }
 
+impl ImmutableBuffer<[u8]> {
+    /// Builds an `ImmutableBuffer` from a slice of bytes, for when the layout of the elements is
+    /// only known at runtime.
+    ///
+    /// `stride` is the size in bytes of one element. Returns an error if `stride` is 0 or if the
+    /// length of `data` is not a multiple of `stride`.
+    ///
+    /// Just like `from_data`, this function returns the newly-created buffer and a future
+    /// representing the initial upload operation.
+    pub fn from_data_slice<'a, I>(data: &[u8], stride: usize, usage: BufferUsage,
+                                  queue_families: I, queue: Arc<Queue>)
+                -> Result<(Arc<ImmutableBuffer<[u8]>>, ImmutableBufferFromBufferFuture),
+                          FromDataSliceError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        let source = CpuAccessibleBuffer::from_data_slice(queue.device().clone(),
+                                                          BufferUsage::transfer_source(),
+                                                          iter::once(queue.family()), data,
+                                                          stride)?;
+        Ok(ImmutableBuffer::from_buffer(source, usage, queue_families, queue)?)
+    }
+}
+
 impl<T: ?Sized> ImmutableBuffer<T> {
     /// Builds a new buffer without checking the size and granting free access for the initial
     /// upload.
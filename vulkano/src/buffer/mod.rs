@@ -36,6 +36,10 @@
 //! If you just want to get started, you can use the `CpuAccessibleBuffer` everywhere, as it is
 //! the most flexible type of buffer.
 //!
+//! For uploading a lot of data at each frame, a `StagingRing` avoids creating a new buffer for
+//! each upload. For uniforms that change at each draw, a `FrameAllocator` packs them in one
+//! buffer that can be bound with dynamic offsets.
+//!
 //! # Buffers usage
 //!
 //! When you create a buffer object, you have to specify its *usage*. In other words, you have to
@@ -65,8 +69,12 @@
 pub use self::cpu_access::CpuAccessibleBuffer;
 pub use self::cpu_pool::CpuBufferPool;
 pub use self::device_local::DeviceLocalBuffer;
+pub use self::dynamic_subbuffer::DynamicSubbuffer;
+pub use self::dynamic_subbuffer::DynamicSubbufferError;
+pub use self::frame_allocator::FrameAllocator;
 pub use self::immutable::ImmutableBuffer;
 pub use self::slice::BufferSlice;
+pub use self::staging_ring::StagingRing;
 pub use self::sys::BufferCreationError;
 pub use self::traits::BufferAccess;
 pub use self::traits::BufferInner;
@@ -80,10 +88,13 @@ pub use self::view::BufferViewRef;
 pub mod cpu_access;
 pub mod cpu_pool;
 pub mod device_local;
+pub mod frame_allocator;
 pub mod immutable;
+pub mod staging_ring;
 pub mod sys;
 pub mod view;
 
+mod dynamic_subbuffer;
 mod slice;
 mod traits;
-mod usage;
+pub(crate) mod usage;
@@ -148,6 +148,21 @@ impl<T: ?Sized, B> BufferSlice<T, B> {
             size: size,
         }
     }
+
+    /// Changes the type of the content of the slice, without changing its range.
+    ///
+    /// # Safety
+    ///
+    /// The range of the slice must be suitable to hold an `R`.
+    #[inline]
+    pub(crate) unsafe fn reinterpret<R: ?Sized>(self) -> BufferSlice<R, B> {
+        BufferSlice {
+            marker: PhantomData,
+            resource: self.resource,
+            offset: self.offset,
+            size: self.size,
+        }
+    }
 }
 
 impl<T, B> BufferSlice<[T], B> {
@@ -0,0 +1,479 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Ring buffer for uploading data to the GPU.
+//!
+//! Creating a new buffer for each upload is slow, as each creation involves a memory
+//! allocation. A `StagingRing` allocates one large host-visible buffer once, and copies the data
+//! to upload into consecutive ranges of this buffer. The ranges can then be used as the source
+//! of a copy command.
+//!
+//! The ranges of a frame can't be reused until the GPU has finished reading them. This is why
+//! you must call `end_frame()` with the future of the submission that uses the ranges that were
+//! staged during the frame. The ring signals a fence at the end of this future, and considers
+//! the ranges of the frame as free only once this fence is signaled.
+//!
+//! ```
+//! use vulkano::buffer::StagingRing;
+//! use vulkano::buffer::staging_ring::StagingRingPolicy;
+//!
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! let mut ring = StagingRing::new(device.clone(), 16 * 1024 * 1024, StagingRingPolicy::Block)
+//!     .unwrap();
+//!
+//! loop {
+//!     let vertices = ring.stage(&[0.0f32, 1.0, 2.0, 3.0]).unwrap();
+//!     // Copy from `vertices` to a device-local buffer, then submit the command buffer.
+//!     # let future = vulkano::sync::now(device.clone());
+//!     let future = ring.end_frame(future).unwrap();
+//!     # break;
+//! }
+//! ```
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::iter;
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use buffer::BufferAccess;
+use buffer::BufferSlice;
+use buffer::BufferUsage;
+use buffer::CpuAccessibleBuffer;
+use device::Device;
+use device::DeviceOwned;
+use sync::FenceSignalFuture;
+use sync::FlushError;
+use sync::GpuFuture;
+
+use OomError;
+
+/// What a `StagingRing` does when there is not enough free space for new data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StagingRingPolicy {
+    /// Blocks until enough frames have been finished by the GPU. If the data doesn't fit even
+    /// after all previous frames are finished, `stage()` returns `OutOfSpace`.
+    Block,
+
+    /// Replaces the ring with a larger one. The old buffer stays alive as long as the GPU uses
+    /// it.
+    Grow,
+}
+
+/// Slice of a `StagingRing`. Can be used as the source of a copy command.
+pub type StagingRingSlice<T> = BufferSlice<[T], Arc<CpuAccessibleBuffer<[u8]>>>;
+
+/// Ring of host-visible memory for uploading data to the GPU.
+///
+/// See the documentation of the `staging_ring` module.
+pub struct StagingRing {
+    // The buffer that is currently used.
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+    // What to do when the buffer is full.
+    policy: StagingRingPolicy,
+    // Offset where the next data will be written, if there is enough room.
+    head: usize,
+    // Offset of the first byte that may still be read by the GPU. Only meaningful if `used` is
+    // not 0.
+    tail: usize,
+    // Number of bytes between `tail` and `head`, including padding.
+    used: usize,
+    // Number of bytes that have been staged since the last call to `end_frame()`, including
+    // padding.
+    frame_bytes: usize,
+    // Frames that have been submitted but that may not be finished yet, from the oldest to the
+    // newest.
+    frames: VecDeque<Frame>,
+}
+
+// A frame that may still be in use by the GPU.
+struct Frame {
+    // Value of `head` at the end of the frame.
+    end: usize,
+    // Number of bytes used by the frame, including padding.
+    bytes: usize,
+    // Signaled when the GPU no longer needs the data of the frame.
+    fence: Box<FrameFence>,
+}
+
+// Type-erased `FenceSignalFuture`.
+trait FrameFence {
+    fn wait(&self, timeout: Option<Duration>) -> Result<(), FlushError>;
+}
+
+impl<F> FrameFence for Arc<FenceSignalFuture<F>> where F: GpuFuture {
+    #[inline]
+    fn wait(&self, timeout: Option<Duration>) -> Result<(), FlushError> {
+        FenceSignalFuture::wait(self, timeout)
+    }
+}
+
+impl StagingRing {
+    /// Builds a new `StagingRing` of `capacity` bytes.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `capacity` is 0.
+    ///
+    pub fn new(device: Arc<Device>, capacity: usize, policy: StagingRingPolicy)
+               -> Result<StagingRing, OomError>
+    {
+        assert!(capacity >= 1);
+
+        Ok(StagingRing {
+            buffer: try!(StagingRing::create_buffer(device, capacity)),
+            policy: policy,
+            head: 0,
+            tail: 0,
+            used: 0,
+            frame_bytes: 0,
+            frames: VecDeque::new(),
+        })
+    }
+
+    #[inline]
+    fn create_buffer(device: Arc<Device>, capacity: usize)
+                     -> Result<Arc<CpuAccessibleBuffer<[u8]>>, OomError>
+    {
+        unsafe {
+            CpuAccessibleBuffer::uninitialized_array(device, capacity,
+                                                     BufferUsage::transfer_source(),
+                                                     iter::empty())
+        }
+    }
+
+    /// Returns the size of the ring in bytes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.size()
+    }
+
+    /// Returns the number of bytes that are waiting to be read by the GPU, including the ones of
+    /// the current frame.
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Returns the policy of the ring.
+    #[inline]
+    pub fn policy(&self) -> StagingRingPolicy {
+        self.policy
+    }
+
+    /// Copies `data` into the ring and returns the slice that contains it.
+    ///
+    /// The offset of the slice is a multiple of 4 and of the size of `T`, which makes it usable
+    /// as the source of a buffer-to-buffer or a buffer-to-image copy.
+    pub fn stage<T>(&mut self, data: &[T]) -> Result<StagingRingSlice<T>, StagingRingError>
+        where T: Copy + Send + Sync + 'static
+    {
+        let size = data.len() * mem::size_of::<T>();
+        let alignment = lcm(4, cmp::max(mem::size_of::<T>(), 1));
+
+        try!(self.cleanup_finished());
+
+        let offset = loop {
+            if let Some(offset) = self.try_alloc(size, alignment) {
+                break offset;
+            }
+
+            match self.policy {
+                StagingRingPolicy::Block => {
+                    if self.frames.is_empty() {
+                        return Err(StagingRingError::OutOfSpace);
+                    }
+
+                    try!(self.frames[0].fence.wait(None));
+                    self.retire_oldest_frame();
+                },
+                StagingRingPolicy::Grow => {
+                    let mut capacity = self.capacity() * 2;
+                    while capacity < size {
+                        capacity *= 2;
+                    }
+
+                    self.buffer = try!(StagingRing::create_buffer(self.device().clone(),
+                                                                  capacity));
+                    self.head = 0;
+                    self.tail = 0;
+                    self.used = 0;
+                    self.frame_bytes = 0;
+                    self.frames.clear();
+                },
+            }
+        };
+
+        unsafe {
+            // There is no other object that has access to the buffer, and the GPU doesn't read
+            // this range. Therefore the lock can't fail and there is no race. Only the written
+            // range is mapped and flushed.
+            let mut mapping = self.buffer.write_range(offset .. offset + size).unwrap();
+            ptr::copy_nonoverlapping(data.as_ptr() as *const u8, mapping.as_mut_ptr(), size);
+        }
+
+        let slice = BufferSlice::from_typed_buffer_access(self.buffer.clone())
+            .slice(offset .. offset + size).unwrap();
+        Ok(unsafe { slice.reinterpret() })
+    }
+
+    /// Ends the current frame. `future` must contain all the commands that read from the slices
+    /// that were staged since the previous call to `end_frame()`.
+    ///
+    /// Signals a fence after `future` and flushes it. The space used by the frame is reclaimed
+    /// once the fence is signaled. The returned future can be used to chain more operations.
+    pub fn end_frame<F>(&mut self, future: F)
+                        -> Result<Arc<FenceSignalFuture<F>>, FlushError>
+        where F: GpuFuture + 'static
+    {
+        let future = Arc::new(try!(future.then_signal_fence_and_flush()));
+
+        if self.frame_bytes != 0 {
+            self.frames.push_back(Frame {
+                end: self.head,
+                bytes: self.frame_bytes,
+                fence: Box::new(future.clone()),
+            });
+
+            self.frame_bytes = 0;
+        }
+
+        Ok(future)
+    }
+
+    // Reclaims the space of the frames that are finished, without blocking.
+    fn cleanup_finished(&mut self) -> Result<(), FlushError> {
+        while !self.frames.is_empty() {
+            match self.frames[0].fence.wait(Some(Duration::from_secs(0))) {
+                Ok(()) => self.retire_oldest_frame(),
+                Err(FlushError::Timeout) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn retire_oldest_frame(&mut self) {
+        let frame = self.frames.pop_front().unwrap();
+        self.tail = frame.end;
+        self.used -= frame.bytes;
+    }
+
+    // Tries to find `size` free bytes at an offset that is a multiple of `alignment`. Returns the
+    // offset and marks the bytes as used.
+    fn try_alloc(&mut self, size: usize, alignment: usize) -> Option<usize> {
+        let capacity = self.capacity();
+
+        if self.used == 0 {
+            // Start from the beginning in order to have as much contiguous space as possible.
+            self.head = 0;
+            self.tail = 0;
+        }
+
+        let aligned_head = align(self.head, alignment);
+
+        let (offset, end) = if self.used == 0 || self.head > self.tail {
+            // The free space is `head .. capacity` and `0 .. tail`.
+            if aligned_head + size <= capacity {
+                (aligned_head, aligned_head + size)
+            } else if size <= self.tail {
+                // Wrap around. The bytes at the end of the buffer are lost until the next wrap.
+                (0, size)
+            } else {
+                return None;
+            }
+        } else if self.head < self.tail {
+            // The free space is `head .. tail`.
+            if aligned_head + size <= self.tail {
+                (aligned_head, aligned_head + size)
+            } else {
+                return None;
+            }
+        } else {
+            // `head == tail` with some bytes used means that the ring is full.
+            return None;
+        };
+
+        let consumed = if offset >= self.head {
+            end - self.head
+        } else {
+            (capacity - self.head) + end
+        };
+
+        self.head = end;
+        self.used += consumed;
+        self.frame_bytes += consumed;
+        Some(offset)
+    }
+}
+
+unsafe impl DeviceOwned for StagingRing {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.buffer.device()
+    }
+}
+
+impl fmt::Debug for StagingRing {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("StagingRing")
+            .field("capacity", &self.capacity())
+            .field("policy", &self.policy)
+            .field("used", &self.used)
+            .field("frames_in_flight", &self.frames.len())
+            .finish()
+    }
+}
+
+#[inline]
+fn align(value: usize, alignment: usize) -> usize {
+    (value + alignment - 1) / alignment * alignment
+}
+
+#[inline]
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        let t = x % y;
+        x = y;
+        y = t;
+    }
+    a / x * b
+}
+
+/// Error that can happen when staging data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StagingRingError {
+    /// Not enough memory to grow the ring.
+    OomError(OomError),
+    /// Error while waiting for a previous frame to finish.
+    FlushError(FlushError),
+    /// The data doesn't fit in the ring, even after all the previous frames have finished.
+    OutOfSpace,
+}
+
+impl error::Error for StagingRingError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            StagingRingError::OomError(_) => "not enough memory to grow the ring",
+            StagingRingError::FlushError(_) => "error while waiting for a previous frame to \
+                                                finish",
+            StagingRingError::OutOfSpace => "the data doesn't fit in the ring, even after all \
+                                             the previous frames have finished",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            StagingRingError::OomError(ref err) => Some(err),
+            StagingRingError::FlushError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for StagingRingError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for StagingRingError {
+    #[inline]
+    fn from(err: OomError) -> StagingRingError {
+        StagingRingError::OomError(err)
+    }
+}
+
+impl From<FlushError> for StagingRingError {
+    #[inline]
+    fn from(err: FlushError) -> StagingRingError {
+        StagingRingError::FlushError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferAccess;
+    use buffer::StagingRing;
+    use buffer::staging_ring::StagingRingError;
+    use buffer::staging_ring::StagingRingPolicy;
+    use sync::now;
+
+    #[test]
+    fn consecutive_and_aligned() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut ring = StagingRing::new(device, 1024, StagingRingPolicy::Block).unwrap();
+
+        let a = ring.stage(&[1u8, 2, 3]).unwrap();
+        let b = ring.stage(&[1.0f32, 2.0]).unwrap();
+        let c = ring.stage(&[[0u8; 3]; 2]).unwrap();
+
+        assert_eq!(a.offset(), 0);
+        assert_eq!(a.size(), 3);
+        assert_eq!(b.offset(), 4);
+        assert_eq!(b.len(), 2);
+        assert_eq!(c.offset(), 12);
+        assert_eq!(c.size(), 6);
+        assert_eq!(ring.used(), 18);
+
+        let content = a.buffer().read().unwrap();
+        assert_eq!(&content[0 .. 3], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn reuse_after_frame() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut ring = StagingRing::new(device.clone(), 64, StagingRingPolicy::Block).unwrap();
+
+        for _ in 0 .. 10 {
+            // Doesn't fit twice in the ring, so each frame waits for the previous one.
+            ring.stage(&[0u32; 12]).unwrap();
+            ring.end_frame(now(device.clone())).unwrap();
+        }
+
+        assert_eq!(ring.capacity(), 64);
+    }
+
+    #[test]
+    fn too_large() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut ring = StagingRing::new(device, 64, StagingRingPolicy::Block).unwrap();
+        ring.stage(&[0u8; 48]).unwrap();
+
+        match ring.stage(&[0u8; 32]) {
+            Err(StagingRingError::OutOfSpace) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn grow() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut ring = StagingRing::new(device, 64, StagingRingPolicy::Grow).unwrap();
+        ring.stage(&[0u8; 48]).unwrap();
+        let slice = ring.stage(&[0u8; 200]).unwrap();
+
+        assert_eq!(ring.capacity(), 256);
+        assert_eq!(slice.offset(), 0);
+        assert_eq!(slice.buffer().size(), 256);
+    }
+}
@@ -36,6 +36,7 @@ use buffer::usage::usage_to_bits;
 use device::Device;
 use device::DeviceOwned;
 use memory::DeviceMemory;
+use memory::ExternalMemoryHandleType;
 use memory::MemoryRequirements;
 use sync::Sharing;
 
@@ -51,6 +52,10 @@ pub struct UnsafeBuffer {
     device: Arc<Device>,
     size: usize,
     usage: vk::BufferUsageFlags,
+    // True if the buffer was created with the `Concurrent` sharing mode.
+    concurrent: bool,
+    // The handle type passed to `with_external_memory`, if any.
+    external_handle_type: Option<ExternalMemoryHandleType>,
 }
 
 impl UnsafeBuffer {
@@ -67,6 +72,40 @@ impl UnsafeBuffer {
                              sharing: Sharing<I>, sparse: SparseLevel)
                              -> Result<(UnsafeBuffer, MemoryRequirements), BufferCreationError>
         where I: Iterator<Item = u32>
+    {
+        UnsafeBuffer::new_impl(device, size, usage, sharing, sparse, None)
+    }
+
+    /// Same as `new`, but the buffer can be bound to memory that is exported or imported as an
+    /// operating system handle of the given type.
+    ///
+    /// Returns `UnsupportedExternalHandleType` if the physical device can neither export nor
+    /// import buffers with this usage as this handle type, or if this can't be checked because
+    /// the `khr_external_memory_capabilities` instance extension is not enabled.
+    ///
+    /// The memory must then be allocated with `DeviceMemory::alloc_exportable` or imported with
+    /// `DeviceMemory::import_fd` or similar.
+    ///
+    /// # Panic
+    ///
+    /// Same as `new`.
+    ///
+    pub unsafe fn with_external_memory<'a, I>(device: Arc<Device>, size: usize,
+                                              usage: BufferUsage, sharing: Sharing<I>,
+                                              sparse: SparseLevel,
+                                              handle_type: ExternalMemoryHandleType)
+                                              -> Result<(UnsafeBuffer, MemoryRequirements),
+                                                        BufferCreationError>
+        where I: Iterator<Item = u32>
+    {
+        UnsafeBuffer::new_impl(device, size, usage, sharing, sparse, Some(handle_type))
+    }
+
+    unsafe fn new_impl<'a, I>(device: Arc<Device>, size: usize, usage: BufferUsage,
+                              sharing: Sharing<I>, sparse: SparseLevel,
+                              external_handle_type: Option<ExternalMemoryHandleType>)
+                              -> Result<(UnsafeBuffer, MemoryRequirements), BufferCreationError>
+        where I: Iterator<Item = u32>
     {
         let vk = device.pointers();
 
@@ -87,6 +126,37 @@ impl UnsafeBuffer {
             return Err(BufferCreationError::SparseResidencyAliasedFeatureNotEnabled);
         }
 
+        // Checking the device address feature.
+        if usage.shader_device_address && !device.enabled_features().buffer_device_address {
+            return Err(BufferCreationError::BufferDeviceAddressFeatureNotEnabled);
+        }
+
+        // Checking that the handle type can be used with this kind of buffer.
+        let external_infos = match external_handle_type {
+            Some(handle_type) => {
+                if !device.loaded_extensions().khr_external_memory {
+                    return Err(BufferCreationError::ExternalMemoryExtensionNotEnabled);
+                }
+
+                match device.physical_device().external_buffer_properties(usage, handle_type) {
+                    Some(ref props) if props.exportable || props.importable => (),
+                    _ => return Err(BufferCreationError::UnsupportedExternalHandleType),
+                }
+
+                Some(vk::ExternalMemoryBufferCreateInfoKHR {
+                    sType: vk::STRUCTURE_TYPE_EXTERNAL_MEMORY_BUFFER_CREATE_INFO_KHR,
+                    pNext: ptr::null(),
+                    handleTypes: handle_type as u32,
+                })
+            },
+            None => None,
+        };
+
+        let concurrent = match sharing {
+            Sharing::Exclusive => false,
+            Sharing::Concurrent(_) => true,
+        };
+
         let buffer = {
             let (sh_mode, sh_indices) = match sharing {
                 Sharing::Exclusive => (vk::SHARING_MODE_EXCLUSIVE, SmallVec::<[u32; 8]>::new()),
@@ -95,7 +165,8 @@ impl UnsafeBuffer {
 
             let infos = vk::BufferCreateInfo {
                 sType: vk::STRUCTURE_TYPE_BUFFER_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: external_infos.as_ref().map(|i| i as *const _ as *const _)
+                                     .unwrap_or(ptr::null()),
                 flags: sparse.to_flags(),
                 size: size as u64,
                 usage: usage_bits,
@@ -145,6 +216,8 @@ impl UnsafeBuffer {
             device: device.clone(),
             size: size as usize,
             usage: usage_bits,
+            concurrent: concurrent,
+            external_handle_type: external_handle_type,
         };
 
         Ok((obj, mem_reqs))
@@ -165,6 +238,10 @@ impl UnsafeBuffer {
             mem_reqs.memoryTypeBits & (1 << memory.memory_type().id()) != 0
         });
 
+        // Exportable memory can only hold buffers created with the same handle type.
+        debug_assert!(memory.export_handle_type().is_none() ||
+                      memory.export_handle_type() == self.external_handle_type);
+
         // Check for alignment correctness.
         {
             let limits = self.device().physical_device().limits();
@@ -190,6 +267,12 @@ impl UnsafeBuffer {
         self.size
     }
 
+    /// Returns the handle type that was passed to `with_external_memory`, if any.
+    #[inline]
+    pub fn external_memory_handle_type(&self) -> Option<ExternalMemoryHandleType> {
+        self.external_handle_type
+    }
+
     #[inline]
     pub fn usage_transfer_src(&self) -> bool {
         (self.usage & vk::BUFFER_USAGE_TRANSFER_SRC_BIT) != 0
@@ -235,6 +318,48 @@ impl UnsafeBuffer {
         (self.usage & vk::BUFFER_USAGE_INDIRECT_BUFFER_BIT) != 0
     }
 
+    #[inline]
+    pub fn usage_conditional_rendering(&self) -> bool {
+        (self.usage & vk::BUFFER_USAGE_CONDITIONAL_RENDERING_BIT_EXT) != 0
+    }
+
+    /// Returns true if the buffer was created with the `Concurrent` sharing mode, in which case
+    /// it can be accessed by multiple queue families without ownership transfers.
+    #[inline]
+    pub fn concurrent_sharing(&self) -> bool {
+        self.concurrent
+    }
+
+    #[inline]
+    pub fn usage_shader_device_address(&self) -> bool {
+        (self.usage & vk::BUFFER_USAGE_SHADER_DEVICE_ADDRESS_BIT_KHR) != 0
+    }
+
+    /// Returns the address of the buffer in the address space of the device.
+    ///
+    /// # Safety
+    ///
+    /// - The buffer must be bound to memory.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the buffer wasn't created with the `shader_device_address` usage.
+    ///
+    pub unsafe fn device_address(&self) -> u64 {
+        assert!(self.usage_shader_device_address(),
+                "The buffer wasn't created with the `shader_device_address` usage");
+
+        let vk = self.device.pointers();
+
+        let infos = vk::BufferDeviceAddressInfoKHR {
+            sType: vk::STRUCTURE_TYPE_BUFFER_DEVICE_ADDRESS_INFO_KHR,
+            pNext: ptr::null(),
+            buffer: self.buffer,
+        };
+
+        vk.GetBufferDeviceAddressKHR(self.device.internal_object(), &infos)
+    }
+
     /// Returns a key unique to each `UnsafeBuffer`. Can be used for the `conflicts_key` method.
     #[inline]
     pub fn key(&self) -> u64 {
@@ -313,6 +438,14 @@ pub enum BufferCreationError {
     SparseResidencyBufferFeatureNotEnabled,
     /// Sparse aliasing was requested but the corresponding feature wasn't enabled.
     SparseResidencyAliasedFeatureNotEnabled,
+    /// The `shader_device_address` usage was requested but the `buffer_device_address` feature
+    /// wasn't enabled.
+    BufferDeviceAddressFeatureNotEnabled,
+    /// External memory was requested but the corresponding extension wasn't enabled.
+    ExternalMemoryExtensionNotEnabled,
+    /// The physical device doesn't support exporting or importing buffers with this usage as
+    /// the requested handle type.
+    UnsupportedExternalHandleType,
 }
 
 impl error::Error for BufferCreationError {
@@ -329,6 +462,17 @@ impl error::Error for BufferCreationError {
             BufferCreationError::SparseResidencyAliasedFeatureNotEnabled => {
                 "sparse aliasing was requested but the corresponding feature wasn't enabled"
             },
+            BufferCreationError::BufferDeviceAddressFeatureNotEnabled => {
+                "the `shader_device_address` usage was requested but the `buffer_device_address` \
+                 feature wasn't enabled"
+            },
+            BufferCreationError::ExternalMemoryExtensionNotEnabled => {
+                "external memory was requested but the corresponding extension wasn't enabled"
+            },
+            BufferCreationError::UnsupportedExternalHandleType => {
+                "the physical device doesn't support this external memory handle type for this \
+                 buffer"
+            },
         }
     }
 
@@ -377,6 +521,7 @@ mod tests {
 
     use device::Device;
     use device::DeviceOwned;
+    use memory::ExternalMemoryHandleType;
     use sync::Sharing;
 
     #[test]
@@ -414,6 +559,20 @@ mod tests {
         };
     }
 
+    #[test]
+    fn missing_feature_buffer_device_address() {
+        let (device, _) = gfx_dev_and_queue!();
+        let usage = BufferUsage { shader_device_address: true, .. BufferUsage::none() };
+        unsafe {
+            match UnsafeBuffer::new(device, 128, usage, Sharing::Exclusive::<Empty<_>>,
+                                    SparseLevel::none())
+            {
+                Err(BufferCreationError::BufferDeviceAddressFeatureNotEnabled) => (),
+                _ => panic!()
+            }
+        };
+    }
+
     #[test]
     fn missing_feature_sparse_binding() {
         let (device, _) = gfx_dev_and_queue!();
@@ -455,4 +614,19 @@ mod tests {
             }
         };
     }
+
+    #[test]
+    fn missing_extension_external_memory() {
+        let (device, _) = gfx_dev_and_queue!();
+        unsafe {
+            match UnsafeBuffer::with_external_memory(device, 128, BufferUsage::all(),
+                                                     Sharing::Exclusive::<Empty<_>>,
+                                                     SparseLevel::none(),
+                                                     ExternalMemoryHandleType::OpaqueFd)
+            {
+                Err(BufferCreationError::ExternalMemoryExtensionNotEnabled) => (),
+                _ => panic!()
+            }
+        };
+    }
 }
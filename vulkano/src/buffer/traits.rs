@@ -102,6 +102,21 @@ pub unsafe trait BufferAccess: DeviceOwned {
         self.inner().buffer.size()
     }
 
+    /// Returns the address of the start of the buffer in the address space of the device.
+    ///
+    /// Shaders can access the content of the buffer through this address. The buffer must have
+    /// been created with the `shader_device_address` usage.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the buffer wasn't created with the `shader_device_address` usage.
+    ///
+    #[inline]
+    fn device_address(&self) -> u64 {
+        let inner = self.inner();
+        unsafe { inner.buffer.device_address() + inner.offset as u64 }
+    }
+
     /// Returns the length of the buffer in number of elements.
     ///
     /// This method can only be called for buffers whose type is known to be an array.
@@ -162,6 +177,9 @@ pub unsafe trait BufferAccess: DeviceOwned {
     ///
     /// If this function returns `false`, this means that we are allowed to access the offset/size
     /// of `self` at the same time as the offset/size of `other` without causing a data race.
+    ///
+    /// The default implementation compares the byte ranges within the underlying buffer, so that
+    /// two disjoint slices of the same buffer don't conflict.
     fn conflicts_buffer(&self, self_offset: usize, self_size: usize,
                         other: &BufferAccess, other_offset: usize, other_size: usize)
                         -> bool
@@ -176,16 +194,7 @@ pub unsafe trait BufferAccess: DeviceOwned {
 
         let self_offset = self_offset + self.inner().offset;
         let other_offset = other_offset + other.inner().offset;
-
-        if self_offset < other_offset && self_offset + self_size <= other_offset {
-            return false;
-        }
-
-        if other_offset < self_offset && other_offset + other_size <= self_offset {
-            return false;
-        }
-
-        true
+        ranges_overlap(self_offset, self_size, other_offset, other_size)
     }
 
     /// Returns true if an access to `self` (as defined by `self_offset` and `self_size`)
@@ -256,6 +265,13 @@ pub unsafe trait BufferAccess: DeviceOwned {
     unsafe fn increase_gpu_lock(&self);
 }
 
+// Returns true if the range of `a_size` bytes at `a_offset` and the range of `b_size` bytes at
+// `b_offset` have at least one byte in common.
+#[inline]
+fn ranges_overlap(a_offset: usize, a_size: usize, b_offset: usize, b_size: usize) -> bool {
+    a_size != 0 && b_size != 0 && a_offset < b_offset + b_size && b_offset < a_offset + a_size
+}
+
 /// Inner information about a buffer.
 #[derive(Copy, Clone, Debug)]
 pub struct BufferInner<'a> {
@@ -309,3 +325,25 @@ pub unsafe trait TypedBufferAccess: BufferAccess {
 unsafe impl<T> TypedBufferAccess for T where T: SafeDeref, T::Target: TypedBufferAccess {
     type Content = <T::Target as TypedBufferAccess>::Content;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ranges_overlap;
+
+    #[test]
+    fn disjoint_ranges() {
+        assert!(!ranges_overlap(0, 16, 16, 16));
+        assert!(!ranges_overlap(16, 16, 0, 16));
+        assert!(!ranges_overlap(0, 16, 64, 4));
+        assert!(!ranges_overlap(8, 0, 0, 16));
+    }
+
+    #[test]
+    fn overlapping_ranges() {
+        assert!(ranges_overlap(0, 16, 15, 16));
+        assert!(ranges_overlap(15, 16, 0, 16));
+        assert!(ranges_overlap(0, 64, 16, 4));
+        assert!(ranges_overlap(16, 4, 0, 64));
+        assert!(ranges_overlap(8, 8, 8, 8));
+    }
+}
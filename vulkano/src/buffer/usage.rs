@@ -27,6 +27,13 @@ pub struct BufferUsage {
     pub index_buffer: bool,
     pub vertex_buffer: bool,
     pub indirect_buffer: bool,
+    /// The buffer can be used as the predicate of conditional rendering. Requires the
+    /// `ext_conditional_rendering` extension.
+    pub conditional_rendering: bool,
+    /// The device address of the buffer can be queried with `device_address()`, so that shaders
+    /// can access it through a pointer. Requires the `khr_buffer_device_address` and
+    /// `khr_device_group` extensions and the `buffer_device_address` feature.
+    pub shader_device_address: bool,
 }
 
 impl BufferUsage {
@@ -43,10 +50,15 @@ impl BufferUsage {
             index_buffer: false,
             vertex_buffer: false,
             indirect_buffer: false,
+            conditional_rendering: false,
+            shader_device_address: false,
         }
     }
 
     /// Builds a `BufferUsage` with all values set to true. Can be used for quick prototyping.
+    ///
+    /// The usages that require a device extension, such as `conditional_rendering`, are left
+    /// to false.
     #[inline]
     pub fn all() -> BufferUsage {
         BufferUsage {
@@ -59,6 +71,8 @@ impl BufferUsage {
             index_buffer: true,
             vertex_buffer: true,
             indirect_buffer: true,
+            conditional_rendering: false,
+            shader_device_address: false,
         }
     }
 
@@ -175,6 +189,8 @@ impl BitOr for BufferUsage {
             index_buffer: self.index_buffer || rhs.index_buffer,
             vertex_buffer: self.vertex_buffer || rhs.vertex_buffer,
             indirect_buffer: self.indirect_buffer || rhs.indirect_buffer,
+            conditional_rendering: self.conditional_rendering || rhs.conditional_rendering,
+            shader_device_address: self.shader_device_address || rhs.shader_device_address,
         }
     }
 }
@@ -192,5 +208,7 @@ pub fn usage_to_bits(usage: BufferUsage) -> vk::BufferUsageFlagBits {
     if usage.index_buffer { result |= vk::BUFFER_USAGE_INDEX_BUFFER_BIT; }
     if usage.vertex_buffer { result |= vk::BUFFER_USAGE_VERTEX_BUFFER_BIT; }
     if usage.indirect_buffer { result |= vk::BUFFER_USAGE_INDIRECT_BUFFER_BIT; }
+    if usage.conditional_rendering { result |= vk::BUFFER_USAGE_CONDITIONAL_RENDERING_BIT_EXT; }
+    if usage.shader_device_address { result |= vk::BUFFER_USAGE_SHADER_DEVICE_ADDRESS_BIT_KHR; }
     result
 }
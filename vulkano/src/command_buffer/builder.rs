@@ -9,6 +9,7 @@
 
 use std::error;
 use std::fmt;
+use std::ops::Range;
 use std::sync::Arc;
 
 use buffer::Buffer;
@@ -17,20 +18,30 @@ use buffer::TypedBufferAccess;
 use device::DeviceOwned;
 use command_buffer::DrawIndirectCommand;
 use command_buffer::DynamicState;
+use command_buffer::SubpassContents;
 use command_buffer::cb::AddCommand;
 use command_buffer::cb::CommandBufferBuild;
 use command_buffer::commands_extra;
 use command_buffer::commands_raw;
+use descriptor::descriptor::ShaderStages;
 use descriptor::descriptor_set::DescriptorSetsCollection;
+use descriptor::pipeline_layout::PipelineLayoutAbstract;
 use framebuffer::FramebufferAbstract;
 use framebuffer::RenderPassAbstract;
 use framebuffer::RenderPassDescClearValues;
 use image::Image;
+use image::ImageSubresourceLayers;
 use instance::QueueFamily;
 use pipeline::ComputePipelineAbstract;
 use pipeline::GraphicsPipelineAbstract;
+use pipeline::PipelineBindPoint;
 use pipeline::vertex::VertexSource;
 use pipeline::input_assembly::Index;
+use pipeline::viewport::Scissor;
+use pipeline::viewport::Viewport;
+use sync::AccessFlagBits;
+use sync::Event;
+use sync::PipelineStages;
 
 ///
 /// > **Note**: This trait is just a utility trait. Do not implement it yourself. Instead
@@ -133,32 +144,231 @@ pub unsafe trait CommandBufferBuilder: DeviceOwned {
         Ok(self.add(cmd)?)
     }
 
+    /// Adds a command that copies the content of an image to a buffer.
+    ///
+    /// The first layer and the first mipmap level of the image are copied. This command does not
+    /// perform any conversion, and the data inside the buffer will have the format of the image.
+    ///
+    /// If the image is not in the `TransferSrcOptimal` layout, it is transitioned to this layout
+    /// for the duration of the copy.
+    #[inline]
+    fn copy_image_to_buffer<I, B, O>(self, image: I, buffer: B)
+                                     -> Result<O, CommandBufferBuilderError<commands_raw::CmdCopyImageToBufferError>>
+        where Self: Sized + AddCommand<commands_raw::CmdCopyImageToBuffer<I::Access, B::Access>, Out = O>,
+              I: Image, B: Buffer
+    {
+        let cmd = match commands_raw::CmdCopyImageToBuffer::new(image.access(), buffer.access()) {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Same as `copy_image_to_buffer` but lets you specify a range for the source image.
+    #[inline]
+    fn copy_image_to_buffer_dimensions<I, B, O>(self, image: I, buffer: B, offset: [u32; 3],
+                                                size: [u32; 3], first_layer: u32, num_layers: u32,
+                                                mipmap: u32) -> Result<O, CommandBufferBuilderError<commands_raw::CmdCopyImageToBufferError>>
+        where Self: Sized + AddCommand<commands_raw::CmdCopyImageToBuffer<I::Access, B::Access>, Out = O>,
+              I: Image, B: Buffer
+    {
+        let cmd = match commands_raw::CmdCopyImageToBuffer::with_dimensions(image.access(),
+                                                                            buffer.access(), offset, size,
+                                                                            first_layer, num_layers, mipmap)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds a command that copies a region of an image to another image.
+    ///
+    /// The two formats must either be identical, or both be uncompressed color formats whose
+    /// texels have the same size. This command does not perform any conversion. The region of
+    /// size `extent` must fit in both subresources, whose dimensions are the dimensions of their
+    /// mipmap level.
+    ///
+    /// If the source is not in the `TransferSrcOptimal` layout or the destination is not in the
+    /// `TransferDstOptimal` layout, they are transitioned to these layouts for the duration of
+    /// the copy.
+    #[inline]
+    fn copy_image<S, D, O>(self, source: S, source_subresource: ImageSubresourceLayers,
+                           source_offset: [u32; 3], destination: D,
+                           destination_subresource: ImageSubresourceLayers,
+                           destination_offset: [u32; 3], extent: [u32; 3])
+                           -> Result<O, CommandBufferBuilderError<commands_raw::CmdCopyImageError>>
+        where Self: Sized + AddCommand<commands_raw::CmdCopyImage<S::Access, D::Access>, Out = O>,
+              S: Image, D: Image
+    {
+        let cmd = match commands_raw::CmdCopyImage::new(source.access(), source_subresource,
+                                                        source_offset, destination.access(),
+                                                        destination_subresource,
+                                                        destination_offset, extent)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds the release half of a transfer of the ownership of `buffer` from the queue family of
+    /// this builder to `destination`.
+    ///
+    /// `stages` and `access` describe how the previous commands of this queue family used the
+    /// buffer. The transfer is completed by calling `acquire_buffer_ownership` with the same
+    /// queue families on a command buffer of the `destination` queue family, which must be
+    /// submitted after this one and wait for it, for example with a semaphore.
+    ///
+    /// This is only useful for buffers that were created with the `Exclusive` sharing mode.
+    #[inline]
+    fn release_buffer_ownership<B, O>(self, buffer: B, destination: QueueFamily,
+                                      stages: PipelineStages, access: AccessFlagBits)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdQueueOwnershipTransferError>>
+        where Self: Sized + AddCommand<commands_raw::CmdBufferOwnershipTransfer<B::Access>, Out = O>,
+              B: Buffer
+    {
+        let cmd = match commands_raw::CmdBufferOwnershipTransfer::release(buffer.access(),
+                                                                          self.queue_family(),
+                                                                          destination, stages,
+                                                                          access)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds the acquire half of a transfer of the ownership of `buffer` from `source` to the
+    /// queue family of this builder.
+    ///
+    /// `stages` and `access` describe how the next commands of this queue family are going to use
+    /// the buffer. See `release_buffer_ownership`.
+    #[inline]
+    fn acquire_buffer_ownership<B, O>(self, buffer: B, source: QueueFamily,
+                                      stages: PipelineStages, access: AccessFlagBits)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdQueueOwnershipTransferError>>
+        where Self: Sized + AddCommand<commands_raw::CmdBufferOwnershipTransfer<B::Access>, Out = O>,
+              B: Buffer
+    {
+        let cmd = match commands_raw::CmdBufferOwnershipTransfer::acquire(buffer.access(), source,
+                                                                          self.queue_family(),
+                                                                          stages, access)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds the release half of a transfer of the ownership of `image` from the queue family of
+    /// this builder to `destination`.
+    ///
+    /// This is the equivalent of `release_buffer_ownership` for images. The layout of the image
+    /// is not modified.
+    #[inline]
+    fn release_image_ownership<I, O>(self, image: I, destination: QueueFamily,
+                                     stages: PipelineStages, access: AccessFlagBits)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdQueueOwnershipTransferError>>
+        where Self: Sized + AddCommand<commands_raw::CmdImageOwnershipTransfer<I::Access>, Out = O>,
+              I: Image
+    {
+        let cmd = match commands_raw::CmdImageOwnershipTransfer::release(image.access(),
+                                                                         self.queue_family(),
+                                                                         destination, stages,
+                                                                         access)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds the acquire half of a transfer of the ownership of `image` from `source` to the queue
+    /// family of this builder.
+    ///
+    /// This is the equivalent of `acquire_buffer_ownership` for images.
+    #[inline]
+    fn acquire_image_ownership<I, O>(self, image: I, source: QueueFamily,
+                                     stages: PipelineStages, access: AccessFlagBits)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdQueueOwnershipTransferError>>
+        where Self: Sized + AddCommand<commands_raw::CmdImageOwnershipTransfer<I::Access>, Out = O>,
+              I: Image
+    {
+        let cmd = match commands_raw::CmdImageOwnershipTransfer::acquire(image.access(), source,
+                                                                         self.queue_family(),
+                                                                         stages, access)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
     /// Adds a command that starts a render pass.
     ///
-    /// If `secondary` is true, then you will only be able to add secondary command buffers while
-    /// you're inside the first subpass of the render pass. If `secondary` is false, you will only
-    /// be able to add inline draw commands and not secondary command buffers.
+    /// `contents` describes the commands of the first subpass. With
+    /// `SubpassContents::SecondaryCommandBuffers`, you will only be able to execute secondary
+    /// command buffers while you're inside the first subpass of the render pass. With
+    /// `SubpassContents::Inline`, you will only be able to add inline draw commands and not
+    /// secondary command buffers.
     ///
     /// You must call this before you can add draw commands.
     #[inline]
-    fn begin_render_pass<F, C, O>(self, framebuffer: F, secondary: bool, clear_values: C)
+    fn begin_render_pass<F, C, O>(self, framebuffer: F, contents: SubpassContents, clear_values: C)
                                   -> Result<O, CommandAddError>
         where Self: Sized + AddCommand<commands_raw::CmdBeginRenderPass<Arc<RenderPassAbstract + Send + Sync>, F>, Out = O>,
               F: FramebufferAbstract + RenderPassDescClearValues<C>
     {
-        let cmd = commands_raw::CmdBeginRenderPass::new(framebuffer, secondary, clear_values);
+        let cmd = commands_raw::CmdBeginRenderPass::new(framebuffer, contents, clear_values);
         self.add(cmd)
     }
 
     /// Adds a command that jumps to the next subpass of the current render pass.
+    ///
+    /// `contents` describes the commands of the new subpass, similar to the parameter of
+    /// `begin_render_pass`.
+    ///
+    /// Returns an error if called outside of a render pass, or if the current subpass is the
+    /// last one of the render pass.
     #[inline]
-    fn next_subpass<O>(self, secondary: bool) -> Result<O, CommandAddError>
+    fn next_subpass<O>(self, contents: SubpassContents) -> Result<O, CommandAddError>
         where Self: Sized + AddCommand<commands_raw::CmdNextSubpass, Out = O>
     {
-        let cmd = commands_raw::CmdNextSubpass::new(secondary);
+        let cmd = commands_raw::CmdNextSubpass::new(contents);
         self.add(cmd)
     }
 
+    /// Adds a command that clears regions of attachments of the current subpass.
+    ///
+    /// Each attachment of `attachments` is cleared in each of the rectangles of `rects`, which
+    /// can cover only some of the layers of the framebuffer. Contrary to the clear values of
+    /// `begin_render_pass`, this can be done at any point within a subpass.
+    ///
+    /// Returns an error if called outside of a render pass, if an attachment is not part of the
+    /// current subpass, or if a rectangle goes beyond the framebuffer.
+    #[inline]
+    fn clear_attachments<A, R, O>(self, attachments: A, rects: R)
+                                  -> Result<O, CommandBufferBuilderError<commands_raw::CmdClearAttachmentsError>>
+        where Self: Sized + AddCommand<commands_raw::CmdClearAttachments, Out = O>,
+              A: IntoIterator<Item = commands_raw::ClearAttachment>,
+              R: IntoIterator<Item = commands_raw::ClearRect>
+    {
+        let cmd = match commands_raw::CmdClearAttachments::new(attachments, rects) {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
     /// Adds a command that ends the current render pass.
     ///
     /// This must be called after you went through all the subpasses and before you can build
@@ -171,26 +381,201 @@ pub unsafe trait CommandBufferBuilder: DeviceOwned {
         self.add(cmd)
     }
 
+    /// Adds a command that starts conditional rendering.
+    ///
+    /// The draw and dispatch commands that are added until `end_conditional_rendering` is called
+    /// are only executed by the GPU if the 32-bits value at `offset` bytes within `buffer` is
+    /// non-zero. If `inverted` is true, they are only executed if this value is zero instead.
+    ///
+    /// The buffer must have been created with the `conditional_rendering` usage. Requires the
+    /// `ext_conditional_rendering` extension and the `conditional_rendering` feature to be
+    /// enabled on the device.
+    #[inline]
+    fn begin_conditional_rendering<B, O>(self, buffer: B, offset: usize, inverted: bool)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdBeginConditionalRenderingError>>
+        where Self: Sized + AddCommand<commands_raw::CmdBeginConditionalRendering<B::Access>, Out = O>,
+              B: Buffer
+    {
+        let cmd = match commands_raw::CmdBeginConditionalRendering::new(buffer.access(), offset,
+                                                                        inverted)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds a command that ends conditional rendering.
+    ///
+    /// Must be called after `begin_conditional_rendering`. If conditional rendering was started
+    /// inside a render pass, it must be ended in the same subpass.
+    #[inline]
+    fn end_conditional_rendering<O>(self) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdEndConditionalRendering, Out = O>
+    {
+        let cmd = commands_raw::CmdEndConditionalRendering::new();
+        self.add(cmd)
+    }
+
+    /// Adds a command that opens a debug label region.
+    ///
+    /// Debugging tools such as RenderDoc show the commands that are added until the next call
+    /// to `end_debug_label` as grouped under this label. Regions can be nested. The color is in
+    /// RGBA; pass `[0.0; 4]` to let the tool choose.
+    ///
+    /// Does nothing if the `ext_debug_utils` extension is not enabled on the instance.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `name` contains a nul character.
+    ///
+    #[inline]
+    fn begin_debug_label<O>(self, name: &str, color: [f32; 4]) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdBeginDebugLabel, Out = O>
+    {
+        let cmd = commands_raw::CmdBeginDebugLabel::new(name, color);
+        self.add(cmd)
+    }
+
+    /// Adds a command that closes the last debug label region opened with `begin_debug_label`.
+    ///
+    /// Returns an error if no debug label region is open.
+    #[inline]
+    fn end_debug_label<O>(self) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdEndDebugLabel, Out = O>
+    {
+        let cmd = commands_raw::CmdEndDebugLabel::new();
+        self.add(cmd)
+    }
+
+    /// Adds a command that inserts a single debug label.
+    ///
+    /// Does nothing if the `ext_debug_utils` extension is not enabled on the instance.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `name` contains a nul character.
+    ///
+    #[inline]
+    fn insert_debug_label<O>(self, name: &str, color: [f32; 4]) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdInsertDebugLabel, Out = O>
+    {
+        let cmd = commands_raw::CmdInsertDebugLabel::new(name, color);
+        self.add(cmd)
+    }
+
+    /// Adds a command that sets `event` once the previous commands have finished executing
+    /// `stages`.
+    ///
+    /// Can only be used from outside a render pass.
+    #[inline]
+    fn set_event<O>(self, event: Arc<Event>, stages: PipelineStages) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdSetEvent, Out = O>
+    {
+        let cmd = commands_raw::CmdSetEvent::set(event, stages);
+        self.add(cmd)
+    }
+
+    /// Adds a command that resets `event` once the previous commands have finished executing
+    /// `stages`.
+    ///
+    /// Can only be used from outside a render pass.
+    #[inline]
+    fn reset_event<O>(self, event: Arc<Event>, stages: PipelineStages)
+                      -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdSetEvent, Out = O>
+    {
+        let cmd = commands_raw::CmdSetEvent::reset(event, stages);
+        self.add(cmd)
+    }
+
+    /// Adds a command that sets a single viewport and scissor box that cover the whole of
+    /// `framebuffer`.
+    ///
+    /// The depth range of the viewport is `0.0` to `1.0`. If you need another depth range, pass
+    /// a viewport built with `Viewport::fullscreen_with_depth_range` to the dynamic state of the
+    /// draw commands instead.
+    ///
+    /// The pipelines used afterwards must have been created with dynamic viewports and scissors.
+    #[inline]
+    fn set_fullscreen_viewport_scissor<F, O>(self, framebuffer: &F) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdSetState, Out = O>,
+              F: ?Sized + FramebufferAbstract
+    {
+        let dimensions = [framebuffer.width(), framebuffer.height()];
+        let state = DynamicState {
+            line_width: None,
+            viewports: Some(vec![Viewport::fullscreen(dimensions)]),
+            scissors: Some(vec![Scissor::fullscreen(dimensions)]),
+        };
+
+        // Can't fail, as the viewport has a positive height and the line width isn't set.
+        let cmd = commands_raw::CmdSetState::new(self.device().clone(), state).unwrap();
+        self.add(cmd)
+    }
+
     /// Adds a command that draws.
     ///
+    /// Returns an error if the dynamic state isn't supported by the device. See
+    /// `CmdSetState::new` for more information.
+    ///
     /// Can only be used from inside a render pass.
     #[inline]
     fn draw<P, S, Pc, V, O>(self, pipeline: P, dynamic: DynamicState, vertices: V, sets: S,
-                            push_constants: Pc) -> Result<O, CommandAddError>
+                            push_constants: Pc)
+                            -> Result<O, CommandBufferBuilderError<commands_extra::CmdDrawError>>
         where Self: Sized + AddCommand<commands_extra::CmdDraw<V, P, S, Pc>, Out = O>,
               S: DescriptorSetsCollection,
               P: VertexSource<V> + GraphicsPipelineAbstract + Clone
     {
-        let cmd = commands_extra::CmdDraw::new(pipeline, dynamic, vertices, sets, push_constants);
-        self.add(cmd)
+        let cmd = match commands_extra::CmdDraw::new(pipeline, dynamic, vertices, sets,
+                                                     push_constants)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Same as `draw`, but only draws the vertices and instances within the given ranges.
+    ///
+    /// In addition to the errors of `draw`, returns an error if the start of a range is superior
+    /// to its end, or if a range goes beyond the content of the vertex buffers.
+    ///
+    /// Can only be used from inside a render pass.
+    #[inline]
+    fn draw_range<P, S, Pc, V, O>(self, pipeline: P, dynamic: DynamicState, vertices: V,
+                                  vertex_range: Range<u32>, instance_range: Range<u32>, sets: S,
+                                  push_constants: Pc)
+                                  -> Result<O, CommandBufferBuilderError<commands_extra::CmdDrawError>>
+        where Self: Sized + AddCommand<commands_extra::CmdDraw<V, P, S, Pc>, Out = O>,
+              S: DescriptorSetsCollection,
+              P: VertexSource<V> + GraphicsPipelineAbstract + Clone
+    {
+        let cmd = match commands_extra::CmdDraw::with_range(pipeline, dynamic, vertices,
+                                                            vertex_range, instance_range, sets,
+                                                            push_constants)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
     }
 
     /// Adds a command that draws indexed vertices.
     ///
+    /// Returns an error if the type of the indices or the dynamic state isn't supported by the
+    /// device. Using `u8` indices requires the `ext_index_type_uint8` extension and the
+    /// `index_type_uint8` feature.
+    ///
     /// Can only be used from inside a render pass.
     #[inline]
     fn draw_indexed<P, S, Pc, V, Ib, I, O>(self, pipeline: P, dynamic: DynamicState,
-        vertices: V, index_buffer: Ib, sets: S, push_constants: Pc) -> Result<O, CommandAddError>
+        vertices: V, index_buffer: Ib, sets: S, push_constants: Pc)
+        -> Result<O, CommandBufferBuilderError<commands_extra::CmdDrawIndexedError>>
         where Self: Sized + AddCommand<commands_extra::CmdDrawIndexed<V, Ib::Access, P, S, Pc>, Out = O>,
               S: DescriptorSetsCollection,
               P: VertexSource<V> + GraphicsPipelineAbstract + Clone,
@@ -198,26 +583,125 @@ pub unsafe trait CommandBufferBuilder: DeviceOwned {
               Ib::Access: TypedBufferAccess<Content = [I]>,
               I: Index + 'static
     {
-        let cmd = commands_extra::CmdDrawIndexed::new(pipeline, dynamic, vertices, index_buffer.access(),
-                                           sets, push_constants);
-        self.add(cmd)
+        let cmd = match commands_extra::CmdDrawIndexed::new(pipeline, dynamic, vertices,
+                                                            index_buffer.access(), sets,
+                                                            push_constants)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Same as `draw_indexed`, but only draws the indices and instances within the given ranges.
+    ///
+    /// Returns an error if the start of a range is superior to its end, if the index range goes
+    /// beyond the content of the index buffer, or if the instance range goes beyond the content
+    /// of the per-instance vertex buffers.
+    ///
+    /// Can only be used from inside a render pass.
+    #[inline]
+    fn draw_indexed_range<P, S, Pc, V, Ib, I, O>(self, pipeline: P, dynamic: DynamicState,
+        vertices: V, index_buffer: Ib, index_range: Range<u32>, instance_range: Range<u32>,
+        sets: S, push_constants: Pc)
+        -> Result<O, CommandBufferBuilderError<commands_extra::CmdDrawIndexedError>>
+        where Self: Sized + AddCommand<commands_extra::CmdDrawIndexed<V, Ib::Access, P, S, Pc>, Out = O>,
+              S: DescriptorSetsCollection,
+              P: VertexSource<V> + GraphicsPipelineAbstract + Clone,
+              Ib: Buffer,
+              Ib::Access: TypedBufferAccess<Content = [I]>,
+              I: Index + 'static
+    {
+        let cmd = match commands_extra::CmdDrawIndexed::with_range(pipeline, dynamic, vertices,
+                                                                   index_buffer.access(),
+                                                                   index_range, instance_range,
+                                                                   sets, push_constants)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
     }
 
     /// Adds an indirect draw command.
     ///
+    /// Returns an error if the dynamic state isn't supported by the device.
+    ///
     /// Can only be used from inside a render pass.
     #[inline]
     fn draw_indirect<P, S, Pc, V, B, O>(self, pipeline: P, dynamic: DynamicState,
-        vertices: V, indirect_buffer: B, sets: S, push_constants: Pc) -> Result<O, CommandAddError>
+        vertices: V, indirect_buffer: B, sets: S, push_constants: Pc)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdSetStateError>>
         where Self: Sized + AddCommand<commands_extra::CmdDrawIndirect<V, B::Access, P, S, Pc>, Out = O>,
               S: DescriptorSetsCollection,
               P: VertexSource<V> + GraphicsPipelineAbstract + Clone,
               B: Buffer,
               B::Access: TypedBufferAccess<Content = [DrawIndirectCommand]>
     {
-        let cmd = commands_extra::CmdDrawIndirect::new(pipeline, dynamic, vertices, indirect_buffer.access(),
-                                           sets, push_constants);
-        self.add(cmd)
+        let cmd = match commands_extra::CmdDrawIndirect::new(pipeline, dynamic, vertices,
+                                                             indirect_buffer.access(), sets,
+                                                             push_constants)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds a command that updates a part of the push constants of `pipeline_layout`.
+    ///
+    /// The content of `data` is written at `offset` bytes from the start of the push constants,
+    /// and is visible to the shaders of `stages`. The offset and the size of `data` must be
+    /// multiples of 4, and the range must match the push constant ranges of the layout. See
+    /// `CmdPushConstants::with_range` for more information.
+    ///
+    /// This is useful when only a few bytes of a large push constants block change between two
+    /// draw commands.
+    #[inline]
+    fn push_constants_range<Pl, Pc, O>(self, pipeline_layout: Pl, stages: ShaderStages,
+                                       offset: usize, data: Pc)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdPushConstantsError>>
+        where Self: Sized + AddCommand<commands_raw::CmdPushConstants<Pc, Pl>, Out = O>,
+              Pl: PipelineLayoutAbstract
+    {
+        let cmd = match commands_raw::CmdPushConstants::with_range(pipeline_layout, stages,
+                                                                   offset, data)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds a command that binds `sets` to the `bind_point` slot.
+    ///
+    /// The draw and dispatch commands already bind their own descriptor sets, but this can be
+    /// used to bind the same sets to both the graphics and the compute slots, as the two slots
+    /// are independent. Returns an error if the sets are not compatible with `pipeline_layout`.
+    ///
+    /// Depending on the layers of the builder, an error can also be returned if the sets are not
+    /// compatible with the layout of the pipeline that is bound at `bind_point`.
+    #[inline]
+    fn bind_descriptor_sets<Pl, S, O>(self, bind_point: PipelineBindPoint, pipeline_layout: Pl,
+                                      sets: S)
+        -> Result<O, CommandBufferBuilderError<commands_raw::CmdBindDescriptorSetsError>>
+        where Self: Sized + AddCommand<commands_raw::CmdBindDescriptorSets<S, Pl>, Out = O>,
+              Pl: PipelineLayoutAbstract,
+              S: DescriptorSetsCollection
+    {
+        let cmd = match commands_raw::CmdBindDescriptorSets::with_bind_point(bind_point,
+                                                                             pipeline_layout,
+                                                                             sets)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
     }
 
     /// Executes a compute shader.
@@ -235,6 +719,46 @@ pub unsafe trait CommandBufferBuilder: DeviceOwned {
         Ok(self.add(cmd)?)
     }
 
+    /// Same as `dispatch`, but the workgroups are numbered starting from `base_group` instead of
+    /// 0. The `gl_WorkGroupID` seen by the shader is offset accordingly, which makes it possible
+    /// to split a large dispatch into multiple smaller ones.
+    ///
+    /// A non-zero base requires the `khr_device_group` extension, and a pipeline created with
+    /// `ComputePipeline::with_dispatch_base`.
+    fn dispatch_base<P, S, Pc, O>(self, base_group: [u32; 3], group_count: [u32; 3], pipeline: P,
+                                  sets: S, push_constants: Pc)
+                                  -> Result<O, CommandBufferBuilderError<commands_extra::CmdDispatchError>>
+        where Self: Sized + AddCommand<commands_extra::CmdDispatch<P, S, Pc>, Out = O>,
+              S: DescriptorSetsCollection,
+              P: Clone + ComputePipelineAbstract,
+    {
+        let cmd = match commands_extra::CmdDispatch::with_base(base_group, group_count, pipeline,
+                                                               sets, push_constants)
+        {
+            Ok(cmd) => cmd,
+            Err(err) => return Err(CommandBufferBuilderError::CommandBuildError(err)),
+        };
+
+        Ok(self.add(cmd)?)
+    }
+
+    /// Adds the barrier required between a dispatch that uses the descriptor sets `previous` and
+    /// a dispatch that uses the descriptor sets `next`.
+    ///
+    /// The storage buffers and images written by the previous dispatch are made visible to the
+    /// next dispatch. See the documentation of `CmdComputeBarrier` for more information.
+    ///
+    /// Can only be used from outside a render pass.
+    #[inline]
+    fn compute_barrier<Sp, Sn, O>(self, previous: Sp, next: Sn) -> Result<O, CommandAddError>
+        where Self: Sized + AddCommand<commands_raw::CmdComputeBarrier<Sp, Sn>, Out = O>,
+              Sp: DescriptorSetsCollection,
+              Sn: DescriptorSetsCollection
+    {
+        let cmd = commands_raw::CmdComputeBarrier::new(previous, next);
+        self.add(cmd)
+    }
+
     /// Builds the actual command buffer.
     ///
     /// You must call this function after you have finished adding commands to the command buffer
@@ -334,6 +858,59 @@ pub enum CommandAddError {
     /// Trying to execute a secondary command buffer in a primary command buffer of a different
     /// queue family.
     QueueFamilyMismatch,
+
+    /// Trying to end a debug label while no debug label is open.
+    NoDebugLabelToEnd,
+
+    /// Trying to start conditional rendering while it is already active.
+    ConditionalRenderingAlreadyActive,
+
+    /// Trying to end conditional rendering while it is not active.
+    NoConditionalRenderingToEnd,
+
+    /// Conditional rendering must be ended in the same subpass as where it began, or outside of
+    /// a render pass if it began outside of one.
+    ConditionalRenderingScopeMismatch,
+
+    /// The graphics pipeline was not created for the current subpass, or for a render pass that
+    /// is compatible with the current render pass.
+    IncompatibleRenderPass,
+
+    /// The descriptor sets are not compatible with the layout of the pipeline that is bound at
+    /// the same bind point.
+    IncompatibleDescriptorSets,
+
+    /// Trying to go to the next subpass while the current subpass is the last one of the render
+    /// pass.
+    NumSubpassesExceeded,
+
+    /// Trying to add a command other than the execution of a secondary command buffer to a
+    /// subpass whose contents are `SubpassContents::SecondaryCommandBuffers`.
+    InlineCommandsForbidden,
+
+    /// Trying to execute a secondary command buffer in a subpass whose contents are
+    /// `SubpassContents::Inline`.
+    SecondaryCommandBuffersForbidden,
+
+    /// An attachment to clear is not a color or depth-stencil attachment of the current subpass,
+    /// or doesn't have the aspect to clear.
+    ClearAttachmentNotInSubpass,
+
+    /// A rectangle to clear goes beyond the dimensions or the layers of the framebuffer.
+    ClearRectOutOfRange,
+
+    /// A viewport exceeds the limits of the device or goes beyond the dimensions of the
+    /// framebuffer.
+    ViewportOutOfRange {
+        /// Index of the viewport.
+        index: u32,
+    },
+
+    /// A scissor has an invalid origin or size, or starts outside of the framebuffer.
+    ScissorOutOfRange {
+        /// Index of the scissor.
+        index: u32,
+    },
 }
 
 impl error::Error for CommandAddError {
@@ -359,6 +936,49 @@ impl error::Error for CommandAddError {
                 "trying to execute a secondary command buffer in a primary command buffer of a \
                  different queue family"
             },
+            CommandAddError::NoDebugLabelToEnd => {
+                "trying to end a debug label while no debug label is open"
+            },
+            CommandAddError::ConditionalRenderingAlreadyActive => {
+                "trying to start conditional rendering while it is already active"
+            },
+            CommandAddError::NoConditionalRenderingToEnd => {
+                "trying to end conditional rendering while it is not active"
+            },
+            CommandAddError::ConditionalRenderingScopeMismatch => {
+                "conditional rendering must be ended in the same subpass as where it began"
+            },
+            CommandAddError::IncompatibleRenderPass => {
+                "the graphics pipeline is not compatible with the current subpass"
+            },
+            CommandAddError::IncompatibleDescriptorSets => {
+                "the descriptor sets are not compatible with the layout of the bound pipeline"
+            },
+            CommandAddError::NumSubpassesExceeded => {
+                "trying to go to the next subpass while the current subpass is the last one of \
+                 the render pass"
+            },
+            CommandAddError::InlineCommandsForbidden => {
+                "the contents of the current subpass are secondary command buffers, therefore \
+                 other commands can't be added"
+            },
+            CommandAddError::SecondaryCommandBuffersForbidden => {
+                "the contents of the current subpass are inline, therefore secondary command \
+                 buffers can't be executed"
+            },
+            CommandAddError::ClearAttachmentNotInSubpass => {
+                "an attachment to clear is not part of the current subpass"
+            },
+            CommandAddError::ClearRectOutOfRange => {
+                "a rectangle to clear goes beyond the dimensions of the framebuffer"
+            },
+            CommandAddError::ViewportOutOfRange { .. } => {
+                "a viewport exceeds the limits of the device or goes beyond the dimensions of the \
+                 framebuffer"
+            },
+            CommandAddError::ScissorOutOfRange { .. } => {
+                "a scissor has an invalid origin or size, or starts outside of the framebuffer"
+            },
         }
     }
 }
@@ -90,23 +90,34 @@ macro_rules! pass_through {
     }
 }
 
+pass_through!((B), commands_raw::CmdBeginConditionalRendering<B>);
+pass_through!((), commands_raw::CmdBeginDebugLabel);
 pass_through!((Rp, F), commands_raw::CmdBeginRenderPass<Rp, F>);
 pass_through!((S, Pl), commands_raw::CmdBindDescriptorSets<S, Pl>);
 pass_through!((B), commands_raw::CmdBindIndexBuffer<B>);
 pass_through!((Pl), commands_raw::CmdBindPipeline<Pl>);
 pass_through!((V), commands_raw::CmdBindVertexBuffers<V>);
+pass_through!((B), commands_raw::CmdBufferMemoryBarrier<B>);
+pass_through!((B), commands_raw::CmdBufferOwnershipTransfer<B>);
 pass_through!((S, D), commands_raw::CmdBlitImage<S, D>);
 pass_through!((), commands_raw::CmdClearAttachments);
+pass_through!((Sp, Sn), commands_raw::CmdComputeBarrier<Sp, Sn>);
 pass_through!((S, D), commands_raw::CmdCopyBuffer<S, D>);
 pass_through!((S, D), commands_raw::CmdCopyBufferToImage<S, D>);
 pass_through!((S, D), commands_raw::CmdCopyImage<S, D>);
+pass_through!((S, D), commands_raw::CmdCopyImageToBuffer<S, D>);
 pass_through!((), commands_raw::CmdDispatchRaw);
 pass_through!((), commands_raw::CmdDrawRaw);
 pass_through!((), commands_raw::CmdDrawIndexedRaw);
 pass_through!((B), commands_raw::CmdDrawIndirectRaw<B>);
+pass_through!((), commands_raw::CmdEndConditionalRendering);
+pass_through!((), commands_raw::CmdEndDebugLabel);
 pass_through!((), commands_raw::CmdEndRenderPass);
 pass_through!((C), commands_raw::CmdExecuteCommands<C>);
 pass_through!((B), commands_raw::CmdFillBuffer<B>);
+pass_through!((Im), commands_raw::CmdImageMemoryBarrier<Im>);
+pass_through!((Im), commands_raw::CmdImageOwnershipTransfer<Im>);
+pass_through!((), commands_raw::CmdInsertDebugLabel);
 pass_through!((), commands_raw::CmdNextSubpass);
 pass_through!((Pc, Pl), commands_raw::CmdPushConstants<Pc, Pl>);
 pass_through!((S, D), commands_raw::CmdResolveImage<S, D>);
@@ -12,10 +12,21 @@ use command_buffer::cb::AddCommand;
 use command_buffer::cb::CommandBufferBuild;
 use command_buffer::CommandAddError;
 use command_buffer::CommandBufferBuilder;
+use command_buffer::SubpassContents;
 use command_buffer::commands_raw;
+use descriptor::descriptor::DescriptorDesc;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
+use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
 use device::Device;
 use device::DeviceOwned;
+use format::FormatTy;
+use framebuffer::FramebufferAbstract;
+use framebuffer::RenderPassAbstract;
+use framebuffer::RenderPassDesc;
 use instance::QueueFamily;
+use pipeline::PipelineBindPoint;
+use VulkanObject;
+use vk;
 
 /// Layer around a command buffer builder that checks whether the commands can be executed in the
 /// given context related to render passes.
@@ -26,8 +37,26 @@ use instance::QueueFamily;
 ///   pass, checks that we are within or outside of a render pass.
 /// - When leaving the render pass or going to the next subpass, makes sure that the number of
 ///   subpasses of the current render pass is respected.
+/// - Inside a subpass whose contents are `SubpassContents::SecondaryCommandBuffers`, makes sure
+///   that only secondary command buffers are executed. Inside a subpass whose contents are
+///   `SubpassContents::Inline`, makes sure that no secondary command buffer is executed. This is
+///   only checked if the render pass was started with this layer.
 /// - When binding a graphics pipeline or drawing, makes sure that the pipeline is valid for the
-///   current render pass.
+///   current render pass and that its static viewports and scissors fit in the framebuffer. This
+///   is only checked if the render pass was started with this layer.
+/// - When setting the dynamic viewports and scissors, makes sure that they respect the limits of
+///   the device and, if the render pass was started with this layer, that they fit in the
+///   framebuffer.
+/// - When clearing attachments, makes sure that the attachments belong to the current subpass and
+///   that the rectangles are within the framebuffer. This is only checked if the render pass was
+///   started with this layer.
+/// - When binding descriptor sets, makes sure that they are compatible with the layout of the
+///   pipeline that is bound at the same bind point. This is only checked if the pipeline was bound
+///   with this layer.
+/// - When closing a debug label, makes sure that a debug label is open.
+/// - When starting conditional rendering, makes sure that it isn't already active. When ending
+///   it, makes sure that it is active and that it is ended in the same subpass as where it began,
+///   or outside of a render pass if it began outside of one.
 ///
 pub struct ContextCheckLayer<I> {
     // Inner command buffer builder.
@@ -36,6 +65,82 @@ pub struct ContextCheckLayer<I> {
     inside_render_pass: bool,
     // True if entering/leaving a render pass or going to the next subpass is allowed.
     allow_render_pass_ops: bool,
+    // Number of debug labels that have been opened and not closed yet.
+    debug_label_depth: u32,
+    // If conditional rendering is active, contains true if it was started inside a render pass.
+    conditional_rendering: Option<bool>,
+    // The current render pass, if we are inside a render pass and if it is known.
+    render_pass: Option<CurrentRenderPass>,
+    // Layout of the graphics pipeline that is bound, if it was bound with this layer.
+    graphics_layout: Option<BoundPipelineLayout>,
+    // Layout of the compute pipeline that is bound, if it was bound with this layer.
+    compute_layout: Option<BoundPipelineLayout>,
+}
+
+// Description of the render pass that the command buffer is currently inside of.
+struct CurrentRenderPass {
+    // The framebuffer that was passed when beginning the render pass. Also gives access to the
+    // description of the render pass.
+    desc: Box<RenderPassAbstract + Send + Sync>,
+    // Index of the current subpass.
+    subpass: u32,
+    // Contents of the current subpass.
+    contents: SubpassContents,
+    // Width, height and layers of the framebuffer.
+    dimensions: [u32; 3],
+    // Last graphics pipeline that has been checked against the current subpass, or 0.
+    checked_pipeline: vk::Pipeline,
+}
+
+// Description of the layout of a pipeline that has been bound.
+struct BoundPipelineLayout {
+    // The pipeline whose layout this is.
+    pipeline: vk::Pipeline,
+    // For each set of the layout, the descriptors of each binding.
+    sets: Vec<Vec<Option<DescriptorDesc>>>,
+}
+
+impl BoundPipelineLayout {
+    // Copies the descriptors of `layout`, which is the layout of `pipeline`.
+    fn new(pipeline: vk::Pipeline, layout: &PipelineLayoutDesc) -> BoundPipelineLayout {
+        let sets = (0 .. layout.num_sets()).map(|set| {
+            (0 .. layout.num_bindings_in_set(set).unwrap_or(0))
+                .map(|binding| layout.descriptor(set, binding))
+                .collect()
+        }).collect();
+
+        BoundPipelineLayout {
+            pipeline: pipeline,
+            sets: sets,
+        }
+    }
+}
+
+unsafe impl PipelineLayoutDesc for BoundPipelineLayout {
+    #[inline]
+    fn num_sets(&self) -> usize {
+        self.sets.len()
+    }
+
+    #[inline]
+    fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+        self.sets.get(set).map(|set| set.len())
+    }
+
+    #[inline]
+    fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+        self.sets.get(set).and_then(|set| set.get(binding)).and_then(|desc| desc.clone())
+    }
+
+    #[inline]
+    fn num_push_constants_ranges(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> {
+        None
+    }
 }
 
 impl<I> ContextCheckLayer<I> {
@@ -57,6 +162,11 @@ impl<I> ContextCheckLayer<I> {
             inner: inner,
             inside_render_pass: inside_render_pass,
             allow_render_pass_ops: allow_render_pass_ops,
+            debug_label_depth: 0,
+            conditional_rendering: None,
+            render_pass: None,
+            graphics_layout: None,
+            compute_layout: None,
         }
     }
 
@@ -97,10 +207,17 @@ unsafe impl<I> CommandBufferBuilder for ContextCheckLayer<I>
     }
 }
 
-// TODO:
-// impl!((C), commands_raw::CmdExecuteCommands<C>);
-
-// FIXME: must also check that a pipeline's render pass matches the render pass
+// Returns an error if the contents of the current subpass are known to be secondary command
+// buffers, in which case no other command can be added.
+#[inline]
+fn check_inline_allowed(render_pass: &Option<CurrentRenderPass>) -> Result<(), CommandAddError> {
+    match *render_pass {
+        Some(CurrentRenderPass { contents: SubpassContents::SecondaryCommandBuffers, .. }) => {
+            Err(CommandAddError::InlineCommandsForbidden)
+        },
+        _ => Ok(()),
+    }
+}
 
 // FIXME:
 // > If the variable multisample rate feature is not supported, pipeline is a graphics pipeline,
@@ -117,22 +234,27 @@ macro_rules! impl_always {
 
             #[inline]
             fn add(self, command: $cmd) -> Result<Self::Out, CommandAddError> {
+                check_inline_allowed(&self.render_pass)?;
+
                 Ok(ContextCheckLayer {
                     inner: self.inner.add(command)?,
                     inside_render_pass: self.inside_render_pass,
                     allow_render_pass_ops: self.allow_render_pass_ops,
+                    debug_label_depth: self.debug_label_depth,
+                    conditional_rendering: self.conditional_rendering,
+                    render_pass: self.render_pass,
+                    graphics_layout: self.graphics_layout,
+                    compute_layout: self.compute_layout,
                 })
             }
         }
     }
 }
 
-impl_always!((S, Pl), commands_raw::CmdBindDescriptorSets<S, Pl>);
 impl_always!((B), commands_raw::CmdBindIndexBuffer<B>);
-impl_always!((Pl), commands_raw::CmdBindPipeline<Pl>);
 impl_always!((V), commands_raw::CmdBindVertexBuffers<V>);
+impl_always!((), commands_raw::CmdInsertDebugLabel);
 impl_always!((Pc, Pl), commands_raw::CmdPushConstants<Pc, Pl>);
-impl_always!((), commands_raw::CmdSetState);
 
 macro_rules! impl_inside_only {
     (($($param:ident),*), $cmd:ty) => {
@@ -147,17 +269,23 @@ macro_rules! impl_inside_only {
                     return Err(CommandAddError::ForbiddenOutsideRenderPass);
                 }
 
+                check_inline_allowed(&self.render_pass)?;
+
                 Ok(ContextCheckLayer {
                     inner: self.inner.add(command)?,
                     inside_render_pass: self.inside_render_pass,
                     allow_render_pass_ops: self.allow_render_pass_ops,
+                    debug_label_depth: self.debug_label_depth,
+                    conditional_rendering: self.conditional_rendering,
+                    render_pass: self.render_pass,
+                    graphics_layout: self.graphics_layout,
+                    compute_layout: self.compute_layout,
                 })
             }
         }
     }
 }
 
-impl_inside_only!((), commands_raw::CmdClearAttachments);
 impl_inside_only!((), commands_raw::CmdDrawIndexedRaw);
 impl_inside_only!((B), commands_raw::CmdDrawIndirectRaw<B>);
 impl_inside_only!((), commands_raw::CmdDrawRaw);
@@ -179,6 +307,11 @@ macro_rules! impl_outside_only {
                     inner: self.inner.add(command)?,
                     inside_render_pass: self.inside_render_pass,
                     allow_render_pass_ops: self.allow_render_pass_ops,
+                    debug_label_depth: self.debug_label_depth,
+                    conditional_rendering: self.conditional_rendering,
+                    render_pass: self.render_pass,
+                    graphics_layout: self.graphics_layout,
+                    compute_layout: self.compute_layout,
                 })
             }
         }
@@ -186,17 +319,290 @@ macro_rules! impl_outside_only {
 }
 
 impl_outside_only!((S, D), commands_raw::CmdBlitImage<S, D>);
+impl_outside_only!((B), commands_raw::CmdBufferMemoryBarrier<B>);
+impl_outside_only!((B), commands_raw::CmdBufferOwnershipTransfer<B>);
+impl_outside_only!((Sp, Sn), commands_raw::CmdComputeBarrier<Sp, Sn>);
 impl_outside_only!((S, D), commands_raw::CmdCopyBuffer<S, D>);
 impl_outside_only!((S, D), commands_raw::CmdCopyBufferToImage<S, D>);
 impl_outside_only!((S, D), commands_raw::CmdCopyImage<S, D>);
+impl_outside_only!((S, D), commands_raw::CmdCopyImageToBuffer<S, D>);
 impl_outside_only!((), commands_raw::CmdDispatchRaw);
 impl_outside_only!((B), commands_raw::CmdFillBuffer<B>);
+impl_outside_only!((Im), commands_raw::CmdImageMemoryBarrier<Im>);
+impl_outside_only!((Im), commands_raw::CmdImageOwnershipTransfer<Im>);
 impl_outside_only!((S, D), commands_raw::CmdResolveImage<S, D>);
 impl_outside_only!((), commands_raw::CmdSetEvent);
 impl_outside_only!((B, D), commands_raw::CmdUpdateBuffer<B, D>);
 
+unsafe impl<'a, I, O, Pl> AddCommand<commands_raw::CmdBindPipeline<Pl>> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdBindPipeline<Pl>, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdBindPipeline<Pl>) -> Result<Self::Out, CommandAddError> {
+        check_inline_allowed(&self.render_pass)?;
+
+        if command.is_graphics() && self.inside_render_pass {
+            if let Some(ref mut rp) = self.render_pass {
+                let raw_pipeline = command.sys().internal_object();
+
+                // Binding the same pipeline multiple times in a row within the same subpass is
+                // very common, so we only check it once.
+                if rp.checked_pipeline != raw_pipeline {
+                    if !command.is_compatible_with_subpass(&rp.desc, rp.subpass) {
+                        return Err(CommandAddError::IncompatibleRenderPass);
+                    }
+
+                    command.check_framebuffer_dimensions([rp.dimensions[0], rp.dimensions[1]])?;
+
+                    rp.checked_pipeline = raw_pipeline;
+                }
+            }
+        }
+
+        // A disabled command binds the same pipeline as the previous one, in which case we keep
+        // the layout that we already have.
+        let raw_pipeline = command.sys().internal_object();
+        if raw_pipeline != 0 {
+            let bound_layout = if command.is_graphics() { &mut self.graphics_layout }
+                               else { &mut self.compute_layout };
+
+            let up_to_date = match *bound_layout {
+                Some(ref layout) => layout.pipeline == raw_pipeline,
+                None => false,
+            };
+
+            if !up_to_date {
+                *bound_layout = Some(BoundPipelineLayout::new(raw_pipeline,
+                                                              command.pipeline_layout()));
+            }
+        }
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+unsafe impl<'a, I, O, S, Pl> AddCommand<commands_raw::CmdBindDescriptorSets<S, Pl>> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdBindDescriptorSets<S, Pl>, Out = O>,
+          Pl: PipelineLayoutDesc
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdBindDescriptorSets<S, Pl>) -> Result<Self::Out, CommandAddError> {
+        check_inline_allowed(&self.render_pass)?;
+
+        let bound_layout = match command.bind_point() {
+            PipelineBindPoint::Graphics => &self.graphics_layout,
+            PipelineBindPoint::Compute => &self.compute_layout,
+        };
+
+        if let Some(ref bound_layout) = *bound_layout {
+            if !command.is_compatible_with(bound_layout) {
+                return Err(CommandAddError::IncompatibleDescriptorSets);
+            }
+        }
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+unsafe impl<'a, I, O> AddCommand<commands_raw::CmdSetState> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdSetState, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdSetState) -> Result<Self::Out, CommandAddError> {
+        check_inline_allowed(&self.render_pass)?;
+
+        let dimensions = if self.inside_render_pass {
+            self.render_pass.as_ref().map(|rp| [rp.dimensions[0], rp.dimensions[1]])
+        } else {
+            None
+        };
+
+        check_set_state(&command, dimensions)?;
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+// Checks the viewports and scissors of a `CmdSetState` against the limits of the device and,
+// if known, against the dimensions of the current framebuffer.
+fn check_set_state(command: &commands_raw::CmdSetState, dimensions: Option<[u32; 2]>)
+                   -> Result<(), CommandAddError>
+{
+    let limits = command.device().physical_device().limits();
+
+    if let Some(ref viewports) = command.state().viewports {
+        for (index, viewport) in viewports.iter().enumerate() {
+            let in_framebuffer = dimensions.map(|d| viewport.fits_in(d)).unwrap_or(true);
+            if !viewport.is_within_limits(&limits) || !in_framebuffer {
+                return Err(CommandAddError::ViewportOutOfRange { index: index as u32 });
+            }
+        }
+    }
+
+    if let Some(ref scissors) = command.state().scissors {
+        for (index, scissor) in scissors.iter().enumerate() {
+            let in_framebuffer = dimensions.map(|d| scissor.starts_in(d)).unwrap_or(true);
+            if !scissor.is_valid() || !in_framebuffer {
+                return Err(CommandAddError::ScissorOutOfRange { index: index as u32 });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+unsafe impl<'a, I, O> AddCommand<commands_raw::CmdClearAttachments> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdClearAttachments, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdClearAttachments) -> Result<Self::Out, CommandAddError> {
+        if !self.inside_render_pass {
+            return Err(CommandAddError::ForbiddenOutsideRenderPass);
+        }
+
+        check_inline_allowed(&self.render_pass)?;
+
+        if let Some(ref rp) = self.render_pass {
+            check_clear_attachments(rp, &command)?;
+        }
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+// Checks the attachments and the rectangles of a `CmdClearAttachments` against the current
+// subpass.
+fn check_clear_attachments(rp: &CurrentRenderPass, command: &commands_raw::CmdClearAttachments)
+                           -> Result<(), CommandAddError>
+{
+    let subpass = match rp.desc.subpass_desc(rp.subpass as usize) {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+
+    for num in command.color_attachments() {
+        if num as usize >= subpass.color_attachments.len() {
+            return Err(CommandAddError::ClearAttachmentNotInSubpass);
+        }
+    }
+
+    if command.clears_depth() || command.clears_stencil() {
+        let format_ty = subpass.depth_stencil
+                               .and_then(|(num, _)| rp.desc.attachment_desc(num))
+                               .map(|desc| desc.format.ty());
+
+        let (has_depth, has_stencil) = match format_ty {
+            Some(FormatTy::Depth) => (true, false),
+            Some(FormatTy::Stencil) => (false, true),
+            Some(FormatTy::DepthStencil) => (true, true),
+            _ => (false, false),
+        };
+
+        if (command.clears_depth() && !has_depth) || (command.clears_stencil() && !has_stencil) {
+            return Err(CommandAddError::ClearAttachmentNotInSubpass);
+        }
+    }
+
+    let multiview = rp.desc.view_mask(rp.subpass as usize) != 0;
+
+    let (width, height, layers) = (rp.dimensions[0], rp.dimensions[1], rp.dimensions[2]);
+
+    for rect in command.rects() {
+        if rect.offset[0] as u64 + rect.extent[0] as u64 > width as u64 ||
+           rect.offset[1] as u64 + rect.extent[1] as u64 > height as u64
+        {
+            return Err(CommandAddError::ClearRectOutOfRange);
+        }
+
+        // With multiview, the layers are selected by the view mask of the subpass.
+        if multiview {
+            if rect.base_array_layer != 0 || rect.layer_count != 1 {
+                return Err(CommandAddError::ClearRectOutOfRange);
+            }
+        } else if rect.base_array_layer as u64 + rect.layer_count as u64 > layers as u64 {
+            return Err(CommandAddError::ClearRectOutOfRange);
+        }
+    }
+
+    Ok(())
+}
+
+unsafe impl<'a, I, O, C> AddCommand<commands_raw::CmdExecuteCommands<C>> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdExecuteCommands<C>, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdExecuteCommands<C>) -> Result<Self::Out, CommandAddError> {
+        match self.render_pass {
+            Some(CurrentRenderPass { contents: SubpassContents::Inline, .. }) => {
+                return Err(CommandAddError::SecondaryCommandBuffersForbidden);
+            },
+            _ => (),
+        }
+
+        // The secondary command buffers can bind other pipelines, and the pipelines that are
+        // bound afterwards are unknown.
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: None,
+            compute_layout: None,
+        })
+    }
+}
+
 unsafe impl<'a, I, O, Rp, F> AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>> for ContextCheckLayer<I>
-    where I: AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>, Out = O>
+    where I: AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>, Out = O>,
+          F: FramebufferAbstract + Send + Sync + Clone + 'static
 {
     type Out = ContextCheckLayer<O>;
 
@@ -210,10 +616,23 @@ unsafe impl<'a, I, O, Rp, F> AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>>
             return Err(CommandAddError::ForbiddenInSecondaryCommandBuffer);
         }
 
+        let render_pass = CurrentRenderPass {
+            desc: Box::new(command.framebuffer().clone()),
+            subpass: 0,
+            contents: command.contents(),
+            dimensions: command.framebuffer().dimensions(),
+            checked_pipeline: 0,
+        };
+
         Ok(ContextCheckLayer {
             inner: self.inner.add(command)?,
             inside_render_pass: true,
             allow_render_pass_ops: true,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: Some(render_pass),
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
         })
     }
 }
@@ -233,12 +652,36 @@ unsafe impl<'a, I, O> AddCommand<commands_raw::CmdNextSubpass> for ContextCheckL
             return Err(CommandAddError::ForbiddenInSecondaryCommandBuffer);
         }
 
-        // FIXME: check number of subpasses
+        if let Some(ref rp) = self.render_pass {
+            if rp.subpass as usize + 1 >= rp.desc.num_subpasses() {
+                return Err(CommandAddError::NumSubpassesExceeded);
+            }
+        }
+
+        if self.conditional_rendering == Some(true) {
+            return Err(CommandAddError::ConditionalRenderingScopeMismatch);
+        }
+
+        let contents = command.contents();
+        let render_pass = self.render_pass.map(|rp| {
+            CurrentRenderPass {
+                desc: rp.desc,
+                subpass: rp.subpass + 1,
+                contents: contents,
+                dimensions: rp.dimensions,
+                checked_pipeline: 0,
+            }
+        });
 
         Ok(ContextCheckLayer {
             inner: self.inner.add(command)?,
             inside_render_pass: true,
             allow_render_pass_ops: true,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
         })
     }
 }
@@ -260,10 +703,177 @@ unsafe impl<'a, I, O> AddCommand<commands_raw::CmdEndRenderPass> for ContextChec
 
         // FIXME: check number of subpasses
 
+        if self.conditional_rendering == Some(true) {
+            return Err(CommandAddError::ConditionalRenderingScopeMismatch);
+        }
+
         Ok(ContextCheckLayer {
             inner: self.inner.add(command)?,
             inside_render_pass: false,
             allow_render_pass_ops: true,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: None,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+unsafe impl<'a, I, O> AddCommand<commands_raw::CmdBeginDebugLabel> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdBeginDebugLabel, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdBeginDebugLabel) -> Result<Self::Out, CommandAddError> {
+        check_inline_allowed(&self.render_pass)?;
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth + 1,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+unsafe impl<'a, I, O> AddCommand<commands_raw::CmdEndDebugLabel> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdEndDebugLabel, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdEndDebugLabel) -> Result<Self::Out, CommandAddError> {
+        if self.debug_label_depth == 0 {
+            return Err(CommandAddError::NoDebugLabelToEnd);
+        }
+
+        check_inline_allowed(&self.render_pass)?;
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth - 1,
+            conditional_rendering: self.conditional_rendering,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+unsafe impl<'a, I, O, B> AddCommand<commands_raw::CmdBeginConditionalRendering<B>> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdBeginConditionalRendering<B>, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdBeginConditionalRendering<B>) -> Result<Self::Out, CommandAddError> {
+        if self.conditional_rendering.is_some() {
+            return Err(CommandAddError::ConditionalRenderingAlreadyActive);
+        }
+
+        check_inline_allowed(&self.render_pass)?;
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: Some(self.inside_render_pass),
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
         })
     }
 }
+
+unsafe impl<'a, I, O> AddCommand<commands_raw::CmdEndConditionalRendering> for ContextCheckLayer<I>
+    where I: AddCommand<commands_raw::CmdEndConditionalRendering, Out = O>
+{
+    type Out = ContextCheckLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdEndConditionalRendering) -> Result<Self::Out, CommandAddError> {
+        match self.conditional_rendering {
+            None => return Err(CommandAddError::NoConditionalRenderingToEnd),
+            Some(inside) if inside != self.inside_render_pass => {
+                return Err(CommandAddError::ConditionalRenderingScopeMismatch);
+            },
+            Some(_) => (),
+        }
+
+        check_inline_allowed(&self.render_pass)?;
+
+        Ok(ContextCheckLayer {
+            inner: self.inner.add(command)?,
+            inside_render_pass: self.inside_render_pass,
+            allow_render_pass_ops: self.allow_render_pass_ops,
+            debug_label_depth: self.debug_label_depth,
+            conditional_rendering: None,
+            render_pass: self.render_pass,
+            graphics_layout: self.graphics_layout,
+            compute_layout: self.compute_layout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandAddError;
+    use command_buffer::CommandBufferBuilder;
+    use command_buffer::SubpassContents;
+    use command_buffer::cb::AddCommand;
+    use command_buffer::commands_raw::CmdDrawRaw;
+    use device::Device;
+    use framebuffer::EmptySinglePassRenderPassDesc;
+    use framebuffer::Framebuffer;
+    use framebuffer::FramebufferAbstract;
+    use framebuffer::RenderPassDesc;
+
+    fn single_pass_framebuffer(device: &Arc<Device>) -> Arc<FramebufferAbstract + Send + Sync> {
+        let render_pass = Arc::new(EmptySinglePassRenderPassDesc
+                                       .build_render_pass(device.clone()).unwrap());
+        Arc::new(Framebuffer::with_dimensions(render_pass, [16, 16, 1]).build().unwrap())
+    }
+
+    #[test]
+    fn draw_in_secondary_contents_subpass() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let framebuffer = single_pass_framebuffer(&device);
+        let result = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+            .begin_render_pass(framebuffer, SubpassContents::SecondaryCommandBuffers,
+                               Vec::new()).unwrap()
+            .add(unsafe { CmdDrawRaw::new(3, 1, 0, 0) });
+
+        match result {
+            Err(CommandAddError::InlineCommandsForbidden) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn next_subpass_after_last() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let framebuffer = single_pass_framebuffer(&device);
+        let result = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+            .begin_render_pass(framebuffer, SubpassContents::Inline,
+                               Vec::new()).unwrap()
+            .next_subpass(SubpassContents::Inline);
+
+        match result {
+            Err(CommandAddError::NumSubpassesExceeded) => (),
+            _ => panic!()
+        }
+    }
+}
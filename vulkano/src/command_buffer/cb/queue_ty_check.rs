@@ -91,10 +91,18 @@ macro_rules! q_ty_impl_always {
     }
 }
 
+q_ty_impl_always!((), commands_raw::CmdBeginDebugLabel);
+q_ty_impl_always!((B), commands_raw::CmdBufferMemoryBarrier<B>);
+q_ty_impl_always!((B), commands_raw::CmdBufferOwnershipTransfer<B>);
 q_ty_impl_always!((S, D), commands_raw::CmdCopyBuffer<S, D>);
 q_ty_impl_always!((S, D), commands_raw::CmdCopyBufferToImage<S, D>);
 q_ty_impl_always!((S, D), commands_raw::CmdCopyImage<S, D>);
+q_ty_impl_always!((S, D), commands_raw::CmdCopyImageToBuffer<S, D>);
+q_ty_impl_always!((), commands_raw::CmdEndDebugLabel);
 q_ty_impl_always!((B), commands_raw::CmdFillBuffer<B>);
+q_ty_impl_always!((Im), commands_raw::CmdImageMemoryBarrier<Im>);
+q_ty_impl_always!((Im), commands_raw::CmdImageOwnershipTransfer<Im>);
+q_ty_impl_always!((), commands_raw::CmdInsertDebugLabel);
 q_ty_impl_always!((B, D), commands_raw::CmdUpdateBuffer<B, D>);
 
 macro_rules! q_ty_impl_graphics {
@@ -151,6 +159,7 @@ macro_rules! q_ty_impl_compute {
     }
 }
 
+q_ty_impl_compute!((Sp, Sn), commands_raw::CmdComputeBarrier<Sp, Sn>);
 q_ty_impl_compute!((), commands_raw::CmdDispatchRaw);
 
 macro_rules! q_ty_impl_graphics_or_compute {
@@ -171,6 +180,8 @@ macro_rules! q_ty_impl_graphics_or_compute {
     }
 }
 
+q_ty_impl_graphics_or_compute!((B), commands_raw::CmdBeginConditionalRendering<B>);
+q_ty_impl_graphics_or_compute!((), commands_raw::CmdEndConditionalRendering);
 q_ty_impl_graphics_or_compute!((Pc, Pl), commands_raw::CmdPushConstants<Pc, Pl>);
 q_ty_impl_graphics_or_compute!((), commands_raw::CmdSetEvent);
 q_ty_impl_graphics_or_compute!((), commands_raw::CmdSetState);
@@ -17,6 +17,7 @@ use command_buffer::DynamicState;
 use device::Device;
 use device::DeviceOwned;
 use instance::QueueFamily;
+use pipeline::PipelineBindPoint;
 use VulkanObject;
 use vk;
 
@@ -47,6 +48,10 @@ pub struct StateCacheLayer<I> {
     graphics_pipeline: vk::Pipeline,
     // The latest bind vertex buffers command.
     vertex_buffers: Option<commands_raw::CmdBindVertexBuffersHash>,
+    // The latest bind descriptor sets command for the graphics bind point.
+    graphics_descriptor_sets: Option<commands_raw::CmdBindDescriptorSetsHash>,
+    // The latest bind descriptor sets command for the compute bind point.
+    compute_descriptor_sets: Option<commands_raw::CmdBindDescriptorSetsHash>,
 }
 
 impl<I> StateCacheLayer<I> {
@@ -61,6 +66,8 @@ impl<I> StateCacheLayer<I> {
             compute_pipeline: 0,
             graphics_pipeline: 0,
             vertex_buffers: None,
+            graphics_descriptor_sets: None,
+            compute_descriptor_sets: None,
         }
     }
 
@@ -122,6 +129,8 @@ unsafe impl<Pl, I, O> AddCommand<commands_raw::CmdBindPipeline<Pl>> for StateCac
             graphics_pipeline: self.graphics_pipeline,
             compute_pipeline: self.compute_pipeline,
             vertex_buffers: self.vertex_buffers,
+            graphics_descriptor_sets: self.graphics_descriptor_sets,
+            compute_descriptor_sets: self.compute_descriptor_sets,
         })
     }
 }
@@ -142,6 +151,8 @@ unsafe impl<Cb, I, O> AddCommand<commands_raw::CmdExecuteCommands<Cb>> for State
             compute_pipeline: 0,
             graphics_pipeline: 0,
             vertex_buffers: None,
+            graphics_descriptor_sets: None,
+            compute_descriptor_sets: None,
         })
     }
 }
@@ -170,12 +181,18 @@ unsafe impl<I, O> AddCommand<commands_raw::CmdSetState> for StateCacheLayer<I>
 
         // TODO: missing implementations
 
+        // Can't fail, as `command_state` is a subset of a state that has already been checked.
+        let command = commands_raw::CmdSetState::new(command.device().clone(), command_state)
+            .unwrap();
+
         Ok(StateCacheLayer {
-            inner: self.inner.add(commands_raw::CmdSetState::new(command.device().clone(), command_state))?,
+            inner: self.inner.add(command)?,
             dynamic_state: self.dynamic_state,
             graphics_pipeline: self.graphics_pipeline,
             compute_pipeline: self.compute_pipeline,
             vertex_buffers: self.vertex_buffers,
+            graphics_descriptor_sets: self.graphics_descriptor_sets,
+            compute_descriptor_sets: self.compute_descriptor_sets,
         })
     }
 }
@@ -208,6 +225,45 @@ unsafe impl<I, O, B> AddCommand<commands_raw::CmdBindVertexBuffers<B>> for State
             graphics_pipeline: self.graphics_pipeline,
             compute_pipeline: self.compute_pipeline,
             vertex_buffers: self.vertex_buffers,
+            graphics_descriptor_sets: self.graphics_descriptor_sets,
+            compute_descriptor_sets: self.compute_descriptor_sets,
+        })
+    }
+}
+
+unsafe impl<I, O, S, Pl> AddCommand<commands_raw::CmdBindDescriptorSets<S, Pl>> for StateCacheLayer<I>
+    where I: AddCommand<commands_raw::CmdBindDescriptorSets<S, Pl>, Out = O>
+{
+    type Out = StateCacheLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdBindDescriptorSets<S, Pl>)
+           -> Result<Self::Out, CommandAddError>
+    {
+        // The graphics and compute bind points have their own sets, so binding to one of them
+        // doesn't invalidate what we know about the other.
+        let new_command = {
+            let curr = match command.bind_point() {
+                PipelineBindPoint::Graphics => &mut self.graphics_descriptor_sets,
+                PipelineBindPoint::Compute => &mut self.compute_descriptor_sets,
+            };
+
+            if curr.as_ref() == Some(command.hash()) {
+                command.disabled()
+            } else {
+                *curr = Some(command.hash().clone());
+                command
+            }
+        };
+
+        Ok(StateCacheLayer {
+            inner: self.inner.add(new_command)?,
+            dynamic_state: self.dynamic_state,
+            graphics_pipeline: self.graphics_pipeline,
+            compute_pipeline: self.compute_pipeline,
+            vertex_buffers: self.vertex_buffers,
+            graphics_descriptor_sets: self.graphics_descriptor_sets,
+            compute_descriptor_sets: self.compute_descriptor_sets,
         })
     }
 }
@@ -239,28 +295,122 @@ macro_rules! pass_through {
                     graphics_pipeline: self.graphics_pipeline,
                     compute_pipeline: self.compute_pipeline,
                     vertex_buffers: self.vertex_buffers,
+                    graphics_descriptor_sets: self.graphics_descriptor_sets,
+                    compute_descriptor_sets: self.compute_descriptor_sets,
                 })
             }
         }
     }
 }
 
+pass_through!((B), commands_raw::CmdBeginConditionalRendering<B>);
+pass_through!((), commands_raw::CmdBeginDebugLabel);
 pass_through!((Rp, F), commands_raw::CmdBeginRenderPass<Rp, F>);
-pass_through!((S, Pl), commands_raw::CmdBindDescriptorSets<S, Pl>);
 pass_through!((B), commands_raw::CmdBindIndexBuffer<B>);
 pass_through!((S, D), commands_raw::CmdBlitImage<S, D>);
+pass_through!((B), commands_raw::CmdBufferMemoryBarrier<B>);
+pass_through!((B), commands_raw::CmdBufferOwnershipTransfer<B>);
 pass_through!((), commands_raw::CmdClearAttachments);
+pass_through!((Sp, Sn), commands_raw::CmdComputeBarrier<Sp, Sn>);
 pass_through!((S, D), commands_raw::CmdCopyBuffer<S, D>);
 pass_through!((S, D), commands_raw::CmdCopyBufferToImage<S, D>);
 pass_through!((S, D), commands_raw::CmdCopyImage<S, D>);
+pass_through!((S, D), commands_raw::CmdCopyImageToBuffer<S, D>);
 pass_through!((), commands_raw::CmdDispatchRaw);
 pass_through!((), commands_raw::CmdDrawIndexedRaw);
 pass_through!((B), commands_raw::CmdDrawIndirectRaw<B>);
 pass_through!((), commands_raw::CmdDrawRaw);
+pass_through!((), commands_raw::CmdEndConditionalRendering);
+pass_through!((), commands_raw::CmdEndDebugLabel);
 pass_through!((), commands_raw::CmdEndRenderPass);
 pass_through!((B), commands_raw::CmdFillBuffer<B>);
+pass_through!((Im), commands_raw::CmdImageMemoryBarrier<Im>);
+pass_through!((Im), commands_raw::CmdImageOwnershipTransfer<Im>);
+pass_through!((), commands_raw::CmdInsertDebugLabel);
 pass_through!((), commands_raw::CmdNextSubpass);
 pass_through!((Pc, Pl), commands_raw::CmdPushConstants<Pc, Pl>);
 pass_through!((S, D), commands_raw::CmdResolveImage<S, D>);
 pass_through!((), commands_raw::CmdSetEvent);
 pass_through!((B, D), commands_raw::CmdUpdateBuffer<B, D>);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use command_buffer::cb::AddCommand;
+    use command_buffer::CommandAddError;
+    use command_buffer::commands_raw::CmdBindDescriptorSets;
+    use descriptor::pipeline_layout::EmptyPipelineDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use pipeline::PipelineBindPoint;
+    use super::StateCacheLayer;
+
+    // Builder that records the bind point of the descriptor sets commands that are added to it,
+    // and whether they are enabled.
+    struct Recorder(Vec<(PipelineBindPoint, bool)>);
+
+    unsafe impl<S, Pl> AddCommand<CmdBindDescriptorSets<S, Pl>> for Recorder {
+        type Out = Recorder;
+
+        fn add(mut self, command: CmdBindDescriptorSets<S, Pl>)
+               -> Result<Recorder, CommandAddError>
+        {
+            self.0.push((command.bind_point(), command.is_enabled()));
+            Ok(self)
+        }
+    }
+
+    #[test]
+    fn same_sets_not_rebound() {
+        let (device, _) = gfx_dev_and_queue!();
+        let layout = Arc::new(EmptyPipelineDesc.build(device).unwrap());
+
+        let cmd = |bind_point| {
+            CmdBindDescriptorSets::with_bind_point(bind_point, layout.clone(), ()).unwrap()
+        };
+
+        let recorder = StateCacheLayer::new(Recorder(Vec::new()))
+            .add(cmd(PipelineBindPoint::Graphics)).unwrap()
+            .add(cmd(PipelineBindPoint::Graphics)).unwrap()
+            .into_inner();
+
+        assert_eq!(recorder.0, vec![(PipelineBindPoint::Graphics, true),
+                                    (PipelineBindPoint::Graphics, false)]);
+    }
+
+    #[test]
+    fn bind_points_cached_separately() {
+        let (device, _) = gfx_dev_and_queue!();
+        let layout = Arc::new(EmptyPipelineDesc.build(device).unwrap());
+
+        let cmd = |bind_point| {
+            CmdBindDescriptorSets::with_bind_point(bind_point, layout.clone(), ()).unwrap()
+        };
+
+        let recorder = StateCacheLayer::new(Recorder(Vec::new()))
+            .add(cmd(PipelineBindPoint::Graphics)).unwrap()
+            .add(cmd(PipelineBindPoint::Compute)).unwrap()
+            .add(cmd(PipelineBindPoint::Graphics)).unwrap()
+            .add(cmd(PipelineBindPoint::Compute)).unwrap()
+            .into_inner();
+
+        assert_eq!(recorder.0, vec![(PipelineBindPoint::Graphics, true),
+                                    (PipelineBindPoint::Compute, true),
+                                    (PipelineBindPoint::Graphics, false),
+                                    (PipelineBindPoint::Compute, false)]);
+    }
+
+    #[test]
+    fn other_layout_rebound() {
+        let (device, _) = gfx_dev_and_queue!();
+        let layout1 = Arc::new(EmptyPipelineDesc.build(device.clone()).unwrap());
+        let layout2 = Arc::new(EmptyPipelineDesc.build(device).unwrap());
+
+        let graphics = PipelineBindPoint::Graphics;
+        let recorder = StateCacheLayer::new(Recorder(Vec::new()))
+            .add(CmdBindDescriptorSets::with_bind_point(graphics, layout1, ()).unwrap()).unwrap()
+            .add(CmdBindDescriptorSets::with_bind_point(graphics, layout2, ()).unwrap()).unwrap()
+            .into_inner();
+
+        assert_eq!(recorder.0, vec![(graphics, true), (graphics, true)]);
+    }
+}
@@ -350,9 +350,25 @@ macro_rules! pass_through {
 
 // FIXME: implement manually
 pass_through!((S, Pl), commands_raw::CmdBindDescriptorSets<S, Pl>);
+pass_through!((Sp, Sn), commands_raw::CmdComputeBarrier<Sp, Sn>);
 pass_through!((V), commands_raw::CmdBindVertexBuffers<V>);
 pass_through!((C), commands_raw::CmdExecuteCommands<C>);
 
+unsafe impl<I, O> AddCommand<commands_raw::CmdBeginDebugLabel> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdBeginDebugLabel, Out = O>
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdBeginDebugLabel) -> Result<Self::Out, CommandAddError> {
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
 unsafe impl<I, O, Rp, F> AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>> for SubmitSyncBuilderLayer<I>
     where I: AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>, Out = O>,
           F: FramebufferAbstract + Send + Sync + Clone + 'static
@@ -371,6 +387,26 @@ unsafe impl<I, O, Rp, F> AddCommand<commands_raw::CmdBeginRenderPass<Rp, F>> for
     }
 }
 
+unsafe impl<I, O, B> AddCommand<commands_raw::CmdBeginConditionalRendering<B>> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdBeginConditionalRendering<B>, Out = O>,
+          B: BufferAccess + Send + Sync + Clone + 'static
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdBeginConditionalRendering<B>) -> Result<Self::Out, CommandAddError> {
+        self.add_buffer(command.buffer(), false,
+                        PipelineStages { conditional_rendering: true, .. PipelineStages::none() },
+                        AccessFlagBits { conditional_rendering_read: true, .. AccessFlagBits::none() });
+
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
 unsafe impl<I, O, B> AddCommand<commands_raw::CmdBindIndexBuffer<B>> for SubmitSyncBuilderLayer<I>
     where I: AddCommand<commands_raw::CmdBindIndexBuffer<B>, Out = O>,
           B: BufferAccess + Send + Sync + Clone + 'static
@@ -430,6 +466,42 @@ unsafe impl<I, O, S, D> AddCommand<commands_raw::CmdBlitImage<S, D>> for SubmitS
     }
 }
 
+unsafe impl<I, O, B> AddCommand<commands_raw::CmdBufferOwnershipTransfer<B>> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdBufferOwnershipTransfer<B>, Out = O>,
+          B: BufferAccess + Send + Sync + Clone + 'static
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdBufferOwnershipTransfer<B>) -> Result<Self::Out, CommandAddError> {
+        self.add_buffer(command.buffer(), true, command.stages(), command.access());
+
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
+unsafe impl<I, O, B> AddCommand<commands_raw::CmdBufferMemoryBarrier<B>> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdBufferMemoryBarrier<B>, Out = O>,
+          B: BufferAccess + Send + Sync + Clone + 'static
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdBufferMemoryBarrier<B>) -> Result<Self::Out, CommandAddError> {
+        self.add_buffer(command.buffer(), true, command.stages(), command.access());
+
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
 unsafe impl<I, O> AddCommand<commands_raw::CmdClearAttachments> for SubmitSyncBuilderLayer<I>
     where I: AddCommand<commands_raw::CmdClearAttachments, Out = O>
 {
@@ -517,6 +589,30 @@ unsafe impl<I, O, S, D> AddCommand<commands_raw::CmdCopyImage<S, D>> for SubmitS
     }
 }
 
+unsafe impl<I, O, S, D> AddCommand<commands_raw::CmdCopyImageToBuffer<S, D>> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdCopyImageToBuffer<S, D>, Out = O>,
+          S: ImageAccess + Send + Sync + Clone + 'static,
+          D: BufferAccess + Send + Sync + Clone + 'static
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdCopyImageToBuffer<S, D>) -> Result<Self::Out, CommandAddError> {
+        self.add_image(command.source(), false,
+                       PipelineStages { transfer: true, .. PipelineStages::none() },
+                       AccessFlagBits { transfer_read: true, .. AccessFlagBits::none() });
+        self.add_buffer(command.destination(), true,
+                        PipelineStages { transfer: true, .. PipelineStages::none() },
+                        AccessFlagBits { transfer_write: true, .. AccessFlagBits::none() });
+
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
 unsafe impl<I, O> AddCommand<commands_raw::CmdDispatchRaw> for SubmitSyncBuilderLayer<I>
     where I: AddCommand<commands_raw::CmdDispatchRaw, Out = O>
 {
@@ -582,6 +678,36 @@ unsafe impl<I, O, B> AddCommand<commands_raw::CmdDrawIndirectRaw<B>> for SubmitS
     }
 }
 
+unsafe impl<I, O> AddCommand<commands_raw::CmdEndConditionalRendering> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdEndConditionalRendering, Out = O>
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdEndConditionalRendering) -> Result<Self::Out, CommandAddError> {
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
+unsafe impl<I, O> AddCommand<commands_raw::CmdEndDebugLabel> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdEndDebugLabel, Out = O>
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdEndDebugLabel) -> Result<Self::Out, CommandAddError> {
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
 unsafe impl<I, O> AddCommand<commands_raw::CmdEndRenderPass> for SubmitSyncBuilderLayer<I>
     where I: AddCommand<commands_raw::CmdEndRenderPass, Out = O>
 {
@@ -617,6 +743,57 @@ unsafe impl<I, O, B> AddCommand<commands_raw::CmdFillBuffer<B>> for SubmitSyncBu
     }
 }
 
+unsafe impl<I, O, Im> AddCommand<commands_raw::CmdImageOwnershipTransfer<Im>> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdImageOwnershipTransfer<Im>, Out = O>,
+          Im: ImageAccess + Send + Sync + Clone + 'static
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdImageOwnershipTransfer<Im>) -> Result<Self::Out, CommandAddError> {
+        self.add_image(command.image(), true, command.stages(), command.access());
+
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
+unsafe impl<I, O, Im> AddCommand<commands_raw::CmdImageMemoryBarrier<Im>> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdImageMemoryBarrier<Im>, Out = O>,
+          Im: ImageAccess + Send + Sync + Clone + 'static
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(mut self, command: commands_raw::CmdImageMemoryBarrier<Im>) -> Result<Self::Out, CommandAddError> {
+        self.add_image(command.image(), true, command.stages(), command.access());
+
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
+unsafe impl<I, O> AddCommand<commands_raw::CmdInsertDebugLabel> for SubmitSyncBuilderLayer<I>
+    where I: AddCommand<commands_raw::CmdInsertDebugLabel, Out = O>
+{
+    type Out = SubmitSyncBuilderLayer<O>;
+
+    #[inline]
+    fn add(self, command: commands_raw::CmdInsertDebugLabel) -> Result<Self::Out, CommandAddError> {
+        Ok(SubmitSyncBuilderLayer {
+            inner: AddCommand::add(self.inner, command)?,
+            resources: self.resources,
+            behavior: self.behavior,
+        })
+    }
+}
+
 unsafe impl<I, O> AddCommand<commands_raw::CmdNextSubpass> for SubmitSyncBuilderLayer<I>
     where I: AddCommand<commands_raw::CmdNextSubpass, Out = O>
 {
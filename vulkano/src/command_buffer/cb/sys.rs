@@ -15,6 +15,8 @@ use buffer::BufferAccess;
 use command_buffer::CommandBuffer;
 use command_buffer::CommandBufferBuilder;
 use command_buffer::CommandBufferExecError;
+use command_buffer::CommandBufferInheritance;
+use command_buffer::CommandBufferInheritanceError;
 use command_buffer::cb::CommandBufferBuild;
 use command_buffer::pool::CommandPool;
 use command_buffer::pool::CommandPoolBuilderAlloc;
@@ -155,6 +157,45 @@ impl<P> UnsafeCommandBufferBuilder<P> where P: CommandPool {
     pub unsafe fn already_allocated<R, F>(alloc: P::Builder, kind: Kind<R, F>, flags: Flags)
                                           -> Result<UnsafeCommandBufferBuilder<P>, OomError>
         where R: RenderPassAbstract, F: FramebufferAbstract
+    {
+        let secondary = match kind {
+            Kind::Primary => false,
+            Kind::Secondary | Kind::SecondaryRenderPass { .. } => true,
+        };
+
+        UnsafeCommandBufferBuilder::begin(alloc, secondary, &CommandBufferInheritance::from(kind),
+                                          flags)
+    }
+
+    /// Creates a new builder for a secondary command buffer.
+    ///
+    /// Contrary to `new`, this lets you specify the queries that are inherited from the primary
+    /// command buffer. Returns an error if the features required by `inheritance` are not
+    /// enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// See the `new` method.
+    ///
+    pub unsafe fn secondary<R, F>(pool: &P, inheritance: CommandBufferInheritance<R, F>,
+                                  flags: Flags)
+                                  -> Result<UnsafeCommandBufferBuilder<P>,
+                                            CommandBufferInheritanceError>
+        where R: RenderPassAbstract, F: FramebufferAbstract
+    {
+        try!(inheritance.check(pool.device()));
+
+        let cmd = try!(pool.alloc(true, 1)).next().expect("Requested one command buffer from \
+                                                            the command pool, but got zero.");
+        Ok(try!(UnsafeCommandBufferBuilder::begin(cmd, true, &inheritance, flags)))
+    }
+
+    // Starts recording `alloc`, which must have been allocated as secondary if and only if
+    // `secondary` is true. The inheritance is ignored by Vulkan for primary command buffers.
+    unsafe fn begin<R, F>(alloc: P::Builder, secondary: bool,
+                          inheritance: &CommandBufferInheritance<R, F>, flags: Flags)
+                          -> Result<UnsafeCommandBufferBuilder<P>, OomError>
+        where R: RenderPassAbstract, F: FramebufferAbstract
     {
         let device = alloc.device().clone();
         let vk = device.pointers();
@@ -167,40 +208,12 @@ impl<P> UnsafeCommandBufferBuilder<P> where P: CommandPool {
                 Flags::OneTimeSubmit => vk::COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT,
             };
 
-            let b = match kind {
-                Kind::Primary | Kind::Secondary => 0,
-                Kind::SecondaryRenderPass { .. } => {
-                    vk::COMMAND_BUFFER_USAGE_RENDER_PASS_CONTINUE_BIT
-                },
-            };
+            let b = if secondary { inheritance.usage_flags() } else { 0 };
 
             a | b
         };
 
-        let (rp, sp) = if let Kind::SecondaryRenderPass { ref subpass, .. } = kind {
-            (subpass.render_pass().inner().internal_object(), subpass.index())
-        } else {
-            (0, 0)
-        };
-
-        let framebuffer = if let Kind::SecondaryRenderPass { ref subpass, framebuffer: Some(ref framebuffer) } = kind {
-            // TODO: restore check
-            //assert!(framebuffer.is_compatible_with(subpass.render_pass()));     // TODO: proper error
-            FramebufferAbstract::inner(&framebuffer).internal_object()
-        } else {
-            0
-        };
-
-        let inheritance = vk::CommandBufferInheritanceInfo {
-            sType: vk::STRUCTURE_TYPE_COMMAND_BUFFER_INHERITANCE_INFO,
-            pNext: ptr::null(),
-            renderPass: rp,
-            subpass: sp,
-            framebuffer: framebuffer,
-            occlusionQueryEnable: 0,            // TODO:
-            queryFlags: 0,          // TODO:
-            pipelineStatistics: 0,          // TODO:
-        };
+        let inheritance = inheritance.to_vulkan();
 
         let infos = vk::CommandBufferBeginInfo {
             sType: vk::STRUCTURE_TYPE_COMMAND_BUFFER_BEGIN_INFO,
@@ -215,10 +228,7 @@ impl<P> UnsafeCommandBufferBuilder<P> where P: CommandPool {
             cmd: Some(alloc),
             device: device.clone(),
             flags: flags,
-            secondary_cb: match kind {
-                Kind::Primary => false,
-                Kind::Secondary | Kind::SecondaryRenderPass { .. } => true,
-            },
+            secondary_cb: secondary,
         })
     }
 }
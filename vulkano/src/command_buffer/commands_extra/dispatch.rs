@@ -20,6 +20,7 @@ use command_buffer::commands_raw::CmdDispatchRawError;
 use command_buffer::commands_raw::CmdPushConstants;
 use command_buffer::commands_raw::CmdPushConstantsError;
 use descriptor::descriptor_set::DescriptorSetsCollection;
+use device::DeviceOwned;
 use pipeline::ComputePipelineAbstract;
 
 /// Command that executes a compute shader.
@@ -39,7 +40,7 @@ impl<P, S, Pc> CmdDispatch<P, S, Pc>
         where P: Clone
     {
         let bind_pipeline = CmdBindPipeline::bind_compute_pipeline(pipeline.clone());
-        let descriptor_sets = try!(CmdBindDescriptorSets::new(true, pipeline.clone(), sets));
+        let descriptor_sets = try!(CmdBindDescriptorSets::new(false, pipeline.clone(), sets));
         let push_constants = try!(CmdPushConstants::new(pipeline.clone(), push_constants));
         let dispatch_raw = try!(unsafe { CmdDispatchRaw::new(pipeline.device().clone(), dimensions) });
 
@@ -50,6 +51,23 @@ impl<P, S, Pc> CmdDispatch<P, S, Pc>
             dispatch_raw: dispatch_raw,
         })
     }
+
+    /// See the documentation of the `dispatch_base` method.
+    pub fn with_base(base: [u32; 3], dimensions: [u32; 3], pipeline: P, sets: S,
+                     push_constants: Pc)
+                     -> Result<CmdDispatch<P, S, Pc>, CmdDispatchError>
+        where P: Clone
+    {
+        if base != [0, 0, 0] && !pipeline.allows_dispatch_base() {
+            return Err(CmdDispatchError::DispatchBaseNotAllowed);
+        }
+
+        let mut cmd = try!(CmdDispatch::new(dimensions, pipeline, sets, push_constants));
+        cmd.dispatch_raw = try!(unsafe {
+            CmdDispatchRaw::with_base(cmd.dispatch_raw.device().clone(), base, dimensions)
+        });
+        Ok(cmd)
+    }
 }
 
 unsafe impl<Cb, P, S, Pc, O, O1, O2, O3> AddCommand<CmdDispatch<P, S, Pc>> for Cb
@@ -78,6 +96,9 @@ pub enum CmdDispatchError {
     BindDescriptorSetsError(CmdBindDescriptorSetsError),
     /// Error while setting push constants.
     PushConstantsError(CmdPushConstantsError),
+    /// The base workgroup is not zero, but the pipeline wasn't created with the dispatch base
+    /// flag.
+    DispatchBaseNotAllowed,
 }
 
 impl From<CmdDispatchRawError> for CmdDispatchError {
@@ -106,7 +127,7 @@ impl error::Error for CmdDispatchError {
     fn description(&self) -> &str {
         match *self {
             CmdDispatchError::DispatchRawError(_) => {
-                "the dispatch dimensions are larger than the hardware limits"
+                "the dispatch dimensions or base are not supported by the device"
             },
             CmdDispatchError::BindDescriptorSetsError(_) => {
                 "error while binding descriptor sets"
@@ -114,6 +135,10 @@ impl error::Error for CmdDispatchError {
             CmdDispatchError::PushConstantsError(_) => {
                 "error while setting push constants"
             },
+            CmdDispatchError::DispatchBaseNotAllowed => {
+                "the base workgroup is not zero, but the pipeline wasn't created with the \
+                 dispatch base flag"
+            },
         }
     }
 
@@ -123,6 +148,7 @@ impl error::Error for CmdDispatchError {
             CmdDispatchError::DispatchRawError(ref err) => Some(err),
             CmdDispatchError::BindDescriptorSetsError(ref err) => Some(err),
             CmdDispatchError::PushConstantsError(ref err) => Some(err),
+            CmdDispatchError::DispatchBaseNotAllowed => None,
         }
     }
 }
@@ -7,6 +7,10 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
+use std::ops::Range;
+
 use command_buffer::CommandAddError;
 use command_buffer::DynamicState;
 use command_buffer::cb::AddCommand;
@@ -16,6 +20,7 @@ use command_buffer::commands_raw::CmdBindVertexBuffers;
 use command_buffer::commands_raw::CmdDrawRaw;
 use command_buffer::commands_raw::CmdPushConstants;
 use command_buffer::commands_raw::CmdSetState;
+use command_buffer::commands_raw::CmdSetStateError;
 use descriptor::descriptor_set::DescriptorSetsCollection;
 use pipeline::GraphicsPipelineAbstract;
 use pipeline::vertex::VertexSource;
@@ -35,27 +40,70 @@ impl<V, P, S, Pc> CmdDraw<V, P, S, Pc>
 {
     /// See the documentation of the `draw` method.
     pub fn new(pipeline: P, dynamic: DynamicState, vertices: V, sets: S, push_constants: Pc)
-               -> CmdDraw<V, P, S, Pc>
+               -> Result<CmdDraw<V, P, S, Pc>, CmdDrawError>
         where P: VertexSource<V> + Clone
     {
         let (_, vertex_count, instance_count) = pipeline.decode(&vertices);
 
         let bind_pipeline = CmdBindPipeline::bind_graphics_pipeline(pipeline.clone());
         let device = bind_pipeline.device().clone();
-        let set_state = CmdSetState::new(device, dynamic);
+        let set_state = try!(CmdSetState::new(device, dynamic));
         let descriptor_sets = CmdBindDescriptorSets::new(true, pipeline.clone(), sets).unwrap() /* TODO: error */;
         let push_constants = CmdPushConstants::new(pipeline.clone(), push_constants).unwrap() /* TODO: error */;
         let vertex_buffers = CmdBindVertexBuffers::new(&pipeline, vertices);
         let draw_raw = unsafe { CmdDrawRaw::new(vertex_count as u32, instance_count as u32, 0, 0) };
 
-        CmdDraw {
+        Ok(CmdDraw {
             vertex_buffers: vertex_buffers,
             push_constants: push_constants,
             descriptor_sets: descriptor_sets,
             set_state: set_state,
             bind_pipeline: bind_pipeline,
             draw_raw: draw_raw,
+        })
+    }
+
+    /// Same as `new`, but only draws the vertices and instances within the given ranges.
+    ///
+    /// In addition to the errors of `new`, returns an error if the start of a range is superior
+    /// to its end, or if the ranges go beyond the content of the vertex buffers. The instance
+    /// range is only checked if the vertex source has per-instance buffers. The ranges are only
+    /// checked against the buffers if the recording validation of the device is enabled.
+    pub fn with_range(pipeline: P, dynamic: DynamicState, vertices: V, vertex_range: Range<u32>,
+                      instance_range: Range<u32>, sets: S, push_constants: Pc)
+                      -> Result<CmdDraw<V, P, S, Pc>, CmdDrawError>
+        where P: VertexSource<V> + Clone
+    {
+        try!(check_range_order(&vertex_range));
+        try!(check_range_order(&instance_range));
+
+        if pipeline.device().recording_validation() {
+            let (_, vertex_count, instance_count) = pipeline.decode(&vertices);
+
+            if vertex_range.end as usize > vertex_count {
+                return Err(CmdDrawError::OutOfBounds(DrawOutOfBounds::Vertices {
+                    requested: vertex_range,
+                    available: vertex_count,
+                }));
+            }
+
+            if VertexSource::<V>::has_per_instance_buffers(&pipeline) &&
+               instance_range.end as usize > instance_count
+            {
+                return Err(CmdDrawError::OutOfBounds(DrawOutOfBounds::Instances {
+                    requested: instance_range,
+                    available: instance_count,
+                }));
+            }
         }
+
+        let mut cmd = try!(CmdDraw::new(pipeline, dynamic, vertices, sets, push_constants));
+        cmd.draw_raw = unsafe {
+            CmdDrawRaw::new(vertex_range.end - vertex_range.start,
+                            instance_range.end - instance_range.start,
+                            vertex_range.start, instance_range.start)
+        };
+        Ok(cmd)
     }
 }
 
@@ -79,3 +127,149 @@ unsafe impl<Cb, V, P, S, Pc, O, O1, O2, O3, O4, O5> AddCommand<CmdDraw<V, P, S,
                .add(command.draw_raw)?)
     }
 }
+
+// Returns an error if the start of `range` is superior to its end.
+#[inline]
+pub(super) fn check_range_order(range: &Range<u32>) -> Result<(), DrawOutOfBounds> {
+    if range.start > range.end {
+        return Err(DrawOutOfBounds::InvalidRange { requested: range.clone() });
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when creating a `CmdDraw`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CmdDrawError {
+    /// The dynamic state is not supported by the device.
+    SetStateError(CmdSetStateError),
+    /// The range of the draw is invalid or goes beyond the content of the buffers.
+    OutOfBounds(DrawOutOfBounds),
+}
+
+impl From<CmdSetStateError> for CmdDrawError {
+    #[inline]
+    fn from(err: CmdSetStateError) -> CmdDrawError {
+        CmdDrawError::SetStateError(err)
+    }
+}
+
+impl From<DrawOutOfBounds> for CmdDrawError {
+    #[inline]
+    fn from(err: DrawOutOfBounds) -> CmdDrawError {
+        CmdDrawError::OutOfBounds(err)
+    }
+}
+
+impl error::Error for CmdDrawError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdDrawError::SetStateError(_) => {
+                "the dynamic state is not supported by the device"
+            },
+            CmdDrawError::OutOfBounds(_) => {
+                "the range of the draw is invalid or goes beyond the content of the buffers"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            CmdDrawError::SetStateError(ref err) => Some(err),
+            CmdDrawError::OutOfBounds(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for CmdDrawError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+/// Error that can happen when the range of a draw command is invalid or goes beyond the content
+/// of the bound buffers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawOutOfBounds {
+    /// The start of a range is superior to its end.
+    InvalidRange {
+        /// Range that was requested.
+        requested: Range<u32>,
+    },
+
+    /// The range of vertices is larger than the number of elements in the vertex buffers.
+    Vertices {
+        /// Range of vertices that was requested.
+        requested: Range<u32>,
+        /// Number of vertices in the vertex buffers.
+        available: usize,
+    },
+
+    /// The range of indices is larger than the number of elements in the index buffer.
+    Indices {
+        /// Range of indices that was requested.
+        requested: Range<u32>,
+        /// Number of indices in the index buffer.
+        available: usize,
+    },
+
+    /// The range of instances is larger than the number of elements in the per-instance vertex
+    /// buffers.
+    Instances {
+        /// Range of instances that was requested.
+        requested: Range<u32>,
+        /// Number of instances in the per-instance buffers.
+        available: usize,
+    },
+}
+
+impl error::Error for DrawOutOfBounds {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DrawOutOfBounds::InvalidRange { .. } => {
+                "the start of a range is superior to its end"
+            },
+            DrawOutOfBounds::Vertices { .. } => {
+                "the range of vertices is larger than the number of elements in the vertex buffers"
+            },
+            DrawOutOfBounds::Indices { .. } => {
+                "the range of indices is larger than the number of elements in the index buffer"
+            },
+            DrawOutOfBounds::Instances { .. } => {
+                "the range of instances is larger than the number of elements in the per-instance \
+                 buffers"
+            },
+        }
+    }
+}
+
+impl fmt::Display for DrawOutOfBounds {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_range_order;
+    use super::DrawOutOfBounds;
+
+    #[test]
+    fn range_order() {
+        assert!(check_range_order(&(0 .. 3)).is_ok());
+        assert!(check_range_order(&(2 .. 2)).is_ok());
+    }
+
+    #[test]
+    fn reversed_range() {
+        match check_range_order(&(5 .. 2)) {
+            Err(DrawOutOfBounds::InvalidRange { requested }) => assert_eq!(requested, 5 .. 2),
+            _ => panic!()
+        };
+    }
+}
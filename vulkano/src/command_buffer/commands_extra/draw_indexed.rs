@@ -7,6 +7,10 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
+use std::ops::Range;
+
 use buffer::BufferAccess;
 use buffer::TypedBufferAccess;
 use command_buffer::CommandAddError;
@@ -14,11 +18,15 @@ use command_buffer::DynamicState;
 use command_buffer::cb::AddCommand;
 use command_buffer::commands_raw::CmdBindDescriptorSets;
 use command_buffer::commands_raw::CmdBindIndexBuffer;
+use command_buffer::commands_raw::CmdBindIndexBufferError;
 use command_buffer::commands_raw::CmdBindPipeline;
 use command_buffer::commands_raw::CmdBindVertexBuffers;
 use command_buffer::commands_raw::CmdPushConstants;
 use command_buffer::commands_raw::CmdSetState;
+use command_buffer::commands_raw::CmdSetStateError;
 use command_buffer::commands_raw::CmdDrawIndexedRaw;
+use command_buffer::commands_extra::DrawOutOfBounds;
+use command_buffer::commands_extra::draw;
 use descriptor::descriptor_set::DescriptorSetsCollection;
 use pipeline::GraphicsPipelineAbstract;
 use pipeline::input_assembly::Index;
@@ -42,10 +50,10 @@ impl<V, Ib, I, P, S, Pc> CmdDrawIndexed<V, Ib, P, S, Pc>
           Ib: BufferAccess + TypedBufferAccess<Content = [I]>,
           I: Index + 'static
 {
-    /// See the documentation of the `draw` method.
+    /// See the documentation of the `draw_indexed` method.
     pub fn new(pipeline: P, dynamic: DynamicState,
         vertices: V, index_buffer: Ib, sets: S, push_constants: Pc)
-               -> CmdDrawIndexed<V, Ib, P, S, Pc>
+               -> Result<CmdDrawIndexed<V, Ib, P, S, Pc>, CmdDrawIndexedError>
         where P: VertexSource<V> + Clone
     {
         let index_count = index_buffer.len();
@@ -53,11 +61,11 @@ impl<V, Ib, I, P, S, Pc> CmdDrawIndexed<V, Ib, P, S, Pc>
 
         let bind_pipeline = CmdBindPipeline::bind_graphics_pipeline(pipeline.clone());
         let device = bind_pipeline.device().clone();
-        let set_state = CmdSetState::new(device, dynamic);
+        let set_state = try!(CmdSetState::new(device, dynamic));
         let descriptor_sets = CmdBindDescriptorSets::new(true, pipeline.clone(), sets).unwrap() /* TODO: error */;
         let push_constants = CmdPushConstants::new(pipeline.clone(), push_constants).unwrap() /* TODO: error */;
         let vertex_buffers = CmdBindVertexBuffers::new(&pipeline, vertices);
-        let index_buffer = CmdBindIndexBuffer::new(index_buffer);
+        let index_buffer = try!(CmdBindIndexBuffer::new(index_buffer));
         let draw_indexed_raw = unsafe {
             CmdDrawIndexedRaw::new(
                 index_count as u32, instance_count as u32,
@@ -66,7 +74,7 @@ impl<V, Ib, I, P, S, Pc> CmdDrawIndexed<V, Ib, P, S, Pc>
         };
         // TODO: check that dynamic state is not missing some elements required by the pipeline
 
-        CmdDrawIndexed {
+        Ok(CmdDrawIndexed {
             vertex_buffers: vertex_buffers,
             index_buffer: index_buffer,
             push_constants: push_constants,
@@ -74,7 +82,53 @@ impl<V, Ib, I, P, S, Pc> CmdDrawIndexed<V, Ib, P, S, Pc>
             set_state: set_state,
             bind_pipeline: bind_pipeline,
             draw_indexed_raw: draw_indexed_raw,
+        })
+    }
+
+    /// Same as `new`, but only draws the indices and instances within the given ranges.
+    ///
+    /// In addition to the errors of `new`, returns an error if the start of a range is superior
+    /// to its end, if the index range goes beyond the content of the index buffer, or if the
+    /// instance range goes beyond the content of the per-instance vertex buffers. The instance
+    /// range is only checked if the vertex source has per-instance buffers. The ranges are only
+    /// checked against the buffers if the recording validation of the device is enabled.
+    pub fn with_range(pipeline: P, dynamic: DynamicState, vertices: V, index_buffer: Ib,
+                      index_range: Range<u32>, instance_range: Range<u32>, sets: S,
+                      push_constants: Pc)
+                      -> Result<CmdDrawIndexed<V, Ib, P, S, Pc>, CmdDrawIndexedError>
+        where P: VertexSource<V> + Clone
+    {
+        try!(draw::check_range_order(&index_range));
+        try!(draw::check_range_order(&instance_range));
+
+        if pipeline.device().recording_validation() {
+            let index_count = index_buffer.len();
+            if index_range.end as usize > index_count {
+                return Err(CmdDrawIndexedError::OutOfBounds(DrawOutOfBounds::Indices {
+                    requested: index_range,
+                    available: index_count,
+                }));
+            }
+
+            let (_, _, instance_count) = pipeline.decode(&vertices);
+            if VertexSource::<V>::has_per_instance_buffers(&pipeline) &&
+               instance_range.end as usize > instance_count
+            {
+                return Err(CmdDrawIndexedError::OutOfBounds(DrawOutOfBounds::Instances {
+                    requested: instance_range,
+                    available: instance_count,
+                }));
+            }
         }
+
+        let mut cmd = try!(CmdDrawIndexed::new(pipeline, dynamic, vertices, index_buffer, sets,
+                                               push_constants));
+        cmd.draw_indexed_raw = unsafe {
+            CmdDrawIndexedRaw::new(index_range.end - index_range.start,
+                                   instance_range.end - instance_range.start,
+                                   index_range.start, 0, instance_range.start)
+        };
+        Ok(cmd)
     }
 }
 
@@ -99,3 +153,68 @@ unsafe impl<Cb, V, Ib, P, S, Pc, O, O1, O2, O3, O4, O5, O6> AddCommand<CmdDrawIn
                .add(command.draw_indexed_raw)?)
     }
 }
+
+/// Error that can happen when creating a `CmdDrawIndexed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CmdDrawIndexedError {
+    /// Error while binding the index buffer.
+    BindIndexBufferError(CmdBindIndexBufferError),
+    /// The dynamic state is not supported by the device.
+    SetStateError(CmdSetStateError),
+    /// The range of the draw is invalid or goes beyond the content of the buffers.
+    OutOfBounds(DrawOutOfBounds),
+}
+
+impl From<CmdBindIndexBufferError> for CmdDrawIndexedError {
+    #[inline]
+    fn from(err: CmdBindIndexBufferError) -> CmdDrawIndexedError {
+        CmdDrawIndexedError::BindIndexBufferError(err)
+    }
+}
+
+impl From<CmdSetStateError> for CmdDrawIndexedError {
+    #[inline]
+    fn from(err: CmdSetStateError) -> CmdDrawIndexedError {
+        CmdDrawIndexedError::SetStateError(err)
+    }
+}
+
+impl From<DrawOutOfBounds> for CmdDrawIndexedError {
+    #[inline]
+    fn from(err: DrawOutOfBounds) -> CmdDrawIndexedError {
+        CmdDrawIndexedError::OutOfBounds(err)
+    }
+}
+
+impl error::Error for CmdDrawIndexedError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdDrawIndexedError::BindIndexBufferError(_) => {
+                "error while binding the index buffer"
+            },
+            CmdDrawIndexedError::SetStateError(_) => {
+                "the dynamic state is not supported by the device"
+            },
+            CmdDrawIndexedError::OutOfBounds(_) => {
+                "the range of the draw is invalid or goes beyond the content of the buffers"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            CmdDrawIndexedError::BindIndexBufferError(ref err) => Some(err),
+            CmdDrawIndexedError::SetStateError(ref err) => Some(err),
+            CmdDrawIndexedError::OutOfBounds(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for CmdDrawIndexedError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
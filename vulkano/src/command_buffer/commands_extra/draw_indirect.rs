@@ -19,6 +19,7 @@ use command_buffer::commands_raw::CmdBindVertexBuffers;
 use command_buffer::commands_raw::CmdDrawIndirectRaw;
 use command_buffer::commands_raw::CmdPushConstants;
 use command_buffer::commands_raw::CmdSetState;
+use command_buffer::commands_raw::CmdSetStateError;
 use descriptor::descriptor_set::DescriptorSetsCollection;
 use pipeline::GraphicsPipelineAbstract;
 use pipeline::vertex::VertexSource;
@@ -39,7 +40,7 @@ impl<V, I, P, S, Pc> CmdDrawIndirect<V, I, P, S, Pc>
 {
     /// See the documentation of the `draw` method.
     pub fn new(pipeline: P, dynamic: DynamicState, vertices: V, indirect_buffer: I, sets: S,
-               push_constants: Pc) -> CmdDrawIndirect<V, I, P, S, Pc>
+               push_constants: Pc) -> Result<CmdDrawIndirect<V, I, P, S, Pc>, CmdSetStateError>
         where P: VertexSource<V> + Clone
     {
         let draw_count = indirect_buffer.len() as u32;
@@ -48,20 +49,20 @@ impl<V, I, P, S, Pc> CmdDrawIndirect<V, I, P, S, Pc>
 
         let bind_pipeline = CmdBindPipeline::bind_graphics_pipeline(pipeline.clone());
         let device = bind_pipeline.device().clone();
-        let set_state = CmdSetState::new(device, dynamic);
+        let set_state = try!(CmdSetState::new(device, dynamic));
         let descriptor_sets = CmdBindDescriptorSets::new(true, pipeline.clone(), sets).unwrap() /* TODO: error */;
         let push_constants = CmdPushConstants::new(pipeline.clone(), push_constants).unwrap() /* TODO: error */;
         let vertex_buffers = CmdBindVertexBuffers::new(&pipeline, vertices);
         let draw_raw = unsafe { CmdDrawIndirectRaw::new(indirect_buffer, draw_count) };
 
-        CmdDrawIndirect {
+        Ok(CmdDrawIndirect {
             vertex_buffers: vertex_buffers,
             push_constants: push_constants,
             descriptor_sets: descriptor_sets,
             set_state: set_state,
             bind_pipeline: bind_pipeline,
             draw_raw: draw_raw,
-        }
+        })
     }
 }
 
@@ -13,8 +13,8 @@
 
 pub use self::dispatch::{CmdDispatch, CmdDispatchError};
 //pub use self::dispatch_indirect::{CmdDispatchIndirect, CmdDispatchIndirectError};
-pub use self::draw::CmdDraw;
-pub use self::draw_indexed::CmdDrawIndexed;
+pub use self::draw::{CmdDraw, CmdDrawError, DrawOutOfBounds};
+pub use self::draw_indexed::{CmdDrawIndexed, CmdDrawIndexedError};
 pub use self::draw_indirect::CmdDrawIndirect;
 
 mod dispatch;
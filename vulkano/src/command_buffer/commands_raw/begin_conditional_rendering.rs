@@ -0,0 +1,203 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use buffer::BufferInner;
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::pool::CommandPool;
+use device::Device;
+use device::DeviceOwned;
+use VulkanObject;
+use vk;
+
+/// Command that starts conditional rendering.
+///
+/// The draw and dispatch commands that are recorded until the next `CmdEndConditionalRendering`
+/// are only executed if the 32-bits value in the buffer at the given offset is non-zero (or zero,
+/// if the condition is inverted). The value is read by the GPU when the command buffer executes.
+pub struct CmdBeginConditionalRendering<B> {
+    // The buffer that contains the predicate. Unused, but we need to keep it alive.
+    buffer: B,
+    // Raw handle of the buffer.
+    raw_buffer: vk::Buffer,
+    // Offset of the predicate within the raw buffer.
+    offset: vk::DeviceSize,
+    // Flags to pass to the command.
+    flags: vk::ConditionalRenderingFlagsEXT,
+}
+
+impl<B> CmdBeginConditionalRendering<B>
+    where B: BufferAccess
+{
+    /// Builds the command.
+    ///
+    /// The predicate is the 32-bits value at `offset` bytes within `buffer`. If `inverted` is
+    /// true, the commands are executed if the predicate is zero instead of non-zero.
+    ///
+    /// Requires the `ext_conditional_rendering` extension and the `conditional_rendering`
+    /// feature to be enabled on the device.
+    pub fn new(buffer: B, offset: usize, inverted: bool)
+               -> Result<CmdBeginConditionalRendering<B>, CmdBeginConditionalRenderingError>
+    {
+        {
+            let device = buffer.device();
+            if !device.loaded_extensions().ext_conditional_rendering {
+                return Err(CmdBeginConditionalRenderingError::ExtensionNotEnabled);
+            }
+            if !device.enabled_features().conditional_rendering {
+                return Err(CmdBeginConditionalRenderingError::FeatureNotEnabled);
+            }
+        }
+
+        if offset % 4 != 0 {
+            return Err(CmdBeginConditionalRenderingError::WrongAlignment);
+        }
+        match offset.checked_add(4) {
+            Some(end) if end <= buffer.size() => (),
+            _ => return Err(CmdBeginConditionalRenderingError::OutOfRange),
+        }
+
+        let (raw_buffer, raw_offset) = {
+            let BufferInner { buffer: inner, offset: inner_offset } = buffer.inner();
+            if !inner.usage_conditional_rendering() {
+                return Err(CmdBeginConditionalRenderingError::BufferMissingUsage);
+            }
+            (inner.internal_object(), inner_offset + offset)
+        };
+
+        Ok(CmdBeginConditionalRendering {
+            buffer: buffer,
+            raw_buffer: raw_buffer,
+            offset: raw_offset as vk::DeviceSize,
+            flags: if inverted { vk::CONDITIONAL_RENDERING_INVERTED_BIT_EXT } else { 0 },
+        })
+    }
+}
+
+impl<B> CmdBeginConditionalRendering<B> {
+    /// Returns the buffer that contains the predicate.
+    #[inline]
+    pub fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Returns true if the commands are executed when the predicate is zero.
+    #[inline]
+    pub fn inverted(&self) -> bool {
+        (self.flags & vk::CONDITIONAL_RENDERING_INVERTED_BIT_EXT) != 0
+    }
+}
+
+unsafe impl<B> DeviceOwned for CmdBeginConditionalRendering<B>
+    where B: DeviceOwned
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.buffer.device()
+    }
+}
+
+unsafe impl<'a, P, B> AddCommand<&'a CmdBeginConditionalRendering<B>> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdBeginConditionalRendering<B>) -> Result<Self::Out, CommandAddError> {
+        unsafe {
+            let vk = self.device().pointers();
+            let cmd = self.internal_object();
+
+            let infos = vk::ConditionalRenderingBeginInfoEXT {
+                sType: vk::STRUCTURE_TYPE_CONDITIONAL_RENDERING_BEGIN_INFO_EXT,
+                pNext: ptr::null(),
+                buffer: command.raw_buffer,
+                offset: command.offset,
+                flags: command.flags,
+            };
+
+            vk.CmdBeginConditionalRenderingEXT(cmd, &infos);
+        }
+
+        Ok(self)
+    }
+}
+
+/// Error that can happen when creating a `CmdBeginConditionalRendering`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmdBeginConditionalRenderingError {
+    /// The `VK_EXT_conditional_rendering` extension is not enabled on the device.
+    ExtensionNotEnabled,
+    /// The `conditional_rendering` feature is not enabled on the device.
+    FeatureNotEnabled,
+    /// The "conditional rendering" usage must be enabled on the buffer.
+    BufferMissingUsage,
+    /// The offset of the predicate is not a multiple of 4.
+    WrongAlignment,
+    /// The predicate is outside of the buffer.
+    OutOfRange,
+}
+
+impl error::Error for CmdBeginConditionalRenderingError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdBeginConditionalRenderingError::ExtensionNotEnabled => {
+                "the device extension `VK_EXT_conditional_rendering` is not enabled"
+            },
+            CmdBeginConditionalRenderingError::FeatureNotEnabled => {
+                "the `conditional_rendering` feature is not enabled"
+            },
+            CmdBeginConditionalRenderingError::BufferMissingUsage => {
+                "the conditional rendering usage must be enabled on the buffer"
+            },
+            CmdBeginConditionalRenderingError::WrongAlignment => {
+                "the offset of the predicate is not a multiple of 4"
+            },
+            CmdBeginConditionalRenderingError::OutOfRange => {
+                "the predicate is outside of the buffer"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CmdBeginConditionalRenderingError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::commands_raw::CmdBeginConditionalRendering;
+    use command_buffer::commands_raw::CmdBeginConditionalRenderingError;
+
+    #[test]
+    fn extension_required() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        match CmdBeginConditionalRendering::new(buffer, 0, false) {
+            Err(CmdBeginConditionalRenderingError::ExtensionNotEnabled) => (),
+            _ => panic!()
+        };
+    }
+}
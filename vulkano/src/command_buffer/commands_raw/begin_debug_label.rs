@@ -0,0 +1,89 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::ffi::CString;
+use std::ptr;
+
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::pool::CommandPool;
+use device::DeviceOwned;
+use VulkanObject;
+use vk;
+
+/// Command that opens a debug label region.
+///
+/// The region is shown by debugging tools such as RenderDoc, and lasts until the next
+/// `CmdEndDebugLabel`. Does nothing if the `ext_debug_utils` extension is not enabled on the
+/// instance.
+#[derive(Debug, Clone)]
+pub struct CmdBeginDebugLabel {
+    // Name of the label.
+    name: CString,
+    // Color of the label.
+    color: [f32; 4],
+}
+
+impl CmdBeginDebugLabel {
+    /// See the documentation of the `begin_debug_label` method.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `name` contains a nul character.
+    ///
+    #[inline]
+    pub fn new(name: &str, color: [f32; 4]) -> CmdBeginDebugLabel {
+        CmdBeginDebugLabel {
+            name: CString::new(name).expect("the name of a debug label can't contain nul characters"),
+            color: color,
+        }
+    }
+
+    /// Returns the name of the label.
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.to_str().unwrap()
+    }
+
+    /// Returns the color of the label.
+    #[inline]
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+}
+
+unsafe impl<'a, P> AddCommand<&'a CmdBeginDebugLabel> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdBeginDebugLabel) -> Result<Self::Out, CommandAddError> {
+        if !self.device().instance().loaded_extensions().ext_debug_utils {
+            return Ok(self);
+        }
+
+        unsafe {
+            let vk = self.device().pointers();
+            let cmd = self.internal_object();
+
+            let infos = vk::DebugUtilsLabelEXT {
+                sType: vk::STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT,
+                pNext: ptr::null(),
+                pLabelName: command.name.as_ptr(),
+                color: command.color,
+            };
+
+            vk.CmdBeginDebugUtilsLabelEXT(cmd, &infos);
+        }
+
+        Ok(self)
+    }
+}
@@ -13,6 +13,7 @@ use std::ptr;
 use smallvec::SmallVec;
 
 use command_buffer::CommandAddError;
+use command_buffer::SubpassContents;
 use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
 use command_buffer::pool::CommandPool;
@@ -28,7 +29,7 @@ use vk;
 /// Command that makes the command buffer enter a render pass.
 pub struct CmdBeginRenderPass<Rp, F> {
     // Inline or secondary.
-    contents: vk::SubpassContents,
+    contents: SubpassContents,
     // The draw area.
     rect: [Range<u32>; 2],
     // The clear values for the clear attachments.
@@ -50,7 +51,7 @@ impl<F> CmdBeginRenderPass<Arc<RenderPassAbstract + Send + Sync>, F>
 {
     /// See the documentation of the `begin_render_pass` method.
     // TODO: allow setting more parameters
-    pub fn new<C>(framebuffer: F, secondary: bool, clear_values: C)
+    pub fn new<C>(framebuffer: F, contents: SubpassContents, clear_values: C)
                   -> CmdBeginRenderPass<Arc<RenderPassAbstract + Send + Sync>, F>
         where F: RenderPassDescClearValues<C>
     {
@@ -96,8 +97,7 @@ impl<F> CmdBeginRenderPass<Arc<RenderPassAbstract + Send + Sync>, F>
                     0 .. framebuffer.dimensions()[1]];
 
         CmdBeginRenderPass {
-            contents: if secondary { vk::SUBPASS_CONTENTS_SECONDARY_COMMAND_BUFFERS }
-                      else { vk::SUBPASS_CONTENTS_INLINE },
+            contents: contents,
             rect: rect,
             clear_values: clear_values,
             raw_render_pass: raw_render_pass,
@@ -115,6 +115,12 @@ impl<Rp, F> CmdBeginRenderPass<Rp, F> {
     pub fn framebuffer(&self) -> &F {
         &self.framebuffer
     }
+
+    /// Returns the contents of the first subpass.
+    #[inline]
+    pub fn contents(&self) -> SubpassContents {
+        self.contents
+    }
 }
 
 unsafe impl<Rp, F> DeviceOwned for CmdBeginRenderPass<Rp, F>
@@ -156,7 +162,7 @@ unsafe impl<'a, P, Rp, F> AddCommand<&'a CmdBeginRenderPass<Rp, F>> for UnsafeCo
                 pClearValues: command.clear_values.as_ptr(),
             };
 
-            vk.CmdBeginRenderPass(cmd, &begin, command.contents);
+            vk.CmdBeginRenderPass(cmd, &begin, command.contents as vk::SubpassContents);
         }
 
         Ok(self)
@@ -9,7 +9,6 @@
 
 use std::error;
 use std::fmt;
-use std::ptr;
 use std::sync::Arc;
 use smallvec::SmallVec;
 
@@ -17,23 +16,25 @@ use command_buffer::CommandAddError;
 use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
 use command_buffer::pool::CommandPool;
+use descriptor::descriptor::DescriptorType;
 use descriptor::descriptor_set::DescriptorSetsCollection;
 use descriptor::pipeline_layout::PipelineLayoutAbstract;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
 use descriptor::pipeline_layout::PipelineLayoutSetsCompatible;
 use device::Device;
 use device::DeviceOwned;
+use pipeline::PipelineBindPoint;
 use VulkanObject;
 use vk;
 
 /// Command that binds descriptor sets to the command buffer.
 pub struct CmdBindDescriptorSets<S, P> {
-    // The raw Vulkan enum representing the kind of pipeline.
-    pipeline_ty: vk::PipelineBindPoint,
-    // The raw pipeline object to bind.
-    raw_pipeline_layout: vk::PipelineLayout,
-    // The raw sets to bind. Array where each element is a tuple of the first set to bind and the
-    // sets to bind.
-    raw_sets: SmallVec<[(u32, SmallVec<[vk::DescriptorSet; 8]>); 4]>,
+    // The slot where the sets are bound.
+    bind_point: PipelineBindPoint,
+    // Actual raw state of the command.
+    state: CmdBindDescriptorSetsHash,
+    // False if the command has been disabled.
+    enabled: bool,
     // The device of the pipeline object, so that we can compare it with the command buffer's
     // device.
     device: Arc<Device>,
@@ -51,20 +52,48 @@ impl<S, P> CmdBindDescriptorSets<S, P>
     /// If `graphics` is true, the sets will be bound to the graphics slot. If false, they will be
     /// bound to the compute slot.
     ///
-    /// Returns an error if the sets are not compatible with the pipeline layout.
+    /// Returns an error if the sets are not compatible with the pipeline layout, if the dynamic
+    /// offsets of the sets don't match their dynamic descriptors, or if a dynamic offset makes a
+    /// buffer descriptor go past the end of its buffer. See
+    /// `DescriptorSetsCollection::dynamic_offsets`. The compatibility of the sets is only checked
+    /// if the recording validation of the device is enabled.
     #[inline]
     pub fn new(graphics: bool, pipeline_layout: P, sets: S)
-               -> Result<CmdBindDescriptorSets<S, P>, CmdBindDescriptorSetsError> 
+               -> Result<CmdBindDescriptorSets<S, P>, CmdBindDescriptorSetsError>
     {
-        if !PipelineLayoutSetsCompatible::is_compatible(&pipeline_layout, &sets) {
-            return Err(CmdBindDescriptorSetsError::IncompatibleSets);
-        }
+        let bind_point = if graphics { PipelineBindPoint::Graphics }
+                         else { PipelineBindPoint::Compute };
+        CmdBindDescriptorSets::with_bind_point(bind_point, pipeline_layout, sets)
+    }
 
+    /// Builds the command that binds the sets to `bind_point`.
+    ///
+    /// A set can be bound at both bind points, as long as it is compatible with the layouts of
+    /// both the graphics and the compute pipelines. The same errors as `new` can be returned.
+    pub fn with_bind_point(bind_point: PipelineBindPoint, pipeline_layout: P, sets: S)
+                           -> Result<CmdBindDescriptorSets<S, P>, CmdBindDescriptorSetsError>
+    {
         let raw_pipeline_layout = pipeline_layout.sys().internal_object();
         let device = pipeline_layout.device().clone();
 
+        if device.recording_validation() &&
+           !PipelineLayoutSetsCompatible::is_compatible(&pipeline_layout, &sets)
+        {
+            return Err(CmdBindDescriptorSetsError::IncompatibleSets);
+        }
+
+        let (uniform_alignment, storage_alignment) = {
+            let limits = device.physical_device().limits();
+            (limits.min_uniform_buffer_offset_alignment() as u32,
+             limits.min_storage_buffer_offset_alignment() as u32)
+        };
+
+        let dynamic_offsets = sets.dynamic_offsets();
+        let mut next_offset = 0;
+
         let raw_sets = {
-            let mut raw_sets: SmallVec<[(u32, SmallVec<[_; 8]>); 4]> = SmallVec::new();
+            let mut raw_sets: SmallVec<[(u32, SmallVec<[_; 8]>, SmallVec<[_; 8]>); 4]> =
+                SmallVec::new();
             let mut add_new = true;
             for set_num in 0 .. sets.num_sets() {
                 let set = match sets.descriptor_set(set_num) {
@@ -74,20 +103,34 @@ impl<S, P> CmdBindDescriptorSets<S, P>
                 
                 if add_new {
                     let mut v = SmallVec::new(); v.push(set);
-                    raw_sets.push((set_num as u32, v));
+                    raw_sets.push((set_num as u32, v, SmallVec::new()));
                     add_new = false;
                 } else {
                     raw_sets.last_mut().unwrap().1.push(set);
                 }
+
+                let offsets = try!(set_dynamic_offsets(&pipeline_layout, &sets, set_num,
+                                                       dynamic_offsets, &mut next_offset,
+                                                       uniform_alignment, storage_alignment));
+                raw_sets.last_mut().unwrap().2.extend(offsets);
             }
             raw_sets
         };
 
+        if next_offset != dynamic_offsets.len() {
+            return Err(CmdBindDescriptorSetsError::WrongDynamicOffsetsCount {
+                expected: next_offset,
+                obtained: dynamic_offsets.len(),
+            });
+        }
+
         Ok(CmdBindDescriptorSets {
-            raw_pipeline_layout: raw_pipeline_layout,
-            raw_sets: raw_sets,
-            pipeline_ty: if graphics { vk::PIPELINE_BIND_POINT_GRAPHICS }
-                         else { vk::PIPELINE_BIND_POINT_COMPUTE },
+            bind_point: bind_point,
+            state: CmdBindDescriptorSetsHash {
+                raw_pipeline_layout: raw_pipeline_layout,
+                raw_sets: raw_sets,
+            },
+            enabled: true,
             device: device,
             sets: sets,
             pipeline_layout: pipeline_layout,
@@ -95,15 +138,142 @@ impl<S, P> CmdBindDescriptorSets<S, P>
     }
 }
 
+// Returns the dynamic offsets of the set number `set_num` of `layout`, taken from `offsets`
+// starting at `*next_offset`. Dynamic offsets are consumed in the order of the bindings of the
+// set, and `*next_offset` is advanced even if `offsets` is too short so that the caller can
+// compare it with the number of offsets that were passed.
+//
+// If `sets` knows the buffer of a dynamic descriptor, also checks that the offset doesn't make
+// the range of the descriptor go past the end of the underlying buffer.
+fn set_dynamic_offsets<L: ?Sized, S: ?Sized>(layout: &L, sets: &S, set_num: usize,
+                                             offsets: &[u32], next_offset: &mut usize,
+                                             uniform_alignment: u32, storage_alignment: u32)
+                                             -> Result<SmallVec<[u32; 8]>,
+                                                       CmdBindDescriptorSetsError>
+    where L: PipelineLayoutDesc, S: DescriptorSetsCollection
+{
+    let mut set_offsets = SmallVec::new();
+
+    for binding in 0 .. layout.num_bindings_in_set(set_num).unwrap_or(0) {
+        let (ty, array_count) = match layout.descriptor(set_num, binding) {
+            Some(desc) => match desc.ty.ty() {
+                Some(ty) if ty.is_dynamic() => (ty, desc.array_count),
+                _ => continue,
+            },
+            None => continue,
+        };
+
+        let alignment = if ty == DescriptorType::UniformBufferDynamic { uniform_alignment }
+                        else { storage_alignment };
+
+        for array_index in 0 .. array_count as usize {
+            if let Some(&offset) = offsets.get(*next_offset) {
+                if offset % alignment != 0 {
+                    return Err(CmdBindDescriptorSetsError::UnalignedDynamicOffset {
+                        index: *next_offset,
+                        offset: offset,
+                        alignment: alignment,
+                    });
+                }
+
+                if let Some(buffer) = sets.buffer(set_num, binding, array_index) {
+                    let inner = buffer.inner();
+                    let max = inner.buffer.size() - inner.offset - buffer.size();
+                    if offset as usize > max {
+                        return Err(CmdBindDescriptorSetsError::DynamicOffsetOutOfRange {
+                            index: *next_offset,
+                            offset: offset,
+                            max: max,
+                        });
+                    }
+                }
+
+                set_offsets.push(offset);
+            }
+
+            *next_offset += 1;
+        }
+    }
+
+    Ok(set_offsets)
+}
+
 impl<S, P> CmdBindDescriptorSets<S, P> {
     /// True if we bind to the graphics pipeline. False if the compute pipeline.
-    // TODO: should be an enum?
     #[inline]
     pub fn is_graphics(&self) -> bool {
-        self.pipeline_ty == vk::PIPELINE_BIND_POINT_GRAPHICS
+        self.bind_point == PipelineBindPoint::Graphics
+    }
+
+    /// Returns the slot where the sets are bound.
+    #[inline]
+    pub fn bind_point(&self) -> PipelineBindPoint {
+        self.bind_point
+    }
+
+    /// Returns false if the command has been disabled, in which case executing it doesn't do
+    /// anything.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns true if the sets bound by this command can be used with a pipeline whose layout is
+    /// `layout`, in other words if for each set that is bound, the layout of the command describes
+    /// all the descriptors that `layout` expects in this set.
+    ///
+    /// The sets that the command doesn't bind are not checked.
+    pub fn is_compatible_with<L: ?Sized>(&self, layout: &L) -> bool
+        where P: PipelineLayoutDesc, L: PipelineLayoutDesc
+    {
+        for &(first_set, ref sets, _) in self.state.raw_sets.iter() {
+            for set_num in first_set as usize .. first_set as usize + sets.len() {
+                for binding in 0 .. layout.num_bindings_in_set(set_num).unwrap_or(0) {
+                    let expected = match layout.descriptor(set_num, binding) {
+                        Some(desc) => desc,
+                        None => continue,
+                    };
+
+                    match self.pipeline_layout.descriptor(set_num, binding) {
+                        Some(ref desc) if desc.is_superset_of(&expected) => (),
+                        _ => return false,
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Returns a hash that represents the command.
+    #[inline]
+    pub fn hash(&self) -> &CmdBindDescriptorSetsHash {
+        &self.state
+    }
+
+    /// This disables the command but keeps it alive. All getters still return the same value, but
+    /// executing the command will not do anything.
+    #[inline]
+    pub fn disabled(mut self) -> CmdBindDescriptorSets<S, P> {
+        self.enabled = false;
+        self
     }
 }
 
+/// A "hash" of the bind descriptor sets command. Can be compared with a previous hash to
+/// determine if two commands bind the same sets, assuming they use the same bind point.
+///
+/// > **Note**: This is not *actually* a hash, because there's no collision. If two objects are
+/// > equal, then the commands are always identical.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CmdBindDescriptorSetsHash {
+    // The raw pipeline layout.
+    raw_pipeline_layout: vk::PipelineLayout,
+    // The raw sets to bind. Array where each element is a tuple of the first set to bind, the
+    // sets to bind, and the dynamic offsets of these sets.
+    raw_sets: SmallVec<[(u32, SmallVec<[vk::DescriptorSet; 8]>, SmallVec<[u32; 8]>); 4]>,
+}
+
 unsafe impl<S, Pl> DeviceOwned for CmdBindDescriptorSets<S, Pl>
     where Pl: DeviceOwned
 {
@@ -120,14 +290,19 @@ unsafe impl<'a, P, Pl, S> AddCommand<&'a CmdBindDescriptorSets<S, Pl>> for Unsaf
 
     #[inline]
     fn add(self, command: &'a CmdBindDescriptorSets<S, Pl>) -> Result<Self::Out, CommandAddError> {
+        if !command.is_enabled() {
+            return Ok(self);
+        }
+
         unsafe {
             let vk = self.device().pointers();
             let cmd = self.internal_object();
 
-            for &(first_set, ref sets) in command.raw_sets.iter() {
-                vk.CmdBindDescriptorSets(cmd, command.pipeline_ty, command.raw_pipeline_layout,
+            for &(first_set, ref sets, ref dynamic_offsets) in command.state.raw_sets.iter() {
+                vk.CmdBindDescriptorSets(cmd, command.bind_point as u32,
+                                         command.state.raw_pipeline_layout,
                                          first_set, sets.len() as u32, sets.as_ptr(),
-                                         0, ptr::null());        // TODO: dynamic offset not supported
+                                         dynamic_offsets.len() as u32, dynamic_offsets.as_ptr());
             }
         }
 
@@ -141,6 +316,36 @@ pub enum CmdBindDescriptorSetsError {
     /// The sets are not compatible with the pipeline layout.
     // TODO: inner error
     IncompatibleSets,
+
+    /// The number of dynamic offsets doesn't match the number of dynamic descriptors in the sets.
+    WrongDynamicOffsetsCount {
+        /// Number of array elements of dynamic descriptors in the sets.
+        expected: usize,
+        /// Number of dynamic offsets that were passed.
+        obtained: usize,
+    },
+
+    /// A dynamic offset is not a multiple of the `min_uniform_buffer_offset_alignment` or
+    /// `min_storage_buffer_offset_alignment` limit.
+    UnalignedDynamicOffset {
+        /// Index of the offset in the list of dynamic offsets.
+        index: usize,
+        /// Value of the offset.
+        offset: u32,
+        /// Required alignment.
+        alignment: u32,
+    },
+
+    /// A dynamic offset makes the range of a buffer descriptor go past the end of the
+    /// underlying buffer.
+    DynamicOffsetOutOfRange {
+        /// Index of the offset in the list of dynamic offsets.
+        index: usize,
+        /// Value of the offset.
+        offset: u32,
+        /// Highest offset that can be applied to the descriptor.
+        max: usize,
+    },
 }
 
 impl error::Error for CmdBindDescriptorSetsError {
@@ -150,6 +355,16 @@ impl error::Error for CmdBindDescriptorSetsError {
             CmdBindDescriptorSetsError::IncompatibleSets => {
                 "the sets are not compatible with the pipeline layout"
             },
+            CmdBindDescriptorSetsError::WrongDynamicOffsetsCount { .. } => {
+                "the number of dynamic offsets doesn't match the number of dynamic descriptors \
+                 in the sets"
+            },
+            CmdBindDescriptorSetsError::UnalignedDynamicOffset { .. } => {
+                "a dynamic offset is not a multiple of the required alignment"
+            },
+            CmdBindDescriptorSetsError::DynamicOffsetOutOfRange { .. } => {
+                "a dynamic offset makes a descriptor go past the end of its buffer"
+            },
         }
     }
 }
@@ -160,3 +375,211 @@ impl fmt::Display for CmdBindDescriptorSetsError {
         write!(fmt, "{}", error::Error::description(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::commands_raw::CmdBindDescriptorSets;
+    use command_buffer::commands_raw::CmdBindDescriptorSetsError;
+    use descriptor::descriptor::DescriptorBufferContentDesc;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorSetsCollection;
+    use descriptor::descriptor_set::DynamicOffsets;
+    use descriptor::descriptor_set::SimpleDescriptorSetBufferExt;
+    use descriptor::descriptor_set::SimpleDescriptorSetBuilder;
+    use descriptor::pipeline_layout::EmptyPipelineDesc;
+    use descriptor::pipeline_layout::PipelineLayout;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescNames;
+    use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+    use super::set_dynamic_offsets;
+
+    // Layout with a single set made of a dynamic uniform buffer of two array elements, followed
+    // by a dynamic storage buffer.
+    struct DynamicBuffersLayout;
+
+    unsafe impl PipelineLayoutDesc for DynamicBuffersLayout {
+        fn num_sets(&self) -> usize { 1 }
+
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            if set == 0 { Some(2) } else { None }
+        }
+
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            if set != 0 || binding >= 2 {
+                return None;
+            }
+
+            Some(DescriptorDesc {
+                ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                    dynamic: Some(true),
+                    storage: binding == 1,
+                    content: DescriptorBufferContentDesc::F32,
+                }),
+                array_count: if binding == 0 { 2 } else { 1 },
+                stages: ShaderStages::all(),
+                readonly: true,
+            })
+        }
+
+        fn num_push_constants_ranges(&self) -> usize { 0 }
+
+        fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> { None }
+    }
+
+    // Layout with a single buffer named `buf` at (0, 0). The buffer is a storage buffer if
+    // `storage` is true, and a uniform buffer otherwise.
+    struct BufferLayout {
+        storage: bool,
+        dynamic: bool,
+    }
+
+    unsafe impl PipelineLayoutDesc for BufferLayout {
+        fn num_sets(&self) -> usize { 1 }
+
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            if set == 0 { Some(1) } else { None }
+        }
+
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            if (set, binding) != (0, 0) {
+                return None;
+            }
+
+            Some(DescriptorDesc {
+                ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                    dynamic: Some(self.dynamic),
+                    storage: self.storage,
+                    content: DescriptorBufferContentDesc::F32,
+                }),
+                array_count: 1,
+                stages: ShaderStages::all(),
+                readonly: true,
+            })
+        }
+
+        fn num_push_constants_ranges(&self) -> usize { 0 }
+
+        fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> { None }
+    }
+
+    unsafe impl PipelineLayoutDescNames for BufferLayout {
+        fn descriptor_by_name(&self, name: &str) -> Option<(usize, usize)> {
+            if name == "buf" { Some((0, 0)) } else { None }
+        }
+    }
+
+    #[test]
+    fn dynamic_offsets_in_order() {
+        let mut next = 0;
+        let offsets = set_dynamic_offsets(&DynamicBuffersLayout, &(), 0, &[0, 256, 32],
+                                          &mut next, 256, 16).unwrap();
+        assert_eq!(&offsets[..], &[0, 256, 32]);
+        assert_eq!(next, 3);
+    }
+
+    #[test]
+    fn missing_dynamic_offsets_counted() {
+        let mut next = 0;
+        let offsets = set_dynamic_offsets(&DynamicBuffersLayout, &(), 0, &[0], &mut next, 256,
+                                          16).unwrap();
+        assert_eq!(&offsets[..], &[0]);
+        assert_eq!(next, 3);
+    }
+
+    #[test]
+    fn unaligned_dynamic_offset() {
+        let mut next = 0;
+        match set_dynamic_offsets(&DynamicBuffersLayout, &(), 0, &[0, 128, 32], &mut next, 256,
+                                  16)
+        {
+            Err(CmdBindDescriptorSetsError::UnalignedDynamicOffset { index: 1, offset: 128,
+                                                                     alignment: 256 }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn too_many_dynamic_offsets() {
+        let (device, _) = gfx_dev_and_queue!();
+        let layout = EmptyPipelineDesc.build(device).unwrap();
+
+        match CmdBindDescriptorSets::new(true, layout, DynamicOffsets::new((), Some(0))) {
+            Err(CmdBindDescriptorSetsError::WrongDynamicOffsetsCount { expected: 0,
+                                                                       obtained: 1 }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn compatible_with_bound_layout() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let layout = PipelineLayout::new(device.clone(), BufferLayout { storage: true,
+                                                                       dynamic: false });
+        let layout = Arc::new(layout.unwrap());
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+        let set = buffer.add_me(SimpleDescriptorSetBuilder::new(layout.clone(), 0), "buf")
+            .build().unwrap();
+
+        let command = CmdBindDescriptorSets::new(false, layout, set).unwrap();
+        assert!(command.is_compatible_with(&BufferLayout { storage: true, dynamic: false }));
+        assert!(command.is_compatible_with(&EmptyPipelineDesc));
+        assert!(!command.is_compatible_with(&BufferLayout { storage: false, dynamic: false }));
+    }
+
+    #[test]
+    fn unbound_sets_not_checked() {
+        let (device, _) = gfx_dev_and_queue!();
+        let layout = PipelineLayout::new(device, BufferLayout { storage: true, dynamic: false });
+        let layout = layout.unwrap();
+
+        let command = CmdBindDescriptorSets::new(false, layout, ()).unwrap();
+        assert!(command.is_compatible_with(&BufferLayout { storage: false, dynamic: false }));
+    }
+
+    #[test]
+    fn dynamic_offset_out_of_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let alignment = device.physical_device().limits().min_storage_buffer_offset_alignment();
+        let layout = PipelineLayout::new(device.clone(), BufferLayout { storage: true,
+                                                                       dynamic: true });
+        let layout = Arc::new(layout.unwrap());
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+        let set = buffer.add_me(SimpleDescriptorSetBuilder::new(layout.clone(), 0), "buf")
+            .build().unwrap();
+        let set = Arc::new(set);
+
+        assert!(CmdBindDescriptorSets::new(false, layout.clone(),
+                                           DynamicOffsets::new(set.clone(), Some(0))).is_ok());
+
+        // The buffer is entirely covered by the descriptor, so any other offset is too large.
+        let sets = DynamicOffsets::new(set, Some(alignment as u32));
+        match CmdBindDescriptorSets::new(false, layout, sets) {
+            Err(CmdBindDescriptorSetsError::DynamicOffsetOutOfRange { index: 0, max: 0, .. }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn dynamic_offset_shifts_buffer_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let layout = PipelineLayout::new(device.clone(), BufferLayout { storage: true,
+                                                                       dynamic: true });
+        let layout = Arc::new(layout.unwrap());
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+        let set = buffer.add_me(SimpleDescriptorSetBuilder::new(layout, 0), "buf")
+            .build().unwrap();
+
+        let sets = DynamicOffsets::new(set, Some(256));
+        let ranges = sets.buffers_ranges().map(|(_, offset)| offset).collect::<Vec<_>>();
+        assert_eq!(ranges, vec![256]);
+    }
+}
@@ -7,6 +7,8 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
 use std::sync::Arc;
 
 use buffer::BufferAccess;
@@ -18,6 +20,7 @@ use command_buffer::pool::CommandPool;
 use device::Device;
 use device::DeviceOwned;
 use pipeline::input_assembly::Index;
+use pipeline::input_assembly::IndexType;
 use VulkanObject;
 use vk;
 
@@ -40,29 +43,47 @@ impl<B, I> CmdBindIndexBuffer<B>
           I: Index + 'static
 {
     /// Builds the command.
+    ///
+    /// Returns an error if the index type isn't supported by the device, or if the buffer
+    /// doesn't have the index buffer usage.
     #[inline]
-    pub fn new(buffer: B) -> CmdBindIndexBuffer<B> {
+    pub fn new(buffer: B) -> Result<CmdBindIndexBuffer<B>, CmdBindIndexBufferError> {
         let device;
         let raw_buffer;
         let offset;
 
+        let index_type = I::ty();
+
         {
             let inner = buffer.inner();
             debug_assert!(inner.offset < inner.buffer.size());
-            // TODO: check > The sum of offset and the address of the range of VkDeviceMemory object that is backing buffer, must be a multiple of the type indicated by indexType
-            assert!(inner.buffer.usage_index_buffer());     // TODO: error
+            if !inner.buffer.usage_index_buffer() {
+                return Err(CmdBindIndexBufferError::BufferMissingUsage);
+            }
+            if inner.offset % index_type.size() != 0 {
+                return Err(CmdBindIndexBufferError::WrongAlignment);
+            }
             device = inner.buffer.device().clone();
             raw_buffer = inner.buffer.internal_object();
             offset = inner.offset as vk::DeviceSize;
         }
 
-        CmdBindIndexBuffer {
+        if index_type == IndexType::U8 {
+            if !device.loaded_extensions().ext_index_type_uint8 {
+                return Err(CmdBindIndexBufferError::Uint8ExtensionNotEnabled);
+            }
+            if !device.enabled_features().index_type_uint8 {
+                return Err(CmdBindIndexBufferError::Uint8FeatureNotEnabled);
+            }
+        }
+
+        Ok(CmdBindIndexBuffer {
             raw_buffer: raw_buffer,
             offset: offset,
-            index_type: I::ty() as vk::IndexType,
+            index_type: index_type as vk::IndexType,
             device: device,
             buffer: buffer,
-        }
+        })
     }
 }
 
@@ -99,3 +120,92 @@ unsafe impl<'a, P, B> AddCommand<&'a CmdBindIndexBuffer<B>> for UnsafeCommandBuf
         Ok(self)
     }
 }
+
+/// Error that can happen when creating a `CmdBindIndexBuffer`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmdBindIndexBufferError {
+    /// The "index buffer" usage must be enabled on the buffer.
+    BufferMissingUsage,
+    /// The offset of the buffer is not a multiple of the size of an index.
+    WrongAlignment,
+    /// The `VK_EXT_index_type_uint8` extension must be enabled to use `u8` indices.
+    Uint8ExtensionNotEnabled,
+    /// The `index_type_uint8` feature must be enabled to use `u8` indices.
+    Uint8FeatureNotEnabled,
+}
+
+impl error::Error for CmdBindIndexBufferError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdBindIndexBufferError::BufferMissingUsage => {
+                "the index buffer usage must be enabled on the buffer"
+            },
+            CmdBindIndexBufferError::WrongAlignment => {
+                "the offset of the buffer is not a multiple of the size of an index"
+            },
+            CmdBindIndexBufferError::Uint8ExtensionNotEnabled => {
+                "the device extension `VK_EXT_index_type_uint8` is not enabled"
+            },
+            CmdBindIndexBufferError::Uint8FeatureNotEnabled => {
+                "the `index_type_uint8` feature is not enabled"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CmdBindIndexBufferError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::commands_raw::CmdBindIndexBuffer;
+    use command_buffer::commands_raw::CmdBindIndexBufferError;
+
+    #[test]
+    fn bind_u16_u32() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let u16_buffer = CpuAccessibleBuffer::from_iter(device.clone(),
+                                                        BufferUsage::index_buffer(),
+                                                        Some(queue.family()), 0 .. 6u16).unwrap();
+        assert!(CmdBindIndexBuffer::new(u16_buffer).is_ok());
+
+        let u32_buffer = CpuAccessibleBuffer::from_iter(device.clone(),
+                                                        BufferUsage::index_buffer(),
+                                                        Some(queue.family()), 0 .. 6u32).unwrap();
+        assert!(CmdBindIndexBuffer::new(u32_buffer).is_ok());
+    }
+
+    #[test]
+    fn u8_requires_feature() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::index_buffer(),
+                                                    Some(queue.family()), 0 .. 6u8).unwrap();
+
+        match CmdBindIndexBuffer::new(buffer) {
+            Err(CmdBindIndexBufferError::Uint8ExtensionNotEnabled) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn missing_usage() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::vertex_buffer(),
+                                                    Some(queue.family()), 0 .. 6u16).unwrap();
+
+        match CmdBindIndexBuffer::new(buffer) {
+            Err(CmdBindIndexBufferError::BufferMissingUsage) => (),
+            _ => panic!()
+        };
+    }
+}
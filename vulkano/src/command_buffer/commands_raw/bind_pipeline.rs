@@ -15,7 +15,11 @@ use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
 use command_buffer::pool::CommandPool;
 use device::Device;
+use descriptor::pipeline_layout::PipelineLayoutAbstract;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
 use device::DeviceOwned;
+use framebuffer::RenderPassAbstract;
+use framebuffer::RenderPassCompatible;
 use pipeline::ComputePipelineAbstract;
 use pipeline::GraphicsPipelineAbstract;
 use VulkanObject;
@@ -32,6 +36,13 @@ pub struct CmdBindPipeline<P> {
     device: Arc<Device>,
     // The pipeline object to bind. Unused, but we need to keep it alive.
     pipeline: P,
+    // Function that returns the layout of the pipeline.
+    layout: fn(&P) -> &PipelineLayoutDesc,
+    // For graphics pipelines, function that checks whether the pipeline can be used in a subpass.
+    subpass_check: Option<fn(&P, &RenderPassAbstract, u32) -> bool>,
+    // For graphics pipelines, function that checks whether the static viewports and scissors of
+    // the pipeline fit in a framebuffer.
+    framebuffer_check: Option<fn(&P, [u32; 2]) -> Result<(), CommandAddError>>,
 }
 
 impl<P> CmdBindPipeline<P> {
@@ -50,6 +61,9 @@ impl<P> CmdBindPipeline<P> {
             pipeline_ty: vk::PIPELINE_BIND_POINT_COMPUTE,
             device: device,
             pipeline: pipeline,
+            layout: pipeline_layout::<P>,
+            subpass_check: None,
+            framebuffer_check: None,
         }
     }
 
@@ -68,6 +82,9 @@ impl<P> CmdBindPipeline<P> {
             pipeline_ty: vk::PIPELINE_BIND_POINT_GRAPHICS,
             device: device,
             pipeline: pipeline,
+            layout: pipeline_layout::<P>,
+            subpass_check: Some(graphics_subpass_check::<P>),
+            framebuffer_check: Some(graphics_framebuffer_check::<P>),
         }
     }
 
@@ -97,6 +114,90 @@ impl<P> CmdBindPipeline<P> {
     pub fn sys(&self) -> CmdBindPipelineSys {
         CmdBindPipelineSys(self.raw_pipeline, PhantomData)
     }
+
+    /// Returns the layout of the pipeline that will be bound.
+    #[inline]
+    pub fn pipeline_layout(&self) -> &PipelineLayoutDesc {
+        (self.layout)(&self.pipeline)
+    }
+
+    /// Returns true if the pipeline can be used in the subpass `subpass` of `render_pass`, in
+    /// other words if it was created for this subpass index and a render pass compatible with
+    /// `render_pass`.
+    ///
+    /// Always returns true for compute pipelines, and for graphics pipelines that don't know the
+    /// index of their subpass.
+    #[inline]
+    pub fn is_compatible_with_subpass(&self, render_pass: &RenderPassAbstract, subpass: u32)
+                                      -> bool
+    {
+        match self.subpass_check {
+            Some(check) => check(&self.pipeline, render_pass, subpass),
+            None => true,
+        }
+    }
+
+    /// Checks that the viewports and scissors of the pipeline that are not dynamic fit in a
+    /// framebuffer of the given width and height.
+    ///
+    /// Always succeeds for compute pipelines.
+    #[inline]
+    pub fn check_framebuffer_dimensions(&self, dimensions: [u32; 2])
+                                        -> Result<(), CommandAddError>
+    {
+        match self.framebuffer_check {
+            Some(check) => check(&self.pipeline, dimensions),
+            None => Ok(()),
+        }
+    }
+}
+
+// Returns the layout of a pipeline.
+fn pipeline_layout<P>(pipeline: &P) -> &PipelineLayoutDesc
+    where P: PipelineLayoutAbstract
+{
+    pipeline
+}
+
+// Checks whether a graphics pipeline can be used in a subpass of a render pass.
+fn graphics_subpass_check<P>(pipeline: &P, render_pass: &RenderPassAbstract, subpass: u32) -> bool
+    where P: GraphicsPipelineAbstract
+{
+    match pipeline.subpass_index() {
+        Some(index) if index == subpass => (),
+        Some(_) => return false,
+        None => return true,
+    }
+
+    // Pipelines are usually created with the same render pass object as the one that is used to
+    // draw, in which case there is no need to compare the descriptions.
+    if RenderPassAbstract::inner(pipeline).internal_object() ==
+       render_pass.inner().internal_object()
+    {
+        return true;
+    }
+
+    RenderPassCompatible::is_compatible_with(pipeline, render_pass)
+}
+
+// Checks whether the static viewports and scissors of a graphics pipeline fit in a framebuffer.
+fn graphics_framebuffer_check<P>(pipeline: &P, dimensions: [u32; 2])
+                                 -> Result<(), CommandAddError>
+    where P: GraphicsPipelineAbstract
+{
+    for (index, viewport) in pipeline.static_viewports().iter().enumerate() {
+        if !viewport.fits_in(dimensions) {
+            return Err(CommandAddError::ViewportOutOfRange { index: index as u32 });
+        }
+    }
+
+    for (index, scissor) in pipeline.static_scissors().iter().enumerate() {
+        if !scissor.starts_in(dimensions) {
+            return Err(CommandAddError::ScissorOutOfRange { index: index as u32 });
+        }
+    }
+
+    Ok(())
 }
 
 unsafe impl<'a, P, Pl> AddCommand<&'a CmdBindPipeline<Pl>> for UnsafeCommandBufferBuilder<P>
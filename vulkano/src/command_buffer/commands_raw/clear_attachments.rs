@@ -7,6 +7,8 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
 use smallvec::SmallVec;
 
 use command_buffer::CommandAddError;
@@ -14,10 +16,14 @@ use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
 use command_buffer::pool::CommandPool;
 use device::DeviceOwned;
+use format::ClearValue;
 use VulkanObject;
 use vk;
 
 /// Command that clears framebuffer attachments of the current render pass.
+///
+/// Contrary to the clear values passed when beginning a render pass, this command can clear only
+/// some regions and some layers of the attachments, and can be used in the middle of a subpass.
 pub struct CmdClearAttachments {
     // The attachments to clear.
     attachments: SmallVec<[vk::ClearAttachment; 8]>,
@@ -25,7 +31,143 @@ pub struct CmdClearAttachments {
     rects: SmallVec<[vk::ClearRect; 4]>,
 }
 
-// TODO: add constructor
+/// Attachment of the current subpass to clear with `CmdClearAttachments`.
+#[derive(Debug, Copy, Clone)]
+pub enum ClearAttachment {
+    /// Clears the color attachment whose index is given, within the color attachments of the
+    /// current subpass. The value must be `Float`, `Int` or `Uint`.
+    Color(u32, ClearValue),
+    /// Clears the depth aspect of the depth-stencil attachment of the current subpass.
+    Depth(f32),
+    /// Clears the stencil aspect of the depth-stencil attachment of the current subpass.
+    Stencil(u32),
+    /// Clears both aspects of the depth-stencil attachment of the current subpass.
+    DepthStencil((f32, u32)),
+}
+
+/// Region of the attachments to clear with `CmdClearAttachments`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClearRect {
+    /// Coordinates in pixels of the top-left hand corner of the rectangle.
+    pub offset: [u32; 2],
+    /// Width and height in pixels of the rectangle.
+    pub extent: [u32; 2],
+    /// First layer of the framebuffer to clear.
+    pub base_array_layer: u32,
+    /// Number of layers to clear.
+    pub layer_count: u32,
+}
+
+impl CmdClearAttachments {
+    /// Builds the command. Each attachment is cleared in each of the rectangles.
+    ///
+    /// Returns an error if one of the lists is empty, if a rectangle is empty, or if a color
+    /// attachment is given a value that isn't a color. The attachments and the rectangles are
+    /// compared with the current subpass and framebuffer when the command is added to a command
+    /// buffer.
+    pub fn new<A, R>(attachments: A, rects: R)
+                     -> Result<CmdClearAttachments, CmdClearAttachmentsError>
+        where A: IntoIterator<Item = ClearAttachment>,
+              R: IntoIterator<Item = ClearRect>
+    {
+        let attachments = attachments.into_iter().map(|attachment| {
+            let (aspect, color, value) = match attachment {
+                ClearAttachment::Color(num, value) => {
+                    let value = match value {
+                        ClearValue::Float(val) => vk::ClearColorValue::float32(val),
+                        ClearValue::Int(val) => vk::ClearColorValue::int32(val),
+                        ClearValue::Uint(val) => vk::ClearColorValue::uint32(val),
+                        _ => return Err(CmdClearAttachmentsError::NotColorValue),
+                    };
+
+                    (vk::IMAGE_ASPECT_COLOR_BIT, num, vk::ClearValue::color(value))
+                },
+                ClearAttachment::Depth(depth) => {
+                    (vk::IMAGE_ASPECT_DEPTH_BIT, 0, depth_stencil_value(depth, 0))
+                },
+                ClearAttachment::Stencil(stencil) => {
+                    (vk::IMAGE_ASPECT_STENCIL_BIT, 0, depth_stencil_value(0.0, stencil))
+                },
+                ClearAttachment::DepthStencil((depth, stencil)) => {
+                    (vk::IMAGE_ASPECT_DEPTH_BIT | vk::IMAGE_ASPECT_STENCIL_BIT, 0,
+                     depth_stencil_value(depth, stencil))
+                },
+            };
+
+            Ok(vk::ClearAttachment {
+                aspectMask: aspect,
+                colorAttachment: color,
+                clearValue: value,
+            })
+        }).collect::<Result<SmallVec<[_; 8]>, _>>()?;
+
+        let rects = rects.into_iter().map(|rect| {
+            if rect.extent[0] == 0 || rect.extent[1] == 0 || rect.layer_count == 0 {
+                return Err(CmdClearAttachmentsError::EmptyRect);
+            }
+
+            Ok(vk::ClearRect {
+                rect: vk::Rect2D {
+                    offset: vk::Offset2D { x: rect.offset[0] as i32, y: rect.offset[1] as i32 },
+                    extent: vk::Extent2D { width: rect.extent[0], height: rect.extent[1] },
+                },
+                baseArrayLayer: rect.base_array_layer,
+                layerCount: rect.layer_count,
+            })
+        }).collect::<Result<SmallVec<[_; 4]>, _>>()?;
+
+        if attachments.is_empty() || rects.is_empty() {
+            return Err(CmdClearAttachmentsError::NothingToClear);
+        }
+
+        Ok(CmdClearAttachments {
+            attachments: attachments,
+            rects: rects,
+        })
+    }
+
+    /// Returns the indices of the color attachments to clear, within the color attachments of
+    /// the subpass.
+    #[inline]
+    pub fn color_attachments<'a>(&'a self) -> Box<Iterator<Item = u32> + 'a> {
+        Box::new(self.attachments.iter()
+                     .filter(|a| (a.aspectMask & vk::IMAGE_ASPECT_COLOR_BIT) != 0)
+                     .map(|a| a.colorAttachment))
+    }
+
+    /// Returns true if the depth aspect of the depth-stencil attachment is cleared.
+    #[inline]
+    pub fn clears_depth(&self) -> bool {
+        self.attachments.iter().any(|a| (a.aspectMask & vk::IMAGE_ASPECT_DEPTH_BIT) != 0)
+    }
+
+    /// Returns true if the stencil aspect of the depth-stencil attachment is cleared.
+    #[inline]
+    pub fn clears_stencil(&self) -> bool {
+        self.attachments.iter().any(|a| (a.aspectMask & vk::IMAGE_ASPECT_STENCIL_BIT) != 0)
+    }
+
+    /// Returns the rectangles to clear.
+    #[inline]
+    pub fn rects<'a>(&'a self) -> Box<Iterator<Item = ClearRect> + 'a> {
+        Box::new(self.rects.iter().map(|r| {
+            ClearRect {
+                offset: [r.rect.offset.x as u32, r.rect.offset.y as u32],
+                extent: [r.rect.extent.width, r.rect.extent.height],
+                base_array_layer: r.baseArrayLayer,
+                layer_count: r.layerCount,
+            }
+        }))
+    }
+}
+
+#[inline]
+fn depth_stencil_value(depth: f32, stencil: u32) -> vk::ClearValue {
+    vk::ClearValue::depth_stencil(vk::ClearDepthStencilValue {
+        depth: depth,
+        stencil: stencil,
+    })
+}
 
 unsafe impl<'a, P> AddCommand<&'a CmdClearAttachments> for UnsafeCommandBufferBuilder<P>
     where P: CommandPool
@@ -46,3 +188,92 @@ unsafe impl<'a, P> AddCommand<&'a CmdClearAttachments> for UnsafeCommandBufferBu
         Ok(self)
     }
 }
+
+/// Error that can happen when creating a `CmdClearAttachments`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmdClearAttachmentsError {
+    /// The list of attachments or the list of rectangles is empty.
+    NothingToClear,
+    /// A rectangle has a width, a height or a number of layers of 0.
+    EmptyRect,
+    /// A color attachment was given a clear value that isn't a color.
+    NotColorValue,
+}
+
+impl error::Error for CmdClearAttachmentsError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdClearAttachmentsError::NothingToClear => {
+                "the list of attachments or the list of rectangles is empty"
+            },
+            CmdClearAttachmentsError::EmptyRect => {
+                "a rectangle has a width, a height or a number of layers of 0"
+            },
+            CmdClearAttachmentsError::NotColorValue => {
+                "a color attachment was given a clear value that isn't a color"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CmdClearAttachmentsError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::commands_raw::ClearAttachment;
+    use command_buffer::commands_raw::ClearRect;
+    use command_buffer::commands_raw::CmdClearAttachments;
+    use command_buffer::commands_raw::CmdClearAttachmentsError;
+    use format::ClearValue;
+
+    const RECT: ClearRect = ClearRect {
+        offset: [0, 0],
+        extent: [16, 16],
+        base_array_layer: 1,
+        layer_count: 1,
+    };
+
+    #[test]
+    fn basic() {
+        let attachments = vec![
+            ClearAttachment::Color(1, ClearValue::Float([0.0, 0.0, 1.0, 1.0])),
+            ClearAttachment::Depth(1.0),
+        ];
+
+        let cmd = CmdClearAttachments::new(attachments, Some(RECT)).unwrap();
+        assert_eq!(cmd.color_attachments().collect::<Vec<_>>(), vec![1]);
+        assert!(cmd.clears_depth());
+        assert!(!cmd.clears_stencil());
+        assert_eq!(cmd.rects().collect::<Vec<_>>(), vec![RECT]);
+    }
+
+    #[test]
+    fn not_color_value() {
+        let attachments = Some(ClearAttachment::Color(0, ClearValue::Depth(1.0)));
+        match CmdClearAttachments::new(attachments, Some(RECT)) {
+            Err(CmdClearAttachmentsError::NotColorValue) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let attachments = Some(ClearAttachment::Stencil(0));
+        match CmdClearAttachments::new(attachments, None) {
+            Err(CmdClearAttachmentsError::NothingToClear) => (),
+            _ => panic!()
+        }
+
+        let rect = ClearRect { layer_count: 0, .. RECT };
+        match CmdClearAttachments::new(attachments, Some(rect)) {
+            Err(CmdClearAttachmentsError::EmptyRect) => (),
+            _ => panic!()
+        }
+    }
+}
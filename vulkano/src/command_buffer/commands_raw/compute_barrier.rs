@@ -0,0 +1,327 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use smallvec::SmallVec;
+
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::commands_raw::CmdPipelineBarrier;
+use command_buffer::pool::CommandPool;
+use descriptor::descriptor::DescriptorType;
+use descriptor::descriptor_set::DescriptorSetsCollection;
+use sync::AccessFlagBits;
+use sync::PipelineStages;
+
+/// Command that adds the barrier required between two compute dispatches, by looking at the
+/// descriptor sets they use.
+///
+/// Every buffer or image of `next` that is also used by `previous` receives a memory barrier
+/// with the `compute_shader` stage on both sides. If `previous` contains a writable storage
+/// descriptor, the writes are made visible to the `shader_read` accesses of the next dispatch.
+/// If only `next` writes, the barrier is a pure execution dependency that prevents the next
+/// dispatch from overwriting data that the previous one is still reading.
+///
+/// Buffers are compared with `conflicts_buffer`, which means that two dispatches that access
+/// disjoint ranges of the same buffer, for example two slices, don't get a barrier between them.
+/// The ranges take the dynamic offsets of the sets into account, see
+/// `DescriptorSetsCollection::buffers_ranges`.
+///
+/// > **Note**: The resources are compared as a whole, regardless of which binding they are
+/// > attached to. A barrier may therefore be emitted for a resource that is only read by both
+/// > dispatches, if they also share a written resource.
+pub struct CmdComputeBarrier<P, N> {
+    // Descriptor sets used by the previous dispatch.
+    previous: P,
+    // Descriptor sets used by the next dispatch.
+    next: N,
+    // Indices within `next.buffers_ranges()` of the buffers that need a barrier.
+    buffers: SmallVec<[usize; 4]>,
+    // Indices within `next.images_list()` of the images that need a barrier.
+    images: SmallVec<[usize; 4]>,
+    // Accesses on both sides of the barriers.
+    src_access: AccessFlagBits,
+    dst_access: AccessFlagBits,
+}
+
+impl<P, N> CmdComputeBarrier<P, N>
+    where P: DescriptorSetsCollection, N: DescriptorSetsCollection
+{
+    /// Builds the barrier between a dispatch that uses `previous` and a dispatch that uses
+    /// `next`.
+    pub fn new(previous: P, next: N) -> CmdComputeBarrier<P, N> {
+        let previous_writes = writes_storage(&previous);
+        let next_writes = writes_storage(&next);
+
+        let (buffers, images) = if previous_writes || next_writes {
+            let buffers = next.buffers_ranges().enumerate().filter(|&(_, (buf, offset))| {
+                previous.buffers_ranges().any(|(prev, prev_offset)| {
+                    buf.conflicts_buffer(offset, buf.size(), prev, prev_offset, prev.size())
+                })
+            }).map(|(num, _)| num).collect();
+
+            let images = next.images_list().enumerate().filter(|&(_, img)| {
+                previous.images_list().any(|prev| img.conflicts_image_all(prev))
+            }).map(|(num, _)| num).collect();
+
+            (buffers, images)
+        } else {
+            (SmallVec::new(), SmallVec::new())
+        };
+
+        let (src_access, dst_access) = barrier_access(previous_writes, next_writes);
+
+        CmdComputeBarrier {
+            previous: previous,
+            next: next,
+            buffers: buffers,
+            images: images,
+            src_access: src_access,
+            dst_access: dst_access,
+        }
+    }
+}
+
+impl<P, N> CmdComputeBarrier<P, N> {
+    /// Returns the descriptor sets used by the previous dispatch.
+    #[inline]
+    pub fn previous(&self) -> &P {
+        &self.previous
+    }
+
+    /// Returns the descriptor sets used by the next dispatch.
+    #[inline]
+    pub fn next(&self) -> &N {
+        &self.next
+    }
+
+    /// Returns true if no barrier is needed between the two dispatches.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty() && self.images.is_empty()
+    }
+}
+
+unsafe impl<'a, Pool, P, N> AddCommand<&'a CmdComputeBarrier<P, N>> for UnsafeCommandBufferBuilder<Pool>
+    where Pool: CommandPool, N: DescriptorSetsCollection
+{
+    type Out = UnsafeCommandBufferBuilder<Pool>;
+
+    #[inline]
+    fn add(self, command: &'a CmdComputeBarrier<P, N>) -> Result<Self::Out, CommandAddError> {
+        if command.is_empty() {
+            return Ok(self);
+        }
+
+        let stages = PipelineStages { compute_shader: true, .. PipelineStages::none() };
+        let mut barrier = CmdPipelineBarrier::new();
+
+        for (num, (buffer, offset)) in command.next.buffers_ranges().enumerate() {
+            if !command.buffers.contains(&num) {
+                continue;
+            }
+
+            unsafe {
+                barrier.add_buffer_memory_barrier(buffer, stages, command.src_access, stages,
+                                                  command.dst_access, false, None, offset,
+                                                  buffer.size());
+            }
+        }
+
+        for (num, image) in command.next.images_list().enumerate() {
+            if !command.images.contains(&num) {
+                continue;
+            }
+
+            let layout = image.initial_layout_requirement();
+            unsafe {
+                barrier.add_image_memory_barrier(image, 0 .. image.mipmap_levels(),
+                                                 0 .. image.dimensions().array_layers(), stages,
+                                                 command.src_access, stages, command.dst_access,
+                                                 false, None, layout, layout);
+            }
+        }
+
+        AddCommand::add(self, &barrier)
+    }
+}
+
+// Returns true if one of the descriptors of `sets` is a storage descriptor that the shader
+// writes to.
+fn writes_storage<S>(sets: &S) -> bool
+    where S: DescriptorSetsCollection
+{
+    for set in 0 .. sets.num_sets() {
+        for binding in 0 .. sets.num_bindings_in_set(set).unwrap_or(0) {
+            let desc = match sets.descriptor(set, binding) {
+                Some(desc) => desc,
+                None => continue,
+            };
+
+            if desc.readonly {
+                continue;
+            }
+
+            match desc.ty.ty() {
+                Some(DescriptorType::StorageBuffer) |
+                Some(DescriptorType::StorageBufferDynamic) |
+                Some(DescriptorType::StorageImage) |
+                Some(DescriptorType::StorageTexelBuffer) => return true,
+                _ => ()
+            }
+        }
+    }
+
+    false
+}
+
+// Returns the source and destination accesses of the barriers, depending on which side writes.
+fn barrier_access(previous_writes: bool, next_writes: bool) -> (AccessFlagBits, AccessFlagBits) {
+    if !previous_writes {
+        // Write-after-read hazards only need an execution dependency.
+        return (AccessFlagBits::none(), AccessFlagBits::none());
+    }
+
+    let src = AccessFlagBits { shader_write: true, .. AccessFlagBits::none() };
+    let dst = AccessFlagBits {
+        shader_read: true,
+        shader_write: next_writes,
+        .. AccessFlagBits::none()
+    };
+
+    (src, dst)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use std::sync::Arc;
+
+    use buffer::BufferAccess;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::commands_raw::CmdComputeBarrier;
+    use descriptor::descriptor::DescriptorBufferContentDesc;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorSetsCollection;
+    use descriptor::descriptor_set::UnsafeDescriptorSet;
+    use image::ImageAccess;
+    use sync::AccessFlagBits;
+
+    // Collection made of a single storage buffer.
+    struct StorageBuffer {
+        buffer: Arc<BufferAccess + Send + Sync>,
+        readonly: bool,
+    }
+
+    unsafe impl DescriptorSetsCollection for StorageBuffer {
+        fn num_sets(&self) -> usize { 1 }
+        fn descriptor_set(&self, _: usize) -> Option<&UnsafeDescriptorSet> { None }
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            if set == 0 { Some(1) } else { None }
+        }
+
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            if set != 0 || binding != 0 {
+                return None;
+            }
+
+            Some(DescriptorDesc {
+                ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                    dynamic: Some(false),
+                    storage: true,
+                    content: DescriptorBufferContentDesc::F32,
+                }),
+                array_count: 1,
+                stages: ShaderStages::compute(),
+                readonly: self.readonly,
+            })
+        }
+
+        fn buffers_list<'a>(&'a self) -> Box<Iterator<Item = &'a BufferAccess> + 'a> {
+            Box::new(iter::once(&*self.buffer as &BufferAccess))
+        }
+
+        fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a> {
+            Box::new(iter::empty())
+        }
+    }
+
+    #[test]
+    fn write_then_read() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        let write = StorageBuffer { buffer: buffer.clone(), readonly: false };
+        let read = StorageBuffer { buffer: buffer, readonly: true };
+
+        let cmd = CmdComputeBarrier::new(write, read);
+        assert_eq!(&cmd.buffers[..], &[0]);
+        assert_eq!(cmd.src_access, AccessFlagBits { shader_write: true, .. AccessFlagBits::none() });
+        assert_eq!(cmd.dst_access, AccessFlagBits { shader_read: true, .. AccessFlagBits::none() });
+    }
+
+    #[test]
+    fn read_then_read() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        let first = StorageBuffer { buffer: buffer.clone(), readonly: true };
+        let second = StorageBuffer { buffer: buffer, readonly: true };
+        assert!(CmdComputeBarrier::new(first, second).is_empty());
+    }
+
+    #[test]
+    fn disjoint_ranges() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0 .. 16u32).unwrap();
+
+        let first = buffer.clone().into_buffer_slice().slice(0 .. 8).unwrap();
+        let second = buffer.clone().into_buffer_slice().slice(8 .. 16).unwrap();
+
+        let write = StorageBuffer { buffer: Arc::new(first), readonly: false };
+        let read = StorageBuffer { buffer: Arc::new(second), readonly: true };
+        assert!(CmdComputeBarrier::new(write, read).is_empty());
+    }
+
+    #[test]
+    fn overlapping_ranges() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()), 0 .. 16u32).unwrap();
+
+        let first = buffer.clone().into_buffer_slice().slice(0 .. 8).unwrap();
+        let second = buffer.clone().into_buffer_slice().slice(4 .. 12).unwrap();
+
+        let write = StorageBuffer { buffer: Arc::new(first), readonly: false };
+        let read = StorageBuffer { buffer: Arc::new(second), readonly: true };
+        let cmd = CmdComputeBarrier::new(write, read);
+        assert_eq!(&cmd.buffers[..], &[0]);
+    }
+
+    #[test]
+    fn access_inference() {
+        let (src, dst) = super::barrier_access(true, true);
+        assert!(src.shader_write);
+        assert!(dst.shader_read && dst.shader_write);
+
+        let (src, dst) = super::barrier_access(false, true);
+        assert!(src.is_empty() && dst.is_empty());
+    }
+}
@@ -7,27 +7,42 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
 use std::error;
 use std::fmt;
 use std::sync::Arc;
 use command_buffer::CommandAddError;
 use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::commands_raw::CmdPipelineBarrier;
 use command_buffer::pool::CommandPool;
 use device::Device;
 use device::DeviceOwned;
+use format::FormatTy;
+use image::ImageAccess;
+use image::ImageLayout;
+use image::ImageSubresourceLayers;
+use sync::AccessFlagBits;
+use sync::PipelineStages;
 use VulkanObject;
 use vk;
 
 /// Command that copies from an image to another image.
+///
+/// If the source is not in the `TransferSrcOptimal` layout or the destination is not in the
+/// `TransferDstOptimal` layout when the command starts, they are transitioned to these layouts
+/// before the copy. If an image isn't in the layout of its final layout requirement after the
+/// copy, it is then transitioned to that layout.
 #[derive(Debug, Clone)]
 pub struct CmdCopyImage<S, D> {
     // The source image.
     source: S,
     // Raw source image.
     source_raw: vk::Image,
-    // Layout of the source image.
-    source_layout: vk::ImageLayout,
+    // Layout of the source image before the command.
+    source_initial_layout: ImageLayout,
+    // Layout of the source image after the command.
+    source_final_layout: ImageLayout,
     // Offset in the source.
     source_offset: [i32; 3],
     source_aspect_mask: vk::ImageAspectFlags,
@@ -38,8 +53,10 @@ pub struct CmdCopyImage<S, D> {
     destination: D,
     // Raw destination image.
     destination_raw: vk::Image,
-    // Layout of the destination image.
-    destination_layout: vk::ImageLayout,
+    // Layout of the destination image before the command.
+    destination_initial_layout: ImageLayout,
+    // Layout of the destination image after the command.
+    destination_final_layout: ImageLayout,
     // Offset in the destination.
     destination_offset: [i32; 3],
     destination_aspect_mask: vk::ImageAspectFlags,
@@ -50,7 +67,108 @@ pub struct CmdCopyImage<S, D> {
     extent: [u32; 3],
 }
 
-// TODO: add constructor
+impl<S, D> CmdCopyImage<S, D> where S: ImageAccess, D: ImageAccess {
+    /// Builds a command that copies a region of `source` to `destination`.
+    ///
+    /// The two formats must either be identical, or both be uncompressed color formats with the
+    /// same texel size. The number of layers of the two subresources must be the same.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the source and the destination were not created with the same device.
+    ///
+    pub fn new(source: S, source_subresource: ImageSubresourceLayers, source_offset: [u32; 3],
+               destination: D, destination_subresource: ImageSubresourceLayers,
+               destination_offset: [u32; 3], extent: [u32; 3])
+               -> Result<CmdCopyImage<S, D>, CmdCopyImageError>
+    {
+        assert_eq!(source.inner().device().internal_object(),
+                   destination.inner().device().internal_object());
+
+        let source_raw = {
+            let inner = source.inner();
+            if !inner.usage_transfer_src() {
+                return Err(CmdCopyImageError::SourceMissingTransferUsage);
+            }
+            inner.internal_object()
+        };
+
+        let destination_raw = {
+            let inner = destination.inner();
+            if !inner.usage_transfer_dest() {
+                return Err(CmdCopyImageError::DestinationMissingTransferUsage);
+            }
+            inner.internal_object()
+        };
+
+        if !formats_compatible(&source, &destination) {
+            return Err(CmdCopyImageError::IncompatibleFormats);
+        }
+
+        if source.samples() != destination.samples() {
+            return Err(CmdCopyImageError::SampleCountMismatch);
+        }
+
+        if source_subresource.num_layers != destination_subresource.num_layers {
+            return Err(CmdCopyImageError::LayerCountMismatch);
+        }
+
+        if !region_in_image(&source, &source_subresource, source_offset, extent) {
+            return Err(CmdCopyImageError::SourceOutOfImageRange);
+        }
+
+        if !region_in_image(&destination, &destination_subresource, destination_offset, extent) {
+            return Err(CmdCopyImageError::DestinationOutOfImageRange);
+        }
+
+        if source.conflicts_image(source_subresource.first_layer, source_subresource.num_layers,
+                                  source_subresource.mip_level, 1, &destination,
+                                  destination_subresource.first_layer,
+                                  destination_subresource.num_layers,
+                                  destination_subresource.mip_level, 1)
+        {
+            return Err(CmdCopyImageError::OverlappingRanges);
+        } else {
+            debug_assert!(!destination.conflicts_image(destination_subresource.first_layer,
+                                                       destination_subresource.num_layers,
+                                                       destination_subresource.mip_level, 1,
+                                                       &source, source_subresource.first_layer,
+                                                       source_subresource.num_layers,
+                                                       source_subresource.mip_level, 1));
+        }
+
+        let source_aspect_mask = aspect_mask(&source);
+        let destination_aspect_mask = aspect_mask(&destination);
+        let source_initial_layout = source.initial_layout_requirement();
+        let source_final_layout = source.final_layout_requirement();
+        let destination_initial_layout = destination.initial_layout_requirement();
+        let destination_final_layout = destination.final_layout_requirement();
+
+        Ok(CmdCopyImage {
+            source: source,
+            source_raw: source_raw,
+            source_initial_layout: source_initial_layout,
+            source_final_layout: source_final_layout,
+            source_offset: [source_offset[0] as i32, source_offset[1] as i32,
+                            source_offset[2] as i32],
+            source_aspect_mask: source_aspect_mask,
+            source_mip_level: source_subresource.mip_level,
+            source_base_array_layer: source_subresource.first_layer,
+            source_layer_count: source_subresource.num_layers,
+            destination: destination,
+            destination_raw: destination_raw,
+            destination_initial_layout: destination_initial_layout,
+            destination_final_layout: destination_final_layout,
+            destination_offset: [destination_offset[0] as i32, destination_offset[1] as i32,
+                                 destination_offset[2] as i32],
+            destination_aspect_mask: destination_aspect_mask,
+            destination_mip_level: destination_subresource.mip_level,
+            destination_base_array_layer: destination_subresource.first_layer,
+            destination_layer_count: destination_subresource.num_layers,
+            extent: extent,
+        })
+    }
+}
 
 impl<S, D> CmdCopyImage<S, D> {
     /// Returns the source image.
@@ -66,26 +184,150 @@ impl<S, D> CmdCopyImage<S, D> {
     }
 }
 
-unsafe impl<S, D> DeviceOwned for CmdCopyImage<S, D> where S: DeviceOwned {
+// Returns true if the content of `source` can be copied to `destination` without conversion.
+//
+// Depth and stencil formats are only compatible with themselves. Color formats are compatible if
+// their texels have the same size. Compressed formats are only compatible with themselves, as we
+// don't know the size of their blocks.
+fn formats_compatible<S, D>(source: &S, destination: &D) -> bool
+    where S: ImageAccess, D: ImageAccess
+{
+    let source_format = source.format();
+    let destination_format = destination.format();
+
+    if source_format == destination_format {
+        return true;
+    }
+
+    let is_color = |ty| match ty {
+        FormatTy::Float | FormatTy::Uint | FormatTy::Sint => true,
+        _ => false,
+    };
+
+    if !is_color(source_format.ty()) || !is_color(destination_format.ty()) {
+        return false;
+    }
+
+    match (source_format.size(), destination_format.size()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+// Returns true if the region starting at `offset` of size `extent` fits in the given subresource
+// of `image`.
+fn region_in_image<I>(image: &I, subresource: &ImageSubresourceLayers, offset: [u32; 3],
+                      extent: [u32; 3]) -> bool
+    where I: ImageAccess
+{
+    let in_range = |first: u32, len: u32, max: u32| {
+        first.checked_add(len).map(|end| end <= max).unwrap_or(false)
+    };
+
+    if subresource.num_layers == 0 || subresource.mip_level >= image.mipmap_levels() ||
+       !in_range(subresource.first_layer, subresource.num_layers,
+                 image.dimensions().array_layers())
+    {
+        return false;
+    }
+
+    let dims = image.dimensions().width_height_depth();
+    (0 .. 3).all(|i| {
+        let mip_dim = cmp::max(1, dims[i] >> subresource.mip_level);
+        in_range(offset[i], extent[i], mip_dim)
+    })
+}
+
+// Returns the layout in which an image that is in the `initial` layout is accessed by the copy.
+// `General` is valid for transfers, therefore images in this layout aren't transitioned.
+fn transfer_layout(initial: ImageLayout, optimal: ImageLayout) -> ImageLayout {
+    if initial == ImageLayout::General {
+        initial
+    } else {
+        optimal
+    }
+}
+
+// Returns all the aspects of the format of `image`.
+fn aspect_mask<I>(image: &I) -> vk::ImageAspectFlags
+    where I: ImageAccess
+{
+    if image.has_depth() || image.has_stencil() {
+        let mut mask = 0;
+        if image.has_depth() { mask |= vk::IMAGE_ASPECT_DEPTH_BIT; }
+        if image.has_stencil() { mask |= vk::IMAGE_ASPECT_STENCIL_BIT; }
+        mask
+    } else {
+        vk::IMAGE_ASPECT_COLOR_BIT
+    }
+}
+
+unsafe impl<S, D> DeviceOwned for CmdCopyImage<S, D> where S: ImageAccess {
     #[inline]
     fn device(&self) -> &Arc<Device> {
-        self.source.device()
+        self.source.inner().device()
     }
 }
 
 unsafe impl<'a, P, S, D> AddCommand<&'a CmdCopyImage<S, D>> for UnsafeCommandBufferBuilder<P>
-    where P: CommandPool
+    where P: CommandPool, S: ImageAccess, D: ImageAccess
 {
     type Out = UnsafeCommandBufferBuilder<P>;
 
     #[inline]
     fn add(self, command: &'a CmdCopyImage<S, D>) -> Result<Self::Out, CommandAddError> {
-        unsafe {
-            debug_assert!(command.source_layout == vk::IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL ||
-                          command.source_layout == vk::IMAGE_LAYOUT_GENERAL);
-            debug_assert!(command.destination_layout == vk::IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL ||
-                          command.destination_layout == vk::IMAGE_LAYOUT_GENERAL);
+        let source_layout = transfer_layout(command.source_initial_layout,
+                                            ImageLayout::TransferSrcOptimal);
+        let destination_layout = transfer_layout(command.destination_initial_layout,
+                                                 ImageLayout::TransferDstOptimal);
+
+        let source_needs_transition = source_layout != command.source_initial_layout;
+        let destination_needs_transition = destination_layout != command.destination_initial_layout;
+        let source_needs_final_transition = source_layout != command.source_final_layout;
+        let destination_needs_final_transition =
+            destination_layout != command.destination_final_layout;
+
+        let source_mipmaps = command.source_mip_level .. command.source_mip_level + 1;
+        let source_layers = command.source_base_array_layer ..
+                            command.source_base_array_layer + command.source_layer_count;
+        let destination_mipmaps = command.destination_mip_level ..
+                                  command.destination_mip_level + 1;
+        let destination_layers = command.destination_base_array_layer ..
+                                 command.destination_base_array_layer +
+                                 command.destination_layer_count;
 
+        let transfer_stage = PipelineStages { transfer: true, .. PipelineStages::none() };
+        let all_stages = PipelineStages { all_commands: true, .. PipelineStages::none() };
+        let transfer_read = AccessFlagBits { transfer_read: true, .. AccessFlagBits::none() };
+        let transfer_write = AccessFlagBits { transfer_write: true, .. AccessFlagBits::none() };
+
+        let mut builder = self;
+
+        if source_needs_transition || destination_needs_transition {
+            let mut barrier = CmdPipelineBarrier::new();
+            unsafe {
+                if source_needs_transition {
+                    barrier.add_image_memory_barrier(&command.source, source_mipmaps.clone(),
+                                                     source_layers.clone(), all_stages,
+                                                     AccessFlagBits::all(), transfer_stage,
+                                                     transfer_read, false, None,
+                                                     command.source_initial_layout,
+                                                     source_layout);
+                }
+                if destination_needs_transition {
+                    barrier.add_image_memory_barrier(&command.destination,
+                                                     destination_mipmaps.clone(),
+                                                     destination_layers.clone(), all_stages,
+                                                     AccessFlagBits::all(), transfer_stage,
+                                                     transfer_write, false, None,
+                                                     command.destination_initial_layout,
+                                                     destination_layout);
+                }
+            }
+            builder = AddCommand::add(builder, &barrier)?;
+        }
+
+        unsafe {
             let region = vk::ImageCopy {
                 srcSubresource: vk::ImageSubresourceLayers {
                     aspectMask: command.source_aspect_mask,
@@ -116,26 +358,89 @@ unsafe impl<'a, P, S, D> AddCommand<&'a CmdCopyImage<S, D>> for UnsafeCommandBuf
                 },
             };
 
-            let vk = self.device().pointers();
-            let cmd = self.internal_object();
-            vk.CmdCopyImage(cmd, command.source_raw, command.source_layout,
-                            command.destination_raw, command.destination_layout,
+            let vk = builder.device().pointers();
+            let cmd = builder.internal_object();
+            vk.CmdCopyImage(cmd, command.source_raw, source_layout as u32,
+                            command.destination_raw, destination_layout as u32,
                             1, &region as *const _);
         }
 
-        Ok(self)
+        if source_needs_final_transition || destination_needs_final_transition {
+            let mut barrier = CmdPipelineBarrier::new();
+            unsafe {
+                if source_needs_final_transition {
+                    barrier.add_image_memory_barrier(&command.source, source_mipmaps,
+                                                     source_layers, transfer_stage,
+                                                     transfer_read, all_stages,
+                                                     AccessFlagBits::all(), false, None,
+                                                     source_layout,
+                                                     command.source_final_layout);
+                }
+                if destination_needs_final_transition {
+                    barrier.add_image_memory_barrier(&command.destination, destination_mipmaps,
+                                                     destination_layers, transfer_stage,
+                                                     transfer_write, all_stages,
+                                                     AccessFlagBits::all(), false, None,
+                                                     destination_layout,
+                                                     command.destination_final_layout);
+                }
+            }
+            builder = AddCommand::add(builder, &barrier)?;
+        }
+
+        Ok(builder)
     }
 }
 
 /// Error that can happen when creating a `CmdCopyImage`.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum CmdCopyImageError {
+    /// The source image is missing the transfer source usage.
+    SourceMissingTransferUsage,
+    /// The destination image is missing the transfer destination usage.
+    DestinationMissingTransferUsage,
+    /// The formats of the source and the destination are not compatible for copies.
+    IncompatibleFormats,
+    /// The source and the destination don't have the same number of samples.
+    SampleCountMismatch,
+    /// The source and the destination subresources don't have the same number of layers.
+    LayerCountMismatch,
+    /// The copied region is out of range of the source image.
+    SourceOutOfImageRange,
+    /// The copied region is out of range of the destination image.
+    DestinationOutOfImageRange,
+    /// The source and destination are overlapping in memory.
+    OverlappingRanges,
 }
 
 impl error::Error for CmdCopyImageError {
     #[inline]
     fn description(&self) -> &str {
         match *self {
+            CmdCopyImageError::SourceMissingTransferUsage => {
+                "the source image is missing the transfer source usage"
+            },
+            CmdCopyImageError::DestinationMissingTransferUsage => {
+                "the destination image is missing the transfer destination usage"
+            },
+            CmdCopyImageError::IncompatibleFormats => {
+                "the formats of the source and the destination are not compatible for copies"
+            },
+            CmdCopyImageError::SampleCountMismatch => {
+                "the source and the destination don't have the same number of samples"
+            },
+            CmdCopyImageError::LayerCountMismatch => {
+                "the source and the destination subresources don't have the same number of layers"
+            },
+            CmdCopyImageError::SourceOutOfImageRange => {
+                "the copied region is out of range of the source image"
+            },
+            CmdCopyImageError::DestinationOutOfImageRange => {
+                "the copied region is out of range of the destination image"
+            },
+            CmdCopyImageError::OverlappingRanges => {
+                "the source and destination are overlapping in memory"
+            },
         }
     }
 }
@@ -146,3 +451,120 @@ impl fmt::Display for CmdCopyImageError {
         write!(fmt, "{}", error::Error::description(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::commands_raw::CmdCopyImage;
+    use command_buffer::commands_raw::CmdCopyImageError;
+    use format::Format;
+    use image::Dimensions;
+    use image::ImageSubresourceLayers;
+    use image::StorageImage;
+
+    #[test]
+    fn incompatible_formats() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dims = Dimensions::Dim2d { width: 16, height: 16 };
+        let source = StorageImage::new(device.clone(), dims, Format::R8G8B8A8Unorm,
+                                       Some(queue.family())).unwrap();
+        let destination = StorageImage::new(device, dims, Format::R16G16B16A16Sfloat,
+                                            Some(queue.family())).unwrap();
+
+        let sub = ImageSubresourceLayers::mip_level(0);
+        match CmdCopyImage::new(source, sub, [0, 0, 0], destination, sub, [0, 0, 0],
+                                [16, 16, 1])
+        {
+            Err(CmdCopyImageError::IncompatibleFormats) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn extent_out_of_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let source = StorageImage::new(device.clone(), Dimensions::Dim2d { width: 16, height: 16 },
+                                       Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+        let destination = StorageImage::new(device, Dimensions::Dim2d { width: 8, height: 8 },
+                                            Format::R8G8B8A8Uint, Some(queue.family())).unwrap();
+
+        let sub = ImageSubresourceLayers::mip_level(0);
+        match CmdCopyImage::new(source, sub, [0, 0, 0], destination, sub, [0, 0, 0],
+                                [16, 16, 1])
+        {
+            Err(CmdCopyImageError::DestinationOutOfImageRange) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dims = Dimensions::Dim2d { width: 16, height: 16 };
+        let source = StorageImage::new(device.clone(), dims, Format::R8G8B8A8Unorm,
+                                       Some(queue.family())).unwrap();
+        let destination = StorageImage::new(device, dims, Format::R8G8B8A8Unorm,
+                                            Some(queue.family())).unwrap();
+
+        let sub = ImageSubresourceLayers::mip_level(0);
+        match CmdCopyImage::new(source, sub, [0xffffffff, 0, 0], destination, sub, [0, 0, 0],
+                                [16, 16, 1])
+        {
+            Err(CmdCopyImageError::SourceOutOfImageRange) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn layers_overflow() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dims = Dimensions::Dim2d { width: 16, height: 16 };
+        let source = StorageImage::new(device.clone(), dims, Format::R8G8B8A8Unorm,
+                                       Some(queue.family())).unwrap();
+        let destination = StorageImage::new(device, dims, Format::R8G8B8A8Unorm,
+                                            Some(queue.family())).unwrap();
+
+        let src = ImageSubresourceLayers { mip_level: 0, first_layer: 0xffffffff, num_layers: 1 };
+        let dest = ImageSubresourceLayers::mip_level(0);
+        match CmdCopyImage::new(source, src, [0, 0, 0], destination, dest, [0, 0, 0],
+                                [16, 16, 1])
+        {
+            Err(CmdCopyImageError::SourceOutOfImageRange) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn disjoint_layers_of_same_image() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dims = Dimensions::Dim2dArray { width: 16, height: 16, array_layers: 2 };
+        let image = StorageImage::new(device, dims, Format::R8G8B8A8Unorm,
+                                      Some(queue.family())).unwrap();
+
+        let src = ImageSubresourceLayers { mip_level: 0, first_layer: 0, num_layers: 1 };
+        let dest = ImageSubresourceLayers { mip_level: 0, first_layer: 1, num_layers: 1 };
+        assert!(CmdCopyImage::new(image.clone(), src, [0, 0, 0], image, dest, [0, 0, 0],
+                                  [16, 16, 1]).is_ok());
+    }
+
+    #[test]
+    fn overlapping_layers_of_same_image() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dims = Dimensions::Dim2dArray { width: 16, height: 16, array_layers: 2 };
+        let image = StorageImage::new(device, dims, Format::R8G8B8A8Unorm,
+                                      Some(queue.family())).unwrap();
+
+        let sub = ImageSubresourceLayers { mip_level: 0, first_layer: 0, num_layers: 2 };
+        match CmdCopyImage::new(image.clone(), sub, [0, 0, 0], image, sub, [0, 0, 0],
+                                [16, 16, 1])
+        {
+            Err(CmdCopyImageError::OverlappingRanges) => (),
+            _ => panic!()
+        };
+    }
+}
@@ -0,0 +1,384 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+use buffer::BufferAccess;
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::commands_raw::CmdPipelineBarrier;
+use command_buffer::pool::CommandPool;
+use device::Device;
+use device::DeviceOwned;
+use image::ImageAccess;
+use image::ImageLayout;
+use sync::AccessFlagBits;
+use sync::PipelineStages;
+use VulkanObject;
+use vk;
+
+/// Command that copies from an image to a buffer.
+///
+/// If the image is not in the `TransferSrcOptimal` layout when the command starts, it is
+/// transitioned to this layout before the copy and transitioned back afterwards.
+#[derive(Debug, Clone)]
+pub struct CmdCopyImageToBuffer<S, D> {
+    // The source image.
+    source: S,
+    // Raw source image.
+    source_raw: vk::Image,
+    // Layout of the source image before and after the command.
+    source_outer_layout: ImageLayout,
+    // Offset in the source.
+    source_offset: [i32; 3],
+    source_aspect_mask: vk::ImageAspectFlags,
+    source_mip_level: u32,
+    source_base_array_layer: u32,
+    source_layer_count: u32,
+    // The destination buffer.
+    destination: D,
+    // Raw destination buffer.
+    destination_raw: vk::Buffer,
+    // Offset in the destination.
+    destination_offset: vk::DeviceSize,
+    // Size.
+    extent: [u32; 3],
+}
+
+impl<S, D> CmdCopyImageToBuffer<S, D> where S: ImageAccess, D: BufferAccess {
+    #[inline]
+    pub fn new(source: S, destination: D)
+               -> Result<CmdCopyImageToBuffer<S, D>, CmdCopyImageToBufferError>
+    {
+        let dims = source.dimensions().width_height_depth();
+        CmdCopyImageToBuffer::with_dimensions(source, destination, [0, 0, 0], dims, 0, 1, 0)
+    }
+
+    pub fn with_dimensions(source: S, destination: D, offset: [u32; 3], size: [u32; 3],
+                           first_layer: u32, num_layers: u32, mipmap: u32)
+                           -> Result<CmdCopyImageToBuffer<S, D>, CmdCopyImageToBufferError>
+    {
+        // FIXME: check buffer content format
+
+        assert_eq!(source.inner().device().internal_object(),
+                   destination.inner().buffer.device().internal_object());
+
+        if source.samples() != 1 {
+            return Err(CmdCopyImageToBufferError::SourceMultisampled);
+        }
+
+        let source_raw = {
+            let inner = source.inner();
+            if !inner.usage_transfer_src() {
+                return Err(CmdCopyImageToBufferError::SourceMissingTransferUsage);
+            }
+            inner.internal_object()
+        };
+
+        {
+            let dims = source.dimensions().width_height_depth();
+            let in_range = |first: u32, len: u32, max: u32| {
+                first.checked_add(len).map(|end| end <= max).unwrap_or(false)
+            };
+
+            if !in_range(offset[0], size[0], dims[0]) || !in_range(offset[1], size[1], dims[1]) ||
+               !in_range(offset[2], size[2], dims[2]) || mipmap >= source.mipmap_levels() ||
+               !in_range(first_layer, num_layers, source.dimensions().array_layers())
+            {
+                return Err(CmdCopyImageToBufferError::OutOfImageRange);
+            }
+        }
+
+        let (destination_raw, dst_offset) = {
+            let inner = destination.inner();
+            if !inner.buffer.usage_transfer_dest() {
+                return Err(CmdCopyImageToBufferError::DestinationMissingTransferUsage);
+            }
+            (inner.buffer.internal_object(), inner.offset)
+        };
+
+        if let Some(texel_size) = source.format().size() {
+            let required = texel_size * size[0] as usize * size[1] as usize * size[2] as usize *
+                           num_layers as usize;
+            if destination.size() < required {
+                return Err(CmdCopyImageToBufferError::DestinationTooSmall);
+            }
+        }
+
+        if destination.conflicts_image(0, destination.size(), &source, first_layer, num_layers,
+                                       mipmap, 1)
+        {
+            return Err(CmdCopyImageToBufferError::OverlappingRanges);
+        } else {
+            debug_assert!(!source.conflicts_buffer(first_layer, num_layers, mipmap, 1,
+                                                   &destination, 0, destination.size()));
+        }
+
+        // A copy between an image and a buffer can only access one aspect at a time.
+        let aspect_mask = match (source.has_depth(), source.has_stencil()) {
+            (true, true) => return Err(CmdCopyImageToBufferError::SourceDepthStencil),
+            (true, false) => vk::IMAGE_ASPECT_DEPTH_BIT,
+            (false, true) => vk::IMAGE_ASPECT_STENCIL_BIT,
+            (false, false) => vk::IMAGE_ASPECT_COLOR_BIT,
+        };
+
+        let source_outer_layout = source.initial_layout_requirement();
+
+        Ok(CmdCopyImageToBuffer {
+            source: source,
+            source_raw: source_raw,
+            source_outer_layout: source_outer_layout,
+            source_offset: [offset[0] as i32, offset[1] as i32, offset[2] as i32],
+            source_aspect_mask: aspect_mask,
+            source_mip_level: mipmap,
+            source_base_array_layer: first_layer,
+            source_layer_count: num_layers,
+            destination: destination,
+            destination_raw: destination_raw,
+            destination_offset: dst_offset as vk::DeviceSize,
+            extent: size,
+        })
+    }
+}
+
+impl<S, D> CmdCopyImageToBuffer<S, D> {
+    /// Returns the source image.
+    #[inline]
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the destination buffer.
+    #[inline]
+    pub fn destination(&self) -> &D {
+        &self.destination
+    }
+}
+
+unsafe impl<S, D> DeviceOwned for CmdCopyImageToBuffer<S, D> where D: DeviceOwned {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.destination.device()
+    }
+}
+
+unsafe impl<'a, P, S, D> AddCommand<&'a CmdCopyImageToBuffer<S, D>> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool, S: ImageAccess
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdCopyImageToBuffer<S, D>) -> Result<Self::Out, CommandAddError> {
+        let needs_transition = command.source_outer_layout != ImageLayout::TransferSrcOptimal &&
+                               command.source_outer_layout != ImageLayout::General;
+        let mipmaps = command.source_mip_level .. command.source_mip_level + 1;
+        let layers = command.source_base_array_layer ..
+                     command.source_base_array_layer + command.source_layer_count;
+
+        let transfer_stage = PipelineStages { transfer: true, .. PipelineStages::none() };
+        let all_stages = PipelineStages { all_commands: true, .. PipelineStages::none() };
+        let transfer_read = AccessFlagBits { transfer_read: true, .. AccessFlagBits::none() };
+
+        let mut builder = self;
+
+        if needs_transition {
+            let mut barrier = CmdPipelineBarrier::new();
+            unsafe {
+                barrier.add_image_memory_barrier(&command.source, mipmaps.clone(), layers.clone(),
+                                                 all_stages, AccessFlagBits::all(),
+                                                 transfer_stage, transfer_read, false, None,
+                                                 command.source_outer_layout,
+                                                 ImageLayout::TransferSrcOptimal);
+            }
+            builder = AddCommand::add(builder, &barrier)?;
+        }
+
+        unsafe {
+            let region = vk::BufferImageCopy {
+                bufferOffset: command.destination_offset,
+                bufferRowLength: 0,
+                bufferImageHeight: 0,
+                imageSubresource: vk::ImageSubresourceLayers {
+                    aspectMask: command.source_aspect_mask,
+                    mipLevel: command.source_mip_level,
+                    baseArrayLayer: command.source_base_array_layer,
+                    layerCount: command.source_layer_count,
+                },
+                imageOffset: vk::Offset3D {
+                    x: command.source_offset[0],
+                    y: command.source_offset[1],
+                    z: command.source_offset[2],
+                },
+                imageExtent: vk::Extent3D {
+                    width: command.extent[0],
+                    height: command.extent[1],
+                    depth: command.extent[2],
+                },
+            };
+
+            let vk = builder.device().pointers();
+            let cmd = builder.internal_object();
+            let layout = if needs_transition {
+                vk::IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL
+            } else {
+                command.source_outer_layout as u32
+            };
+            vk.CmdCopyImageToBuffer(cmd, command.source_raw, layout, command.destination_raw,
+                                    1, &region as *const _);
+        }
+
+        if needs_transition {
+            let mut barrier = CmdPipelineBarrier::new();
+            unsafe {
+                barrier.add_image_memory_barrier(&command.source, mipmaps, layers,
+                                                 transfer_stage, transfer_read,
+                                                 all_stages, AccessFlagBits::all(), false, None,
+                                                 ImageLayout::TransferSrcOptimal,
+                                                 command.source_outer_layout);
+            }
+            builder = AddCommand::add(builder, &barrier)?;
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Error that can happen when creating a `CmdCopyImageToBuffer`.
+#[derive(Debug, Copy, Clone)]
+pub enum CmdCopyImageToBufferError {
+    /// The source image is missing the transfer source usage.
+    SourceMissingTransferUsage,
+    /// The source image has more than one sample per pixel.
+    SourceMultisampled,
+    /// The source image has both a depth and a stencil aspect, while a copy to a buffer can only
+    /// copy one aspect.
+    SourceDepthStencil,
+    /// The destination buffer is missing the transfer destination usage.
+    DestinationMissingTransferUsage,
+    /// The destination buffer is too small to contain the copied region.
+    DestinationTooSmall,
+    /// The dimensions are out of range of the image.
+    OutOfImageRange,
+    /// The source and destination are overlapping in memory.
+    OverlappingRanges,
+}
+
+impl error::Error for CmdCopyImageToBufferError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdCopyImageToBufferError::SourceMissingTransferUsage => {
+                "the source image is missing the transfer source usage"
+            },
+            CmdCopyImageToBufferError::SourceMultisampled => {
+                "the source image has more than one sample per pixel"
+            },
+            CmdCopyImageToBufferError::SourceDepthStencil => {
+                "the source image has both a depth and a stencil aspect"
+            },
+            CmdCopyImageToBufferError::DestinationMissingTransferUsage => {
+                "the destination buffer is missing the transfer destination usage"
+            },
+            CmdCopyImageToBufferError::DestinationTooSmall => {
+                "the destination buffer is too small to contain the copied region"
+            },
+            CmdCopyImageToBufferError::OutOfImageRange => {
+                "the dimensions are out of range of the image"
+            },
+            CmdCopyImageToBufferError::OverlappingRanges => {
+                "the source and destination are overlapping in memory"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CmdCopyImageToBufferError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::u32;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::commands_raw::CmdCopyImageToBuffer;
+    use command_buffer::commands_raw::CmdCopyImageToBufferError;
+    use format::Format;
+    use image::AttachmentImage;
+    use image::Dimensions;
+    use image::ImageUsage;
+    use image::ImageView;
+    use image::StorageImage;
+
+    #[test]
+    fn whole_image() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let image = StorageImage::new(device.clone(), Dimensions::Dim2d { width: 16, height: 16 },
+                                      Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 16 * 16 * 4).map(|_| 0u8)).unwrap();
+
+        assert!(CmdCopyImageToBuffer::new(image, buffer).is_ok());
+    }
+
+    #[test]
+    fn destination_too_small() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let image = StorageImage::new(device.clone(), Dimensions::Dim2d { width: 16, height: 16 },
+                                      Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 16).map(|_| 0u8)).unwrap();
+
+        match CmdCopyImageToBuffer::new(image, buffer) {
+            Err(CmdCopyImageToBufferError::DestinationTooSmall) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let image = StorageImage::new(device.clone(), Dimensions::Dim2d { width: 16, height: 16 },
+                                      Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 16 * 16 * 4).map(|_| 0u8)).unwrap();
+
+        match CmdCopyImageToBuffer::with_dimensions(image, buffer, [u32::MAX, 0, 0], [2, 1, 1],
+                                                    0, 1, 0)
+        {
+            Err(CmdCopyImageToBufferError::OutOfImageRange) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn depth_aspect() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage { transfer_source: true, .. ImageUsage::none() };
+        let image = AttachmentImage::with_usage(device.clone(), [16, 16], Format::D16Unorm,
+                                                usage).unwrap().access();
+        let buffer = CpuAccessibleBuffer::from_iter(device, BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 16 * 16 * 2).map(|_| 0u8)).unwrap();
+
+        assert!(CmdCopyImageToBuffer::new(image, buffer).is_ok());
+    }
+}
@@ -24,6 +24,8 @@ use VulkanObject;
 /// > **Note**: Unless you are writing a custom implementation of a command buffer, you are
 /// > encouraged to ignore this struct and use a `CmdDispatch` instead.
 pub struct CmdDispatchRaw {
+    // Index of the first workgroup. Non-zero values require `vkCmdDispatchBaseKHR`.
+    base: [u32; 3],
     dimensions: [u32; 3],
     device: Arc<Device>,
 }
@@ -55,11 +57,51 @@ impl CmdDispatchRaw {
         }
 
         Ok(CmdDispatchRaw {
+            base: [0, 0, 0],
             dimensions: dimensions,
             device: device,
         })
     }
 
+    /// Same as `new`, but the workgroups are numbered starting from `base` instead of 0. The
+    /// `gl_WorkGroupID` seen by the shader is offset by `base`.
+    ///
+    /// Returns an error if `base` is not zero and the `khr_device_group` extension is not
+    /// enabled, or if the last workgroup goes beyond the limits of the device.
+    ///
+    /// # Safety
+    ///
+    /// Same as `new`. In addition, if `base` is not zero, the bound pipeline must have been
+    /// created with the dispatch base flag.
+    ///
+    pub unsafe fn with_base(device: Arc<Device>, base: [u32; 3], dimensions: [u32; 3])
+                            -> Result<CmdDispatchRaw, CmdDispatchRawError>
+    {
+        if base != [0, 0, 0] && !device.loaded_extensions().khr_device_group {
+            return Err(CmdDispatchRawError::DeviceGroupExtensionNotEnabled);
+        }
+
+        let max_dims = device.physical_device().limits().max_compute_work_group_count();
+
+        for i in 0 .. 3 {
+            if base[i] as u64 + dimensions[i] as u64 > max_dims[i] as u64 {
+                return Err(CmdDispatchRawError::DimensionsTooLarge);
+            }
+        }
+
+        Ok(CmdDispatchRaw {
+            base: base,
+            dimensions: dimensions,
+            device: device,
+        })
+    }
+
+    /// Returns the index of the first workgroup.
+    #[inline]
+    pub fn base(&self) -> [u32; 3] {
+        self.base
+    }
+
     /// Builds a new command that executes a compute shader.
     ///
     /// The command will use the descriptor sets, push constants, and pipeline currently bound.
@@ -78,6 +120,7 @@ impl CmdDispatchRaw {
                                        -> Result<CmdDispatchRaw, CmdDispatchRawError>
     {
         Ok(CmdDispatchRaw {
+            base: [0, 0, 0],
             dimensions: dimensions,
             device: device,
         })
@@ -101,8 +144,14 @@ unsafe impl<'a, P> AddCommand<&'a CmdDispatchRaw> for UnsafeCommandBufferBuilder
         unsafe {
             let vk = self.device().pointers();
             let cmd = self.internal_object();
-            vk.CmdDispatch(cmd, command.dimensions[0], command.dimensions[1],
-                           command.dimensions[2]);
+            if command.base == [0, 0, 0] {
+                vk.CmdDispatch(cmd, command.dimensions[0], command.dimensions[1],
+                               command.dimensions[2]);
+            } else {
+                vk.CmdDispatchBaseKHR(cmd, command.base[0], command.base[1], command.base[2],
+                                      command.dimensions[0], command.dimensions[1],
+                                      command.dimensions[2]);
+            }
         }
 
         Ok(self)
@@ -114,6 +163,8 @@ unsafe impl<'a, P> AddCommand<&'a CmdDispatchRaw> for UnsafeCommandBufferBuilder
 pub enum CmdDispatchRawError {
     /// The dispatch dimensions are larger than the hardware limits.
     DimensionsTooLarge,
+    /// The `khr_device_group` extension must be enabled in order to use a dispatch base.
+    DeviceGroupExtensionNotEnabled,
 }
 
 impl error::Error for CmdDispatchRawError {
@@ -123,6 +174,9 @@ impl error::Error for CmdDispatchRawError {
             CmdDispatchRawError::DimensionsTooLarge => {
                 "the dispatch dimensions are larger than the hardware limits"
             },
+            CmdDispatchRawError::DeviceGroupExtensionNotEnabled => {
+                "the `khr_device_group` extension must be enabled in order to use a dispatch base"
+            },
         }
     }
 }
@@ -165,4 +219,19 @@ mod tests {
             _ => panic!()
         }
     }
+
+    #[test]
+    fn base_requires_device_group() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match unsafe { CmdDispatchRaw::with_base(device.clone(), [0, 0, 0], [16, 1, 1]) } {
+            Ok(cmd) => assert_eq!(cmd.base(), [0, 0, 0]),
+            _ => panic!()
+        }
+
+        match unsafe { CmdDispatchRaw::with_base(device, [16, 0, 0], [16, 1, 1]) } {
+            Err(CmdDispatchRawError::DeviceGroupExtensionNotEnabled) => (),
+            _ => panic!()
+        }
+    }
 }
@@ -0,0 +1,64 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::pool::CommandPool;
+use device::DeviceOwned;
+use VulkanObject;
+
+/// Command that ends conditional rendering.
+#[derive(Debug, Copy, Clone)]
+pub struct CmdEndConditionalRendering;
+
+impl CmdEndConditionalRendering {
+    /// See the documentation of the `end_conditional_rendering` method.
+    #[inline]
+    pub fn new() -> CmdEndConditionalRendering {
+        CmdEndConditionalRendering
+    }
+}
+
+unsafe impl<'a, P> AddCommand<&'a CmdEndConditionalRendering> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdEndConditionalRendering) -> Result<Self::Out, CommandAddError> {
+        unsafe {
+            let vk = self.device().pointers();
+            let cmd = self.internal_object();
+            vk.CmdEndConditionalRenderingEXT(cmd);
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandAddError;
+    use command_buffer::CommandBufferBuilder;
+
+    #[test]
+    fn not_active() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let result = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+            .end_conditional_rendering();
+
+        match result {
+            Err(CommandAddError::NoConditionalRenderingToEnd) => (),
+            _ => panic!()
+        };
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::pool::CommandPool;
+use device::DeviceOwned;
+use VulkanObject;
+
+/// Command that closes the last debug label region opened with `CmdBeginDebugLabel`.
+///
+/// Does nothing if the `ext_debug_utils` extension is not enabled on the instance.
+#[derive(Debug, Copy, Clone)]
+pub struct CmdEndDebugLabel;
+
+impl CmdEndDebugLabel {
+    /// See the documentation of the `end_debug_label` method.
+    #[inline]
+    pub fn new() -> CmdEndDebugLabel {
+        CmdEndDebugLabel
+    }
+}
+
+unsafe impl<'a, P> AddCommand<&'a CmdEndDebugLabel> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdEndDebugLabel) -> Result<Self::Out, CommandAddError> {
+        if !self.device().instance().loaded_extensions().ext_debug_utils {
+            return Ok(self);
+        }
+
+        unsafe {
+            let vk = self.device().pointers();
+            let cmd = self.internal_object();
+            vk.CmdEndDebugUtilsLabelEXT(cmd);
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandAddError;
+    use command_buffer::CommandBufferBuilder;
+
+    #[test]
+    fn nested() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+            .begin_debug_label("outer", [1.0, 0.0, 0.0, 1.0]).unwrap()
+            .begin_debug_label("inner", [0.0; 4]).unwrap()
+            .insert_debug_label("marker", [0.0; 4]).unwrap()
+            .end_debug_label().unwrap()
+            .end_debug_label().unwrap()
+            .build().unwrap();
+    }
+
+    #[test]
+    fn mismatched_end() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let result = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+            .begin_debug_label("label", [0.0; 4]).unwrap()
+            .end_debug_label().unwrap()
+            .end_debug_label();
+
+        match result {
+            Err(CommandAddError::NoDebugLabelToEnd) => (),
+            _ => panic!()
+        };
+    }
+}
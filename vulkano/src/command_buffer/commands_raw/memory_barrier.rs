@@ -0,0 +1,243 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::commands_raw::CmdPipelineBarrier;
+use command_buffer::pool::CommandPool;
+use device::Device;
+use device::DeviceOwned;
+use image::ImageAccess;
+use image::ImageLayout;
+use sync::AccessFlagBits;
+use sync::PipelineStages;
+
+/// Command that makes the writes performed on a whole buffer by previous commands visible to
+/// the following commands.
+///
+/// Contrary to `CmdPipelineBarrier`, this command holds the buffer, which allows the upper layers
+/// of the command buffer to keep it alive and to synchronize it with the previous submissions.
+pub struct CmdBufferMemoryBarrier<B> {
+    // The buffer concerned by the barrier.
+    buffer: B,
+    // Stages and accesses on both sides of the barrier.
+    scopes: BarrierScopes,
+}
+
+impl<B> CmdBufferMemoryBarrier<B> where B: BufferAccess {
+    /// Builds a new barrier.
+    ///
+    /// `src_stages` and `src_access` are the stages and the accesses of the commands that
+    /// previously used the buffer. `dst_stages` and `dst_access` are the stages and the accesses
+    /// of the commands that are going to use it.
+    #[inline]
+    pub fn new(buffer: B, src_stages: PipelineStages, src_access: AccessFlagBits,
+               dst_stages: PipelineStages, dst_access: AccessFlagBits)
+               -> CmdBufferMemoryBarrier<B>
+    {
+        CmdBufferMemoryBarrier {
+            buffer: buffer,
+            scopes: BarrierScopes::new(src_stages, src_access, dst_stages, dst_access),
+        }
+    }
+}
+
+impl<B> CmdBufferMemoryBarrier<B> {
+    /// Returns the buffer concerned by the barrier.
+    #[inline]
+    pub fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Returns the stages of the commands that are going to use the buffer.
+    #[inline]
+    pub fn stages(&self) -> PipelineStages {
+        self.scopes.dst_stages
+    }
+
+    /// Returns the accesses of the commands that are going to use the buffer.
+    #[inline]
+    pub fn access(&self) -> AccessFlagBits {
+        self.scopes.dst_access
+    }
+}
+
+unsafe impl<B> DeviceOwned for CmdBufferMemoryBarrier<B> where B: DeviceOwned {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.buffer.device()
+    }
+}
+
+unsafe impl<'a, P, B> AddCommand<&'a CmdBufferMemoryBarrier<B>> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool, B: BufferAccess
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdBufferMemoryBarrier<B>) -> Result<Self::Out, CommandAddError> {
+        let s = &command.scopes;
+        let mut barrier = CmdPipelineBarrier::new();
+
+        unsafe {
+            barrier.add_buffer_memory_barrier(&command.buffer, s.src_stages, s.src_access,
+                                              s.dst_stages, s.dst_access, false, None, 0,
+                                              command.buffer.size());
+        }
+
+        AddCommand::add(self, &barrier)
+    }
+}
+
+/// Command that makes the writes performed on a whole image by previous commands visible to
+/// the following commands.
+///
+/// This is the equivalent of `CmdBufferMemoryBarrier` for images. Unless the barrier is built with
+/// `with_layout_transition`, the layout of the image is left unchanged and is assumed to be its
+/// initial layout requirement.
+pub struct CmdImageMemoryBarrier<I> {
+    // The image concerned by the barrier.
+    image: I,
+    // Layout of the image before and after the barrier.
+    layouts: (ImageLayout, ImageLayout),
+    // Stages and accesses on both sides of the barrier.
+    scopes: BarrierScopes,
+}
+
+impl<I> CmdImageMemoryBarrier<I> where I: ImageAccess {
+    /// Builds a new barrier.
+    ///
+    /// `src_stages` and `src_access` are the stages and the accesses of the commands that
+    /// previously used the image. `dst_stages` and `dst_access` are the stages and the accesses
+    /// of the commands that are going to use it.
+    #[inline]
+    pub fn new(image: I, src_stages: PipelineStages, src_access: AccessFlagBits,
+               dst_stages: PipelineStages, dst_access: AccessFlagBits)
+               -> CmdImageMemoryBarrier<I>
+    {
+        let layout = image.initial_layout_requirement();
+        CmdImageMemoryBarrier {
+            image: image,
+            layouts: (layout, layout),
+            scopes: BarrierScopes::new(src_stages, src_access, dst_stages, dst_access),
+        }
+    }
+
+    /// Same as `new`, but also transitions the whole image from `old_layout` to `new_layout`.
+    ///
+    /// # Safety
+    ///
+    /// - The image must be in `old_layout` when the barrier is executed, unless `old_layout` is
+    ///   `Undefined`, in which case the content of the image is discarded.
+    /// - The commands that use the image afterwards must expect it to be in `new_layout`.
+    ///
+    #[inline]
+    pub unsafe fn with_layout_transition(image: I, src_stages: PipelineStages,
+                                         src_access: AccessFlagBits, dst_stages: PipelineStages,
+                                         dst_access: AccessFlagBits, old_layout: ImageLayout,
+                                         new_layout: ImageLayout) -> CmdImageMemoryBarrier<I>
+    {
+        CmdImageMemoryBarrier {
+            image: image,
+            layouts: (old_layout, new_layout),
+            scopes: BarrierScopes::new(src_stages, src_access, dst_stages, dst_access),
+        }
+    }
+}
+
+impl<I> CmdImageMemoryBarrier<I> {
+    /// Returns the image concerned by the barrier.
+    #[inline]
+    pub fn image(&self) -> &I {
+        &self.image
+    }
+
+    /// Returns the stages of the commands that are going to use the image.
+    #[inline]
+    pub fn stages(&self) -> PipelineStages {
+        self.scopes.dst_stages
+    }
+
+    /// Returns the accesses of the commands that are going to use the image.
+    #[inline]
+    pub fn access(&self) -> AccessFlagBits {
+        self.scopes.dst_access
+    }
+}
+
+unsafe impl<I> DeviceOwned for CmdImageMemoryBarrier<I> where I: ImageAccess {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.inner().device()
+    }
+}
+
+unsafe impl<'a, P, I> AddCommand<&'a CmdImageMemoryBarrier<I>> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool, I: ImageAccess
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdImageMemoryBarrier<I>) -> Result<Self::Out, CommandAddError> {
+        let s = &command.scopes;
+        let mut barrier = CmdPipelineBarrier::new();
+
+        unsafe {
+            barrier.add_image_memory_barrier(&command.image, 0 .. command.image.mipmap_levels(),
+                                             0 .. command.image.dimensions().array_layers(),
+                                             s.src_stages, s.src_access, s.dst_stages,
+                                             s.dst_access, false, None, command.layouts.0,
+                                             command.layouts.1);
+        }
+
+        AddCommand::add(self, &barrier)
+    }
+}
+
+// Stages and accesses on both sides of a barrier.
+#[derive(Debug, Copy, Clone)]
+struct BarrierScopes {
+    src_stages: PipelineStages,
+    src_access: AccessFlagBits,
+    dst_stages: PipelineStages,
+    dst_access: AccessFlagBits,
+}
+
+impl BarrierScopes {
+    // A barrier with no source stage is invalid. An empty source scope means that there is
+    // nothing to wait for, so we use the top of the pipe. Same for the destination scope with the
+    // bottom of the pipe.
+    #[inline]
+    fn new(src_stages: PipelineStages, src_access: AccessFlagBits, dst_stages: PipelineStages,
+           dst_access: AccessFlagBits) -> BarrierScopes
+    {
+        let src_stages = if src_stages.is_empty() {
+            PipelineStages { top_of_pipe: true, .. PipelineStages::none() }
+        } else {
+            src_stages
+        };
+
+        let dst_stages = if dst_stages.is_empty() {
+            PipelineStages { bottom_of_pipe: true, .. PipelineStages::none() }
+        } else {
+            dst_stages
+        };
+
+        BarrierScopes {
+            src_stages: src_stages,
+            src_access: src_access,
+            dst_stages: dst_stages,
+            dst_access: dst_access,
+        }
+    }
+}
@@ -11,31 +11,44 @@
 //!
 //! This module only contains the base commands that have direct equivalents in the Vulkan API.
 
+pub use self::begin_conditional_rendering::{CmdBeginConditionalRendering, CmdBeginConditionalRenderingError};
+pub use self::begin_debug_label::CmdBeginDebugLabel;
 pub use self::begin_render_pass::CmdBeginRenderPass;
-pub use self::bind_index_buffer::CmdBindIndexBuffer;
-pub use self::bind_descriptor_sets::{CmdBindDescriptorSets, CmdBindDescriptorSetsError};
+pub use self::bind_index_buffer::{CmdBindIndexBuffer, CmdBindIndexBufferError};
+pub use self::bind_descriptor_sets::{CmdBindDescriptorSets, CmdBindDescriptorSetsError, CmdBindDescriptorSetsHash};
 pub use self::bind_pipeline::{CmdBindPipeline, CmdBindPipelineSys};
 pub use self::bind_vertex_buffers::{CmdBindVertexBuffers, CmdBindVertexBuffersHash};
 pub use self::blit_image::{CmdBlitImage, CmdBlitImageError};
-pub use self::clear_attachments::CmdClearAttachments;
+pub use self::clear_attachments::{CmdClearAttachments, CmdClearAttachmentsError};
+pub use self::clear_attachments::{ClearAttachment, ClearRect};
+pub use self::compute_barrier::CmdComputeBarrier;
 pub use self::copy_buffer::{CmdCopyBuffer, CmdCopyBufferError};
 pub use self::copy_buffer_to_image::{CmdCopyBufferToImage, CmdCopyBufferToImageError};
 pub use self::copy_image::{CmdCopyImage, CmdCopyImageError};
+pub use self::copy_image_to_buffer::{CmdCopyImageToBuffer, CmdCopyImageToBufferError};
 pub use self::dispatch_raw::{CmdDispatchRaw, CmdDispatchRawError};
 pub use self::draw_indexed_raw::CmdDrawIndexedRaw;
 pub use self::draw_indirect_raw::CmdDrawIndirectRaw;
 pub use self::draw_raw::CmdDrawRaw;
+pub use self::end_conditional_rendering::CmdEndConditionalRendering;
+pub use self::end_debug_label::CmdEndDebugLabel;
 pub use self::end_render_pass::CmdEndRenderPass;
 pub use self::execute::CmdExecuteCommands;
 pub use self::fill_buffer::{CmdFillBuffer, CmdFillBufferError};
+pub use self::insert_debug_label::CmdInsertDebugLabel;
+pub use self::memory_barrier::{CmdBufferMemoryBarrier, CmdImageMemoryBarrier};
 pub use self::next_subpass::CmdNextSubpass;
 pub use self::pipeline_barrier::CmdPipelineBarrier;
 pub use self::push_constants::{CmdPushConstants, CmdPushConstantsError};
+pub use self::queue_ownership::{CmdBufferOwnershipTransfer, CmdImageOwnershipTransfer};
+pub use self::queue_ownership::CmdQueueOwnershipTransferError;
 pub use self::resolve_image::{CmdResolveImage, CmdResolveImageError};
 pub use self::set_event::CmdSetEvent;
-pub use self::set_state::{CmdSetState};
+pub use self::set_state::{CmdSetState, CmdSetStateError};
 pub use self::update_buffer::{CmdUpdateBuffer, CmdUpdateBufferError};
 
+mod begin_conditional_rendering;
+mod begin_debug_label;
 mod begin_render_pass;
 mod bind_descriptor_sets;
 mod bind_index_buffer;
@@ -43,19 +56,26 @@ mod bind_pipeline;
 mod bind_vertex_buffers;
 mod blit_image;
 mod clear_attachments;
+mod compute_barrier;
 mod copy_buffer;
 mod copy_buffer_to_image;
 mod copy_image;
+mod copy_image_to_buffer;
 mod dispatch_raw;
 mod draw_indexed_raw;
 mod draw_indirect_raw;
 mod draw_raw;
+mod end_conditional_rendering;
+mod end_debug_label;
 mod end_render_pass;
 mod execute;
 mod fill_buffer;
+mod insert_debug_label;
+mod memory_barrier;
 mod next_subpass;
 mod pipeline_barrier;
 mod push_constants;
+mod queue_ownership;
 mod resolve_image;
 mod set_event;
 mod set_state;
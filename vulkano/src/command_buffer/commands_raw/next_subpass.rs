@@ -8,6 +8,7 @@
 // according to those terms.
 
 use command_buffer::CommandAddError;
+use command_buffer::SubpassContents;
 use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
 use command_buffer::pool::CommandPool;
@@ -19,18 +20,23 @@ use vk;
 #[derive(Debug, Copy, Clone)]
 pub struct CmdNextSubpass{
     // The parameter for vkCmdNextSubpass.
-    contents: vk::SubpassContents,
+    contents: SubpassContents,
 }
 
 impl CmdNextSubpass {
     /// See the documentation of the `next_subpass` method.
     #[inline]
-    pub fn new(secondary: bool) -> CmdNextSubpass {
+    pub fn new(contents: SubpassContents) -> CmdNextSubpass {
         CmdNextSubpass {
-            contents: if secondary { vk::SUBPASS_CONTENTS_SECONDARY_COMMAND_BUFFERS }
-                      else { vk::SUBPASS_CONTENTS_INLINE }
+            contents: contents,
         }
     }
+
+    /// Returns the contents of the new subpass.
+    #[inline]
+    pub fn contents(&self) -> SubpassContents {
+        self.contents
+    }
 }
 
 unsafe impl<'a, P> AddCommand<&'a CmdNextSubpass> for UnsafeCommandBufferBuilder<P>
@@ -43,7 +49,7 @@ unsafe impl<'a, P> AddCommand<&'a CmdNextSubpass> for UnsafeCommandBufferBuilder
         unsafe {
             let vk = self.device().pointers();
             let cmd = self.internal_object();
-            vk.CmdNextSubpass(cmd, command.contents);
+            vk.CmdNextSubpass(cmd, command.contents as vk::SubpassContents);
         }
 
         Ok(self)
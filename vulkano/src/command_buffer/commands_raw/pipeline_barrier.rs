@@ -91,6 +91,8 @@ impl<'a> CmdPipelineBarrier<'a> {
     ///
     /// - If the pipeline stages include geometry or tessellation stages, then the corresponding
     ///   features must have been enabled in the device.
+    /// - If the pipeline stages include the conditional rendering stage, then the
+    ///   `ext_conditional_rendering` extension must have been enabled in the device.
     /// - There are certain rules regarding the pipeline barriers inside render passes.
     ///
     #[inline]
@@ -231,6 +233,26 @@ impl<'a> CmdPipelineBarrier<'a> {
             },
         });
     }
+
+    /// Adds an image memory barrier that transitions the layout of an image, with the accesses
+    /// returned by `AccessFlagBits::for_layout_transition`.
+    ///
+    /// # Safety
+    ///
+    /// Same as `add_image_memory_barrier`.
+    ///
+    #[inline]
+    pub unsafe fn add_image_layout_transition<I: ?Sized>(&mut self, image: &'a I,
+                  mipmaps: Range<u32>, layers: Range<u32>, source_stage: PipelineStages,
+                  dest_stage: PipelineStages, current_layout: ImageLayout, new_layout: ImageLayout)
+        where I: ImageAccess
+    {
+        let (source_access, dest_access) =
+            AccessFlagBits::for_layout_transition(current_layout, new_layout);
+        self.add_image_memory_barrier(image, mipmaps, layers, source_stage, source_access,
+                                      dest_stage, dest_access, false, None, current_layout,
+                                      new_layout);
+    }
 }
 
 unsafe impl<'a, P> AddCommand<&'a CmdPipelineBarrier<'a>> for UnsafeCommandBufferBuilder<P>
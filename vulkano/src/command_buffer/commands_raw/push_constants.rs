@@ -9,13 +9,16 @@
 
 use std::error;
 use std::fmt;
+use std::mem;
 use std::sync::Arc;
 
 use command_buffer::CommandAddError;
 use command_buffer::cb::AddCommand;
 use command_buffer::cb::UnsafeCommandBufferBuilder;
 use command_buffer::pool::CommandPool;
+use descriptor::descriptor::ShaderStages;
 use descriptor::pipeline_layout::PipelineLayoutAbstract;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
 use descriptor::pipeline_layout::PipelineLayoutPushConstantsCompatible;
 use device::Device;
 use device::DeviceOwned;
@@ -30,6 +33,9 @@ pub struct CmdPushConstants<Pc, Pl> {
     push_constants: Pc,
     // The pipeline layout.
     pipeline_layout: Pl,
+    // If `Some`, the data is written to the given stages at the given offset. Otherwise the data
+    // covers all the push constant ranges of the layout.
+    range: Option<(ShaderStages, usize)>,
 }
 
 impl<Pc, Pl> CmdPushConstants<Pc, Pl>
@@ -37,25 +43,100 @@ impl<Pc, Pl> CmdPushConstants<Pc, Pl>
 {
     /// Builds the command.
     ///
-    /// Returns an error if the push constants are not compatible with the pipeline layout.
+    /// Returns an error if the push constants are not compatible with the pipeline layout. This
+    /// is only checked if the recording validation of the device is enabled.
     #[inline]
     pub fn new(pipeline_layout: Pl, push_constants: Pc)
                -> Result<CmdPushConstants<Pc, Pl>, CmdPushConstantsError> 
     {
-        if !PipelineLayoutPushConstantsCompatible::is_compatible(&pipeline_layout, &push_constants) {
+        let device = pipeline_layout.device().clone();
+
+        if device.recording_validation() &&
+           !PipelineLayoutPushConstantsCompatible::is_compatible(&pipeline_layout, &push_constants)
+        {
             return Err(CmdPushConstantsError::IncompatibleData);
         }
 
+        Ok(CmdPushConstants {
+            device: device,
+            push_constants: push_constants,
+            pipeline_layout: pipeline_layout,
+            range: None,
+        })
+    }
+
+    /// Builds a command that only updates a part of the push constants.
+    ///
+    /// The content of `push_constants` is written at `offset` bytes from the start of the push
+    /// constants, for the given shader stages. The rest of the push constants are left untouched.
+    ///
+    /// Returns an error if the offset or the size of the data is not a multiple of 4, if the
+    /// range is not entirely covered by push constant ranges of the layout that include all of
+    /// `stages`, or if a push constant range of the layout that overlaps the range is accessible
+    /// from stages that are not in `stages`.
+    pub fn with_range(pipeline_layout: Pl, stages: ShaderStages, offset: usize,
+                      push_constants: Pc)
+                      -> Result<CmdPushConstants<Pc, Pl>, CmdPushConstantsError>
+    {
+        try!(check_range(&pipeline_layout, stages, offset, mem::size_of::<Pc>()));
+
         let device = pipeline_layout.device().clone();
 
         Ok(CmdPushConstants {
             device: device,
             push_constants: push_constants,
             pipeline_layout: pipeline_layout,
+            range: Some((stages, offset)),
         })
     }
 }
 
+// Checks that the range of push constants `offset .. offset + size` can be updated for `stages`.
+//
+// Each 4-bytes word of the range must be covered, for each stage of `stages`, by a range of the
+// layout. Additionally each range of the layout that overlaps a word must not be accessible from
+// a stage outside of `stages`.
+fn check_range<Pl: ?Sized>(pipeline_layout: &Pl, stages: ShaderStages, offset: usize,
+                           size: usize) -> Result<(), CmdPushConstantsError>
+    where Pl: PipelineLayoutDesc
+{
+    if offset % 4 != 0 || size % 4 != 0 {
+        return Err(CmdPushConstantsError::UnalignedRange);
+    }
+
+    if size == 0 || stages == ShaderStages::none() {
+        return Err(CmdPushConstantsError::EmptyRange);
+    }
+
+    for word in (offset / 4) .. ((offset + size) / 4) {
+        let byte = word * 4;
+        let mut covered = ShaderStages::none();
+
+        for num_range in 0 .. pipeline_layout.num_push_constants_ranges() {
+            let range = match pipeline_layout.push_constants_range(num_range) {
+                Some(r) => r,
+                None => continue
+            };
+
+            if byte < range.offset || byte >= range.offset + range.size {
+                continue;
+            }
+
+            if !stages.is_superset_of(&range.stages) {
+                return Err(CmdPushConstantsError::StagesMismatch);
+            }
+
+            covered = covered | range.stages;
+        }
+
+        if !covered.is_superset_of(&stages) {
+            return Err(CmdPushConstantsError::RangeNotCovered);
+        }
+    }
+
+    Ok(())
+}
+
 unsafe impl<Pc, Pl> DeviceOwned for CmdPushConstants<Pc, Pl> {
     #[inline]
     fn device(&self) -> &Arc<Device> {
@@ -76,7 +157,14 @@ unsafe impl<'a, P, Pc, Pl> AddCommand<&'a CmdPushConstants<Pc, Pl>> for UnsafeCo
             let cmd = self.internal_object();
 
             let data_raw = &command.push_constants as *const Pc as *const u8;
-            
+
+            if let Some((stages, offset)) = command.range {
+                vk.CmdPushConstants(cmd, command.pipeline_layout.sys().internal_object(),
+                                    stages.into(), offset as u32, mem::size_of::<Pc>() as u32,
+                                    data_raw as *const _);
+                return Ok(self);
+            }
+
             for num_range in 0 .. command.pipeline_layout.num_push_constants_ranges() {
                 let range = match command.pipeline_layout.push_constants_range(num_range) {
                     Some(r) => r,
@@ -102,6 +190,20 @@ pub enum CmdPushConstantsError {
     /// The push constants are not compatible with the pipeline layout.
     // TODO: inner error
     IncompatibleData,
+
+    /// The offset or the size of the range of push constants is not a multiple of 4.
+    UnalignedRange,
+
+    /// The range of push constants is empty or doesn't have any shader stage.
+    EmptyRange,
+
+    /// Part of the range of push constants isn't covered by the push constant ranges of the
+    /// pipeline layout for all the requested shader stages.
+    RangeNotCovered,
+
+    /// A push constant range of the pipeline layout that overlaps the range of push constants is
+    /// accessible from shader stages that were not requested.
+    StagesMismatch,
 }
 
 impl error::Error for CmdPushConstantsError {
@@ -111,6 +213,20 @@ impl error::Error for CmdPushConstantsError {
             CmdPushConstantsError::IncompatibleData => {
                 "the push constants are not compatible with the pipeline layout"
             },
+            CmdPushConstantsError::UnalignedRange => {
+                "the offset or the size of the range of push constants is not a multiple of 4"
+            },
+            CmdPushConstantsError::EmptyRange => {
+                "the range of push constants is empty or doesn't have any shader stage"
+            },
+            CmdPushConstantsError::RangeNotCovered => {
+                "part of the range of push constants isn't covered by the pipeline layout for \
+                 all the requested shader stages"
+            },
+            CmdPushConstantsError::StagesMismatch => {
+                "a push constant range of the pipeline layout that overlaps the range is \
+                 accessible from shader stages that were not requested"
+            },
         }
     }
 }
@@ -121,3 +237,71 @@ impl fmt::Display for CmdPushConstantsError {
         write!(fmt, "{}", error::Error::description(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::check_range;
+    use command_buffer::commands_raw::CmdPushConstantsError;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+
+    // A vertex range at `0 .. 16`, and a fragment range at `16 .. 32`.
+    struct TwoRanges;
+    unsafe impl PipelineLayoutDesc for TwoRanges {
+        fn num_sets(&self) -> usize { 0 }
+        fn num_bindings_in_set(&self, _: usize) -> Option<usize> { None }
+        fn descriptor(&self, _: usize, _: usize) -> Option<DescriptorDesc> { None }
+        fn num_push_constants_ranges(&self) -> usize { 2 }
+        fn push_constants_range(&self, num: usize) -> Option<PipelineLayoutDescPcRange> {
+            match num {
+                0 => Some(PipelineLayoutDescPcRange {
+                    offset: 0,
+                    size: 16,
+                    stages: ShaderStages { vertex: true, .. ShaderStages::none() },
+                }),
+                1 => Some(PipelineLayoutDescPcRange {
+                    offset: 16,
+                    size: 16,
+                    stages: ShaderStages { fragment: true, .. ShaderStages::none() },
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn valid_range() {
+        let fragment = ShaderStages { fragment: true, .. ShaderStages::none() };
+        assert!(check_range(&TwoRanges, fragment, 20, 8).is_ok());
+    }
+
+    #[test]
+    fn unaligned() {
+        let vertex = ShaderStages { vertex: true, .. ShaderStages::none() };
+        match check_range(&TwoRanges, vertex, 2, 4) {
+            Err(CmdPushConstantsError::UnalignedRange) => (),
+            _ => panic!()
+        };
+        match check_range(&TwoRanges, vertex, 0, 6) {
+            Err(CmdPushConstantsError::UnalignedRange) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn not_covered() {
+        let vertex = ShaderStages { vertex: true, .. ShaderStages::none() };
+        match check_range(&TwoRanges, vertex, 28, 8) {
+            Err(CmdPushConstantsError::StagesMismatch) => (),
+            _ => panic!()
+        };
+
+        let fragment = ShaderStages { fragment: true, .. ShaderStages::none() };
+        match check_range(&TwoRanges, fragment, 28, 8) {
+            Err(CmdPushConstantsError::RangeNotCovered) => (),
+            _ => panic!()
+        };
+    }
+}
@@ -0,0 +1,390 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use command_buffer::CommandAddError;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::UnsafeCommandBufferBuilder;
+use command_buffer::commands_raw::CmdPipelineBarrier;
+use command_buffer::pool::CommandPool;
+use device::Device;
+use device::DeviceOwned;
+use image::ImageAccess;
+use image::ImageLayout;
+use instance::QueueFamily;
+use sync::AccessFlagBits;
+use sync::PipelineStages;
+
+/// Command that transfers the ownership of a buffer from one queue family to another.
+///
+/// A resource created with the `Exclusive` sharing mode can only be accessed by one queue family
+/// at a time. Transferring it to another queue family requires two barriers: a *release* barrier
+/// recorded in a command buffer submitted to the source queue family, followed by an *acquire*
+/// barrier recorded in a command buffer submitted to the destination queue family. Both barriers
+/// must use the same pair of queue families, and the submission that contains the acquire
+/// barrier must wait for the one that contains the release barrier, for example with a
+/// semaphore.
+///
+/// The content of the buffer is undefined after the transfer if only one of the two barriers is
+/// executed.
+pub struct CmdBufferOwnershipTransfer<B> {
+    // The buffer whose ownership is transferred.
+    buffer: B,
+    // Ownership transfer.
+    transfer: OwnershipTransfer,
+}
+
+impl<B> CmdBufferOwnershipTransfer<B> where B: BufferAccess {
+    /// Builds the release half of an ownership transfer of `buffer`, to be recorded in a command
+    /// buffer of the `source` queue family.
+    ///
+    /// `stages` and `access` are the stages and the accesses of the commands that previously
+    /// used the buffer on the source queue family.
+    #[inline]
+    pub fn release(buffer: B, source: QueueFamily, destination: QueueFamily,
+                   stages: PipelineStages, access: AccessFlagBits)
+                   -> Result<CmdBufferOwnershipTransfer<B>, CmdQueueOwnershipTransferError>
+    {
+        let transfer = try!(OwnershipTransfer::release(buffer.device(), source,
+                                                       destination, stages, access));
+
+        Ok(CmdBufferOwnershipTransfer {
+            buffer: buffer,
+            transfer: transfer,
+        })
+    }
+
+    /// Builds the acquire half of an ownership transfer of `buffer`, to be recorded in a command
+    /// buffer of the `destination` queue family.
+    ///
+    /// `stages` and `access` are the stages and the accesses of the commands that are going to
+    /// use the buffer on the destination queue family.
+    #[inline]
+    pub fn acquire(buffer: B, source: QueueFamily, destination: QueueFamily,
+                   stages: PipelineStages, access: AccessFlagBits)
+                   -> Result<CmdBufferOwnershipTransfer<B>, CmdQueueOwnershipTransferError>
+    {
+        let transfer = try!(OwnershipTransfer::acquire(buffer.device(), source,
+                                                       destination, stages, access));
+
+        Ok(CmdBufferOwnershipTransfer {
+            buffer: buffer,
+            transfer: transfer,
+        })
+    }
+}
+
+impl<B> CmdBufferOwnershipTransfer<B> {
+    /// Returns the buffer whose ownership is transferred.
+    #[inline]
+    pub fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Returns true if this is the release half of the transfer.
+    #[inline]
+    pub fn is_release(&self) -> bool {
+        self.transfer.release
+    }
+
+    /// Returns the stages that were passed when building the command.
+    #[inline]
+    pub fn stages(&self) -> PipelineStages {
+        self.transfer.stages()
+    }
+
+    /// Returns the accesses that were passed when building the command.
+    #[inline]
+    pub fn access(&self) -> AccessFlagBits {
+        self.transfer.access()
+    }
+}
+
+unsafe impl<B> DeviceOwned for CmdBufferOwnershipTransfer<B> where B: DeviceOwned {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.buffer.device()
+    }
+}
+
+unsafe impl<'a, P, B> AddCommand<&'a CmdBufferOwnershipTransfer<B>> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool, B: BufferAccess
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdBufferOwnershipTransfer<B>) -> Result<Self::Out, CommandAddError> {
+        let t = &command.transfer;
+        let mut barrier = CmdPipelineBarrier::new();
+
+        unsafe {
+            barrier.add_buffer_memory_barrier(&command.buffer, t.src_stages, t.src_access,
+                                              t.dst_stages, t.dst_access, false,
+                                              Some((t.src_family, t.dst_family)), 0,
+                                              command.buffer.size());
+        }
+
+        AddCommand::add(self, &barrier)
+    }
+}
+
+/// Command that transfers the ownership of an image from one queue family to another.
+///
+/// This is the equivalent of `CmdBufferOwnershipTransfer` for images. The layout of the image
+/// is left unchanged and is assumed to be its initial layout requirement.
+pub struct CmdImageOwnershipTransfer<I> {
+    // The image whose ownership is transferred.
+    image: I,
+    // Layout of the image before and after the transfer.
+    layout: ImageLayout,
+    // Ownership transfer.
+    transfer: OwnershipTransfer,
+}
+
+impl<I> CmdImageOwnershipTransfer<I> where I: ImageAccess {
+    /// Builds the release half of an ownership transfer of `image`, to be recorded in a command
+    /// buffer of the `source` queue family.
+    ///
+    /// `stages` and `access` are the stages and the accesses of the commands that previously
+    /// used the image on the source queue family.
+    #[inline]
+    pub fn release(image: I, source: QueueFamily, destination: QueueFamily,
+                   stages: PipelineStages, access: AccessFlagBits)
+                   -> Result<CmdImageOwnershipTransfer<I>, CmdQueueOwnershipTransferError>
+    {
+        let transfer = try!(OwnershipTransfer::release(image.inner().device(), source,
+                                                       destination, stages, access));
+
+        Ok(CmdImageOwnershipTransfer {
+            layout: image.initial_layout_requirement(),
+            image: image,
+            transfer: transfer,
+        })
+    }
+
+    /// Builds the acquire half of an ownership transfer of `image`, to be recorded in a command
+    /// buffer of the `destination` queue family.
+    ///
+    /// `stages` and `access` are the stages and the accesses of the commands that are going to
+    /// use the image on the destination queue family.
+    #[inline]
+    pub fn acquire(image: I, source: QueueFamily, destination: QueueFamily,
+                   stages: PipelineStages, access: AccessFlagBits)
+                   -> Result<CmdImageOwnershipTransfer<I>, CmdQueueOwnershipTransferError>
+    {
+        let transfer = try!(OwnershipTransfer::acquire(image.inner().device(), source,
+                                                       destination, stages, access));
+
+        Ok(CmdImageOwnershipTransfer {
+            layout: image.initial_layout_requirement(),
+            image: image,
+            transfer: transfer,
+        })
+    }
+}
+
+impl<I> CmdImageOwnershipTransfer<I> {
+    /// Returns the image whose ownership is transferred.
+    #[inline]
+    pub fn image(&self) -> &I {
+        &self.image
+    }
+
+    /// Returns true if this is the release half of the transfer.
+    #[inline]
+    pub fn is_release(&self) -> bool {
+        self.transfer.release
+    }
+
+    /// Returns the stages that were passed when building the command.
+    #[inline]
+    pub fn stages(&self) -> PipelineStages {
+        self.transfer.stages()
+    }
+
+    /// Returns the accesses that were passed when building the command.
+    #[inline]
+    pub fn access(&self) -> AccessFlagBits {
+        self.transfer.access()
+    }
+}
+
+unsafe impl<I> DeviceOwned for CmdImageOwnershipTransfer<I> where I: ImageAccess {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.inner().device()
+    }
+}
+
+unsafe impl<'a, P, I> AddCommand<&'a CmdImageOwnershipTransfer<I>> for UnsafeCommandBufferBuilder<P>
+    where P: CommandPool, I: ImageAccess
+{
+    type Out = UnsafeCommandBufferBuilder<P>;
+
+    #[inline]
+    fn add(self, command: &'a CmdImageOwnershipTransfer<I>) -> Result<Self::Out, CommandAddError> {
+        let t = &command.transfer;
+        let mut barrier = CmdPipelineBarrier::new();
+
+        unsafe {
+            barrier.add_image_memory_barrier(&command.image, 0 .. command.image.mipmap_levels(),
+                                             0 .. command.image.dimensions().array_layers(),
+                                             t.src_stages, t.src_access, t.dst_stages,
+                                             t.dst_access, false,
+                                             Some((t.src_family, t.dst_family)),
+                                             command.layout, command.layout);
+        }
+
+        AddCommand::add(self, &barrier)
+    }
+}
+
+// Stages, accesses and queue families of one half of an ownership transfer.
+#[derive(Debug, Copy, Clone)]
+struct OwnershipTransfer {
+    release: bool,
+    src_family: u32,
+    dst_family: u32,
+    src_stages: PipelineStages,
+    src_access: AccessFlagBits,
+    dst_stages: PipelineStages,
+    dst_access: AccessFlagBits,
+}
+
+impl OwnershipTransfer {
+    // Stages of the commands that use the resource on the queue family that records this half.
+    #[inline]
+    fn stages(&self) -> PipelineStages {
+        if self.release { self.src_stages } else { self.dst_stages }
+    }
+
+    // Accesses of the commands that use the resource on the queue family that records this half.
+    #[inline]
+    fn access(&self) -> AccessFlagBits {
+        if self.release { self.src_access } else { self.dst_access }
+    }
+
+    // For the release barrier, the destination access mask is ignored by the implementation. We
+    // use the bottom of the pipe so that the barrier doesn't block any following command.
+    fn release(device: &Device, source: QueueFamily, destination: QueueFamily,
+               stages: PipelineStages, access: AccessFlagBits)
+               -> Result<OwnershipTransfer, CmdQueueOwnershipTransferError>
+    {
+        try!(check_families(device, source, destination));
+
+        Ok(OwnershipTransfer {
+            release: true,
+            src_family: source.id(),
+            dst_family: destination.id(),
+            src_stages: stages,
+            src_access: access,
+            dst_stages: PipelineStages { bottom_of_pipe: true, .. PipelineStages::none() },
+            dst_access: AccessFlagBits::none(),
+        })
+    }
+
+    // For the acquire barrier, the source access mask is ignored by the implementation. We use
+    // the top of the pipe so that the barrier doesn't wait for any previous command.
+    fn acquire(device: &Device, source: QueueFamily, destination: QueueFamily,
+               stages: PipelineStages, access: AccessFlagBits)
+               -> Result<OwnershipTransfer, CmdQueueOwnershipTransferError>
+    {
+        try!(check_families(device, source, destination));
+
+        Ok(OwnershipTransfer {
+            release: false,
+            src_family: source.id(),
+            dst_family: destination.id(),
+            src_stages: PipelineStages { top_of_pipe: true, .. PipelineStages::none() },
+            src_access: AccessFlagBits::none(),
+            dst_stages: stages,
+            dst_access: access,
+        })
+    }
+}
+
+// Checks that the two queue families are different and belong to the physical device of `device`.
+fn check_families(device: &Device, source: QueueFamily, destination: QueueFamily)
+                  -> Result<(), CmdQueueOwnershipTransferError>
+{
+    let physical_device = device.physical_device().index();
+    if source.physical_device().index() != physical_device ||
+       destination.physical_device().index() != physical_device
+    {
+        return Err(CmdQueueOwnershipTransferError::WrongPhysicalDevice);
+    }
+
+    if source.id() == destination.id() {
+        return Err(CmdQueueOwnershipTransferError::SameQueueFamily);
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when creating a queue ownership transfer command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmdQueueOwnershipTransferError {
+    /// The source and destination queue families are the same.
+    SameQueueFamily,
+    /// One of the queue families doesn't belong to the physical device of the resource.
+    WrongPhysicalDevice,
+}
+
+impl error::Error for CmdQueueOwnershipTransferError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdQueueOwnershipTransferError::SameQueueFamily => {
+                "the source and destination queue families are the same"
+            },
+            CmdQueueOwnershipTransferError::WrongPhysicalDevice => {
+                "one of the queue families doesn't belong to the physical device of the resource"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CmdQueueOwnershipTransferError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::commands_raw::CmdBufferOwnershipTransfer;
+    use command_buffer::commands_raw::CmdQueueOwnershipTransferError;
+    use sync::AccessFlagBits;
+    use sync::PipelineStages;
+
+    #[test]
+    fn same_queue_family() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        let stages = PipelineStages { transfer: true, .. PipelineStages::none() };
+        let access = AccessFlagBits { transfer_write: true, .. AccessFlagBits::none() };
+
+        match CmdBufferOwnershipTransfer::release(buffer, queue.family(), queue.family(), stages,
+                                                  access)
+        {
+            Err(CmdQueueOwnershipTransferError::SameQueueFamily) => (),
+            _ => panic!()
+        };
+    }
+}
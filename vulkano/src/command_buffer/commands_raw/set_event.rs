@@ -15,6 +15,7 @@ use command_buffer::pool::CommandPool;
 use device::Device;
 use device::DeviceOwned;
 use sync::Event;
+use sync::PipelineStages;
 use VulkanObject;
 use vk;
 
@@ -25,11 +26,63 @@ pub struct CmdSetEvent {
     event: Arc<Event>,
     // The pipeline stages after which the event should be set or reset.
     stages: vk::PipelineStageFlags,
-    // If true calls `vkCmdSetEvent`, otherwise `vkCmdSetEvent`.
+    // If true calls `vkCmdSetEvent`, otherwise `vkCmdResetEvent`.
     set: bool,
 }
 
-// TODO: add constructor
+impl CmdSetEvent {
+    /// Builds a command that sets `event` once all the previous commands have finished executing
+    /// `stages`.
+    ///
+    /// If `stages` is empty, the event is set after the bottom of the pipe.
+    #[inline]
+    pub fn set(event: Arc<Event>, stages: PipelineStages) -> CmdSetEvent {
+        CmdSetEvent::new(event, stages, true)
+    }
+
+    /// Builds a command that resets `event` once all the previous commands have finished
+    /// executing `stages`.
+    ///
+    /// If `stages` is empty, the event is reset after the bottom of the pipe.
+    #[inline]
+    pub fn reset(event: Arc<Event>, stages: PipelineStages) -> CmdSetEvent {
+        CmdSetEvent::new(event, stages, false)
+    }
+
+    #[inline]
+    fn new(event: Arc<Event>, stages: PipelineStages, set: bool) -> CmdSetEvent {
+        // A stage mask of zero is invalid.
+        let stages = if stages.is_empty() {
+            PipelineStages { bottom_of_pipe: true, .. PipelineStages::none() }
+        } else {
+            stages
+        };
+
+        CmdSetEvent {
+            event: event,
+            stages: stages.into(),
+            set: set,
+        }
+    }
+
+    /// Returns the event that is set or reset.
+    #[inline]
+    pub fn event(&self) -> &Arc<Event> {
+        &self.event
+    }
+
+    /// Returns the stages after which the event is set or reset.
+    #[inline]
+    pub fn stages(&self) -> PipelineStages {
+        PipelineStages::from(self.stages)
+    }
+
+    /// Returns true if the command sets the event, and false if it resets it.
+    #[inline]
+    pub fn is_set(&self) -> bool {
+        self.set
+    }
+}
 
 unsafe impl DeviceOwned for CmdSetEvent {
     #[inline]
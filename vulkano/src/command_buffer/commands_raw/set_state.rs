@@ -7,6 +7,8 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
 use std::sync::Arc;
 use smallvec::SmallVec;
 
@@ -34,9 +36,27 @@ impl CmdSetState {
     ///
     /// Since this command checks whether the dynamic state is supported by the device, you have
     /// to pass the device as well when building the command.
-    // TODO: should check the limits and features of the device
-    pub fn new(device: Arc<Device>, state: DynamicState) -> CmdSetState {
-        CmdSetState {
+    ///
+    /// Returns an error if the line width is not `1.0` and the `wide_lines` feature is not
+    /// enabled, or if a viewport has a negative height and the `VK_KHR_maintenance1` extension
+    /// is not enabled.
+    // TODO: should check the limits and other features of the device
+    pub fn new(device: Arc<Device>, state: DynamicState) -> Result<CmdSetState, CmdSetStateError> {
+        if let Some(line_width) = state.line_width {
+            if line_width != 1.0 && !device.enabled_features().wide_lines {
+                return Err(CmdSetStateError::WideLinesFeatureNotEnabled);
+            }
+        }
+
+        if let Some(ref viewports) = state.viewports {
+            if !device.loaded_extensions().khr_maintenance1 &&
+               viewports.iter().any(|vp| vp.dimensions[1] < 0.0)
+            {
+                return Err(CmdSetStateError::NegativeViewportHeightNotSupported);
+            }
+        }
+
+        Ok(CmdSetState {
             device: device,
             dynamic_state: DynamicState {
                 // This constructor is explicitely layed out so that we don't forget to
@@ -45,7 +65,7 @@ impl CmdSetState {
                 viewports: state.viewports,
                 scissors: state.scissors,
             },
-        }
+        })
     }
 
     #[inline]
@@ -96,3 +116,75 @@ unsafe impl<'a, P> AddCommand<&'a CmdSetState> for UnsafeCommandBufferBuilder<P>
         Ok(self)
     }
 }
+
+/// Error that can happen when creating a `CmdSetState`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmdSetStateError {
+    /// The `wide_lines` feature must be enabled in order to use a line width other than 1.0.
+    WideLinesFeatureNotEnabled,
+    /// A viewport has a negative height, which requires the `VK_KHR_maintenance1` extension.
+    NegativeViewportHeightNotSupported,
+}
+
+impl error::Error for CmdSetStateError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CmdSetStateError::WideLinesFeatureNotEnabled => {
+                "the `wide_lines` feature must be enabled in order to use a line width other \
+                 than 1.0"
+            },
+            CmdSetStateError::NegativeViewportHeightNotSupported => {
+                "a viewport has a negative height, which requires the `VK_KHR_maintenance1` \
+                 extension"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CmdSetStateError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::DynamicState;
+    use pipeline::viewport::Viewport;
+    use super::CmdSetState;
+    use super::CmdSetStateError;
+
+    #[test]
+    fn negative_viewport_height() {
+        let (device, _) = gfx_dev_and_queue!();
+        assert!(!device.loaded_extensions().khr_maintenance1);
+
+        let state = DynamicState {
+            viewports: Some(vec![Viewport {
+                origin: [0.0, 64.0],
+                dimensions: [64.0, -64.0],
+                depth_range: 0.0 .. 1.0,
+            }]),
+            .. DynamicState::none()
+        };
+
+        match CmdSetState::new(device, state) {
+            Err(CmdSetStateError::NegativeViewportHeightNotSupported) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn wide_lines_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let state = DynamicState { line_width: Some(2.0), .. DynamicState::none() };
+
+        match CmdSetState::new(device, state) {
+            Err(CmdSetStateError::WideLinesFeatureNotEnabled) => (),
+            _ => panic!()
+        }
+    }
+}
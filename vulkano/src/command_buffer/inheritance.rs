@@ -0,0 +1,311 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::ptr;
+
+use command_buffer::cb::Kind;
+use device::Device;
+use framebuffer::EmptySinglePassRenderPassDesc;
+use framebuffer::Framebuffer;
+use framebuffer::FramebufferAbstract;
+use framebuffer::RenderPass;
+use framebuffer::RenderPassAbstract;
+use framebuffer::Subpass;
+use query::QueryPipelineStatisticFlags;
+use OomError;
+use VulkanObject;
+use vk;
+
+/// Describes what a secondary command buffer inherits from the primary command buffer that
+/// executes it.
+///
+/// A secondary command buffer is either scoped to a subpass of a render pass, in which case it
+/// can only be executed inside this subpass and can only contain commands that are allowed inside
+/// a render pass, or not scoped to a render pass, in which case it can only be executed outside of
+/// render passes. Use `new()` for the latter, which is what you want for compute and transfer
+/// operations, and `render_pass()` for the former.
+///
+/// Additionally, the secondary command buffer can be executed while an occlusion query or a
+/// pipeline statistics query is active in the primary command buffer, if you enable it here.
+///
+/// ```
+/// use vulkano::command_buffer::CommandBufferInheritance;
+///
+/// // A secondary command buffer that is executed outside of render passes, but within an
+/// // occlusion query.
+/// let inheritance = CommandBufferInheritance::new().occlusion_query(false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandBufferInheritance<R, F> {
+    // The subpass and the optional framebuffer, if the command buffer is scoped to a render pass.
+    render_pass: Option<(Subpass<R>, Option<F>)>,
+    // `Some` if the command buffer can be executed while an occlusion query is active. Contains
+    // true if the query is allowed to be precise.
+    occlusion_query: Option<bool>,
+    // The pipeline statistics that can be active when the command buffer is executed.
+    pipeline_statistics: QueryPipelineStatisticFlags,
+}
+
+impl CommandBufferInheritance<RenderPass<EmptySinglePassRenderPassDesc>,
+                              Framebuffer<RenderPass<EmptySinglePassRenderPassDesc>, ()>>
+{
+    /// Builds a `CommandBufferInheritance` for a secondary command buffer that is executed
+    /// outside of render passes. No query is inherited.
+    #[inline]
+    pub fn new() -> CommandBufferInheritance<RenderPass<EmptySinglePassRenderPassDesc>,
+                                             Framebuffer<RenderPass<EmptySinglePassRenderPassDesc>, ()>>
+    {
+        CommandBufferInheritance {
+            render_pass: None,
+            occlusion_query: None,
+            pipeline_statistics: QueryPipelineStatisticFlags::none(),
+        }
+    }
+}
+
+impl<R, F> CommandBufferInheritance<R, F> {
+    /// Builds a `CommandBufferInheritance` for a secondary command buffer that is executed inside
+    /// of `subpass`. No query is inherited.
+    ///
+    /// The framebuffer that will be used when executing the command buffer is optional and is an
+    /// optimization hint for the implementation.
+    #[inline]
+    pub fn render_pass(subpass: Subpass<R>, framebuffer: Option<F>)
+                       -> CommandBufferInheritance<R, F>
+    {
+        CommandBufferInheritance {
+            render_pass: Some((subpass, framebuffer)),
+            occlusion_query: None,
+            pipeline_statistics: QueryPipelineStatisticFlags::none(),
+        }
+    }
+
+    /// Allows the command buffer to be executed while an occlusion query is active. If `precise`
+    /// is true, the active query is allowed to be precise.
+    ///
+    /// Requires the `inherited_queries` feature, and the `occlusion_query_precise` feature if
+    /// `precise` is true.
+    #[inline]
+    pub fn occlusion_query(mut self, precise: bool) -> CommandBufferInheritance<R, F> {
+        self.occlusion_query = Some(precise);
+        self
+    }
+
+    /// Allows the command buffer to be executed while a pipeline statistics query that counts
+    /// `flags` is active.
+    ///
+    /// Requires the `pipeline_statistics_query` feature if `flags` is not empty.
+    #[inline]
+    pub fn pipeline_statistics(mut self, flags: QueryPipelineStatisticFlags)
+                               -> CommandBufferInheritance<R, F>
+    {
+        self.pipeline_statistics = flags;
+        self
+    }
+
+    /// Returns the subpass the command buffer is scoped to, or `None` if it is executed outside
+    /// of render passes.
+    #[inline]
+    pub fn subpass(&self) -> Option<&Subpass<R>> {
+        self.render_pass.as_ref().map(|&(ref subpass, _)| subpass)
+    }
+
+    /// Returns true if the command buffer can be executed while an occlusion query is active.
+    #[inline]
+    pub fn inherits_occlusion_query(&self) -> bool {
+        self.occlusion_query.is_some()
+    }
+
+    /// Checks whether the features required by this inheritance are enabled on `device`.
+    pub fn check(&self, device: &Device) -> Result<(), CommandBufferInheritanceError> {
+        let features = device.enabled_features();
+
+        if let Some(precise) = self.occlusion_query {
+            if !features.inherited_queries {
+                return Err(CommandBufferInheritanceError::InheritedQueriesFeatureNotEnabled);
+            }
+            if precise && !features.occlusion_query_precise {
+                return Err(CommandBufferInheritanceError::OcclusionQueryPreciseFeatureNotEnabled);
+            }
+        }
+
+        let statistics: vk::QueryPipelineStatisticFlags = self.pipeline_statistics.into();
+        if statistics != 0 && !features.pipeline_statistics_query {
+            return Err(CommandBufferInheritanceError::PipelineStatisticsQueryFeatureNotEnabled);
+        }
+
+        Ok(())
+    }
+}
+
+impl<R, F> CommandBufferInheritance<R, F>
+    where R: RenderPassAbstract, F: FramebufferAbstract
+{
+    // Returns the usage flags that this inheritance adds to the command buffer.
+    #[inline]
+    pub(crate) fn usage_flags(&self) -> vk::CommandBufferUsageFlags {
+        if self.render_pass.is_some() {
+            vk::COMMAND_BUFFER_USAGE_RENDER_PASS_CONTINUE_BIT
+        } else {
+            0
+        }
+    }
+
+    // Builds the Vulkan struct. The render pass and framebuffer fields are only set for command
+    // buffers that are scoped to a render pass, as required by the specs.
+    pub(crate) fn to_vulkan(&self) -> vk::CommandBufferInheritanceInfo {
+        let (render_pass, subpass, framebuffer) = match self.render_pass {
+            Some((ref subpass, ref framebuffer)) => {
+                // TODO: check that the framebuffer is compatible with the subpass
+                let framebuffer = match *framebuffer {
+                    Some(ref framebuffer) => {
+                        FramebufferAbstract::inner(framebuffer).internal_object()
+                    },
+                    None => 0,
+                };
+
+                (subpass.render_pass().inner().internal_object(), subpass.index(), framebuffer)
+            },
+            None => (0, 0, 0),
+        };
+
+        let (occlusion_query_enable, query_flags) = match self.occlusion_query {
+            Some(true) => (vk::TRUE, vk::QUERY_CONTROL_PRECISE_BIT),
+            Some(false) => (vk::TRUE, 0),
+            None => (vk::FALSE, 0),
+        };
+
+        vk::CommandBufferInheritanceInfo {
+            sType: vk::STRUCTURE_TYPE_COMMAND_BUFFER_INHERITANCE_INFO,
+            pNext: ptr::null(),
+            renderPass: render_pass,
+            subpass: subpass,
+            framebuffer: framebuffer,
+            occlusionQueryEnable: occlusion_query_enable,
+            queryFlags: query_flags,
+            pipelineStatistics: self.pipeline_statistics.into(),
+        }
+    }
+}
+
+impl<R, F> From<Kind<R, F>> for CommandBufferInheritance<R, F> {
+    #[inline]
+    fn from(kind: Kind<R, F>) -> CommandBufferInheritance<R, F> {
+        let render_pass = match kind {
+            Kind::Primary | Kind::Secondary => None,
+            Kind::SecondaryRenderPass { subpass, framebuffer } => Some((subpass, framebuffer)),
+        };
+
+        CommandBufferInheritance {
+            render_pass: render_pass,
+            occlusion_query: None,
+            pipeline_statistics: QueryPipelineStatisticFlags::none(),
+        }
+    }
+}
+
+/// Error that can happen when creating a secondary command buffer with a
+/// `CommandBufferInheritance`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandBufferInheritanceError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// Inheriting an occlusion query requires the `inherited_queries` feature.
+    InheritedQueriesFeatureNotEnabled,
+    /// Inheriting a precise occlusion query requires the `occlusion_query_precise` feature.
+    OcclusionQueryPreciseFeatureNotEnabled,
+    /// Inheriting pipeline statistics requires the `pipeline_statistics_query` feature.
+    PipelineStatisticsQueryFeatureNotEnabled,
+}
+
+impl error::Error for CommandBufferInheritanceError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CommandBufferInheritanceError::OomError(_) => "not enough memory available",
+            CommandBufferInheritanceError::InheritedQueriesFeatureNotEnabled => {
+                "inheriting an occlusion query requires the `inherited_queries` feature"
+            },
+            CommandBufferInheritanceError::OcclusionQueryPreciseFeatureNotEnabled => {
+                "inheriting a precise occlusion query requires the `occlusion_query_precise` \
+                 feature"
+            },
+            CommandBufferInheritanceError::PipelineStatisticsQueryFeatureNotEnabled => {
+                "inheriting pipeline statistics requires the `pipeline_statistics_query` feature"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            CommandBufferInheritanceError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for CommandBufferInheritanceError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for CommandBufferInheritanceError {
+    #[inline]
+    fn from(err: OomError) -> CommandBufferInheritanceError {
+        CommandBufferInheritanceError::OomError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::CommandBufferInheritance;
+    use command_buffer::CommandBufferInheritanceError;
+    use query::QueryPipelineStatisticFlags;
+
+    #[test]
+    fn no_render_pass() {
+        let inheritance = CommandBufferInheritance::new();
+        assert!(inheritance.subpass().is_none());
+        assert_eq!(inheritance.usage_flags(), 0);
+
+        let infos = inheritance.to_vulkan();
+        assert_eq!(infos.renderPass, 0);
+        assert_eq!(infos.framebuffer, 0);
+        assert_eq!(infos.occlusionQueryEnable, 0);
+    }
+
+    #[test]
+    fn occlusion_query_requires_feature() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let inheritance = CommandBufferInheritance::new().occlusion_query(false);
+        assert!(inheritance.inherits_occlusion_query());
+        assert_eq!(inheritance.check(&device),
+                   Err(CommandBufferInheritanceError::InheritedQueriesFeatureNotEnabled));
+    }
+
+    #[test]
+    fn pipeline_statistics_requires_feature() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let flags = QueryPipelineStatisticFlags {
+            compute_shader_invocations: true,
+            .. QueryPipelineStatisticFlags::none()
+        };
+
+        let inheritance = CommandBufferInheritance::new().pipeline_statistics(flags);
+        assert_eq!(inheritance.check(&device),
+                   Err(CommandBufferInheritanceError::PipelineStatisticsQueryFeatureNotEnabled));
+    }
+}
@@ -78,13 +78,17 @@ pub use self::auto::AutoCommandBufferBuilder;
 pub use self::builder::CommandAddError;
 pub use self::builder::CommandBufferBuilder;
 pub use self::builder::CommandBufferBuilderError;
+pub use self::inheritance::CommandBufferInheritance;
+pub use self::inheritance::CommandBufferInheritanceError;
 pub use self::traits::CommandBuffer;
 pub use self::traits::CommandBufferBuild;
 pub use self::traits::CommandBufferExecError;
 pub use self::traits::CommandBufferExecFuture;
+pub use self::traits::CommandBufferExecStatus;
 
 use pipeline::viewport::Viewport;
 use pipeline::viewport::Scissor;
+use vk;
 
 pub mod cb;
 pub mod commands_extra;
@@ -94,35 +98,75 @@ pub mod submit;
 
 mod auto;
 mod builder;
+mod inheritance;
 mod traits;
 
+/// Parameters of one draw of an indirect draw command.
+///
+/// Has the same layout as `VkDrawIndirectCommand`, so that an array of this struct can be used
+/// as the content of the indirect buffer passed to `draw_indirect`, whether it is filled on the
+/// CPU or by a shader.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct DrawIndirectCommand {
+    /// Number of vertices to draw.
     pub vertex_count: u32,
+    /// Number of instances to draw.
     pub instance_count: u32,
+    /// Index of the first vertex to draw.
     pub first_vertex: u32,
+    /// Instance ID of the first instance to draw.
     pub first_instance: u32,
 }
 
+/// Parameters of one draw of an indexed indirect draw command.
+///
+/// Has the same layout as `VkDrawIndexedIndirectCommand`.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct DrawIndexedIndirectCommand {
+    /// Number of indices to draw.
     pub index_count: u32,
+    /// Number of instances to draw.
     pub instance_count: u32,
+    /// Position of the first index to read within the index buffer.
     pub first_index: u32,
-    pub vertex_offset: u32,
+    /// Value added to each index before reading the vertex buffers.
+    pub vertex_offset: i32,
+    /// Instance ID of the first instance to draw.
     pub first_instance: u32,
 }
 
+/// Parameters of an indirect dispatch command.
+///
+/// Has the same layout as `VkDispatchIndirectCommand`, so that it can be used as the content of
+/// the buffer passed to `dispatch_indirect`.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct DispatchIndirectCommand {
+    /// Number of work groups on the X dimension.
     pub x: u32,
+    /// Number of work groups on the Y dimension.
     pub y: u32,
+    /// Number of work groups on the Z dimension.
     pub z: u32,
 }
 
+/// Describes what the commands of a subpass are made of.
+///
+/// Passed when beginning a render pass or when going to the next subpass.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum SubpassContents {
+    /// The subpass only contains commands that are recorded directly in the primary command
+    /// buffer, such as draw commands. Secondary command buffers can't be executed.
+    Inline = vk::SUBPASS_CONTENTS_INLINE,
+
+    /// The subpass only contains executions of secondary command buffers. Draw commands can't be
+    /// added directly.
+    SecondaryCommandBuffers = vk::SUBPASS_CONTENTS_SECONDARY_COMMAND_BUFFERS,
+}
+
 /// The dynamic state to use for a draw command.
 #[derive(Debug, Clone)]
 pub struct DynamicState {
@@ -148,3 +192,27 @@ impl Default for DynamicState {
         DynamicState::none()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use command_buffer::DispatchIndirectCommand;
+    use command_buffer::DrawIndexedIndirectCommand;
+    use command_buffer::DrawIndirectCommand;
+    use vk;
+
+    #[test]
+    fn indirect_commands_layout() {
+        assert_eq!(mem::size_of::<DrawIndirectCommand>(),
+                   mem::size_of::<vk::DrawIndirectCommand>());
+        assert_eq!(mem::size_of::<DrawIndexedIndirectCommand>(),
+                   mem::size_of::<vk::DrawIndexedIndirectCommand>());
+        assert_eq!(mem::size_of::<DispatchIndirectCommand>(),
+                   mem::size_of::<vk::DispatchIndirectCommand>());
+
+        assert_eq!(mem::size_of::<DrawIndirectCommand>(), 16);
+        assert_eq!(mem::size_of::<DrawIndexedIndirectCommand>(), 20);
+        assert_eq!(mem::size_of::<DispatchIndirectCommand>(), 12);
+    }
+}
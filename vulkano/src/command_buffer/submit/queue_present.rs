@@ -10,7 +10,6 @@
 use std::error;
 use std::fmt;
 use std::marker::PhantomData;
-use std::mem;
 use std::ptr;
 use smallvec::SmallVec;
 
@@ -31,6 +30,8 @@ use SynchronizedVulkanObject;
 pub struct SubmitPresentBuilder<'a> {
     wait_semaphores: SmallVec<[vk::Semaphore; 8]>,
     swapchains: SmallVec<[vk::SwapchainKHR; 4]>,
+    // Same as `swapchains`, but with the objects, used to update their status after presenting.
+    swapchain_objects: SmallVec<[&'a Swapchain; 4]>,
     image_indices: SmallVec<[u32; 4]>,
     marker: PhantomData<&'a ()>,
 }
@@ -42,6 +43,7 @@ impl<'a> SubmitPresentBuilder<'a> {
         SubmitPresentBuilder {
             wait_semaphores: SmallVec::new(),
             swapchains: SmallVec::new(),
+            swapchain_objects: SmallVec::new(),
             image_indices: SmallVec::new(),
             marker: PhantomData,
         }
@@ -82,25 +84,62 @@ impl<'a> SubmitPresentBuilder<'a> {
     pub unsafe fn add_swapchain(&mut self, swapchain: &'a Swapchain, image_num: u32) {
         debug_assert!(image_num < swapchain.num_images());
         self.swapchains.push(swapchain.internal_object());
+        self.swapchain_objects.push(swapchain);
         self.image_indices.push(image_num);
     }
 
     /// Submits the command. Calls `vkQueuePresentKHR`.
     ///
+    /// If the presentation returns `VK_SUBOPTIMAL_KHR` or `VK_ERROR_OUT_OF_DATE_KHR`, this is
+    /// reflected by the `status()` of the swapchains.
+    ///
+    /// Returns the first error among the swapchains. Use `submit_with_results` to get the result
+    /// of each swapchain instead.
+    ///
     /// # Panic
     ///
     /// Panics if no swapchain image has been added to the builder.
     ///
     pub fn submit(self, queue: &Queue) -> Result<(), SubmitPresentError> {
+        for result in try!(self.submit_with_results(queue)) {
+            try!(result);
+        }
+
+        Ok(())
+    }
+
+    /// Same as `submit`, but returns the result of the presentation of each swapchain, in the
+    /// order in which they were added.
+    ///
+    /// The outer error is returned for errors that affect the whole submission, in other words
+    /// `OomError` and `DeviceLost`. The other errors are specific to one swapchain, so that the
+    /// swapchains that are out of date can be recreated independently from the others.
+    ///
+    /// # Panic
+    ///
+    /// Panics if no swapchain image has been added to the builder.
+    ///
+    pub fn submit_with_results(self, queue: &Queue)
+                               -> Result<SmallVec<[Result<(), SubmitPresentError>; 4]>,
+                                         SubmitPresentError>
+    {
         unsafe {
             debug_assert_eq!(self.swapchains.len(), self.image_indices.len());
             assert!(!self.swapchains.is_empty(),
                     "Tried to submit a present command without any swapchain");
 
-            let vk = queue.device().pointers();
+            if queue.device().is_lost() {
+                return Err(SubmitPresentError::DeviceLost);
+            }
+
+            let device = queue.device();
+            let vk = device.pointers();
             let queue = queue.internal_object_guard();
 
-            let mut results = vec![mem::uninitialized(); self.swapchains.len()];       // TODO: alloca
+            // Some drivers don't write the individual results. We fill them with a value that
+            // isn't a valid result in order to detect it.
+            let mut results: SmallVec<[vk::Result; 4]> =
+                self.swapchains.iter().map(|_| RESULT_NOT_WRITTEN).collect();
 
             let infos = vk::PresentInfoKHR {
                 sType: vk::STRUCTURE_TYPE_PRESENT_INFO_KHR,
@@ -113,18 +152,32 @@ impl<'a> SubmitPresentBuilder<'a> {
                 pResults: results.as_mut_ptr(),
             };
 
-            try!(check_errors(vk.QueuePresentKHR(*queue, &infos)));
+            let result = device.track_lost(check_errors(vk.QueuePresentKHR(*queue, &infos)));
 
-            for result in results {
-                // TODO: AMD driver initially didn't write the results ; check that it's been fixed
-                //try!(check_errors(result));
+            match result {
+                Err(err @ Error::OutOfHostMemory) |
+                Err(err @ Error::OutOfDeviceMemory) |
+                Err(err @ Error::DeviceLost) => return Err(err.into()),
+                _ => ()
             }
 
-            Ok(())
+            Ok(self.swapchain_objects.iter().zip(results.iter()).map(|(swapchain, &individual)| {
+                // If the driver didn't write the individual result, the global result applies.
+                let individual = if individual == RESULT_NOT_WRITTEN {
+                    result
+                } else {
+                    check_errors(individual)
+                };
+
+                swapchain.update_status(individual).map(|_| ()).map_err(From::from)
+            }).collect())
         }
     }
 }
 
+// Value of `VK_RESULT_MAX_ENUM`, which is never returned by the implementation.
+const RESULT_NOT_WRITTEN: vk::Result = 0x7fffffff;
+
 /// Error that can happen when submitting the present prototype.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -200,13 +200,21 @@ impl<'a> SubmitCommandBufferBuilder<'a> {
 
     /// Submits the command buffer to the given queue.
     ///
+    /// On success, returns the ID that the queue assigned to this submission. See the
+    /// documentation of `Queue` for more information.
+    ///
     /// > **Note**: This is an expensive operation, so you may want to merge as many builders as
     /// > possible together and avoid submitting them one by one.
     ///
-    pub fn submit(self, queue: &Queue) -> Result<(), SubmitCommandBufferError> {
+    pub fn submit(self, queue: &Queue) -> Result<u64, SubmitCommandBufferError> {
         unsafe {
-            let vk = queue.device().pointers();
-            let queue = queue.internal_object_guard();
+            if queue.device().is_lost() {
+                return Err(SubmitCommandBufferError::DeviceLost);
+            }
+
+            let device = queue.device();
+            let vk = device.pointers();
+            let queue_guard = queue.internal_object_guard();
 
             debug_assert_eq!(self.wait_semaphores.len(), self.dest_stages.len());
 
@@ -222,8 +230,11 @@ impl<'a> SubmitCommandBufferBuilder<'a> {
                 pSignalSemaphores: self.signal_semaphores.as_ptr(),
             };
 
-            try!(check_errors(vk.QueueSubmit(*queue, 1, &batch, self.fence)));
-            Ok(())
+            try!(device.track_lost(check_errors(vk.QueueSubmit(*queue_guard, 1, &batch,
+                                                                self.fence))));
+            // The ID must be assigned before the guard is released, so that the order of the IDs
+            // matches the order of the submissions.
+            Ok(queue.register_submission())
         }
     }
 
@@ -13,6 +13,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use buffer::BufferAccess;
 use command_buffer::cb::UnsafeCommandBuffer;
@@ -20,6 +21,7 @@ use command_buffer::pool::CommandPool;
 use command_buffer::submit::SubmitAnyBuilder;
 use command_buffer::submit::SubmitCommandBufferBuilder;
 use device::Device;
+use device::DeviceWaitError;
 use device::DeviceOwned;
 use device::Queue;
 use image::ImageLayout;
@@ -29,6 +31,8 @@ use sync::now;
 use sync::AccessError;
 use sync::AccessCheckError;
 use sync::AccessFlagBits;
+use sync::Fence;
+use sync::FenceWaitError;
 use sync::FlushError;
 use sync::NowFuture;
 use sync::GpuFuture;
@@ -115,10 +119,28 @@ pub unsafe trait CommandBuffer: DeviceOwned {
     fn execute_after<F>(self, future: F, queue: Arc<Queue>)
                         -> Result<CommandBufferExecFuture<F, Self>, CommandBufferExecError>
         where Self: Sized + 'static, F: GpuFuture
+    {
+        self.try_execute_after(future, queue).map_err(|(err, _)| err)
+    }
+
+    /// Same as `execute_after`, except that `future` is given back if the command buffer can't
+    /// be executed.
+    ///
+    /// This is useful when `future` must not be dropped, for example because it acquires a
+    /// swapchain image that must still be presented.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the device of the command buffer is not the same as the device of the future.
+    fn try_execute_after<F>(self, future: F, queue: Arc<Queue>)
+                            -> Result<CommandBufferExecFuture<F, Self>, (CommandBufferExecError, F)>
+        where Self: Sized + 'static, F: GpuFuture
     {
         assert_eq!(self.device().internal_object(), future.device().internal_object());
 
-        self.prepare_submit(&future, &queue)?;
+        if let Err(err) = self.prepare_submit(&future, &queue) {
+            return Err((err, future));
+        }
 
         if !future.queue_change_allowed() {
             assert!(future.queue().unwrap().is_same(&queue));
@@ -129,6 +151,7 @@ pub unsafe trait CommandBuffer: DeviceOwned {
             command_buffer: self,
             queue: queue,
             submitted: Mutex::new(false),
+            fence: Mutex::new(None),
             finished: AtomicBool::new(false),
         })
     }
@@ -183,6 +206,29 @@ unsafe impl<T> CommandBuffer for T where T: SafeDeref, T::Target: CommandBuffer
 
 /// Represents a command buffer being executed by the GPU and the moment when the execution
 /// finishes.
+///
+/// You can find out whether the execution is finished with `wait()`, which doesn't block if you
+/// pass a timeout of zero, or block until it is finished with `wait_blocking()`. Both functions
+/// flush the future if necessary, and signal a fence at the end of the submission. Once the
+/// execution is known to be finished, the resources used by the command buffer and by the
+/// previous futures are released, and dropping the future no longer blocks.
+///
+/// ```
+/// use std::time::Duration;
+/// use vulkano::command_buffer::CommandBuffer;
+/// use vulkano::command_buffer::CommandBufferExecFuture;
+/// use vulkano::command_buffer::CommandBufferExecStatus;
+/// use vulkano::sync::GpuFuture;
+///
+/// # fn example<F: GpuFuture, Cb: CommandBuffer>(future: CommandBufferExecFuture<F, Cb>) {
+/// // In the event loop of the application:
+/// match future.wait(Duration::from_secs(0)).unwrap() {
+///     CommandBufferExecStatus::Pending => (),     // try again later
+///     CommandBufferExecStatus::Complete => println!("done"),
+///     CommandBufferExecStatus::DeviceLost => panic!("device lost"),
+/// }
+/// # }
+/// ```
 #[must_use = "Dropping this object will immediately block the thread until the GPU has finished processing the submission"]
 pub struct CommandBufferExecFuture<F, Cb> where F: GpuFuture, Cb: CommandBuffer {
     previous: F,
@@ -192,14 +238,169 @@ pub struct CommandBufferExecFuture<F, Cb> where F: GpuFuture, Cb: CommandBuffer
     // If flush is called multiple times, we want to block so that only one flushing is executed.
     // Therefore we use a `Mutex<bool>` and not an `AtomicBool`.
     submitted: Mutex<bool>,
+    // Fence signaled at the end of the submission, if `wait` or `wait_blocking` was called.
+    // Contains the ID of the submission that signals it. The fence is shared so that it can be
+    // waited upon without holding the lock.
+    fence: Mutex<Option<(Arc<Fence>, u64)>>,
     finished: AtomicBool,
 }
 
+impl<F, Cb> CommandBufferExecFuture<F, Cb> where F: GpuFuture, Cb: CommandBuffer {
+    /// Waits until the execution of the command buffer is finished or until `timeout` has
+    /// elapsed, and returns the status of the execution. Performs a flush if necessary.
+    ///
+    /// If `timeout` is zero, this function never blocks and only queries the status of the fence
+    /// with `vkGetFenceStatus`. This is the way to integrate the completion of the GPU work in
+    /// the event loop of an application.
+    #[inline]
+    pub fn wait(&self, timeout: Duration) -> Result<CommandBufferExecStatus, FlushError> {
+        self.wait_impl(Some(timeout))
+    }
+
+    /// Blocks the current thread until the execution of the command buffer is finished. Performs
+    /// a flush if necessary.
+    ///
+    /// If `timeout` is `None`, then the wait is infinite. Otherwise the thread will unblock after
+    /// the specified timeout has elapsed and `FlushError::Timeout` will be returned. Returns
+    /// `FlushError::DeviceLost` if the device has been lost.
+    pub fn wait_blocking(&self, timeout: Option<Duration>) -> Result<(), FlushError> {
+        match try!(self.wait_impl(timeout)) {
+            CommandBufferExecStatus::Complete => Ok(()),
+            CommandBufferExecStatus::Pending => Err(FlushError::Timeout),
+            CommandBufferExecStatus::DeviceLost => Err(FlushError::DeviceLost),
+        }
+    }
+
+    // Implementation of `wait` and `wait_blocking`. A timeout of `None` is infinite.
+    fn wait_impl(&self, timeout: Option<Duration>) -> Result<CommandBufferExecStatus, FlushError> {
+        if self.finished.load(Ordering::SeqCst) {
+            return Ok(CommandBufferExecStatus::Complete);
+        }
+
+        try!(self.flush_impl(true));
+
+        // The lock isn't held while blocking, so that other threads can still flush or wait.
+        let (fence, submission_id) = {
+            let fence = self.fence.lock().unwrap();
+            let &(ref fence, submission_id) = fence.as_ref().unwrap();
+            (fence.clone(), submission_id)
+        };
+
+        let result = match fence.wait(timeout) {
+            Ok(()) => Ok(true),
+            Err(FenceWaitError::Timeout) => Ok(false),
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(false) => Ok(CommandBufferExecStatus::Pending),
+            Ok(true) => {
+                self.queue.mark_completed(submission_id);
+                unsafe { self.signal_finished(); }
+                Ok(CommandBufferExecStatus::Complete)
+            },
+            // Nothing is executing anymore, so the resources can be released.
+            Err(FenceWaitError::DeviceLostError) => {
+                unsafe { self.signal_finished(); }
+                Ok(CommandBufferExecStatus::DeviceLost)
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // Implementation of `flush`. If `with_fence` is true, also makes sure that `self.fence`
+    // contains a fence that is signaled at the end of the submission.
+    fn flush_impl(&self, with_fence: bool) -> Result<(), FlushError> {
+        unsafe {
+            let mut submitted = self.submitted.lock().unwrap();
+            let mut fence = self.fence.lock().unwrap();
+
+            if *submitted && (!with_fence || fence.is_some()) {
+                return Ok(());
+            }
+
+            let new_fence = if with_fence {
+                Some(try!(Fence::from_pool(self.device().clone()).map_err(FlushError::OomError)))
+            } else {
+                None
+            };
+
+            let queue = self.queue.clone();
+
+            let builder = if *submitted {
+                // The command buffer has already been submitted without a fence. Queues execute
+                // their submissions in order, so an empty submission signals the fence after it.
+                Some(SubmitCommandBufferBuilder::new())
+            } else {
+                match try!(self.build_submission()) {
+                    SubmitAnyBuilder::Empty => {
+                        if new_fence.is_some() {
+                            Some(SubmitCommandBufferBuilder::new())
+                        } else {
+                            None
+                        }
+                    },
+                    SubmitAnyBuilder::CommandBuffer(builder) => Some(builder),
+                    _ => unreachable!(),
+                }
+            };
+
+            if let Some(mut builder) = builder {
+                let fence_attached = match new_fence {
+                    Some(ref new_fence) if !builder.has_fence() => {
+                        builder.set_fence_signal(new_fence);
+                        true
+                    },
+                    _ => false,
+                };
+
+                let mut submission_id = try!(builder.submit(&queue));
+                *submitted = true;
+
+                if let Some(new_fence) = new_fence {
+                    // The submission already signals a fence that was added by a previous future.
+                    // Queues execute their submissions in order, so an empty submission signals
+                    // our fence after it.
+                    if !fence_attached {
+                        let mut builder = SubmitCommandBufferBuilder::new();
+                        builder.set_fence_signal(&new_fence);
+                        submission_id = try!(builder.submit(&queue));
+                    }
+
+                    *fence = Some((Arc::new(new_fence), submission_id));
+                }
+            }
+
+            // Only write `true` here in order to try again next time if we failed to submit.
+            *submitted = true;
+            Ok(())
+        }
+    }
+}
+
+/// Status of the execution of a command buffer, returned by `CommandBufferExecFuture::wait`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CommandBufferExecStatus {
+    /// The command buffer is still being executed, or is waiting to be executed.
+    Pending,
+    /// The execution of the command buffer is finished.
+    Complete,
+    /// The device has been lost. The command buffer may or may not have been executed, but
+    /// nothing is executing anymore.
+    DeviceLost,
+}
+
 unsafe impl<F, Cb> GpuFuture for CommandBufferExecFuture<F, Cb>
     where F: GpuFuture, Cb: CommandBuffer
 {
     #[inline]
     fn cleanup_finished(&mut self) {
+        // If a fence was submitted by `wait`, we can find out without blocking whether the
+        // execution is finished and release the resources.
+        if !*self.finished.get_mut() && self.fence.get_mut().unwrap().is_some() {
+            let _ = self.wait(Duration::from_secs(0));
+        }
+
         self.previous.cleanup_finished();
     }
 
@@ -232,32 +433,16 @@ unsafe impl<F, Cb> GpuFuture for CommandBufferExecFuture<F, Cb>
 
     #[inline]
     fn flush(&self) -> Result<(), FlushError> {
-        unsafe {
-            let mut submitted = self.submitted.lock().unwrap();
-            if *submitted {
-                return Ok(());
-            }
-
-            let queue = self.queue.clone();
-
-            match try!(self.build_submission()) {
-                SubmitAnyBuilder::Empty => {},
-                SubmitAnyBuilder::CommandBuffer(builder) => {
-                    try!(builder.submit(&queue));
-                },
-                _ => unreachable!(),
-            };
-
-            // Only write `true` here in order to try again next time if we failed to submit.
-            *submitted = true;
-            Ok(())
-        }
+        self.flush_impl(false)
     }
 
     #[inline]
     unsafe fn signal_finished(&self) {
-        self.finished.store(true, Ordering::SeqCst);
-        self.previous.signal_finished();
+        // Multiple threads can find out at the same time that the execution is finished. Only
+        // the first one signals the previous future.
+        if !self.finished.swap(true, Ordering::SeqCst) {
+            self.previous.signal_finished();
+        }
     }
 
     #[inline]
@@ -310,10 +495,30 @@ impl<F, Cb> Drop for CommandBufferExecFuture<F, Cb> where F: GpuFuture, Cb: Comm
     fn drop(&mut self) {
         unsafe {
             if !*self.finished.get_mut() {
-                // TODO: handle errors?
-                self.flush().unwrap();
-                // Block until the queue finished.
-                self.queue.wait().unwrap();
+                // If the device has been lost, nothing is executing anymore and there is no need
+                // to wait.
+                // TODO: handle other errors?
+                match self.flush() {
+                    Ok(()) | Err(FlushError::DeviceLost) => (),
+                    Err(err) => panic!("{:?}", err),
+                }
+                // Block until the submission is finished. Without a fence, we have to wait for
+                // the whole queue.
+                // Note that this returns immediately if the device has been lost.
+                match *self.fence.get_mut().unwrap() {
+                    Some((ref fence, _)) => {
+                        match fence.wait(None) {
+                            Ok(()) | Err(FenceWaitError::DeviceLostError) => (),
+                            Err(err) => panic!("{:?}", err),
+                        }
+                    },
+                    None => {
+                        match self.queue.wait() {
+                            Ok(()) | Err(DeviceWaitError::DeviceLost) => (),
+                            Err(err) => panic!("{:?}", err),
+                        }
+                    },
+                }
                 self.previous.signal_finished();
             }
         }
@@ -358,3 +563,63 @@ impl From<AccessError> for CommandBufferExecError {
         CommandBufferExecError::AccessError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use command_buffer::CommandBufferBuild;
+    use command_buffer::CommandBufferExecStatus;
+    use sync::GpuFuture;
+
+    #[test]
+    fn wait_complete() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb = AutoCommandBufferBuilder::new(device, queue.family()).unwrap().build().unwrap();
+        let future = cb.execute(queue.clone()).unwrap();
+
+        future.wait_blocking(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(future.wait(Duration::from_secs(0)).unwrap(),
+                   CommandBufferExecStatus::Complete);
+        assert!(queue.last_completed_id() >= 1);
+    }
+
+    #[test]
+    fn wait_after_flush() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb = AutoCommandBufferBuilder::new(device, queue.family()).unwrap().build().unwrap();
+        let mut future = cb.execute(queue.clone()).unwrap();
+        future.flush().unwrap();
+
+        // The command buffer was submitted without a fence, so an empty submission is needed.
+        assert_eq!(future.wait(Duration::from_secs(5)).unwrap(),
+                   CommandBufferExecStatus::Complete);
+        assert_eq!(queue.last_submitted_id(), 2);
+        future.cleanup_finished();
+    }
+
+    #[test]
+    fn wait_from_multiple_threads() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb = AutoCommandBufferBuilder::new(device, queue.family()).unwrap().build().unwrap();
+        let future = Arc::new(cb.execute(queue.clone()).unwrap());
+
+        let threads = (0 .. 4).map(|_| {
+            let future = future.clone();
+            thread::spawn(move || future.wait_blocking(Some(Duration::from_secs(5))).unwrap())
+        }).collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(future.wait(Duration::from_secs(0)).unwrap(),
+                   CommandBufferExecStatus::Complete);
+    }
+}
@@ -321,6 +321,21 @@ pub enum DescriptorType {
     InputAttachment = vk::DESCRIPTOR_TYPE_INPUT_ATTACHMENT,
 }
 
+impl DescriptorType {
+    /// Returns true for dynamic uniform and storage buffers.
+    ///
+    /// When binding a descriptor set, one dynamic offset must be passed for each array element of
+    /// each dynamic descriptor of the set. The offset is added to the offset of the buffer that
+    /// was written in the descriptor.
+    #[inline]
+    pub fn is_dynamic(&self) -> bool {
+        match *self {
+            DescriptorType::UniformBufferDynamic | DescriptorType::StorageBufferDynamic => true,
+            _ => false,
+        }
+    }
+}
+
 /// Describes which shader stages have access to a descriptor.
 // TODO: add example with BitOr
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -0,0 +1,292 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use device::Device;
+use device::DeviceOwned;
+use descriptor::descriptor_set::DescriptorsCount;
+use descriptor::descriptor_set::DescriptorPool;
+use descriptor::descriptor_set::DescriptorPoolAlloc;
+use descriptor::descriptor_set::UnsafeDescriptorPool;
+use descriptor::descriptor_set::UnsafeDescriptorSet;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use OomError;
+
+/// Allocator of descriptor sets that manages multiple Vulkan descriptor pools.
+///
+/// The sets are grouped by the number of descriptors of each type of their layout. Each group
+/// has its own list of pools, and each of these pools is sized to hold exactly `sets_per_pool`
+/// sets of this shape. This means that the number of sets of a pool is enough to know whether
+/// there is space left in it, and that no room is wasted on descriptor types that the layouts
+/// don't use.
+///
+/// When all the pools of a group are full, or when allocating from them returns an
+/// `OutOfPoolMemory` or `FragmentedPool` error, a new pool is created and the allocation is
+/// retried from it.
+///
+/// # Example
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use vulkano::device::Device;
+/// # use vulkano::descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+/// use vulkano::descriptor::descriptor_set::DescriptorPool;
+/// use vulkano::descriptor::descriptor_set::DescriptorSetAllocator;
+///
+/// # let device: Arc<Device> = return;
+/// # let layout: Arc<UnsafeDescriptorSetLayout> = return;
+/// let allocator = Arc::new(DescriptorSetAllocator::new(device.clone()));
+/// let set = allocator.alloc(&layout).unwrap();
+/// ```
+pub struct DescriptorSetAllocator {
+    device: Arc<Device>,
+    // Number of sets that each new pool can hold.
+    sets_per_pool: u32,
+    // List of groups of pools, with the number of descriptors of each set of the group.
+    groups: Mutex<Vec<(DescriptorsCount, Vec<Arc<Mutex<Pool>>>)>>,
+}
+
+struct Pool {
+    pool: UnsafeDescriptorPool,
+    // Number of sets that can still be allocated, or 0 if the pool reported an error.
+    remaining_sets_count: u32,
+}
+
+impl DescriptorSetAllocator {
+    /// Builds a new `DescriptorSetAllocator` whose pools hold 32 sets each.
+    #[inline]
+    pub fn new(device: Arc<Device>) -> DescriptorSetAllocator {
+        DescriptorSetAllocator::with_sets_per_pool(device, 32)
+    }
+
+    /// Builds a new `DescriptorSetAllocator` whose pools hold `sets_per_pool` sets each.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `sets_per_pool` is 0.
+    ///
+    pub fn with_sets_per_pool(device: Arc<Device>, sets_per_pool: u32)
+                              -> DescriptorSetAllocator
+    {
+        assert_ne!(sets_per_pool, 0, "The number of sets per pool can't be 0");
+
+        DescriptorSetAllocator {
+            device: device,
+            sets_per_pool: sets_per_pool,
+            groups: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the number of sets that each pool can hold.
+    #[inline]
+    pub fn sets_per_pool(&self) -> u32 {
+        self.sets_per_pool
+    }
+
+    /// Returns the number of Vulkan descriptor pools that have been created so far.
+    pub fn num_pools(&self) -> usize {
+        self.groups.lock().unwrap().iter().map(|&(_, ref pools)| pools.len()).sum()
+    }
+}
+
+unsafe impl DescriptorPool for Arc<DescriptorSetAllocator> {
+    type Alloc = DescriptorSetAllocatorAlloc;
+
+    /// Allocates a descriptor set with the given layout.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the layout doesn't contain any descriptor.
+    ///
+    fn alloc(&self, layout: &UnsafeDescriptorSetLayout)
+             -> Result<DescriptorSetAllocatorAlloc, OomError>
+    {
+        let count = *layout.descriptors_count();
+        let mut groups = self.groups.lock().unwrap();
+
+        let group_num = match groups.iter().position(|&(c, _)| c == count) {
+            Some(n) => n,
+            None => {
+                groups.push((count, Vec::new()));
+                groups.len() - 1
+            },
+        };
+
+        let pools = &mut groups[group_num].1;
+
+        // Try the existing pools of the group.
+        for pool_arc in pools.iter() {
+            let mut pool = pool_arc.lock().unwrap();
+
+            if pool.remaining_sets_count == 0 {
+                continue;
+            }
+
+            let alloc = unsafe {
+                match pool.pool.alloc(Some(layout)) {
+                    Ok(mut sets) => sets.next().unwrap(),
+                    // The pool can't hold this set even though we think it has some space left.
+                    // We consider it full until one of its sets is freed.
+                    Err(ref err) if err.is_pool_exhausted() => {
+                        pool.remaining_sets_count = 0;
+                        continue;
+                    },
+                    Err(err) => return Err(err.into()),
+                }
+            };
+
+            pool.remaining_sets_count -= 1;
+
+            return Ok(DescriptorSetAllocatorAlloc {
+                pool: pool_arc.clone(),
+                set: Some(alloc),
+            });
+        }
+
+        // All the pools of the group are full. Create a new one.
+        let mut new_pool = try!(UnsafeDescriptorPool::new(self.device.clone(),
+                                                          &(count * self.sets_per_pool),
+                                                          self.sets_per_pool, true));
+
+        let alloc = unsafe {
+            // The pool has been sized for this layout, but the implementation can still fail
+            // for reasons that get reported as a fragmented pool. There's no other pool to try.
+            match new_pool.alloc(Some(layout)) {
+                Ok(mut sets) => sets.next().unwrap(),
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        let pool_obj = Arc::new(Mutex::new(Pool {
+            pool: new_pool,
+            remaining_sets_count: self.sets_per_pool - 1,
+        }));
+
+        pools.push(pool_obj.clone());
+
+        Ok(DescriptorSetAllocatorAlloc {
+            pool: pool_obj,
+            set: Some(alloc),
+        })
+    }
+}
+
+unsafe impl DeviceOwned for DescriptorSetAllocator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// A descriptor set allocated from a `DescriptorSetAllocator`.
+pub struct DescriptorSetAllocatorAlloc {
+    pool: Arc<Mutex<Pool>>,
+    // The set. Inside an option so that we can extract it in the destructor.
+    set: Option<UnsafeDescriptorSet>,
+}
+
+impl DescriptorPoolAlloc for DescriptorSetAllocatorAlloc {
+    #[inline]
+    fn inner(&self) -> &UnsafeDescriptorSet {
+        self.set.as_ref().unwrap()
+    }
+
+    #[inline]
+    fn inner_mut(&mut self) -> &mut UnsafeDescriptorSet {
+        self.set.as_mut().unwrap()
+    }
+}
+
+impl Drop for DescriptorSetAllocatorAlloc {
+    fn drop(&mut self) {
+        unsafe {
+            let mut pool = self.pool.lock().unwrap();
+            pool.pool.free(self.set.take()).unwrap();
+            // Add back the capacity only after freeing, in case of a panic during the free.
+            pool.remaining_sets_count += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use std::sync::Arc;
+    use descriptor::descriptor::DescriptorBufferContentDesc;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorPool;
+    use descriptor::descriptor_set::DescriptorSetAllocator;
+    use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+
+    fn buffer_desc(storage: bool) -> DescriptorDesc {
+        DescriptorDesc {
+            ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                dynamic: Some(false),
+                storage: storage,
+                content: DescriptorBufferContentDesc::F32,
+            }),
+            array_count: 1,
+            stages: ShaderStages::all(),
+            readonly: true,
+        }
+    }
+
+    #[test]
+    fn more_sets_than_one_pool() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                    iter::once(Some(buffer_desc(false))))
+            .unwrap();
+        let allocator = Arc::new(DescriptorSetAllocator::with_sets_per_pool(device, 4));
+
+        let sets = (0 .. 10).map(|_| allocator.alloc(&layout).unwrap()).collect::<Vec<_>>();
+        assert_eq!(sets.len(), 10);
+        assert_eq!(allocator.num_pools(), 3);
+    }
+
+    #[test]
+    fn freed_sets_reused() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                    iter::once(Some(buffer_desc(false))))
+            .unwrap();
+        let allocator = Arc::new(DescriptorSetAllocator::with_sets_per_pool(device, 2));
+
+        for _ in 0 .. 10 {
+            let _set = allocator.alloc(&layout).unwrap();
+        }
+
+        assert_eq!(allocator.num_pools(), 1);
+    }
+
+    #[test]
+    fn pools_per_layout_shape() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let uniform = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                     iter::once(Some(buffer_desc(false))))
+            .unwrap();
+        let storage = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                     iter::once(Some(buffer_desc(true))))
+            .unwrap();
+        let allocator = Arc::new(DescriptorSetAllocator::new(device));
+
+        let _a = allocator.alloc(&uniform).unwrap();
+        let _b = allocator.alloc(&storage).unwrap();
+        let _c = allocator.alloc(&uniform).unwrap();
+        assert_eq!(allocator.num_pools(), 2);
+    }
+}
@@ -8,6 +8,8 @@
 // according to those terms.
 
 use std::iter;
+use smallvec::SmallVec;
+
 use buffer::BufferAccess;
 use descriptor::descriptor::DescriptorDesc;
 use descriptor::descriptor_set::DescriptorSet;
@@ -40,6 +42,157 @@ pub unsafe trait DescriptorSetsCollection {
 
     /// Returns the list of images used by this descriptor set. Includes image views.
     fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a>;
+
+    /// Returns the buffer written in the given array element of the given binding of the given
+    /// set, if the descriptor is a uniform or storage buffer.
+    ///
+    /// This is used to check that the dynamic offsets of the buffer don't go past its end. The
+    /// default implementation returns `None`, in which case the check is skipped.
+    #[inline]
+    fn buffer(&self, set: usize, binding: usize, index: usize) -> Option<&BufferAccess> {
+        None
+    }
+
+    /// Returns the list of buffers used by the sets, along with the offset in bytes from the
+    /// start of each buffer of the range that the shaders can access. This range has the same
+    /// size as the buffer, and the offset is non-zero for buffers that are bound with a dynamic
+    /// offset.
+    ///
+    /// The default implementation returns the buffers of `buffers_list` with an offset of zero.
+    #[inline]
+    fn buffers_ranges<'a>(&'a self) -> Box<Iterator<Item = (&'a BufferAccess, usize)> + 'a> {
+        Box::new(self.buffers_list().map(|buffer| (buffer, 0)))
+    }
+
+    /// Returns the dynamic offsets to pass when binding the sets.
+    ///
+    /// There must be one offset for each array element of each dynamic buffer descriptor, ordered
+    /// by set, then by binding, then by array element. The default implementation returns an
+    /// empty list, which is only valid if the sets don't contain any dynamic descriptor. Use
+    /// `DynamicOffsets` to attach offsets to a collection.
+    #[inline]
+    fn dynamic_offsets(&self) -> &[u32] {
+        &[]
+    }
+}
+
+/// Wraps around a collection of descriptor sets and the dynamic offsets to bind them with.
+///
+/// ```
+/// use vulkano::descriptor::descriptor_set::DynamicOffsets;
+///
+/// # use vulkano::descriptor::descriptor_set::DescriptorSet;
+/// # fn example<S: DescriptorSet>(set: S) {
+/// // `set` contains a dynamic uniform buffer, whose content starts 256 bytes after the offset
+/// // that was written in the descriptor.
+/// let sets = DynamicOffsets::new(set, Some(256));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicOffsets<S> {
+    sets: S,
+    offsets: SmallVec<[u32; 8]>,
+}
+
+impl<S> DynamicOffsets<S> {
+    /// Builds a new `DynamicOffsets`.
+    #[inline]
+    pub fn new<I>(sets: S, offsets: I) -> DynamicOffsets<S>
+        where I: IntoIterator<Item = u32>
+    {
+        DynamicOffsets {
+            sets: sets,
+            offsets: offsets.into_iter().collect(),
+        }
+    }
+
+    /// Returns the wrapped collection.
+    #[inline]
+    pub fn sets(&self) -> &S {
+        &self.sets
+    }
+}
+
+unsafe impl<S> DescriptorSetsCollection for DynamicOffsets<S>
+    where S: DescriptorSetsCollection
+{
+    #[inline]
+    fn num_sets(&self) -> usize {
+        self.sets.num_sets()
+    }
+
+    #[inline]
+    fn descriptor_set(&self, set: usize) -> Option<&UnsafeDescriptorSet> {
+        self.sets.descriptor_set(set)
+    }
+
+    #[inline]
+    fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+        self.sets.num_bindings_in_set(set)
+    }
+
+    #[inline]
+    fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+        self.sets.descriptor(set, binding)
+    }
+
+    #[inline]
+    fn buffers_list<'a>(&'a self) -> Box<Iterator<Item = &'a BufferAccess> + 'a> {
+        self.sets.buffers_list()
+    }
+
+    #[inline]
+    fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a> {
+        self.sets.images_list()
+    }
+
+    #[inline]
+    fn buffer(&self, set: usize, binding: usize, index: usize) -> Option<&BufferAccess> {
+        self.sets.buffer(set, binding, index)
+    }
+
+    fn buffers_ranges<'a>(&'a self) -> Box<Iterator<Item = (&'a BufferAccess, usize)> + 'a> {
+        // The dynamic offsets are applied in the order of the sets, then of the bindings, then of
+        // the array elements. We find the buffer that each of them applies to, then shift the
+        // range of this buffer in the list of the inner sets.
+        let mut shifted: SmallVec<[(*const u8, usize); 8]> = SmallVec::new();
+        let mut offsets = self.offsets.iter();
+
+        'sets: for set in 0 .. self.sets.num_sets() {
+            for binding in 0 .. self.sets.num_bindings_in_set(set).unwrap_or(0) {
+                let array_count = match self.sets.descriptor(set, binding) {
+                    Some(ref desc) if desc.ty.ty().map(|ty| ty.is_dynamic()).unwrap_or(false) => {
+                        desc.array_count
+                    },
+                    _ => continue,
+                };
+
+                for index in 0 .. array_count as usize {
+                    let offset = match offsets.next() {
+                        Some(&offset) => offset as usize,
+                        None => break 'sets,
+                    };
+
+                    if let Some(buffer) = self.sets.buffer(set, binding, index) {
+                        shifted.push((buffer as *const _ as *const u8, offset));
+                    }
+                }
+            }
+        }
+
+        Box::new(self.sets.buffers_ranges().map(move |(buffer, offset)| {
+            let ptr = buffer as *const _ as *const u8;
+            match shifted.iter().find(|&&(shifted_ptr, _)| shifted_ptr == ptr) {
+                Some(&(_, dynamic_offset)) => (buffer, offset + dynamic_offset),
+                None => (buffer, offset),
+            }
+        }))
+    }
+
+    #[inline]
+    fn dynamic_offsets(&self) -> &[u32] {
+        &self.offsets
+    }
 }
 
 unsafe impl DescriptorSetsCollection for () {
@@ -115,6 +268,14 @@ unsafe impl<T> DescriptorSetsCollection for T
     fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a> {
         DescriptorSet::images_list(self)
     }
+
+    #[inline]
+    fn buffer(&self, set: usize, binding: usize, index: usize) -> Option<&BufferAccess> {
+        match set {
+            0 => DescriptorSet::buffer(self, binding, index),
+            _ => None
+        }
+    }
 }
 
 macro_rules! impl_collection {
@@ -192,6 +353,29 @@ macro_rules! impl_collection {
                 None
             }
 
+            #[inline]
+            fn buffer(&self, mut set: usize, binding: usize, index: usize)
+                      -> Option<&BufferAccess>
+            {
+                #![allow(non_snake_case)]
+                #![allow(unused_mut)]       // For the `set` parameter.
+
+                if set == 0 {
+                    return DescriptorSet::buffer(&self.0, binding, index);
+                }
+
+                let &(_, $(ref $others,)*) = self;
+
+                $(
+                    set -= 1;
+                    if set == 0 {
+                        return DescriptorSet::buffer($others, binding, index);
+                    }
+                )*
+
+                None
+            }
+
             #[inline]
             fn buffers_list<'a>(&'a self) -> Box<Iterator<Item = &'a BufferAccess> + 'a> {
                 #![allow(non_snake_case)]
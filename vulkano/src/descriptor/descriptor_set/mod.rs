@@ -28,6 +28,8 @@
 //!   descriptor sets. However it is different from Vulkan descriptor pools in the sense that an
 //!   implementation of the `DescriptorPool` trait can manage multiple Vulkan descriptor pools.
 //! - The `StdDescriptorPool` type is a default implementation of the `DescriptorPool` trait.
+//! - The `DescriptorSetAllocator` type is another implementation of the `DescriptorPool` trait,
+//!   which creates pools sized for the layouts of the sets that are allocated from it.
 //! - The `DescriptorSet` trait is implemented on types that wrap around Vulkan descriptor sets in
 //!   a safe way. A Vulkan descriptor set is inherently unsafe, so we need safe wrappers around
 //!   them.
@@ -40,7 +42,10 @@ use descriptor::descriptor::DescriptorDesc;
 use image::ImageAccess;
 use SafeDeref;
 
+pub use self::allocator::DescriptorSetAllocator;
+pub use self::allocator::DescriptorSetAllocatorAlloc;
 pub use self::collection::DescriptorSetsCollection;
+pub use self::collection::DynamicOffsets;
 pub use self::std_pool::StdDescriptorPool;
 pub use self::std_pool::StdDescriptorPoolAlloc;
 pub use self::simple::*;
@@ -49,6 +54,7 @@ pub use self::sys::DescriptorPoolAlloc;
 pub use self::sys::DescriptorPoolAllocError;
 pub use self::sys::DescriptorWrite;
 pub use self::sys::DescriptorsCount;
+pub use self::sys::MissingUsageError;
 pub use self::sys::UnsafeDescriptorPool;
 pub use self::sys::UnsafeDescriptorPoolAllocIter;
 pub use self::sys::UnsafeDescriptorSet;
@@ -56,6 +62,7 @@ pub use self::unsafe_layout::UnsafeDescriptorSetLayout;
 
 pub mod collection;
 
+mod allocator;
 mod simple;
 mod std_pool;
 mod sys;
@@ -75,6 +82,16 @@ pub unsafe trait DescriptorSet: DescriptorSetDesc {
     /// Returns the list of images used by this descriptor set. Includes image views.
     // TODO: meh for boxing
     fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a>;
+
+    /// Returns the buffer written in the given array element of the given binding, if the
+    /// descriptor is a uniform or storage buffer.
+    ///
+    /// This is used to check that the dynamic offsets of the buffer don't go past its end. The
+    /// default implementation returns `None`, in which case the check is skipped.
+    #[inline]
+    fn buffer(&self, binding: usize, index: usize) -> Option<&BufferAccess> {
+        None
+    }
 }
 
 unsafe impl<T> DescriptorSet for T where T: SafeDeref, T::Target: DescriptorSet {
@@ -92,6 +109,11 @@ unsafe impl<T> DescriptorSet for T where T: SafeDeref, T::Target: DescriptorSet
     fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a> {
         (**self).images_list()
     }
+
+    #[inline]
+    fn buffer(&self, binding: usize, index: usize) -> Option<&BufferAccess> {
+        (**self).buffer(binding, index)
+    }
 }
 
 /// Trait for objects that describe the layout of the descriptors of a set.
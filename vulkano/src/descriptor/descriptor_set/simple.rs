@@ -7,9 +7,12 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
 use std::sync::Arc;
 
 use buffer::BufferAccess;
+use buffer::BufferView;
 use buffer::BufferViewRef;
 use buffer::Buffer;
 use descriptor::descriptor::DescriptorDesc;
@@ -21,6 +24,7 @@ use descriptor::descriptor_set::DescriptorPool;
 use descriptor::descriptor_set::DescriptorPoolAlloc;
 use descriptor::descriptor_set::UnsafeDescriptorSet;
 use descriptor::descriptor_set::DescriptorWrite;
+use descriptor::descriptor_set::MissingUsageError;
 use descriptor::descriptor_set::StdDescriptorPool;
 use descriptor::pipeline_layout::PipelineLayoutAbstract;
 use device::Device;
@@ -51,7 +55,9 @@ use sync::PipelineStages;
 pub struct SimpleDescriptorSet<R, P = Arc<StdDescriptorPool>> where P: DescriptorPool {
     inner: P::Alloc,
     resources: R,
-    layout: Arc<UnsafeDescriptorSetLayout>
+    layout: Arc<UnsafeDescriptorSetLayout>,
+    // The descriptor of each binding, as described in the pipeline layout.
+    descriptors: Vec<Option<DescriptorDesc>>,
 }
 
 impl<R, P> SimpleDescriptorSet<R, P> where P: DescriptorPool {
@@ -62,7 +68,9 @@ impl<R, P> SimpleDescriptorSet<R, P> where P: DescriptorPool {
     }
 }
 
-unsafe impl<R, P> DescriptorSet for SimpleDescriptorSet<R, P> where P: DescriptorPool {
+unsafe impl<R, P> DescriptorSet for SimpleDescriptorSet<R, P>
+    where P: DescriptorPool, R: SimpleDescriptorSetResources
+{
     #[inline]
     fn inner(&self) -> &UnsafeDescriptorSet {
         self.inner.inner()
@@ -70,28 +78,42 @@ unsafe impl<R, P> DescriptorSet for SimpleDescriptorSet<R, P> where P: Descripto
 
     #[inline]
     fn buffers_list<'a>(&'a self) -> Box<Iterator<Item = &'a BufferAccess> + 'a> {
-        unimplemented!()
+        let mut list = Vec::new();
+        self.resources.buffers(&mut list);
+        Box::new(list.into_iter())
     }
 
     #[inline]
     fn images_list<'a>(&'a self) -> Box<Iterator<Item = &'a ImageAccess> + 'a> {
         unimplemented!()
     }
+
+    #[inline]
+    fn buffer(&self, binding: usize, index: usize) -> Option<&BufferAccess> {
+        // Arrays of buffers are not supported by the builder.
+        if index != 0 {
+            return None;
+        }
+
+        self.resources.buffer(binding)
+    }
 }
 
 unsafe impl<R, P> DescriptorSetDesc for SimpleDescriptorSet<R, P> where P: DescriptorPool {
     #[inline]
     fn num_bindings(&self) -> usize {
-        unimplemented!()        // FIXME:
+        self.descriptors.len()
     }
 
     #[inline]
     fn descriptor(&self, binding: usize) -> Option<DescriptorDesc> {
-        unimplemented!()        // FIXME:
+        self.descriptors.get(binding).and_then(|desc| desc.clone())
     }
 }
 
 /// Builds a descriptor set in the form of a `SimpleDescriptorSet` object.
+///
+/// Returns a `Result<SimpleDescriptorSet, SimpleDescriptorSetError>`.
 // TODO: more doc
 #[macro_export]
 macro_rules! simple_descriptor_set {
@@ -144,6 +166,8 @@ pub struct SimpleDescriptorSetBuilder<L, R> {
     writes: Vec<DescriptorWrite>,
     // Holds the resources alive.
     resources: R,
+    // The first error that happened while adding a resource. Returned by `build()`.
+    error: Option<SimpleDescriptorSetError>,
 }
 
 impl<L> SimpleDescriptorSetBuilder<L, ()> where L: PipelineLayoutAbstract {
@@ -164,16 +188,29 @@ impl<L> SimpleDescriptorSetBuilder<L, ()> where L: PipelineLayoutAbstract {
             set_id: set_id,
             writes: Vec::with_capacity(cap),
             resources: (),
+            error: None,
         }
     }
 }
 
 impl<L, R> SimpleDescriptorSetBuilder<L, R> where L: PipelineLayoutAbstract {
     /// Builds a `SimpleDescriptorSet` from the builder.
-    pub fn build(self) -> SimpleDescriptorSet<R, Arc<StdDescriptorPool>> {
+    ///
+    /// Returns an error if one of the resources that were added can't be written in its
+    /// descriptor.
+    pub fn build(self)
+                 -> Result<SimpleDescriptorSet<R, Arc<StdDescriptorPool>>, SimpleDescriptorSetError>
+    {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
         // TODO: check that we filled everything
         let pool = Device::standard_descriptor_pool(self.layout.device());
         let set_layout = self.layout.descriptor_set_layout(self.set_id).unwrap().clone();       // FIXME: error
+        let descriptors = (0 .. self.layout.num_bindings_in_set(self.set_id).unwrap_or(0))
+            .map(|binding| self.layout.descriptor(self.set_id, binding))
+            .collect();
 
         let set = unsafe {
             let mut set = pool.alloc(&set_layout).unwrap();      // FIXME: error
@@ -181,10 +218,38 @@ impl<L, R> SimpleDescriptorSetBuilder<L, R> where L: PipelineLayoutAbstract {
             set
         };
 
-        SimpleDescriptorSet {
+        Ok(SimpleDescriptorSet {
             inner: set,
             resources: self.resources,
             layout: set_layout,
+            descriptors: descriptors,
+        })
+    }
+
+    // Stores the error to return from `build()`, unless an earlier error is already stored.
+    #[inline]
+    fn add_error(&mut self, err: SimpleDescriptorSetError) {
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
+    }
+
+    // Runs `check`, which verifies that a resource has the usage required by its descriptor.
+    // Skipped if the recording validation of the device is disabled. Returns false and stores
+    // the error if the usage is missing.
+    fn check_usage<F>(&mut self, check: F) -> bool
+        where F: FnOnce() -> Result<(), MissingUsageError>
+    {
+        if !self.layout.device().recording_validation() {
+            return true;
+        }
+
+        match check() {
+            Ok(()) => true,
+            Err(err) => {
+                self.add_error(SimpleDescriptorSetError::MissingUsage(err));
+                false
+            },
         }
     }
 }
@@ -215,13 +280,24 @@ unsafe impl<L, R, T> SimpleDescriptorSetBufferExt<L, R> for T
         let desc = i.layout.descriptor(set_id, binding_id).unwrap();     // TODO: Result instead
 
         assert!(desc.array_count == 1);     // not implemented
-        i.writes.push(match desc.ty.ty().unwrap() {
+
+        // Catches the most common mistake early, instead of at the next validation layer check.
+        let ty = desc.ty.ty().unwrap();
+        i.check_usage(|| DescriptorWrite::check_buffer_usage(ty, &buffer));
+
+        i.writes.push(match ty {
             DescriptorType::UniformBuffer => unsafe {
                 DescriptorWrite::uniform_buffer(binding_id as u32, 0, &buffer)
             },
             DescriptorType::StorageBuffer => unsafe {
                 DescriptorWrite::storage_buffer(binding_id as u32, 0, &buffer)
             },
+            DescriptorType::UniformBufferDynamic => unsafe {
+                DescriptorWrite::dynamic_uniform_buffer(binding_id as u32, 0, &buffer)
+            },
+            DescriptorType::StorageBufferDynamic => unsafe {
+                DescriptorWrite::dynamic_storage_buffer(binding_id as u32, 0, &buffer)
+            },
             _ => panic!()
         });
 
@@ -229,8 +305,54 @@ unsafe impl<L, R, T> SimpleDescriptorSetBufferExt<L, R> for T
             layout: i.layout,
             set_id: i.set_id,
             writes: i.writes,
+            error: i.error,
             resources: (i.resources, SimpleDescriptorSetBuf {
                 buffer: buffer,
+                binding: binding_id,
+                write: !desc.readonly,
+                stage: PipelineStages::none(),      // FIXME:
+                access: AccessFlagBits::none(),     // FIXME:
+            })
+        }
+    }
+}
+
+unsafe impl<L, R, F, B> SimpleDescriptorSetBufferExt<L, R> for Arc<BufferView<F, B>>
+    where B: BufferAccess + 'static, F: 'static + Send + Sync, L: PipelineLayoutAbstract
+{
+    type Out = (R, SimpleDescriptorSetBufView<Arc<BufferView<F, B>>>);
+
+    fn add_me(self, mut i: SimpleDescriptorSetBuilder<L, R>, name: &str)
+              -> SimpleDescriptorSetBuilder<L, Self::Out>
+    {
+        let (set_id, binding_id) = i.layout.descriptor_by_name(name).unwrap();    // TODO: Result instead
+        assert_eq!(set_id, i.set_id);       // TODO: Result instead
+        let desc = i.layout.descriptor(set_id, binding_id).unwrap();     // TODO: Result instead
+
+        assert!(desc.array_count == 1);     // not implemented
+        let ty = desc.ty.ty().unwrap();
+
+        // The writes below panic if the usage is missing, so we only add them if the check
+        // passed. `build()` fails anyway if it didn't.
+        if i.check_usage(|| DescriptorWrite::check_buffer_usage(ty, self.buffer())) {
+            i.writes.push(match ty {
+                DescriptorType::UniformTexelBuffer => {
+                    DescriptorWrite::uniform_texel_buffer(binding_id as u32, 0, &self)
+                },
+                DescriptorType::StorageTexelBuffer => {
+                    DescriptorWrite::storage_texel_buffer(binding_id as u32, 0, &self)
+                },
+                _ => panic!()
+            });
+        }
+
+        SimpleDescriptorSetBuilder {
+            layout: i.layout,
+            set_id: i.set_id,
+            writes: i.writes,
+            error: i.error,
+            resources: (i.resources, SimpleDescriptorSetBufView {
+                view: self,
                 write: !desc.readonly,
                 stage: PipelineStages::none(),      // FIXME:
                 access: AccessFlagBits::none(),     // FIXME:
@@ -265,7 +387,9 @@ unsafe impl<L, R, T> SimpleDescriptorSetImageExt<L, R> for T
         let desc = i.layout.descriptor(set_id, binding_id).unwrap();     // TODO: Result instead
 
         assert!(desc.array_count == 1);     // not implemented
-        i.writes.push(match desc.ty.ty().unwrap() {
+        let ty = desc.ty.ty().unwrap();
+        i.check_usage(|| DescriptorWrite::check_image_usage(ty, &image_view));
+        i.writes.push(match ty {
             DescriptorType::SampledImage => {
                 DescriptorWrite::sampled_image(binding_id as u32, 0, &image_view)
             },
@@ -282,6 +406,7 @@ unsafe impl<L, R, T> SimpleDescriptorSetImageExt<L, R> for T
             layout: i.layout,
             set_id: i.set_id,
             writes: i.writes,
+            error: i.error,
             resources: (i.resources, SimpleDescriptorSetImg {
                 image: image_view,
                 sampler: None,
@@ -313,7 +438,12 @@ unsafe impl<L, R, T> SimpleDescriptorSetImageExt<L, R> for (T, Arc<Sampler>)
         let desc = i.layout.descriptor(set_id, binding_id).unwrap();     // TODO: Result instead
 
         assert!(desc.array_count == 1);     // not implemented
-        i.writes.push(match desc.ty.ty().unwrap() {
+        let ty = desc.ty.ty().unwrap();
+        i.check_usage(|| DescriptorWrite::check_image_usage(ty, &image_view));
+        if !DescriptorWrite::check_linear_filtering(&self.1, &image_view) {
+            i.add_error(SimpleDescriptorSetError::LinearFilteringNotSupported);
+        }
+        i.writes.push(match ty {
             DescriptorType::CombinedImageSampler => {
                 DescriptorWrite::combined_image_sampler(binding_id as u32, 0, &self.1, &image_view)
             },
@@ -324,6 +454,7 @@ unsafe impl<L, R, T> SimpleDescriptorSetImageExt<L, R> for (T, Arc<Sampler>)
             layout: i.layout,
             set_id: i.set_id,
             writes: i.writes,
+            error: i.error,
             resources: (i.resources, SimpleDescriptorSetImg {
                 image: image_view,
                 sampler: Some(self.1),
@@ -354,12 +485,17 @@ unsafe impl<L, R, T> SimpleDescriptorSetImageExt<L, R> for Vec<(T, Arc<Sampler>)
         let desc = i.layout.descriptor(set_id, binding_id).unwrap();     // TODO: Result instead
 
         assert_eq!(desc.array_count as usize, self.len());     // not implemented
+        let ty = desc.ty.ty().unwrap();
 
         let mut imgs = Vec::new();
         for (num, (img, sampler)) in self.into_iter().enumerate() {
             let image_view = img.access();
+            i.check_usage(|| DescriptorWrite::check_image_usage(ty, &image_view));
+            if !DescriptorWrite::check_linear_filtering(&sampler, &image_view) {
+                i.add_error(SimpleDescriptorSetError::LinearFilteringNotSupported);
+            }
 
-            i.writes.push(match desc.ty.ty().unwrap() {
+            i.writes.push(match ty {
                 DescriptorType::CombinedImageSampler => {
                     DescriptorWrite::combined_image_sampler(binding_id as u32, num as u32,
                                                             &sampler, &image_view)
@@ -385,11 +521,60 @@ unsafe impl<L, R, T> SimpleDescriptorSetImageExt<L, R> for Vec<(T, Arc<Sampler>)
             layout: i.layout,
             set_id: i.set_id,
             writes: i.writes,
+            error: i.error,
             resources: (i.resources, imgs),
         }
     }
 }
 
+/// Error that can happen when building a `SimpleDescriptorSet`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SimpleDescriptorSetError {
+    /// A buffer, buffer view or image is missing the usage required by its descriptor.
+    MissingUsage(MissingUsageError),
+
+    /// A sampler that uses a linear filtering was paired with an image view whose format doesn't
+    /// support linear filtering.
+    LinearFilteringNotSupported,
+}
+
+impl error::Error for SimpleDescriptorSetError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SimpleDescriptorSetError::MissingUsage(_) => {
+                "a resource is missing the usage required by its descriptor"
+            },
+            SimpleDescriptorSetError::LinearFilteringNotSupported => {
+                "a sampler that uses a linear filtering was paired with an image view whose \
+                 format doesn't support it"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            SimpleDescriptorSetError::MissingUsage(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<MissingUsageError> for SimpleDescriptorSetError {
+    #[inline]
+    fn from(err: MissingUsageError) -> SimpleDescriptorSetError {
+        SimpleDescriptorSetError::MissingUsage(err)
+    }
+}
+
+impl fmt::Display for SimpleDescriptorSetError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
 /*
 /// Internal trait related to the `SimpleDescriptorSet` system.
 pub unsafe trait SimpleDescriptorSetResourcesCollection {
@@ -403,9 +588,94 @@ unsafe impl SimpleDescriptorSetResourcesCollection for () {
     }
 }*/
 
+/// Internal trait related to the `SimpleDescriptorSet` system. Implemented on the list of
+/// resources of a set.
+pub unsafe trait SimpleDescriptorSetResources {
+    /// Returns the buffer written in the given binding, if it is a uniform or storage buffer.
+    fn buffer(&self, binding: usize) -> Option<&BufferAccess>;
+
+    /// Appends the buffers of the list to `list`. Includes buffer views.
+    fn buffers<'a>(&'a self, list: &mut Vec<&'a BufferAccess>);
+}
+
+unsafe impl SimpleDescriptorSetResources for () {
+    #[inline]
+    fn buffer(&self, _: usize) -> Option<&BufferAccess> {
+        None
+    }
+
+    #[inline]
+    fn buffers<'a>(&'a self, _: &mut Vec<&'a BufferAccess>) {
+    }
+}
+
+unsafe impl<R, B> SimpleDescriptorSetResources for (R, SimpleDescriptorSetBuf<B>)
+    where R: SimpleDescriptorSetResources, B: BufferAccess
+{
+    #[inline]
+    fn buffer(&self, binding: usize) -> Option<&BufferAccess> {
+        if self.1.binding == binding {
+            Some(&self.1.buffer)
+        } else {
+            self.0.buffer(binding)
+        }
+    }
+
+    #[inline]
+    fn buffers<'a>(&'a self, list: &mut Vec<&'a BufferAccess>) {
+        self.0.buffers(list);
+        list.push(&self.1.buffer);
+    }
+}
+
+unsafe impl<R, V> SimpleDescriptorSetResources for (R, SimpleDescriptorSetBufView<V>)
+    where R: SimpleDescriptorSetResources, V: BufferViewRef
+{
+    #[inline]
+    fn buffer(&self, binding: usize) -> Option<&BufferAccess> {
+        self.0.buffer(binding)
+    }
+
+    #[inline]
+    fn buffers<'a>(&'a self, list: &mut Vec<&'a BufferAccess>) {
+        self.0.buffers(list);
+        list.push(self.1.view.view().buffer());
+    }
+}
+
+unsafe impl<R, I> SimpleDescriptorSetResources for (R, SimpleDescriptorSetImg<I>)
+    where R: SimpleDescriptorSetResources
+{
+    #[inline]
+    fn buffer(&self, binding: usize) -> Option<&BufferAccess> {
+        self.0.buffer(binding)
+    }
+
+    #[inline]
+    fn buffers<'a>(&'a self, list: &mut Vec<&'a BufferAccess>) {
+        self.0.buffers(list);
+    }
+}
+
+unsafe impl<R, I> SimpleDescriptorSetResources for (R, Vec<SimpleDescriptorSetImg<I>>)
+    where R: SimpleDescriptorSetResources
+{
+    #[inline]
+    fn buffer(&self, binding: usize) -> Option<&BufferAccess> {
+        self.0.buffer(binding)
+    }
+
+    #[inline]
+    fn buffers<'a>(&'a self, list: &mut Vec<&'a BufferAccess>) {
+        self.0.buffers(list);
+    }
+}
+
 /// Internal object related to the `SimpleDescriptorSet` system.
 pub struct SimpleDescriptorSetBuf<B> {
     buffer: B,
+    // The binding of the buffer in the set.
+    binding: usize,
     write: bool,
     stage: PipelineStages,
     access: AccessFlagBits,
@@ -517,3 +787,68 @@ unsafe impl<A, B> SimpleDescriptorSetResourcesCollection for (A, B)
         self.1.add_transition(sink);
     }
 }*/
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use descriptor::descriptor::DescriptorBufferContentDesc;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::MissingUsageError;
+    use descriptor::descriptor_set::SimpleDescriptorSetError;
+    use descriptor::pipeline_layout::PipelineLayout;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescNames;
+    use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+
+    // Layout with a storage buffer named `buf` at (0, 0).
+    struct Layout;
+    unsafe impl PipelineLayoutDesc for Layout {
+        fn num_sets(&self) -> usize { 1 }
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            if set == 0 { Some(1) } else { None }
+        }
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            if (set, binding) != (0, 0) {
+                return None;
+            }
+
+            Some(DescriptorDesc {
+                ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                    dynamic: Some(false),
+                    storage: true,
+                    content: DescriptorBufferContentDesc::F32,
+                }),
+                array_count: 1,
+                stages: ShaderStages::compute(),
+                readonly: false,
+            })
+        }
+        fn num_push_constants_ranges(&self) -> usize { 0 }
+        fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> { None }
+    }
+    unsafe impl PipelineLayoutDescNames for Layout {
+        fn descriptor_by_name(&self, name: &str) -> Option<(usize, usize)> {
+            if name == "buf" { Some((0, 0)) } else { None }
+        }
+    }
+
+    #[test]
+    fn missing_storage_usage() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let layout = Arc::new(PipelineLayout::new(device.clone(), Layout).unwrap());
+        let usage = BufferUsage { uniform_buffer: true, .. BufferUsage::none() };
+        let buffer = CpuAccessibleBuffer::from_data(device, usage, Some(queue.family()), 0u32)
+            .unwrap();
+
+        match simple_descriptor_set!(layout, 0, { buf: buffer }) {
+            Err(SimpleDescriptorSetError::MissingUsage(MissingUsageError::StorageBuffer)) => (),
+            _ => panic!()
+        }
+    }
+}
@@ -15,7 +15,6 @@ use device::DeviceOwned;
 use descriptor::descriptor_set::DescriptorsCount;
 use descriptor::descriptor_set::DescriptorPool;
 use descriptor::descriptor_set::DescriptorPoolAlloc;
-use descriptor::descriptor_set::DescriptorPoolAllocError;
 use descriptor::descriptor_set::UnsafeDescriptorPool;
 use descriptor::descriptor_set::UnsafeDescriptorSet;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
@@ -26,6 +25,10 @@ use OomError;
 /// Whenever a set is allocated, this implementation will try to find a pool that has some space
 /// for it. If there is one, allocate from it. If there is none, create a new pool whose capacity
 /// is 40 sets and 40 times the requested descriptors. This number is arbitrary.
+///
+/// If allocating from a pool returns a `FragmentedPool` or an `OutOfPoolMemory` error, the pool
+/// is considered full until one of its sets is freed, and the allocation is retried with the
+/// other pools or with a new one. These errors are therefore never returned to the user.
 pub struct StdDescriptorPool {
     device: Arc<Device>,
     pools: Mutex<Vec<Arc<Mutex<Pool>>>>,
@@ -35,6 +38,9 @@ struct Pool {
     pool: UnsafeDescriptorPool,
     remaining_capacity: DescriptorsCount,
     remaining_sets_count: u32,
+    // True if allocating from the pool returned `FragmentedPool` or `OutOfPoolMemory`. Cleared
+    // when one of its sets is freed.
+    full: bool,
 }
 
 impl StdDescriptorPool {
@@ -69,7 +75,7 @@ unsafe impl DescriptorPool for Arc<StdDescriptorPool> {
         for pool_arc in pools.iter_mut() {
             let mut pool = pool_arc.lock().unwrap();
 
-            if pool.remaining_sets_count == 0 {
+            if pool.full || pool.remaining_sets_count == 0 {
                 continue;
             }
 
@@ -77,22 +83,23 @@ unsafe impl DescriptorPool for Arc<StdDescriptorPool> {
                 continue;
             }
 
-            // Note that we decrease these values *before* trying to allocate from the pool.
-            // If allocating from the pool results in an error, we just ignore it. In order to
-            // avoid trying the same failing pool every time, we "pollute" it by reducing the
-            // available space.
-            pool.remaining_sets_count -= 1;
-            pool.remaining_capacity -= *layout.descriptors_count();
-
             let alloc = unsafe {
                 match pool.pool.alloc(Some(layout)) {
                     Ok(mut sets) => sets.next().unwrap(),
-                    // An error can happen if we're out of memory, or if the pool is fragmented.
-                    // We handle these errors by just ignoring this pool and trying the next ones.
-                    Err(_) => continue,
+                    // The pool is fragmented or its real capacity is lower than what we tracked.
+                    // We consider it full until one of its sets is freed, and try the next ones.
+                    Err(ref err) if err.is_pool_exhausted() => {
+                        pool.full = true;
+                        continue;
+                    },
+                    // Creating a new pool would most likely fail as well.
+                    Err(err) => return Err(err.into()),
                 }
             };
 
+            pool.remaining_sets_count -= 1;
+            pool.remaining_capacity -= *layout.descriptors_count();
+
             return Ok(StdDescriptorPoolAlloc {
                 pool: pool_arc.clone(),
                 set: Some(alloc),
@@ -110,16 +117,9 @@ unsafe impl DescriptorPool for Arc<StdDescriptorPool> {
         let alloc = unsafe {
             match new_pool.alloc(Some(layout)) {
                 Ok(mut sets) => sets.next().unwrap(),
-                Err(DescriptorPoolAllocError::OutOfHostMemory) => {
-                    return Err(OomError::OutOfHostMemory);
-                },
-                Err(DescriptorPoolAllocError::OutOfDeviceMemory) => {
-                    return Err(OomError::OutOfDeviceMemory);
-                },
-                // A fragmented pool error can't happen at the first ever allocation.
-                Err(DescriptorPoolAllocError::FragmentedPool) => unreachable!(),
-                // Out of pool memory cannot happen at the first ever allocation.
-                Err(DescriptorPoolAllocError::OutOfPoolMemory) => unreachable!(),
+                // `FragmentedPool` and `OutOfPoolMemory` shouldn't happen at the first ever
+                // allocation, but `alloc` also returns `FragmentedPool` for unknown errors.
+                Err(err) => return Err(err.into()),
             }
         };
 
@@ -127,6 +127,7 @@ unsafe impl DescriptorPool for Arc<StdDescriptorPool> {
             pool: new_pool,
             remaining_capacity: count - *layout.descriptors_count(),
             remaining_sets_count: 40 - 1,
+            full: false,
         }));
 
         pools.push(pool_obj.clone());
@@ -167,6 +168,91 @@ impl Drop for StdDescriptorPoolAlloc {
             // Add back the capacity only after freeing, in case of a panic during the free.
             pool.remaining_sets_count += 1;
             pool.remaining_capacity += self.descriptors;
+            pool.full = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use descriptor::descriptor::DescriptorBufferContentDesc;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorPool;
+    use descriptor::descriptor_set::StdDescriptorPool;
+    use descriptor::descriptor_set::UnsafeDescriptorPool;
+    use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use descriptor::descriptor_set::std_pool::Pool;
+
+    fn uniform_buffer_desc() -> DescriptorDesc {
+        DescriptorDesc {
+            ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                dynamic: Some(false),
+                storage: false,
+                content: DescriptorBufferContentDesc::F32,
+            }),
+            array_count: 1,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
         }
     }
+
+    #[test]
+    fn more_sets_than_one_pool() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                    iter::once(Some(uniform_buffer_desc())))
+            .unwrap();
+        let pool = Arc::new(StdDescriptorPool::new(device));
+
+        let sets = (0 .. 100).map(|_| pool.alloc(&layout).unwrap()).collect::<Vec<_>>();
+        assert_eq!(sets.len(), 100);
+        assert!(pool.pools.lock().unwrap().len() >= 3);
+    }
+
+    #[test]
+    fn exhausted_pool_skipped() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                    iter::once(Some(uniform_buffer_desc())))
+            .unwrap();
+        let pool = Arc::new(StdDescriptorPool::new(device.clone()));
+
+        // A pool that can only hold one set, but whose tracked capacity is larger. Allocating a
+        // second set from it fails with `OutOfPoolMemory`.
+        let count = *layout.descriptors_count();
+        let small = UnsafeDescriptorPool::new(device, &count, 1, true).unwrap();
+        pool.pools.lock().unwrap().push(Arc::new(Mutex::new(Pool {
+            pool: small,
+            remaining_capacity: count * 10,
+            remaining_sets_count: 10,
+            full: false,
+        })));
+
+        let first = pool.alloc(&layout).unwrap();
+        let _second = pool.alloc(&layout).unwrap();
+
+        {
+            let pools = pool.pools.lock().unwrap();
+            assert_eq!(pools.len(), 2);
+            let small = pools[0].lock().unwrap();
+            assert!(small.full);
+            // Only the successful allocation is counted.
+            assert_eq!(small.remaining_sets_count, 9);
+            assert!(small.remaining_capacity == count * 9);
+        }
+
+        drop(first);
+        let pools = pool.pools.lock().unwrap();
+        let small = pools[0].lock().unwrap();
+        assert!(!small.full);
+        assert_eq!(small.remaining_sets_count, 10);
+    }
 }
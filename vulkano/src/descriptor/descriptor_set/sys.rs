@@ -24,6 +24,7 @@ use descriptor::descriptor::DescriptorType;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
 use device::Device;
 use device::DeviceOwned;
+use image::ImageLayout;
 use image::ImageViewAccess;
 use sampler::Sampler;
 
@@ -92,18 +93,24 @@ macro_rules! descriptors_count {
             /// Adds one descriptor of the given type to the count.
             #[inline]
             pub fn add_one(&mut self, ty: DescriptorType) {
+                self.add_num(ty, 1);
+            }
+
+            /// Adds `num` descriptors of the given type to the count.
+            #[inline]
+            pub fn add_num(&mut self, ty: DescriptorType, num: u32) {
                 match ty {
-                    DescriptorType::Sampler => self.sampler += 1,
-                    DescriptorType::CombinedImageSampler => self.combined_image_sampler += 1,
-                    DescriptorType::SampledImage => self.sampled_image += 1,
-                    DescriptorType::StorageImage => self.storage_image += 1,
-                    DescriptorType::UniformTexelBuffer => self.uniform_texel_buffer += 1,
-                    DescriptorType::StorageTexelBuffer => self.storage_texel_buffer += 1,
-                    DescriptorType::UniformBuffer => self.uniform_buffer += 1,
-                    DescriptorType::StorageBuffer => self.storage_buffer += 1,
-                    DescriptorType::UniformBufferDynamic => self.uniform_buffer_dynamic += 1,
-                    DescriptorType::StorageBufferDynamic => self.storage_buffer_dynamic += 1,
-                    DescriptorType::InputAttachment => self.input_attachment += 1,
+                    DescriptorType::Sampler => self.sampler += num,
+                    DescriptorType::CombinedImageSampler => self.combined_image_sampler += num,
+                    DescriptorType::SampledImage => self.sampled_image += num,
+                    DescriptorType::StorageImage => self.storage_image += num,
+                    DescriptorType::UniformTexelBuffer => self.uniform_texel_buffer += num,
+                    DescriptorType::StorageTexelBuffer => self.storage_texel_buffer += num,
+                    DescriptorType::UniformBuffer => self.uniform_buffer += num,
+                    DescriptorType::StorageBuffer => self.storage_buffer += num,
+                    DescriptorType::UniformBufferDynamic => self.uniform_buffer_dynamic += num,
+                    DescriptorType::StorageBufferDynamic => self.storage_buffer_dynamic += num,
+                    DescriptorType::InputAttachment => self.input_attachment += num,
                 };
             }
         }
@@ -367,6 +374,9 @@ impl UnsafeDescriptorPool {
             vk::ERROR_OUT_OF_POOL_MEMORY_KHR => {
                 return Err(DescriptorPoolAllocError::OutOfPoolMemory);
             },
+            vk::ERROR_FRAGMENTED_POOL => {
+                return Err(DescriptorPoolAllocError::FragmentedPool);
+            },
             c if (c as i32) < 0 => {
                 return Err(DescriptorPoolAllocError::FragmentedPool);
             },
@@ -491,6 +501,36 @@ impl fmt::Display for DescriptorPoolAllocError {
     }
 }
 
+impl DescriptorPoolAllocError {
+    /// Returns true for `FragmentedPool` and `OutOfPoolMemory`. These errors mean that this
+    /// specific pool can't hold the set, and allocating from another pool may succeed.
+    #[inline]
+    pub fn is_pool_exhausted(&self) -> bool {
+        match *self {
+            DescriptorPoolAllocError::FragmentedPool |
+            DescriptorPoolAllocError::OutOfPoolMemory => true,
+            DescriptorPoolAllocError::OutOfHostMemory |
+            DescriptorPoolAllocError::OutOfDeviceMemory => false,
+        }
+    }
+}
+
+impl From<DescriptorPoolAllocError> for OomError {
+    /// `FragmentedPool` and `OutOfPoolMemory` are turned into `OutOfDeviceMemory`.
+    ///
+    /// Note that `alloc` returns `FragmentedPool` for any unknown error, so these errors can
+    /// happen even with a new pool.
+    #[inline]
+    fn from(err: DescriptorPoolAllocError) -> OomError {
+        match err {
+            DescriptorPoolAllocError::OutOfHostMemory => OomError::OutOfHostMemory,
+            DescriptorPoolAllocError::OutOfDeviceMemory |
+            DescriptorPoolAllocError::FragmentedPool |
+            DescriptorPoolAllocError::OutOfPoolMemory => OomError::OutOfDeviceMemory,
+        }
+    }
+}
+
 /// Iterator to the descriptor sets allocated from an unsafe descriptor pool.
 #[derive(Debug)]
 pub struct UnsafeDescriptorPoolAllocIter {
@@ -790,6 +830,8 @@ impl DescriptorWrite {
         }
     }
 
+    /// > **Note**: If the sampler uses a linear filtering, the format of the image view must
+    /// > support it. Use `check_linear_filtering` to find out.
     #[inline]
     pub fn combined_image_sampler<I>(binding: u32, array_element: u32, sampler: &Arc<Sampler>, image: &I) -> DescriptorWrite
         where I: ImageViewAccess
@@ -908,6 +950,30 @@ impl DescriptorWrite {
         }
     }
 
+    /// Same as `input_attachment`, but uses `layout` instead of the layout returned by
+    /// `descriptor_set_input_attachment_layout()`.
+    ///
+    /// This is useful when the image is also an attachment of the current subpass, in which case
+    /// it must be in the `General` layout.
+    ///
+    /// # Safety
+    ///
+    /// The image must be in the `layout` layout when the descriptor set is used.
+    #[inline]
+    pub unsafe fn input_attachment_with_layout<I>(binding: u32, array_element: u32, image: &I,
+                                                  layout: ImageLayout) -> DescriptorWrite
+        where I: ImageViewAccess
+    {
+        DescriptorWrite {
+            binding: binding,
+            first_array_element: array_element,
+            inner: smallvec!({
+                DescriptorWriteInner::InputAttachment(image.inner().internal_object(),
+                                                      layout as u32)
+            }),
+        }
+    }
+
     /// Returns the type corresponding to this write.
     #[inline]
     pub fn ty(&self) -> DescriptorType {
@@ -925,19 +991,169 @@ impl DescriptorWrite {
             DescriptorWriteInner::InputAttachment(_, _) => DescriptorType::InputAttachment,
         }
     }
+
+    /// Checks that `buffer` was created with the usage required to be written in a descriptor of
+    /// type `ty`. For texel buffers, pass the buffer of the view.
+    ///
+    /// Descriptor types that aren't buffer types are ignored.
+    pub fn check_buffer_usage<B>(ty: DescriptorType, buffer: &B) -> Result<(), MissingUsageError>
+        where B: BufferAccess
+    {
+        let inner = buffer.inner().buffer;
+
+        match ty {
+            DescriptorType::UniformBuffer | DescriptorType::UniformBufferDynamic => {
+                if !inner.usage_uniform_buffer() {
+                    return Err(MissingUsageError::UniformBuffer);
+                }
+            },
+            DescriptorType::StorageBuffer | DescriptorType::StorageBufferDynamic => {
+                if !inner.usage_storage_buffer() {
+                    return Err(MissingUsageError::StorageBuffer);
+                }
+            },
+            DescriptorType::UniformTexelBuffer => {
+                if !inner.usage_uniform_texel_buffer() {
+                    return Err(MissingUsageError::UniformTexelBuffer);
+                }
+            },
+            DescriptorType::StorageTexelBuffer => {
+                if !inner.usage_storage_texel_buffer() {
+                    return Err(MissingUsageError::StorageTexelBuffer);
+                }
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `image` was created with the usage required to be written in a descriptor of
+    /// type `ty`.
+    ///
+    /// Descriptor types that aren't image types are ignored.
+    pub fn check_image_usage<I>(ty: DescriptorType, image: &I) -> Result<(), MissingUsageError>
+        where I: ImageViewAccess
+    {
+        let inner = image.inner();
+
+        match ty {
+            DescriptorType::SampledImage | DescriptorType::CombinedImageSampler => {
+                if !inner.usage_sampled() {
+                    return Err(MissingUsageError::Sampled);
+                }
+            },
+            DescriptorType::StorageImage => {
+                if !inner.usage_storage() {
+                    return Err(MissingUsageError::Storage);
+                }
+            },
+            DescriptorType::InputAttachment => {
+                if !inner.usage_input_attachment() {
+                    return Err(MissingUsageError::InputAttachment);
+                }
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Returns false if `sampler` uses a linear filtering but the format of `image` doesn't
+    /// support it, in which case they can't be written together in a combined image sampler.
+    #[inline]
+    pub fn check_linear_filtering<I>(sampler: &Sampler, image: &I) -> bool
+        where I: ImageViewAccess
+    {
+        !sampler.uses_linear_filtering() || image.parent().inner().supports_linear_filtering()
+    }
+}
+
+/// Error when a buffer or an image is written in a descriptor, but was not created with the
+/// usage that the type of the descriptor requires. Each variant names the missing usage.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MissingUsageError {
+    /// The buffer is missing the `uniform_buffer` usage.
+    UniformBuffer,
+    /// The buffer is missing the `storage_buffer` usage.
+    StorageBuffer,
+    /// The buffer is missing the `uniform_texel_buffer` usage.
+    UniformTexelBuffer,
+    /// The buffer is missing the `storage_texel_buffer` usage.
+    StorageTexelBuffer,
+    /// The image is missing the `sampled` usage.
+    Sampled,
+    /// The image is missing the `storage` usage.
+    Storage,
+    /// The image is missing the `input_attachment` usage.
+    InputAttachment,
+}
+
+impl error::Error for MissingUsageError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            MissingUsageError::UniformBuffer => {
+                "the buffer is missing the `uniform_buffer` usage"
+            },
+            MissingUsageError::StorageBuffer => {
+                "the buffer is missing the `storage_buffer` usage"
+            },
+            MissingUsageError::UniformTexelBuffer => {
+                "the buffer is missing the `uniform_texel_buffer` usage"
+            },
+            MissingUsageError::StorageTexelBuffer => {
+                "the buffer is missing the `storage_texel_buffer` usage"
+            },
+            MissingUsageError::Sampled => {
+                "the image is missing the `sampled` usage"
+            },
+            MissingUsageError::Storage => {
+                "the image is missing the `storage` usage"
+            },
+            MissingUsageError::InputAttachment => {
+                "the image is missing the `input_attachment` usage"
+            },
+        }
+    }
+}
+
+impl fmt::Display for MissingUsageError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::iter;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
     use descriptor::descriptor::DescriptorDesc;
     use descriptor::descriptor::DescriptorDescTy;
     use descriptor::descriptor::DescriptorBufferDesc;
     use descriptor::descriptor::DescriptorBufferContentDesc;
     use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor::DescriptorType;
+    use descriptor::descriptor_set::DescriptorPoolAllocError;
+    use descriptor::descriptor_set::DescriptorWrite;
     use descriptor::descriptor_set::DescriptorsCount;
+    use descriptor::descriptor_set::MissingUsageError;
     use descriptor::descriptor_set::UnsafeDescriptorPool;
     use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use format::D32Sfloat;
+    use format::Format;
+    use format::R8G8B8A8Unorm;
+    use image::AttachmentImage;
+    use image::ImageTiling;
+    use image::ImageUsage;
+    use image::ImageView;
+    use sampler::Filter;
+    use sampler::MipmapMode;
+    use sampler::Sampler;
+    use sampler::SamplerAddressMode;
+    use OomError;
 
     #[test]
     fn pool_create() {
@@ -1043,4 +1259,83 @@ mod tests {
             assert_eq!(sets.count(), 0);
         }
     }
+
+    #[test]
+    fn storage_buffer_missing_usage() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let usage = BufferUsage { uniform_buffer: true, .. BufferUsage::none() };
+        let buffer = CpuAccessibleBuffer::from_data(device, usage, Some(queue.family()), 0u32)
+            .unwrap();
+
+        assert_eq!(DescriptorWrite::check_buffer_usage(DescriptorType::StorageBuffer, &buffer),
+                   Err(MissingUsageError::StorageBuffer));
+        assert_eq!(DescriptorWrite::check_buffer_usage(DescriptorType::UniformBuffer, &buffer),
+                   Ok(()));
+    }
+
+    #[test]
+    fn texel_buffer_missing_usage() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let usage = BufferUsage { uniform_texel_buffer: true, .. BufferUsage::none() };
+        let buffer = CpuAccessibleBuffer::from_data(device, usage, Some(queue.family()), 0u32)
+            .unwrap();
+
+        assert_eq!(DescriptorWrite::check_buffer_usage(DescriptorType::StorageTexelBuffer,
+                                                       &buffer),
+                   Err(MissingUsageError::StorageTexelBuffer));
+        assert_eq!(DescriptorWrite::check_buffer_usage(DescriptorType::UniformTexelBuffer,
+                                                       &buffer),
+                   Ok(()));
+    }
+
+    #[test]
+    fn sampled_image_missing_usage() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let image = AttachmentImage::new(device, [16, 16], R8G8B8A8Unorm).unwrap().access();
+        assert_eq!(DescriptorWrite::check_image_usage(DescriptorType::SampledImage, &image),
+                   Err(MissingUsageError::Sampled));
+    }
+
+    #[test]
+    fn pool_exhausted_errors() {
+        assert!(DescriptorPoolAllocError::FragmentedPool.is_pool_exhausted());
+        assert!(DescriptorPoolAllocError::OutOfPoolMemory.is_pool_exhausted());
+        assert!(!DescriptorPoolAllocError::OutOfHostMemory.is_pool_exhausted());
+
+        assert_eq!(OomError::from(DescriptorPoolAllocError::FragmentedPool),
+                   OomError::OutOfDeviceMemory);
+        assert_eq!(OomError::from(DescriptorPoolAllocError::OutOfHostMemory),
+                   OomError::OutOfHostMemory);
+    }
+
+    #[test]
+    fn linear_filtering_matches_format() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage { sampled: true, .. ImageUsage::none() };
+        let image = AttachmentImage::with_usage(device.clone(), [16, 16], D32Sfloat, usage)
+            .unwrap().access();
+        let sampler = Sampler::simple_repeat_linear(device.clone());
+
+        let filterable = Format::D32Sfloat.is_linear_filterable(device.physical_device(),
+                                                                ImageTiling::Optimal);
+        assert_eq!(DescriptorWrite::check_linear_filtering(&sampler, &image), filterable);
+    }
+
+    #[test]
+    fn nearest_filtering_always_ok() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage { sampled: true, .. ImageUsage::none() };
+        let image = AttachmentImage::with_usage(device.clone(), [16, 16], D32Sfloat, usage)
+            .unwrap().access();
+        let sampler = Sampler::new(device, Filter::Nearest, Filter::Nearest, MipmapMode::Nearest,
+                                   SamplerAddressMode::Repeat, SamplerAddressMode::Repeat,
+                                   SamplerAddressMode::Repeat, 0.0, 1.0, 0.0, 0.0).unwrap();
+
+        assert!(DescriptorWrite::check_linear_filtering(&sampler, &image));
+    }
 }
@@ -22,6 +22,7 @@ use descriptor::descriptor::DescriptorDesc;
 use descriptor::descriptor_set::DescriptorsCount;
 use device::Device;
 use device::DeviceOwned;
+use sampler::Sampler;
 
 /// Describes to the Vulkan implementation the layout of all descriptors within a descriptor set.
 ///
@@ -35,6 +36,8 @@ pub struct UnsafeDescriptorSetLayout {
     device: Arc<Device>,
     // Number of descriptors.
     descriptors_count: DescriptorsCount,
+    // Immutable samplers of the layout. Kept alive for as long as the layout exists.
+    immutable_samplers: Vec<Arc<Sampler>>,
 }
 
 impl UnsafeDescriptorSetLayout {
@@ -43,31 +46,71 @@ impl UnsafeDescriptorSetLayout {
     /// The descriptors must be passed in the order of the bindings. In order words, descriptor
     /// at bind point 0 first, then descriptor at bind point 1, and so on. If a binding must remain
     /// empty, you can make the iterator yield `None` for an element.
+    #[inline]
     pub fn new<I>(device: Arc<Device>, descriptors: I)
                   -> Result<UnsafeDescriptorSetLayout, OomError>
         where I: IntoIterator<Item = Option<DescriptorDesc>>
     {
-        let mut descriptors_count = DescriptorsCount::zero();
+        let descriptors = descriptors.into_iter().map(|desc| desc.map(|desc| (desc, Vec::new())));
+        UnsafeDescriptorSetLayout::with_immutable_samplers(device, descriptors)
+    }
 
-        let bindings = descriptors.into_iter().enumerate().filter_map(|(binding, desc)| {
-            let desc = match desc {
-                Some(d) => d,
-                None => return None
-            };
+    /// Builds a new `UnsafeDescriptorSetLayout` with the given descriptors, some of which have
+    /// immutable samplers.
+    ///
+    /// Same as `new`, except that each descriptor is accompanied by a list of samplers. If the
+    /// list is empty, the descriptor doesn't use immutable samplers. Otherwise the list must
+    /// contain one sampler per element of the descriptor's array, and these samplers are
+    /// permanently bound to the descriptor.
+    ///
+    /// Samplers that perform a YCbCr conversion can only be used this way.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if a list of samplers is not empty and its length is not equal to the array
+    ///   count of the descriptor.
+    /// - Panics if a sampler was not created with the same device.
+    ///
+    pub fn with_immutable_samplers<I>(device: Arc<Device>, descriptors: I)
+                                      -> Result<UnsafeDescriptorSetLayout, OomError>
+        where I: IntoIterator<Item = Option<(DescriptorDesc, Vec<Arc<Sampler>>)>>
+    {
+        let mut descriptors_count = DescriptorsCount::zero();
+        let mut immutable_samplers = Vec::new();
+
+        let descriptors = descriptors.into_iter().enumerate().filter_map(|(binding, desc)| {
+            desc.map(|(desc, samplers)| {
+                assert!(samplers.is_empty() || samplers.len() == desc.array_count as usize);
+                for sampler in samplers.iter() {
+                    assert_eq!(sampler.device().internal_object(), device.internal_object());
+                }
+
+                let raw_samplers = samplers.iter()
+                    .map(|s| s.internal_object())
+                    .collect::<SmallVec<[_; 4]>>();
+                immutable_samplers.extend(samplers);
+                (binding, desc, raw_samplers)
+            })
+        }).collect::<SmallVec<[_; 32]>>();
 
+        let bindings = descriptors.iter().map(|&(binding, ref desc, ref raw_samplers)| {
             // FIXME: it is not legal to pass eg. the TESSELLATION_SHADER bit when the device
             //        doesn't have tess shaders enabled
 
             let ty = desc.ty.ty().unwrap();     // TODO: shouldn't panic
             descriptors_count.add_one(ty);
 
-            Some(vk::DescriptorSetLayoutBinding {
+            vk::DescriptorSetLayoutBinding {
                 binding: binding as u32,
                 descriptorType: ty as u32,
                 descriptorCount: desc.array_count,
                 stageFlags: desc.stages.into(),
-                pImmutableSamplers: ptr::null(),        // FIXME: not yet implemented
-            })
+                pImmutableSamplers: if raw_samplers.is_empty() {
+                    ptr::null()
+                } else {
+                    raw_samplers.as_ptr()
+                },
+            }
         }).collect::<SmallVec<[_; 32]>>();
 
         // Note that it seems legal to have no descriptor at all in the set.
@@ -92,6 +135,7 @@ impl UnsafeDescriptorSetLayout {
             layout: layout,
             device: device,
             descriptors_count: descriptors_count,
+            immutable_samplers: immutable_samplers,
         })
     }
 
@@ -100,6 +144,12 @@ impl UnsafeDescriptorSetLayout {
     pub fn descriptors_count(&self) -> &DescriptorsCount {
         &self.descriptors_count
     }
+
+    /// Returns the immutable samplers of the layout, in the order of the bindings.
+    #[inline]
+    pub fn immutable_samplers(&self) -> &[Arc<Sampler>] {
+        &self.immutable_samplers
+    }
 }
 
 unsafe impl DeviceOwned for UnsafeDescriptorSetLayout {
@@ -148,6 +198,7 @@ mod tests {
     use descriptor::descriptor::ShaderStages;
     use descriptor::descriptor_set::DescriptorsCount;
     use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use sampler::Sampler;
 
     #[test]
     fn empty() {
@@ -177,4 +228,26 @@ mod tests {
             .. DescriptorsCount::zero()
         });
     }
+    #[test]
+    fn immutable_samplers() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let sampler = Sampler::simple_repeat_linear(device.clone());
+
+        let layout = DescriptorDesc {
+            ty: DescriptorDescTy::Sampler,
+            array_count: 2,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
+        };
+
+        let sl = UnsafeDescriptorSetLayout::with_immutable_samplers(device.clone(),
+            iter::once(Some((layout, vec![sampler.clone(), sampler.clone()])))).unwrap();
+
+        assert_eq!(sl.immutable_samplers().len(), 2);
+        assert_eq!(sl.descriptors_count(), &DescriptorsCount {
+            sampler: 1,
+            .. DescriptorsCount::zero()
+        });
+    }
 }
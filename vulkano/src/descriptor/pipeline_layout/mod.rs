@@ -61,9 +61,12 @@ pub use self::traits::PipelineLayoutSuperset;
 pub use self::traits::PipelineLayoutNotSupersetError;
 pub use self::traits::PipelineLayoutSetsCompatible;
 pub use self::traits::PipelineLayoutPushConstantsCompatible;
+pub use self::push_constants_only::PushConstantsOnlyLayout;
 pub use self::union::PipelineLayoutDescUnion;
+pub use self::union::PipelineLayoutDescUnionError;
 
 mod empty;
+mod push_constants_only;
 mod sys;
 mod traits;
 mod union;
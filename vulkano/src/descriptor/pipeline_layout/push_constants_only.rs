@@ -0,0 +1,171 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+
+use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor::ShaderStages;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
+use descriptor::pipeline_layout::PipelineLayoutDescNames;
+use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+
+/// Description of a pipeline layout that has no descriptor set, and a single range of push
+/// constants whose content is of type `T`.
+///
+/// The range starts at offset 0 and covers `size_of::<T>()` bytes, which must be a multiple of
+/// 4. When the layout is built, the size is compared with the `max_push_constants_size` limit of
+/// the device and a `MaxPushConstantsSizeExceeded` error is returned if it is too large.
+///
+/// # Example
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use vulkano::device::Device;
+/// use vulkano::descriptor::descriptor::ShaderStages;
+/// use vulkano::descriptor::pipeline_layout::PipelineLayoutDesc;
+/// use vulkano::descriptor::pipeline_layout::PushConstantsOnlyLayout;
+///
+/// #[derive(Copy, Clone)]
+/// struct Params {
+///     offset: [f32; 2],
+///     scale: f32,
+///     time: f32,
+/// }
+///
+/// # let device: Arc<Device> = return;
+/// let desc = PushConstantsOnlyLayout::<Params>::new(ShaderStages::compute());
+/// let pipeline_layout = desc.build(device.clone()).unwrap();
+/// ```
+pub struct PushConstantsOnlyLayout<T> {
+    stages: ShaderStages,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PushConstantsOnlyLayout<T> where T: Copy {
+    /// Builds the description of a layout whose push constants are accessible from `stages`.
+    #[inline]
+    pub fn new(stages: ShaderStages) -> PushConstantsOnlyLayout<T> {
+        PushConstantsOnlyLayout {
+            stages: stages,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the stages that can access the push constants.
+    #[inline]
+    pub fn stages(&self) -> ShaderStages {
+        self.stages
+    }
+}
+
+impl<T> Clone for PushConstantsOnlyLayout<T> {
+    #[inline]
+    fn clone(&self) -> PushConstantsOnlyLayout<T> {
+        PushConstantsOnlyLayout {
+            stages: self.stages,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Copy for PushConstantsOnlyLayout<T> {}
+
+impl<T> fmt::Debug for PushConstantsOnlyLayout<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("PushConstantsOnlyLayout")
+           .field("stages", &self.stages)
+           .field("size", &mem::size_of::<T>())
+           .finish()
+    }
+}
+
+unsafe impl<T> PipelineLayoutDesc for PushConstantsOnlyLayout<T> {
+    #[inline]
+    fn num_sets(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn num_bindings_in_set(&self, _: usize) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn descriptor(&self, _: usize, _: usize) -> Option<DescriptorDesc> {
+        None
+    }
+
+    #[inline]
+    fn num_push_constants_ranges(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn push_constants_range(&self, num: usize) -> Option<PipelineLayoutDescPcRange> {
+        if num != 0 {
+            return None;
+        }
+
+        Some(PipelineLayoutDescPcRange {
+            offset: 0,
+            size: mem::size_of::<T>(),
+            stages: self.stages,
+        })
+    }
+}
+
+unsafe impl<T> PipelineLayoutDescNames for PushConstantsOnlyLayout<T> {
+    #[inline]
+    fn descriptor_by_name(&self, _: &str) -> Option<(usize, usize)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::pipeline_layout::PipelineLayoutCreationError;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PushConstantsOnlyLayout;
+
+    #[test]
+    fn single_range() {
+        let desc = PushConstantsOnlyLayout::<[f32; 4]>::new(ShaderStages::compute());
+        assert_eq!(desc.num_sets(), 0);
+        assert_eq!(desc.num_push_constants_ranges(), 1);
+
+        let range = desc.push_constants_range(0).unwrap();
+        assert_eq!(range.offset, 0);
+        assert_eq!(range.size, 16);
+        assert_eq!(range.stages, ShaderStages::compute());
+        assert!(desc.push_constants_range(1).is_none());
+    }
+
+    #[test]
+    fn build() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let desc = PushConstantsOnlyLayout::<[u32; 4]>::new(ShaderStages::all());
+        assert!(desc.build(device).is_ok());
+    }
+
+    #[test]
+    fn too_large() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        // 64 kiB is way above the limit of all existing implementations.
+        let desc = PushConstantsOnlyLayout::<[u32; 16384]>::new(ShaderStages::compute());
+        match desc.build(device) {
+            Err(PipelineLayoutCreationError::MaxPushConstantsSizeExceeded) => (),
+            _ => panic!()
+        }
+    }
+}
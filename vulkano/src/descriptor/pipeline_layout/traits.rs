@@ -209,7 +209,35 @@ unsafe impl<T: ?Sized, U: ?Sized> PipelineLayoutSuperset<U> for T
             }
         }
 
-        // FIXME: check push constants
+        // Every byte of the push constants used by `other` must be covered by ranges of `self`
+        // that are accessible from the same stages.
+        for num in 0 .. other.num_push_constants_ranges() {
+            let range = match other.push_constants_range(num) {
+                Some(r) => r,
+                None => continue,
+            };
+
+            let end = range.offset + range.size;
+            let mut covered = range.offset;
+
+            while covered < end {
+                let next = (0 .. self.num_push_constants_ranges())
+                    .filter_map(|n| self.push_constants_range(n))
+                    .filter(|r| r.stages.is_superset_of(&range.stages))
+                    .filter(|r| r.offset <= covered && r.offset + r.size > covered)
+                    .map(|r| r.offset + r.size)
+                    .max();
+
+                match next {
+                    Some(n) => covered = n,
+                    None => return Err(PipelineLayoutNotSupersetError::PushConstantMismatch {
+                        offset: range.offset,
+                        size: range.size,
+                        stages: range.stages,
+                    }),
+                }
+            }
+        }
 
         Ok(())
     }
@@ -237,6 +265,17 @@ pub enum PipelineLayoutNotSupersetError {
         descriptor: u32,
         // TODO: child error here
     },
+
+    /// A range of push constants of the child is not entirely covered by the ranges of the
+    /// parent layout that are accessible from the same stages.
+    PushConstantMismatch {
+        /// Offset of the range of the child.
+        offset: usize,
+        /// Size of the range of the child.
+        size: usize,
+        /// Stages that access the range.
+        stages: ShaderStages,
+    },
 }
 
 impl error::Error for PipelineLayoutNotSupersetError {
@@ -252,6 +291,9 @@ impl error::Error for PipelineLayoutNotSupersetError {
             PipelineLayoutNotSupersetError::IncompatibleDescriptors { .. } => {
                 "two descriptors are incompatible"
             },
+            PipelineLayoutNotSupersetError::PushConstantMismatch { .. } => {
+                "a range of push constants is not covered by the parent layout"
+            },
         }
     }
 }
@@ -315,3 +357,45 @@ unsafe impl<T: ?Sized, U: ?Sized> PipelineLayoutPushConstantsCompatible<U> for T
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::pipeline_layout::EmptyPipelineDesc;
+    use descriptor::pipeline_layout::PipelineLayoutNotSupersetError;
+    use descriptor::pipeline_layout::PipelineLayoutSuperset;
+    use descriptor::pipeline_layout::PushConstantsOnlyLayout;
+
+    #[test]
+    fn push_constants_covered() {
+        let layout = PushConstantsOnlyLayout::<[u32; 8]>::new(ShaderStages::all());
+        let stages = ShaderStages { vertex: true, .. ShaderStages::none() };
+        let shader = PushConstantsOnlyLayout::<[u32; 4]>::new(stages);
+        assert!(layout.ensure_superset_of(&shader).is_ok());
+    }
+
+    #[test]
+    fn push_constants_too_small() {
+        let layout = PushConstantsOnlyLayout::<[u32; 2]>::new(ShaderStages::all());
+        let stages = ShaderStages { vertex: true, .. ShaderStages::none() };
+        let shader = PushConstantsOnlyLayout::<[u32; 4]>::new(stages);
+        match layout.ensure_superset_of(&shader) {
+            Err(PipelineLayoutNotSupersetError::PushConstantMismatch { offset: 0, size: 16, .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn push_constants_wrong_stages() {
+        let layout = PushConstantsOnlyLayout::<[u32; 4]>::new(ShaderStages::compute());
+        let stages = ShaderStages { fragment: true, .. ShaderStages::none() };
+        let shader = PushConstantsOnlyLayout::<[u32; 4]>::new(stages);
+        assert!(layout.ensure_superset_of(&shader).is_err());
+    }
+
+    #[test]
+    fn push_constants_missing() {
+        let shader = PushConstantsOnlyLayout::<[u32; 4]>::new(ShaderStages::compute());
+        assert!(EmptyPipelineDesc.ensure_superset_of(&shader).is_err());
+    }
+}
@@ -8,6 +8,8 @@
 // according to those terms.
 
 use std::cmp;
+use std::error;
+use std::fmt;
 use std::sync::Arc;
 use descriptor::descriptor::DescriptorDesc;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
@@ -25,12 +27,45 @@ pub struct PipelineLayoutDescUnion<A, B> {
 }
 
 impl<A, B> PipelineLayoutDescUnion<A, B> {
-    // FIXME: check collisions
+    /// Builds the union of `a` and `b`, without checking whether they are compatible.
+    ///
+    /// If `a` and `b` have different descriptors at the same binding, querying that descriptor
+    /// will panic. Use `try_new` to detect this situation at construction.
     pub fn new(a: A, b: B) -> PipelineLayoutDescUnion<A, B> {
         PipelineLayoutDescUnion { a: a, b: b }
     }
 }
 
+impl<A, B> PipelineLayoutDescUnion<A, B>
+    where A: PipelineLayoutDesc, B: PipelineLayoutDesc
+{
+    /// Builds the union of `a` and `b`.
+    ///
+    /// Returns an error if `a` and `b` both have a descriptor at the same binding but their
+    /// types don't match.
+    pub fn try_new(a: A, b: B) -> Result<PipelineLayoutDescUnion<A, B>, PipelineLayoutDescUnionError> {
+        for set in 0 .. cmp::min(a.num_sets(), b.num_sets()) {
+            let num_bindings = match (a.num_bindings_in_set(set), b.num_bindings_in_set(set)) {
+                (Some(a), Some(b)) => cmp::min(a, b),
+                _ => continue,
+            };
+
+            for binding in 0 .. num_bindings {
+                if let (Some(da), Some(db)) = (a.descriptor(set, binding), b.descriptor(set, binding)) {
+                    if da.union(&db).is_none() {
+                        return Err(PipelineLayoutDescUnionError {
+                            set: set,
+                            binding: binding,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(PipelineLayoutDescUnion { a: a, b: b })
+    }
+}
+
 unsafe impl<A, B> PipelineLayoutDesc for PipelineLayoutDescUnion<A, B>
     where A: PipelineLayoutDesc, B: PipelineLayoutDesc
 {
@@ -79,29 +114,40 @@ unsafe impl<A, B> PipelineLayoutDesc for PipelineLayoutDescUnion<A, B>
         }).next().unwrap()
     }
 
-    // TODO: needs tests
     #[inline]
     fn push_constants_range(&self, num: usize) -> Option<PipelineLayoutDescPcRange> {
-        // The strategy here is that we return the same ranges as `self.a`, except that if there
-        // happens to be a range with a similar stage in `self.b` then we adjust the offset and
-        // size of the range coming from `self.a` to include the range of `self.b`.
+        // The strategy here is that we return the same ranges as `self.a`, except that the ranges
+        // of `self.b` that share a stage with a range of `self.a` or that overlap it are merged
+        // into it. The merged range covers both ranges and has the stages of both, so that
+        // pushing constants to it is valid for all the shaders that read them.
         //
         // After all the ranges of `self.a` have been returned, we return the ones from `self.b`
-        // that don't intersect with any range of `self.a`.
+        // that haven't been merged into any range of `self.a`.
 
         if let Some(mut pc) = self.a.push_constants_range(num) {
-            // We try to find the ranges in `self.b` that share the same stages as us.
-            for n in 0 .. self.b.num_push_constants_ranges() {
-                let other_pc = self.b.push_constants_range(n).unwrap();
-
-                if other_pc.stages.intersects(&pc.stages) {
-                    if other_pc.offset < pc.offset {
-                        pc.size += pc.offset - other_pc.offset;
-                        pc.size = cmp::max(pc.size, other_pc.size);
-                        pc.offset = other_pc.offset;
-
-                    } else if other_pc.offset > pc.offset {
-                        pc.size = cmp::max(pc.size, other_pc.size + (other_pc.offset - pc.offset));
+            // Merging a range of `self.b` can make the range overlap with other ranges of
+            // `self.b`, so we loop until nothing changes.
+            let mut changed = true;
+            while changed {
+                changed = false;
+
+                for n in 0 .. self.b.num_push_constants_ranges() {
+                    let other_pc = self.b.push_constants_range(n).unwrap();
+                    if !ranges_mergeable(&pc, &other_pc) {
+                        continue;
+                    }
+
+                    let start = cmp::min(pc.offset, other_pc.offset);
+                    let end = cmp::max(pc.offset + pc.size, other_pc.offset + other_pc.size);
+                    let stages = pc.stages | other_pc.stages;
+
+                    if start != pc.offset || end != pc.offset + pc.size || stages != pc.stages {
+                        pc = PipelineLayoutDescPcRange {
+                            offset: start,
+                            size: end - start,
+                            stages: stages,
+                        };
+                        changed = true;
                     }
                 }
             }
@@ -114,23 +160,31 @@ unsafe impl<A, B> PipelineLayoutDesc for PipelineLayoutDescUnion<A, B>
             let pc = self.b.push_constants_range(b_r).unwrap();
 
             for n in 0 .. self.a.num_push_constants_ranges() {
-                let other_pc = self.a.push_constants_range(n).unwrap();
-                if other_pc.stages.intersects(&pc.stages) {
+                let merged_pc = self.push_constants_range(n).unwrap();
+                if ranges_mergeable(&merged_pc, &pc) {
                     continue 'outer_loop;
                 }
             }
-            
+
             if num == 0 {
                 return Some(pc);
             } else {
                 num -= 1;
             }
         }
-        
+
         None
     }
 }
 
+// Returns true if two push constants ranges must be merged into one, in other words if they
+// share a stage or if their bytes overlap.
+#[inline]
+fn ranges_mergeable(a: &PipelineLayoutDescPcRange, b: &PipelineLayoutDescPcRange) -> bool {
+    a.stages.intersects(&b.stages) ||
+    (a.offset < b.offset + b.size && b.offset < a.offset + a.size)
+}
+
 unsafe impl<A, B> PipelineLayoutDescNames for PipelineLayoutDescUnion<A, B>
     where A: PipelineLayoutDescNames, B: PipelineLayoutDescNames
 {
@@ -147,3 +201,149 @@ unsafe impl<A, B> PipelineLayoutDescNames for PipelineLayoutDescUnion<A, B>
         }
     }
 }
+
+/// Error when building the union of two pipeline layout descriptions whose descriptors conflict.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PipelineLayoutDescUnionError {
+    /// Set of the conflicting descriptor.
+    pub set: usize,
+    /// Binding of the conflicting descriptor within its set.
+    pub binding: usize,
+}
+
+impl error::Error for PipelineLayoutDescUnionError {
+    #[inline]
+    fn description(&self) -> &str {
+        "the two layouts have different descriptors at the same binding"
+    }
+}
+
+impl fmt::Display for PipelineLayoutDescUnionError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+    use descriptor::pipeline_layout::PipelineLayoutDescUnion;
+    use descriptor::pipeline_layout::PipelineLayoutDescUnionError;
+
+    // Layout with a single descriptor at set 0, binding 0.
+    struct Single(DescriptorDescTy, ShaderStages);
+
+    unsafe impl PipelineLayoutDesc for Single {
+        fn num_sets(&self) -> usize { 1 }
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            if set == 0 { Some(1) } else { None }
+        }
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            if set != 0 || binding != 0 {
+                return None;
+            }
+
+            Some(DescriptorDesc {
+                ty: self.0.clone(),
+                array_count: 1,
+                stages: self.1,
+                readonly: true,
+            })
+        }
+        fn num_push_constants_ranges(&self) -> usize { 0 }
+        fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> { None }
+    }
+
+    // Layout with a single push constants range, similar to the ones generated for shaders.
+    struct PushConstants(ShaderStages, usize, usize);
+
+    unsafe impl PipelineLayoutDesc for PushConstants {
+        fn num_sets(&self) -> usize { 0 }
+        fn num_bindings_in_set(&self, _: usize) -> Option<usize> { None }
+        fn descriptor(&self, _: usize, _: usize) -> Option<DescriptorDesc> { None }
+        fn num_push_constants_ranges(&self) -> usize { 1 }
+        fn push_constants_range(&self, num: usize) -> Option<PipelineLayoutDescPcRange> {
+            if num != 0 {
+                return None;
+            }
+
+            Some(PipelineLayoutDescPcRange {
+                offset: self.1,
+                size: self.2,
+                stages: self.0,
+            })
+        }
+    }
+
+    #[test]
+    fn push_constants_shared_by_vertex_and_fragment() {
+        // A vertex shader and a fragment shader that share the same push constants block.
+        let vs = PushConstants(ShaderStages { vertex: true, .. ShaderStages::none() }, 0, 16);
+        let fs = PushConstants(ShaderStages { fragment: true, .. ShaderStages::none() }, 0, 16);
+
+        let union = PipelineLayoutDescUnion::new(vs, fs);
+        assert_eq!(union.num_push_constants_ranges(), 1);
+
+        let range = union.push_constants_range(0).unwrap();
+        assert_eq!(range.offset, 0);
+        assert_eq!(range.size, 16);
+        assert!(range.stages.vertex && range.stages.fragment);
+    }
+
+    #[test]
+    fn push_constants_partially_overlapping() {
+        let vs = PushConstants(ShaderStages { vertex: true, .. ShaderStages::none() }, 0, 16);
+        let fs = PushConstants(ShaderStages { fragment: true, .. ShaderStages::none() }, 8, 16);
+
+        let union = PipelineLayoutDescUnion::new(vs, fs);
+        assert_eq!(union.num_push_constants_ranges(), 1);
+
+        let range = union.push_constants_range(0).unwrap();
+        assert_eq!(range.offset, 0);
+        assert_eq!(range.size, 24);
+        assert!(range.stages.vertex && range.stages.fragment);
+    }
+
+    #[test]
+    fn push_constants_disjoint() {
+        let vs = PushConstants(ShaderStages { vertex: true, .. ShaderStages::none() }, 0, 16);
+        let fs = PushConstants(ShaderStages { fragment: true, .. ShaderStages::none() }, 16, 16);
+
+        let union = PipelineLayoutDescUnion::new(vs, fs);
+        assert_eq!(union.num_push_constants_ranges(), 2);
+
+        let range = union.push_constants_range(0).unwrap();
+        assert_eq!((range.offset, range.size), (0, 16));
+        assert!(range.stages.vertex && !range.stages.fragment);
+        let range = union.push_constants_range(1).unwrap();
+        assert_eq!((range.offset, range.size), (16, 16));
+        assert!(!range.stages.vertex && range.stages.fragment);
+    }
+
+    #[test]
+    fn try_new_compatible() {
+        let a = Single(DescriptorDescTy::Sampler, ShaderStages { vertex: true, .. ShaderStages::none() });
+        let b = Single(DescriptorDescTy::Sampler, ShaderStages { fragment: true, .. ShaderStages::none() });
+
+        let union = PipelineLayoutDescUnion::try_new(a, b).unwrap();
+        let desc = union.descriptor(0, 0).unwrap();
+        assert!(desc.stages.vertex && desc.stages.fragment);
+    }
+
+    #[test]
+    fn try_new_conflict() {
+        let a = Single(DescriptorDescTy::Sampler, ShaderStages::all_graphics());
+        let b = Single(DescriptorDescTy::TexelBuffer { storage: false, format: None },
+                       ShaderStages::all_graphics());
+
+        match PipelineLayoutDescUnion::try_new(a, b) {
+            Err(PipelineLayoutDescUnionError { set: 0, binding: 0 }) => (),
+            _ => panic!()
+        }
+    }
+}
@@ -62,7 +62,16 @@
 //! Not all physical devices support all possible features and extensions. For example mobile
 //! devices tend to not support geometry shaders, because their hardware is not capable of it. You
 //! can query what is supported with respectively `PhysicalDevice::supported_features` and
-//! TODO: oops, there's no method for querying supported extensions in vulkan yet.
+//! `DeviceExtensions::supported_by_device`.
+//!
+//! Extensions that are not part of `DeviceExtensions`, for example extensions whose name is only
+//! known at runtime, can be enabled with `Device::with_extension_names`. The list of the names of
+//! all the extensions supported by a device is returned by
+//! `RawDeviceExtensions::supported_by_device`.
+//!
+//! If you would rather enable as many optional features as possible than fail, use
+//! `Device::with_features_subset`, which drops the unsupported features and tells you which ones
+//! were dropped.
 //!
 //! > **Note**: The fact that you need to manually enable features at initialization also means
 //! > that you don't need to worry about a capability not being supported later on in your code.
@@ -85,22 +94,44 @@
 //!
 //! The `Device::new` function returns the newly-created device, but also the list of queues.
 //!
+//! # Recording-time validation
+//!
+//! Some of the checks that vulkano performs when you record a command buffer are expensive, and
+//! you may want to skip them once your application is known to be correct. They can be turned
+//! on and off at any time with `Device::set_recording_validation`. They are always enabled by
+//! default.
+//!
+//! The checks that respect this flag are:
+//!
+//! - The vertex, index and instance ranges of `draw_range` and `draw_indexed_range`, which are
+//!   compared with the content of the buffers.
+//! - The compatibility of the descriptor sets with the pipeline layout when binding them.
+//! - The compatibility of the push constants with the pipeline layout.
+//! - The usage of the buffers, buffer views and images written in a `SimpleDescriptorSet`.
+//!
+//! All the other checks are always performed. Disabling the validation is unsafe, as an invalid
+//! command that is no longer caught leads to undefined behavior on the GPU.
+//!
 //! # Extended example
 //!
 //! TODO: write
 
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::ffi::CString;
 use std::fmt;
 use std::error;
 use std::hash::BuildHasherDefault;
 use std::mem;
 use std::ops::Deref;
+use std::os::raw::c_void;
 use std::ptr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
 use std::sync::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use smallvec::SmallVec;
 use fnv::FnvHasher;
 
@@ -111,6 +142,8 @@ use instance::Instance;
 use instance::PhysicalDevice;
 use instance::QueueFamily;
 use memory::pool::StdMemoryPool;
+use sync::FencePool;
+use sync::SemaphorePool;
 
 use Error;
 use OomError;
@@ -130,6 +163,12 @@ pub struct Device {
     standard_pool: Mutex<Weak<StdMemoryPool>>,
     standard_descriptor_pool: Mutex<Weak<StdDescriptorPool>>,
     standard_command_pools: Mutex<HashMap<u32, Weak<StandardCommandPool>, BuildHasherDefault<FnvHasher>>>,
+    semaphore_pool: SemaphorePool,
+    fence_pool: FencePool,
+    // True if a Vulkan function returned `VK_ERROR_DEVICE_LOST`.
+    lost: AtomicBool,
+    // True if the expensive checks are performed when recording command buffers.
+    recording_validation: AtomicBool,
     features: Features,
     extensions: DeviceExtensions,
 }
@@ -169,7 +208,8 @@ impl Device {
         let queue_families = queue_families.into_iter();
 
         if !phys.supported_features().superset_of(&requested_features) {
-            return Err(DeviceCreationError::UnsupportedFeatures);
+            let missing = requested_features.difference(phys.supported_features());
+            return Err(DeviceCreationError::UnsupportedFeatures(missing));
         }
 
         let vk_i = phys.instance().pointers();
@@ -195,6 +235,34 @@ impl Device {
             extension.as_ptr()
         }).collect::<SmallVec<[_; 16]>>();
 
+        // Features provided by extensions can only be enabled alongside their extension.
+        let loaded_extensions: DeviceExtensions = (&extensions).into();
+        if requested_features.sampler_ycbcr_conversion &&
+           !loaded_extensions.khr_sampler_ycbcr_conversion
+        {
+            return Err(DeviceCreationError::FeatureExtensionNotEnabled);
+        }
+        if requested_features.index_type_uint8 && !loaded_extensions.ext_index_type_uint8 {
+            return Err(DeviceCreationError::FeatureExtensionNotEnabled);
+        }
+        if requested_features.conditional_rendering &&
+           !loaded_extensions.ext_conditional_rendering
+        {
+            return Err(DeviceCreationError::FeatureExtensionNotEnabled);
+        }
+        if (requested_features.multiview || requested_features.multiview_geometry_shader ||
+            requested_features.multiview_tessellation_shader) && !loaded_extensions.khr_multiview
+        {
+            return Err(DeviceCreationError::FeatureExtensionNotEnabled);
+        }
+        // Memory that holds such buffers must be allocated with `VkMemoryAllocateFlagsInfoKHR`,
+        // which comes from `khr_device_group`.
+        if requested_features.buffer_device_address &&
+           (!loaded_extensions.khr_buffer_device_address || !loaded_extensions.khr_device_group)
+        {
+            return Err(DeviceCreationError::FeatureExtensionNotEnabled);
+        }
+
         // device creation
         let device = unsafe {
             // each element of `queues` is a `(queue_family, priorities)`
@@ -253,9 +321,77 @@ impl Device {
                 features
             };
 
+            let mut index_type_uint8 = vk::PhysicalDeviceIndexTypeUint8FeaturesEXT {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_INDEX_TYPE_UINT8_FEATURES_EXT,
+                pNext: ptr::null(),
+                indexTypeUint8: vk::TRUE,
+            };
+
+            let mut conditional_rendering = vk::PhysicalDeviceConditionalRenderingFeaturesEXT {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_CONDITIONAL_RENDERING_FEATURES_EXT,
+                pNext: ptr::null(),
+                conditionalRendering: vk::TRUE,
+                inheritedConditionalRendering: vk::FALSE,
+            };
+
+            let mut multiview = vk::PhysicalDeviceMultiviewFeaturesKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_FEATURES_KHR,
+                pNext: ptr::null(),
+                multiview: if requested_features.multiview { vk::TRUE } else { vk::FALSE },
+                multiviewGeometryShader: if requested_features.multiview_geometry_shader {
+                    vk::TRUE
+                } else {
+                    vk::FALSE
+                },
+                multiviewTessellationShader: if requested_features.multiview_tessellation_shader {
+                    vk::TRUE
+                } else {
+                    vk::FALSE
+                },
+            };
+
+            let mut buffer_device_address = vk::PhysicalDeviceBufferDeviceAddressFeaturesKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_BUFFER_DEVICE_ADDRESS_FEATURES_KHR,
+                pNext: ptr::null(),
+                bufferDeviceAddress: vk::TRUE,
+                bufferDeviceAddressCaptureReplay: vk::FALSE,
+                bufferDeviceAddressMultiDevice: vk::FALSE,
+            };
+
+            let mut ycbcr_conversion = vk::PhysicalDeviceSamplerYcbcrConversionFeaturesKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_SAMPLER_YCBCR_CONVERSION_FEATURES_KHR,
+                pNext: ptr::null(),
+                samplerYcbcrConversion: vk::TRUE,
+            };
+
+            // Chain the structs of the requested features that are provided by extensions.
+            let mut next: *const c_void = ptr::null();
+            if requested_features.index_type_uint8 {
+                index_type_uint8.pNext = next;
+                next = &index_type_uint8 as *const _ as *const _;
+            }
+            if requested_features.conditional_rendering {
+                conditional_rendering.pNext = next;
+                next = &conditional_rendering as *const _ as *const _;
+            }
+            if requested_features.multiview || requested_features.multiview_geometry_shader ||
+               requested_features.multiview_tessellation_shader
+            {
+                multiview.pNext = next;
+                next = &multiview as *const _ as *const _;
+            }
+            if requested_features.buffer_device_address {
+                buffer_device_address.pNext = next;
+                next = &buffer_device_address as *const _ as *const _;
+            }
+            if requested_features.sampler_ycbcr_conversion {
+                ycbcr_conversion.pNext = next;
+                next = &ycbcr_conversion as *const _ as *const _;
+            }
+
             let infos = vk::DeviceCreateInfo {
                 sType: vk::STRUCTURE_TYPE_DEVICE_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: next,
                 flags: 0,   // reserved
                 queueCreateInfoCount: queues.len() as u32,
                 pQueueCreateInfos: queues.as_ptr(),
@@ -285,8 +421,12 @@ impl Device {
             standard_pool: Mutex::new(Weak::new()),
             standard_descriptor_pool: Mutex::new(Weak::new()),
             standard_command_pools: Mutex::new(Default::default()),
+            semaphore_pool: SemaphorePool::new(),
+            fence_pool: FencePool::new(),
+            lost: AtomicBool::new(false),
+            recording_validation: AtomicBool::new(true),
             features: requested_features.clone(),
-            extensions: (&extensions).into(),
+            extensions: loaded_extensions,
         });
 
         // Iterator for the produced queues.
@@ -299,6 +439,96 @@ impl Device {
         Ok((device, output_queues))
     }
 
+    /// Same as `new`, except that the features that aren't supported by the physical device are
+    /// dropped instead of making the creation fail.
+    ///
+    /// The device is created with the intersection of `desired_features` and the features
+    /// supported by `phys`. Features that require a device extension which is not part of
+    /// `extensions` are dropped as well.
+    ///
+    /// In addition to the device and its queues, returns the list of features that were
+    /// requested but not enabled. The features that were actually enabled can be retrieved with
+    /// `enabled_features()`.
+    ///
+    /// # Panic
+    ///
+    /// Same as `new`.
+    ///
+    pub fn with_features_subset<'a, I, Ext>(phys: &'a PhysicalDevice, desired_features: &Features,
+                                            extensions: Ext, queue_families: I)
+                                            -> Result<(Arc<Device>, QueuesIter, Features),
+                                                      DeviceCreationError>
+        where I: IntoIterator<Item = (QueueFamily<'a>, f32)>,
+              Ext: Into<RawDeviceExtensions>,
+    {
+        let extensions = extensions.into();
+
+        let mut enabled = desired_features.intersection(phys.supported_features());
+        {
+            let loaded_extensions: DeviceExtensions = (&extensions).into();
+            if !loaded_extensions.khr_sampler_ycbcr_conversion {
+                enabled.sampler_ycbcr_conversion = false;
+            }
+            if !loaded_extensions.ext_index_type_uint8 {
+                enabled.index_type_uint8 = false;
+            }
+            if !loaded_extensions.ext_conditional_rendering {
+                enabled.conditional_rendering = false;
+            }
+            if !loaded_extensions.khr_multiview {
+                enabled.multiview = false;
+                enabled.multiview_geometry_shader = false;
+                enabled.multiview_tessellation_shader = false;
+            }
+            if !loaded_extensions.khr_buffer_device_address || !loaded_extensions.khr_device_group {
+                enabled.buffer_device_address = false;
+            }
+        }
+
+        let dropped = desired_features.difference(&enabled);
+        let (device, queues) = try!(Device::new(phys, &enabled, extensions, queue_families));
+        Ok((device, queues, dropped))
+    }
+
+    /// Same as `new`, except that additional extensions can be requested by name, for example
+    /// extensions that are discovered at runtime or that are not part of `DeviceExtensions`.
+    ///
+    /// The device is created with the union of `extensions` and of the names of
+    /// `extension_names` that are supported by `phys`. The names that are not supported by `phys`
+    /// are ignored instead of making the creation fail.
+    ///
+    /// In addition to the device and its queues, returns the names of `extension_names` that
+    /// were not enabled because they are not supported.
+    ///
+    /// # Panic
+    ///
+    /// Same as `new`.
+    ///
+    pub fn with_extension_names<'a, I, N>(phys: &'a PhysicalDevice, requested_features: &Features,
+                                          extensions: &DeviceExtensions, extension_names: N,
+                                          queue_families: I)
+                                          -> Result<(Arc<Device>, QueuesIter, Vec<CString>),
+                                                    DeviceCreationError>
+        where I: IntoIterator<Item = (QueueFamily<'a>, f32)>,
+              N: IntoIterator<Item = CString>,
+    {
+        let supported = RawDeviceExtensions::supported_by_device(phys);
+
+        let mut enabled = RawDeviceExtensions::from(extensions);
+        let mut unrecognized = Vec::new();
+        for name in extension_names {
+            if supported.iter().any(|s| *s == name) {
+                enabled.insert(name);
+            } else {
+                unrecognized.push(name);
+            }
+        }
+
+        let (device, queues) = try!(Device::new(phys, requested_features, enabled,
+                                                queue_families));
+        Ok((device, queues, unrecognized))
+    }
+
     /// Grants access to the pointers to the Vulkan functions of the device.
     #[inline]
     pub(crate) fn pointers(&self) -> &vk::DevicePointers {
@@ -316,11 +546,65 @@ impl Device {
     /// of the device (either explicitely or implicitely, for example with a future's destructor)
     /// while this function is waiting.
     ///
-    pub unsafe fn wait(&self) -> Result<(), OomError> {
-        try!(check_errors(self.vk.DeviceWaitIdle(self.device)));
+    /// Returns `DeviceWaitError::DeviceLost` if the device has been lost, either before or during
+    /// the wait.
+    pub unsafe fn wait(&self) -> Result<(), DeviceWaitError> {
+        if self.is_lost() {
+            return Err(DeviceWaitError::DeviceLost);
+        }
+
+        try!(self.track_lost(check_errors(self.vk.DeviceWaitIdle(self.device))));
         Ok(())
     }
 
+    /// Returns true if the connection to the device has been lost.
+    ///
+    /// The device is marked as lost as soon as an operation returns `VK_ERROR_DEVICE_LOST`.
+    /// Afterwards, submissions, presentations and waits on this device immediately fail
+    /// with a `DeviceLost` error instead of calling the Vulkan implementation. The only way to
+    /// recover is to destroy all the objects of the device and create a new one.
+    #[inline]
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Marks the device as lost if `result` is a `DeviceLost` error, then returns `result`.
+    #[inline]
+    pub(crate) fn track_lost<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(Error::DeviceLost) = result {
+            self.lost.store(true, Ordering::Release);
+        }
+
+        result
+    }
+
+    /// Returns true if the expensive checks are performed when recording command buffers that
+    /// use this device.
+    ///
+    /// See the documentation of the `device` module for the list of checks that depend on this
+    /// flag.
+    #[inline]
+    pub fn recording_validation(&self) -> bool {
+        self.recording_validation.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables the expensive checks when recording command buffers that use this
+    /// device. The default is `true`.
+    ///
+    /// The change only affects the commands that are built afterwards.
+    ///
+    /// # Safety
+    ///
+    /// While the validation is disabled, you must ensure that the commands that you record are
+    /// valid with regard to the checks listed in the documentation of the `device` module. For
+    /// example the ranges passed to `draw_range` must be within the buffers, and the descriptor
+    /// sets and push constants must be compatible with the pipeline layout. Otherwise the GPU
+    /// may access memory out of bounds.
+    #[inline]
+    pub unsafe fn set_recording_validation(&self, enabled: bool) {
+        self.recording_validation.store(enabled, Ordering::Relaxed);
+    }
+
     /// Returns the instance used to create this device.
     #[inline]
     pub fn instance(&self) -> &Arc<Instance> {
@@ -373,6 +657,18 @@ impl Device {
         new_pool
     }
 
+    /// Returns the pool of semaphores that are reused by `Semaphore::from_pool`.
+    #[inline]
+    pub fn semaphore_pool(&self) -> &SemaphorePool {
+        &self.semaphore_pool
+    }
+
+    /// Returns the pool of fences that are reused by `Fence::from_pool`.
+    #[inline]
+    pub fn fence_pool(&self) -> &FencePool {
+        &self.fence_pool
+    }
+
     /// Returns the standard command buffer pool used by default if you don't provide any other
     /// pool.
     ///
@@ -423,6 +719,8 @@ impl Drop for Device {
     fn drop(&mut self) {
         unsafe {
             self.vk.DeviceWaitIdle(self.device);
+            self.semaphore_pool.destroy_all(&self.vk, self.device);
+            self.fence_pool.destroy_all(&self.vk, self.device);
             self.vk.DestroyDevice(self.device, ptr::null());
         }
     }
@@ -470,6 +768,7 @@ impl Iterator for QueuesIter {
 
             Some(Arc::new(Queue {
                 queue: Mutex::new(output),
+                submissions: Mutex::new(Submissions { last_submitted: 0, last_completed: 0 }),
                 device: self.device.clone(),
                 family: family,
                 id: id,
@@ -487,7 +786,7 @@ impl Iterator for QueuesIter {
 impl ExactSizeIterator for QueuesIter {}
 
 /// Error that can be returned when creating a device.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DeviceCreationError {
     /// There is no memory available on the host (ie. the CPU, RAM, etc.).
     OutOfHostMemory,
@@ -495,12 +794,15 @@ pub enum DeviceCreationError {
     OutOfDeviceMemory,
     /// Tried to create too many queues for a given family.
     TooManyQueuesForFamily,
-    /// Some of the requested features are unsupported by the physical device.
-    UnsupportedFeatures,
+    /// Some of the requested features are unsupported by the physical device. Contains the
+    /// features that are missing.
+    UnsupportedFeatures(Features),
     /// The priority of one of the queues is out of the [0.0; 1.0] range.
     PriorityOutOfRange,
     /// Some of the requested device extensions are not supported by the physical device.
     ExtensionNotPresent,
+    /// Some of the requested features require a device extension that wasn't requested.
+    FeatureExtensionNotEnabled,
 }
 
 impl error::Error for DeviceCreationError {
@@ -514,7 +816,7 @@ impl error::Error for DeviceCreationError {
             DeviceCreationError::TooManyQueuesForFamily => {
                 "tried to create too many queues for a given family"
             },
-            DeviceCreationError::UnsupportedFeatures => {
+            DeviceCreationError::UnsupportedFeatures(_) => {
                 "some of the requested features are unsupported by the physical device"
             },
             DeviceCreationError::PriorityOutOfRange => {
@@ -522,7 +824,10 @@ impl error::Error for DeviceCreationError {
             },
             DeviceCreationError::ExtensionNotPresent => {
                 "some of the requested device extensions are not supported by the physical device"
-            }
+            },
+            DeviceCreationError::FeatureExtensionNotEnabled => {
+                "some of the requested features require a device extension that wasn't requested"
+            },
         }
     }
 }
@@ -530,7 +835,12 @@ impl error::Error for DeviceCreationError {
 impl fmt::Display for DeviceCreationError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(fmt, "{}", error::Error::description(self))
+        match *self {
+            DeviceCreationError::UnsupportedFeatures(ref missing) => {
+                write!(fmt, "{}: {}", error::Error::description(self), missing)
+            },
+            _ => write!(fmt, "{}", error::Error::description(self)),
+        }
     }
 }
 
@@ -546,16 +856,89 @@ impl From<Error> for DeviceCreationError {
     }
 }
 
+/// Error that can be returned when waiting for a device or a queue to finish its work.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeviceWaitError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// The connection to the device has been lost.
+    DeviceLost,
+}
+
+impl error::Error for DeviceWaitError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DeviceWaitError::OomError(_) => "no memory available",
+            DeviceWaitError::DeviceLost => "the connection to the device has been lost",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            DeviceWaitError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for DeviceWaitError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for DeviceWaitError {
+    #[inline]
+    fn from(err: OomError) -> DeviceWaitError {
+        DeviceWaitError::OomError(err)
+    }
+}
+
+impl From<Error> for DeviceWaitError {
+    #[inline]
+    fn from(err: Error) -> DeviceWaitError {
+        match err {
+            err @ Error::OutOfHostMemory => DeviceWaitError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => DeviceWaitError::OomError(OomError::from(err)),
+            Error::DeviceLost => DeviceWaitError::DeviceLost,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
 /// Represents a queue where commands can be submitted.
+///
+/// # Submission IDs
+///
+/// Each successful submission of command buffers to a queue is assigned an ID. The IDs start at
+/// 1 and increase by one with each submission, in the same order as the submissions are seen by
+/// the Vulkan implementation. The IDs of two different queues are unrelated.
+///
+/// The queue keeps track of the last submitted ID, and of the last ID that is known to have
+/// finished executing. Since a queue executes its submissions in order, all the submissions
+/// whose ID is lower than or equal to `last_completed_id()` have finished as well.
 // TODO: should use internal synchronization?
 #[derive(Debug)]
 pub struct Queue {
     queue: Mutex<vk::Queue>,
+    // Only locked while `queue` is locked as well, except for reading.
+    submissions: Mutex<Submissions>,
     device: Arc<Device>,
     family: u32,
     id: u32,    // id within family
 }
 
+#[derive(Debug)]
+struct Submissions {
+    // ID of the last submission, or 0 if nothing was submitted yet.
+    last_submitted: u64,
+    // ID of the last submission known to be finished, or 0 if none.
+    last_completed: u64,
+}
+
 impl Queue {
     /// Returns the device this queue belongs to.
     #[inline]
@@ -583,16 +966,76 @@ impl Queue {
         self.id
     }
 
+    /// Returns the ID of the last submission made to this queue, or 0 if nothing was submitted
+    /// yet.
+    ///
+    /// See the documentation of `Queue` for more information about submission IDs.
+    #[inline]
+    pub fn last_submitted_id(&self) -> u64 {
+        self.submissions.lock().unwrap().last_submitted
+    }
+
+    /// Returns the ID of the last submission that is known to have finished executing, or 0 if
+    /// none.
+    ///
+    /// This value is only updated when vulkano finds out that a submission is finished, for
+    /// example when waiting for a fence or for the queue to be idle. The GPU may therefore be
+    /// further ahead than what this function returns.
+    #[inline]
+    pub fn last_completed_id(&self) -> u64 {
+        self.submissions.lock().unwrap().last_completed
+    }
+
+    /// Assigns an ID to a submission that has just been made to the queue. The guard returned by
+    /// `internal_object_guard()` must have been held during the submission and must still be
+    /// held.
+    #[inline]
+    pub(crate) fn register_submission(&self) -> u64 {
+        let mut submissions = self.submissions.lock().unwrap();
+        submissions.last_submitted += 1;
+        submissions.last_submitted
+    }
+
+    /// Marks the submission `id` and all the submissions before it as finished.
+    #[inline]
+    pub(crate) fn mark_completed(&self, id: u64) {
+        let mut submissions = self.submissions.lock().unwrap();
+        debug_assert!(id <= submissions.last_submitted);
+        if id > submissions.last_completed {
+            submissions.last_completed = id;
+        }
+    }
+
     /// Waits until all work on this queue has finished.
     ///
     /// Just like `Device::wait()`, you shouldn't have to call this function in a typical program.
+    ///
+    /// Returns `DeviceWaitError::DeviceLost` if the device has been lost, either before or during
+    /// the wait. Since nothing executes anymore on a lost device, all the submissions are then
+    /// considered completed.
     #[inline]
-    pub fn wait(&self) -> Result<(), OomError> {
+    pub fn wait(&self) -> Result<(), DeviceWaitError> {
         unsafe {
             let vk = self.device.pointers();
             let queue = self.queue.lock().unwrap();
-            try!(check_errors(vk.QueueWaitIdle(*queue)));
-            Ok(())
+
+            let result = if self.device.is_lost() {
+                Err(Error::DeviceLost)
+            } else {
+                self.device.track_lost(check_errors(vk.QueueWaitIdle(*queue)))
+            };
+
+            match result {
+                Ok(_) | Err(Error::DeviceLost) => {
+                    // Nothing can be submitted while we hold the lock, so everything that was
+                    // submitted so far is finished.
+                    let mut submissions = self.submissions.lock().unwrap();
+                    submissions.last_completed = submissions.last_submitted;
+                    try!(result);
+                    Ok(())
+                },
+                Err(err) => Err(err.into()),
+            }
         }
     }
 }
@@ -608,12 +1051,18 @@ unsafe impl SynchronizedVulkanObject for Queue {
 
 #[cfg(test)]
 mod tests {
+    use std::ffi::CString;
     use std::sync::Arc;
+    use std::thread;
+    use command_buffer::submit::SubmitCommandBufferBuilder;
+    use command_buffer::submit::SubmitCommandBufferError;
     use device::Device;
     use device::DeviceCreationError;
     use device::DeviceExtensions;
+    use device::DeviceWaitError;
     use features::Features;
     use instance;
+    use Error;
 
     #[test]
     fn one_ref() {
@@ -621,6 +1070,64 @@ mod tests {
         assert!(Arc::get_mut(&mut device).is_some());
     }
 
+    #[test]
+    fn recording_validation_toggle() {
+        let (device, _) = gfx_dev_and_queue!();
+        assert!(device.recording_validation());
+
+        unsafe {
+            device.set_recording_validation(false);
+            assert!(!device.recording_validation());
+            device.set_recording_validation(true);
+            assert!(device.recording_validation());
+        }
+    }
+
+    #[test]
+    fn lost_fails_fast() {
+        let (device, queue) = gfx_dev_and_queue!();
+        assert!(!device.is_lost());
+
+        let _ = device.track_lost::<()>(Err(Error::DeviceLost));
+        assert!(device.is_lost());
+
+        match SubmitCommandBufferBuilder::new().submit(&queue) {
+            Err(SubmitCommandBufferError::DeviceLost) => (),
+            _ => panic!()
+        }
+
+        assert_eq!(queue.wait(), Err(DeviceWaitError::DeviceLost));
+        assert_eq!(unsafe { device.wait() }, Err(DeviceWaitError::DeviceLost));
+    }
+
+    #[test]
+    fn submission_ids_from_two_threads() {
+        let (_, queue) = gfx_dev_and_queue!();
+        assert_eq!(queue.last_submitted_id(), 0);
+
+        let threads = (0 .. 2).map(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                (0 .. 32).map(|_| SubmitCommandBufferBuilder::new().submit(&queue).unwrap())
+                         .collect::<Vec<_>>()
+            })
+        }).collect::<Vec<_>>();
+
+        let mut all_ids = Vec::new();
+        for thread in threads {
+            let ids = thread.join().unwrap();
+            assert!(ids.windows(2).all(|w| w[0] < w[1]));
+            all_ids.extend(ids);
+        }
+
+        all_ids.sort();
+        assert_eq!(all_ids, (1 .. 65).collect::<Vec<u64>>());
+        assert_eq!(queue.last_submitted_id(), 64);
+
+        queue.wait().unwrap();
+        assert_eq!(queue.last_completed_id(), 64);
+    }
+
     #[test]
     fn too_many_queues() {
         let instance = instance!();
@@ -655,7 +1162,84 @@ mod tests {
         }
 
         match Device::new(&physical, &features, &DeviceExtensions::none(), Some((family, 1.0))) {
-            Err(DeviceCreationError::UnsupportedFeatures) => return,     // Success
+            Err(DeviceCreationError::UnsupportedFeatures(missing)) => {
+                // Success
+                assert_eq!(missing, features.difference(physical.supported_features()));
+            },
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn features_subset() {
+        let instance = instance!();
+        let physical = match instance::PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return
+        };
+
+        let family = physical.queue_families().next().unwrap();
+
+        let (device, _, dropped) = match Device::with_features_subset(&physical, &Features::all(),
+                                                                      &DeviceExtensions::none(),
+                                                                      Some((family, 1.0)))
+        {
+            Ok(r) => r,
+            Err(_) => return
+        };
+
+        assert!(physical.supported_features().superset_of(device.enabled_features()));
+        assert!(!device.enabled_features().sampler_ycbcr_conversion);
+        assert_eq!(device.enabled_features().intersection(&dropped), Features::none());
+    }
+
+    #[test]
+    fn unrecognized_extension_names() {
+        let instance = instance!();
+        let physical = match instance::PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return
+        };
+
+        let family = physical.queue_families().next().unwrap();
+        let names = vec![CString::new("VK_VULKANO_unknown_extension").unwrap()];
+
+        let (device, _, unrecognized) = match Device::with_extension_names(&physical,
+                                                                           &Features::none(),
+                                                                           &DeviceExtensions::none(),
+                                                                           names.clone(),
+                                                                           Some((family, 1.0)))
+        {
+            Ok(r) => r,
+            Err(_) => return
+        };
+
+        assert_eq!(unrecognized, names);
+        assert_eq!(*device.loaded_extensions(), DeviceExtensions::none());
+    }
+
+    #[test]
+    fn buffer_device_address_requires_device_group() {
+        let instance = instance!();
+        let physical = match instance::PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return
+        };
+
+        let family = physical.queue_families().next().unwrap();
+
+        let features = Features { buffer_device_address: true, .. Features::none() };
+        if !physical.supported_features().superset_of(&features) {
+            return;
+        }
+
+        let extensions = DeviceExtensions {
+            khr_buffer_device_address: true,
+            .. DeviceExtensions::none()
+        };
+
+        match Device::new(&physical, &features, &extensions, Some((family, 1.0))) {
+            Err(DeviceCreationError::FeatureExtensionNotEnabled) => (),     // Success
             _ => panic!()
         };
     }
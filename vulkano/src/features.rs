@@ -7,16 +7,24 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::fmt;
+
 use vk;
 
 macro_rules! features {
-    ($($name:ident => $vk:ident,)+) => (
+    ($($name:ident => $vk:ident,)+ ; $($ext_name:ident,)*) => (
         /// Represents all the features that are available on a physical device or enabled on
         /// a logical device.
         ///
         /// Note that the `robust_buffer_access` is guaranteed to be supported by all Vulkan
         /// implementations.
         ///
+        /// Some features are provided by device extensions instead of the core specifications,
+        /// such as `sampler_ycbcr_conversion`. They are only reported as supported if the
+        /// physical device supports the corresponding extension and the instance was created
+        /// with the `khr_get_physical_device_properties2` extension. Enabling them also requires
+        /// enabling the extension on the device.
+        ///
         /// # Example
         ///
         /// ```
@@ -40,12 +48,16 @@ macro_rules! features {
         /// let features_to_request = optimal_features.intersection(physical_device.supported_features());
         /// ```
         ///
-        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        /// The `Debug` and `Display` implementations only list the features that are true.
+        #[derive(Clone, PartialEq, Eq, Hash)]
         #[allow(missing_docs)]
         pub struct Features {
             $(
                 pub $name: bool,
             )+
+            $(
+                pub $ext_name: bool,
+            )*
         }
 
         impl Features {
@@ -55,6 +67,9 @@ macro_rules! features {
                     $(
                         $name: false,
                     )+
+                    $(
+                        $ext_name: false,
+                    )*
                 }
             }
 
@@ -67,6 +82,9 @@ macro_rules! features {
                     $(
                         $name: true,
                     )+
+                    $(
+                        $ext_name: true,
+                    )*
                 }
             }
 
@@ -76,6 +94,7 @@ macro_rules! features {
             /// in self is true as well.
             pub fn superset_of(&self, other: &Features) -> bool {
                 $((self.$name == true || other.$name == false))&&+
+                $(&& (self.$ext_name == true || other.$ext_name == false))*
             }
 
             /// Builds a `Features` that is the intersection of `self` and another `Features`
@@ -87,6 +106,9 @@ macro_rules! features {
                     $(
                         $name: self.$name && other.$name,
                     )+
+                    $(
+                        $ext_name: self.$ext_name && other.$ext_name,
+                    )*
                 }
             }
 
@@ -98,8 +120,37 @@ macro_rules! features {
                     $(
                         $name: self.$name && !other.$name,
                     )+
+                    $(
+                        $ext_name: self.$ext_name && !other.$ext_name,
+                    )*
                 }
             }
+
+            /// Returns the names of the features that are true, in the order of the fields.
+            ///
+            /// Combined with `difference`, this can be used to tell which features are missing.
+            pub fn enabled_names(&self) -> Vec<&'static str> {
+                let mut names = Vec::new();
+                $(
+                    if self.$name { names.push(stringify!($name)); }
+                )+
+                $(
+                    if self.$ext_name { names.push(stringify!($ext_name)); }
+                )*
+                names
+            }
+        }
+
+        impl fmt::Debug for Features {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                write!(fmt, "Features {{{}}}", self.enabled_names().join(", "))
+            }
+        }
+
+        impl fmt::Display for Features {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                write!(fmt, "{}", self.enabled_names().join(", "))
+            }
         }
 
         #[doc(hidden)]
@@ -109,6 +160,9 @@ macro_rules! features {
                     $(
                         $name: features.$vk != 0,
                     )+
+                    $(
+                        $ext_name: false,
+                    )*
                 }
             }
         }
@@ -182,4 +236,49 @@ features!{
     sparse_residency_aliased => sparseResidencyAliased,
     variable_multisample_rate => variableMultisampleRate,
     inherited_queries => inheritedQueries,
+    ;
+    // Features provided by extensions. They are queried and enabled by chaining the structs of
+    // the corresponding extensions.
+    sampler_ycbcr_conversion,
+    index_type_uint8,
+    conditional_rendering,
+    multiview,
+    multiview_geometry_shader,
+    multiview_tessellation_shader,
+    buffer_device_address,
+}
+
+#[cfg(test)]
+mod tests {
+    use instance::Features;
+
+    #[test]
+    fn difference() {
+        let requested = Features {
+            geometry_shader: true,
+            wide_lines: true,
+            multiview: true,
+            .. Features::none()
+        };
+        let supported = Features { wide_lines: true, .. Features::none() };
+
+        let missing = requested.difference(&supported);
+        assert_eq!(missing.enabled_names(), vec!["geometry_shader", "multiview"]);
+        assert_eq!(supported.difference(&requested), Features::none());
+        assert_eq!(requested.difference(&Features::all()), Features::none());
+    }
+
+    #[test]
+    fn display_enabled_only() {
+        let features = Features {
+            robust_buffer_access: true,
+            sampler_ycbcr_conversion: true,
+            .. Features::none()
+        };
+
+        assert_eq!(format!("{}", features), "robust_buffer_access, sampler_ycbcr_conversion");
+        assert_eq!(format!("{:?}", features),
+                   "Features {robust_buffer_access, sampler_ycbcr_conversion}");
+        assert_eq!(format!("{}", Features::none()), "");
+    }
 }
@@ -48,6 +48,14 @@
 //!   it is read. The fourth channel (usually used for alpha), if present, is not concerned by the
 //!   conversion.
 //!
+//! # YCbCr formats
+//!
+//! Formats whose name contains `Plane` are multi-planar formats, where each component (or pair
+//! of components) is stored in a separate plane of memory. They, and the `422` formats, are
+//! usually produced by video decoders. Creating an image with them requires the
+//! `VK_KHR_sampler_ycbcr_conversion` extension and the `sampler_ycbcr_conversion` feature, and
+//! sampling them requires a `SamplerYcbcrConversion` (see the `sampler` module).
+//!
 //! # Choosing a format
 //! 
 //! The following formats are guaranteed to be supported for everything that is related to
@@ -101,7 +109,16 @@
 //!
 //! // TODO: storage formats
 //!
+//! For any other format, the features that a physical device supports can be queried with
+//! `Format::properties`. For example `Format::is_linear_filterable` tells whether images of a
+//! format can be sampled with a linear filtering.
+//!
+use std::mem;
 use std::vec::IntoIter as VecIntoIter;
+
+use image::ImageTiling;
+use instance::PhysicalDevice;
+use VulkanObject;
 use vk;
 
 // TODO: add enumerations for color, depth, stencil and depthstencil formats
@@ -176,6 +193,17 @@ macro_rules! formats {
                     )+
                 }
             }
+
+            /// Returns the number of memory planes of this format. Returns 1 for all formats
+            /// except the multi-planar YCbCr formats.
+            #[inline]
+            pub fn planes(&self) -> u32 {
+                match *self {
+                    $(
+                        Format::$name => formats!(__inner_planes__ $name $($f_ty)*),
+                    )+
+                }
+            }
         }
 
         $(
@@ -343,6 +371,22 @@ macro_rules! formats {
         }
     };
 
+    (__inner_impl__ $name:ident ycbcr=$planes:expr) => {
+        unsafe impl FormatDesc for $name {
+            type ClearValue = [f32; 4];
+
+            #[inline]
+            fn format(&self) -> Format {
+                Format::$name
+            }
+
+            #[inline]
+            fn decode_clear_value(&self, val: Self::ClearValue) -> ClearValue {
+                val.into()
+            }
+        }
+    };
+
     (__inner_ty__ $name:ident float=$num:tt) => { FormatTy::Float };
     (__inner_ty__ $name:ident uint=$num:tt) => { FormatTy::Uint };
     (__inner_ty__ $name:ident sint=$num:tt) => { FormatTy::Sint };
@@ -350,6 +394,10 @@ macro_rules! formats {
     (__inner_ty__ $name:ident stencil) => { FormatTy::Stencil };
     (__inner_ty__ $name:ident depthstencil) => { FormatTy::DepthStencil };
     (__inner_ty__ $name:ident compressed=$f:tt) => { FormatTy::Compressed };
+    (__inner_ty__ $name:ident ycbcr=$planes:tt) => { FormatTy::Ycbcr };
+
+    (__inner_planes__ $name:ident ycbcr=$planes:tt) => { $planes };
+    (__inner_planes__ $name:ident $($f_ty:tt)*) => { 1 };
 
 
     (__inner_strongstorage__ $name:ident $ty:ty) => {
@@ -545,6 +593,30 @@ formats! {
     ASTC_12x10SrgbBlock => FORMAT_ASTC_12x10_SRGB_BLOCK [None] [compressed=texture_compression_astc_ldr] {},
     ASTC_12x12UnormBlock => FORMAT_ASTC_12x12_UNORM_BLOCK [None] [compressed=texture_compression_astc_ldr] {},
     ASTC_12x12SrgbBlock => FORMAT_ASTC_12x12_SRGB_BLOCK [None] [compressed=texture_compression_astc_ldr] {},
+    G8B8G8R8_422Unorm => FORMAT_G8B8G8R8_422_UNORM_KHR [None] [ycbcr=1] {},
+    B8G8R8G8_422Unorm => FORMAT_B8G8R8G8_422_UNORM_KHR [None] [ycbcr=1] {},
+    G8_B8_R8_3Plane420Unorm => FORMAT_G8_B8_R8_3PLANE_420_UNORM_KHR [None] [ycbcr=3] {},
+    G8_B8R8_2Plane420Unorm => FORMAT_G8_B8R8_2PLANE_420_UNORM_KHR [None] [ycbcr=2] {},
+    G8_B8_R8_3Plane422Unorm => FORMAT_G8_B8_R8_3PLANE_422_UNORM_KHR [None] [ycbcr=3] {},
+    G8_B8R8_2Plane422Unorm => FORMAT_G8_B8R8_2PLANE_422_UNORM_KHR [None] [ycbcr=2] {},
+    G8_B8_R8_3Plane444Unorm => FORMAT_G8_B8_R8_3PLANE_444_UNORM_KHR [None] [ycbcr=3] {},
+    G10X6_B10X6_R10X6_3Plane420Unorm3Pack16 => FORMAT_G10X6_B10X6_R10X6_3PLANE_420_UNORM_3PACK16_KHR [None] [ycbcr=3] {},
+    G10X6_B10X6R10X6_2Plane420Unorm3Pack16 => FORMAT_G10X6_B10X6R10X6_2PLANE_420_UNORM_3PACK16_KHR [None] [ycbcr=2] {},
+    G10X6_B10X6_R10X6_3Plane422Unorm3Pack16 => FORMAT_G10X6_B10X6_R10X6_3PLANE_422_UNORM_3PACK16_KHR [None] [ycbcr=3] {},
+    G10X6_B10X6R10X6_2Plane422Unorm3Pack16 => FORMAT_G10X6_B10X6R10X6_2PLANE_422_UNORM_3PACK16_KHR [None] [ycbcr=2] {},
+    G10X6_B10X6_R10X6_3Plane444Unorm3Pack16 => FORMAT_G10X6_B10X6_R10X6_3PLANE_444_UNORM_3PACK16_KHR [None] [ycbcr=3] {},
+    G12X4_B12X4_R12X4_3Plane420Unorm3Pack16 => FORMAT_G12X4_B12X4_R12X4_3PLANE_420_UNORM_3PACK16_KHR [None] [ycbcr=3] {},
+    G12X4_B12X4R12X4_2Plane420Unorm3Pack16 => FORMAT_G12X4_B12X4R12X4_2PLANE_420_UNORM_3PACK16_KHR [None] [ycbcr=2] {},
+    G12X4_B12X4_R12X4_3Plane422Unorm3Pack16 => FORMAT_G12X4_B12X4_R12X4_3PLANE_422_UNORM_3PACK16_KHR [None] [ycbcr=3] {},
+    G12X4_B12X4R12X4_2Plane422Unorm3Pack16 => FORMAT_G12X4_B12X4R12X4_2PLANE_422_UNORM_3PACK16_KHR [None] [ycbcr=2] {},
+    G12X4_B12X4_R12X4_3Plane444Unorm3Pack16 => FORMAT_G12X4_B12X4_R12X4_3PLANE_444_UNORM_3PACK16_KHR [None] [ycbcr=3] {},
+    G16B16G16R16_422Unorm => FORMAT_G16B16G16R16_422_UNORM_KHR [None] [ycbcr=1] {},
+    B16G16R16G16_422Unorm => FORMAT_B16G16R16G16_422_UNORM_KHR [None] [ycbcr=1] {},
+    G16_B16_R16_3Plane420Unorm => FORMAT_G16_B16_R16_3PLANE_420_UNORM_KHR [None] [ycbcr=3] {},
+    G16_B16R16_2Plane420Unorm => FORMAT_G16_B16R16_2PLANE_420_UNORM_KHR [None] [ycbcr=2] {},
+    G16_B16_R16_3Plane422Unorm => FORMAT_G16_B16_R16_3PLANE_422_UNORM_KHR [None] [ycbcr=3] {},
+    G16_B16R16_2Plane422Unorm => FORMAT_G16_B16R16_2PLANE_422_UNORM_KHR [None] [ycbcr=2] {},
+    G16_B16_R16_3Plane444Unorm => FORMAT_G16_B16_R16_3PLANE_444_UNORM_KHR [None] [ycbcr=3] {},
 }
 
 pub unsafe trait FormatDesc {
@@ -668,6 +740,108 @@ pub enum FormatTy {
     Stencil,
     DepthStencil,
     Compressed,
+    Ycbcr,
+}
+
+impl Format {
+    /// Queries the features that the physical device supports for this format.
+    pub fn properties(&self, physical_device: PhysicalDevice) -> FormatProperties {
+        let vk_i = physical_device.instance().pointers();
+
+        let props = unsafe {
+            let mut output = mem::uninitialized();
+            vk_i.GetPhysicalDeviceFormatProperties(physical_device.internal_object(),
+                                                   *self as u32, &mut output);
+            output
+        };
+
+        FormatProperties {
+            linear_tiling_features: props.linearTilingFeatures.into(),
+            optimal_tiling_features: props.optimalTilingFeatures.into(),
+            buffer_features: props.bufferFeatures.into(),
+        }
+    }
+
+    /// Returns true if images of this format and with the given tiling can be sampled with a
+    /// linear filtering on the physical device.
+    ///
+    /// This is guaranteed for the most common color formats, but often isn't the case for
+    /// integer, 32-bits floating-point and depth formats.
+    #[inline]
+    pub fn is_linear_filterable(&self, physical_device: PhysicalDevice, tiling: ImageTiling)
+                                -> bool
+    {
+        self.properties(physical_device).tiling_features(tiling).sampled_image_filter_linear
+    }
+}
+
+/// Features supported by a physical device for a format. Returned by `Format::properties`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FormatProperties {
+    /// Features supported by images with a linear tiling.
+    pub linear_tiling_features: FormatFeatures,
+    /// Features supported by images with an optimal tiling.
+    pub optimal_tiling_features: FormatFeatures,
+    /// Features supported by buffers.
+    pub buffer_features: FormatFeatures,
+}
+
+impl FormatProperties {
+    /// Returns the features supported by images with the given tiling.
+    #[inline]
+    pub fn tiling_features(&self, tiling: ImageTiling) -> FormatFeatures {
+        match tiling {
+            ImageTiling::Linear => self.linear_tiling_features,
+            ImageTiling::Optimal => self.optimal_tiling_features,
+        }
+    }
+}
+
+/// List of the features that can be supported for a format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[allow(missing_docs)]
+pub struct FormatFeatures {
+    pub sampled_image: bool,
+    pub storage_image: bool,
+    pub storage_image_atomic: bool,
+    pub uniform_texel_buffer: bool,
+    pub storage_texel_buffer: bool,
+    pub storage_texel_buffer_atomic: bool,
+    pub vertex_buffer: bool,
+    pub color_attachment: bool,
+    pub color_attachment_blend: bool,
+    pub depth_stencil_attachment: bool,
+    pub blit_src: bool,
+    pub blit_dst: bool,
+    pub sampled_image_filter_linear: bool,
+    pub transfer_src: bool,
+    pub transfer_dst: bool,
+}
+
+impl From<vk::FormatFeatureFlags> for FormatFeatures {
+    #[inline]
+    fn from(val: vk::FormatFeatureFlags) -> FormatFeatures {
+        FormatFeatures {
+            sampled_image: (val & vk::FORMAT_FEATURE_SAMPLED_IMAGE_BIT) != 0,
+            storage_image: (val & vk::FORMAT_FEATURE_STORAGE_IMAGE_BIT) != 0,
+            storage_image_atomic: (val & vk::FORMAT_FEATURE_STORAGE_IMAGE_ATOMIC_BIT) != 0,
+            uniform_texel_buffer: (val & vk::FORMAT_FEATURE_UNIFORM_TEXEL_BUFFER_BIT) != 0,
+            storage_texel_buffer: (val & vk::FORMAT_FEATURE_STORAGE_TEXEL_BUFFER_BIT) != 0,
+            storage_texel_buffer_atomic:
+                (val & vk::FORMAT_FEATURE_STORAGE_TEXEL_BUFFER_ATOMIC_BIT) != 0,
+            vertex_buffer: (val & vk::FORMAT_FEATURE_VERTEX_BUFFER_BIT) != 0,
+            color_attachment: (val & vk::FORMAT_FEATURE_COLOR_ATTACHMENT_BIT) != 0,
+            color_attachment_blend: (val & vk::FORMAT_FEATURE_COLOR_ATTACHMENT_BLEND_BIT) != 0,
+            depth_stencil_attachment:
+                (val & vk::FORMAT_FEATURE_DEPTH_STENCIL_ATTACHMENT_BIT) != 0,
+            blit_src: (val & vk::FORMAT_FEATURE_BLIT_SRC_BIT) != 0,
+            blit_dst: (val & vk::FORMAT_FEATURE_BLIT_DST_BIT) != 0,
+            sampled_image_filter_linear:
+                (val & vk::FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR_BIT) != 0,
+            transfer_src: (val & vk::FORMAT_FEATURE_TRANSFER_SRC_BIT_KHR) != 0,
+            transfer_dst: (val & vk::FORMAT_FEATURE_TRANSFER_DST_BIT_KHR) != 0,
+        }
+    }
 }
 
 /// Describes a uniform value that will be used to fill an image.
@@ -85,11 +85,37 @@ pub unsafe trait RenderPassDesc: RenderPassDescClearValues<Vec<ClearValue>> {
         RenderPassDescDependencies { render_pass: self, num: 0 }
     }
 
+    /// Returns the view mask of a subpass, for multiview rendering.
+    ///
+    /// Each bit that is set in the mask corresponds to a view (ie. a layer of the attachments)
+    /// that the subpass renders to. The draw commands of the subpass are broadcast to every
+    /// view, and shaders can read the index of the current view through the `ViewIndex`
+    /// built-in (`gl_ViewIndex` in GLSL).
+    ///
+    /// Multiview rendering requires the `khr_multiview` extension and the `multiview` feature.
+    /// Either all the subpasses of a render pass have a non-zero view mask, or none of them. The
+    /// default implementation returns 0, which disables multiview rendering.
+    #[inline]
+    fn view_mask(&self, subpass: usize) -> u32 {
+        0
+    }
+
+    /// Returns the correlation masks of the render pass.
+    ///
+    /// Each mask is a set of views that are likely to be spatially correlated, which the
+    /// implementation may use as an optimization hint. Ignored if multiview rendering is not
+    /// used. The default implementation returns an empty list.
+    #[inline]
+    fn correlation_masks(&self) -> Vec<u32> {
+        Vec::new()
+    }
+
     /// Returns true if this render pass is compatible with another render pass.
     ///
-    /// Two render passes that contain one subpass are compatible if they are identical. Two render
-    /// passes that contain more than one subpass are compatible if they are identical except for
-    /// the load/store operations and the image layouts.
+    /// Two render passes are compatible if they have the same number of subpasses, if the
+    /// attachments referenced by each subpass have the same format and number of samples, and if
+    /// their view masks and dependencies are identical. The load/store operations and the image
+    /// layouts are ignored.
     ///
     /// This function is just a shortcut for the `RenderPassCompatible` trait.
     #[inline]
@@ -259,6 +285,16 @@ unsafe impl<T> RenderPassDesc for T where T: SafeDeref, T::Target: RenderPassDes
     fn dependency_desc(&self, num: usize) -> Option<LayoutPassDependencyDescription> {
         (**self).dependency_desc(num)
     }
+
+    #[inline]
+    fn view_mask(&self, subpass: usize) -> u32 {
+        (**self).view_mask(subpass)
+    }
+
+    #[inline]
+    fn correlation_masks(&self) -> Vec<u32> {
+        (**self).correlation_masks()
+    }
 }
 
 /// Iterator to the attachments of a `RenderPassDesc`.
@@ -412,7 +448,7 @@ pub struct LayoutPassDescription {
 /// The implementation is allowed to change the order of the passes within a render pass, unless
 /// you specify that there exists a dependency between two passes (ie. the result of one will be
 /// used as the input of another one).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LayoutPassDependencyDescription {
     /// Index of the subpass that writes the data that `destination_subpass` is going to use.
     pub source_subpass: usize,
@@ -445,6 +481,14 @@ pub struct LayoutPassDependencyDescription {
     /// Passing `false` is always safer than passing `true`, but in practice you rarely need to
     /// pass `false`.
     pub by_region: bool,
+
+    /// If true, then each view of the destination subpass only depends on the same view of the
+    /// source subpass. If false, then each view of the destination subpass depends on all the
+    /// views of the source subpass.
+    ///
+    /// Can only be true if the render pass uses multiview rendering. See the documentation of
+    /// `RenderPassDesc::view_mask`.
+    pub view_local: bool,
 }
 
 /// Describes what the implementation should do with an attachment after all the subpasses have
@@ -0,0 +1,584 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+
+use format::ClearValue;
+use framebuffer::LayoutAttachmentDescription;
+use framebuffer::LayoutPassDependencyDescription;
+use framebuffer::LayoutPassDescription;
+use framebuffer::RenderPassDesc;
+use framebuffer::RenderPassDescClearValues;
+use sync::AccessFlagBits;
+use sync::PipelineStages;
+
+/// Builds the description of a render pass from explicit attachments, subpasses and
+/// dependencies.
+///
+/// Contrary to the `single_pass_renderpass!` and `ordered_passes_renderpass!` macros, which
+/// generate conservative dependencies between each pair of consecutive subpasses, the dependencies
+/// of the render pass are exactly the ones you add with `dependency()`. This lets you express
+/// tight barriers, for example between a subpass that writes a color attachment and a subpass that
+/// reads it as an input attachment from the fragment shader.
+///
+/// The dependencies are checked when calling `build()`.
+///
+/// ```
+/// use vulkano::framebuffer::LayoutPassDependencyDescription;
+/// use vulkano::framebuffer::RenderPassDesc;
+/// use vulkano::framebuffer::RenderPassDescBuilder;
+/// use vulkano::sync::AccessFlagBits;
+/// use vulkano::sync::PipelineStages;
+/// # use vulkano::framebuffer::LayoutAttachmentDescription;
+/// # use vulkano::framebuffer::LayoutPassDescription;
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// # let attachment: LayoutAttachmentDescription = return;
+/// # let (first_pass, second_pass): (LayoutPassDescription, LayoutPassDescription) = return;
+///
+/// let desc = RenderPassDescBuilder::new()
+///     .attachment(attachment)
+///     .subpass(first_pass)
+///     .subpass(second_pass)
+///     .dependency(LayoutPassDependencyDescription {
+///         source_subpass: 0,
+///         destination_subpass: 1,
+///         src_stages: PipelineStages { color_attachment_output: true, .. PipelineStages::none() },
+///         dst_stages: PipelineStages { fragment_shader: true, .. PipelineStages::none() },
+///         src_access: AccessFlagBits { color_attachment_write: true, .. AccessFlagBits::none() },
+///         dst_access: AccessFlagBits { input_attachment_read: true, .. AccessFlagBits::none() },
+///         by_region: true,
+///         view_local: false,
+///     })
+///     .build().unwrap();
+///
+/// let render_pass = desc.build_render_pass(device.clone()).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderPassDescBuilder {
+    attachments: Vec<LayoutAttachmentDescription>,
+    subpasses: Vec<LayoutPassDescription>,
+    // View mask of each subpass. Always has the same length as `subpasses`.
+    view_masks: Vec<u32>,
+    dependencies: Vec<LayoutPassDependencyDescription>,
+    correlation_masks: Vec<u32>,
+}
+
+impl RenderPassDescBuilder {
+    /// Starts building a render pass description with no attachment, subpass or dependency.
+    #[inline]
+    pub fn new() -> RenderPassDescBuilder {
+        RenderPassDescBuilder {
+            attachments: Vec::new(),
+            subpasses: Vec::new(),
+            view_masks: Vec::new(),
+            dependencies: Vec::new(),
+            correlation_masks: Vec::new(),
+        }
+    }
+
+    /// Adds an attachment. Its index is the number of attachments that were added before.
+    #[inline]
+    pub fn attachment(mut self, attachment: LayoutAttachmentDescription) -> RenderPassDescBuilder {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Adds a subpass. Its index is the number of subpasses that were added before.
+    #[inline]
+    pub fn subpass(mut self, subpass: LayoutPassDescription) -> RenderPassDescBuilder {
+        self.subpasses.push(subpass);
+        self.view_masks.push(0);
+        self
+    }
+
+    /// Adds a subpass that uses multiview rendering. Its index is the number of subpasses that
+    /// were added before.
+    ///
+    /// Each bit set in `view_mask` is a view that the subpass renders to. See the documentation
+    /// of `RenderPassDesc::view_mask` for more information. Either all subpasses or none of them
+    /// must use multiview rendering.
+    #[inline]
+    pub fn multiview_subpass(mut self, subpass: LayoutPassDescription, view_mask: u32)
+                             -> RenderPassDescBuilder
+    {
+        self.subpasses.push(subpass);
+        self.view_masks.push(view_mask);
+        self
+    }
+
+    /// Adds a set of views that are likely to be spatially correlated.
+    ///
+    /// This is only a hint for the implementation and is ignored if the render pass doesn't use
+    /// multiview rendering.
+    #[inline]
+    pub fn correlation_mask(mut self, mask: u32) -> RenderPassDescBuilder {
+        self.correlation_masks.push(mask);
+        self
+    }
+
+    /// Adds a dependency between two subpasses.
+    #[inline]
+    pub fn dependency(mut self, dependency: LayoutPassDependencyDescription)
+                      -> RenderPassDescBuilder
+    {
+        self.dependencies.push(dependency);
+        self
+    }
+
+    /// Checks the description and builds an object that implements `RenderPassDesc`.
+    pub fn build(self) -> Result<ExplicitRenderPassDesc, RenderPassDescBuilderError> {
+        if self.subpasses.is_empty() {
+            return Err(RenderPassDescBuilderError::NoSubpass);
+        }
+
+        let multiview = self.view_masks[0] != 0;
+        if self.view_masks.iter().any(|&mask| (mask != 0) != multiview) {
+            return Err(RenderPassDescBuilderError::InconsistentViewMasks);
+        }
+
+        for (subpass, desc) in self.subpasses.iter().enumerate() {
+            let num_attachments = self.attachments.len();
+            let out_of_range = desc.color_attachments.iter()
+                .chain(desc.depth_stencil.iter())
+                .chain(desc.input_attachments.iter())
+                .chain(desc.resolve_attachments.iter())
+                .map(|&(id, _)| id)
+                .chain(desc.preserve_attachments.iter().cloned())
+                .any(|id| id >= num_attachments);
+
+            if out_of_range {
+                return Err(RenderPassDescBuilderError::AttachmentOutOfRange { subpass: subpass });
+            }
+        }
+
+        for (id, dependency) in self.dependencies.iter().enumerate() {
+            try!(check_dependency(id, dependency, self.subpasses.len()));
+        }
+
+        Ok(ExplicitRenderPassDesc {
+            attachments: self.attachments,
+            subpasses: self.subpasses,
+            view_masks: self.view_masks,
+            dependencies: self.dependencies,
+            correlation_masks: self.correlation_masks,
+        })
+    }
+}
+
+// Checks the dependency number `id` of a render pass that has `num_subpasses` subpasses.
+fn check_dependency(id: usize, dependency: &LayoutPassDependencyDescription, num_subpasses: usize)
+                    -> Result<(), RenderPassDescBuilderError>
+{
+    if dependency.source_subpass >= num_subpasses ||
+       dependency.destination_subpass >= num_subpasses
+    {
+        return Err(RenderPassDescBuilderError::SubpassOutOfRange { dependency: id });
+    }
+
+    if dependency.source_subpass > dependency.destination_subpass {
+        return Err(RenderPassDescBuilderError::BackwardDependency { dependency: id });
+    }
+
+    for stages in &[dependency.src_stages, dependency.dst_stages] {
+        let bits: u32 = (*stages).into();
+        if bits == 0 {
+            return Err(RenderPassDescBuilderError::EmptyStageMask { dependency: id });
+        }
+        if stages.compute_shader || stages.transfer || stages.host {
+            return Err(RenderPassDescBuilderError::StageNotInRenderPass { dependency: id });
+        }
+    }
+
+    if !stages_support_access(&dependency.src_stages, &dependency.src_access) ||
+       !stages_support_access(&dependency.dst_stages, &dependency.dst_access)
+    {
+        return Err(RenderPassDescBuilderError::AccessNotSupportedByStages { dependency: id });
+    }
+
+    Ok(())
+}
+
+// Returns true if each access type of `access` can be performed by at least one of the stages
+// of `stages`, as described in the "Supported access types" table of the Vulkan specs.
+fn stages_support_access(stages: &PipelineStages, access: &AccessFlagBits) -> bool {
+    if stages.all_commands {
+        return true;
+    }
+
+    let graphics = stages.all_graphics;
+    let shader = graphics || stages.vertex_shader || stages.tessellation_control_shader ||
+                 stages.tessellation_evaluation_shader || stages.geometry_shader ||
+                 stages.fragment_shader || stages.compute_shader;
+    let fragment_tests = graphics || stages.early_fragment_tests || stages.late_fragment_tests;
+
+    (!access.indirect_command_read || graphics || stages.draw_indirect) &&
+    (!access.index_read || graphics || stages.vertex_input) &&
+    (!access.vertex_attribute_read || graphics || stages.vertex_input) &&
+    (!access.uniform_read || shader) &&
+    (!access.shader_read || shader) &&
+    (!access.shader_write || shader) &&
+    (!access.input_attachment_read || graphics || stages.fragment_shader) &&
+    (!access.color_attachment_read || graphics || stages.color_attachment_output) &&
+    (!access.color_attachment_write || graphics || stages.color_attachment_output) &&
+    (!access.depth_stencil_attachment_read || fragment_tests) &&
+    (!access.depth_stencil_attachment_write || fragment_tests) &&
+    (!access.transfer_read || stages.transfer) &&
+    (!access.transfer_write || stages.transfer) &&
+    (!access.host_read || stages.host) &&
+    (!access.host_write || stages.host) &&
+    (!access.conditional_rendering_read || graphics || stages.conditional_rendering)
+}
+
+/// Description of a render pass built with a `RenderPassDescBuilder`.
+#[derive(Debug, Clone)]
+pub struct ExplicitRenderPassDesc {
+    attachments: Vec<LayoutAttachmentDescription>,
+    subpasses: Vec<LayoutPassDescription>,
+    // View mask of each subpass. Always has the same length as `subpasses`.
+    view_masks: Vec<u32>,
+    dependencies: Vec<LayoutPassDependencyDescription>,
+    correlation_masks: Vec<u32>,
+}
+
+impl ExplicitRenderPassDesc {
+    /// Copies the description of an existing render pass.
+    pub fn from_desc<D: ?Sized>(desc: &D) -> ExplicitRenderPassDesc where D: RenderPassDesc {
+        ExplicitRenderPassDesc {
+            attachments: (0 .. desc.num_attachments()).filter_map(|n| desc.attachment_desc(n))
+                                                       .collect(),
+            subpasses: (0 .. desc.num_subpasses()).filter_map(|n| desc.subpass_desc(n)).collect(),
+            view_masks: (0 .. desc.num_subpasses()).map(|n| desc.view_mask(n)).collect(),
+            dependencies: (0 .. desc.num_dependencies()).filter_map(|n| desc.dependency_desc(n))
+                                                         .collect(),
+            correlation_masks: desc.correlation_masks(),
+        }
+    }
+}
+
+unsafe impl RenderPassDesc for ExplicitRenderPassDesc {
+    #[inline]
+    fn num_attachments(&self) -> usize {
+        self.attachments.len()
+    }
+
+    #[inline]
+    fn attachment_desc(&self, num: usize) -> Option<LayoutAttachmentDescription> {
+        self.attachments.get(num).cloned()
+    }
+
+    #[inline]
+    fn num_subpasses(&self) -> usize {
+        self.subpasses.len()
+    }
+
+    #[inline]
+    fn subpass_desc(&self, num: usize) -> Option<LayoutPassDescription> {
+        self.subpasses.get(num).cloned()
+    }
+
+    #[inline]
+    fn num_dependencies(&self) -> usize {
+        self.dependencies.len()
+    }
+
+    #[inline]
+    fn dependency_desc(&self, num: usize) -> Option<LayoutPassDependencyDescription> {
+        self.dependencies.get(num).cloned()
+    }
+
+    #[inline]
+    fn view_mask(&self, subpass: usize) -> u32 {
+        self.view_masks.get(subpass).cloned().unwrap_or(0)
+    }
+
+    #[inline]
+    fn correlation_masks(&self) -> Vec<u32> {
+        self.correlation_masks.clone()
+    }
+}
+
+unsafe impl RenderPassDescClearValues<Vec<ClearValue>> for ExplicitRenderPassDesc {
+    #[inline]
+    fn convert_clear_values(&self, values: Vec<ClearValue>) -> Box<Iterator<Item = ClearValue>> {
+        // FIXME: safety checks
+        Box::new(values.into_iter())
+    }
+}
+
+/// Error that can happen when building a `RenderPassDescBuilder`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderPassDescBuilderError {
+    /// A render pass must have at least one subpass.
+    NoSubpass,
+    /// Some subpasses use multiview rendering and others don't.
+    InconsistentViewMasks,
+    /// A subpass refers to an attachment that doesn't exist.
+    AttachmentOutOfRange {
+        /// Index of the subpass.
+        subpass: usize,
+    },
+    /// A dependency refers to a subpass that doesn't exist.
+    SubpassOutOfRange {
+        /// Index of the dependency.
+        dependency: usize,
+    },
+    /// The source subpass of a dependency comes after its destination subpass.
+    BackwardDependency {
+        /// Index of the dependency.
+        dependency: usize,
+    },
+    /// The source or destination stages of a dependency are empty.
+    EmptyStageMask {
+        /// Index of the dependency.
+        dependency: usize,
+    },
+    /// A dependency contains the compute, transfer or host stage, which don't exist within a
+    /// render pass.
+    StageNotInRenderPass {
+        /// Index of the dependency.
+        dependency: usize,
+    },
+    /// An access type of a dependency can't be performed by any of its stages.
+    AccessNotSupportedByStages {
+        /// Index of the dependency.
+        dependency: usize,
+    },
+}
+
+impl error::Error for RenderPassDescBuilderError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            RenderPassDescBuilderError::NoSubpass => {
+                "a render pass must have at least one subpass"
+            },
+            RenderPassDescBuilderError::InconsistentViewMasks => {
+                "some subpasses use multiview rendering and others don't"
+            },
+            RenderPassDescBuilderError::AttachmentOutOfRange { .. } => {
+                "a subpass refers to an attachment that doesn't exist"
+            },
+            RenderPassDescBuilderError::SubpassOutOfRange { .. } => {
+                "a dependency refers to a subpass that doesn't exist"
+            },
+            RenderPassDescBuilderError::BackwardDependency { .. } => {
+                "the source subpass of a dependency comes after its destination subpass"
+            },
+            RenderPassDescBuilderError::EmptyStageMask { .. } => {
+                "the source or destination stages of a dependency are empty"
+            },
+            RenderPassDescBuilderError::StageNotInRenderPass { .. } => {
+                "a dependency contains a stage that doesn't exist within a render pass"
+            },
+            RenderPassDescBuilderError::AccessNotSupportedByStages { .. } => {
+                "an access type of a dependency can't be performed by any of its stages"
+            },
+        }
+    }
+}
+
+impl fmt::Display for RenderPassDescBuilderError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use format::Format;
+    use framebuffer::ExplicitRenderPassDesc;
+    use framebuffer::LayoutAttachmentDescription;
+    use framebuffer::LayoutPassDependencyDescription;
+    use framebuffer::LayoutPassDescription;
+    use framebuffer::LoadOp;
+    use framebuffer::RenderPassDesc;
+    use framebuffer::RenderPassDescBuilder;
+    use framebuffer::RenderPassDescBuilderError;
+    use framebuffer::StoreOp;
+    use image::ImageLayout;
+    use sync::AccessFlagBits;
+    use sync::PipelineStages;
+
+    fn two_passes() -> RenderPassDescBuilder {
+        let attachment = LayoutAttachmentDescription {
+            format: Format::R8G8B8A8Unorm,
+            samples: 1,
+            load: LoadOp::Clear,
+            store: StoreOp::DontCare,
+            stencil_load: LoadOp::DontCare,
+            stencil_store: StoreOp::DontCare,
+            initial_layout: ImageLayout::Undefined,
+            final_layout: ImageLayout::ShaderReadOnlyOptimal,
+        };
+
+        let first = LayoutPassDescription {
+            color_attachments: vec![(0, ImageLayout::ColorAttachmentOptimal)],
+            depth_stencil: None,
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        let second = LayoutPassDescription {
+            color_attachments: vec![],
+            depth_stencil: None,
+            input_attachments: vec![(0, ImageLayout::ShaderReadOnlyOptimal)],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        RenderPassDescBuilder::new().attachment(attachment).subpass(first).subpass(second)
+    }
+
+    fn input_attachment_dependency() -> LayoutPassDependencyDescription {
+        LayoutPassDependencyDescription {
+            source_subpass: 0,
+            destination_subpass: 1,
+            src_stages: PipelineStages { color_attachment_output: true, .. PipelineStages::none() },
+            dst_stages: PipelineStages { fragment_shader: true, .. PipelineStages::none() },
+            src_access: AccessFlagBits { color_attachment_write: true, .. AccessFlagBits::none() },
+            dst_access: AccessFlagBits { input_attachment_read: true, .. AccessFlagBits::none() },
+            by_region: true,
+            view_local: false,
+        }
+    }
+
+    #[test]
+    fn explicit_dependency() {
+        let desc = two_passes().dependency(input_attachment_dependency()).build().unwrap();
+        assert_eq!(desc.num_attachments(), 1);
+        assert_eq!(desc.num_subpasses(), 2);
+        assert_eq!(desc.num_dependencies(), 1);
+        assert!(desc.dependency_desc(0).unwrap().dst_access.input_attachment_read);
+    }
+
+    #[test]
+    fn access_not_supported() {
+        let dependency = LayoutPassDependencyDescription {
+            dst_stages: PipelineStages { vertex_input: true, .. PipelineStages::none() },
+            .. input_attachment_dependency()
+        };
+
+        match two_passes().dependency(dependency).build() {
+            Err(RenderPassDescBuilderError::AccessNotSupportedByStages { dependency: 0 }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn backward_dependency() {
+        let dependency = LayoutPassDependencyDescription {
+            source_subpass: 1,
+            destination_subpass: 0,
+            .. input_attachment_dependency()
+        };
+
+        match two_passes().dependency(dependency).build() {
+            Err(RenderPassDescBuilderError::BackwardDependency { dependency: 0 }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn compute_stage_forbidden() {
+        let dependency = LayoutPassDependencyDescription {
+            src_stages: PipelineStages { compute_shader: true, .. PipelineStages::none() },
+            src_access: AccessFlagBits { shader_write: true, .. AccessFlagBits::none() },
+            .. input_attachment_dependency()
+        };
+
+        match two_passes().dependency(dependency).build() {
+            Err(RenderPassDescBuilderError::StageNotInRenderPass { dependency: 0 }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn inconsistent_view_masks() {
+        let pass = LayoutPassDescription {
+            color_attachments: vec![],
+            depth_stencil: None,
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        let desc = RenderPassDescBuilder::new()
+            .multiview_subpass(pass.clone(), 0b11)
+            .multiview_subpass(pass.clone(), 0b01)
+            .correlation_mask(0b11)
+            .build()
+            .unwrap();
+        assert_eq!(desc.view_mask(1), 0b01);
+        assert_eq!(desc.correlation_masks(), vec![0b11]);
+
+        match RenderPassDescBuilder::new().multiview_subpass(pass.clone(), 0b11).subpass(pass)
+                                          .build()
+        {
+            Err(RenderPassDescBuilderError::InconsistentViewMasks) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn compatibility() {
+        let desc = two_passes().build().unwrap();
+
+        // Load/store operations and layouts are ignored.
+        let other = two_passes().build().unwrap();
+        let mut atch = other.attachment_desc(0).unwrap();
+        atch.load = LoadOp::Load;
+        atch.final_layout = ImageLayout::ColorAttachmentOptimal;
+        let other = RenderPassDescBuilder::new().attachment(atch)
+                                                .subpass(other.subpass_desc(0).unwrap())
+                                                .subpass(other.subpass_desc(1).unwrap())
+                                                .build().unwrap();
+        assert!(desc.is_compatible_with(&other));
+        assert!(desc.is_compatible_with(&ExplicitRenderPassDesc::from_desc(&desc)));
+
+        // Different format.
+        let mut atch = desc.attachment_desc(0).unwrap();
+        atch.format = Format::B8G8R8A8Unorm;
+        let other = RenderPassDescBuilder::new().attachment(atch)
+                                                .subpass(desc.subpass_desc(0).unwrap())
+                                                .subpass(desc.subpass_desc(1).unwrap())
+                                                .build().unwrap();
+        assert!(!desc.is_compatible_with(&other));
+
+        // Different number of subpasses.
+        let other = RenderPassDescBuilder::new().attachment(desc.attachment_desc(0).unwrap())
+                                                .subpass(desc.subpass_desc(0).unwrap())
+                                                .build().unwrap();
+        assert!(!desc.is_compatible_with(&other));
+
+        // Different dependencies.
+        let other = two_passes().dependency(input_attachment_dependency()).build().unwrap();
+        assert!(!desc.is_compatible_with(&other));
+        let desc = two_passes().dependency(input_attachment_dependency()).build().unwrap();
+        assert!(desc.is_compatible_with(&other));
+    }
+
+    #[test]
+    fn attachment_out_of_range() {
+        let pass = LayoutPassDescription {
+            color_attachments: vec![(3, ImageLayout::ColorAttachmentOptimal)],
+            depth_stencil: None,
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        match RenderPassDescBuilder::new().subpass(pass).build() {
+            Err(RenderPassDescBuilderError::AttachmentOutOfRange { subpass: 0 }) => (),
+            _ => panic!()
+        };
+    }
+}
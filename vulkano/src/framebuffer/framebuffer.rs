@@ -410,6 +410,16 @@ unsafe impl<Rp, A> RenderPassDesc for Framebuffer<Rp, A> where Rp: RenderPassDes
     fn dependency_desc(&self, num: usize) -> Option<LayoutPassDependencyDescription> {
         self.render_pass.dependency_desc(num)
     }
+
+    #[inline]
+    fn view_mask(&self, subpass: usize) -> u32 {
+        self.render_pass.view_mask(subpass)
+    }
+
+    #[inline]
+    fn correlation_masks(&self) -> Vec<u32> {
+        self.render_pass.correlation_masks()
+    }
 }
 
 unsafe impl<C, Rp, A> RenderPassDescClearValues<C> for Framebuffer<Rp, A>
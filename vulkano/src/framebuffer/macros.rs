@@ -241,6 +241,7 @@ macro_rules! ordered_passes_renderpass {
                     src_access: AccessFlagBits::all(),         // TODO: correct values
                     dst_access: AccessFlagBits::all(),         // TODO: correct values
                     by_region: true,            // TODO: correct values
+                    view_local: false,
                 })
             }
 
@@ -81,6 +81,10 @@
 //!
 //! See the documentation of the macro for more details. TODO: put link here
 //!
+//! The macros generate conservative dependencies between the subpasses. If you need to specify
+//! the exact pipeline stages and access types of each dependency, build the description with a
+//! `RenderPassDescBuilder` instead.
+//!
 //! Once a `RenderPass<_>` struct is created, it implements the same render-pass-related traits as
 //! its template parameter.
 //!
@@ -102,6 +106,9 @@ pub use self::desc::RenderPassDescSubpasses;
 pub use self::desc::RenderPassDescDependencies;
 pub use self::desc::StoreOp;
 pub use self::desc::LoadOp;
+pub use self::desc_builder::ExplicitRenderPassDesc;
+pub use self::desc_builder::RenderPassDescBuilder;
+pub use self::desc_builder::RenderPassDescBuilderError;
 pub use self::empty::EmptySinglePassRenderPassDesc;
 pub use self::framebuffer::Framebuffer;
 pub use self::framebuffer::FramebufferBuilder;
@@ -122,6 +129,7 @@ mod macros;
 mod attachments_list;
 mod compat_atch;
 mod desc;
+mod desc_builder;
 mod empty;
 mod framebuffer;
 mod sys;
@@ -231,6 +231,18 @@ impl<D> RenderPass<D> where D: RenderPassDesc {
             out
         };
 
+        // The conditional rendering stage and access are provided by an extension.
+        let conditional_rendering = description.dependency_descs().any(|dependency| {
+            dependency.src_stages.conditional_rendering ||
+            dependency.dst_stages.conditional_rendering ||
+            dependency.src_access.conditional_rendering_read ||
+            dependency.dst_access.conditional_rendering_read
+        });
+
+        if conditional_rendering && !device.loaded_extensions().ext_conditional_rendering {
+            return Err(RenderPassCreationError::ConditionalRenderingExtensionNotEnabled);
+        }
+
         let dependencies = description.dependency_descs().map(|dependency| {
             debug_assert!(dependency.source_subpass < passes.len());
             debug_assert!(dependency.destination_subpass < passes.len());
@@ -242,14 +254,54 @@ impl<D> RenderPass<D> where D: RenderPassDesc {
                 dstStageMask: dependency.dst_stages.into(),
                 srcAccessMask: dependency.src_access.into(),
                 dstAccessMask: dependency.dst_access.into(),
-                dependencyFlags: if dependency.by_region { vk::DEPENDENCY_BY_REGION_BIT } else { 0 },
+                dependencyFlags: {
+                    let mut flags = 0;
+                    if dependency.by_region { flags |= vk::DEPENDENCY_BY_REGION_BIT; }
+                    if dependency.view_local { flags |= vk::DEPENDENCY_VIEW_LOCAL_BIT_KHR; }
+                    flags
+                },
             }
         }).collect::<SmallVec<[_; 16]>>();
 
+        // View masks for multiview rendering. Multiview is only enabled if at least one of the
+        // view masks is non-zero.
+        let view_masks = (0 .. passes.len()).map(|pass| description.view_mask(pass))
+                                            .collect::<SmallVec<[_; 16]>>();
+        let correlation_masks = description.correlation_masks();
+        let multiview = view_masks.iter().any(|&mask| mask != 0);
+
+        if multiview {
+            if !device.loaded_extensions().khr_multiview {
+                return Err(RenderPassCreationError::MultiviewExtensionNotEnabled);
+            }
+            if !device.enabled_features().multiview {
+                return Err(RenderPassCreationError::MultiviewFeatureNotEnabled);
+            }
+            // If the limit couldn't be queried, fall back to the minimum value guaranteed by the
+            // specification.
+            let max_view_count = device.physical_device().max_multiview_view_count().unwrap_or(6);
+            try!(check_view_masks(&view_masks, max_view_count));
+        } else if description.dependency_descs().any(|dependency| dependency.view_local) {
+            return Err(RenderPassCreationError::ViewLocalDependencyWithoutMultiview);
+        }
+
+        let multiview_infos = vk::RenderPassMultiviewCreateInfoKHR {
+            sType: vk::STRUCTURE_TYPE_RENDER_PASS_MULTIVIEW_CREATE_INFO_KHR,
+            pNext: ptr::null(),
+            subpassCount: view_masks.len() as u32,
+            pViewMasks: view_masks.as_ptr(),
+            dependencyCount: 0,
+            pViewOffsets: ptr::null(),
+            correlationMaskCount: correlation_masks.len() as u32,
+            pCorrelationMasks: if correlation_masks.is_empty() { ptr::null() }
+                               else { correlation_masks.as_ptr() },
+        };
+
         let render_pass = unsafe {
             let infos = vk::RenderPassCreateInfo {
                 sType: vk::STRUCTURE_TYPE_RENDER_PASS_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: if multiview { &multiview_infos as *const _ as *const _ }
+                       else { ptr::null() },
                 flags: 0,   // reserved
                 attachmentCount: attachments.len() as u32,
                 pAttachments: if attachments.is_empty() { ptr::null() }
@@ -355,6 +407,16 @@ unsafe impl<D> RenderPassDesc for RenderPass<D> where D: RenderPassDesc {
     fn dependency_desc(&self, num: usize) -> Option<LayoutPassDependencyDescription> {
         self.desc.dependency_desc(num)
     }
+
+    #[inline]
+    fn view_mask(&self, subpass: usize) -> u32 {
+        self.desc.view_mask(subpass)
+    }
+
+    #[inline]
+    fn correlation_masks(&self) -> Vec<u32> {
+        self.desc.correlation_masks()
+    }
 }
 
 unsafe impl<C, D> RenderPassDescClearValues<C> for RenderPass<D>
@@ -420,6 +482,20 @@ pub enum RenderPassCreationError {
     OomError(OomError),
     /// The maximum number of color attachments has been exceeded.
     ColorAttachmentsLimitExceeded,
+    /// Multiview rendering requires the `khr_multiview` extension to be enabled on the device.
+    MultiviewExtensionNotEnabled,
+    /// Multiview rendering requires the `multiview` feature to be enabled on the device.
+    MultiviewFeatureNotEnabled,
+    /// Some subpasses have a non-zero view mask and others don't.
+    InconsistentViewMasks,
+    /// A view mask contains a view whose index is not lower than the `max_multiview_view_count`
+    /// of the physical device.
+    MultiviewViewCountExceeded,
+    /// A dependency is view-local but the render pass doesn't use multiview rendering.
+    ViewLocalDependencyWithoutMultiview,
+    /// A dependency uses the conditional rendering stage or access, which requires the
+    /// `ext_conditional_rendering` extension to be enabled on the device.
+    ConditionalRenderingExtensionNotEnabled,
 }
 
 impl error::Error for RenderPassCreationError {
@@ -430,6 +506,25 @@ impl error::Error for RenderPassCreationError {
             RenderPassCreationError::ColorAttachmentsLimitExceeded => {
                 "the maximum number of color attachments has been exceeded"
             },
+            RenderPassCreationError::MultiviewExtensionNotEnabled => {
+                "multiview rendering requires the `khr_multiview` extension to be enabled"
+            },
+            RenderPassCreationError::MultiviewFeatureNotEnabled => {
+                "multiview rendering requires the `multiview` feature to be enabled"
+            },
+            RenderPassCreationError::InconsistentViewMasks => {
+                "some subpasses have a non-zero view mask and others don't"
+            },
+            RenderPassCreationError::MultiviewViewCountExceeded => {
+                "a view mask contains a view whose index exceeds the maximum number of views"
+            },
+            RenderPassCreationError::ViewLocalDependencyWithoutMultiview => {
+                "a dependency is view-local but the render pass doesn't use multiview rendering"
+            },
+            RenderPassCreationError::ConditionalRenderingExtensionNotEnabled => {
+                "a dependency uses the conditional rendering stage or access, which requires the \
+                 `ext_conditional_rendering` extension to be enabled"
+            },
         }
     }
 
@@ -471,11 +566,35 @@ impl From<Error> for RenderPassCreationError {
     }
 }
 
+// Checks the view masks of a multiview render pass. All the masks must be non-zero, and the
+// index of their highest view must be lower than `max_view_count`.
+fn check_view_masks(view_masks: &[u32], max_view_count: u32)
+                    -> Result<(), RenderPassCreationError>
+{
+    for &mask in view_masks {
+        if mask == 0 {
+            return Err(RenderPassCreationError::InconsistentViewMasks);
+        }
+        if 32 - mask.leading_zeros() > max_view_count {
+            return Err(RenderPassCreationError::MultiviewViewCountExceeded);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use format::Format;
+    use framebuffer::LayoutPassDependencyDescription;
+    use framebuffer::LayoutPassDescription;
     use framebuffer::RenderPass;
     use framebuffer::RenderPassCreationError;
+    use framebuffer::RenderPassDesc;
+    use framebuffer::RenderPassDescBuilder;
+    use framebuffer::sys::check_view_masks;
+    use sync::AccessFlagBits;
+    use sync::PipelineStages;
 
     #[test]
     fn empty() {
@@ -517,6 +636,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn multiview_requires_extension() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pass = LayoutPassDescription {
+            color_attachments: vec![],
+            depth_stencil: None,
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        let desc = RenderPassDescBuilder::new().multiview_subpass(pass, 0b11).build().unwrap();
+
+        match desc.build_render_pass(device) {
+            Err(RenderPassCreationError::MultiviewExtensionNotEnabled) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn conditional_rendering_dependency_requires_extension() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pass = LayoutPassDescription {
+            color_attachments: vec![],
+            depth_stencil: None,
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        let dependency = LayoutPassDependencyDescription {
+            source_subpass: 0,
+            destination_subpass: 1,
+            src_stages: PipelineStages { conditional_rendering: true, .. PipelineStages::none() },
+            dst_stages: PipelineStages { conditional_rendering: true, .. PipelineStages::none() },
+            src_access: AccessFlagBits::none(),
+            dst_access: AccessFlagBits { conditional_rendering_read: true,
+                                         .. AccessFlagBits::none() },
+            by_region: true,
+            view_local: false,
+        };
+
+        let desc = RenderPassDescBuilder::new().subpass(pass.clone()).subpass(pass)
+                                               .dependency(dependency).build().unwrap();
+
+        match desc.build_render_pass(device) {
+            Err(RenderPassCreationError::ConditionalRenderingExtensionNotEnabled) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn view_local_dependency_requires_multiview() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pass = LayoutPassDescription {
+            color_attachments: vec![],
+            depth_stencil: None,
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![],
+        };
+
+        let dependency = LayoutPassDependencyDescription {
+            source_subpass: 0,
+            destination_subpass: 1,
+            src_stages: PipelineStages { all_graphics: true, .. PipelineStages::none() },
+            dst_stages: PipelineStages { all_graphics: true, .. PipelineStages::none() },
+            src_access: AccessFlagBits::none(),
+            dst_access: AccessFlagBits::none(),
+            by_region: true,
+            view_local: true,
+        };
+
+        let desc = RenderPassDescBuilder::new().subpass(pass.clone()).subpass(pass)
+                                               .dependency(dependency).build().unwrap();
+
+        match desc.build_render_pass(device) {
+            Err(RenderPassCreationError::ViewLocalDependencyWithoutMultiview) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn view_masks_checked() {
+        assert!(check_view_masks(&[0b11, 0b1], 2).is_ok());
+        assert_eq!(check_view_masks(&[0b11, 0], 2),
+                   Err(RenderPassCreationError::InconsistentViewMasks));
+        assert_eq!(check_view_masks(&[0b100], 2),
+                   Err(RenderPassCreationError::MultiviewViewCountExceeded));
+        assert!(check_view_masks(&[1 << 31], 32).is_ok());
+    }
+
     #[test]
     fn non_zero_granularity() {
         let (device, _) = gfx_dev_and_queue!();
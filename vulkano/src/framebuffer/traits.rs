@@ -7,11 +7,14 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
+
 use device::DeviceOwned;
 use format::ClearValue;
 use framebuffer::FramebufferSys;
 use framebuffer::RenderPassDesc;
 use framebuffer::RenderPassSys;
+use image::ImageLayout;
 use image::ImageViewAccess;
 use pipeline::shader::ShaderInterfaceDef;
 
@@ -212,17 +215,73 @@ unsafe impl<A, B: ?Sized> RenderPassCompatible<B> for A
     where A: RenderPassDesc, B: RenderPassDesc
 {
     fn is_compatible_with(&self, other: &B) -> bool {
-        // FIXME:
-        /*for (atch1, atch2) in (&self).attachments().zip(other.attachments()) {
-            if !atch1.is_compatible_with(&atch2) {
+        if self.num_subpasses() != other.num_subpasses() {
+            return false;
+        }
+
+        for subpass in 0 .. self.num_subpasses() {
+            let desc1 = self.subpass_desc(subpass).unwrap();
+            let desc2 = other.subpass_desc(subpass).unwrap();
+
+            if self.view_mask(subpass) != other.view_mask(subpass) {
+                return false;
+            }
+
+            let depth1: Vec<_> = desc1.depth_stencil.into_iter().collect();
+            let depth2: Vec<_> = desc2.depth_stencil.into_iter().collect();
+
+            if !references_compatible(self, &desc1.color_attachments, other,
+                                      &desc2.color_attachments) ||
+               !references_compatible(self, &desc1.input_attachments, other,
+                                      &desc2.input_attachments) ||
+               !references_compatible(self, &desc1.resolve_attachments, other,
+                                      &desc2.resolve_attachments) ||
+               !references_compatible(self, &depth1, other, &depth2)
+            {
+                return false;
+            }
+        }
+
+        if self.num_dependencies() != other.num_dependencies() {
+            return false;
+        }
+
+        for num in 0 .. self.num_dependencies() {
+            if self.dependency_desc(num) != other.dependency_desc(num) {
                 return false;
             }
-        }*/
+        }
 
-        return true;
+        true
+    }
+}
+
+// Returns true if two lists of attachment references are compatible, as defined in the
+// `Render Pass Compatibility` section of the Vulkan specs.
+//
+// The references are compatible if the attachments they point to have the same format and number
+// of samples. If one list is shorter than the other, the missing entries are considered unused
+// and are only compatible with unused entries.
+fn references_compatible<A: ?Sized, B: ?Sized>(rp1: &A, refs1: &[(usize, ImageLayout)], rp2: &B,
+                                               refs2: &[(usize, ImageLayout)]) -> bool
+    where A: RenderPassDesc, B: RenderPassDesc
+{
+    for num in 0 .. cmp::max(refs1.len(), refs2.len()) {
+        let atch1 = refs1.get(num).and_then(|&(id, _)| rp1.attachment_desc(id));
+        let atch2 = refs2.get(num).and_then(|&(id, _)| rp2.attachment_desc(id));
 
-        // FIXME: finish
+        match (atch1, atch2) {
+            (Some(atch1), Some(atch2)) => {
+                if !atch1.is_compatible_with(&atch2) {
+                    return false;
+                }
+            },
+            (None, None) => (),
+            _ => return false,
+        }
     }
+
+    true
 }
 
 /// Represents a subpass within a `RenderPassAbstract` object.
@@ -258,6 +317,12 @@ impl<L> Subpass<L> where L: RenderPassDesc {
         self.render_pass.num_color_attachments(self.subpass_id).unwrap()
     }
 
+    /// Returns the view mask of this subpass. Non-zero if the subpass uses multiview rendering.
+    #[inline]
+    pub fn view_mask(&self) -> u32 {
+        self.render_pass.view_mask(self.subpass_id as usize)
+    }
+
     /// Returns true if the subpass has a depth attachment or a depth-stencil attachment.
     #[inline]
     pub fn has_depth(&self) -> bool {
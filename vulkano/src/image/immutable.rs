@@ -7,15 +7,29 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
+use std::error;
+use std::fmt;
 use std::sync::Arc;
 use smallvec::SmallVec;
 
+use command_buffer::AutoCommandBufferBuilder;
+use command_buffer::CommandAddError;
+use command_buffer::CommandBuffer;
+use command_buffer::CommandBufferExecError;
+use command_buffer::CommandBufferExecFuture;
+use command_buffer::cb::AddCommand;
+use command_buffer::cb::CommandBufferBuild;
+use command_buffer::commands_raw::CmdCopyImage;
+use command_buffer::commands_raw::CmdCopyImageError;
+use command_buffer::commands_raw::CmdImageMemoryBarrier;
 use device::Device;
 use device::Queue;
 use format::Format;
 use format::FormatDesc;
 use image::Dimensions;
 use image::ImageDimensions;
+use image::ImageSubresourceLayers;
 use image::MipmapsCount;
 use image::sys::ImageCreationError;
 use image::ImageLayout;
@@ -33,7 +47,11 @@ use memory::pool::MemoryPool;
 use memory::pool::MemoryPoolAlloc;
 use memory::pool::StdMemoryPool;
 use sync::AccessError;
+use sync::AccessFlagBits;
+use sync::GpuFuture;
+use sync::PipelineStages;
 use sync::Sharing;
+use OomError;
 
 /// Image whose purpose is to be used for read-only purposes. You can write to the image once,
 /// but then you must only ever read from it. TODO: clarify because of blit operations
@@ -59,6 +77,9 @@ impl<F> ImmutableImage<F> {
     }
 
     /// Builds a new immutable image with the given number of mipmaps.
+    ///
+    /// Use `MipmapsCount::Max` to allocate the full chain of mipmaps without having to compute
+    /// its length yourself.
     pub fn with_mipmaps<'a, I, M>(device: Arc<Device>, dimensions: Dimensions, format: F,
                                   mipmaps: M, queue_families: I)
                                   -> Result<Arc<ImmutableImage<F>>, ImageCreationError>
@@ -114,6 +135,89 @@ impl<F> ImmutableImage<F> {
     }
 }
 
+// TODO: make this prettier
+type ImmutableImageFromImageFuture<P> = CommandBufferExecFuture<P, ::command_buffer::cb::SubmitSyncLayer<::command_buffer::cb::AbstractStorageLayer<::command_buffer::cb::UnsafeCommandBuffer<Arc<::command_buffer::pool::standard::StandardCommandPool>>>>>;
+
+impl<F> ImmutableImage<F> where F: FormatDesc + 'static + Send + Sync {
+    /// Builds a new immutable image whose content is copied from `source`, for example a
+    /// `StorageImage` that was filled by a compute shader.
+    ///
+    /// All the mipmap levels and array layers of `source` are copied. The `format` and the
+    /// `dimensions` must match the ones of `source`, and `source` must have been created with
+    /// the `transfer_source` usage.
+    ///
+    /// The copy is submitted to `queue` after `future`, which is normally the future of the
+    /// commands that write `source`. Pass `sync::now()` if `source` is already ready. The
+    /// returned future must be waited upon, for example with `join`, before the image is used.
+    pub fn from_image<S, P>(source: S, dimensions: Dimensions, format: F, future: P,
+                            queue: Arc<Queue>)
+                            -> Result<(Arc<ImmutableImage<F>>, ImmutableImageFromImageFuture<P>),
+                                      ImmutableImageFromImageError>
+        where S: ImageAccess + Clone + Send + Sync + 'static,
+              P: GpuFuture
+    {
+        if source.format() != format.format() {
+            return Err(ImmutableImageFromImageError::FormatMismatch);
+        }
+
+        if source.dimensions() != dimensions.to_image_dimensions() {
+            return Err(ImmutableImageFromImageError::DimensionsMismatch);
+        }
+
+        let device = queue.device().clone();
+        let mipmaps = source.mipmap_levels();
+        let image = try!(ImmutableImage::with_mipmaps(device.clone(), dimensions, format, mipmaps,
+                                                      Some(queue.family())));
+        let init = ImmutableImageInitialization { image: image.clone() };
+
+        let transfer = PipelineStages { transfer: true, .. PipelineStages::none() };
+        let transfer_write = AccessFlagBits { transfer_write: true, .. AccessFlagBits::none() };
+        let all_commands = PipelineStages::all_commands();
+        let shader_read = AccessFlagBits { shader_read: true, .. AccessFlagBits::none() };
+
+        let mut cb = try!(AutoCommandBufferBuilder::new(device, queue.family()));
+
+        // The image was just created, so its content can be discarded.
+        cb = try!(cb.add(unsafe {
+            CmdImageMemoryBarrier::with_layout_transition(init.clone(), PipelineStages::none(),
+                                                          AccessFlagBits::none(), transfer,
+                                                          transfer_write, ImageLayout::Undefined,
+                                                          ImageLayout::TransferDstOptimal)
+        }));
+
+        let layers = source.dimensions().array_layers();
+        let dims = source.dimensions().width_height_depth();
+
+        for mip_level in 0 .. mipmaps {
+            let subresource = ImageSubresourceLayers {
+                mip_level: mip_level,
+                first_layer: 0,
+                num_layers: layers,
+            };
+
+            let extent = [
+                cmp::max(1, dims[0] >> mip_level),
+                cmp::max(1, dims[1] >> mip_level),
+                cmp::max(1, dims[2] >> mip_level),
+            ];
+
+            let copy = try!(CmdCopyImage::new(source.clone(), subresource, [0, 0, 0],
+                                              init.clone(), subresource, [0, 0, 0], extent));
+            cb = try!(cb.add(copy));
+        }
+
+        cb = try!(cb.add(unsafe {
+            CmdImageMemoryBarrier::with_layout_transition(init, transfer, transfer_write,
+                                                          all_commands, shader_read,
+                                                          ImageLayout::TransferDstOptimal,
+                                                          ImageLayout::ShaderReadOnlyOptimal)
+        }));
+
+        let future = try!(try!(cb.build()).execute_after(future, queue));
+        Ok((image, future))
+    }
+}
+
 impl<F, A> ImmutableImage<F, A> where A: MemoryPool {
     /// Returns the dimensions of the image.
     #[inline]
@@ -199,6 +303,54 @@ unsafe impl<F, A> ImageAccess for ImmutableImage<F, A> where F: 'static + Send +
     }
 }
 
+// Access to an immutable image while it is being initialized.
+//
+// The image is kept in the `TransferDstOptimal` layout for the duration of the initialization.
+// The transitions from and to the other layouts are done manually.
+struct ImmutableImageInitialization<F, A = Arc<StdMemoryPool>> where A: MemoryPool {
+    image: Arc<ImmutableImage<F, A>>,
+}
+
+impl<F, A> Clone for ImmutableImageInitialization<F, A> where A: MemoryPool {
+    #[inline]
+    fn clone(&self) -> ImmutableImageInitialization<F, A> {
+        ImmutableImageInitialization { image: self.image.clone() }
+    }
+}
+
+unsafe impl<F, A> ImageAccess for ImmutableImageInitialization<F, A>
+    where F: 'static + Send + Sync, A: MemoryPool
+{
+    #[inline]
+    fn inner(&self) -> &UnsafeImage {
+        &self.image.image
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::TransferDstOptimal
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::TransferDstOptimal
+    }
+
+    #[inline]
+    fn conflict_key(&self, _: u32, _: u32, _: u32, _: u32) -> u64 {
+        self.image.image.key()
+    }
+
+    #[inline]
+    fn try_gpu_lock(&self, _: bool, _: &Queue) -> Result<(), AccessError> {
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+    }
+}
+
 unsafe impl<P, F, A> ImageContent<P> for ImmutableImage<F, A>
     where F: 'static + Send + Sync, A: MemoryPool
 {
@@ -251,3 +403,176 @@ unsafe impl<F: 'static, A> ImageViewAccess for ImmutableImage<F, A>
         true
     }
 }
+
+/// Error that can happen when creating an `ImmutableImage` from another image.
+#[derive(Debug, Clone)]
+pub enum ImmutableImageFromImageError {
+    /// Error while creating the image.
+    ImageCreationError(ImageCreationError),
+    /// Not enough memory.
+    OomError(OomError),
+    /// The format of the source image doesn't match the requested format.
+    FormatMismatch,
+    /// The dimensions of the source image don't match the requested dimensions.
+    DimensionsMismatch,
+    /// The source image can't be copied, for example because it is missing the
+    /// `transfer_source` usage.
+    CopyError(CmdCopyImageError),
+    /// Error while adding a command to the command buffer.
+    CommandAddError(CommandAddError),
+    /// Error while submitting the command buffer.
+    CommandBufferExecError(CommandBufferExecError),
+}
+
+impl error::Error for ImmutableImageFromImageError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ImmutableImageFromImageError::ImageCreationError(_) => {
+                "error while creating the image"
+            },
+            ImmutableImageFromImageError::OomError(_) => "not enough memory available",
+            ImmutableImageFromImageError::FormatMismatch => {
+                "the format of the source image doesn't match the requested format"
+            },
+            ImmutableImageFromImageError::DimensionsMismatch => {
+                "the dimensions of the source image don't match the requested dimensions"
+            },
+            ImmutableImageFromImageError::CopyError(_) => "the source image can't be copied",
+            ImmutableImageFromImageError::CommandAddError(_) => {
+                "error while adding a command to the command buffer"
+            },
+            ImmutableImageFromImageError::CommandBufferExecError(_) => {
+                "error while submitting the command buffer"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ImmutableImageFromImageError::ImageCreationError(ref err) => Some(err),
+            ImmutableImageFromImageError::OomError(ref err) => Some(err),
+            ImmutableImageFromImageError::CopyError(ref err) => Some(err),
+            ImmutableImageFromImageError::CommandAddError(ref err) => Some(err),
+            ImmutableImageFromImageError::CommandBufferExecError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ImmutableImageFromImageError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<ImageCreationError> for ImmutableImageFromImageError {
+    #[inline]
+    fn from(err: ImageCreationError) -> ImmutableImageFromImageError {
+        ImmutableImageFromImageError::ImageCreationError(err)
+    }
+}
+
+impl From<OomError> for ImmutableImageFromImageError {
+    #[inline]
+    fn from(err: OomError) -> ImmutableImageFromImageError {
+        ImmutableImageFromImageError::OomError(err)
+    }
+}
+
+impl From<CmdCopyImageError> for ImmutableImageFromImageError {
+    #[inline]
+    fn from(err: CmdCopyImageError) -> ImmutableImageFromImageError {
+        ImmutableImageFromImageError::CopyError(err)
+    }
+}
+
+impl From<CommandAddError> for ImmutableImageFromImageError {
+    #[inline]
+    fn from(err: CommandAddError) -> ImmutableImageFromImageError {
+        ImmutableImageFromImageError::CommandAddError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for ImmutableImageFromImageError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> ImmutableImageFromImageError {
+        ImmutableImageFromImageError::CommandBufferExecError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use command_buffer::CommandBufferBuilder;
+    use format::Format;
+    use image::Dimensions;
+    use image::ImmutableImage;
+    use image::ImmutableImageFromImageError;
+    use image::StorageImage;
+    use sync::GpuFuture;
+    use sync::now;
+
+    #[test]
+    fn from_image_dimensions_mismatch() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let source = StorageImage::new(device, Dimensions::Dim2d { width: 32, height: 32 },
+                                       Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+
+        let dimensions = Dimensions::Dim2d { width: 16, height: 16 };
+        match ImmutableImage::from_image(source, dimensions, Format::R8G8B8A8Unorm,
+                                         now(queue.device().clone()), queue)
+        {
+            Err(ImmutableImageFromImageError::DimensionsMismatch) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn from_image_format_mismatch() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dimensions = Dimensions::Dim2d { width: 32, height: 32 };
+        let source = StorageImage::new(device, dimensions, Format::R8G8B8A8Unorm,
+                                       Some(queue.family())).unwrap();
+
+        match ImmutableImage::from_image(source, dimensions, Format::R32Sfloat,
+                                         now(queue.device().clone()), queue)
+        {
+            Err(ImmutableImageFromImageError::FormatMismatch) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn from_image_after_future() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dimensions = Dimensions::Dim2d { width: 32, height: 32 };
+        let source = StorageImage::new(device.clone(), dimensions, Format::R8G8B8A8Unorm,
+                                       Some(queue.family())).unwrap();
+
+        let pixels = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()),
+                                                    (0 .. 32 * 32).map(|_| [0u8, 0, 255, 255]))
+                                         .unwrap();
+
+        let written = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+            .copy_buffer_to_image(pixels, source.clone()).unwrap()
+            .build().unwrap()
+            .execute(queue.clone()).unwrap();
+
+        let (image, future) = ImmutableImage::from_image(source, dimensions,
+                                                         Format::R8G8B8A8Unorm, written,
+                                                         queue).unwrap();
+        future.then_signal_fence_and_flush().unwrap().wait(None).unwrap();
+
+        assert_eq!(image.dimensions(), dimensions);
+    }
+}
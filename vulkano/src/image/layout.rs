@@ -40,3 +40,13 @@ pub enum ImageLayout {
     Preinitialized = vk::IMAGE_LAYOUT_PREINITIALIZED,
     PresentSrc = vk::IMAGE_LAYOUT_PRESENT_SRC_KHR,
 }
+
+/// Layout that vulkano believes a subresource of an image is in. Returned by
+/// `ImageAccess::current_layout`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrackedLayout {
+    /// The subresource is known to be in this layout.
+    Known(ImageLayout),
+    /// Vulkano doesn't know the layout of the subresource.
+    Unknown,
+}
@@ -0,0 +1,612 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::iter::Empty;
+use std::mem;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+use std::sync::TryLockError;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use device::Device;
+use device::DeviceOwned;
+use device::Queue;
+use format::ClearValue;
+use format::Format;
+use format::FormatDesc;
+use format::FormatTy;
+use image::Dimensions;
+use image::ImageDimensions;
+use image::ImageLayout;
+use image::ImageUsage;
+use image::TrackedLayout;
+use image::sys::ImageCreationError;
+use image::sys::LinearLayout;
+use image::sys::UnsafeImage;
+use image::sys::UnsafeImageView;
+use image::traits::Image;
+use image::traits::ImageAccess;
+use image::traits::ImageClearValue;
+use image::traits::ImageContent;
+use image::traits::ImageView;
+use image::traits::ImageViewAccess;
+use memory::CpuAccess as MemCpuAccess;
+use memory::pool::AllocLayout;
+use memory::pool::MemoryPool;
+use memory::pool::MemoryPoolAlloc;
+use memory::pool::StdMemoryPool;
+use sync::AccessError;
+use sync::Sharing;
+
+/// Two-dimensional color image with linear tiling, whose memory can be accessed directly from
+/// the CPU.
+///
+/// Images with linear tiling store their texels row by row, which means that their content can
+/// be read or written through a mapping of their memory without any copy. This is especially
+/// efficient on devices where the host and the GPU share the same memory. On the other hand much
+/// fewer formats and usages are supported with linear tiling, and the GPU may access such images
+/// more slowly than images with optimal tiling.
+///
+/// Rows can be padded by the implementation. Use `layout()` to know where each row starts
+/// within the slice returned by `read()` and `write()`.
+///
+/// The image is created in the `Preinitialized` layout, which means that you can write its
+/// content from the CPU before using it on the GPU. The first command buffer that uses the image
+/// transitions it to the `General` layout, which it then stays in.
+#[derive(Debug)]
+pub struct LinearImage<F, A = Arc<StdMemoryPool>> where A: MemoryPool {
+    // Inner implementation.
+    image: UnsafeImage,
+
+    // We maintain a view of the whole image.
+    view: UnsafeImageView,
+
+    // Memory used to back the image. Always host-visible.
+    memory: A::Alloc,
+
+    // Number of bytes of `memory` that belong to the image.
+    size: usize,
+
+    // Layout of the image in memory. It never changes, so we query it only once.
+    layout: LinearLayout,
+
+    // Format.
+    format: F,
+
+    // Lock for CPU accesses.
+    access: RwLock<()>,
+
+    // Number of times this image is locked on the GPU side.
+    gpu_lock: AtomicUsize,
+
+    // True if a command buffer that transitions the image out of the `Preinitialized` layout has
+    // been submitted.
+    initialized: AtomicBool,
+}
+
+impl<F> LinearImage<F> {
+    /// Creates a new image with the given dimensions, format and usage.
+    ///
+    /// Returns `FormatNotSupported` or `UnsupportedUsage` if the format, or the format combined
+    /// with the usage, doesn't support linear tiling. Depth and stencil formats are never
+    /// supported.
+    pub fn new(device: Arc<Device>, dimensions: [u32; 2], format: F, usage: ImageUsage)
+               -> Result<Arc<LinearImage<F>>, ImageCreationError>
+        where F: FormatDesc
+    {
+        match format.format().ty() {
+            FormatTy::Float | FormatTy::Uint | FormatTy::Sint => (),
+            _ => return Err(ImageCreationError::FormatNotSupported),
+        };
+
+        let dims = Dimensions::Dim2d { width: dimensions[0], height: dimensions[1] };
+
+        let (image, mem_reqs) = unsafe {
+            try!(UnsafeImage::new(device.clone(), usage, format.format(),
+                                  dims.to_image_dimensions(), 1, 1,
+                                  Sharing::Exclusive::<Empty<u32>>, true, true))
+        };
+
+        let mem_ty = match device.physical_device().memory_types()
+                                 .filter(|t| (mem_reqs.memory_type_bits & (1 << t.id())) != 0)
+                                 .filter(|t| t.is_host_visible())
+                                 .next()
+        {
+            Some(t) => t,
+            None => return Err(ImageCreationError::NoHostVisibleMemory),
+        };
+
+        let mem = try!(MemoryPool::alloc(&Device::standard_pool(&device), mem_ty,
+                                         mem_reqs.size, mem_reqs.alignment, AllocLayout::Linear));
+        debug_assert!((mem.offset() % mem_reqs.alignment) == 0);
+        debug_assert!(mem.mapped_memory().is_some());
+        unsafe { try!(image.bind_memory(mem.memory(), mem.offset())); }
+
+        // We checked above that the format is a color format, and the image has linear tiling.
+        let layout = unsafe { image.color_linear_layout(0) };
+
+        let view = unsafe {
+            try!(UnsafeImageView::raw(&image, dims.to_view_type(), 0 .. 1, 0 .. 1))
+        };
+
+        Ok(Arc::new(LinearImage {
+            image: image,
+            view: view,
+            memory: mem,
+            size: mem_reqs.size,
+            layout: layout,
+            format: format,
+            access: RwLock::new(()),
+            gpu_lock: AtomicUsize::new(0),
+            initialized: AtomicBool::new(false),
+        }))
+    }
+}
+
+impl<F, A> LinearImage<F, A> where A: MemoryPool {
+    /// Returns the dimensions of the image.
+    #[inline]
+    pub fn dimensions(&self) -> [u32; 2] {
+        let dims = self.image.dimensions();
+        [dims.width(), dims.height()]
+    }
+
+    /// Returns the layout of the image in memory.
+    ///
+    /// The offsets are relative to the start of the slice returned by `read()` and `write()`.
+    #[inline]
+    pub fn layout(&self) -> LinearLayout {
+        self.layout
+    }
+
+    /// Locks the image in order to read its content from the CPU.
+    ///
+    /// The returned slice covers the whole memory of the image, including the padding between
+    /// rows.
+    ///
+    /// Returns `WouldBlock` if the image is being written from the CPU, or if a command buffer
+    /// that uses the image hasn't been destroyed yet.
+    #[inline]
+    pub fn read(&self) -> Result<LinearImageReadLock, TryLockError<RwLockReadGuard<()>>> {
+        let lock = try!(self.access.try_read());
+
+        // Command buffers that use the image only release their lock once they are destroyed,
+        // which can only happen after the GPU has finished executing them.
+        if self.gpu_lock.load(Ordering::SeqCst) != 0 {
+            return Err(TryLockError::WouldBlock);
+        }
+
+        let offset = self.memory.offset();
+        let range = offset .. offset + self.size;
+
+        Ok(LinearImageReadLock {
+            inner: unsafe { self.memory.mapped_memory().unwrap().read_write(range) },
+            lock: lock,
+        })
+    }
+
+    /// Locks the image in order to write its content from the CPU.
+    ///
+    /// The returned slice covers the whole memory of the image, including the padding between
+    /// rows.
+    ///
+    /// Returns `WouldBlock` if the image is being read or written from the CPU, or if a command
+    /// buffer that uses the image hasn't been destroyed yet.
+    #[inline]
+    pub fn write(&self) -> Result<LinearImageWriteLock, TryLockError<RwLockWriteGuard<()>>> {
+        let lock = try!(self.access.try_write());
+
+        if self.gpu_lock.load(Ordering::SeqCst) != 0 {
+            return Err(TryLockError::WouldBlock);
+        }
+
+        let offset = self.memory.offset();
+        let range = offset .. offset + self.size;
+
+        Ok(LinearImageWriteLock {
+            inner: unsafe { self.memory.mapped_memory().unwrap().read_write(range) },
+            lock: lock,
+        })
+    }
+}
+
+/// Object that can be used to read the content of a `LinearImage`.
+pub struct LinearImageReadLock<'a> {
+    inner: MemCpuAccess<'a, [u8]>,
+    lock: RwLockReadGuard<'a, ()>,
+}
+
+impl<'a> Deref for LinearImageReadLock<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.inner.deref()
+    }
+}
+
+/// Object that can be used to read or write the content of a `LinearImage`.
+pub struct LinearImageWriteLock<'a> {
+    inner: MemCpuAccess<'a, [u8]>,
+    lock: RwLockWriteGuard<'a, ()>,
+}
+
+impl<'a> Deref for LinearImageWriteLock<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.inner.deref()
+    }
+}
+
+impl<'a> DerefMut for LinearImageWriteLock<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.inner.deref_mut()
+    }
+}
+
+unsafe impl<F, A> DeviceOwned for LinearImage<F, A> where A: MemoryPool {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+unsafe impl<F, A> Image for Arc<LinearImage<F, A>>
+    where F: 'static + Send + Sync, A: MemoryPool
+{
+    type Access = LinearImageAccess<F, A>;
+
+    #[inline]
+    fn access(self) -> LinearImageAccess<F, A> {
+        LinearImageAccess::new(self)
+    }
+
+    #[inline]
+    fn format(&self) -> Format {
+        self.image.format()
+    }
+
+    #[inline]
+    fn samples(&self) -> u32 {
+        self.image.samples()
+    }
+
+    #[inline]
+    fn dimensions(&self) -> ImageDimensions {
+        self.image.dimensions()
+    }
+}
+
+unsafe impl<F, A> ImageView for Arc<LinearImage<F, A>>
+    where F: 'static + Send + Sync, A: MemoryPool
+{
+    type Access = LinearImageAccess<F, A>;
+
+    #[inline]
+    fn access(self) -> LinearImageAccess<F, A> {
+        LinearImageAccess::new(self)
+    }
+}
+
+/// GPU access to a linear image.
+///
+/// The image can't be accessed from the CPU as long as a command buffer that uses this object
+/// exists.
+pub struct LinearImageAccess<F, A = Arc<StdMemoryPool>> where A: MemoryPool {
+    img: Arc<LinearImage<F, A>>,
+    // Layout the image is expected to be in when a command buffer that uses this object starts.
+    // Either `Preinitialized` or `General`.
+    initial_layout: ImageLayout,
+    // Number of GPU locks held by this object on the image.
+    locks: AtomicUsize,
+}
+
+impl<F, A> LinearImageAccess<F, A> where A: MemoryPool {
+    #[inline]
+    fn new(img: Arc<LinearImage<F, A>>) -> LinearImageAccess<F, A> {
+        let initial_layout = if img.initialized.load(Ordering::SeqCst) {
+            ImageLayout::General
+        } else {
+            ImageLayout::Preinitialized
+        };
+
+        LinearImageAccess {
+            img: img,
+            initial_layout: initial_layout,
+            locks: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<F, A> Clone for LinearImageAccess<F, A> where A: MemoryPool {
+    #[inline]
+    fn clone(&self) -> LinearImageAccess<F, A> {
+        // The locks belong to the original object and are released when it is destroyed.
+        LinearImageAccess {
+            img: self.img.clone(),
+            initial_layout: self.initial_layout,
+            locks: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<F, A> Drop for LinearImageAccess<F, A> where A: MemoryPool {
+    fn drop(&mut self) {
+        let locks = *self.locks.get_mut();
+        if locks != 0 {
+            let prev_val = self.img.gpu_lock.fetch_sub(locks, Ordering::SeqCst);
+            debug_assert!(prev_val >= locks);
+        }
+    }
+}
+
+unsafe impl<F, A> ImageAccess for LinearImageAccess<F, A>
+    where F: 'static + Send + Sync, A: MemoryPool
+{
+    #[inline]
+    fn inner(&self) -> &UnsafeImage {
+        &self.img.image
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        self.initial_layout
+    }
+
+    // The `General` layout is the only one that allows accessing the image from the CPU and from
+    // the GPU without any transition.
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn current_layout(&self, _: u32, _: u32) -> TrackedLayout {
+        if self.img.initialized.load(Ordering::SeqCst) {
+            TrackedLayout::Known(ImageLayout::General)
+        } else {
+            TrackedLayout::Known(ImageLayout::Preinitialized)
+        }
+    }
+
+    #[inline]
+    fn conflict_key(&self, _: u32, _: u32, _: u32, _: u32) -> u64 {
+        self.img.image.key()
+    }
+
+    fn try_gpu_lock(&self, _: bool, _: &Queue) -> Result<(), AccessError> {
+        // Holding the CPU lock while locking the image on the GPU side prevents `read()` and
+        // `write()` from succeeding in-between.
+        let _cpu_lock = match self.img.access.try_write() {
+            Ok(lock) => lock,
+            Err(_) => return Err(AccessError::AlreadyInUse),
+        };
+
+        if self.img.gpu_lock.compare_and_swap(0, 1, Ordering::SeqCst) != 0 {
+            return Err(AccessError::AlreadyInUse);
+        }
+
+        let initialized = self.img.initialized.load(Ordering::SeqCst);
+        match (self.initial_layout, initialized) {
+            (ImageLayout::Preinitialized, false) => {
+                self.img.initialized.store(true, Ordering::SeqCst);
+            },
+            (ImageLayout::General, true) => (),
+            (requested, false) => {
+                self.img.gpu_lock.fetch_sub(1, Ordering::SeqCst);
+                return Err(AccessError::ImageNotInitialized { requested: requested });
+            },
+            (requested, true) => {
+                self.img.gpu_lock.fetch_sub(1, Ordering::SeqCst);
+                return Err(AccessError::UnexpectedImageLayout {
+                    allowed: ImageLayout::General,
+                    requested: requested,
+                });
+            },
+        }
+
+        self.locks.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        let val = self.img.gpu_lock.fetch_add(1, Ordering::SeqCst);
+        debug_assert!(val >= 1);
+        self.locks.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+unsafe impl<F, A> ImageClearValue<F::ClearValue> for LinearImageAccess<F, A>
+    where F: FormatDesc + 'static + Send + Sync, A: MemoryPool
+{
+    #[inline]
+    fn decode(&self, value: F::ClearValue) -> Option<ClearValue> {
+        Some(self.img.format.decode_clear_value(value))
+    }
+}
+
+unsafe impl<P, F, A> ImageContent<P> for LinearImageAccess<F, A>
+    where F: 'static + Send + Sync, A: MemoryPool
+{
+    #[inline]
+    fn matches_format(&self) -> bool {
+        self.img.image.format().size() == Some(mem::size_of::<P>())
+    }
+}
+
+unsafe impl<F, A> ImageViewAccess for LinearImageAccess<F, A>
+    where F: 'static + Send + Sync, A: MemoryPool
+{
+    #[inline]
+    fn parent(&self) -> &ImageAccess {
+        self
+    }
+
+    #[inline]
+    fn dimensions(&self) -> Dimensions {
+        let dims = self.img.image.dimensions();
+        Dimensions::Dim2d { width: dims.width(), height: dims.height() }
+    }
+
+    #[inline]
+    fn inner(&self) -> &UnsafeImageView {
+        &self.img.view
+    }
+
+    #[inline]
+    fn descriptor_set_storage_image_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_set_combined_image_sampler_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_set_sampled_image_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_set_input_attachment_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn identity_swizzle(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinearImage;
+    use format::Format;
+    use image::ImageLayout;
+    use image::ImageTiling;
+    use image::ImageUsage;
+    use image::TrackedLayout;
+    use image::sys::ImageCreationError;
+    use image::traits::Image;
+    use image::traits::ImageAccess;
+    use image::traits::ImageContent;
+    use sync::AccessError;
+
+    macro_rules! linear_image {
+        ($device:expr) => ({
+            let usage = ImageUsage { transfer_dest: true, .. ImageUsage::none() };
+            match LinearImage::new($device, [16, 16], Format::R8G8B8A8Unorm, usage) {
+                Ok(img) => img,
+                Err(ImageCreationError::FormatNotSupported) => return,
+                Err(ImageCreationError::UnsupportedUsage) => return,
+                Err(ImageCreationError::NoHostVisibleMemory) => return,
+                Err(err) => panic!("{:?}", err),
+            }
+        })
+    }
+
+    #[test]
+    fn create() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage { transfer_dest: true, .. ImageUsage::none() };
+        let img = match LinearImage::new(device, [16, 16], Format::R8G8B8A8Unorm, usage) {
+            Ok(img) => img,
+            Err(ImageCreationError::FormatNotSupported) => return,
+            Err(ImageCreationError::UnsupportedUsage) => return,
+            Err(ImageCreationError::NoHostVisibleMemory) => return,
+            Err(err) => panic!("{:?}", err),
+        };
+
+        assert_eq!(img.clone().access().inner().tiling(), ImageTiling::Linear);
+        assert!(img.layout().row_pitch >= 16 * 4);
+        assert!(img.read().unwrap().len() >= img.layout().offset + img.layout().size);
+    }
+
+    #[test]
+    fn depth_not_supported() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match LinearImage::new(device, [16, 16], Format::D16Unorm, ImageUsage::none()) {
+            Err(ImageCreationError::FormatNotSupported) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn matches_format() {
+        let (device, _) = gfx_dev_and_queue!();
+        let img = linear_image!(device);
+
+        let access = img.access();
+        assert!(ImageContent::<[u8; 4]>::matches_format(&access));
+        assert!(!ImageContent::<u8>::matches_format(&access));
+    }
+
+    #[test]
+    fn cpu_lock_prevents_gpu_lock() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let img = linear_image!(device);
+
+        let access = img.clone().access();
+        let _write = img.write().unwrap();
+        assert_eq!(access.try_gpu_lock(true, &queue), Err(AccessError::AlreadyInUse));
+    }
+
+    #[test]
+    fn gpu_lock_prevents_cpu_lock() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let img = linear_image!(device);
+
+        let access = img.clone().access();
+        access.try_gpu_lock(true, &queue).unwrap();
+        assert!(img.read().is_err());
+        assert!(img.write().is_err());
+
+        drop(access);
+        assert!(img.write().is_ok());
+    }
+
+    #[test]
+    fn preinitialized_layout() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let img = linear_image!(device);
+
+        let first = img.clone().access();
+        let outdated = img.clone().access();
+        assert_eq!(first.initial_layout_requirement(), ImageLayout::Preinitialized);
+        assert_eq!(first.current_layout(0, 0), TrackedLayout::Known(ImageLayout::Preinitialized));
+
+        first.try_gpu_lock(true, &queue).unwrap();
+        drop(first);
+
+        let second = img.clone().access();
+        assert_eq!(second.initial_layout_requirement(), ImageLayout::General);
+        assert_eq!(second.current_layout(0, 0), TrackedLayout::Known(ImageLayout::General));
+        assert!(second.try_gpu_lock(true, &queue).is_ok());
+        drop(second);
+
+        // Still expects the image to be `Preinitialized`.
+        match outdated.try_gpu_lock(true, &queue) {
+            Err(AccessError::UnexpectedImageLayout { .. }) => (),
+            _ => panic!()
+        };
+    }
+}
@@ -41,17 +41,43 @@
 //! - An `ImmutableImage` stores data which never need be changed after the initial upload,
 //!   like a texture.
 //!
+//! The content of an image can be read from the CPU with the `read_back` function. For rendering
+//! without a window, `OffscreenFrame` bundles an `AttachmentImage` with a way to read it back.
+//! A `LinearImage` uses linear tiling and host-visible memory, so that its content can be
+//! accessed directly from the CPU without any copy.
+//!
 //! # Low-level informations
 //!
 //! To be written.
 //!
 
+use std::cmp;
+
+use vk;
+
 pub use self::attachment::AttachmentImage;
 pub use self::immutable::ImmutableImage;
+pub use self::immutable::ImmutableImageFromImageError;
 pub use self::layout::ImageLayout;
+pub use self::layout::TrackedLayout;
+pub use self::linear::LinearImage;
+pub use self::linear::LinearImageAccess;
+pub use self::linear::LinearImageReadLock;
+pub use self::linear::LinearImageWriteLock;
+pub use self::offscreen::OffscreenFrame;
+pub use self::properties::ImageAspect;
+pub use self::properties::ImageCreateFlags;
+pub use self::properties::ImageFormatProperties;
+pub use self::properties::ImageSubresourceLayers;
+pub use self::properties::ImageTiling;
+pub use self::properties::ImageType;
+pub use self::readback::read_back;
+pub use self::readback::ImageReadBack;
+pub use self::readback::ImageReadBackError;
 pub use self::storage::StorageImage;
 pub use self::swapchain::SwapchainImage;
 pub use self::sys::ImageCreationError;
+pub use self::sys::SubresourceLayoutError;
 pub use self::traits::ImageAccess;
 pub use self::traits::ImageViewAccess;
 pub use self::traits::Image;
@@ -61,6 +87,10 @@ pub use self::usage::ImageUsage;
 pub mod attachment;     // TODO: make private
 pub mod immutable;      // TODO: make private
 mod layout;
+mod linear;
+mod offscreen;
+mod properties;
+pub mod readback;
 mod storage;
 pub mod swapchain;      // TODO: make private
 pub mod sys;
@@ -79,11 +109,19 @@ pub enum MipmapsCount {
     /// implementation may report that it supports a greater value.
     Log2,
 
+    /// Allocates the full chain of mipmaps, down to a level whose dimensions are all 1. The
+    /// number of mipmaps is `floor(log2(max(width, height, depth))) + 1`, which is the maximum
+    /// allowed by the Vulkan specs. See `ImageDimensions::max_mipmaps`.
+    ///
+    /// Creating the image may still fail if the implementation supports fewer mipmaps for the
+    /// given format.
+    Max,
+
     /// Allocate one mipmap (ie. just the main level). Always supported.
     One,
 
-    /// Allocate the given number of mipmaps. May result in an error if the value is out of range
-    /// of what the implementation supports.
+    /// Allocate the given number of mipmaps. Results in an error if the value is 0, greater than
+    /// what `Max` would allocate, or out of range of what the implementation supports.
     Specific(u32),
 }
 
@@ -141,6 +179,35 @@ impl Default for ComponentSwizzle {
     }
 }
 
+#[doc(hidden)]
+impl Into<vk::ComponentMapping> for Swizzle {
+    #[inline]
+    fn into(self) -> vk::ComponentMapping {
+        vk::ComponentMapping {
+            r: self.r.into(),
+            g: self.g.into(),
+            b: self.b.into(),
+            a: self.a.into(),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl Into<vk::ComponentSwizzle> for ComponentSwizzle {
+    #[inline]
+    fn into(self) -> vk::ComponentSwizzle {
+        match self {
+            ComponentSwizzle::Identity => vk::COMPONENT_SWIZZLE_IDENTITY,
+            ComponentSwizzle::Zero => vk::COMPONENT_SWIZZLE_ZERO,
+            ComponentSwizzle::One => vk::COMPONENT_SWIZZLE_ONE,
+            ComponentSwizzle::Red => vk::COMPONENT_SWIZZLE_R,
+            ComponentSwizzle::Green => vk::COMPONENT_SWIZZLE_G,
+            ComponentSwizzle::Blue => vk::COMPONENT_SWIZZLE_B,
+            ComponentSwizzle::Alpha => vk::COMPONENT_SWIZZLE_A,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Dimensions {
     Dim1d { width: u32 },
@@ -339,4 +406,19 @@ impl ImageDimensions {
             ImageDimensions::Dim3d { .. }  => 1,
         }
     }
+
+    /// Returns the number of mipmaps of the full chain of mipmaps of an image with these
+    /// dimensions, in other words `floor(log2(max(width, height, depth))) + 1`.
+    ///
+    /// This is the maximum number of mipmaps that an image with these dimensions can have.
+    /// Returns 0 if one of the dimensions is 0.
+    #[inline]
+    pub fn max_mipmaps(&self) -> u32 {
+        if self.width() == 0 || self.height() == 0 || self.depth() == 0 {
+            return 0;
+        }
+
+        let largest_dim = cmp::max(self.width(), cmp::max(self.height(), self.depth()));
+        32 - largest_dim.leading_zeros()
+    }
 }
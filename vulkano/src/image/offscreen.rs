@@ -0,0 +1,113 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+
+use command_buffer::CommandBufferExecFuture;
+use device::Device;
+use device::DeviceOwned;
+use device::Queue;
+use format::FormatDesc;
+use image::AttachmentImage;
+use image::ImageUsage;
+use image::readback::read_back;
+use image::readback::ImageReadBack;
+use image::readback::ImageReadBackError;
+use image::readback::ReadBackCommandBuffer;
+use image::sys::ImageCreationError;
+use sync::GpuFuture;
+
+/// Color image to render to when there is no window, plus a way to read its content back.
+///
+/// This is the equivalent of a swapchain image for headless rendering. It doesn't require a
+/// `Surface` nor the `khr_swapchain` device extension, which makes it usable on servers and in
+/// continuous integration.
+///
+/// Use `image()` as the attachment of a framebuffer, submit your draw commands, then call
+/// `read()` with the future of the submission to get the content of the image.
+#[derive(Debug)]
+pub struct OffscreenFrame<F> {
+    image: Arc<AttachmentImage<F>>,
+    queue: Arc<Queue>,
+}
+
+impl<F> OffscreenFrame<F>
+    where F: FormatDesc + Send + Sync + 'static
+{
+    /// Builds a new `OffscreenFrame` whose content will be read back through `queue`.
+    ///
+    /// The image can be used as a color attachment and as the source of a transfer.
+    #[inline]
+    pub fn new(queue: Arc<Queue>, dimensions: [u32; 2], format: F)
+               -> Result<OffscreenFrame<F>, ImageCreationError>
+    {
+        let usage = ImageUsage {
+            transfer_source: true,
+            .. ImageUsage::none()
+        };
+
+        let image = try!(AttachmentImage::with_usage(queue.device().clone(), dimensions, format,
+                                                     usage));
+
+        Ok(OffscreenFrame {
+            image: image,
+            queue: queue,
+        })
+    }
+
+    /// Returns the image to render to.
+    #[inline]
+    pub fn image(&self) -> &Arc<AttachmentImage<F>> {
+        &self.image
+    }
+
+    /// Returns the queue that is used to read back the image.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns the dimensions of the image.
+    #[inline]
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.image.dimensions()
+    }
+
+    /// Copies the whole image into a buffer after `future`, and returns the future of the copy.
+    ///
+    /// This is the non-blocking version of `read()`. See the documentation of `read_back`.
+    #[inline]
+    pub fn read_back_after<Fut>(&self, future: Fut)
+                                -> Result<(CommandBufferExecFuture<Fut, ReadBackCommandBuffer>,
+                                           ImageReadBack), ImageReadBackError>
+        where Fut: GpuFuture
+    {
+        read_back(future, self.image.clone(), self.queue.clone(), [0, 0], self.dimensions())
+    }
+
+    /// Copies the whole image after `future`, blocks until the copy is finished, and returns the
+    /// content of the image.
+    ///
+    /// Texels are tightly packed, and rows go from the top of the image to the bottom. BGRA
+    /// formats are swizzled to RGBA.
+    pub fn read<Fut>(&self, future: Fut) -> Result<Vec<u8>, ImageReadBackError>
+        where Fut: GpuFuture
+    {
+        let (future, readback) = try!(self.read_back_after(future));
+        try!(try!(future.then_signal_fence_and_flush()).wait(None));
+        readback.to_vec(true)
+    }
+}
+
+unsafe impl<F> DeviceOwned for OffscreenFrame<F> {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.queue.device()
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use vk;
+
+/// Number of dimensions of an image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ImageType {
+    Dim1d = vk::IMAGE_TYPE_1D,
+    Dim2d = vk::IMAGE_TYPE_2D,
+    Dim3d = vk::IMAGE_TYPE_3D,
+}
+
+/// Layout of the texels of an image in memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ImageTiling {
+    /// The texels are laid out in an implementation-specific way that is optimal for the GPU.
+    Optimal = vk::IMAGE_TILING_OPTIMAL,
+
+    /// The texels are laid out row by row, which lets the CPU access them directly when the
+    /// memory is mapped. Much fewer formats and usages are supported than with `Optimal`.
+    Linear = vk::IMAGE_TILING_LINEAR,
+}
+
+/// Component of an image.
+///
+/// Color images only have a color aspect. Depth-stencil images have both a depth and a stencil
+/// aspect.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ImageAspect {
+    Color = vk::IMAGE_ASPECT_COLOR_BIT,
+    Depth = vk::IMAGE_ASPECT_DEPTH_BIT,
+    Stencil = vk::IMAGE_ASPECT_STENCIL_BIT,
+}
+
+/// Range of array layers within one mipmap level of an image.
+///
+/// Used to describe the part of an image that a transfer command reads or writes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ImageSubresourceLayers {
+    /// The mipmap level.
+    pub mip_level: u32,
+    /// The first array layer.
+    pub first_layer: u32,
+    /// The number of array layers. Must be at least 1.
+    pub num_layers: u32,
+}
+
+impl ImageSubresourceLayers {
+    /// Builds an `ImageSubresourceLayers` that covers only the first array layer of the given
+    /// mipmap level.
+    #[inline]
+    pub fn mip_level(mip_level: u32) -> ImageSubresourceLayers {
+        ImageSubresourceLayers {
+            mip_level: mip_level,
+            first_layer: 0,
+            num_layers: 1,
+        }
+    }
+}
+
+/// Additional properties of an image that are chosen at creation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ImageCreateFlags {
+    /// The image will be backed by sparse memory binding.
+    pub sparse_binding: bool,
+    /// The image can be partially backed by memory. Requires `sparse_binding`.
+    pub sparse_residency: bool,
+    /// The memory of the image can alias the memory of another resource. Requires
+    /// `sparse_binding`.
+    pub sparse_aliased: bool,
+    /// Views of the image can have a different format than the image.
+    pub mutable_format: bool,
+    /// Cubemap views can be created from the image.
+    pub cube_compatible: bool,
+}
+
+impl ImageCreateFlags {
+    /// Builds an `ImageCreateFlags` with all values set to false.
+    #[inline]
+    pub fn none() -> ImageCreateFlags {
+        ImageCreateFlags {
+            sparse_binding: false,
+            sparse_residency: false,
+            sparse_aliased: false,
+            mutable_format: false,
+            cube_compatible: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn to_bits(&self) -> vk::ImageCreateFlags {
+        let mut result = 0;
+        if self.sparse_binding { result |= vk::IMAGE_CREATE_SPARSE_BINDING_BIT; }
+        if self.sparse_residency { result |= vk::IMAGE_CREATE_SPARSE_RESIDENCY_BIT; }
+        if self.sparse_aliased { result |= vk::IMAGE_CREATE_SPARSE_ALIASED_BIT; }
+        if self.mutable_format { result |= vk::IMAGE_CREATE_MUTABLE_FORMAT_BIT; }
+        if self.cube_compatible { result |= vk::IMAGE_CREATE_CUBE_COMPATIBLE_BIT; }
+        result
+    }
+}
+
+/// Limits of the images that can be created with a certain combination of format, type, tiling,
+/// usage and flags.
+///
+/// Obtained by calling `PhysicalDevice::image_format_properties`. These values can be higher or
+/// lower than the general limits returned by `PhysicalDevice::limits()`, and take precedence
+/// over them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageFormatProperties {
+    /// Maximum width, height and depth of the image.
+    pub max_extent: [u32; 3],
+    /// Maximum number of mipmap levels.
+    pub max_mip_levels: u32,
+    /// Maximum number of array layers.
+    pub max_array_layers: u32,
+    /// Bitmask of the supported numbers of samples. Bit `n` is set if `2^n` samples are
+    /// supported.
+    pub sample_counts: u32,
+    /// Maximum total size of the image in bytes. Guaranteed to be at least 2^31.
+    pub max_resource_size: usize,
+}
+
+impl ImageFormatProperties {
+    /// Returns true if an image can be created with the given number of samples.
+    #[inline]
+    pub fn supports_samples(&self, samples: u32) -> bool {
+        samples.is_power_of_two() && (self.sample_counts & samples) != 0
+    }
+}
+
+#[doc(hidden)]
+impl From<vk::ImageFormatProperties> for ImageFormatProperties {
+    #[inline]
+    fn from(props: vk::ImageFormatProperties) -> ImageFormatProperties {
+        ImageFormatProperties {
+            max_extent: [props.maxExtent.width, props.maxExtent.height, props.maxExtent.depth],
+            max_mip_levels: props.maxMipLevels,
+            max_array_layers: props.maxArrayLayers,
+            sample_counts: props.sampleCounts,
+            max_resource_size: props.maxResourceSize as usize,
+        }
+    }
+}
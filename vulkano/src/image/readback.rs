@@ -0,0 +1,260 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Reading back the content of an image from the CPU.
+//!
+//! The `read_back` function copies a region of an image into a newly-allocated
+//! `CpuAccessibleBuffer<[u8]>`, which is the usual way to take a screenshot of a swapchain image.
+//!
+//! The image must have been created with the `transfer_source` usage. For swapchain images, this
+//! means that you must pass an `ImageUsage` with `transfer_source` set to `true` when creating
+//! the swapchain.
+//!
+//! The data is copied as-is without any conversion. Swapchain images often have a
+//! `B8G8R8A8` format, in which case the red and blue components must be swapped before saving
+//! the data in a file whose format expects RGBA. The `ImageReadBack::to_vec` method can do that
+//! for you.
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use buffer::BufferUsage;
+use buffer::CpuAccessibleBuffer;
+use command_buffer::AutoCommandBufferBuilder;
+use command_buffer::CommandBufferBuild;
+use command_buffer::CommandBufferBuilder;
+use command_buffer::CommandBufferBuilderError;
+use command_buffer::CommandBufferExecError;
+use command_buffer::CommandBufferExecFuture;
+use command_buffer::cb::AddCommand;
+use command_buffer::commands_raw::CmdCopyImageToBuffer;
+use command_buffer::commands_raw::CmdCopyImageToBufferError;
+use device::Queue;
+use format::Format;
+use image::traits::Image;
+use sync::FlushError;
+use sync::GpuFuture;
+
+use OomError;
+
+/// Type of the command buffer that is executed by `read_back`.
+pub type ReadBackCommandBuffer = <AutoCommandBufferBuilder as CommandBufferBuild>::Out;
+
+/// Copies a region of the first layer and first mipmap level of an image into a newly-allocated
+/// buffer, after `future`.
+///
+/// Returns the future that represents the end of the copy, and an `ImageReadBack` object that
+/// describes the content of the buffer. You must wait for the future to be finished (for example
+/// with `then_signal_fence_and_flush()` followed with `wait()`) before reading the buffer.
+///
+/// Since swapchain images can only be accessed after they have been acquired, `future` should
+/// depend on the future returned by `acquire_next_image` when reading a swapchain image.
+///
+/// The image is transitioned to the `TransferSrcOptimal` layout for the duration of the copy,
+/// then transitioned back to its original layout.
+///
+/// # Panic
+///
+/// - Panics if the image was not created with the same device as `queue`.
+///
+pub fn read_back<F, I>(future: F, image: I, queue: Arc<Queue>, offset: [u32; 2],
+                       dimensions: [u32; 2])
+                       -> Result<(CommandBufferExecFuture<F, ReadBackCommandBuffer>, ImageReadBack),
+                                 ImageReadBackError>
+    where F: GpuFuture,
+          I: Image,
+          AutoCommandBufferBuilder: AddCommand<CmdCopyImageToBuffer<I::Access, Arc<CpuAccessibleBuffer<[u8]>>>,
+                                               Out = AutoCommandBufferBuilder>
+{
+    let format = image.format();
+    let texel_size = match format.size() {
+        Some(s) => s,
+        None => return Err(ImageReadBackError::UnsupportedFormat),
+    };
+
+    let row_stride = texel_size * dimensions[0] as usize;
+    let buffer_size = row_stride * dimensions[1] as usize;
+
+    let buffer = unsafe {
+        // The content of the buffer is entirely overwritten by the copy.
+        CpuAccessibleBuffer::uninitialized_array(queue.device().clone(), buffer_size,
+                                                 BufferUsage::transfer_dest(),
+                                                 Some(queue.family()))?
+    };
+
+    let builder = AutoCommandBufferBuilder::new(queue.device().clone(), queue.family())?
+        .copy_image_to_buffer_dimensions(image, buffer.clone(), [offset[0], offset[1], 0],
+                                         [dimensions[0], dimensions[1], 1], 0, 1, 0)?;
+    let command_buffer = CommandBufferBuild::build(builder)?;
+
+    let future = future.then_execute(queue, command_buffer)?;
+
+    Ok((future, ImageReadBack {
+        buffer: buffer,
+        format: format,
+        dimensions: dimensions,
+        row_stride: row_stride,
+    }))
+}
+
+/// Content of an image that has been copied to a buffer by `read_back`.
+pub struct ImageReadBack {
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+    format: Format,
+    dimensions: [u32; 2],
+    row_stride: usize,
+}
+
+impl ImageReadBack {
+    /// Returns the buffer that contains the data. Rows of texels are tightly packed.
+    #[inline]
+    pub fn buffer(&self) -> &Arc<CpuAccessibleBuffer<[u8]>> {
+        &self.buffer
+    }
+
+    /// Returns the format of the data, which is the format of the image.
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the width and height of the region that was copied.
+    #[inline]
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    /// Returns the number of bytes between the start of a row of texels and the start of the
+    /// next one.
+    #[inline]
+    pub fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    /// Returns true if the format stores the blue component before the red component.
+    #[inline]
+    pub fn is_bgra(&self) -> bool {
+        match self.format {
+            Format::B8G8R8A8Unorm | Format::B8G8R8A8Snorm | Format::B8G8R8A8Uscaled |
+            Format::B8G8R8A8Sscaled | Format::B8G8R8A8Uint | Format::B8G8R8A8Sint |
+            Format::B8G8R8A8Srgb => true,
+            _ => false,
+        }
+    }
+
+    /// Copies the content of the buffer in a `Vec`.
+    ///
+    /// If `swizzle_to_rgba` is true and `is_bgra()` returns true, the red and blue components of
+    /// each texel are swapped so that the data is in RGBA order.
+    ///
+    /// You must only call this function after the copy is finished on the GPU.
+    pub fn to_vec(&self, swizzle_to_rgba: bool) -> Result<Vec<u8>, ImageReadBackError> {
+        let content = match self.buffer.read() {
+            Ok(c) => c,
+            Err(_) => return Err(ImageReadBackError::BufferLocked),
+        };
+
+        let mut data = content.to_vec();
+
+        if swizzle_to_rgba && self.is_bgra() {
+            for texel in data.chunks_mut(4) {
+                texel.swap(0, 2);
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Error that can happen when reading back an image.
+#[derive(Debug, Clone)]
+pub enum ImageReadBackError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// Error while adding the copy command to the command buffer.
+    CopyError(CommandBufferBuilderError<CmdCopyImageToBufferError>),
+
+    /// Error while executing the command buffer.
+    ExecError(CommandBufferExecError),
+
+    /// Error while submitting the copy or waiting for it to finish.
+    FlushError(FlushError),
+
+    /// The format of the image doesn't have a fixed size per texel (eg. compressed formats).
+    UnsupportedFormat,
+
+    /// The buffer is locked by the CPU or the GPU and can't be read.
+    BufferLocked,
+}
+
+impl error::Error for ImageReadBackError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ImageReadBackError::OomError(_) => "not enough memory",
+            ImageReadBackError::CopyError(_) => "error while adding the copy command",
+            ImageReadBackError::ExecError(_) => "error while executing the command buffer",
+            ImageReadBackError::FlushError(_) => "error while submitting the copy",
+            ImageReadBackError::UnsupportedFormat => {
+                "the format of the image doesn't have a fixed size per texel"
+            },
+            ImageReadBackError::BufferLocked => {
+                "the buffer is locked and can't be read"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ImageReadBackError::OomError(ref err) => Some(err),
+            ImageReadBackError::CopyError(ref err) => Some(err),
+            ImageReadBackError::ExecError(ref err) => Some(err),
+            ImageReadBackError::FlushError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ImageReadBackError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ImageReadBackError {
+    #[inline]
+    fn from(err: OomError) -> ImageReadBackError {
+        ImageReadBackError::OomError(err)
+    }
+}
+
+impl From<CommandBufferBuilderError<CmdCopyImageToBufferError>> for ImageReadBackError {
+    #[inline]
+    fn from(err: CommandBufferBuilderError<CmdCopyImageToBufferError>) -> ImageReadBackError {
+        ImageReadBackError::CopyError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for ImageReadBackError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> ImageReadBackError {
+        ImageReadBackError::ExecError(err)
+    }
+}
+
+impl From<FlushError> for ImageReadBackError {
+    #[inline]
+    fn from(err: FlushError) -> ImageReadBackError {
+        ImageReadBackError::FlushError(err)
+    }
+}
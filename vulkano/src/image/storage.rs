@@ -9,6 +9,7 @@
 
 use std::iter::Empty;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use smallvec::SmallVec;
@@ -24,6 +25,8 @@ use image::ImageDimensions;
 use image::sys::ImageCreationError;
 use image::ImageLayout;
 use image::ImageUsage;
+use image::MipmapsCount;
+use image::TrackedLayout;
 use image::sys::UnsafeImage;
 use image::sys::UnsafeImageView;
 use image::traits::ImageAccess;
@@ -32,7 +35,9 @@ use image::traits::ImageContent;
 use image::traits::ImageViewAccess;
 use image::traits::Image;
 use image::traits::ImageView;
+use instance::MemoryType;
 use instance::QueueFamily;
+use memory::MemoryPreference;
 use memory::pool::AllocLayout;
 use memory::pool::MemoryPool;
 use memory::pool::MemoryPoolAlloc;
@@ -47,7 +52,8 @@ pub struct StorageImage<F, A = Arc<StdMemoryPool>> where A: MemoryPool {
     // Inner implementation.
     image: UnsafeImage,
 
-    // We maintain a view of the whole image.
+    // We maintain a view of the first mipmap level of the whole image. A view that is used as a
+    // storage image descriptor must contain exactly one mipmap level.
     view: UnsafeImageView,
 
     // Memory used to back the image.
@@ -64,14 +70,65 @@ pub struct StorageImage<F, A = Arc<StdMemoryPool>> where A: MemoryPool {
 
     // Number of times this image is locked on the GPU side.
     gpu_lock: AtomicUsize,
+
+    // True once a command buffer that uses the image has been submitted. Before that, the image
+    // is still in the `Undefined` layout.
+    initialized: AtomicBool,
 }
 
 impl<F> StorageImage<F> {
     /// Creates a new image with the given dimensions and format.
+    #[inline]
     pub fn new<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F, queue_families: I)
                       -> Result<Arc<StorageImage<F>>, ImageCreationError>
         where F: FormatDesc,
-                 I: IntoIterator<Item = QueueFamily<'a>>
+              I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        StorageImage::with_memory_preference(device, dimensions, format, queue_families,
+                                             MemoryPreference::device_local())
+    }
+
+    /// Same as `new`, but allocates the given number of mipmaps.
+    ///
+    /// Use `MipmapsCount::Max` to allocate the full chain of mipmaps without having to compute
+    /// its length yourself.
+    ///
+    /// The image can still be used as a storage image in a descriptor set, but only the first
+    /// mipmap level is visible through descriptors. The other levels can be accessed with
+    /// transfer commands, for example to generate them with blits.
+    #[inline]
+    pub fn with_mipmaps<'a, I, M>(device: Arc<Device>, dimensions: Dimensions, format: F,
+                                  mipmaps: M, queue_families: I)
+                                  -> Result<Arc<StorageImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>,
+              M: Into<MipmapsCount>
+    {
+        StorageImage::new_impl(device, dimensions, format, mipmaps.into(), queue_families,
+                               MemoryPreference::device_local())
+    }
+
+    /// Same as `new`, but allocates the image from the memory type that best matches
+    /// `preference`.
+    ///
+    /// Returns `NoSuitableMemoryType` if none of the memory types that support the image has the
+    /// required properties.
+    #[inline]
+    pub fn with_memory_preference<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F,
+                                         queue_families: I, preference: MemoryPreference)
+                                         -> Result<Arc<StorageImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        StorageImage::new_impl(device, dimensions, format, MipmapsCount::One, queue_families,
+                               preference)
+    }
+
+    fn new_impl<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F,
+                       mipmaps: MipmapsCount, queue_families: I, preference: MemoryPreference)
+                       -> Result<Arc<StorageImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>
     {
         let is_depth = match format.format().ty() {
             FormatTy::Depth => true,
@@ -103,16 +160,12 @@ impl<F> StorageImage<F> {
             };
 
             try!(UnsafeImage::new(device.clone(), usage, format.format(), dimensions.to_image_dimensions(),
-                                  1, 1, Sharing::Exclusive::<Empty<u32>>, false, false))
+                                  1, mipmaps, Sharing::Exclusive::<Empty<u32>>, false, false))
         };
 
-        let mem_ty = {
-            let device_local = device.physical_device().memory_types()
-                                     .filter(|t| (mem_reqs.memory_type_bits & (1 << t.id())) != 0)
-                                     .filter(|t| t.is_device_local());
-            let any = device.physical_device().memory_types()
-                            .filter(|t| (mem_reqs.memory_type_bits & (1 << t.id())) != 0);
-            device_local.chain(any).next().unwrap()
+        let mem_ty = match preference.choose(device.physical_device(), mem_reqs.memory_type_bits) {
+            Some(t) => t,
+            None => return Err(ImageCreationError::NoSuitableMemoryType),
         };
 
         let mem = try!(MemoryPool::alloc(&Device::standard_pool(&device), mem_ty,
@@ -121,7 +174,7 @@ impl<F> StorageImage<F> {
         unsafe { try!(image.bind_memory(mem.memory(), mem.offset())); }
 
         let view = unsafe {
-            try!(UnsafeImageView::raw(&image, dimensions.to_view_type(), 0 .. image.mipmap_levels(),
+            try!(UnsafeImageView::raw(&image, dimensions.to_view_type(), 0 .. 1,
                                       0 .. image.dimensions().array_layers()))
         };
 
@@ -133,6 +186,7 @@ impl<F> StorageImage<F> {
             format: format,
             queue_families: queue_families,
             gpu_lock: AtomicUsize::new(0),
+            initialized: AtomicBool::new(false),
         }))
     }
 }
@@ -143,6 +197,12 @@ impl<F, A> StorageImage<F, A> where A: MemoryPool {
     pub fn dimensions(&self) -> Dimensions {
         self.dimensions
     }
+
+    /// Returns the memory type that the image was allocated from.
+    #[inline]
+    pub fn memory_type(&self) -> MemoryType {
+        self.memory.memory().memory_type()
+    }
 }
 
 // FIXME: wrong
@@ -200,6 +260,15 @@ unsafe impl<F, A> ImageAccess for StorageImage<F, A> where F: 'static + Send + S
         ImageLayout::General
     }
 
+    #[inline]
+    fn current_layout(&self, _: u32, _: u32) -> TrackedLayout {
+        if self.initialized.load(Ordering::SeqCst) {
+            TrackedLayout::Known(ImageLayout::General)
+        } else {
+            TrackedLayout::Known(ImageLayout::Undefined)
+        }
+    }
+
     #[inline]
     fn conflict_key(&self, _: u32, _: u32, _: u32, _: u32) -> u64 {
         self.image.key()
@@ -209,6 +278,7 @@ unsafe impl<F, A> ImageAccess for StorageImage<F, A> where F: 'static + Send + S
     fn try_gpu_lock(&self, _: bool, _: &Queue) -> Result<(), AccessError> {
         let val = self.gpu_lock.fetch_add(1, Ordering::SeqCst);
         if val == 1 {
+            self.initialized.store(true, Ordering::SeqCst);
             Ok(())
         } else {
             self.gpu_lock.fetch_sub(1, Ordering::SeqCst);
@@ -290,6 +360,10 @@ mod tests {
     use super::StorageImage;
     use format::Format;
     use image::Dimensions;
+    use image::ImageAccess;
+    use image::ImageLayout;
+    use image::MipmapsCount;
+    use image::TrackedLayout;
 
     #[test]
     fn create() {
@@ -297,4 +371,28 @@ mod tests {
         let _img = StorageImage::new(device, Dimensions::Dim2d { width: 32, height: 32 },
                                      Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
     }
+
+    #[test]
+    fn full_mipmaps() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let img = StorageImage::with_mipmaps(device, Dimensions::Dim2d { width: 48, height: 20 },
+                                             Format::R8G8B8A8Unorm, MipmapsCount::Max,
+                                             Some(queue.family())).unwrap();
+        assert_eq!(img.mipmap_levels(), 6);
+    }
+
+    #[test]
+    fn current_layout() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let img = StorageImage::new(device, Dimensions::Dim2d { width: 32, height: 32 },
+                                    Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+
+        // The image hasn't been used by any command buffer yet.
+        assert_eq!(img.current_layout(0, 0), TrackedLayout::Known(ImageLayout::Undefined));
+
+        // The wrapper doesn't change the actual layout.
+        let undefined = unsafe { img.clone().forced_undefined_initial_layout(false) };
+        assert_eq!(undefined.initial_layout_requirement(), ImageLayout::Undefined);
+        assert_eq!(undefined.current_layout(0, 0), TrackedLayout::Known(ImageLayout::Undefined));
+    }
 }
@@ -13,6 +13,7 @@
 //! other image or image view types of this library, and all custom image or image view types
 //! that you create must wrap around the types in this module.
 
+use std::cmp;
 use std::error;
 use std::fmt;
 use std::mem;
@@ -22,14 +23,21 @@ use std::sync::Arc;
 use smallvec::SmallVec;
 
 use device::Device;
+use device::DeviceOwned;
 use format::Format;
 use format::FormatTy;
+use image::ImageAspect;
+use image::ImageCreateFlags;
 use image::ImageDimensions;
+use image::ImageTiling;
+use image::ImageType;
 use image::ImageUsage;
 use image::MipmapsCount;
 use image::ViewType;
 use memory::DeviceMemory;
+use memory::ExternalMemoryHandleType;
 use memory::MemoryRequirements;
+use sampler::SamplerYcbcrConversion;
 use sync::Sharing;
 
 use Error;
@@ -60,10 +68,17 @@ pub struct UnsafeImage {
     dimensions: ImageDimensions,
     samples: u32,
     mipmaps: u32,
+    tiling: ImageTiling,
 
     // Features that are supported for this particular format.
     format_features: vk::FormatFeatureFlagBits,
 
+    // True if the image was created with the `Concurrent` sharing mode.
+    concurrent: bool,
+
+    // The handle type passed to `with_external_memory`, if any.
+    external_handle_type: Option<ExternalMemoryHandleType>,
+
     // `vkDestroyImage` is called only if `needs_destruction` is true.
     needs_destruction: bool,
 }
@@ -91,18 +106,67 @@ impl UnsafeImage {
         };
 
         UnsafeImage::new_impl(device, usage, format, dimensions, num_samples, mipmaps.into(),
-                              sharing, linear_tiling, preinitialized_layout)
+                              sharing, linear_tiling, preinitialized_layout, None)
+    }
+
+    /// Same as `new`, but the image can be bound to memory that is exported or imported as an
+    /// operating system handle of the given type.
+    ///
+    /// The memory must then be allocated with `DeviceMemory::alloc_exportable` or imported with
+    /// `DeviceMemory::import_fd` or similar.
+    ///
+    /// Returns `UnsupportedExternalHandleType` if the physical device can neither export nor
+    /// import such images as this handle type, or if this can't be checked because the
+    /// `khr_external_memory_capabilities` instance extension is not enabled.
+    ///
+    /// # Panic
+    ///
+    /// Same as `new`.
+    ///
+    #[inline]
+    pub unsafe fn with_external_memory<'a, Mi, I>(device: Arc<Device>, usage: ImageUsage,
+                                                  format: Format, dimensions: ImageDimensions,
+                                                  num_samples: u32, mipmaps: Mi,
+                                                  sharing: Sharing<I>, linear_tiling: bool,
+                                                  preinitialized_layout: bool,
+                                                  handle_type: ExternalMemoryHandleType)
+                                                  -> Result<(UnsafeImage, MemoryRequirements),
+                                                            ImageCreationError>
+        where Mi: Into<MipmapsCount>, I: Iterator<Item = u32>
+    {
+        let sharing = match sharing {
+            Sharing::Exclusive => (vk::SHARING_MODE_EXCLUSIVE, SmallVec::<[u32; 8]>::new()),
+            Sharing::Concurrent(ids) => (vk::SHARING_MODE_CONCURRENT, ids.collect()),
+        };
+
+        UnsafeImage::new_impl(device, usage, format, dimensions, num_samples, mipmaps.into(),
+                              sharing, linear_tiling, preinitialized_layout, Some(handle_type))
     }
 
     // Non-templated version to avoid inlining and improve compile times.
     unsafe fn new_impl(device: Arc<Device>, usage: ImageUsage, format: Format,
                        dimensions: ImageDimensions, num_samples: u32, mipmaps: MipmapsCount,
                        (sh_mode, sh_indices): (vk::SharingMode, SmallVec<[u32; 8]>),
-                       linear_tiling: bool, preinitialized_layout: bool)
+                       linear_tiling: bool, preinitialized_layout: bool,
+                       external_handle_type: Option<ExternalMemoryHandleType>)
                        -> Result<(UnsafeImage, MemoryRequirements), ImageCreationError>
     {
         // TODO: doesn't check that the proper features are enabled
 
+        if external_handle_type.is_some() && !device.loaded_extensions().khr_external_memory {
+            return Err(ImageCreationError::ExternalMemoryExtensionNotEnabled);
+        }
+
+        if format.ty() == FormatTy::Ycbcr {
+            if !device.loaded_extensions().khr_sampler_ycbcr_conversion {
+                return Err(ImageCreationError::YcbcrConversionExtensionNotEnabled);
+            }
+
+            if !device.enabled_features().sampler_ycbcr_conversion {
+                return Err(ImageCreationError::YcbcrConversionFeatureNotEnabled);
+            }
+        }
+
         let vk = device.pointers();
         let vk_i = device.instance().pointers();
 
@@ -166,123 +230,10 @@ impl UnsafeImage {
             }
         }
 
-        // This function is going to perform various checks and write to `capabilities_error` in
-        // case of error.
-        //
-        // If `capabilities_error` is not `None` after the checks are finished, the function will
-        // check for additional image capabilities (section 31.4 of the specs).
-        let mut capabilities_error = None;
-
-        // Compute the maximum number of mipmaps.
-        // TODO: only compte if necessary?
-        let max_mipmaps = {
-            let smallest_dim: u32 = match dimensions {
-                ImageDimensions::Dim1d { width, .. } => width,
-                ImageDimensions::Dim2d { width, height, .. } => {
-                    if width < height { width } else { height }
-                },
-                ImageDimensions::Dim3d { width, height, depth } => {
-                    if width < height {
-                        if depth < width { depth } else { width }
-                    } else {
-                        if depth < height { depth } else { height }
-                    }
-                },
-            };
-
-            32 - smallest_dim.leading_zeros()
-        };
-
-        // Compute the number of mipmaps.
-        let mipmaps = match mipmaps.into() {
-            MipmapsCount::Specific(num) => {
-                if num < 1 {
-                    return Err(ImageCreationError::InvalidMipmapsCount {
-                        obtained: num, valid_range: 1 .. max_mipmaps + 1
-                    });
-                } else if num > max_mipmaps {
-                    capabilities_error = Some(ImageCreationError::InvalidMipmapsCount {
-                        obtained: num, valid_range: 1 .. max_mipmaps + 1
-                    });
-                }
-
-                num
-            },
-            MipmapsCount::Log2 => max_mipmaps,
-            MipmapsCount::One => 1,
-        };
-
-        // Checking whether the number of samples is supported.
-        if num_samples == 0 {
+        // Checking whether the number of samples is valid. Whether it is supported is checked
+        // below with the image format properties.
+        if num_samples == 0 || !num_samples.is_power_of_two() {
             return Err(ImageCreationError::UnsupportedSamplesCount { obtained: num_samples });
-
-        } else if !num_samples.is_power_of_two() {
-            return Err(ImageCreationError::UnsupportedSamplesCount { obtained: num_samples });
-
-        } else {
-            let mut supported_samples = 0x7f;       // all bits up to VK_SAMPLE_COUNT_64_BIT
-
-            if usage.sampled {
-                match format.ty() {
-                    FormatTy::Float | FormatTy::Compressed => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .sampled_image_color_sample_counts();
-                    },
-                    FormatTy::Uint | FormatTy::Sint => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .sampled_image_integer_sample_counts();
-                    },
-                    FormatTy::Depth => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .sampled_image_depth_sample_counts();
-                    },
-                    FormatTy::Stencil => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .sampled_image_stencil_sample_counts();
-                    },
-                    FormatTy::DepthStencil => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .sampled_image_depth_sample_counts();
-                        supported_samples &= device.physical_device().limits()
-                                                   .sampled_image_stencil_sample_counts();
-                    },
-                }
-            }
-
-            if usage.storage {
-                supported_samples &= device.physical_device().limits()
-                                           .storage_image_sample_counts();
-            }
-
-            if usage.color_attachment || usage.depth_stencil_attachment || usage.input_attachment ||
-               usage.transient_attachment
-            {
-                match format.ty() {
-                    FormatTy::Float | FormatTy::Compressed | FormatTy::Uint | FormatTy::Sint => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .framebuffer_color_sample_counts();
-                    },
-                    FormatTy::Depth => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .framebuffer_depth_sample_counts();
-                    },
-                    FormatTy::Stencil => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .framebuffer_stencil_sample_counts();
-                    },
-                    FormatTy::DepthStencil => {
-                        supported_samples &= device.physical_device().limits()
-                                                   .framebuffer_depth_sample_counts();
-                        supported_samples &= device.physical_device().limits()
-                                                   .framebuffer_stencil_sample_counts();
-                    },
-                }
-            }
-
-            if (num_samples & supported_samples) == 0 {
-                let err = ImageCreationError::UnsupportedSamplesCount { obtained: num_samples };
-                capabilities_error = Some(err);
-            }
         }
 
         // If the `shaderStorageImageMultisample` feature is not enabled and we have
@@ -300,7 +251,7 @@ impl UnsafeImage {
                     return Err(ImageCreationError::UnsupportedDimensions { dimensions: dimensions });
                 }
                 let extent = vk::Extent3D { width: width, height: 1, depth: 1 };
-                (vk::IMAGE_TYPE_1D, extent, array_layers, 0)
+                (ImageType::Dim1d, extent, array_layers, ImageCreateFlags::none())
             },
             ImageDimensions::Dim2d { width, height, array_layers, cubemap_compatible } => {
                 if width == 0 || height == 0 || array_layers == 0 {
@@ -310,93 +261,104 @@ impl UnsafeImage {
                     return Err(ImageCreationError::UnsupportedDimensions { dimensions: dimensions });
                 }
                 let extent = vk::Extent3D { width: width, height: height, depth: 1 };
-                let flags = if cubemap_compatible { vk::IMAGE_CREATE_CUBE_COMPATIBLE_BIT }
-                            else { 0 };
-                (vk::IMAGE_TYPE_2D, extent, array_layers, flags)
+                let flags = ImageCreateFlags {
+                    cube_compatible: cubemap_compatible,
+                    .. ImageCreateFlags::none()
+                };
+                (ImageType::Dim2d, extent, array_layers, flags)
             },
             ImageDimensions::Dim3d { width, height, depth } => {
                 if width == 0 || height == 0 || depth == 0 {
                     return Err(ImageCreationError::UnsupportedDimensions { dimensions: dimensions });
                 }
                 let extent = vk::Extent3D { width: width, height: height, depth: depth };
-                (vk::IMAGE_TYPE_3D, extent, 1, 0)
+                (ImageType::Dim3d, extent, 1, ImageCreateFlags::none())
             },
         };
 
-        // Checking the dimensions against the limits.
-        if array_layers > device.physical_device().limits().max_image_array_layers() {
-            let err = ImageCreationError::UnsupportedDimensions { dimensions: dimensions };
-            capabilities_error = Some(err);
-        }
-        match ty {
-            vk::IMAGE_TYPE_1D => {
-                if extent.width > device.physical_device().limits().max_image_dimension_1d() {
-                    let err = ImageCreationError::UnsupportedDimensions { dimensions: dimensions };
-                    capabilities_error = Some(err);
-                }
-            },
-            vk::IMAGE_TYPE_2D => {
-                let limit = device.physical_device().limits().max_image_dimension_2d();
-                if extent.width > limit || extent.height > limit {
-                    let err = ImageCreationError::UnsupportedDimensions { dimensions: dimensions };
-                    capabilities_error = Some(err);
-                }
+        // An image can't have more mipmaps than needed to reach a 1x1x1 level.
+        let max_mipmaps = dimensions.max_mipmaps();
 
-                if (flags & vk::IMAGE_CREATE_CUBE_COMPATIBLE_BIT) != 0 {
-                    let limit = device.physical_device().limits().max_image_dimension_cube();
-                    debug_assert_eq!(extent.width, extent.height);      // checked above
-                    if extent.width > limit {
-                        let err = ImageCreationError::UnsupportedDimensions { dimensions: dimensions };
-                        capabilities_error = Some(err);
-                    }
+        // Compute the number of mipmaps.
+        let mipmaps = match mipmaps.into() {
+            MipmapsCount::Specific(num) => {
+                if num < 1 || num > max_mipmaps {
+                    return Err(ImageCreationError::InvalidMipmapsCount {
+                        obtained: num, valid_range: 1 .. max_mipmaps + 1
+                    });
                 }
+
+                num
             },
-            vk::IMAGE_TYPE_3D => {
-                let limit = device.physical_device().limits().max_image_dimension_3d();
-                if extent.width > limit || extent.height > limit || extent.depth > limit {
-                    let err = ImageCreationError::UnsupportedDimensions { dimensions: dimensions };
-                    capabilities_error = Some(err);
-                }
+            MipmapsCount::Log2 => {
+                let smallest_dim = cmp::min(dimensions.width(),
+                                            cmp::min(dimensions.height(), dimensions.depth()));
+                32 - smallest_dim.leading_zeros()
             },
-            _ => unreachable!()
+            MipmapsCount::Max => max_mipmaps,
+            MipmapsCount::One => 1,
         };
 
-        let usage = usage.to_usage_bits();
+        let tiling = if linear_tiling { ImageTiling::Linear } else { ImageTiling::Optimal };
 
-        // Now that all checks have been performed, if any of the check failed we query the Vulkan
-        // implementation for additional image capabilities.
-        if let Some(capabilities_error) = capabilities_error {
-            let tiling = if linear_tiling {
-                vk::IMAGE_TILING_LINEAR
-            } else {
-                vk::IMAGE_TILING_OPTIMAL
+        // Checking the parameters against the limits of this particular combination of format,
+        // type, tiling, usage and flags. These take precedence over the general device limits.
+        {
+            let props = match try!(device.physical_device()
+                                         .image_format_properties(format, ty, tiling, usage,
+                                                                  flags))
+            {
+                Some(p) => p,
+                None => return Err(ImageCreationError::FormatNotSupported),
             };
 
-            let mut output = mem::uninitialized();
-            let physical_device = device.physical_device().internal_object();
-            let r = vk_i.GetPhysicalDeviceImageFormatProperties(physical_device, format as u32, ty,
-                                                                tiling, usage, 0 /* TODO */,
-                                                                &mut output);
-
-            match check_errors(r) {
-                Ok(_) => (),
-                Err(Error::FormatNotSupported) => return Err(ImageCreationError::FormatNotSupported),
-                Err(err) => return Err(err.into()),
+            if extent.width > props.max_extent[0] || extent.height > props.max_extent[1] ||
+               extent.depth > props.max_extent[2] || array_layers > props.max_array_layers
+            {
+                return Err(ImageCreationError::UnsupportedDimensions { dimensions: dimensions });
+            }
+
+            let max_mip_levels = cmp::min(props.max_mip_levels, max_mipmaps);
+            if mipmaps > max_mip_levels {
+                return Err(ImageCreationError::InvalidMipmapsCount {
+                    obtained: mipmaps, valid_range: 1 .. max_mip_levels + 1
+                });
+            }
+
+            if !props.supports_samples(num_samples) {
+                return Err(ImageCreationError::UnsupportedSamplesCount { obtained: num_samples });
             }
+        }
 
-            if extent.width > output.maxExtent.width || extent.height > output.maxExtent.height ||
-               extent.depth > output.maxExtent.depth || mipmaps > output.maxMipLevels ||
-               array_layers > output.maxArrayLayers || (num_samples & output.sampleCounts) == 0
+        // Checking that the handle type can be used with this kind of image.
+        if let Some(handle_type) = external_handle_type {
+            match try!(device.physical_device()
+                             .external_image_properties(format, ty, tiling, usage, flags,
+                                                        handle_type))
             {
-                return Err(capabilities_error);
+                Some(ref props) if props.exportable || props.importable => (),
+                _ => return Err(ImageCreationError::UnsupportedExternalHandleType),
             }
         }
 
+        let ty = ty as u32;
+        let flags = flags.to_bits();
+        let usage = usage.to_usage_bits();
+
         // Everything now ok. Creating the image.
         let image = {
+            let external_infos = external_handle_type.map(|handle_type| {
+                vk::ExternalMemoryImageCreateInfoKHR {
+                    sType: vk::STRUCTURE_TYPE_EXTERNAL_MEMORY_IMAGE_CREATE_INFO_KHR,
+                    pNext: ptr::null(),
+                    handleTypes: handle_type as u32,
+                }
+            });
+
             let infos = vk::ImageCreateInfo {
                 sType: vk::STRUCTURE_TYPE_IMAGE_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: external_infos.as_ref().map(|i| i as *const _ as *const _)
+                                     .unwrap_or(ptr::null()),
                 flags: flags,
                 imageType: ty,
                 format: format as u32,
@@ -404,11 +366,7 @@ impl UnsafeImage {
                 mipLevels: mipmaps,
                 arrayLayers: array_layers,
                 samples: num_samples,
-                tiling: if linear_tiling {
-                    vk::IMAGE_TILING_LINEAR
-                } else {
-                    vk::IMAGE_TILING_OPTIMAL
-                },
+                tiling: tiling as u32,
                 usage: usage,
                 sharingMode: sh_mode,
                 queueFamilyIndexCount: sh_indices.len() as u32,
@@ -441,7 +399,10 @@ impl UnsafeImage {
             dimensions: dimensions,
             samples: num_samples,
             mipmaps: mipmaps,
+            tiling: tiling,
             format_features: format_features,
+            concurrent: sh_mode == vk::SHARING_MODE_CONCURRENT,
+            external_handle_type: external_handle_type,
             needs_destruction: true,
         };
 
@@ -450,10 +411,13 @@ impl UnsafeImage {
 
     /// Creates an image from a raw handle. The image won't be destroyed.
     ///
-    /// This function is for example used at the swapchain's initialization.
-    pub unsafe fn from_raw(device: Arc<Device>, handle: u64, usage: u32, format: Format,
-                           dimensions: ImageDimensions, samples: u32, mipmaps: u32)
-                           -> UnsafeImage
+    /// This function is for example used at the swapchain's initialization. `sharing` must be
+    /// the sharing mode that the image was created with.
+    pub unsafe fn from_raw<I>(device: Arc<Device>, handle: u64, usage: u32, format: Format,
+                              dimensions: ImageDimensions, samples: u32, mipmaps: u32,
+                              sharing: Sharing<I>)
+                              -> UnsafeImage
+        where I: Iterator<Item = u32>
     {
         let vk_i = device.instance().pointers();
         let physical_device = device.physical_device().internal_object();
@@ -471,7 +435,13 @@ impl UnsafeImage {
             dimensions: dimensions,
             samples: samples,
             mipmaps: mipmaps,
+            tiling: ImageTiling::Optimal,
             format_features: output.optimalTilingFeatures,
+            concurrent: match sharing {
+                Sharing::Exclusive => false,
+                Sharing::Concurrent(_) => true,
+            },
+            external_handle_type: None,
             needs_destruction: false,       // TODO: pass as parameter
         }
     }
@@ -491,6 +461,10 @@ impl UnsafeImage {
             mem_reqs.memoryTypeBits & (1 << memory.memory_type().id()) != 0
         });
 
+        // Exportable memory can only hold images created with the same handle type.
+        debug_assert!(memory.export_handle_type().is_none() ||
+                      memory.export_handle_type() == self.external_handle_type);
+
         try!(check_errors(vk.BindImageMemory(self.device.internal_object(), self.image,
                                              memory.internal_object(), offset as vk::DeviceSize)));
         Ok(())
@@ -521,6 +495,12 @@ impl UnsafeImage {
         self.samples
     }
 
+    /// Returns the tiling that was used to create the image.
+    #[inline]
+    pub fn tiling(&self) -> ImageTiling {
+        self.tiling
+    }
+
     /// Returns a key unique to each `UnsafeImage`. Can be used for the `conflicts_key` method.
     #[inline]
     pub fn key(&self) -> u64 {
@@ -551,7 +531,7 @@ impl UnsafeImage {
     ///
     #[inline]
     pub unsafe fn color_linear_layout(&self, mip_level: u32) -> LinearLayout {
-        self.linear_layout_impl(mip_level, vk::IMAGE_ASPECT_COLOR_BIT)
+        self.linear_layout_impl(mip_level, 0, vk::IMAGE_ASPECT_COLOR_BIT)
     }
 
     /// Same as `color_linear_layout`, except that it retreives the depth component of the image.
@@ -567,7 +547,7 @@ impl UnsafeImage {
     ///
     #[inline]
     pub unsafe fn depth_linear_layout(&self, mip_level: u32) -> LinearLayout {
-        self.linear_layout_impl(mip_level, vk::IMAGE_ASPECT_DEPTH_BIT)
+        self.linear_layout_impl(mip_level, 0, vk::IMAGE_ASPECT_DEPTH_BIT)
     }
 
     /// Same as `color_linear_layout`, except that it retreives the stencil component of the image.
@@ -583,11 +563,61 @@ impl UnsafeImage {
     ///
     #[inline]
     pub unsafe fn stencil_linear_layout(&self, mip_level: u32) -> LinearLayout {
-        self.linear_layout_impl(mip_level, vk::IMAGE_ASPECT_STENCIL_BIT)
+        self.linear_layout_impl(mip_level, 0, vk::IMAGE_ASPECT_STENCIL_BIT)
+    }
+
+    /// Queries the layout of a subresource of an image with linear tiling.
+    ///
+    /// This is the safe version of the `*_linear_layout` functions. Contrary to them, you can
+    /// query any array layer.
+    ///
+    /// Returns an error if the image wasn't created with linear tiling, if the format of the
+    /// image doesn't have the requested aspect, or if the mipmap level or the array layer is out
+    /// of range.
+    pub fn subresource_layout(&self, aspect: ImageAspect, mip_level: u32, array_layer: u32)
+                              -> Result<LinearLayout, SubresourceLayoutError>
+    {
+        if self.tiling != ImageTiling::Linear {
+            return Err(SubresourceLayoutError::NotLinearTiling);
+        }
+
+        let has_aspect = match (aspect, self.format.ty()) {
+            (ImageAspect::Color, FormatTy::Float) => true,
+            (ImageAspect::Color, FormatTy::Uint) => true,
+            (ImageAspect::Color, FormatTy::Sint) => true,
+            (ImageAspect::Color, FormatTy::Compressed) => true,
+            (ImageAspect::Depth, FormatTy::Depth) => true,
+            (ImageAspect::Depth, FormatTy::DepthStencil) => true,
+            (ImageAspect::Stencil, FormatTy::Stencil) => true,
+            (ImageAspect::Stencil, FormatTy::DepthStencil) => true,
+            _ => false,
+        };
+
+        if !has_aspect {
+            return Err(SubresourceLayoutError::AspectNotPresent);
+        }
+
+        if mip_level >= self.mipmaps {
+            return Err(SubresourceLayoutError::MipLevelOutOfRange {
+                obtained: mip_level,
+                max: self.mipmaps,
+            });
+        }
+
+        if array_layer >= self.dimensions.array_layers() {
+            return Err(SubresourceLayoutError::ArrayLayerOutOfRange {
+                obtained: array_layer,
+                max: self.dimensions.array_layers(),
+            });
+        }
+
+        unsafe { Ok(self.linear_layout_impl(mip_level, array_layer, aspect as u32)) }
     }
 
     // Implementation of the `*_layout` functions.
-    unsafe fn linear_layout_impl(&self, mip_level: u32, aspect: u32) -> LinearLayout {
+    unsafe fn linear_layout_impl(&self, mip_level: u32, array_layer: u32, aspect: u32)
+                                 -> LinearLayout
+    {
         let vk = self.device.pointers();
 
         assert!(mip_level < self.mipmaps);
@@ -595,7 +625,7 @@ impl UnsafeImage {
         let subresource = vk::ImageSubresource {
             aspectMask: aspect,
             mipLevel: mip_level,
-            arrayLayer: 0,
+            arrayLayer: array_layer,
         };
 
         let mut out = mem::uninitialized();
@@ -629,6 +659,19 @@ impl UnsafeImage {
         (self.format_features & vk::FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR_BIT) != 0
     }
 
+    /// Returns true if the image was created with the `Concurrent` sharing mode, in which case
+    /// it can be accessed by multiple queue families without ownership transfers.
+    #[inline]
+    pub fn concurrent_sharing(&self) -> bool {
+        self.concurrent
+    }
+
+    /// Returns the handle type that was passed to `with_external_memory`, if any.
+    #[inline]
+    pub fn external_memory_handle_type(&self) -> Option<ExternalMemoryHandleType> {
+        self.external_handle_type
+    }
+
     #[inline]
     pub fn usage_transfer_src(&self) -> bool {
         (self.usage & vk::IMAGE_USAGE_TRANSFER_SRC_BIT) != 0
@@ -717,6 +760,21 @@ pub enum ImageCreationError {
     UnsupportedUsage,
     /// The `shader_storage_image_multisample` feature must be enabled to create such an image.
     ShaderStorageImageMultisampleFeatureNotEnabled,
+    /// External memory was requested but the corresponding extension wasn't enabled.
+    ExternalMemoryExtensionNotEnabled,
+    /// The format is a YCbCr format but the `VK_KHR_sampler_ycbcr_conversion` extension wasn't
+    /// enabled.
+    YcbcrConversionExtensionNotEnabled,
+    /// The format is a YCbCr format but the `sampler_ycbcr_conversion` feature wasn't enabled.
+    YcbcrConversionFeatureNotEnabled,
+    /// The physical device doesn't support exporting or importing such an image as the
+    /// requested handle type.
+    UnsupportedExternalHandleType,
+    /// The image must be accessible from the CPU, but none of the memory types that support it
+    /// is host-visible.
+    NoHostVisibleMemory,
+    /// None of the memory types that support the image has the required properties.
+    NoSuitableMemoryType,
 }
 
 impl error::Error for ImageCreationError {
@@ -738,6 +796,27 @@ impl error::Error for ImageCreationError {
                 "the `shader_storage_image_multisample` feature must be enabled to create such \
                  an image"
             },
+            ImageCreationError::ExternalMemoryExtensionNotEnabled => {
+                "external memory was requested but the corresponding extension wasn't enabled"
+            },
+            ImageCreationError::YcbcrConversionExtensionNotEnabled => {
+                "the format is a YCbCr format but the `VK_KHR_sampler_ycbcr_conversion` \
+                 extension wasn't enabled"
+            },
+            ImageCreationError::YcbcrConversionFeatureNotEnabled => {
+                "the format is a YCbCr format but the `sampler_ycbcr_conversion` feature wasn't \
+                 enabled"
+            },
+            ImageCreationError::UnsupportedExternalHandleType => {
+                "the physical device doesn't support exporting or importing such an image as \
+                 the requested handle type"
+            },
+            ImageCreationError::NoHostVisibleMemory => {
+                "none of the memory types that support the image is host-visible"
+            },
+            ImageCreationError::NoSuitableMemoryType => {
+                "none of the memory types that support the image has the required properties"
+            },
         }
     }
 
@@ -775,9 +854,46 @@ impl From<Error> for ImageCreationError {
     }
 }
 
+/// Error that can happen when querying the layout of a subresource of an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubresourceLayoutError {
+    /// The image wasn't created with linear tiling. The layout of images with optimal tiling is
+    /// opaque.
+    NotLinearTiling,
+    /// The format of the image doesn't have the requested aspect.
+    AspectNotPresent,
+    /// The mipmap level is out of range.
+    MipLevelOutOfRange { obtained: u32, max: u32 },
+    /// The array layer is out of range.
+    ArrayLayerOutOfRange { obtained: u32, max: u32 },
+}
+
+impl error::Error for SubresourceLayoutError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SubresourceLayoutError::NotLinearTiling => "the image wasn't created with linear \
+                                                        tiling",
+            SubresourceLayoutError::AspectNotPresent => "the format of the image doesn't have \
+                                                         the requested aspect",
+            SubresourceLayoutError::MipLevelOutOfRange { .. } => "the mipmap level is out of \
+                                                                  range",
+            SubresourceLayoutError::ArrayLayerOutOfRange { .. } => "the array layer is out of \
+                                                                    range",
+        }
+    }
+}
+
+impl fmt::Display for SubresourceLayoutError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
 /// Describes the memory layout of an image with linear tiling.
 ///
-/// Obtained by calling `*_linear_layout` on the image.
+/// Obtained by calling `subresource_layout` or `*_linear_layout` on the image.
 ///
 /// The address of a texel at `(x, y, z, layer)` is `layer * array_pitch + z * depth_pitch +
 /// y * row_pitch + x * size_of_each_texel + offset`. `size_of_each_texel` must be determined
@@ -805,12 +921,44 @@ pub struct UnsafeImageView {
     usage: vk::ImageUsageFlagBits,
     identity_swizzle: bool,
     format: Format,
+    ycbcr_conversion: Option<Arc<SamplerYcbcrConversion>>,
 }
 
 impl UnsafeImageView {
     /// See the docs of new().
+    #[inline]
     pub unsafe fn raw(image: &UnsafeImage, ty: ViewType, mipmap_levels: Range<u32>,
                       array_layers: Range<u32>) -> Result<UnsafeImageView, OomError>
+    {
+        UnsafeImageView::raw_impl(image, ty, mipmap_levels, array_layers, None)
+    }
+
+    /// Same as `raw()`, but attaches a YCbCr conversion to the view.
+    ///
+    /// Images with a YCbCr format must be sampled through a view created with this function,
+    /// using the same conversion as the sampler.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the conversion was not created with the same device as the image.
+    /// - Panics if the format of the conversion is not the format of the image.
+    /// - Same panic reasons as `new()`.
+    ///
+    #[inline]
+    pub unsafe fn with_ycbcr_conversion(image: &UnsafeImage, ty: ViewType,
+                                        mipmap_levels: Range<u32>, array_layers: Range<u32>,
+                                        conversion: Arc<SamplerYcbcrConversion>)
+                                        -> Result<UnsafeImageView, OomError>
+    {
+        assert_eq!(image.device.internal_object(), conversion.device().internal_object());
+        assert_eq!(image.format, conversion.format());
+        UnsafeImageView::raw_impl(image, ty, mipmap_levels, array_layers, Some(conversion))
+    }
+
+    unsafe fn raw_impl(image: &UnsafeImage, ty: ViewType, mipmap_levels: Range<u32>,
+                       array_layers: Range<u32>,
+                       ycbcr_conversion: Option<Arc<SamplerYcbcrConversion>>)
+                       -> Result<UnsafeImageView, OomError>
     {
         let vk = image.device.pointers();
 
@@ -820,7 +968,8 @@ impl UnsafeImageView {
         assert!(array_layers.end <= image.dimensions.array_layers());
 
         let aspect_mask = match image.format.ty() {
-            FormatTy::Float | FormatTy::Uint | FormatTy::Sint | FormatTy::Compressed => {
+            FormatTy::Float | FormatTy::Uint | FormatTy::Sint | FormatTy::Compressed |
+            FormatTy::Ycbcr => {
                 vk::IMAGE_ASPECT_COLOR_BIT
             },
             FormatTy::Depth => vk::IMAGE_ASPECT_DEPTH_BIT,
@@ -846,9 +995,20 @@ impl UnsafeImageView {
         };
 
         let view = {
+            let ycbcr_infos = ycbcr_conversion.as_ref().map(|conversion| {
+                vk::SamplerYcbcrConversionInfoKHR {
+                    sType: vk::STRUCTURE_TYPE_SAMPLER_YCBCR_CONVERSION_INFO_KHR,
+                    pNext: ptr::null(),
+                    conversion: conversion.internal_object(),
+                }
+            });
+
             let infos = vk::ImageViewCreateInfo {
                 sType: vk::STRUCTURE_TYPE_IMAGE_VIEW_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: match ycbcr_infos {
+                    Some(ref infos) => infos as *const _ as *const _,
+                    None => ptr::null(),
+                },
                 flags: 0,   // reserved
                 image: image.internal_object(),
                 viewType: view_type,
@@ -875,6 +1035,7 @@ impl UnsafeImageView {
             usage: image.usage,
             identity_swizzle: true,     // FIXME:
             format: image.format,
+            ycbcr_conversion: ycbcr_conversion,
         })
     }
 
@@ -905,6 +1066,12 @@ impl UnsafeImageView {
         self.format
     }
 
+    /// Returns the YCbCr conversion attached to the view, if any.
+    #[inline]
+    pub fn ycbcr_conversion(&self) -> Option<&Arc<SamplerYcbcrConversion>> {
+        self.ycbcr_conversion.as_ref()
+    }
+
     #[inline]
     pub fn usage_transfer_src(&self) -> bool {
         (self.usage & vk::IMAGE_USAGE_TRANSFER_SRC_BIT) != 0
@@ -981,8 +1148,13 @@ mod tests {
     use super::UnsafeImage;
     use super::ImageUsage;
 
+    use super::SubresourceLayoutError;
+    use image::ImageAspect;
     use image::ImageDimensions;
+    use image::ImageTiling;
+    use image::MipmapsCount;
     use format::Format;
+    use memory::ExternalMemoryHandleType;
     use sync::Sharing;
 
     #[test]
@@ -1087,7 +1259,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore]       // TODO: AMD card seems to support a u32::MAX number of mipmaps
     fn mipmaps_too_high() {
         let (device, _) = gfx_dev_and_queue!();
 
@@ -1112,6 +1283,64 @@ mod tests {
         };
     }
 
+    #[test]
+    fn max_mipmaps_non_power_of_two() {
+        let dims = ImageDimensions::Dim2d { width: 100, height: 37, array_layers: 6,
+                                            cubemap_compatible: false };
+        assert_eq!(dims.max_mipmaps(), 7);
+
+        assert_eq!(ImageDimensions::Dim1d { width: 1, array_layers: 1 }.max_mipmaps(), 1);
+        assert_eq!(ImageDimensions::Dim1d { width: 255, array_layers: 1 }.max_mipmaps(), 8);
+        assert_eq!(ImageDimensions::Dim1d { width: 256, array_layers: 1 }.max_mipmaps(), 9);
+        assert_eq!(ImageDimensions::Dim3d { width: 3, height: 5, depth: 1000 }.max_mipmaps(), 10);
+        assert_eq!(ImageDimensions::Dim3d { width: 3, height: 0, depth: 1000 }.max_mipmaps(), 0);
+    }
+
+    #[test]
+    fn max_mipmaps_count() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            sampled: true,
+            .. ImageUsage::none()
+        };
+
+        let (image, _) = unsafe {
+            UnsafeImage::new(device, usage, Format::R8G8B8A8Unorm,
+                             ImageDimensions::Dim2d { width: 100, height: 37, array_layers: 1,
+                                                      cubemap_compatible: false },
+                             1, MipmapsCount::Max, Sharing::Exclusive::<Empty<_>>, false, false)
+                .unwrap()
+        };
+
+        assert_eq!(image.mipmap_levels(), 7);
+    }
+
+    #[test]
+    fn mipmaps_above_max() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            sampled: true,
+            .. ImageUsage::none()
+        };
+
+        let res = unsafe {
+            UnsafeImage::new(device, usage, Format::R8G8B8A8Unorm,
+                             ImageDimensions::Dim2d { width: 100, height: 37, array_layers: 1,
+                                                      cubemap_compatible: false }, 1, 8,
+                             Sharing::Exclusive::<Empty<_>>, false, false)
+        };
+
+        match res {
+            Err(ImageCreationError::InvalidMipmapsCount { obtained, valid_range }) => {
+                assert_eq!(obtained, 8);
+                assert_eq!(valid_range, 1 .. 8);
+            },
+            _ => panic!()
+        };
+    }
+
     #[test]
     fn shader_storage_image_multisample() {
         let (device, _) = gfx_dev_and_queue!();
@@ -1202,4 +1431,112 @@ mod tests {
             _ => panic!()
         };
     }
+
+    #[test]
+    fn linear_subresource_layout() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            transfer_dest: true,
+            .. ImageUsage::none()
+        };
+
+        let (img, _) = unsafe {
+            UnsafeImage::new(device, usage, Format::R8G8B8A8Unorm,
+                             ImageDimensions::Dim2d { width: 13, height: 7, array_layers: 1,
+                                                      cubemap_compatible: false }, 1, 1,
+                             Sharing::Exclusive::<Empty<_>>, true, false)
+        }.unwrap();
+
+        assert_eq!(img.tiling(), ImageTiling::Linear);
+
+        let layout = img.subresource_layout(ImageAspect::Color, 0, 0).unwrap();
+        assert!(layout.row_pitch >= 13 * 4);
+        assert!(layout.size >= layout.row_pitch * 6 + 13 * 4);
+
+        match img.subresource_layout(ImageAspect::Depth, 0, 0) {
+            Err(SubresourceLayoutError::AspectNotPresent) => (),
+            _ => panic!()
+        };
+
+        match img.subresource_layout(ImageAspect::Color, 1, 0) {
+            Err(SubresourceLayoutError::MipLevelOutOfRange { obtained: 1, max: 1 }) => (),
+            _ => panic!()
+        };
+
+        match img.subresource_layout(ImageAspect::Color, 0, 1) {
+            Err(SubresourceLayoutError::ArrayLayerOutOfRange { obtained: 1, max: 1 }) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn optimal_subresource_layout() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            sampled: true,
+            .. ImageUsage::none()
+        };
+
+        let (img, _) = unsafe {
+            UnsafeImage::new(device, usage, Format::R8G8B8A8Unorm,
+                             ImageDimensions::Dim2d { width: 32, height: 32, array_layers: 1,
+                                                      cubemap_compatible: false }, 1, 1,
+                             Sharing::Exclusive::<Empty<_>>, false, false)
+        }.unwrap();
+
+        match img.subresource_layout(ImageAspect::Color, 0, 0) {
+            Err(SubresourceLayoutError::NotLinearTiling) => (),
+            _ => panic!()
+        };
+
+        assert_eq!(img.external_memory_handle_type(), None);
+    }
+
+    #[test]
+    fn external_memory_missing_extension() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            sampled: true,
+            .. ImageUsage::none()
+        };
+
+        let res = unsafe {
+            UnsafeImage::with_external_memory(device, usage, Format::R8G8B8A8Unorm,
+                                              ImageDimensions::Dim2d {
+                                                  width: 32, height: 32, array_layers: 1,
+                                                  cubemap_compatible: false
+                                              }, 1, 1, Sharing::Exclusive::<Empty<_>>, false,
+                                              false, ExternalMemoryHandleType::OpaqueFd)
+        };
+
+        match res {
+            Err(ImageCreationError::ExternalMemoryExtensionNotEnabled) => (),
+            _ => panic!()
+        };
+    }
+
+    #[test]
+    fn ycbcr_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            sampled: true,
+            .. ImageUsage::none()
+        };
+
+        let res = unsafe {
+            UnsafeImage::new(device, usage, Format::G8_B8R8_2Plane420Unorm,
+                             ImageDimensions::Dim2d { width: 32, height: 32, array_layers: 1,
+                                                      cubemap_compatible: false }, 1, 1,
+                             Sharing::Exclusive::<Empty<_>>, false, false)
+        };
+
+        match res {
+            Err(ImageCreationError::YcbcrConversionExtensionNotEnabled) => (),
+            _ => panic!()
+        };
+    }
 }
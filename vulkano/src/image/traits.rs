@@ -18,8 +18,12 @@ use format::PossibleDepthFormatDesc;
 use format::PossibleStencilFormatDesc;
 use format::PossibleDepthStencilFormatDesc;
 use image::Dimensions;
+use image::ImageAspect;
 use image::ImageDimensions;
 use image::ImageLayout;
+use image::TrackedLayout;
+use image::sys::LinearLayout;
+use image::sys::SubresourceLayoutError;
 use image::sys::UnsafeImage;
 use image::sys::UnsafeImageView;
 use sampler::Sampler;
@@ -98,6 +102,18 @@ pub unsafe trait ImageAccess {
         self.inner().dimensions()
     }
 
+    /// Returns the layout in memory of a subresource of the image.
+    ///
+    /// Only images with linear tiling have a layout that can be queried. Use this to find where
+    /// each row starts when accessing the memory of the image from the CPU, as rows can be
+    /// padded by the implementation.
+    #[inline]
+    fn subresource_layout(&self, aspect: ImageAspect, mip_level: u32, array_layer: u32)
+                          -> Result<LinearLayout, SubresourceLayoutError>
+    {
+        self.inner().subresource_layout(aspect, mip_level, array_layer)
+    }
+
     /// Returns true if the image can be used as a source for blits.
     #[inline]
     fn supports_blit_source(&self) -> bool {
@@ -116,6 +132,42 @@ pub unsafe trait ImageAccess {
     /// Returns the layout that the image must be returned to before the end of the command buffer.
     fn final_layout_requirement(&self) -> ImageLayout;
 
+    /// Returns the layout that the given mipmap level and array layer are believed to be in
+    /// when the image isn't in use by a command buffer.
+    ///
+    /// This never modifies any state and is only meant to help decide whether a manual barrier
+    /// is needed, or to debug layout-related validation errors.
+    ///
+    /// The default implementation returns the layout requirement of the image if the initial and
+    /// final requirements are the same, and `Unknown` otherwise. Note that:
+    ///
+    /// - The transitions performed by a command buffer that is being built or executed aren't
+    ///   visible. The value describes the layout before and after the command buffers that use
+    ///   the image.
+    /// - Vulkano can't know whether a command buffer that uses the image has been submitted or
+    ///   has finished executing. If you used an unsafe function that leaves the image in another
+    ///   layout, the returned value is wrong.
+    /// - With the default implementation, an image whose content has never been initialized is
+    ///   reported as being in its required layout, even though it is still `Undefined` for the
+    ///   implementation. Images that track their initialization, like `StorageImage`, report
+    ///   `Known(Undefined)` until a command buffer that uses them is submitted.
+    ///
+    #[inline]
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> TrackedLayout {
+        debug_assert!(mip_level < self.mipmap_levels());
+        debug_assert!(array_layer < self.dimensions().array_layers());
+
+        let layout = self.initial_layout_requirement();
+        if layout != self.final_layout_requirement() {
+            return TrackedLayout::Unknown;
+        }
+
+        match layout {
+            ImageLayout::Undefined | ImageLayout::Preinitialized => TrackedLayout::Unknown,
+            layout => TrackedLayout::Known(layout),
+        }
+    }
+
     /// Wraps around this `ImageAccess` and returns an identical `ImageAccess` but whose initial
     /// layout requirement is either `Undefined` or `Preinitialized`.
     #[inline]
@@ -150,6 +202,9 @@ pub unsafe trait ImageAccess {
     ///
     /// If this function returns `false`, this means that we are allowed to access the offset/size
     /// of `self` at the same time as the offset/size of `other` without causing a data race.
+    ///
+    /// The default implementation compares the array layers and the mipmap levels, so that for
+    /// example two different mipmap levels of the same image don't conflict.
     fn conflicts_image(&self, self_first_layer: u32, self_num_layers: u32, self_first_mipmap: u32,
                        self_num_mipmaps: u32, other: &ImageAccess,
                        other_first_layer: u32, other_num_layers: u32, other_first_mipmap: u32,
@@ -163,7 +218,12 @@ pub unsafe trait ImageAccess {
             return false;
         }
 
-        true
+        // The aspects don't need to be compared: the transfer commands and the pipeline barriers
+        // always cover all the aspects of the format of the image, and both accesses are to the
+        // same image and therefore to the same aspects.
+        ranges_overlap(self_first_layer, self_num_layers, other_first_layer, other_num_layers) &&
+            ranges_overlap(self_first_mipmap, self_num_mipmaps, other_first_mipmap,
+                           other_num_mipmaps)
     }
 
     /// Returns a key that uniquely identifies the range given by
@@ -218,6 +278,16 @@ pub unsafe trait ImageAccess {
     unsafe fn increase_gpu_lock(&self);
 }
 
+// Returns true if the `a_num` elements starting at `a_first` and the `b_num` elements starting at
+// `b_first` have at least one element in common.
+#[inline]
+fn ranges_overlap(a_first: u32, a_num: u32, b_first: u32, b_num: u32) -> bool {
+    // Computed with 64 bits integers so that the ends of the ranges can't overflow.
+    let (a_first, a_num, b_first, b_num) = (a_first as u64, a_num as u64, b_first as u64,
+                                            b_num as u64);
+    a_num != 0 && b_num != 0 && a_first < b_first + b_num && b_first < a_first + a_num
+}
+
 unsafe impl<T> ImageAccess for T where T: SafeDeref, T::Target: ImageAccess {
     #[inline]
     fn inner(&self) -> &UnsafeImage {
@@ -234,6 +304,22 @@ unsafe impl<T> ImageAccess for T where T: SafeDeref, T::Target: ImageAccess {
         (**self).final_layout_requirement()
     }
 
+    #[inline]
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> TrackedLayout {
+        (**self).current_layout(mip_level, array_layer)
+    }
+
+    #[inline]
+    fn conflicts_image(&self, self_first_layer: u32, self_num_layers: u32, self_first_mipmap: u32,
+                       self_num_mipmaps: u32, other: &ImageAccess,
+                       other_first_layer: u32, other_num_layers: u32, other_first_mipmap: u32,
+                       other_num_mipmaps: u32) -> bool
+    {
+        (**self).conflicts_image(self_first_layer, self_num_layers, self_first_mipmap,
+                                 self_num_mipmaps, other, other_first_layer, other_num_layers,
+                                 other_first_mipmap, other_num_mipmaps)
+    }
+
     #[inline]
     fn conflict_key(&self, first_layer: u32, num_layers: u32, first_mipmap: u32, num_mipmaps: u32)
                     -> u64
@@ -282,6 +368,12 @@ unsafe impl<I> ImageAccess for ImageAccessFromUndefinedLayout<I>
         self.image.final_layout_requirement()
     }
 
+    #[inline]
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> TrackedLayout {
+        // The wrapper only changes what the next command buffer expects, not the actual layout.
+        self.image.current_layout(mip_level, array_layer)
+    }
+
     #[inline]
     fn conflict_key(&self, first_layer: u32, num_layers: u32, first_mipmap: u32, num_mipmaps: u32)
                     -> u64
@@ -414,3 +506,33 @@ unsafe impl<T> ImageViewAccess for T where T: SafeDeref, T::Target: ImageViewAcc
 pub unsafe trait AttachmentImageView: ImageViewAccess {
     fn accept(&self, initial_layout: ImageLayout, final_layout: ImageLayout) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ranges_overlap;
+
+    #[test]
+    fn disjoint_subresources() {
+        // Generating mipmap 1 from mipmap 0.
+        assert!(!ranges_overlap(0, 1, 1, 1));
+        // Two different array layers.
+        assert!(!ranges_overlap(2, 2, 0, 2));
+        assert!(!ranges_overlap(0, 0, 0, 4));
+    }
+
+    #[test]
+    fn overlapping_subresources() {
+        assert!(ranges_overlap(0, 1, 0, 1));
+        assert!(ranges_overlap(0, 4, 3, 1));
+        assert!(ranges_overlap(3, 1, 0, 4));
+        assert!(ranges_overlap(1, 2, 2, 2));
+    }
+
+    #[test]
+    fn large_ranges_dont_overflow() {
+        use std::u32;
+        assert!(ranges_overlap(u32::MAX, 1, 1, u32::MAX));
+        assert!(!ranges_overlap(u32::MAX - 1, 1, u32::MAX, u32::MAX));
+        assert!(ranges_overlap(u32::MAX - 1, u32::MAX, u32::MAX, u32::MAX));
+    }
+}
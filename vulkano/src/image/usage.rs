@@ -93,6 +93,18 @@ impl ImageUsage {
         }
     }
 
+    /// Builds a `ImageUsage` with `color_attachment` set to true and the rest to false.
+    ///
+    /// This is the usage to pass when creating a swapchain whose images are only drawn to by
+    /// render passes.
+    #[inline]
+    pub fn color_attachment() -> ImageUsage {
+        ImageUsage {
+            color_attachment: true,
+            .. ImageUsage::none()
+        }
+    }
+
     // TODO: these functions shouldn't be public-hidden
     #[doc(hidden)]
     #[inline]
@@ -327,12 +327,17 @@ instance_extensions! {
     khr_android_surface => b"VK_KHR_android_surface",
     khr_win32_surface => b"VK_KHR_win32_surface",
     ext_debug_report => b"VK_EXT_debug_report",
+    ext_debug_utils => b"VK_EXT_debug_utils",
     mvk_ios_surface => b"VK_MVK_ios_surface",
     mvk_macos_surface => b"VK_MVK_macos_surface",
+    ext_metal_surface => b"VK_EXT_metal_surface",
     mvk_moltenvk => b"VK_MVK_moltenvk",     // TODO: confirm that it's an instance extension
     nn_vi_surface => b"VK_NN_vi_surface",
     ext_swapchain_colorspace => b"VK_EXT_swapchain_colorspace",
     khr_get_physical_device_properties2 => b"VK_KHR_get_physical_device_properties2",
+    khr_external_memory_capabilities => b"VK_KHR_external_memory_capabilities",
+    khr_external_semaphore_capabilities => b"VK_KHR_external_semaphore_capabilities",
+    khr_device_group_creation => b"VK_KHR_device_group_creation",
 }
 
 device_extensions! {
@@ -342,6 +347,18 @@ device_extensions! {
     khr_display_swapchain => b"VK_KHR_display_swapchain",
     khr_sampler_mirror_clamp_to_edge => b"VK_KHR_sampler_mirror_clamp_to_edge",
     khr_maintenance1 => b"VK_KHR_maintenance1",
+    khr_sampler_ycbcr_conversion => b"VK_KHR_sampler_ycbcr_conversion",
+    khr_external_memory => b"VK_KHR_external_memory",
+    khr_external_memory_fd => b"VK_KHR_external_memory_fd",
+    khr_external_memory_win32 => b"VK_KHR_external_memory_win32",
+    khr_external_semaphore => b"VK_KHR_external_semaphore",
+    khr_external_semaphore_fd => b"VK_KHR_external_semaphore_fd",
+    khr_external_semaphore_win32 => b"VK_KHR_external_semaphore_win32",
+    ext_index_type_uint8 => b"VK_EXT_index_type_uint8",
+    ext_conditional_rendering => b"VK_EXT_conditional_rendering",
+    khr_multiview => b"VK_KHR_multiview",
+    khr_buffer_device_address => b"VK_KHR_buffer_device_address",
+    khr_device_group => b"VK_KHR_device_group",
 }
 
 /// Error that can happen when loading the list of layers.
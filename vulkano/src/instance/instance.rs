@@ -13,6 +13,7 @@ use std::ffi::CStr;
 use std::ffi::CString;
 use std::fmt;
 use std::mem;
+use std::os::raw::c_void;
 use std::ptr;
 use std::slice;
 use std::sync::Arc;
@@ -26,9 +27,24 @@ use OomError;
 use VulkanObject;
 use vk;
 
+use buffer::BufferUsage;
+use buffer::usage::usage_to_bits;
 use features::Features;
+use format::Format;
+use image::ImageCreateFlags;
+use image::ImageFormatProperties;
+use image::ImageTiling;
+use image::ImageType;
+use image::ImageUsage;
+use memory::ExternalMemoryHandleType;
+use memory::ExternalMemoryProperties;
+use swapchain::CapabilitiesError;
+use swapchain::Surface;
+use sync::ExternalSemaphoreHandleType;
+use sync::ExternalSemaphoreProperties;
 use version::Version;
 use instance::{InstanceExtensions, RawInstanceExtensions};
+use instance::{DeviceExtensions, RawDeviceExtensions};
 
 /// An instance of a Vulkan context. This is the main object that should be created by an
 /// application before everything else.
@@ -279,6 +295,7 @@ impl Instance {
                 memory: memory,
                 queue_families: queue_families,
                 available_features: Features::from(available_features),
+                max_multiview_view_count: None,
             });
         }
         output
@@ -291,15 +308,33 @@ impl Instance {
         let mut output = Vec::with_capacity(physical_devices.len());
 
         for device in physical_devices.into_iter() {
-            let properties: vk::PhysicalDeviceProperties = unsafe {
+            // Properties provided by extensions can only be queried if the physical device
+            // supports the extension.
+            let supports_multiview =
+                Instance::device_supports_extension(vk, device, b"VK_KHR_multiview");
+
+            let (properties, max_multiview_view_count) = unsafe {
+                let mut multiview = vk::PhysicalDeviceMultiviewPropertiesKHR {
+                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_PROPERTIES_KHR,
+                    pNext: ptr::null(),
+                    maxMultiviewViewCount: 0,
+                    maxMultiviewInstanceIndex: 0,
+                };
+
                 let mut output = vk::PhysicalDeviceProperties2KHR {
                     sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2_KHR,
-                    pNext: ptr::null_mut(),
+                    pNext: if supports_multiview { &mut multiview as *mut _ as *const _ }
+                           else { ptr::null() },
                     properties: mem::uninitialized(),
                 };
 
                 vk.GetPhysicalDeviceProperties2KHR(device, &mut output);
-                output.properties
+                let max_multiview_view_count = if supports_multiview {
+                    Some(multiview.maxMultiviewViewCount)
+                } else {
+                    None
+                };
+                (output.properties, max_multiview_view_count)
             };
 
             let queue_families = unsafe {
@@ -329,14 +364,93 @@ impl Instance {
                 output.memoryProperties
             };
 
-            let available_features: vk::PhysicalDeviceFeatures = unsafe {
+            // Features provided by extensions can only be queried if the physical device
+            // supports the extension.
+            let supports_ycbcr_conversion =
+                Instance::device_supports_extension(vk, device,
+                                                    b"VK_KHR_sampler_ycbcr_conversion");
+            let supports_index_type_uint8 =
+                Instance::device_supports_extension(vk, device, b"VK_EXT_index_type_uint8");
+            let supports_conditional_rendering =
+                Instance::device_supports_extension(vk, device, b"VK_EXT_conditional_rendering");
+            let supports_buffer_device_address =
+                Instance::device_supports_extension(vk, device, b"VK_KHR_buffer_device_address");
+
+            let available_features: Features = unsafe {
+                let mut index_type_uint8 = vk::PhysicalDeviceIndexTypeUint8FeaturesEXT {
+                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_INDEX_TYPE_UINT8_FEATURES_EXT,
+                    pNext: ptr::null(),
+                    indexTypeUint8: vk::FALSE,
+                };
+
+                let mut conditional_rendering = vk::PhysicalDeviceConditionalRenderingFeaturesEXT {
+                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_CONDITIONAL_RENDERING_FEATURES_EXT,
+                    pNext: ptr::null(),
+                    conditionalRendering: vk::FALSE,
+                    inheritedConditionalRendering: vk::FALSE,
+                };
+
+                let mut multiview = vk::PhysicalDeviceMultiviewFeaturesKHR {
+                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_FEATURES_KHR,
+                    pNext: ptr::null(),
+                    multiview: vk::FALSE,
+                    multiviewGeometryShader: vk::FALSE,
+                    multiviewTessellationShader: vk::FALSE,
+                };
+
+                let mut buffer_device_address = vk::PhysicalDeviceBufferDeviceAddressFeaturesKHR {
+                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_BUFFER_DEVICE_ADDRESS_FEATURES_KHR,
+                    pNext: ptr::null(),
+                    bufferDeviceAddress: vk::FALSE,
+                    bufferDeviceAddressCaptureReplay: vk::FALSE,
+                    bufferDeviceAddressMultiDevice: vk::FALSE,
+                };
+
+                let mut ycbcr_conversion = vk::PhysicalDeviceSamplerYcbcrConversionFeaturesKHR {
+                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_SAMPLER_YCBCR_CONVERSION_FEATURES_KHR,
+                    pNext: ptr::null(),
+                    samplerYcbcrConversion: vk::FALSE,
+                };
+
+                // Chain the structs of the supported extensions.
+                let mut next: *const c_void = ptr::null();
+                if supports_index_type_uint8 {
+                    index_type_uint8.pNext = next;
+                    next = &mut index_type_uint8 as *mut _ as *const _;
+                }
+                if supports_conditional_rendering {
+                    conditional_rendering.pNext = next;
+                    next = &mut conditional_rendering as *mut _ as *const _;
+                }
+                if supports_multiview {
+                    multiview.pNext = next;
+                    next = &mut multiview as *mut _ as *const _;
+                }
+                if supports_buffer_device_address {
+                    buffer_device_address.pNext = next;
+                    next = &mut buffer_device_address as *mut _ as *const _;
+                }
+                if supports_ycbcr_conversion {
+                    ycbcr_conversion.pNext = next;
+                    next = &mut ycbcr_conversion as *mut _ as *const _;
+                }
+
                 let mut output = vk::PhysicalDeviceFeatures2KHR {
                     sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_FEATURES_2_KHR,
-                    pNext: ptr::null_mut(),
+                    pNext: next,
                     features: mem::uninitialized(),
                 };
                 vk.GetPhysicalDeviceFeatures2KHR(device, &mut output);
-                output.features
+
+                let mut features = Features::from(output.features);
+                features.sampler_ycbcr_conversion = ycbcr_conversion.samplerYcbcrConversion != 0;
+                features.index_type_uint8 = index_type_uint8.indexTypeUint8 != 0;
+                features.conditional_rendering = conditional_rendering.conditionalRendering != 0;
+                features.multiview = multiview.multiview != 0;
+                features.multiview_geometry_shader = multiview.multiviewGeometryShader != 0;
+                features.multiview_tessellation_shader = multiview.multiviewTessellationShader != 0;
+                features.buffer_device_address = buffer_device_address.bufferDeviceAddress != 0;
+                features
             };
 
             output.push(PhysicalDeviceInfos {
@@ -344,12 +458,40 @@ impl Instance {
                 properties: properties,
                 memory: memory,
                 queue_families: queue_families,
-                available_features: Features::from(available_features),
+                available_features: available_features,
+                max_multiview_view_count: max_multiview_view_count,
             });
         }
         output
     }
 
+    /// Returns true if the physical device supports the device extension with the given name.
+    fn device_supports_extension(vk: &vk::InstancePointers, device: vk::PhysicalDevice,
+                                 name: &[u8]) -> bool
+    {
+        let properties: Vec<vk::ExtensionProperties> = unsafe {
+            let mut num = 0;
+            if vk.EnumerateDeviceExtensionProperties(device, ptr::null(), &mut num,
+                                                     ptr::null_mut()) != vk::SUCCESS
+            {
+                return false;
+            }
+
+            let mut properties = Vec::with_capacity(num as usize);
+            if vk.EnumerateDeviceExtensionProperties(device, ptr::null(), &mut num,
+                                                     properties.as_mut_ptr()) != vk::SUCCESS
+            {
+                return false;
+            }
+            properties.set_len(num as usize);
+            properties
+        };
+
+        properties.iter().any(|p| {
+            unsafe { CStr::from_ptr(p.extensionName.as_ptr()) }.to_bytes() == name
+        })
+    }
+
     /*/// Same as `new`, but provides an allocator that will be used by the Vulkan library whenever
     /// it needs to allocate memory on the host.
     ///
@@ -545,6 +687,9 @@ struct PhysicalDeviceInfos {
     queue_families: Vec<vk::QueueFamilyProperties>,
     memory: vk::PhysicalDeviceMemoryProperties,
     available_features: Features,
+    // `None` if the device doesn't support `VK_KHR_multiview` or if the properties couldn't be
+    // queried.
+    max_multiview_view_count: Option<u32>,
 }
 
 /// Represents one of the available devices on this machine.
@@ -777,6 +922,177 @@ impl<'a> PhysicalDevice<'a> {
         Limits { device: *self }
     }
 
+    /// Returns the maximum number of views that a subpass can render to with multiview rendering.
+    ///
+    /// Returns `None` if the physical device doesn't support the `khr_multiview` extension, or if
+    /// the `khr_get_physical_device_properties2` instance extension wasn't enabled.
+    #[inline]
+    pub fn max_multiview_view_count(&self) -> Option<u32> {
+        self.infos().max_multiview_view_count
+    }
+
+    /// Returns the limits of the images that can be created with the given parameters.
+    ///
+    /// Returns `None` if this combination of format, type, tiling, usage and flags is not
+    /// supported at all.
+    pub fn image_format_properties(&self, format: Format, ty: ImageType, tiling: ImageTiling,
+                                   usage: ImageUsage, flags: ImageCreateFlags)
+                                   -> Result<Option<ImageFormatProperties>, OomError>
+    {
+        unsafe {
+            let vk = self.instance.pointers();
+
+            let mut output = mem::uninitialized();
+            let r = vk.GetPhysicalDeviceImageFormatProperties(self.internal_object(),
+                                                              format as u32, ty as u32,
+                                                              tiling as u32,
+                                                              usage.to_usage_bits(),
+                                                              flags.to_bits(), &mut output);
+
+            match check_errors(r) {
+                Ok(_) => Ok(Some(output.into())),
+                Err(Error::FormatNotSupported) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        }
+    }
+
+    /// Returns the capabilities of the physical device regarding the export and import of
+    /// buffers with the given usage as operating system handles of the given type.
+    ///
+    /// Returns `None` if the `khr_external_memory_capabilities` instance extension is not
+    /// enabled.
+    pub fn external_buffer_properties(&self, usage: BufferUsage,
+                                      handle_type: ExternalMemoryHandleType)
+                                      -> Option<ExternalMemoryProperties>
+    {
+        if !self.instance.loaded_extensions().khr_external_memory_capabilities {
+            return None;
+        }
+
+        unsafe {
+            let vk = self.instance.pointers();
+
+            let infos = vk::PhysicalDeviceExternalBufferInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_BUFFER_INFO_KHR,
+                pNext: ptr::null(),
+                flags: 0,
+                usage: usage_to_bits(usage),
+                handleType: handle_type as u32,
+            };
+
+            let mut output = vk::ExternalBufferPropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_BUFFER_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                externalMemoryProperties: mem::zeroed(),
+            };
+
+            vk.GetPhysicalDeviceExternalBufferPropertiesKHR(self.internal_object(), &infos,
+                                                            &mut output);
+            Some(output.externalMemoryProperties.into())
+        }
+    }
+
+    /// Returns the capabilities of the physical device regarding the export and import of
+    /// images with the given parameters as operating system handles of the given type.
+    ///
+    /// Returns `None` if the `khr_external_memory_capabilities` or the
+    /// `khr_get_physical_device_properties2` instance extension is not enabled. If the
+    /// combination of parameters isn't supported at all, the handle type is reported as neither
+    /// exportable nor importable.
+    pub fn external_image_properties(&self, format: Format, ty: ImageType, tiling: ImageTiling,
+                                     usage: ImageUsage, flags: ImageCreateFlags,
+                                     handle_type: ExternalMemoryHandleType)
+                                     -> Result<Option<ExternalMemoryProperties>, OomError>
+    {
+        let extensions = self.instance.loaded_extensions();
+        if !extensions.khr_external_memory_capabilities ||
+           !extensions.khr_get_physical_device_properties2
+        {
+            return Ok(None);
+        }
+
+        unsafe {
+            let vk = self.instance.pointers();
+
+            let external_infos = vk::PhysicalDeviceExternalImageFormatInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_IMAGE_FORMAT_INFO_KHR,
+                pNext: ptr::null(),
+                handleType: handle_type as u32,
+            };
+
+            let infos = vk::PhysicalDeviceImageFormatInfo2KHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_IMAGE_FORMAT_INFO_2_KHR,
+                pNext: &external_infos as *const _ as *const _,
+                format: format as u32,
+                imageType: ty as u32,
+                tiling: tiling as u32,
+                usage: usage.to_usage_bits(),
+                flags: flags.to_bits(),
+            };
+
+            let mut external_output = vk::ExternalImageFormatPropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_IMAGE_FORMAT_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                externalMemoryProperties: mem::zeroed(),
+            };
+
+            let mut output = vk::ImageFormatProperties2KHR {
+                sType: vk::STRUCTURE_TYPE_IMAGE_FORMAT_PROPERTIES_2_KHR,
+                pNext: &mut external_output as *mut _ as *const _,
+                imageFormatProperties: mem::zeroed(),
+            };
+
+            let r = vk.GetPhysicalDeviceImageFormatProperties2KHR(self.internal_object(),
+                                                                  &infos, &mut output);
+
+            match check_errors(r) {
+                Ok(_) => Ok(Some(external_output.externalMemoryProperties.into())),
+                Err(Error::FormatNotSupported) => Ok(Some(ExternalMemoryProperties {
+                    dedicated_only: false,
+                    exportable: false,
+                    importable: false,
+                })),
+                Err(err) => Err(err.into()),
+            }
+        }
+    }
+
+    /// Returns the capabilities of the physical device regarding the export and import of
+    /// semaphore payloads as operating system handles of the given type.
+    ///
+    /// Returns `None` if the `khr_external_semaphore_capabilities` instance extension is not
+    /// enabled.
+    pub fn external_semaphore_properties(&self, handle_type: ExternalSemaphoreHandleType)
+                                         -> Option<ExternalSemaphoreProperties>
+    {
+        if !self.instance.loaded_extensions().khr_external_semaphore_capabilities {
+            return None;
+        }
+
+        unsafe {
+            let vk = self.instance.pointers();
+
+            let infos = vk::PhysicalDeviceExternalSemaphoreInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO_KHR,
+                pNext: ptr::null(),
+                handleType: handle_type as u32,
+            };
+
+            let mut output = vk::ExternalSemaphorePropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_SEMAPHORE_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                exportFromImportedHandleTypes: 0,
+                compatibleHandleTypes: 0,
+                externalSemaphoreFeatures: 0,
+            };
+
+            vk.GetPhysicalDeviceExternalSemaphorePropertiesKHR(self.internal_object(), &infos,
+                                                               &mut output);
+            Some(output.into())
+        }
+    }
+
     /// Returns an opaque number representing the version of the driver of this device.
     ///
     /// The meaning of this number is implementation-specific. It can be used in bug reports, for
@@ -807,6 +1123,79 @@ impl<'a> PhysicalDevice<'a> {
         &self.infos().properties.pipelineCacheUUID
     }
 
+    /// Picks the most suitable physical device of `instance` for an application.
+    ///
+    /// The devices that don't support all of `required_extensions` and `required_features`, that
+    /// don't have a queue family that supports graphics operations, or that can't present to
+    /// `surface` if it is `Some`, are skipped. The remaining devices are ranked by their type
+    /// first, with discrete GPUs being preferred over integrated GPUs, then by the number of
+    /// `preferred_extensions` that they support, then by their limits.
+    ///
+    /// The selection is deterministic: if two devices have the same rank, the one with the lowest
+    /// index is returned. Returns `None` if no device is suitable.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the surface doesn't belong to `instance`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vulkano::instance::Instance;
+    /// # use vulkano::instance::InstanceExtensions;
+    /// use vulkano::device::DeviceExtensions;
+    /// use vulkano::instance::Features;
+    /// use vulkano::instance::PhysicalDevice;
+    ///
+    /// # let instance = Instance::new(None, &InstanceExtensions::none(), None).unwrap();
+    /// let required = DeviceExtensions { khr_swapchain: true, .. DeviceExtensions::none() };
+    /// let physical = PhysicalDevice::rank_and_select(&instance, &required,
+    ///                                                &DeviceExtensions::none(),
+    ///                                                &Features::none(), None)
+    ///     .expect("no suitable device available");
+    /// ```
+    pub fn rank_and_select(instance: &'a Arc<Instance>, required_extensions: &DeviceExtensions,
+                           preferred_extensions: &DeviceExtensions,
+                           required_features: &Features, surface: Option<&Surface>)
+                           -> Option<PhysicalDevice<'a>>
+    {
+        let scores = PhysicalDevice::enumerate(instance).map(|physical| {
+            physical.selection_score(required_extensions, preferred_extensions, required_features,
+                                     surface)
+        });
+
+        select_best(scores).map(|index| PhysicalDevice::from_index(instance, index).unwrap())
+    }
+
+    // Returns the score of this device for `rank_and_select`, or `None` if it is not suitable.
+    fn selection_score(&self, required_extensions: &DeviceExtensions,
+                       preferred_extensions: &DeviceExtensions, required_features: &Features,
+                       surface: Option<&Surface>) -> Option<u64>
+    {
+        let supported = DeviceExtensions::supported_by_device(self);
+        if required_extensions.difference(&supported) != DeviceExtensions::none() {
+            return None;
+        }
+
+        if !self.supported_features().superset_of(required_features) {
+            return None;
+        }
+
+        if !self.queue_families().any(|q| q.supports_graphics()) {
+            return None;
+        }
+
+        if let Some(surface) = surface {
+            if !self.queue_families().any(|q| q.supports_surface(surface).unwrap_or(false)) {
+                return None;
+            }
+        }
+
+        let preferred = RawDeviceExtensions::from(&preferred_extensions.intersection(&supported));
+        Some(device_score(self.ty(), preferred.iter().count(),
+                          self.limits().max_image_dimension_2d()))
+    }
+
     // Internal function to make it easier to get the infos of this device.
     #[inline]
     fn infos(&self) -> &'a PhysicalDeviceInfos {
@@ -823,6 +1212,45 @@ unsafe impl<'a> VulkanObject for PhysicalDevice<'a> {
     }
 }
 
+// Computes the score of a suitable device for `rank_and_select`. The type of the device always
+// matters more than the extensions, which always matter more than the limits.
+fn device_score(ty: PhysicalDeviceType, num_preferred_extensions: usize,
+                max_image_dimension_2d: u32) -> u64
+{
+    let ty_score = match ty {
+        PhysicalDeviceType::DiscreteGpu => 4,
+        PhysicalDeviceType::IntegratedGpu => 3,
+        PhysicalDeviceType::VirtualGpu => 2,
+        PhysicalDeviceType::Cpu => 1,
+        PhysicalDeviceType::Other => 0,
+    };
+
+    ty_score << 48 | (num_preferred_extensions as u64 & 0xffff) << 32 |
+        max_image_dimension_2d as u64
+}
+
+// Returns the index of the element with the highest score, or the first one in case of a tie.
+// Elements that are `None` are skipped.
+fn select_best<I>(scores: I) -> Option<usize>
+    where I: IntoIterator<Item = Option<u64>>
+{
+    let mut best: Option<(usize, u64)> = None;
+
+    for (index, score) in scores.into_iter().enumerate() {
+        let score = match score {
+            Some(s) => s,
+            None => continue,
+        };
+
+        match best {
+            Some((_, best_score)) if best_score >= score => (),
+            _ => best = Some((index, score)),
+        }
+    }
+
+    best.map(|(index, _)| index)
+}
+
 /// Iterator for all the physical devices available on hardware.
 #[derive(Debug, Clone)]
 pub struct PhysicalDevicesIter<'a> {
@@ -931,6 +1359,20 @@ impl<'a> QueueFamily<'a> {
         (self.flags() & vk::QUEUE_SPARSE_BINDING_BIT) != 0
     }
 
+    /// Returns true if queues of this family can present images on `surface`.
+    ///
+    /// This is the same as `Surface::is_supported`. You should use it when choosing the queue
+    /// family that is going to present the images of a swapchain.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the queue family and the surface don't belong to the same instance.
+    ///
+    #[inline]
+    pub fn supports_surface(&self, surface: &Surface) -> Result<bool, CapabilitiesError> {
+        surface.is_supported(*self)
+    }
+
     /// Internal utility function that returns the flags of this queue family.
     #[inline]
     fn flags(&self) -> u32 {
@@ -1306,4 +1748,32 @@ mod tests {
         let by_id = phys.queue_family_by_id(queue_family.id()).unwrap();
         assert_eq!(by_id.id(), queue_family.id());
     }
+
+    #[test]
+    fn device_score_order() {
+        use super::device_score;
+        use instance::PhysicalDeviceType;
+
+        let discrete = device_score(PhysicalDeviceType::DiscreteGpu, 0, 4096);
+        let integrated = device_score(PhysicalDeviceType::IntegratedGpu, 3, 16384);
+        let integrated_small = device_score(PhysicalDeviceType::IntegratedGpu, 3, 8192);
+        let integrated_no_ext = device_score(PhysicalDeviceType::IntegratedGpu, 0, 16384);
+        let cpu = device_score(PhysicalDeviceType::Cpu, 5, 16384);
+
+        assert!(discrete > integrated);
+        assert!(integrated > integrated_small);
+        assert!(integrated_small > integrated_no_ext);
+        assert!(integrated_no_ext > cpu);
+    }
+
+    #[test]
+    fn select_best_device() {
+        use super::select_best;
+
+        assert_eq!(select_best(vec![]), None);
+        assert_eq!(select_best(vec![None, None]), None);
+        assert_eq!(select_best(vec![Some(3), None, Some(7), Some(5)]), Some(2));
+        // Ties are resolved deterministically in favor of the first device.
+        assert_eq!(select_best(vec![None, Some(7), Some(7)]), Some(1));
+    }
 }
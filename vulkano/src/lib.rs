@@ -191,12 +191,14 @@ pub enum Error {
     IncompatibleDriver = vk::ERROR_INCOMPATIBLE_DRIVER,
     TooManyObjects = vk::ERROR_TOO_MANY_OBJECTS,
     FormatNotSupported = vk::ERROR_FORMAT_NOT_SUPPORTED,
+    FragmentedPool = vk::ERROR_FRAGMENTED_POOL,
     SurfaceLost = vk::ERROR_SURFACE_LOST_KHR,
     NativeWindowInUse = vk::ERROR_NATIVE_WINDOW_IN_USE_KHR,
     OutOfDate = vk::ERROR_OUT_OF_DATE_KHR,
     IncompatibleDisplay = vk::ERROR_INCOMPATIBLE_DISPLAY_KHR,
     ValidationFailed = vk::ERROR_VALIDATION_FAILED_EXT,
     OutOfPoolMemory = vk::ERROR_OUT_OF_POOL_MEMORY_KHR,
+    InvalidExternalHandle = vk::ERROR_INVALID_EXTERNAL_HANDLE_KHR,
 }
 
 /// Checks whether the result returned correctly.
@@ -219,6 +221,7 @@ fn check_errors(result: vk::Result) -> Result<Success, Error> {
         vk::ERROR_INCOMPATIBLE_DRIVER => Err(Error::IncompatibleDriver),
         vk::ERROR_TOO_MANY_OBJECTS => Err(Error::TooManyObjects),
         vk::ERROR_FORMAT_NOT_SUPPORTED => Err(Error::FormatNotSupported),
+        vk::ERROR_FRAGMENTED_POOL => Err(Error::FragmentedPool),
         vk::ERROR_SURFACE_LOST_KHR => Err(Error::SurfaceLost),
         vk::ERROR_NATIVE_WINDOW_IN_USE_KHR => Err(Error::NativeWindowInUse),
         vk::SUBOPTIMAL_KHR => Ok(Success::Suboptimal),
@@ -226,6 +229,7 @@ fn check_errors(result: vk::Result) -> Result<Success, Error> {
         vk::ERROR_INCOMPATIBLE_DISPLAY_KHR => Err(Error::IncompatibleDisplay),
         vk::ERROR_VALIDATION_FAILED_EXT => Err(Error::ValidationFailed),
         vk::ERROR_OUT_OF_POOL_MEMORY_KHR => Err(Error::OutOfPoolMemory),
+        vk::ERROR_INVALID_EXTERNAL_HANDLE_KHR => Err(Error::InvalidExternalHandle),
         c => unreachable!("Unexpected error code returned by Vulkan: {}", c)
     }
 }
@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
 use std::fmt;
 use std::mem;
 use std::ptr;
@@ -14,12 +15,17 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::ops::Range;
 use std::os::raw::c_void;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
 use std::sync::Arc;
 
 use instance::MemoryType;
 use device::Device;
 use device::DeviceOwned;
 use memory::Content;
+use memory::ExternalMemoryError;
+use memory::ExternalMemoryHandleType;
+use Error;
 use OomError;
 use VulkanObject;
 use check_errors;
@@ -45,6 +51,8 @@ pub struct DeviceMemory {
     device: Arc<Device>,
     size: usize,
     memory_type_index: u32,
+    // Handle type the memory can be exported as, if any.
+    export_handle_type: Option<ExternalMemoryHandleType>,
 }
 
 impl DeviceMemory {
@@ -62,6 +70,114 @@ impl DeviceMemory {
     #[inline]
     pub fn alloc(device: Arc<Device>, memory_type: MemoryType, size: usize)
                  -> Result<DeviceMemory, OomError>
+    {
+        unsafe {
+            Ok(try!(DeviceMemory::alloc_raw(device, memory_type, size, ptr::null(), None)))
+        }
+    }
+
+    /// Allocates a chunk of memory that can be exported as an operating system handle of the
+    /// given type.
+    ///
+    /// Buffers and images that are bound to this memory must have been created with the same
+    /// handle type, for example with `UnsafeBuffer::with_external_memory`. These constructors
+    /// check that the physical device supports the handle type for the resource, and binding
+    /// checks in debug mode that the handle types match.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `size` is 0.
+    /// - Panics if `memory_type` doesn't belong to the same physical device as `device`.
+    ///
+    pub fn alloc_exportable(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                            handle_type: ExternalMemoryHandleType)
+                            -> Result<DeviceMemory, ExternalMemoryError>
+    {
+        try!(check_handle_type_extensions(&device, handle_type));
+
+        let export = vk::ExportMemoryAllocateInfoKHR {
+            sType: vk::STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO_KHR,
+            pNext: ptr::null(),
+            handleTypes: handle_type as u32,
+        };
+
+        unsafe {
+            Ok(try!(DeviceMemory::alloc_raw(device, memory_type, size,
+                                            &export as *const _ as *const _,
+                                            Some(handle_type))))
+        }
+    }
+
+    /// Imports memory from a POSIX file descriptor that has been exported by another API or
+    /// process.
+    ///
+    /// On success, the ownership of the file descriptor is transferred to the Vulkan
+    /// implementation, and you must not use or close it afterwards.
+    ///
+    /// # Safety
+    ///
+    /// - `fd` must be an opaque file descriptor exported by a Vulkan implementation that refers
+    ///   to at least `size` bytes of memory, and that is compatible with `memory_type`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `size` is 0.
+    /// - Panics if `memory_type` doesn't belong to the same physical device as `device`.
+    ///
+    #[cfg(unix)]
+    pub unsafe fn import_fd(device: Arc<Device>, memory_type: MemoryType, size: usize, fd: RawFd)
+                            -> Result<DeviceMemory, ExternalMemoryError>
+    {
+        try!(check_handle_type_extensions(&device, ExternalMemoryHandleType::OpaqueFd));
+
+        let import = vk::ImportMemoryFdInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_MEMORY_FD_INFO_KHR,
+            pNext: ptr::null(),
+            handleType: vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR,
+            fd: fd,
+        };
+
+        Ok(try!(DeviceMemory::alloc_raw(device, memory_type, size,
+                                        &import as *const _ as *const _, None)))
+    }
+
+    /// Imports memory from a Win32 handle that has been exported by another API or process.
+    ///
+    /// Contrary to file descriptors, importing a Win32 handle doesn't transfer its ownership.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a handle of type `handle_type` that refers to at least `size` bytes of
+    ///   memory, and that is compatible with `memory_type`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `size` is 0.
+    /// - Panics if `memory_type` doesn't belong to the same physical device as `device`.
+    ///
+    #[cfg(windows)]
+    pub unsafe fn import_win32_handle(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                                      handle_type: ExternalMemoryHandleType, handle: *mut c_void)
+                                      -> Result<DeviceMemory, ExternalMemoryError>
+    {
+        try!(check_handle_type_extensions(&device, handle_type));
+
+        let import = vk::ImportMemoryWin32HandleInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_MEMORY_WIN32_HANDLE_INFO_KHR,
+            pNext: ptr::null(),
+            handleType: handle_type as u32,
+            handle: handle,
+            name: ptr::null(),
+        };
+
+        Ok(try!(DeviceMemory::alloc_raw(device, memory_type, size,
+                                        &import as *const _ as *const _, None)))
+    }
+
+    // Allocates memory with the given `pNext` chain in the allocate infos.
+    unsafe fn alloc_raw(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                        next: *const c_void, export_handle_type: Option<ExternalMemoryHandleType>)
+                        -> Result<DeviceMemory, Error>
     {
         assert!(size >= 1);
         assert_eq!(device.physical_device().internal_object(),
@@ -74,12 +190,29 @@ impl DeviceMemory {
             return Err(OomError::OutOfDeviceMemory);
         }*/
 
-        let memory = unsafe {
+        // When the `buffer_device_address` feature is enabled, all allocations can hold buffers
+        // whose device address is queried. The allocator doesn't know which buffers are going to
+        // be bound, so we always pass the flag. The struct comes from `khr_device_group`, which
+        // the device requires alongside this feature.
+        let flags_infos = vk::MemoryAllocateFlagsInfoKHR {
+            sType: vk::STRUCTURE_TYPE_MEMORY_ALLOCATE_FLAGS_INFO_KHR,
+            pNext: next,
+            flags: vk::MEMORY_ALLOCATE_DEVICE_ADDRESS_BIT_KHR,
+            deviceMask: 0,
+        };
+
+        let next = if device.enabled_features().buffer_device_address {
+            &flags_infos as *const _ as *const _
+        } else {
+            next
+        };
+
+        let memory = {
             let vk = device.pointers();
 
             let infos = vk::MemoryAllocateInfo {
                 sType: vk::STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO,
-                pNext: ptr::null(),
+                pNext: next,
                 allocationSize: size as u64,
                 memoryTypeIndex: memory_type.id(),
             };
@@ -95,6 +228,7 @@ impl DeviceMemory {
             device: device,
             size: size,
             memory_type_index: memory_type.id(),
+            export_handle_type: export_handle_type,
         })
     }
 
@@ -114,6 +248,7 @@ impl DeviceMemory {
         let mem = try!(DeviceMemory::alloc(device.clone(), memory_type, size));
 
         let coherent = memory_type.is_host_coherent();
+        let atom_size = device.physical_device().limits().non_coherent_atom_size() as usize;
 
         let ptr = unsafe {
             let mut output = mem::uninitialized();
@@ -127,6 +262,7 @@ impl DeviceMemory {
             memory: mem,
             pointer: ptr,
             coherent: coherent,
+            atom_size: atom_size,
         })
     }
 
@@ -141,6 +277,92 @@ impl DeviceMemory {
     pub fn size(&self) -> usize {
         self.size
     }
+
+    /// Returns the handle type this memory can be exported as, if it was allocated with
+    /// `alloc_exportable`.
+    #[inline]
+    pub fn export_handle_type(&self) -> Option<ExternalMemoryHandleType> {
+        self.export_handle_type
+    }
+
+    /// Exports the memory as a POSIX file descriptor.
+    ///
+    /// The memory must have been allocated with `alloc_exportable` and the `OpaqueFd` handle
+    /// type. Each call returns a new file descriptor, whose ownership belongs to the caller.
+    #[cfg(unix)]
+    pub fn export_fd(&self) -> Result<RawFd, ExternalMemoryError> {
+        if self.export_handle_type != Some(ExternalMemoryHandleType::OpaqueFd) {
+            return Err(ExternalMemoryError::NotExportable);
+        }
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::MemoryGetFdInfoKHR {
+                sType: vk::STRUCTURE_TYPE_MEMORY_GET_FD_INFO_KHR,
+                pNext: ptr::null(),
+                memory: self.memory,
+                handleType: vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.GetMemoryFdKHR(self.device.internal_object(), &infos,
+                                                &mut output)));
+            Ok(output)
+        }
+    }
+
+    /// Exports the memory as a Win32 handle.
+    ///
+    /// The memory must have been allocated with `alloc_exportable` and the same handle type.
+    /// The ownership of the returned handle belongs to the caller, which must close it with
+    /// `CloseHandle` if it is an NT handle.
+    #[cfg(windows)]
+    pub fn export_win32_handle(&self) -> Result<*mut c_void, ExternalMemoryError> {
+        let handle_type = match self.export_handle_type {
+            Some(ty @ ExternalMemoryHandleType::OpaqueWin32) => ty,
+            Some(ty @ ExternalMemoryHandleType::OpaqueWin32Kmt) => ty,
+            _ => return Err(ExternalMemoryError::NotExportable),
+        };
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::MemoryGetWin32HandleInfoKHR {
+                sType: vk::STRUCTURE_TYPE_MEMORY_GET_WIN32_HANDLE_INFO_KHR,
+                pNext: ptr::null(),
+                memory: self.memory,
+                handleType: handle_type as u32,
+            };
+
+            let mut output = ptr::null_mut();
+            try!(check_errors(vk.GetMemoryWin32HandleKHR(self.device.internal_object(), &infos,
+                                                         &mut output)));
+            Ok(output)
+        }
+    }
+}
+
+// Checks that the device extensions required by a handle type are enabled.
+fn check_handle_type_extensions(device: &Device, handle_type: ExternalMemoryHandleType)
+                                -> Result<(), ExternalMemoryError>
+{
+    let extensions = device.loaded_extensions();
+
+    if !extensions.khr_external_memory {
+        return Err(ExternalMemoryError::ExtensionNotEnabled);
+    }
+
+    let supported = match handle_type {
+        ExternalMemoryHandleType::OpaqueFd => extensions.khr_external_memory_fd,
+        _ => extensions.khr_external_memory_win32,
+    };
+
+    if !supported {
+        return Err(ExternalMemoryError::ExtensionNotEnabled);
+    }
+
+    Ok(())
 }
 
 unsafe impl DeviceOwned for DeviceMemory {
@@ -212,6 +434,9 @@ pub struct MappedDeviceMemory {
     memory: DeviceMemory,
     pointer: *mut c_void,
     coherent: bool,
+    // Value of the `non_coherent_atom_size` limit. Ranges of non-coherent memory that are flushed
+    // or invalidated must be aligned to it.
+    atom_size: usize,
 }
 
 // Note that `MappedDeviceMemory` doesn't implement `Drop`, as we don't need to unmap memory before
@@ -240,6 +465,9 @@ impl MappedDeviceMemory {
     /// smallest range as possible, and to not call this function multiple times in a row for
     /// several small changes.
     ///
+    /// If the memory is not host-coherent, the range that is flushed and invalidated is enlarged
+    /// to a multiple of the `non_coherent_atom_size` limit of the physical device.
+    ///
     /// # Safety
     ///
     /// - Type safety is not checked. You must ensure that `T` corresponds to the content of the
@@ -250,18 +478,44 @@ impl MappedDeviceMemory {
     #[inline]
     pub unsafe fn read_write<T: ?Sized>(&self, range: Range<usize>) -> CpuAccess<T>
         where T: Content
+    {
+        self.access(range, true)
+    }
+
+    /// Gives read-only access to the content of the memory.
+    ///
+    /// Same as `read_write`, except that the range is not flushed with
+    /// `vkFlushMappedMemoryRanges` when the `CpuAccess` is destroyed. Multiple `CpuAccess`
+    /// objects returned by this function can therefore be alive at the same time without
+    /// overwriting what the device wrote in the meanwhile.
+    ///
+    /// # Safety
+    ///
+    /// - Same as `read_write`.
+    /// - You must not modify the content of the memory through the returned `CpuAccess`.
+    ///
+    #[inline]
+    pub unsafe fn read<T: ?Sized>(&self, range: Range<usize>) -> CpuAccess<T>
+        where T: Content
+    {
+        self.access(range, false)
+    }
+
+    unsafe fn access<T: ?Sized>(&self, range: Range<usize>, flush: bool) -> CpuAccess<T>
+        where T: Content
     {
         let vk = self.memory.device().pointers();
         let pointer = T::ref_from_ptr((self.pointer as usize + range.start) as *mut _,
                                       range.end - range.start).unwrap();       // TODO: error
 
         if !self.coherent {
+            let atom_range = align_to_atom(range.clone(), self.atom_size, self.memory.size());
             let range = vk::MappedMemoryRange {
                 sType: vk::STRUCTURE_TYPE_MAPPED_MEMORY_RANGE,
                 pNext: ptr::null(),
                 memory: self.memory.internal_object(),
-                offset: range.start as u64,
-                size: (range.end - range.start) as u64,
+                offset: atom_range.start as u64,
+                size: (atom_range.end - atom_range.start) as u64,
             };
 
             // TODO: check result?
@@ -272,6 +526,7 @@ impl MappedDeviceMemory {
             pointer: pointer,
             mem: self,
             coherent: self.coherent,
+            flush: flush,
             range: range,
         }
     }
@@ -316,6 +571,8 @@ pub struct CpuAccess<'a, T: ?Sized + 'a> {
     pointer: *mut T,
     mem: &'a MappedDeviceMemory,
     coherent: bool,
+    // True if the range must be flushed on drop.
+    flush: bool,
     range: Range<usize>,
 }
 
@@ -334,9 +591,16 @@ impl<'a, T: ?Sized + 'a> CpuAccess<'a, T> {
             pointer: f(self.pointer),
             mem: self.mem,
             coherent: self.coherent,
+            flush: self.flush,
             range: self.range.clone(),  // TODO: ?
         }
     }
+
+    /// Returns a pointer to the mapped memory.
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *const T {
+        self.pointer
+    }
 }
 
 unsafe impl<'a, T: ?Sized + 'a> Send for CpuAccess<'a, T> {}
@@ -362,15 +626,17 @@ impl<'a, T: ?Sized + 'a> Drop for CpuAccess<'a, T> {
     #[inline]
     fn drop(&mut self) {
         // If the memory doesn't have the `coherent` flag, we need to flush the data.
-        if !self.coherent {
+        if !self.coherent && self.flush {
             let vk = self.mem.as_ref().device().pointers();
 
+            let atom_range = align_to_atom(self.range.clone(), self.mem.atom_size,
+                                           self.mem.as_ref().size());
             let range = vk::MappedMemoryRange {
                 sType: vk::STRUCTURE_TYPE_MAPPED_MEMORY_RANGE,
                 pNext: ptr::null(),
                 memory: self.mem.as_ref().internal_object(),
-                offset: self.range.start as u64,
-                size: (self.range.end - self.range.start) as u64,
+                offset: atom_range.start as u64,
+                size: (atom_range.end - atom_range.start) as u64,
             };
 
             // TODO: check result?
@@ -382,10 +648,36 @@ impl<'a, T: ?Sized + 'a> Drop for CpuAccess<'a, T> {
     }
 }
 
+// Enlarges `range` so that it can be flushed or invalidated on non-coherent memory.
+//
+// The Vulkan specs require the offset to be a multiple of `atom_size`, and the size to be either
+// a multiple of `atom_size` or to reach the end of the memory.
+#[inline]
+fn align_to_atom(range: Range<usize>, atom_size: usize, memory_size: usize) -> Range<usize> {
+    debug_assert!(atom_size.is_power_of_two());
+    let start = range.start & !(atom_size - 1);
+    let end = (range.end + atom_size - 1) & !(atom_size - 1);
+    start .. cmp::min(end, memory_size)
+}
+
 #[cfg(test)]
 mod tests {
     use OomError;
     use memory::DeviceMemory;
+    use super::align_to_atom;
+
+    #[test]
+    fn atom_alignment() {
+        // Already aligned.
+        assert_eq!(align_to_atom(64 .. 128, 64, 1024), 64 .. 128);
+        // Unaligned start and end.
+        assert_eq!(align_to_atom(70 .. 130, 64, 1024), 64 .. 192);
+        // A partial write at the end of the memory flushes until the end.
+        assert_eq!(align_to_atom(1000 .. 1010, 64, 1010), 960 .. 1010);
+        assert_eq!(align_to_atom(1000 .. 1010, 64, 1024), 960 .. 1024);
+        // Coherent-like atom size of 1 doesn't change anything.
+        assert_eq!(align_to_atom(3 .. 7, 1, 1024), 3 .. 7);
+    }
 
     #[test]
     fn create() {
@@ -0,0 +1,152 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+
+use Error;
+use OomError;
+use vk;
+
+/// Type of an operating system handle that refers to device memory.
+///
+/// Such handles can be used to share memory with another API (CUDA, OpenGL, ...) or with another
+/// process.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ExternalMemoryHandleType {
+    /// A POSIX file descriptor whose only valid use is to be imported in a Vulkan implementation
+    /// (or in an API that supports this file descriptor type).
+    ///
+    /// Requires the `khr_external_memory_fd` device extension.
+    OpaqueFd = vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR,
+
+    /// A Win32 NT handle whose only valid use is to be imported in a Vulkan implementation (or in
+    /// an API that supports this handle type).
+    ///
+    /// Requires the `khr_external_memory_win32` device extension.
+    OpaqueWin32 = vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR,
+
+    /// Same as `OpaqueWin32`, but the handle is a global share handle instead of an NT handle.
+    OpaqueWin32Kmt = vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR,
+
+    /// An NT handle returned by `IDXGIResource1::CreateSharedHandle` for a Direct3D 11 texture.
+    D3d11Texture = vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_BIT_KHR,
+
+    /// A global share handle returned by `IDXGIResource::GetSharedHandle` for a Direct3D 11
+    /// texture.
+    D3d11TextureKmt = vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_KMT_BIT_KHR,
+
+    /// An NT handle returned by `ID3D12Device::CreateSharedHandle` for a Direct3D 12 heap.
+    D3d12Heap = vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_HEAP_BIT_KHR,
+
+    /// An NT handle returned by `ID3D12Device::CreateSharedHandle` for a Direct3D 12 committed
+    /// resource.
+    D3d12Resource = vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_RESOURCE_BIT_KHR,
+}
+
+/// Capabilities of the implementation regarding a type of external memory handle.
+///
+/// Obtained by calling `PhysicalDevice::external_buffer_properties` or
+/// `PhysicalDevice::external_image_properties`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalMemoryProperties {
+    /// The memory must be allocated for a single buffer or image when exported or imported.
+    pub dedicated_only: bool,
+    /// Memory can be exported as a handle of this type.
+    pub exportable: bool,
+    /// A handle of this type can be imported as memory.
+    pub importable: bool,
+}
+
+#[doc(hidden)]
+impl From<vk::ExternalMemoryPropertiesKHR> for ExternalMemoryProperties {
+    #[inline]
+    fn from(props: vk::ExternalMemoryPropertiesKHR) -> ExternalMemoryProperties {
+        let features = props.externalMemoryFeatures;
+        ExternalMemoryProperties {
+            dedicated_only: (features & vk::EXTERNAL_MEMORY_FEATURE_DEDICATED_ONLY_BIT_KHR) != 0,
+            exportable: (features & vk::EXTERNAL_MEMORY_FEATURE_EXPORTABLE_BIT_KHR) != 0,
+            importable: (features & vk::EXTERNAL_MEMORY_FEATURE_IMPORTABLE_BIT_KHR) != 0,
+        }
+    }
+}
+
+/// Error that can happen when exporting or importing memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExternalMemoryError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The device extension required by the handle type was not enabled.
+    ExtensionNotEnabled,
+
+    /// The memory was not allocated with the requested handle type as an export type.
+    NotExportable,
+
+    /// The handle to import is not valid, or is not of the given type.
+    InvalidExternalHandle,
+
+    /// Too many memory objects have been created.
+    TooManyObjects,
+}
+
+impl error::Error for ExternalMemoryError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ExternalMemoryError::OomError(_) => "not enough memory available",
+            ExternalMemoryError::ExtensionNotEnabled => {
+                "the device extension required by the handle type was not enabled"
+            },
+            ExternalMemoryError::NotExportable => {
+                "the memory was not allocated with the requested handle type as an export type"
+            },
+            ExternalMemoryError::InvalidExternalHandle => {
+                "the handle to import is not valid"
+            },
+            ExternalMemoryError::TooManyObjects => "too many memory objects have been created",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ExternalMemoryError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ExternalMemoryError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ExternalMemoryError {
+    #[inline]
+    fn from(err: OomError) -> ExternalMemoryError {
+        ExternalMemoryError::OomError(err)
+    }
+}
+
+impl From<Error> for ExternalMemoryError {
+    #[inline]
+    fn from(err: Error) -> ExternalMemoryError {
+        match err {
+            err @ Error::OutOfHostMemory => ExternalMemoryError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => ExternalMemoryError::OomError(OomError::from(err)),
+            Error::InvalidExternalHandle => ExternalMemoryError::InvalidExternalHandle,
+            Error::TooManyObjects => ExternalMemoryError::TooManyObjects,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
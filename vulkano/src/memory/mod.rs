@@ -84,6 +84,26 @@
 //! trait on your own structure and then use it when you create buffers and images so that they
 //! get memory from that pool. By default if you don't specify any pool when creating a buffer or
 //! an image, an instance of `StdMemoryPool` that is shared by the `Device` object is used.
+//!
+//! The memory type that a buffer or an image is allocated from is chosen automatically. If you
+//! need more control, for example to get host-cached memory for reading back data, some
+//! constructors accept a `MemoryPreference` that describes the required and preferred properties
+//! of the memory type.
+//!
+//! # External memory
+//!
+//! If the `khr_external_memory` device extension is enabled, memory can be shared with other APIs
+//! or processes through operating system handles. Use `DeviceMemory::alloc_exportable()` to
+//! allocate memory that can later be exported with `export_fd()` (or `export_win32_handle()` on
+//! Windows), and `DeviceMemory::import_fd()` (or `import_win32_handle()`) to turn a handle into a
+//! `DeviceMemory`. Buffers and images bound to such memory must be created with their respective
+//! `with_external_memory` constructors.
+//!
+//! Whether a given handle type is supported for a buffer or an image can be queried with
+//! `PhysicalDevice::external_buffer_properties()` and
+//! `PhysicalDevice::external_image_properties()`. The `with_external_memory` constructors return
+//! an error if it isn't, or if these queries aren't available because the
+//! `khr_external_memory_capabilities` instance extension is not enabled.
 
 use std::mem;
 use std::os::raw::c_void;
@@ -94,10 +114,18 @@ use vk;
 pub use self::device_memory::CpuAccess;
 pub use self::device_memory::DeviceMemory;
 pub use self::device_memory::MappedDeviceMemory;
+pub use self::external_memory::ExternalMemoryError;
+pub use self::external_memory::ExternalMemoryHandleType;
+pub use self::external_memory::ExternalMemoryProperties;
 pub use self::pool::MemoryPool;
+pub use self::preference::MemoryAllocError;
+pub use self::preference::MemoryPreference;
+pub use self::preference::MemoryProperties;
 
 mod device_memory;
+mod external_memory;
 pub mod pool;
+mod preference;
 
 /// Represents requirements expressed by the Vulkan implementation.
 #[derive(Debug, Copy, Clone)]
@@ -7,6 +7,8 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
+
 use instance::MemoryType;
 use memory::DeviceMemory;
 use memory::MappedDeviceMemory;
@@ -23,6 +25,19 @@ mod host_visible;
 mod non_host_visible;
 mod pool;
 
+/// Default size of the `DeviceMemory` objects that the standard pools allocate and suballocate
+/// from.
+///
+/// The pools never use blocks larger than an eighth of the size of the memory heap, therefore
+/// the actual size is smaller on small heaps.
+pub const DEFAULT_BLOCK_SIZE: usize = 256 * 1024 * 1024;        // 256 MB
+
+// Returns the size of the blocks that a pool that operates on a heap of `heap_size` bytes
+// allocates, when `block_size` is requested.
+pub(crate) fn clamp_block_size(block_size: usize, heap_size: usize) -> usize {
+    cmp::max(1, cmp::min(block_size, heap_size / 8))
+}
+
 /// Pool of GPU-visible memory that can be allocated from.
 pub unsafe trait MemoryPool {
     /// Object that represents a single allocation. Its destructor should free the chunk.
@@ -30,6 +45,10 @@ pub unsafe trait MemoryPool {
 
     /// Allocates memory from the pool.
     ///
+    /// Returns an `OomError` if there isn't enough device or host memory left. The standard
+    /// pools never panic in this situation, which allows the caller to free some memory and try
+    /// again.
+    ///
     /// # Safety
     ///
     /// - The returned object must match the requirements.
@@ -61,6 +80,19 @@ pub unsafe trait MemoryPoolAlloc {
     fn offset(&self) -> usize;
 }
 
+/// Memory usage of a `StdMemoryPool` on a memory heap. Returned by `StdMemoryPool::statistics`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryHeapStatistics {
+    /// Id of the memory heap.
+    pub heap: u32,
+    /// Total size in bytes of the `DeviceMemory` objects that the pool allocated from the heap.
+    pub allocated: usize,
+    /// Number of bytes that are currently handed out to buffers and images.
+    pub in_use: usize,
+    /// Number of `DeviceMemory` objects that the pool allocated from the heap.
+    pub device_memory_objects: usize,
+}
+
 /// Layout of the object being allocated.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum AllocLayout {
@@ -69,3 +101,22 @@ pub enum AllocLayout {
     /// The object has an optimal layout.
     Optimal,
 }
+
+#[cfg(test)]
+mod tests {
+    use memory::pool::DEFAULT_BLOCK_SIZE;
+    use memory::pool::clamp_block_size;
+
+    #[test]
+    fn block_size_large_heap() {
+        let heap = 8 * DEFAULT_BLOCK_SIZE;
+        assert_eq!(clamp_block_size(DEFAULT_BLOCK_SIZE, heap), DEFAULT_BLOCK_SIZE);
+    }
+
+    #[test]
+    fn block_size_small_heap() {
+        let heap = 256 * 1024 * 1024;
+        assert_eq!(clamp_block_size(DEFAULT_BLOCK_SIZE, heap), 32 * 1024 * 1024);
+        assert_eq!(clamp_block_size(1024, heap), 1024);
+    }
+}
@@ -16,6 +16,9 @@ use device::Device;
 use instance::Instance;
 use instance::MemoryType;
 use memory::DeviceMemory;
+use memory::pool::DEFAULT_BLOCK_SIZE;
+use memory::pool::clamp_block_size;
+use memory::pool::MemoryHeapStatistics;
 use OomError;
 
 /// Memory pool that operates on a given memory type.
@@ -23,6 +26,8 @@ use OomError;
 pub struct StdNonHostVisibleMemoryTypePool {
     device: Arc<Device>,
     memory_type: u32,
+    // Size of the `DeviceMemory` objects that are allocated by the pool.
+    block_size: usize,
     // TODO: obviously very inefficient
     occupied: Mutex<Vec<(Arc<DeviceMemory>, Vec<Range<usize>>)>>,
 }
@@ -38,6 +43,25 @@ impl StdNonHostVisibleMemoryTypePool {
     pub fn new(device: Arc<Device>, memory_type: MemoryType)
                -> Arc<StdNonHostVisibleMemoryTypePool>
     {
+        StdNonHostVisibleMemoryTypePool::with_block_size(device, memory_type, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Same as `new`, but allows choosing the size of the `DeviceMemory` objects that the pool
+    /// allocates and suballocates from.
+    ///
+    /// The block size is clamped to an eighth of the size of the memory heap, so that a single
+    /// block can't reserve most of a small heap. Allocations that are larger than the block size
+    /// get their own `DeviceMemory` object.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `device` and `memory_type` don't belong to the same physical device.
+    /// - Panics if `block_size` is 0.
+    ///
+    pub fn with_block_size(device: Arc<Device>, memory_type: MemoryType, block_size: usize)
+                           -> Arc<StdNonHostVisibleMemoryTypePool>
+    {
+        assert!(block_size != 0);
         assert_eq!(&**device.physical_device().instance() as *const Instance,
                    &**memory_type.physical_device().instance() as *const Instance);
         assert_eq!(device.physical_device().index(), memory_type.physical_device().index());
@@ -45,6 +69,7 @@ impl StdNonHostVisibleMemoryTypePool {
         Arc::new(StdNonHostVisibleMemoryTypePool {
             device: device.clone(),
             memory_type: memory_type.id(),
+            block_size: clamp_block_size(block_size, memory_type.heap().size()),
             occupied: Mutex::new(Vec::new()),
         })
     }
@@ -98,11 +123,19 @@ impl StdNonHostVisibleMemoryTypePool {
             }
         }
 
-        // We need to allocate a new block.
+        // We need to allocate a new block. Allocations that don't fit in a block get a dedicated
+        // `DeviceMemory` object.
         let new_block = {
-            const MIN_BLOCK_SIZE: usize = 8 * 1024 * 1024;      // 8 MB
-            let to_alloc = cmp::max(MIN_BLOCK_SIZE, size.next_power_of_two());
-            let new_block = try!(DeviceMemory::alloc(me.device.clone(), me.memory_type(), to_alloc));
+            let to_alloc = cmp::max(me.block_size, size);
+            let new_block = match DeviceMemory::alloc(me.device.clone(), me.memory_type(), to_alloc) {
+                Ok(block) => block,
+                // There may not be enough memory left for a whole block, but enough for this
+                // allocation alone.
+                Err(_) if to_alloc > size => {
+                    try!(DeviceMemory::alloc(me.device.clone(), me.memory_type(), size))
+                },
+                Err(err) => return Err(err),
+            };
             Arc::new(new_block)
         };
 
@@ -121,11 +154,31 @@ impl StdNonHostVisibleMemoryTypePool {
         &self.device
     }
 
+    /// Returns the size of the `DeviceMemory` objects allocated by this pool.
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
     /// Returns the memory type this pool operates on.
     #[inline]
     pub fn memory_type(&self) -> MemoryType {
         self.device.physical_device().memory_type_by_id(self.memory_type).unwrap()
     }
+
+    /// Adds the memory used by this pool to `stats`.
+    ///
+    /// The statistics are gathered while holding the lock used to allocate and free memory, and
+    /// are therefore consistent with each other.
+    pub fn add_statistics(&self, stats: &mut MemoryHeapStatistics) {
+        let occupied = self.occupied.lock().unwrap();
+
+        for &(ref dev_mem, ref entries) in occupied.iter() {
+            stats.allocated += dev_mem.size();
+            stats.in_use += entries.iter().map(|e| e.end - e.start).sum::<usize>();
+            stats.device_memory_objects += 1;
+        }
+    }
 }
 
 #[derive(Debug)]
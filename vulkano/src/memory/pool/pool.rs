@@ -17,6 +17,8 @@ use fnv::FnvHasher;
 use device::Device;
 use instance::MemoryType;
 use memory::pool::AllocLayout;
+use memory::pool::DEFAULT_BLOCK_SIZE;
+use memory::pool::MemoryHeapStatistics;
 use memory::pool::MemoryPool;
 use memory::pool::MemoryPoolAlloc;
 use memory::pool::StdHostVisibleMemoryTypePool;
@@ -31,6 +33,9 @@ use OomError;
 pub struct StdMemoryPool {
     device: Arc<Device>,
 
+    // Size of the `DeviceMemory` objects allocated by the pools.
+    block_size: usize,
+
     // For each memory type index, stores the associated pool.
     pools: Mutex<HashMap<(u32, AllocLayout), Pool, BuildHasherDefault<FnvHasher>>>,
 }
@@ -39,14 +44,68 @@ impl StdMemoryPool {
     /// Creates a new pool.
     #[inline]
     pub fn new(device: Arc<Device>) -> Arc<StdMemoryPool> {
+        StdMemoryPool::with_block_size(device, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Creates a new pool that allocates `DeviceMemory` objects of `block_size` bytes and
+    /// suballocates from them.
+    ///
+    /// A larger block size reduces the number of `DeviceMemory` objects, which is limited by the
+    /// `max_memory_allocation_count` limit of the device, at the cost of more unused memory.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `block_size` is 0.
+    ///
+    pub fn with_block_size(device: Arc<Device>, block_size: usize) -> Arc<StdMemoryPool> {
+        assert!(block_size != 0);
+
         let cap = device.physical_device().memory_types().len();
         let hasher = BuildHasherDefault::<FnvHasher>::default();
 
         Arc::new(StdMemoryPool {
             device: device.clone(),
+            block_size: block_size,
             pools: Mutex::new(HashMap::with_capacity_and_hasher(cap, hasher)),
         })
     }
+
+    /// Returns the size of the `DeviceMemory` objects allocated by this pool.
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Returns the memory usage of the pool, with one entry per memory heap of the physical
+    /// device, ordered by heap id.
+    ///
+    /// This can be used to display the amount of memory used by the application.
+    pub fn statistics(&self) -> Vec<MemoryHeapStatistics> {
+        let physical_device = self.device.physical_device();
+
+        let mut stats: Vec<_> = physical_device.memory_heaps().map(|heap| {
+            MemoryHeapStatistics {
+                heap: heap.id(),
+                allocated: 0,
+                in_use: 0,
+                device_memory_objects: 0,
+            }
+        }).collect();
+
+        let pools = self.pools.lock().unwrap();
+
+        for (&(memory_type, _), pool) in pools.iter() {
+            let heap = physical_device.memory_type_by_id(memory_type).unwrap().heap().id();
+            let entry = &mut stats[heap as usize];
+
+            match *pool {
+                Pool::HostVisible(ref pool) => pool.add_statistics(entry),
+                Pool::NonHostVisible(ref pool) => pool.add_statistics(entry),
+            }
+        }
+
+        stats
+    }
 }
 
 unsafe impl MemoryPool for Arc<StdMemoryPool> {
@@ -76,14 +135,18 @@ unsafe impl MemoryPool for Arc<StdMemoryPool> {
             Entry::Vacant(entry) => {
                 match memory_type.is_host_visible() {
                     true => {
-                        let pool = StdHostVisibleMemoryTypePool::new(self.device.clone(), memory_type);
+                        let pool = StdHostVisibleMemoryTypePool::with_block_size(self.device.clone(),
+                                                                                 memory_type,
+                                                                                 self.block_size);
                         entry.insert(Pool::HostVisible(pool.clone()));
                         let alloc = try!(StdHostVisibleMemoryTypePool::alloc(&pool, size, alignment));
                         let inner = StdMemoryPoolAllocInner::HostVisible(alloc);
                         Ok(StdMemoryPoolAlloc { inner: inner, pool: self.clone() })
                     },
                     false => {
-                        let pool = StdNonHostVisibleMemoryTypePool::new(self.device.clone(), memory_type);
+                        let pool = StdNonHostVisibleMemoryTypePool::with_block_size(self.device.clone(),
+                                                                                    memory_type,
+                                                                                    self.block_size);
                         entry.insert(Pool::NonHostVisible(pool.clone()));
                         let alloc = try!(StdNonHostVisibleMemoryTypePool::alloc(&pool, size, alignment));
                         let inner = StdMemoryPoolAllocInner::NonHostVisible(alloc);
@@ -148,3 +211,63 @@ enum StdMemoryPoolAllocInner {
     NonHostVisible(StdNonHostVisibleMemoryTypePoolAlloc),
     HostVisible(StdHostVisibleMemoryTypePoolAlloc),
 }
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use device::Device;
+    use memory::pool::AllocLayout;
+    use memory::pool::MemoryPool;
+    use memory::pool::MemoryPoolAlloc;
+    use memory::pool::StdMemoryPool;
+
+    fn in_use(pool: &StdMemoryPool) -> usize {
+        pool.statistics().iter().map(|s| s.in_use).sum()
+    }
+
+    #[test]
+    fn statistics_in_use() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let pool = Device::standard_pool(&device);
+
+        let before = in_use(&pool);
+
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), [0u8; 1024]).unwrap();
+        let during = in_use(&pool);
+        assert!(during >= before + 1024);
+
+        let stats = pool.statistics();
+        assert_eq!(stats.len(), device.physical_device().memory_heaps().len());
+        assert!(stats.iter().all(|s| s.in_use <= s.allocated));
+
+        drop(buffer);
+        assert_eq!(in_use(&pool), before);
+    }
+
+    #[test]
+    fn small_allocations_share_blocks() {
+        let (device, _) = gfx_dev_and_queue!();
+        let pool = StdMemoryPool::with_block_size(device.clone(), 64 * 1024);
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+
+        let allocs = (0 .. 100).map(|_| {
+            pool.alloc(mem_ty, 256, 256, AllocLayout::Linear).unwrap()
+        }).collect::<Vec<_>>();
+
+        let objects: usize = pool.statistics().iter().map(|s| s.device_memory_objects).sum();
+        assert_eq!(objects, 1);
+        assert!(allocs.iter().all(|a| a.memory().size() == 64 * 1024));
+    }
+
+    #[test]
+    fn large_allocation_is_dedicated() {
+        let (device, _) = gfx_dev_and_queue!();
+        let pool = StdMemoryPool::with_block_size(device.clone(), 64 * 1024);
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+
+        let alloc = pool.alloc(mem_ty, 256 * 1024, 256, AllocLayout::Linear).unwrap();
+        assert_eq!(alloc.memory().size(), 256 * 1024);
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+
+use instance::MemoryType;
+use instance::PhysicalDevice;
+use OomError;
+
+/// Properties of a memory type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MemoryProperties {
+    /// The memory is located on the device. See `MemoryType::is_device_local`.
+    pub device_local: bool,
+    /// The memory can be accessed by the host. See `MemoryType::is_host_visible`.
+    pub host_visible: bool,
+    /// Accesses by the host don't need to be flushed. See `MemoryType::is_host_coherent`.
+    pub host_coherent: bool,
+    /// The memory is cached on the host. See `MemoryType::is_host_cached`.
+    pub host_cached: bool,
+    /// The memory is lazily allocated. See `MemoryType::is_lazily_allocated`.
+    pub lazily_allocated: bool,
+}
+
+impl MemoryProperties {
+    /// Builds a `MemoryProperties` with all values set to false.
+    #[inline]
+    pub fn none() -> MemoryProperties {
+        MemoryProperties {
+            device_local: false,
+            host_visible: false,
+            host_coherent: false,
+            host_cached: false,
+            lazily_allocated: false,
+        }
+    }
+
+    /// Returns true if all the properties of `other` are also in `self`.
+    #[inline]
+    pub fn contains(&self, other: &MemoryProperties) -> bool {
+        (self.device_local || !other.device_local) &&
+        (self.host_visible || !other.host_visible) &&
+        (self.host_coherent || !other.host_coherent) &&
+        (self.host_cached || !other.host_cached) &&
+        (self.lazily_allocated || !other.lazily_allocated)
+    }
+
+    /// Returns the number of properties that are in both `self` and `other`.
+    #[inline]
+    fn num_common(&self, other: &MemoryProperties) -> u32 {
+        (self.device_local && other.device_local) as u32 +
+        (self.host_visible && other.host_visible) as u32 +
+        (self.host_coherent && other.host_coherent) as u32 +
+        (self.host_cached && other.host_cached) as u32 +
+        (self.lazily_allocated && other.lazily_allocated) as u32
+    }
+}
+
+impl<'a> From<MemoryType<'a>> for MemoryProperties {
+    #[inline]
+    fn from(ty: MemoryType<'a>) -> MemoryProperties {
+        MemoryProperties {
+            device_local: ty.is_device_local(),
+            host_visible: ty.is_host_visible(),
+            host_coherent: ty.is_host_coherent(),
+            host_cached: ty.is_host_cached(),
+            lazily_allocated: ty.is_lazily_allocated(),
+        }
+    }
+}
+
+/// Describes which memory type a resource should be allocated from.
+///
+/// Only the memory types that have all the `required` properties are considered. Among them, the
+/// one that has the most `preferred` properties is chosen. If no memory type has any of the
+/// preferred properties, the first one that has the required properties is chosen.
+///
+/// ```
+/// use vulkano::memory::MemoryPreference;
+/// use vulkano::memory::MemoryProperties;
+///
+/// // Memory that is fast to read from the CPU, for example to read back the result of a
+/// // computation.
+/// let readback = MemoryPreference {
+///     required: MemoryProperties { host_visible: true, .. MemoryProperties::none() },
+///     preferred: MemoryProperties { host_cached: true, .. MemoryProperties::none() },
+/// };
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MemoryPreference {
+    /// Properties that the memory type must have.
+    pub required: MemoryProperties,
+    /// Properties that the memory type should have if possible.
+    pub preferred: MemoryProperties,
+}
+
+impl MemoryPreference {
+    /// Accepts any memory type, but prefers device-local memory.
+    ///
+    /// This is what vulkano uses for resources that are only accessed by the GPU.
+    #[inline]
+    pub fn device_local() -> MemoryPreference {
+        MemoryPreference {
+            required: MemoryProperties::none(),
+            preferred: MemoryProperties { device_local: true, .. MemoryProperties::none() },
+        }
+    }
+
+    /// Requires host-visible memory, without any other preference.
+    ///
+    /// This is what vulkano uses for resources that are accessed by the CPU.
+    #[inline]
+    pub fn host_visible() -> MemoryPreference {
+        MemoryPreference {
+            required: MemoryProperties { host_visible: true, .. MemoryProperties::none() },
+            preferred: MemoryProperties::none(),
+        }
+    }
+
+    /// Chooses the best memory type of `physical_device` among the ones whose bit is set in
+    /// `memory_type_bits`.
+    ///
+    /// `memory_type_bits` is usually the value of `MemoryRequirements::memory_type_bits`. Returns
+    /// `None` if none of these memory types has the required properties.
+    pub fn choose<'a>(&self, physical_device: PhysicalDevice<'a>, memory_type_bits: u32)
+                      -> Option<MemoryType<'a>>
+    {
+        let mut best: Option<(MemoryType<'a>, u32)> = None;
+
+        for ty in physical_device.memory_types() {
+            if (memory_type_bits & (1 << ty.id())) == 0 {
+                continue;
+            }
+
+            let props = MemoryProperties::from(ty);
+            if !props.contains(&self.required) {
+                continue;
+            }
+
+            // Vulkan orders the memory types by performance, so on equal score we keep the
+            // first one.
+            let score = props.num_common(&self.preferred);
+            match best {
+                Some((_, best_score)) if best_score >= score => (),
+                _ => best = Some((ty, score)),
+            }
+        }
+
+        best.map(|(ty, _)| ty)
+    }
+}
+
+/// Error that can happen when allocating memory for a resource according to a
+/// `MemoryPreference`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemoryAllocError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// None of the memory types that support the resource has the required properties.
+    NoSuitableMemoryType,
+}
+
+impl error::Error for MemoryAllocError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            MemoryAllocError::OomError(_) => "not enough memory available",
+            MemoryAllocError::NoSuitableMemoryType => "none of the memory types that support \
+                                                       the resource has the required properties",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            MemoryAllocError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for MemoryAllocError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for MemoryAllocError {
+    #[inline]
+    fn from(err: OomError) -> MemoryAllocError {
+        MemoryAllocError::OomError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use memory::MemoryPreference;
+    use memory::MemoryProperties;
+
+    #[test]
+    fn host_visible_required() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let ty = MemoryPreference::host_visible().choose(device.physical_device(), !0).unwrap();
+        assert!(ty.is_host_visible());
+    }
+
+    #[test]
+    fn preferred_is_best_effort() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let everything = MemoryProperties {
+            device_local: true,
+            host_visible: true,
+            host_coherent: true,
+            host_cached: true,
+            lazily_allocated: true,
+        };
+
+        let preference = MemoryPreference {
+            required: MemoryProperties::none(),
+            preferred: everything,
+        };
+
+        assert!(preference.choose(device.physical_device(), !0).is_some());
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        // The specs forbid lazily allocated memory from being host-visible.
+        let preference = MemoryPreference {
+            required: MemoryProperties {
+                host_visible: true,
+                lazily_allocated: true,
+                .. MemoryProperties::none()
+            },
+            preferred: MemoryProperties::none(),
+        };
+
+        assert!(preference.choose(device.physical_device(), !0).is_none());
+        assert!(MemoryPreference::device_local().choose(device.physical_device(), 0).is_none());
+    }
+}
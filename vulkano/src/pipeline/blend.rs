@@ -59,6 +59,16 @@ impl Blend {
             blend_constants: Some([0.0, 0.0, 0.0, 0.0]),
         }
     }
+
+    /// Returns true if nothing is written to the color attachments, because the writes to all
+    /// the color components of all the attachments are masked off.
+    #[inline]
+    pub fn is_write_masked(&self) -> bool {
+        match self.attachments {
+            AttachmentsBlend::Collective(ref blend) => blend.is_write_masked(),
+            AttachmentsBlend::Individual(ref blends) => blends.iter().all(|b| b.is_write_masked()),
+        }
+    }
 }
 
 /// Describes how the blending system should behave.
@@ -149,6 +159,24 @@ impl AttachmentBlend {
             mask_alpha: true,
         }
     }
+
+    /// Builds an `AttachmentBlend` where nothing is written to the attachment.
+    #[inline]
+    pub fn write_masked() -> AttachmentBlend {
+        AttachmentBlend {
+            mask_red: false,
+            mask_green: false,
+            mask_blue: false,
+            mask_alpha: false,
+            .. AttachmentBlend::pass_through()
+        }
+    }
+
+    /// Returns true if the writes to all the color components are masked off.
+    #[inline]
+    pub fn is_write_masked(&self) -> bool {
+        !self.mask_red && !self.mask_green && !self.mask_blue && !self.mask_alpha
+    }
 }
 
 #[doc(hidden)]
@@ -22,11 +22,18 @@
 //! of [`get_data`](struct.PipelineCache.html#method.get_data) for example of how to store the data
 //! on the disk, and [`with_data`](struct.PipelineCache.html#method.with_data) for how to reload it.
 //!
+//! The data starts with a header that identifies the device and the driver that produced it. Data
+//! that was produced after a GPU or driver change should be discarded, which
+//! [`with_header_validation`](struct.PipelineCache.html#method.with_header_validation) does for
+//! you.
+//!
 use std::mem;
 use std::ptr;
 use std::sync::Arc;
 
 use device::Device;
+use device::DeviceOwned;
+use instance::PhysicalDevice;
 
 use OomError;
 use VulkanObject;
@@ -53,7 +60,10 @@ impl PipelineCache {
     ///
     /// This example loads a cache from a file, if it exists.
     /// See [`get_data`](#method.get_data) for how to store the data in a file.
-    /// TODO: there's a header in the cached data that must be checked ; talk about this
+    ///
+    /// Note that this function doesn't check the header of the data. Use
+    /// [`with_header_validation`](#method.with_header_validation) if the data may come from
+    /// another device or from another version of the driver.
     ///
     /// ```
     /// # use std::sync::Arc;
@@ -87,6 +97,37 @@ impl PipelineCache {
         PipelineCache::new_impl(device, Some(initial_data))
     }
 
+    /// Builds a new pipeline cache from existing data, after checking that the header of the data
+    /// matches the device.
+    ///
+    /// The vendor ID, the device ID and the pipeline cache UUID stored in the header must be the
+    /// same as the ones of the physical device of `device`. If they aren't, or if the header is
+    /// missing or malformed, the data is discarded and an empty cache is created instead. The
+    /// returned boolean is true if the data was used, and false if it was discarded.
+    ///
+    /// This protects against caches that were produced by another GPU or by another version of
+    /// the driver. It is still unsafe, because the content that follows the header is not checked.
+    #[inline]
+    pub unsafe fn with_header_validation(device: Arc<Device>, initial_data: &[u8])
+                                         -> Result<(Arc<PipelineCache>, bool), OomError>
+    {
+        if PipelineCache::header_matches(initial_data, device.physical_device()) {
+            let cache = try!(PipelineCache::new_impl(device, Some(initial_data)));
+            Ok((cache, true))
+        } else {
+            let cache = try!(PipelineCache::new_impl(device, None));
+            Ok((cache, false))
+        }
+    }
+
+    /// Returns true if `data` starts with a valid pipeline cache header whose vendor ID, device
+    /// ID and pipeline cache UUID are the ones of `physical_device`.
+    #[inline]
+    pub fn header_matches(data: &[u8], physical_device: PhysicalDevice) -> bool {
+        check_header(data, physical_device.pci_vendor_id(), physical_device.pci_device_id(),
+                     physical_device.uuid())
+    }
+
     /// Builds a new empty pipeline cache.
     ///
     /// # Example
@@ -134,12 +175,27 @@ impl PipelineCache {
     ///
     /// It is `self` that is modified here. The pipeline caches passed as parameter are untouched.
     ///
+    /// This is typically used when pipelines are created from multiple threads, each thread
+    /// having its own cache. Once all the pipelines are created, the caches of the threads are
+    /// merged into one that is then stored with `get_data`.
+    ///
     /// # Panic
     ///
     /// - Panics if `self` is included in the list of other pipelines.
+    /// - Panics if one of the other pipelines doesn't belong to the same device as `self`.
     ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use vulkano::pipeline::cache::PipelineCache;
+    /// # let master: Arc<PipelineCache> = return;
+    /// # let thread1: Arc<PipelineCache> = return;
+    /// # let thread2: Arc<PipelineCache> = return;
+    /// master.merge(&[&thread1, &thread2]).unwrap();
+    /// let data = master.get_data().unwrap();
+    /// ```
     // FIXME: vkMergePipelineCaches is not thread safe for the destination cache
-    // TODO: write example
     pub fn merge<'a, I>(&self, pipelines: I) -> Result<(), OomError>
         where I: IntoIterator<Item = &'a &'a Arc<PipelineCache>>
     {
@@ -148,6 +204,8 @@ impl PipelineCache {
 
             let pipelines = pipelines.into_iter().map(|pipeline| {
                 assert!(&***pipeline as *const _ != &*self as *const _);
+                assert_eq!(pipeline.device.internal_object(), self.device.internal_object(),
+                           "Can't merge pipeline caches that belong to different devices");
                 pipeline.cache
             }).collect::<Vec<_>>();
 
@@ -204,6 +262,13 @@ impl PipelineCache {
     }
 }
 
+unsafe impl DeviceOwned for PipelineCache {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
 unsafe impl VulkanObject for PipelineCache {
     type Object = vk::PipelineCache;
 
@@ -223,10 +288,82 @@ impl Drop for PipelineCache {
     }
 }
 
+// Size in bytes of `VkPipelineCacheHeaderVersionOne`.
+const HEADER_VERSION_ONE_SIZE: usize = 32;
+
+// Checks the content of a `VkPipelineCacheHeaderVersionOne` at the start of `data`. Unlike the
+// rest of the Vulkan API, the fields of the header are always stored in little endian.
+fn check_header(data: &[u8], vendor_id: u32, device_id: u32, uuid: &[u8; 16]) -> bool {
+    if data.len() < HEADER_VERSION_ONE_SIZE {
+        return false;
+    }
+
+    let read_u32 = |offset: usize| {
+        (data[offset] as u32) | ((data[offset + 1] as u32) << 8) |
+            ((data[offset + 2] as u32) << 16) | ((data[offset + 3] as u32) << 24)
+    };
+
+    let header_size = read_u32(0) as usize;
+    if header_size < HEADER_VERSION_ONE_SIZE || header_size > data.len() {
+        return false;
+    }
+
+    read_u32(4) == vk::PIPELINE_CACHE_HEADER_VERSION_ONE && read_u32(8) == vendor_id &&
+        read_u32(12) == device_id && data[16 .. 32] == uuid[..]
+}
+
 #[cfg(test)]
 mod tests {
     use pipeline::cache::PipelineCache;
 
+    fn header(vendor_id: u32, device_id: u32, uuid: [u8; 16]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for &value in [32, 1, vendor_id, device_id].iter() {
+            data.extend_from_slice(&[value as u8, (value >> 8) as u8, (value >> 16) as u8,
+                                     (value >> 24) as u8]);
+        }
+        data.extend_from_slice(&uuid);
+        data
+    }
+
+    #[test]
+    fn header_valid() {
+        let mut data = header(0x10de, 0x1b80, [7; 16]);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        assert!(super::check_header(&data, 0x10de, 0x1b80, &[7; 16]));
+    }
+
+    #[test]
+    fn header_mismatch() {
+        let data = header(0x10de, 0x1b80, [7; 16]);
+        assert!(!super::check_header(&data, 0x1002, 0x1b80, &[7; 16]));
+        assert!(!super::check_header(&data, 0x10de, 0x1b81, &[7; 16]));
+        assert!(!super::check_header(&data, 0x10de, 0x1b80, &[8; 16]));
+    }
+
+    #[test]
+    fn header_malformed() {
+        let data = header(0x10de, 0x1b80, [7; 16]);
+        assert!(!super::check_header(&data[.. 31], 0x10de, 0x1b80, &[7; 16]));
+        assert!(!super::check_header(&[], 0x10de, 0x1b80, &[7; 16]));
+
+        let mut wrong_version = data.clone();
+        wrong_version[4] = 2;
+        assert!(!super::check_header(&wrong_version, 0x10de, 0x1b80, &[7; 16]));
+
+        let mut wrong_size = data.clone();
+        wrong_size[0] = 64;
+        assert!(!super::check_header(&wrong_size, 0x10de, 0x1b80, &[7; 16]));
+    }
+
+    #[test]
+    fn foreign_data_discarded() {
+        let (device, _) = gfx_dev_and_queue!();
+        let data = header(0, 0, [0; 16]);
+        let (_, accepted) = unsafe { PipelineCache::with_header_validation(device, &data).unwrap() };
+        assert!(!accepted);
+    }
+
     #[test]
     #[should_panic]
     fn merge_self_forbidden() {
@@ -234,4 +371,17 @@ mod tests {
         let pipeline = PipelineCache::empty(device).unwrap();
         pipeline.merge(&[&pipeline]).unwrap();
     }
+
+    #[test]
+    fn merge_and_serialize() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let master = PipelineCache::empty(device.clone()).unwrap();
+        let first = PipelineCache::empty(device.clone()).unwrap();
+        let second = PipelineCache::empty(device.clone()).unwrap();
+        master.merge(&[&first, &second]).unwrap();
+
+        let data = master.get_data().unwrap();
+        assert!(PipelineCache::header_matches(&data, device.physical_device()));
+    }
 }
@@ -15,6 +15,9 @@ use std::ptr;
 use std::sync::Arc;
 
 use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor::DescriptorType;
+use descriptor::descriptor::ShaderStages;
+use descriptor::descriptor_set::DescriptorsCount;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
 use descriptor::pipeline_layout::PipelineLayout;
 use descriptor::pipeline_layout::PipelineLayoutSys;
@@ -24,6 +27,7 @@ use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
 use descriptor::pipeline_layout::PipelineLayoutSuperset;
 use descriptor::pipeline_layout::PipelineLayoutAbstract;
 use descriptor::pipeline_layout::PipelineLayoutNotSupersetError;
+use pipeline::cache::PipelineCache;
 use pipeline::shader::ComputeShaderEntryPoint;
 use pipeline::shader::SpecializationConstants;
 
@@ -51,18 +55,81 @@ pub struct ComputePipeline<Pl> {
 struct Inner {
     pipeline: vk::Pipeline,
     device: Arc<Device>,
+    // True if the pipeline was created with `VK_PIPELINE_CREATE_DISPATCH_BASE`.
+    dispatch_base: bool,
 }
 
 impl ComputePipeline<()> {
     /// Builds a new `ComputePipeline`.
+    ///
+    /// The pipeline layout is built from the descriptors of the shader, and can be retreived
+    /// with `layout()` in order to build descriptor sets for this pipeline.
+    #[inline]
     pub fn new<Css, Csl>(device: Arc<Device>, shader: &ComputeShaderEntryPoint<Css, Csl>,
-                         specialization: &Css) 
+                         specialization: &Css)
                          -> Result<ComputePipeline<PipelineLayout<Csl>>, ComputePipelineCreationError>
         where Csl: PipelineLayoutDescNames + Clone,
               Css: SpecializationConstants
+    {
+        ComputePipeline::new_with_cache(device, shader, specialization, None)
+    }
+
+    /// Same as `new`, but uses a pipeline cache.
+    ///
+    /// If the cache contains a pipeline that was built with the same shader, the creation is
+    /// faster. Otherwise the newly-created pipeline is added to the cache. See the `cache`
+    /// module.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the cache and the pipeline don't belong to the same device.
+    ///
+    pub fn new_with_cache<Css, Csl>(device: Arc<Device>, shader: &ComputeShaderEntryPoint<Css, Csl>,
+                                    specialization: &Css, cache: Option<&Arc<PipelineCache>>)
+                                    -> Result<ComputePipeline<PipelineLayout<Csl>>, ComputePipelineCreationError>
+        where Csl: PipelineLayoutDescNames + Clone,
+              Css: SpecializationConstants
+    {
+        ComputePipeline::new_impl(device, shader, specialization, cache, false)
+    }
+
+    /// Same as `new_with_cache`, but the pipeline can also be used with a dispatch command that
+    /// has a non-zero base workgroup. See `CommandBufferBuilder::dispatch_base`.
+    ///
+    /// Returns an error if the `khr_device_group` extension is not enabled on the device.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the cache and the pipeline don't belong to the same device.
+    ///
+    pub fn with_dispatch_base<Css, Csl>(device: Arc<Device>,
+                                        shader: &ComputeShaderEntryPoint<Css, Csl>,
+                                        specialization: &Css,
+                                        cache: Option<&Arc<PipelineCache>>)
+                                        -> Result<ComputePipeline<PipelineLayout<Csl>>, ComputePipelineCreationError>
+        where Csl: PipelineLayoutDescNames + Clone,
+              Css: SpecializationConstants
+    {
+        if !device.loaded_extensions().khr_device_group {
+            return Err(ComputePipelineCreationError::DeviceGroupExtensionNotEnabled);
+        }
+
+        ComputePipeline::new_impl(device, shader, specialization, cache, true)
+    }
+
+    fn new_impl<Css, Csl>(device: Arc<Device>, shader: &ComputeShaderEntryPoint<Css, Csl>,
+                          specialization: &Css, cache: Option<&Arc<PipelineCache>>,
+                          dispatch_base: bool)
+                          -> Result<ComputePipeline<PipelineLayout<Csl>>, ComputePipelineCreationError>
+        where Csl: PipelineLayoutDescNames + Clone,
+              Css: SpecializationConstants
     {
         let vk = device.pointers();
 
+        if let Some(cache) = cache {
+            assert_eq!(cache.device().internal_object(), device.internal_object());
+        }
+
         let pipeline_layout = shader.layout().clone().build(device.clone()).unwrap();     // TODO: error
 
         PipelineLayoutSuperset::ensure_superset_of(pipeline_layout.desc(), shader.layout())?;
@@ -93,7 +160,7 @@ impl ComputePipeline<()> {
             let infos = vk::ComputePipelineCreateInfo {
                 sType: vk::STRUCTURE_TYPE_COMPUTE_PIPELINE_CREATE_INFO,
                 pNext: ptr::null(),
-                flags: 0,
+                flags: if dispatch_base { vk::PIPELINE_CREATE_DISPATCH_BASE_KHR } else { 0 },
                 stage: stage,
                 layout: PipelineLayoutAbstract::sys(&pipeline_layout).internal_object(),
                 basePipelineHandle: 0,
@@ -101,7 +168,8 @@ impl ComputePipeline<()> {
             };
 
             let mut output = mem::uninitialized();
-            try!(check_errors(vk.CreateComputePipelines(device.internal_object(), 0,
+            let cache = cache.map(|c| c.internal_object()).unwrap_or(0);
+            try!(check_errors(vk.CreateComputePipelines(device.internal_object(), cache,
                                                         1, &infos, ptr::null(), &mut output)));
             output
         };
@@ -110,6 +178,7 @@ impl ComputePipeline<()> {
             inner: Inner {
                 device: device.clone(),
                 pipeline: pipeline,
+                dispatch_base: dispatch_base,
             },
             pipeline_layout: pipeline_layout,
         })
@@ -131,16 +200,98 @@ impl<Pl> ComputePipeline<Pl> {
     }
 
     /// Returns the pipeline layout used in this compute pipeline.
+    ///
+    /// Descriptor sets that are built against this layout are guaranteed to be compatible with
+    /// the pipeline.
     #[inline]
     pub fn layout(&self) -> &Pl {
         &self.pipeline_layout
     }
 }
 
+impl<Pl> ComputePipeline<Pl> where Pl: PipelineLayoutDesc {
+    /// Returns the descriptors used by the pipeline, as reflected from its layout.
+    ///
+    /// The returned list contains one element per descriptor set, in the order of the sets.
+    /// Sets that the pipeline doesn't use are present but have no binding. This can be used,
+    /// for example, to size descriptor pools with `DescriptorSetRequirements::descriptors_count`.
+    #[inline]
+    pub fn descriptor_requirements(&self) -> Vec<DescriptorSetRequirements> {
+        descriptor_requirements(&self.pipeline_layout)
+    }
+}
+
+/// Descriptors of a set used by a pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorSetRequirements {
+    /// The bindings of the set that contain a descriptor, in increasing order.
+    pub bindings: Vec<DescriptorBindingRequirements>,
+}
+
+impl DescriptorSetRequirements {
+    /// Returns the number of descriptors of each type that a set with these requirements holds.
+    pub fn descriptors_count(&self) -> DescriptorsCount {
+        let mut count = DescriptorsCount::zero();
+        for binding in self.bindings.iter() {
+            count.add_num(binding.ty, binding.array_count);
+        }
+        count
+    }
+}
+
+/// Descriptor of a binding used by a pipeline.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DescriptorBindingRequirements {
+    /// Index of the binding within the set.
+    pub binding: usize,
+    /// Type of the descriptor.
+    pub ty: DescriptorType,
+    /// Number of elements of the array of descriptors. 1 if the binding is not an array.
+    pub array_count: u32,
+    /// Stages that access the descriptor.
+    pub stages: ShaderStages,
+}
+
+// Builds the list of the descriptors of `desc`, set by set.
+fn descriptor_requirements<L>(desc: &L) -> Vec<DescriptorSetRequirements>
+    where L: PipelineLayoutDesc
+{
+    (0 .. desc.num_sets()).map(|set| {
+        let bindings = (0 .. desc.num_bindings_in_set(set).unwrap_or(0)).filter_map(|binding| {
+            let d = match desc.descriptor(set, binding) {
+                Some(d) => d,
+                None => return None,
+            };
+
+            // The type is always known, otherwise the layout couldn't have been created.
+            let ty = match d.ty.ty() {
+                Some(ty) => ty,
+                None => return None,
+            };
+
+            Some(DescriptorBindingRequirements {
+                binding: binding,
+                ty: ty,
+                array_count: d.array_count,
+                stages: d.stages,
+            })
+        }).collect();
+
+        DescriptorSetRequirements { bindings: bindings }
+    }).collect()
+}
+
 /// Trait implemented on all compute pipelines.
 pub unsafe trait ComputePipelineAbstract: PipelineLayoutAbstract {
     /// Returns an opaque object that represents the inside of the compute pipeline.
     fn inner(&self) -> ComputePipelineSys;
+
+    /// Returns true if the pipeline can be used with a dispatch command that has a non-zero base
+    /// workgroup. The default implementation returns `false`.
+    #[inline]
+    fn allows_dispatch_base(&self) -> bool {
+        false
+    }
 }
 
 unsafe impl<Pl> ComputePipelineAbstract for ComputePipeline<Pl>
@@ -150,6 +301,11 @@ unsafe impl<Pl> ComputePipelineAbstract for ComputePipeline<Pl>
     fn inner(&self) -> ComputePipelineSys {
         ComputePipelineSys(self.inner.pipeline, PhantomData)
     }
+
+    #[inline]
+    fn allows_dispatch_base(&self) -> bool {
+        self.inner.dispatch_base
+    }
 }
 
 unsafe impl<T> ComputePipelineAbstract for T
@@ -159,6 +315,11 @@ unsafe impl<T> ComputePipelineAbstract for T
     fn inner(&self) -> ComputePipelineSys {
         (**self).inner()
     }
+
+    #[inline]
+    fn allows_dispatch_base(&self) -> bool {
+        (**self).allows_dispatch_base()
+    }
 }
 
 /// Opaque object that represents the inside of the compute pipeline. Can be made into a trait
@@ -255,6 +416,8 @@ pub enum ComputePipelineCreationError {
     OomError(OomError),
     /// The pipeline layout is not compatible with what the shader expects.
     IncompatiblePipelineLayout(PipelineLayoutNotSupersetError),
+    /// The `khr_device_group` extension must be enabled in order to allow a dispatch base.
+    DeviceGroupExtensionNotEnabled,
 }
 
 impl error::Error for ComputePipelineCreationError {
@@ -265,6 +428,9 @@ impl error::Error for ComputePipelineCreationError {
             ComputePipelineCreationError::IncompatiblePipelineLayout(_) => "the pipeline layout is \
                                                                             not compatible with what \
                                                                             the shader expects",
+            ComputePipelineCreationError::DeviceGroupExtensionNotEnabled => {
+                "the `khr_device_group` extension must be enabled in order to allow a dispatch base"
+            },
         }
     }
 
@@ -273,6 +439,7 @@ impl error::Error for ComputePipelineCreationError {
         match *self {
             ComputePipelineCreationError::OomError(ref err) => Some(err),
             ComputePipelineCreationError::IncompatiblePipelineLayout(ref err) => Some(err),
+            ComputePipelineCreationError::DeviceGroupExtensionNotEnabled => None,
         }
     }
 }
@@ -315,6 +482,79 @@ impl From<Error> for ComputePipelineCreationError {
 
 #[cfg(test)]
 mod tests {
+    use descriptor::descriptor::DescriptorBufferContentDesc;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::DescriptorImageDesc;
+    use descriptor::descriptor::DescriptorImageDescArray;
+    use descriptor::descriptor::DescriptorImageDescDimensions;
+    use descriptor::descriptor::DescriptorType;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorsCount;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+
     // TODO: test for basic creation
     // TODO: test for pipeline layout error
+
+    // Layout of a compute shader with a storage buffer at (0, 0) and an array of four sampled
+    // images at (1, 1).
+    struct Layout;
+    unsafe impl PipelineLayoutDesc for Layout {
+        fn num_sets(&self) -> usize { 2 }
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            match set { 0 => Some(1), 1 => Some(2), _ => None }
+        }
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            let ty = match (set, binding) {
+                (0, 0) => DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                    dynamic: Some(false),
+                    storage: true,
+                    content: DescriptorBufferContentDesc::F32,
+                }),
+                (1, 1) => DescriptorDescTy::Image(DescriptorImageDesc {
+                    sampled: true,
+                    dimensions: DescriptorImageDescDimensions::TwoDimensional,
+                    format: None,
+                    multisampled: false,
+                    array_layers: DescriptorImageDescArray::NonArrayed,
+                }),
+                _ => return None,
+            };
+
+            Some(DescriptorDesc {
+                ty: ty,
+                array_count: if set == 1 { 4 } else { 1 },
+                stages: ShaderStages::compute(),
+                readonly: set == 1,
+            })
+        }
+        fn num_push_constants_ranges(&self) -> usize { 0 }
+        fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> { None }
+    }
+
+    #[test]
+    fn descriptor_requirements() {
+        let reqs = super::descriptor_requirements(&Layout);
+        assert_eq!(reqs.len(), 2);
+
+        assert_eq!(reqs[0].bindings.len(), 1);
+        assert_eq!(reqs[0].bindings[0].binding, 0);
+        assert_eq!(reqs[0].bindings[0].ty, DescriptorType::StorageBuffer);
+        assert_eq!(reqs[0].bindings[0].stages, ShaderStages::compute());
+        assert_eq!(reqs[0].descriptors_count(), DescriptorsCount {
+            storage_buffer: 1,
+            .. DescriptorsCount::zero()
+        });
+
+        assert_eq!(reqs[1].bindings.len(), 1);
+        assert_eq!(reqs[1].bindings[0].binding, 1);
+        assert_eq!(reqs[1].bindings[0].ty, DescriptorType::SampledImage);
+        assert_eq!(reqs[1].bindings[0].array_count, 4);
+        assert_eq!(reqs[1].descriptors_count(), DescriptorsCount {
+            sampled_image: 4,
+            .. DescriptorsCount::zero()
+        });
+    }
 }
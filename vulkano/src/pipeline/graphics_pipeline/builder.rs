@@ -26,8 +26,9 @@ use pipeline::blend::LogicOp;
 use pipeline::depth_stencil::DepthStencil;
 use pipeline::graphics_pipeline::GraphicsPipeline;
 use pipeline::graphics_pipeline::GraphicsPipelineCreationError;
-use pipeline::graphics_pipeline::GraphicsPipelineParams;
+use pipeline::graphics_pipeline::PipelineParams;
 use pipeline::graphics_pipeline::GraphicsPipelineParamsTess;
+use pipeline::graphics_pipeline::check_interfaces;
 use pipeline::input_assembly::InputAssembly;
 use pipeline::input_assembly::PrimitiveTopology;
 use pipeline::multisample::Multisample;
@@ -116,25 +117,62 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
           Rp: RenderPassAbstract + RenderPassSubpassInterface<Fo>,
 {
     /// Builds the graphics pipeline.
-    // TODO: replace Box<PipelineLayoutAbstract> with a PipelineUnion struct without template params
+    ///
+    /// This is the same as `with_auto_layout()`.
+    #[inline]
     pub fn build(self, device: Arc<Device>) -> Result<GraphicsPipeline<Vdef, Box<PipelineLayoutAbstract + Send + Sync>, Rp>, GraphicsPipelineCreationError> {
+        self.with_auto_layout(device)
+    }
+
+    /// Builds the graphics pipeline, with a pipeline layout derived from the shaders.
+    ///
+    /// The pipeline layout is the union of the layouts of all the shader stages, and is created
+    /// automatically. Returns an error if two stages use the same binding for descriptors of
+    /// different types.
+    // TODO: replace Box<PipelineLayoutAbstract> with a PipelineUnion struct without template params
+    pub fn with_auto_layout(self, device: Arc<Device>) -> Result<GraphicsPipeline<Vdef, Box<PipelineLayoutAbstract + Send + Sync>, Rp>, GraphicsPipelineCreationError> {
+        GraphicsPipeline::with_auto_layout(device, self.into_params())
+    }
+
+    /// Builds the graphics pipeline, with an explicit pipeline layout.
+    ///
+    /// The layout must be a superset of the layouts of all the shader stages. This is useful to
+    /// share the same layout between multiple pipelines, so that descriptor sets can be bound
+    /// once for all of them.
+    pub fn with_pipeline_layout<L>(self, device: Arc<Device>, pipeline_layout: L)
+                                   -> Result<GraphicsPipeline<Vdef, L, Rp>, GraphicsPipelineCreationError>
+        where L: PipelineLayoutAbstract
+    {
+        let params = self.into_params();
+        check_interfaces(&params)?;
+        GraphicsPipeline::new_inner(device, params, pipeline_layout)
+    }
+
+    // Turns the builder into the parameters of the pipeline.
+    fn into_params(self) -> PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes,
+                                           Tei, Teo, Tel, Gs, Gi, Go, Gl, Fs, Fi, Fo, Fl, Rp>
+    {
         // TODO: return errors instead of panicking if missing param
-        GraphicsPipeline::with_tessellation_and_geometry(device, GraphicsPipelineParams {
+        PipelineParams {
             vertex_input: self.vertex_input,
             vertex_shader: self.vertex_shader.expect("Vertex shader not specified in the builder"),
             input_assembly: self.input_assembly,
             tessellation: self.tessellation,
             geometry_shader: self.geometry_shader,
-            viewport: self.viewport.expect("Viewport state not specified in the builder"),
+            viewport: match self.viewport {
+                Some(viewport) => viewport,
+                None if self.raster.rasterizer_discard => ViewportsState::Fixed { data: Vec::new() },
+                None => panic!("Viewport state not specified in the builder"),
+            },
             raster: self.raster,
             multisample: self.multisample,
-            fragment_shader: self.fragment_shader.expect("Fragment shader not specified in the builder"),
+            fragment_shader: self.fragment_shader,
             depth_stencil: self.depth_stencil,
             blend: self.blend,
             render_pass: self.render_pass.expect("Render pass not specified in the builder"),
-        })
+        }
     }
-    
+
     // TODO: add build_with_cache method
 }
 
@@ -456,13 +494,17 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
         self
     }
 
-    // TODO: this won't work correctly
-    /*/// Disables the fragment shader stage.
+    /// If true, all the primitives are discarded before rasterization. The default is false.
+    ///
+    /// This is useful when the vertex, tessellation or geometry shaders have side effects, such
+    /// as storing to images or buffers, and nothing has to be drawn. A pipeline that discards
+    /// everything doesn't need a fragment shader or viewports. If there is a fragment shader, it
+    /// never runs. The color attachments, the blend state and the depth-stencil state are ignored.
     #[inline]
-    pub fn rasterizer_discard(mut self) -> Self {
-        self.rasterization.rasterizer_discard. = true;
+    pub fn rasterizer_discard(mut self, discard: bool) -> Self {
+        self.raster.rasterizer_discard = discard;
         self
-    }*/
+    }
 
     /// Sets the front-facing faces to couner-clockwise faces. This is the default.
     ///
@@ -517,6 +559,16 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
         self
     }
 
+    /// Sets the polygon mode. The default is `PolygonMode::Fill`.
+    ///
+    /// Any other mode than `Fill` requires the `fill_mode_non_solid` feature to be enabled on the
+    /// device, otherwise building the pipeline will fail.
+    #[inline]
+    pub fn polygon_mode(mut self, mode: PolygonMode) -> Self {
+        self.raster.polygon_mode = mode;
+        self
+    }
+
     /// Sets the polygon mode to "fill". This is the default.
     #[inline]
     pub fn polygon_mode_fill(mut self) -> Self {
@@ -539,6 +591,9 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
     }
 
     /// Sets the width of the lines, if the GPU needs to draw lines. The default is `1.0`.
+    ///
+    /// Any other value than `1.0` requires the `wide_lines` feature to be enabled on the device,
+    /// and must be within the `line_width_range` limit.
     #[inline]
     pub fn line_width(mut self, value: f32) -> Self {
         self.raster.line_width = Some(value);
@@ -546,7 +601,7 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
     }
 
     /// Sets the width of the lines as dynamic, which means that you will need to set this value
-    /// when drawing.
+    /// when drawing, with the `line_width` member of `DynamicState`.
     #[inline]
     pub fn line_width_dynamic(mut self) -> Self {
         self.raster.line_width = None;
@@ -560,6 +615,11 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
     /// Sets the fragment shader to use.
     ///
     /// The fragment shader is run once for each pixel that is covered by each primitive.
+    ///
+    /// If no fragment shader is set, the pipeline only writes depth values, which is what a
+    /// shadow map or a depth pre-pass needs. In that case `build()` checks that the subpass has
+    /// no color attachment or that all the color writes are masked off, and that depth writes are
+    /// enabled.
     // TODO: correct specialization constants
     #[inline]
     pub fn fragment_shader<Fi2, Fo2, Fl2>(self, shader: FragmentShaderEntryPoint<'a, (), Fi2, Fo2, Fl2>,
@@ -639,6 +699,13 @@ impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
         self.blend_collective(AttachmentBlend::alpha_blending())
     }
 
+    /// Masks off the writes to all the color attachments. Only the depth and stencil attachments
+    /// are written, which is what a pipeline without a fragment shader needs.
+    #[inline]
+    pub fn blend_write_masked(self) -> Self {
+        self.blend_collective(AttachmentBlend::write_masked())
+    }
+
     #[inline]
     pub fn blend_logic_op(mut self, logic_op: LogicOp) -> Self {
         self.blend.logic_op = Some(logic_op);
@@ -26,11 +26,14 @@ use device::Device;
 use device::DeviceOwned;
 use descriptor::PipelineLayoutAbstract;
 use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor::DescriptorDescTy;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
 use descriptor::pipeline_layout::PipelineLayout;
 use descriptor::pipeline_layout::PipelineLayoutDesc;
 use descriptor::pipeline_layout::PipelineLayoutDescNames;
+use descriptor::pipeline_layout::PipelineLayoutCreationError;
 use descriptor::pipeline_layout::PipelineLayoutDescUnion;
+use descriptor::pipeline_layout::PipelineLayoutDescUnionError;
 use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
 use descriptor::pipeline_layout::PipelineLayoutSuperset;
 use descriptor::pipeline_layout::PipelineLayoutNotSupersetError;
@@ -77,13 +80,14 @@ use pipeline::vertex::SingleBufferDefinition;
 use pipeline::vertex::VertexDefinition;
 use pipeline::vertex::VertexSource;
 use pipeline::vertex::IncompatibleVertexDefinitionError;
+use pipeline::viewport::Scissor;
+use pipeline::viewport::Viewport;
 use pipeline::viewport::ViewportsState;
 
 pub use self::builder::GraphicsPipelineBuilder;
 
 mod builder;
-// FIXME: restore
-//mod tests;
+mod tests;
 
 /// Description of a `GraphicsPipeline`.
 #[deprecated = "Use the GraphicsPipelineBuilder instead"]
@@ -148,6 +152,58 @@ pub struct GraphicsPipelineParamsTess<'a, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel
     pub tessellation_evaluation_shader: TessEvaluationShaderEntryPoint<'a, Tes, Tei, Teo, Tel>,
 }
 
+// Same as `GraphicsPipelineParams`, except that the fragment shader is optional. This is what the
+// builder produces, and what the pipeline is created from.
+//
+// Without a fragment shader, for example for a depth-only pass, the subpass must either have no
+// color attachment or have all the color writes masked off by `blend`, and depth writes must be
+// enabled. This doesn't apply if `raster.rasterizer_discard` is true.
+struct PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
+                      Go, Gl, Fs, Fi, Fo, Fl, Rp>
+{
+    vertex_input: Vdef,
+    vertex_shader: VertexShaderEntryPoint<'a, Vsp, Vi, Vo, Vl>,
+    input_assembly: InputAssembly,
+    tessellation: Option<GraphicsPipelineParamsTess<'a, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel>>,
+    geometry_shader: Option<GeometryShaderEntryPoint<'a, Gs, Gi, Go, Gl>>,
+    viewport: ViewportsState,
+    raster: Rasterization,
+    multisample: Multisample,
+    fragment_shader: Option<FragmentShaderEntryPoint<'a, Fs, Fi, Fo, Fl>>,
+    depth_stencil: DepthStencil,
+    blend: Blend,
+    render_pass: Subpass<Rp>,
+}
+
+impl<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi, Go, Gl, Fs, Fi,
+     Fo, Fl, Rp>
+    From<GraphicsPipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel,
+                                Gs, Gi, Go, Gl, Fs, Fi, Fo, Fl, Rp>>
+    for PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gs, Gi,
+                       Go, Gl, Fs, Fi, Fo, Fl, Rp>
+{
+    #[inline]
+    fn from(params: GraphicsPipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes,
+                                           Tei, Teo, Tel, Gs, Gi, Go, Gl, Fs, Fi, Fo, Fl, Rp>)
+            -> Self
+    {
+        PipelineParams {
+            vertex_input: params.vertex_input,
+            vertex_shader: params.vertex_shader,
+            input_assembly: params.input_assembly,
+            tessellation: params.tessellation,
+            geometry_shader: params.geometry_shader,
+            viewport: params.viewport,
+            raster: params.raster,
+            multisample: params.multisample,
+            fragment_shader: Some(params.fragment_shader),
+            depth_stencil: params.depth_stencil,
+            blend: params.blend,
+            render_pass: params.render_pass,
+        }
+    }
+}
+
 /// Defines how the implementation should perform a draw operation.
 ///
 /// This object contains the shaders and the various fixed states that describe how the
@@ -172,6 +228,9 @@ pub struct GraphicsPipeline<VertexDefinition, Layout, RenderP> {
     dynamic_blend_constants: bool,
 
     num_viewports: u32,
+    // Viewports and scissors that are not dynamic, or empty.
+    static_viewports: Vec<Viewport>,
+    static_scissors: Vec<Scissor>,
 }
 
 struct Inner {
@@ -231,7 +290,7 @@ impl<Vdef, Rp> GraphicsPipeline<Vdef, (), Rp>
 
         GraphicsPipeline::new_inner::<_, _, _, _, (), (), (), EmptyPipelineDesc, (), (), (),
                                       EmptyPipelineDesc, (), (), (), EmptyPipelineDesc, _, _, _, _>
-                                      (device, params, pl)
+                                      (device, params.into(), pl)
     }
 
     /// Builds a new graphics pipeline object with a geometry shader.
@@ -279,7 +338,7 @@ impl<Vdef, Rp> GraphicsPipeline<Vdef, (), Rp>
                     .union(params.geometry_shader.as_ref().unwrap().layout().clone())    // FIXME: unwrap()
                     .build(device.clone()).unwrap();      // TODO: error
 
-        GraphicsPipeline::new_inner(device.clone(), params, pl)
+        GraphicsPipeline::new_inner(device.clone(), params.into(), pl)
     }
 
     /// Builds a new graphics pipeline object with tessellation shaders.
@@ -336,7 +395,7 @@ impl<Vdef, Rp> GraphicsPipeline<Vdef, (), Rp>
                     .union(params.tessellation.as_ref().unwrap().tessellation_evaluation_shader.layout().clone())    // FIXME: unwrap()
                     .build(device.clone()).unwrap();      // TODO: error
 
-        GraphicsPipeline::new_inner(device, params, pl)
+        GraphicsPipeline::new_inner(device, params.into(), pl)
     }
 
     /// Builds a new graphics pipeline object with a geometry and tessellation shaders.
@@ -374,75 +433,193 @@ impl<Vdef, Rp> GraphicsPipeline<Vdef, (), Rp>
               Fo: ShaderInterfaceDef,
               Rp: RenderPassAbstract + RenderPassSubpassInterface<Fo>,
     {
-        let pl;
-
-        if let Some(ref tess) = params.tessellation {
-            if let Some(ref gs) = params.geometry_shader {
-                if let Err(err) = tess.tessellation_control_shader.input().matches(params.vertex_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::VertexTessControlStagesMismatch(err));
-                }
-                if let Err(err) = tess.tessellation_evaluation_shader.input().matches(tess.tessellation_control_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::TessControlTessEvalStagesMismatch(err));
-                }
-                if let Err(err) = gs.input().matches(tess.tessellation_evaluation_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::TessEvalGeometryStagesMismatch(err));
-                }
-                if let Err(err) = params.fragment_shader.input().matches(gs.output()) {
-                    return Err(GraphicsPipelineCreationError::GeometryFragmentStagesMismatch(err));
-                }
+        GraphicsPipeline::with_auto_layout(device, params.into())
+    }
 
-                pl = Box::new(params.vertex_shader.layout().clone()
-                    .union(params.fragment_shader.layout().clone())
-                    .union(params.tessellation.as_ref().unwrap().tessellation_control_shader.layout().clone())    // FIXME: unwrap()
-                    .union(params.tessellation.as_ref().unwrap().tessellation_evaluation_shader.layout().clone())    // FIXME: unwrap()
-                    .union(params.geometry_shader.as_ref().unwrap().layout().clone())    // FIXME: unwrap()
-                    .build(device.clone()).unwrap()) as Box<_>;      // TODO: error
+    // Creates the pipeline with a layout made of the union of the layouts of all the shader
+    // stages.
+    fn with_auto_layout<'a, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gsp, Gi, Go,
+                        Gl, Fs, Fi, Fo, Fl>
+                       (device: Arc<Device>,
+                        params: PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes,
+                                               Tei, Teo, Tel, Gsp, Gi, Go, Gl, Fs, Fi, Fo, Fl, Rp>)
+                       -> Result<GraphicsPipeline<Vdef, Box<PipelineLayoutAbstract + Send + Sync>,
+                                                  Rp>,
+                                 GraphicsPipelineCreationError>
+        where Vdef: VertexDefinition<Vi>,
+              Vl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+              Fl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+              Tcl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+              Tel: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+              Gl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+              Tci: ShaderInterfaceDefMatch<Vo>,
+              Tei: ShaderInterfaceDefMatch<Tco>,
+              Gi: ShaderInterfaceDefMatch<Teo> + ShaderInterfaceDefMatch<Vo>,
+              Vo: ShaderInterfaceDef,
+              Tco: ShaderInterfaceDef,
+              Teo: ShaderInterfaceDef,
+              Go: ShaderInterfaceDef,
+              Fi: ShaderInterfaceDefMatch<Go> + ShaderInterfaceDefMatch<Teo> + ShaderInterfaceDefMatch<Vo>,
+              Fo: ShaderInterfaceDef,
+              Rp: RenderPassAbstract + RenderPassSubpassInterface<Fo>,
+    {
+        check_interfaces(&params)?;
+        let pl = auto_layout(&device, &params)?;
+        GraphicsPipeline::new_inner(device, params, pl)
+    }
+}
 
-            } else {
-                if let Err(err) = tess.tessellation_control_shader.input().matches(params.vertex_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::VertexTessControlStagesMismatch(err));
-                }
-                if let Err(err) = tess.tessellation_evaluation_shader.input().matches(tess.tessellation_control_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::TessControlTessEvalStagesMismatch(err));
-                }
-                if let Err(err) = params.fragment_shader.input().matches(tess.tessellation_evaluation_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::TessEvalFragmentStagesMismatch(err));
+// Checks that the interfaces of the successive shader stages match.
+fn check_interfaces<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gsp, Gi, Go,
+                    Gl, Fs, Fi, Fo, Fl, Rp>
+                   (params: &PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl,
+                                                    Tes, Tei, Teo, Tel, Gsp, Gi, Go, Gl, Fs, Fi,
+                                                    Fo, Fl, Rp>)
+                   -> Result<(), GraphicsPipelineCreationError>
+    where Tci: ShaderInterfaceDefMatch<Vo>,
+          Tei: ShaderInterfaceDefMatch<Tco>,
+          Gi: ShaderInterfaceDefMatch<Teo> + ShaderInterfaceDefMatch<Vo>,
+          Vo: ShaderInterfaceDef,
+          Tco: ShaderInterfaceDef,
+          Teo: ShaderInterfaceDef,
+          Go: ShaderInterfaceDef,
+          Fi: ShaderInterfaceDefMatch<Go> + ShaderInterfaceDefMatch<Teo> + ShaderInterfaceDefMatch<Vo>,
+{
+    if let Some(ref tess) = params.tessellation {
+        if let Some(ref gs) = params.geometry_shader {
+            if let Err(err) = tess.tessellation_control_shader.input().matches(params.vertex_shader.output()) {
+                return Err(GraphicsPipelineCreationError::VertexTessControlStagesMismatch(err));
+            }
+            if let Err(err) = tess.tessellation_evaluation_shader.input().matches(tess.tessellation_control_shader.output()) {
+                return Err(GraphicsPipelineCreationError::TessControlTessEvalStagesMismatch(err));
+            }
+            if let Err(err) = gs.input().matches(tess.tessellation_evaluation_shader.output()) {
+                return Err(GraphicsPipelineCreationError::TessEvalGeometryStagesMismatch(err));
+            }
+            if let Some(ref fs) = params.fragment_shader {
+                if let Err(err) = fs.input().matches(gs.output()) {
+                    return Err(GraphicsPipelineCreationError::GeometryFragmentStagesMismatch(err));
                 }
-
-                pl = Box::new(params.vertex_shader.layout().clone()
-                    .union(params.fragment_shader.layout().clone())
-                    .union(params.tessellation.as_ref().unwrap().tessellation_control_shader.layout().clone())    // FIXME: unwrap()
-                    .union(params.tessellation.as_ref().unwrap().tessellation_evaluation_shader.layout().clone())    // FIXME: unwrap()
-                    .build(device.clone()).unwrap()) as Box<_>;      // TODO: error
             }
 
         } else {
-            if let Some(ref geometry_shader) = params.geometry_shader {
-                if let Err(err) = geometry_shader.input().matches(params.vertex_shader.output()) {
-                    return Err(GraphicsPipelineCreationError::VertexGeometryStagesMismatch(err));
+            if let Err(err) = tess.tessellation_control_shader.input().matches(params.vertex_shader.output()) {
+                return Err(GraphicsPipelineCreationError::VertexTessControlStagesMismatch(err));
+            }
+            if let Err(err) = tess.tessellation_evaluation_shader.input().matches(tess.tessellation_control_shader.output()) {
+                return Err(GraphicsPipelineCreationError::TessControlTessEvalStagesMismatch(err));
+            }
+            if let Some(ref fs) = params.fragment_shader {
+                if let Err(err) = fs.input().matches(tess.tessellation_evaluation_shader.output()) {
+                    return Err(GraphicsPipelineCreationError::TessEvalFragmentStagesMismatch(err));
                 }
-                if let Err(err) = params.fragment_shader.input().matches(geometry_shader.output()) {
+            }
+        }
+    } else {
+        if let Some(ref geometry_shader) = params.geometry_shader {
+            if let Err(err) = geometry_shader.input().matches(params.vertex_shader.output()) {
+                return Err(GraphicsPipelineCreationError::VertexGeometryStagesMismatch(err));
+            }
+            if let Some(ref fs) = params.fragment_shader {
+                if let Err(err) = fs.input().matches(geometry_shader.output()) {
                     return Err(GraphicsPipelineCreationError::GeometryFragmentStagesMismatch(err));
                 }
+            }
 
-                pl = Box::new(params.vertex_shader.layout().clone()
-                    .union(params.fragment_shader.layout().clone())
-                    .union(params.geometry_shader.as_ref().unwrap().layout().clone())    // FIXME: unwrap()
-                    .build(device.clone()).unwrap()) as Box<_>;      // TODO: error
-
-            } else {
-                if let Err(err) = params.fragment_shader.input().matches(params.vertex_shader.output()) {
+        } else {
+            if let Some(ref fs) = params.fragment_shader {
+                if let Err(err) = fs.input().matches(params.vertex_shader.output()) {
                     return Err(GraphicsPipelineCreationError::VertexFragmentStagesMismatch(err));
                 }
-
-                pl = Box::new(params.vertex_shader.layout().clone()
-                    .union(params.fragment_shader.layout().clone())
-                    .build(device.clone()).unwrap()) as Box<_>;      // TODO: error
             }
         }
+    }
 
-        GraphicsPipeline::new_inner(device, params, pl)
+    Ok(())
+}
+
+// Builds the pipeline layout made of the union of the layouts of all the shader stages.
+//
+// Returns an error if two stages use the same binding for descriptors of different types.
+fn auto_layout<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gsp, Gi, Go, Gl,
+               Fs, Fi, Fo, Fl, Rp>
+              (device: &Arc<Device>,
+               params: &PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl,
+                                               Tes, Tei, Teo, Tel, Gsp, Gi, Go, Gl, Fs, Fi, Fo,
+                                               Fl, Rp>)
+              -> Result<Box<PipelineLayoutAbstract + Send + Sync>, GraphicsPipelineCreationError>
+    where Vl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+          Fl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+          Tcl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+          Tel: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+          Gl: PipelineLayoutDescNames + Clone + 'static + Send + Sync,
+{
+    let mut desc: Box<PipelineLayoutDescNames + Send + Sync> =
+        Box::new(params.vertex_shader.layout().clone());
+
+    if let Some(ref tess) = params.tessellation {
+        let tcs = tess.tessellation_control_shader.layout().clone();
+        desc = Box::new(PipelineLayoutDescUnion::try_new(desc, tcs)?);
+        let tes = tess.tessellation_evaluation_shader.layout().clone();
+        desc = Box::new(PipelineLayoutDescUnion::try_new(desc, tes)?);
     }
+
+    if let Some(ref gs) = params.geometry_shader {
+        desc = Box::new(PipelineLayoutDescUnion::try_new(desc, gs.layout().clone())?);
+    }
+
+    if let Some(ref fs) = params.fragment_shader {
+        desc = Box::new(PipelineLayoutDescUnion::try_new(desc, fs.layout().clone())?);
+    }
+
+    Ok(Box::new(desc.build(device.clone())?))
+}
+
+// Checks that the input attachment descriptors of the fragment shader can be fed by the input
+// attachments of the subpass.
+//
+// The layout doesn't tell which input attachment index a descriptor reads from, so we can only
+// check that the subpass has input attachments, and that one of them has a number of samples
+// that matches the descriptor.
+fn check_input_attachments<Fl, Rp>(layout: &Fl, subpass: &Subpass<Rp>)
+                                   -> Result<(), GraphicsPipelineCreationError>
+    where Fl: PipelineLayoutDesc,
+          Rp: RenderPassDesc
+{
+    let render_pass = subpass.render_pass();
+    let inputs = render_pass.subpass_desc(subpass.index() as usize)
+                            .map(|desc| desc.input_attachments)
+                            .unwrap_or(Vec::new());
+
+    for set in 0 .. layout.num_sets() {
+        for binding in 0 .. layout.num_bindings_in_set(set).unwrap_or(0) {
+            let multisampled = match layout.descriptor(set, binding).map(|desc| desc.ty) {
+                Some(DescriptorDescTy::InputAttachment { multisampled, .. }) => multisampled,
+                _ => continue,
+            };
+
+            if inputs.is_empty() {
+                return Err(GraphicsPipelineCreationError::MissingInputAttachment {
+                    set: set,
+                    binding: binding,
+                });
+            }
+
+            let samples_match = inputs.iter().any(|&(id, _)| {
+                let samples = render_pass.attachment_desc(id).map(|a| a.samples).unwrap_or(1);
+                (samples > 1) == multisampled
+            });
+
+            if !samples_match {
+                return Err(GraphicsPipelineCreationError::InputAttachmentSamplesMismatch {
+                    set: set,
+                    binding: binding,
+                });
+            }
+        }
+    }
+
+    Ok(())
 }
 
 impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
@@ -451,7 +628,7 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
     fn new_inner<'a, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes, Tei, Teo, Tel, Gsp, Gi, Go, Gl, Fs,
                  Fi, Fo, Fl>
                 (device: Arc<Device>,
-                 params: GraphicsPipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes,
+                 params: PipelineParams<'a, Vdef, Vsp, Vi, Vo, Vl, Tcs, Tci, Tco, Tcl, Tes,
                                                 Tei, Teo, Tel, Gsp, Gi, Go, Gl, Fs, Fi, Fo, Fl, Rp>,
                  pipeline_layout: L)
                  -> Result<GraphicsPipeline<Vdef, L, Rp>, GraphicsPipelineCreationError>
@@ -470,8 +647,10 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
         // TODO: more details in the errors
         PipelineLayoutSuperset::ensure_superset_of(&pipeline_layout,
                                                    params.vertex_shader.layout())?;
-        PipelineLayoutSuperset::ensure_superset_of(&pipeline_layout,
-                                                   params.fragment_shader.layout())?;
+        if let Some(ref fragment_shader) = params.fragment_shader {
+            PipelineLayoutSuperset::ensure_superset_of(&pipeline_layout,
+                                                       fragment_shader.layout())?;
+        }
         if let Some(ref geometry_shader) = params.geometry_shader {
             PipelineLayoutSuperset::ensure_superset_of(&pipeline_layout,
                                                        geometry_shader.layout())?;
@@ -483,12 +662,48 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
                                                        tess.tessellation_evaluation_shader.layout())?;
         }
 
-        // Check that the subpass can accept the output of the fragment shader.
-        if !RenderPassSubpassInterface::is_compatible_with(&params.render_pass.render_pass(),
-                                                           params.render_pass.index(),
-                                                           params.fragment_shader.output())
-        {
-            return Err(GraphicsPipelineCreationError::FragmentShaderRenderPassIncompatible);
+        if let Some(ref fragment_shader) = params.fragment_shader {
+            // Check that the subpass can accept the output of the fragment shader.
+            if !RenderPassSubpassInterface::is_compatible_with(&params.render_pass.render_pass(),
+                                                               params.render_pass.index(),
+                                                               fragment_shader.output())
+            {
+                return Err(GraphicsPipelineCreationError::FragmentShaderRenderPassIncompatible);
+            }
+
+            // Check that the input attachments read by the fragment shader exist.
+            check_input_attachments(fragment_shader.layout(), &params.render_pass)?;
+        } else if !params.raster.rasterizer_discard {
+            // Without a fragment shader the values written to the color attachments are
+            // undefined, so they must not be written at all. The only useful output of such a
+            // pipeline is the depth.
+            if params.render_pass.num_color_attachments() != 0 &&
+               !params.blend.is_write_masked()
+            {
+                return Err(GraphicsPipelineCreationError::NoFragmentShaderColorWrites);
+            }
+
+            if !params.depth_stencil.depth_write {
+                return Err(GraphicsPipelineCreationError::NoFragmentShaderDepthWriteDisabled);
+            }
+        }
+
+        // Check the features required by multiview rendering. The shaders can then read the index
+        // of the view being rendered from the `ViewIndex` built-in.
+        if params.render_pass.view_mask() != 0 {
+            if !device.enabled_features().multiview {
+                return Err(GraphicsPipelineCreationError::MultiviewFeatureNotEnabled);
+            }
+            if params.geometry_shader.is_some() &&
+               !device.enabled_features().multiview_geometry_shader
+            {
+                return Err(GraphicsPipelineCreationError::MultiviewGeometryShaderFeatureNotEnabled);
+            }
+            if params.tessellation.is_some() &&
+               !device.enabled_features().multiview_tessellation_shader
+            {
+                return Err(GraphicsPipelineCreationError::MultiviewTessellationShaderFeatureNotEnabled);
+            }
         }
 
         // Will contain the list of dynamic states. Filled throughout this function.
@@ -508,15 +723,17 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
                 pSpecializationInfo: ptr::null(),       // TODO:
             });
 
-            stages.push(vk::PipelineShaderStageCreateInfo {
-                sType: vk::STRUCTURE_TYPE_PIPELINE_SHADER_STAGE_CREATE_INFO,
-                pNext: ptr::null(),
-                flags: 0,   // reserved
-                stage: vk::SHADER_STAGE_FRAGMENT_BIT,
-                module: params.fragment_shader.module().internal_object(),
-                pName: params.fragment_shader.name().as_ptr(),
-                pSpecializationInfo: ptr::null(),       // TODO:
-            });
+            if let Some(ref fs) = params.fragment_shader {
+                stages.push(vk::PipelineShaderStageCreateInfo {
+                    sType: vk::STRUCTURE_TYPE_PIPELINE_SHADER_STAGE_CREATE_INFO,
+                    pNext: ptr::null(),
+                    flags: 0,   // reserved
+                    stage: vk::SHADER_STAGE_FRAGMENT_BIT,
+                    module: fs.module().internal_object(),
+                    pName: fs.name().as_ptr(),
+                    pSpecializationInfo: ptr::null(),       // TODO:
+                });
+            }
 
             if let Some(ref gs) = params.geometry_shader {
                 if !device.enabled_features().geometry_shader {
@@ -731,21 +948,42 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
         }
 
         for vp in vp_vp.iter() {
+            // A negative height flips the viewport, in which case it extends above `y`.
+            if vp.height < 0.0 && !device.loaded_extensions().khr_maintenance1 {
+                return Err(GraphicsPipelineCreationError::NegativeViewportHeightNotSupported);
+            }
+
+            let (y_min, y_max) = if vp.height < 0.0 { (vp.y + vp.height, vp.y) }
+                                 else { (vp.y, vp.y + vp.height) };
+
             if vp.width > device.physical_device().limits().max_viewport_dimensions()[0] as f32 ||
-               vp.height > device.physical_device().limits().max_viewport_dimensions()[1] as f32
+               vp.height.abs() > device.physical_device().limits().max_viewport_dimensions()[1] as f32
             {
                 return Err(GraphicsPipelineCreationError::MaxViewportDimensionsExceeded);
             }
 
             if vp.x < device.physical_device().limits().viewport_bounds_range()[0] ||
                vp.x + vp.width > device.physical_device().limits().viewport_bounds_range()[1] ||
-               vp.y < device.physical_device().limits().viewport_bounds_range()[0] ||
-               vp.y + vp.height > device.physical_device().limits().viewport_bounds_range()[1]
+               y_min < device.physical_device().limits().viewport_bounds_range()[0] ||
+               y_max > device.physical_device().limits().viewport_bounds_range()[1]
             {
                 return Err(GraphicsPipelineCreationError::ViewportBoundsExceeded);
             }
         }
 
+        let (static_viewports, static_scissors) = match params.viewport {
+            ViewportsState::Fixed { ref data } => {
+                (data.iter().map(|e| e.0.clone()).collect(), data.iter().map(|e| e.1).collect())
+            },
+            ViewportsState::DynamicViewports { ref scissors } => (Vec::new(), scissors.clone()),
+            ViewportsState::DynamicScissors { ref viewports } => (viewports.clone(), Vec::new()),
+            ViewportsState::Dynamic { .. } => (Vec::new(), Vec::new()),
+        };
+
+        if !static_scissors.iter().all(Scissor::is_valid) {
+            return Err(GraphicsPipelineCreationError::InvalidScissor);
+        }
+
         let viewport_info = vk::PipelineViewportStateCreateInfo {
             sType: vk::STRUCTURE_TYPE_PIPELINE_VIEWPORT_STATE_CREATE_INFO,
             pNext: ptr::null(),
@@ -760,6 +998,11 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
             if line_width != 1.0 && !device.enabled_features().wide_lines {
                 return Err(GraphicsPipelineCreationError::WideLinesFeatureNotEnabled);
             }
+
+            let range = device.physical_device().limits().line_width_range();
+            if line_width < range[0] || line_width > range[1] {
+                return Err(GraphicsPipelineCreationError::LineWidthOutOfRange);
+            }
         } else {
             dynamic_states.push(vk::DYNAMIC_STATE_LINE_WIDTH);
         }
@@ -929,7 +1172,10 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
             }
         };
 
-        let blend_atch: SmallVec<[vk::PipelineColorBlendAttachmentState; 8]> = {
+        let blend_atch: SmallVec<[vk::PipelineColorBlendAttachmentState; 8]> = if params.raster.rasterizer_discard {
+            // The blend state is ignored, so there is no need to check it.
+            SmallVec::new()
+        } else {
             let num_atch = params.render_pass.num_color_attachments();
 
             match params.blend.attachments {
@@ -996,11 +1242,27 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
                 pInputAssemblyState: &input_assembly,
                 pTessellationState: tessellation.as_ref().map(|t| t as *const _)
                                                 .unwrap_or(ptr::null()),
-                pViewportState: &viewport_info,
+                pViewportState: if rasterization.rasterizerDiscardEnable == vk::FALSE {
+                    &viewport_info
+                } else {
+                    ptr::null()
+                },
                 pRasterizationState: &rasterization,
-                pMultisampleState: &multisample,
-                pDepthStencilState: &depth_stencil,
-                pColorBlendState: &blend,
+                pMultisampleState: if rasterization.rasterizerDiscardEnable == vk::FALSE {
+                    &multisample
+                } else {
+                    ptr::null()
+                },
+                pDepthStencilState: if rasterization.rasterizerDiscardEnable == vk::FALSE {
+                    &depth_stencil
+                } else {
+                    ptr::null()
+                },
+                pColorBlendState: if rasterization.rasterizerDiscardEnable == vk::FALSE {
+                    &blend
+                } else {
+                    ptr::null()
+                },
                 pDynamicState: dynamic_states.as_ref().map(|s| s as *const _)
                                              .unwrap_or(ptr::null()),
                 layout: PipelineLayoutAbstract::sys(&pipeline_layout).internal_object(),
@@ -1041,6 +1303,8 @@ impl<Vdef, L, Rp> GraphicsPipeline<Vdef, L, Rp>
             dynamic_blend_constants: params.blend.blend_constants.is_none(),
 
             num_viewports: params.viewport.num_viewports(),
+            static_viewports: static_viewports,
+            static_scissors: static_scissors,
         })
     }
 }
@@ -1110,6 +1374,18 @@ impl<Mv, L, Rp> GraphicsPipeline<Mv, L, Rp> {
         self.dynamic_scissor
     }
 
+    /// Returns the viewports of this pipeline, or an empty slice if they are dynamic.
+    #[inline]
+    pub fn static_viewports(&self) -> &[Viewport] {
+        &self.static_viewports
+    }
+
+    /// Returns the scissors of this pipeline, or an empty slice if they are dynamic.
+    #[inline]
+    pub fn static_scissors(&self) -> &[Scissor] {
+        &self.static_scissors
+    }
+
     /// Returns true if the depth bounds used by this pipeline are dynamic.
     #[inline]
     pub fn has_dynamic_depth_bounds(&self) -> bool {
@@ -1238,6 +1514,16 @@ unsafe impl<Mv, L, Rp> RenderPassDesc for GraphicsPipeline<Mv, L, Rp>
     fn dependency_desc(&self, num: usize) -> Option<LayoutPassDependencyDescription> {
         self.render_pass.dependency_desc(num)
     }
+
+    #[inline]
+    fn view_mask(&self, subpass: usize) -> u32 {
+        self.render_pass.view_mask(subpass)
+    }
+
+    #[inline]
+    fn correlation_masks(&self) -> Vec<u32> {
+        self.render_pass.correlation_masks()
+    }
 }
 
 unsafe impl<C, Mv, L, Rp> RenderPassDescClearValues<C> for GraphicsPipeline<Mv, L, Rp>
@@ -1273,6 +1559,34 @@ impl Drop for Inner {
 pub unsafe trait GraphicsPipelineAbstract: PipelineLayoutAbstract + RenderPassAbstract + VertexSource<Vec<Arc<BufferAccess + Send + Sync>>> {
     /// Returns an opaque object that represents the inside of the graphics pipeline.
     fn inner(&self) -> GraphicsPipelineSys;
+
+    /// Returns the index of the subpass of the render pass the pipeline was created for, or
+    /// `None` if it is unknown.
+    ///
+    /// This is used to check that the pipeline is compatible with the subpass it is used in. The
+    /// default implementation returns `None`, in which case this check is skipped.
+    #[inline]
+    fn subpass_index(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns the viewports of the pipeline, or an empty slice if they are dynamic.
+    ///
+    /// The default implementation returns an empty slice, which disables the checks of the
+    /// viewports against the framebuffer.
+    #[inline]
+    fn static_viewports(&self) -> &[Viewport] {
+        &[]
+    }
+
+    /// Returns the scissors of the pipeline, or an empty slice if they are dynamic.
+    ///
+    /// The default implementation returns an empty slice, which disables the checks of the
+    /// scissors against the framebuffer.
+    #[inline]
+    fn static_scissors(&self) -> &[Scissor] {
+        &[]
+    }
 }
 
 unsafe impl<Mv, L, Rp> GraphicsPipelineAbstract for GraphicsPipeline<Mv, L, Rp>
@@ -1282,6 +1596,21 @@ unsafe impl<Mv, L, Rp> GraphicsPipelineAbstract for GraphicsPipeline<Mv, L, Rp>
     fn inner(&self) -> GraphicsPipelineSys {
         GraphicsPipelineSys(self.inner.pipeline, PhantomData)
     }
+
+    #[inline]
+    fn subpass_index(&self) -> Option<u32> {
+        Some(self.render_pass_subpass)
+    }
+
+    #[inline]
+    fn static_viewports(&self) -> &[Viewport] {
+        &self.static_viewports
+    }
+
+    #[inline]
+    fn static_scissors(&self) -> &[Scissor] {
+        &self.static_scissors
+    }
 }
 
 unsafe impl<T> GraphicsPipelineAbstract for T
@@ -1291,6 +1620,21 @@ unsafe impl<T> GraphicsPipelineAbstract for T
     fn inner(&self) -> GraphicsPipelineSys {
         GraphicsPipelineAbstract::inner(&**self)
     }
+
+    #[inline]
+    fn subpass_index(&self) -> Option<u32> {
+        (**self).subpass_index()
+    }
+
+    #[inline]
+    fn static_viewports(&self) -> &[Viewport] {
+        (**self).static_viewports()
+    }
+
+    #[inline]
+    fn static_scissors(&self) -> &[Scissor] {
+        (**self).static_scissors()
+    }
 }
 
 /// Opaque object that represents the inside of the graphics pipeline.
@@ -1414,6 +1758,18 @@ pub enum GraphicsPipelineCreationError {
     /// The `multi_viewport` feature must be enabled in order to use multiple viewports at once.
     MultiViewportFeatureNotEnabled,
 
+    /// The `multiview` feature must be enabled in order to use a subpass that uses multiview
+    /// rendering.
+    MultiviewFeatureNotEnabled,
+
+    /// The `multiview_geometry_shader` feature must be enabled in order to use a geometry shader
+    /// in a subpass that uses multiview rendering.
+    MultiviewGeometryShaderFeatureNotEnabled,
+
+    /// The `multiview_tessellation_shader` feature must be enabled in order to use tessellation
+    /// shaders in a subpass that uses multiview rendering.
+    MultiviewTessellationShaderFeatureNotEnabled,
+
     /// The maximum number of viewports has been exceeded.
     MaxViewportsExceeded {
         /// Maximum allowed value.
@@ -1428,6 +1784,12 @@ pub enum GraphicsPipelineCreationError {
     /// The minimum or maximum bounds of viewports have been exceeded.
     ViewportBoundsExceeded,
 
+    /// A scissor has a negative origin, or its bottom-right hand corner doesn't fit in an `i32`.
+    InvalidScissor,
+
+    /// A viewport has a negative height, which requires the `VK_KHR_maintenance1` extension.
+    NegativeViewportHeightNotSupported,
+
     /// The `wide_lines` feature must be enabled in order to use a line width superior to 1.0.
     WideLinesFeatureNotEnabled,
 
@@ -1482,6 +1844,40 @@ pub enum GraphicsPipelineCreationError {
 
     /// The `maxTessellationPatchSize` limit was exceeded.
     MaxTessellationPatchSizeExceeded,
+
+    /// Two shader stages use the same binding for descriptors of different types.
+    ShaderLayoutsConflict(PipelineLayoutDescUnionError),
+
+    /// Error while creating the pipeline layout derived from the shaders.
+    PipelineLayoutCreationError(PipelineLayoutCreationError),
+
+    /// There is no fragment shader, but the subpass has color attachments whose writes are not
+    /// masked off by the blend state.
+    NoFragmentShaderColorWrites,
+
+    /// There is no fragment shader, but depth writes are disabled.
+    NoFragmentShaderDepthWriteDisabled,
+
+    /// The line width is outside of the `line_width_range` limit.
+    LineWidthOutOfRange,
+
+    /// The fragment shader reads from an input attachment, but the subpass doesn't have any
+    /// input attachment.
+    MissingInputAttachment {
+        /// The set of the input attachment descriptor.
+        set: usize,
+        /// The binding of the input attachment descriptor.
+        binding: usize,
+    },
+
+    /// The fragment shader reads from a multisampled input attachment but the input attachments
+    /// of the subpass are all single-sampled, or vice versa.
+    InputAttachmentSamplesMismatch {
+        /// The set of the input attachment descriptor.
+        set: usize,
+        /// The binding of the input attachment descriptor.
+        binding: usize,
+    },
 }
 
 impl error::Error for GraphicsPipelineCreationError {
@@ -1546,6 +1942,18 @@ impl error::Error for GraphicsPipelineCreationError {
                 "the `multi_viewport` feature must be enabled in order to use multiple viewports \
                  at once"
             },
+            GraphicsPipelineCreationError::MultiviewFeatureNotEnabled => {
+                "the `multiview` feature must be enabled in order to use a subpass that uses \
+                 multiview rendering"
+            },
+            GraphicsPipelineCreationError::MultiviewGeometryShaderFeatureNotEnabled => {
+                "the `multiview_geometry_shader` feature must be enabled in order to use a \
+                 geometry shader in a subpass that uses multiview rendering"
+            },
+            GraphicsPipelineCreationError::MultiviewTessellationShaderFeatureNotEnabled => {
+                "the `multiview_tessellation_shader` feature must be enabled in order to use \
+                 tessellation shaders in a subpass that uses multiview rendering"
+            },
             GraphicsPipelineCreationError::MaxViewportsExceeded { .. } => {
                 "the maximum number of viewports has been exceeded"
             },
@@ -1555,6 +1963,14 @@ impl error::Error for GraphicsPipelineCreationError {
             GraphicsPipelineCreationError::ViewportBoundsExceeded => {
                 "the minimum or maximum bounds of viewports have been exceeded"
             },
+            GraphicsPipelineCreationError::InvalidScissor => {
+                "a scissor has a negative origin, or its bottom-right hand corner doesn't fit in \
+                 an `i32`"
+            },
+            GraphicsPipelineCreationError::NegativeViewportHeightNotSupported => {
+                "a viewport has a negative height, which requires the `VK_KHR_maintenance1` \
+                 extension"
+            },
             GraphicsPipelineCreationError::WideLinesFeatureNotEnabled => {
                 "the `wide_lines` feature must be enabled in order to use a line width \
                  superior to 1.0"
@@ -1610,6 +2026,30 @@ impl error::Error for GraphicsPipelineCreationError {
             GraphicsPipelineCreationError::MaxTessellationPatchSizeExceeded => {
                 "the maximum tessellation patch size was exceeded"
             },
+            GraphicsPipelineCreationError::ShaderLayoutsConflict(_) => {
+                "two shader stages use the same binding for descriptors of different types"
+            },
+            GraphicsPipelineCreationError::PipelineLayoutCreationError(_) => {
+                "error while creating the pipeline layout derived from the shaders"
+            },
+            GraphicsPipelineCreationError::NoFragmentShaderColorWrites => {
+                "there is no fragment shader, but the color attachments of the subpass are \
+                 written"
+            },
+            GraphicsPipelineCreationError::NoFragmentShaderDepthWriteDisabled => {
+                "there is no fragment shader, but depth writes are disabled"
+            },
+            GraphicsPipelineCreationError::LineWidthOutOfRange => {
+                "the line width is outside of the range supported by the device"
+            },
+            GraphicsPipelineCreationError::MissingInputAttachment { .. } => {
+                "the fragment shader reads from an input attachment, but the subpass doesn't \
+                 have any input attachment"
+            },
+            GraphicsPipelineCreationError::InputAttachmentSamplesMismatch { .. } => {
+                "the number of samples of an input attachment descriptor doesn't match the input \
+                 attachments of the subpass"
+            },
         }
     }
 
@@ -1626,6 +2066,8 @@ impl error::Error for GraphicsPipelineCreationError {
             GraphicsPipelineCreationError::TessEvalFragmentStagesMismatch(ref err) => Some(err),
             GraphicsPipelineCreationError::GeometryFragmentStagesMismatch(ref err) => Some(err),
             GraphicsPipelineCreationError::IncompatibleVertexDefinition(ref err) => Some(err),
+            GraphicsPipelineCreationError::ShaderLayoutsConflict(ref err) => Some(err),
+            GraphicsPipelineCreationError::PipelineLayoutCreationError(ref err) => Some(err),
             _ => None
         }
     }
@@ -1652,6 +2094,20 @@ impl From<PipelineLayoutNotSupersetError> for GraphicsPipelineCreationError {
     }
 }
 
+impl From<PipelineLayoutDescUnionError> for GraphicsPipelineCreationError {
+    #[inline]
+    fn from(err: PipelineLayoutDescUnionError) -> GraphicsPipelineCreationError {
+        GraphicsPipelineCreationError::ShaderLayoutsConflict(err)
+    }
+}
+
+impl From<PipelineLayoutCreationError> for GraphicsPipelineCreationError {
+    #[inline]
+    fn from(err: PipelineLayoutCreationError) -> GraphicsPipelineCreationError {
+        GraphicsPipelineCreationError::PipelineLayoutCreationError(err)
+    }
+}
+
 impl From<IncompatibleVertexDefinitionError> for GraphicsPipelineCreationError {
     #[inline]
     fn from(err: IncompatibleVertexDefinitionError) -> GraphicsPipelineCreationError {
@@ -10,12 +10,24 @@
 //! This module contains the unit tests of `GraphicsPipeline`.
 
 #![cfg(test)]
+#![allow(deprecated)]
 
+use std::ffi::CStr;
 use std::ffi::CString;
+use std::sync::Arc;
+use command_buffer::AutoCommandBufferBuilder;
+use command_buffer::CommandAddError;
+use command_buffer::CommandBufferBuilder;
+use command_buffer::SubpassContents;
+use command_buffer::cb::AddCommand;
+use command_buffer::commands_raw::CmdBindPipeline;
+use device::Device;
 use format::Format;
+use framebuffer::Framebuffer;
+use framebuffer::RenderPassAbstract;
 use framebuffer::Subpass;
+use image::AttachmentImage;
 use descriptor::pipeline_layout::EmptyPipelineDesc;
-use descriptor::pipeline_layout::PipelineLayoutDesc;
 use pipeline::GraphicsPipeline;
 use pipeline::GraphicsPipelineParams;
 use pipeline::GraphicsPipelineCreationError;
@@ -26,7 +38,10 @@ use pipeline::input_assembly::PrimitiveTopology;
 use pipeline::multisample::Multisample;
 use pipeline::shader::ShaderModule;
 use pipeline::shader::EmptyShaderInterfaceDef;
+use pipeline::shader::VertexShaderEntryPoint;
 use pipeline::vertex::SingleBufferDefinition;
+use pipeline::viewport::Scissor;
+use pipeline::viewport::Viewport;
 use pipeline::viewport::ViewportsState;
 
 #[test]
@@ -36,7 +51,7 @@ fn create() {
     let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
     let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
 
-    let _ = GraphicsPipeline::new(&device, GraphicsPipelineParams {
+    let _ = GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
         vertex_input: SingleBufferDefinition::<()>::new(),
         vertex_shader: unsafe {
             vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
@@ -58,9 +73,7 @@ fn create() {
         },
         depth_stencil: DepthStencil::disabled(),
         blend: Blend::pass_through(),
-        render_pass: Subpass::from(simple_rp::CustomRenderPass::new(&device, &{
-            simple_rp::Formats { color: (Format::R8G8B8A8Unorm, 1) }
-        }).unwrap(), 0).unwrap(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
     }).unwrap();
 }
 
@@ -71,7 +84,7 @@ fn bad_primitive_restart() {
     let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
     let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
 
-    let result = GraphicsPipeline::new(&device, GraphicsPipelineParams {
+    let result = GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
         vertex_input: SingleBufferDefinition::<()>::new(),
         vertex_shader: unsafe {
             vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
@@ -96,9 +109,7 @@ fn bad_primitive_restart() {
         },
         depth_stencil: DepthStencil::disabled(),
         blend: Blend::pass_through(),
-        render_pass: Subpass::from(simple_rp::CustomRenderPass::new(&device, &{
-            simple_rp::Formats { color: (Format::R8G8B8A8Unorm, 1) }
-        }).unwrap(), 0).unwrap(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
     });
 
     match result {
@@ -114,7 +125,7 @@ fn multi_viewport_feature() {
     let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
     let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
 
-    let result = GraphicsPipeline::new(&device, GraphicsPipelineParams {
+    let result = GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
         vertex_input: SingleBufferDefinition::<()>::new(),
         vertex_shader: unsafe {
             vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
@@ -136,9 +147,7 @@ fn multi_viewport_feature() {
         },
         depth_stencil: DepthStencil::disabled(),
         blend: Blend::pass_through(),
-        render_pass: Subpass::from(simple_rp::CustomRenderPass::new(&device, &{
-            simple_rp::Formats { color: (Format::R8G8B8A8Unorm, 1) }
-        }).unwrap(), 0).unwrap(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
     });
 
     match result {
@@ -154,7 +163,7 @@ fn max_viewports() {
     let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
     let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
 
-    let result = GraphicsPipeline::new(&device, GraphicsPipelineParams {
+    let result = GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
         vertex_input: SingleBufferDefinition::<()>::new(),
         vertex_shader: unsafe {
             vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
@@ -176,9 +185,7 @@ fn max_viewports() {
         },
         depth_stencil: DepthStencil::disabled(),
         blend: Blend::pass_through(),
-        render_pass: Subpass::from(simple_rp::CustomRenderPass::new(&device, &{
-            simple_rp::Formats { color: (Format::R8G8B8A8Unorm, 1) }
-        }).unwrap(), 0).unwrap(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
     });
 
     match result {
@@ -187,6 +194,50 @@ fn max_viewports() {
     }
 }
 
+#[test]
+fn negative_viewport_height() {
+    let (device, _) = gfx_dev_and_queue!();
+
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+    let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
+
+    let viewport = Viewport {
+        origin: [0.0, 0.0],
+        dimensions: [16.0, 16.0],
+        depth_range: 0.0 .. 1.0,
+    };
+
+    let result = GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
+        vertex_input: SingleBufferDefinition::<()>::new(),
+        vertex_shader: unsafe {
+            vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
+                                                        EmptyShaderInterfaceDef,
+                                                        EmptyShaderInterfaceDef,
+                                                        EmptyPipelineDesc)
+        },
+        input_assembly: InputAssembly::triangle_list(),
+        tessellation: None,
+        geometry_shader: None,
+        viewport: ViewportsState::Fixed { data: vec![(viewport.flip_y(), Scissor::irrelevant())] },
+        raster: Default::default(),
+        multisample: Multisample::disabled(),
+        fragment_shader: unsafe {
+            fs.fragment_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
+                                                          EmptyShaderInterfaceDef,
+                                                          EmptyShaderInterfaceDef,
+                                                          EmptyPipelineDesc)
+        },
+        depth_stencil: DepthStencil::disabled(),
+        blend: Blend::pass_through(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
+    });
+
+    match result {
+        Err(GraphicsPipelineCreationError::NegativeViewportHeightNotSupported) => (),
+        _ => panic!()
+    }
+}
+
 #[test]
 fn no_depth_attachment() {
     let (device, _) = gfx_dev_and_queue!();
@@ -194,7 +245,7 @@ fn no_depth_attachment() {
     let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
     let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
 
-    let result = GraphicsPipeline::new(&device, GraphicsPipelineParams {
+    let result = GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
         vertex_input: SingleBufferDefinition::<()>::new(),
         vertex_shader: unsafe {
             vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
@@ -216,9 +267,7 @@ fn no_depth_attachment() {
         },
         depth_stencil: DepthStencil::simple_depth_test(),
         blend: Blend::pass_through(),
-        render_pass: Subpass::from(simple_rp::CustomRenderPass::new(&device, &{
-            simple_rp::Formats { color: (Format::R8G8B8A8Unorm, 1) }
-        }).unwrap(), 0).unwrap(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
     });
 
     match result {
@@ -227,25 +276,208 @@ fn no_depth_attachment() {
     }
 }
 
+#[test]
+fn incompatible_render_pass() {
+    let (device, queue) = gfx_dev_and_queue!();
 
-mod simple_rp {
-    use format::Format;
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+    let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
+
+    let pipeline = Arc::new(GraphicsPipeline::new(device.clone(), GraphicsPipelineParams {
+        vertex_input: SingleBufferDefinition::<()>::new(),
+        vertex_shader: unsafe {
+            vs.vertex_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
+                                                        EmptyShaderInterfaceDef,
+                                                        EmptyShaderInterfaceDef,
+                                                        EmptyPipelineDesc)
+        },
+        input_assembly: InputAssembly::triangle_list(),
+        tessellation: None,
+        geometry_shader: None,
+        viewport: ViewportsState::Dynamic { num: 1 },
+        raster: Default::default(),
+        multisample: Multisample::disabled(),
+        fragment_shader: unsafe {
+            fs.fragment_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
+                                                          EmptyShaderInterfaceDef,
+                                                          EmptyShaderInterfaceDef,
+                                                          EmptyPipelineDesc)
+        },
+        depth_stencil: DepthStencil::disabled(),
+        blend: Blend::pass_through(),
+        render_pass: Subpass::from(simple_render_pass(&device), 0).unwrap(),
+    }).unwrap());
 
-    single_pass_renderpass!{
+    // Same as `simple_render_pass`, except for the format of the attachment.
+    let render_pass = Arc::new(single_pass_renderpass!(device.clone(),
         attachments: {
             color: {
                 load: Clear,
                 store: Store,
-                format: Format,
+                format: Format::B8G8R8A8Unorm,
+                samples: 1,
             }
         },
         pass: {
             color: [color],
             depth_stencil: {}
         }
+    ).unwrap());
+
+    let image = AttachmentImage::new(device.clone(), [16, 16], Format::B8G8R8A8Unorm).unwrap();
+    let framebuffer = Arc::new(Framebuffer::start(render_pass).add(image).unwrap()
+                                                              .build().unwrap());
+
+    let result = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+        .begin_render_pass(framebuffer, SubpassContents::Inline,
+                           vec![[0.0, 0.0, 0.0, 1.0].into()]).unwrap()
+        .add(CmdBindPipeline::bind_graphics_pipeline(pipeline));
+
+    match result {
+        Err(CommandAddError::IncompatibleRenderPass) => (),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn no_fragment_shader_depth_only() {
+    let (device, _) = gfx_dev_and_queue!();
+
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+
+    let render_pass = Arc::new(single_pass_renderpass!(device.clone(),
+        attachments: {
+            depth: {
+                load: Clear,
+                store: Store,
+                format: Format::D16Unorm,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [],
+            depth_stencil: {depth}
+        }
+    ).unwrap());
+
+    let result = GraphicsPipeline::start()
+        .vertex_shader(basic_vs_entry_point(&vs), ())
+        .viewports_dynamic_scissors_irrelevant(1)
+        .depth_stencil_simple_depth()
+        .render_pass(Subpass::from(render_pass, 0).unwrap())
+        .build(device.clone());
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn no_fragment_shader_color_writes() {
+    let (device, _) = gfx_dev_and_queue!();
+
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+
+    let result = GraphicsPipeline::start()
+        .vertex_shader(basic_vs_entry_point(&vs), ())
+        .viewports_dynamic_scissors_irrelevant(1)
+        .depth_stencil_simple_depth()
+        .render_pass(Subpass::from(simple_render_pass(&device), 0).unwrap())
+        .build(device.clone());
+
+    match result {
+        Err(GraphicsPipelineCreationError::NoFragmentShaderColorWrites) => (),
+        _ => panic!()
     }
 }
 
+#[test]
+fn no_fragment_shader_depth_write_disabled() {
+    let (device, _) = gfx_dev_and_queue!();
+
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+
+    let result = GraphicsPipeline::start()
+        .vertex_shader(basic_vs_entry_point(&vs), ())
+        .viewports_dynamic_scissors_irrelevant(1)
+        .depth_stencil_disabled()
+        .blend_write_masked()
+        .render_pass(Subpass::from(simple_render_pass(&device), 0).unwrap())
+        .build(device.clone());
+
+    match result {
+        Err(GraphicsPipelineCreationError::NoFragmentShaderDepthWriteDisabled) => (),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn rasterizer_discard() {
+    let (device, _) = gfx_dev_and_queue!();
+
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+
+    // Neither the viewports nor the fragment shader are needed, and the color attachment of the
+    // subpass is ignored.
+    let result = GraphicsPipeline::start()
+        .vertex_shader(basic_vs_entry_point(&vs), ())
+        .rasterizer_discard(true)
+        .render_pass(Subpass::from(simple_render_pass(&device), 0).unwrap())
+        .build(device.clone());
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn rasterizer_discard_with_fragment_shader() {
+    let (device, _) = gfx_dev_and_queue!();
+
+    let vs = unsafe { ShaderModule::new(device.clone(), &BASIC_VS).unwrap() };
+    let fs = unsafe { ShaderModule::new(device.clone(), &BASIC_FS).unwrap() };
+
+    // Vulkan allows a fragment shader even though it never runs.
+    let result = GraphicsPipeline::start()
+        .vertex_shader(basic_vs_entry_point(&vs), ())
+        .fragment_shader(unsafe {
+            fs.fragment_shader_entry_point::<(), _, _, _>(&CString::new("main").unwrap(),
+                                                          EmptyShaderInterfaceDef,
+                                                          EmptyShaderInterfaceDef,
+                                                          EmptyPipelineDesc)
+        }, ())
+        .rasterizer_discard(true)
+        .render_pass(Subpass::from(simple_render_pass(&device), 0).unwrap())
+        .build(device.clone());
+
+    assert!(result.is_ok());
+}
+
+fn basic_vs_entry_point(vs: &ShaderModule)
+                        -> VertexShaderEntryPoint<(), EmptyShaderInterfaceDef,
+                                                  EmptyShaderInterfaceDef, EmptyPipelineDesc>
+{
+    unsafe {
+        vs.vertex_shader_entry_point::<(), _, _, _>(CStr::from_bytes_with_nul(b"main\0").unwrap(),
+                                                    EmptyShaderInterfaceDef,
+                                                    EmptyShaderInterfaceDef,
+                                                    EmptyPipelineDesc)
+    }
+}
+
+fn simple_render_pass(device: &Arc<Device>) -> Arc<RenderPassAbstract + Send + Sync> {
+    Arc::new(single_pass_renderpass!(device.clone(),
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: Format::R8G8B8A8Unorm,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    ).unwrap())
+}
+
 /*
     #version 450
 
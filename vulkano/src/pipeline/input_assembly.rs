@@ -92,11 +92,21 @@ impl PrimitiveTopology {
 }
 
 /// Trait for types that can be used as indices by the GPU.
+///
+/// > **Note**: Using `u8` indices requires the `ext_index_type_uint8` extension and the
+/// > `index_type_uint8` feature to be enabled on the device.
 pub unsafe trait Index {
     /// Returns the type of data.
     fn ty() -> IndexType;
 }
 
+unsafe impl Index for u8 {
+    #[inline(always)]
+    fn ty() -> IndexType {
+        IndexType::U8
+    }
+}
+
 unsafe impl Index for u16 {
     #[inline(always)]
     fn ty() -> IndexType {
@@ -112,10 +122,23 @@ unsafe impl Index for u32 {
 }
 
 /// An enumeration of all valid index types.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(missing_docs)]
 #[repr(u32)]
 pub enum IndexType {
+    U8 = vk::INDEX_TYPE_UINT8_EXT,
     U16 = vk::INDEX_TYPE_UINT16,
     U32 = vk::INDEX_TYPE_UINT32,
 }
+
+impl IndexType {
+    /// Returns the size in bytes of one index of this type.
+    #[inline]
+    pub fn size(&self) -> usize {
+        match *self {
+            IndexType::U8 => 1,
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        }
+    }
+}
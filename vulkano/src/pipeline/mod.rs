@@ -75,10 +75,14 @@
 // to avoid duplicating code, so we hide the warnings for now
 #![allow(deprecated)]
 
+use vk;
+
 pub use self::compute_pipeline::ComputePipeline;
 pub use self::compute_pipeline::ComputePipelineAbstract;
 pub use self::compute_pipeline::ComputePipelineCreationError;
 pub use self::compute_pipeline::ComputePipelineSys;
+pub use self::compute_pipeline::DescriptorBindingRequirements;
+pub use self::compute_pipeline::DescriptorSetRequirements;
 pub use self::graphics_pipeline::GraphicsPipeline;
 pub use self::graphics_pipeline::GraphicsPipelineAbstract;
 pub use self::graphics_pipeline::GraphicsPipelineBuilder;
@@ -99,3 +103,17 @@ pub mod raster;
 pub mod shader;
 pub mod vertex;
 pub mod viewport;
+
+/// Slot of a command buffer where a pipeline and its descriptor sets are bound.
+///
+/// Graphics and compute pipelines are bound to different slots, and each slot has its own set of
+/// bound descriptor sets. Binding descriptor sets for a compute dispatch doesn't modify the ones
+/// bound for the draw commands, and vice versa.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum PipelineBindPoint {
+    /// Slot used by the draw commands.
+    Graphics = vk::PIPELINE_BIND_POINT_GRAPHICS,
+    /// Slot used by the dispatch commands.
+    Compute = vk::PIPELINE_BIND_POINT_COMPUTE,
+}
@@ -130,11 +130,18 @@ impl Default for FrontFace {
     }
 }
 
+/// Specifies how polygons are rasterized.
+///
+/// Any other mode than `Fill` requires the `fill_mode_non_solid` feature to be enabled on the
+/// device.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum PolygonMode {
+    /// The interior of the polygons is filled. This is the default.
     Fill = vk::POLYGON_MODE_FILL,
+    /// Only the edges of the polygons are drawn, as lines. Useful for wireframe rendering.
     Line = vk::POLYGON_MODE_LINE,
+    /// Only the vertices of the polygons are drawn, as points.
     Point = vk::POLYGON_MODE_POINT,
 }
 
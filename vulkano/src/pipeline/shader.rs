@@ -54,6 +54,10 @@ pub struct ShaderModule<P = Arc<Device>> where P: SafeDeref<Target = Device> {
 impl<P> ShaderModule<P> where P: SafeDeref<Target = Device> {
     /// Builds a new shader module from SPIR-V.
     ///
+    /// # Panic
+    ///
+    /// - Panics if the length of `spirv` is not a multiple of 4.
+    ///
     /// # Safety
     ///
     /// - The SPIR-V code is not validated.
@@ -61,15 +65,41 @@ impl<P> ShaderModule<P> where P: SafeDeref<Target = Device> {
     ///   this function either.
     ///
     pub unsafe fn new(device: P, spirv: &[u8]) -> Result<Arc<ShaderModule<P>>, OomError> {
-        debug_assert!((spirv.len() % 4) == 0);
+        assert!((spirv.len() % 4) == 0, "The length of SPIR-V code must be a multiple of 4");
+        ShaderModule::from_ptr(device, spirv.as_ptr() as *const _, spirv.len())
+    }
 
+    /// Builds a new shader module from SPIR-V words.
+    ///
+    /// Contrary to `new`, this function checks that the code starts with a valid SPIR-V header
+    /// and that the word counts of the instructions are consistent with the length of the code.
+    /// An `InvalidSpirv` error is returned otherwise. The instructions themselves are not
+    /// validated.
+    ///
+    /// # Safety
+    ///
+    /// - The SPIR-V instructions are not validated.
+    /// - The SPIR-V code may require some features that are not enabled. This isn't checked by
+    ///   this function either.
+    ///
+    pub unsafe fn from_words(device: P, words: &[u32])
+                             -> Result<Arc<ShaderModule<P>>, ShaderModuleCreationError>
+    {
+        try!(check_spirv(words));
+        Ok(try!(ShaderModule::from_ptr(device, words.as_ptr(), words.len() * 4)))
+    }
+
+    // Actual implementation of `new` and `from_words`. `size` is in bytes.
+    unsafe fn from_ptr(device: P, code: *const u32, size: usize)
+                       -> Result<Arc<ShaderModule<P>>, OomError>
+    {
         let module = {
             let infos = vk::ShaderModuleCreateInfo {
                 sType: vk::STRUCTURE_TYPE_SHADER_MODULE_CREATE_INFO,
                 pNext: ptr::null(),
                 flags: 0,   // reserved
-                codeSize: spirv.len(),
-                pCode: spirv.as_ptr() as *const _,
+                codeSize: size,
+                pCode: code,
             };
 
             let vk = device.pointers();
@@ -259,6 +289,122 @@ impl<P> Drop for ShaderModule<P> where P: SafeDeref<Target = Device> {
     }
 }
 
+// Magic number at the start of every SPIR-V module.
+const SPIRV_MAGIC: u32 = 0x07230203;
+// Number of words of the header of a SPIR-V module.
+const SPIRV_HEADER_LEN: usize = 5;
+
+// Checks the header of a SPIR-V module and the word counts of its instructions.
+fn check_spirv(words: &[u32]) -> Result<(), InvalidSpirvError> {
+    if words.len() < SPIRV_HEADER_LEN {
+        return Err(InvalidSpirvError::MissingHeader);
+    }
+
+    if words[0] != SPIRV_MAGIC {
+        return Err(InvalidSpirvError::WrongMagicNumber { found: words[0] });
+    }
+
+    let mut offset = SPIRV_HEADER_LEN;
+    while offset < words.len() {
+        let word_count = (words[offset] >> 16) as usize;
+        if word_count == 0 || offset + word_count > words.len() {
+            return Err(InvalidSpirvError::InvalidWordCount { offset: offset });
+        }
+        offset += word_count;
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when creating a shader module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShaderModuleCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// The SPIR-V code is malformed.
+    InvalidSpirv(InvalidSpirvError),
+}
+
+impl error::Error for ShaderModuleCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ShaderModuleCreationError::OomError(_) => "not enough memory available",
+            ShaderModuleCreationError::InvalidSpirv(_) => "the SPIR-V code is malformed",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ShaderModuleCreationError::OomError(ref err) => Some(err),
+            ShaderModuleCreationError::InvalidSpirv(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for ShaderModuleCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ShaderModuleCreationError {
+    #[inline]
+    fn from(err: OomError) -> ShaderModuleCreationError {
+        ShaderModuleCreationError::OomError(err)
+    }
+}
+
+impl From<InvalidSpirvError> for ShaderModuleCreationError {
+    #[inline]
+    fn from(err: InvalidSpirvError) -> ShaderModuleCreationError {
+        ShaderModuleCreationError::InvalidSpirv(err)
+    }
+}
+
+/// Reason why SPIR-V code is malformed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidSpirvError {
+    /// The code is shorter than the header of a SPIR-V module.
+    MissingHeader,
+    /// The code doesn't start with the SPIR-V magic number.
+    WrongMagicNumber {
+        /// The first word of the code.
+        found: u32,
+    },
+    /// The word count of an instruction is 0 or goes past the end of the code.
+    InvalidWordCount {
+        /// Offset in words of the instruction.
+        offset: usize,
+    },
+}
+
+impl error::Error for InvalidSpirvError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            InvalidSpirvError::MissingHeader => {
+                "the code is shorter than the header of a SPIR-V module"
+            },
+            InvalidSpirvError::WrongMagicNumber { .. } => {
+                "the code doesn't start with the SPIR-V magic number"
+            },
+            InvalidSpirvError::InvalidWordCount { .. } => {
+                "the word count of an instruction is 0 or goes past the end of the code"
+            },
+        }
+    }
+}
+
+impl fmt::Display for InvalidSpirvError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
 /// Represents the entry point of a vertex shader in a shader module.
 ///
 /// Can be obtained by calling `vertex_shader_entry_point()` on the shader module.
@@ -721,3 +867,44 @@ pub struct SpecializationMapEntry {
     /// Size of the data in bytes.
     pub size: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::check_spirv;
+    use pipeline::shader::InvalidSpirvError;
+
+    // Header followed by an `OpCapability Shader` instruction.
+    const MINIMAL: [u32; 7] = [0x07230203, 0x00010000, 0, 1, 0, 0x00020011, 1];
+
+    #[test]
+    fn valid() {
+        assert!(check_spirv(&MINIMAL).is_ok());
+        assert!(check_spirv(&MINIMAL[.. 5]).is_ok());
+    }
+
+    #[test]
+    fn missing_header() {
+        assert_eq!(check_spirv(&MINIMAL[.. 4]), Err(InvalidSpirvError::MissingHeader));
+    }
+
+    #[test]
+    fn wrong_magic() {
+        let mut code = MINIMAL;
+        code[0] = 0x03022307;
+        assert_eq!(check_spirv(&code),
+                   Err(InvalidSpirvError::WrongMagicNumber { found: 0x03022307 }));
+    }
+
+    #[test]
+    fn truncated_instruction() {
+        assert_eq!(check_spirv(&MINIMAL[.. 6]),
+                   Err(InvalidSpirvError::InvalidWordCount { offset: 5 }));
+    }
+
+    #[test]
+    fn zero_word_count() {
+        let mut code = MINIMAL;
+        code[5] = 0x00000011;
+        assert_eq!(check_spirv(&code), Err(InvalidSpirvError::InvalidWordCount { offset: 5 }));
+    }
+}
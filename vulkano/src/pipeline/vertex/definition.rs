@@ -110,6 +110,15 @@ pub unsafe trait VertexSource<L> {
     // TODO: better than a Vec
     // TODO: return a struct instead
     fn decode<'l>(&self, &'l L) -> (Vec<BufferInner<'l>>, usize, usize);
+
+    /// Returns true if some of the buffers are read once per instance instead of once per vertex.
+    ///
+    /// If false, the number of instances returned by `decode` is the default number of
+    /// instances to draw, and not a limit.
+    #[inline]
+    fn has_per_instance_buffers(&self) -> bool {
+        false
+    }
 }
 
 unsafe impl<L, T> VertexSource<L> for T where T: SafeDeref, T::Target: VertexSource<L> {
@@ -117,4 +126,9 @@ unsafe impl<L, T> VertexSource<L> for T where T: SafeDeref, T::Target: VertexSou
     fn decode<'l>(&self, list: &'l L) -> (Vec<BufferInner<'l>>, usize, usize) {
         (**self).decode(list)
     }
+
+    #[inline]
+    fn has_per_instance_buffers(&self) -> bool {
+        (**self).has_per_instance_buffers()
+    }
 }
@@ -92,9 +92,14 @@ unsafe impl<T, U> VertexSource<Vec<Arc<BufferAccess + Send + Sync>>> for OneVert
         // FIXME: safety
         assert_eq!(source.len(), 2);
         let len = source[0].size() / mem::size_of::<T>();
-        let inst = source[0].size() / mem::size_of::<U>();
+        let inst = source[1].size() / mem::size_of::<U>();
         (vec![source[0].inner(), source[1].inner()], len, inst)
     }
+
+    #[inline]
+    fn has_per_instance_buffers(&self) -> bool {
+        true
+    }
 }
 
 unsafe impl<'a, T, U, Bt, Bu> VertexSource<(Bt, Bu)> for OneVertexOneInstanceDefinition<T, U>
@@ -105,4 +110,9 @@ unsafe impl<'a, T, U, Bt, Bu> VertexSource<(Bt, Bu)> for OneVertexOneInstanceDef
     fn decode<'l>(&self, source: &'l (Bt, Bu)) -> (Vec<BufferInner<'l>>, usize, usize) {
         (vec![source.0.inner(), source.1.inner()], source.0.len(), source.1.len())
     }
+
+    #[inline]
+    fn has_per_instance_buffers(&self) -> bool {
+        true
+    }
 }
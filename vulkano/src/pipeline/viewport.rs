@@ -47,7 +47,19 @@
 //!
 //! In all cases the number of viewports and scissor boxes must be the same.
 //!
+//! # Flipping the Y axis
+//!
+//! In Vulkan the Y axis of the framebuffer points downwards, contrary to OpenGL. If the
+//! `VK_KHR_maintenance1` extension is enabled on the device, a viewport can have a negative
+//! height, in which case the Y axis is flipped: the vertex coordinate `-1.0` is mapped to
+//! `origin[1]` and `1.0` to `origin[1] + dimensions[1]`, which is above the origin.
+//!
+//! To flip a viewport while covering the same region of the framebuffer, the origin must
+//! therefore be moved to the bottom of the region. This is what `Viewport::flip_y` does. Using a
+//! negative height without the extension is an error.
+//!
 use std::ops::Range;
+use instance::Limits;
 use vk;
 
 /// List of viewports and scissors that are used when creating a graphics pipeline object.
@@ -119,9 +131,6 @@ impl ViewportsState {
 }
 
 /// State of a single viewport.
-// FIXME: check that:
-//        x + width must be less than or equal to viewportBoundsRange[0]
-//        y + height must be less than or equal to viewportBoundsRange[1] 
 #[derive(Debug, Clone)]
 pub struct Viewport {
     /// Coordinates in pixels of the top-left hand corner of the viewport.
@@ -140,6 +149,74 @@ pub struct Viewport {
     pub depth_range: Range<f32>,
 }
 
+impl Viewport {
+    /// Builds a viewport that covers the whole of a framebuffer of the given width and height,
+    /// with a depth range of `0.0` to `1.0`.
+    #[inline]
+    pub fn fullscreen(dimensions: [u32; 2]) -> Viewport {
+        Viewport::fullscreen_with_depth_range(dimensions, 0.0 .. 1.0)
+    }
+
+    /// Same as `fullscreen`, but with a custom depth range.
+    #[inline]
+    pub fn fullscreen_with_depth_range(dimensions: [u32; 2], depth_range: Range<f32>)
+                                       -> Viewport
+    {
+        Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: depth_range,
+        }
+    }
+
+    /// Returns a viewport that covers the same region, but with the Y axis flipped.
+    ///
+    /// The origin is moved to the other horizontal edge of the region and the height is negated.
+    /// A negative height requires the `VK_KHR_maintenance1` extension. See the documentation of
+    /// the `viewport` module.
+    #[inline]
+    pub fn flip_y(self) -> Viewport {
+        Viewport {
+            origin: [self.origin[0], self.origin[1] + self.dimensions[1]],
+            dimensions: [self.dimensions[0], -self.dimensions[1]],
+            depth_range: self.depth_range,
+        }
+    }
+
+    /// Returns true if the viewport respects the `max_viewport_dimensions` and
+    /// `viewport_bounds_range` limits.
+    pub fn is_within_limits(&self, limits: &Limits) -> bool {
+        let (y_min, y_max) = self.y_range();
+        let max_dimensions = limits.max_viewport_dimensions();
+        let bounds = limits.viewport_bounds_range();
+
+        self.dimensions[0] <= max_dimensions[0] as f32 &&
+            self.dimensions[1].abs() <= max_dimensions[1] as f32 &&
+            self.origin[0] >= bounds[0] && self.origin[0] + self.dimensions[0] <= bounds[1] &&
+            y_min >= bounds[0] && y_max <= bounds[1]
+    }
+
+    /// Returns true if the region covered by the viewport is entirely inside a framebuffer of
+    /// the given width and height.
+    pub fn fits_in(&self, dimensions: [u32; 2]) -> bool {
+        let (y_min, y_max) = self.y_range();
+
+        self.origin[0] >= 0.0 && self.origin[0] + self.dimensions[0] <= dimensions[0] as f32 &&
+            y_min >= 0.0 && y_max <= dimensions[1] as f32
+    }
+
+    // Returns the lowest and highest Y coordinates covered by the viewport. A negative height
+    // flips the viewport, in which case it extends above the origin.
+    #[inline]
+    fn y_range(&self) -> (f32, f32) {
+        if self.dimensions[1] < 0.0 {
+            (self.origin[1] + self.dimensions[1], self.origin[1])
+        } else {
+            (self.origin[1], self.origin[1] + self.dimensions[1])
+        }
+    }
+}
+
 #[doc(hidden)]
 impl Into<vk::Viewport> for Viewport {
     #[inline]
@@ -156,9 +233,6 @@ impl Into<vk::Viewport> for Viewport {
 }
 
 /// State of a single scissor box.
-// FIXME: add a check:
-//      Evaluation of (offset.x + extent.width) must not cause a signed integer addition overflow
-//      Evaluation of (offset.y + extent.height) must not cause a signed integer addition overflow 
 #[derive(Debug, Copy, Clone)]
 pub struct Scissor {
     /// Coordinates in pixels of the top-left hand corner of the box.
@@ -177,6 +251,34 @@ impl Scissor {
             dimensions: [0x7fffffff, 0x7fffffff],
         }
     }
+
+    /// Builds a scissor box that covers the whole of a framebuffer of the given width and
+    /// height.
+    #[inline]
+    pub fn fullscreen(dimensions: [u32; 2]) -> Scissor {
+        Scissor {
+            origin: [0, 0],
+            dimensions: dimensions,
+        }
+    }
+
+    /// Returns true if the origin is positive and if the coordinates of the bottom-right hand
+    /// corner fit in an `i32`, as required by Vulkan.
+    pub fn is_valid(&self) -> bool {
+        self.origin[0] >= 0 && self.origin[1] >= 0 &&
+            self.origin[0] as i64 + self.dimensions[0] as i64 <= i32::max_value() as i64 &&
+            self.origin[1] as i64 + self.dimensions[1] as i64 <= i32::max_value() as i64
+    }
+
+    /// Returns true if the origin of the box is inside a framebuffer of the given width and
+    /// height.
+    ///
+    /// The box itself is allowed to go beyond the framebuffer, as the part that is outside is
+    /// discarded anyway. This is what makes `Scissor::irrelevant()` usable with any framebuffer.
+    pub fn starts_in(&self, dimensions: [u32; 2]) -> bool {
+        self.origin[0] >= 0 && self.origin[1] >= 0 &&
+            (self.origin[0] as u32) < dimensions[0] && (self.origin[1] as u32) < dimensions[1]
+    }
 }
 
 impl Default for Scissor {
@@ -202,3 +304,76 @@ impl Into<vk::Rect2D> for Scissor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pipeline::viewport::Scissor;
+    use pipeline::viewport::Viewport;
+
+    #[test]
+    fn flip_y() {
+        let viewport = Viewport {
+            origin: [10.0, 20.0],
+            dimensions: [640.0, 480.0],
+            depth_range: 0.0 .. 1.0,
+        };
+
+        let flipped = viewport.clone().flip_y();
+        assert_eq!(flipped.origin, [10.0, 500.0]);
+        assert_eq!(flipped.dimensions, [640.0, -480.0]);
+
+        let back = flipped.flip_y();
+        assert_eq!(back.origin, viewport.origin);
+        assert_eq!(back.dimensions, viewport.dimensions);
+    }
+
+    #[test]
+    fn fullscreen() {
+        let viewport = Viewport::fullscreen([640, 480]);
+        assert_eq!(viewport.origin, [0.0, 0.0]);
+        assert_eq!(viewport.dimensions, [640.0, 480.0]);
+        assert_eq!(viewport.depth_range, 0.0 .. 1.0);
+        assert!(viewport.fits_in([640, 480]));
+
+        let viewport = Viewport::fullscreen_with_depth_range([640, 480], 1.0 .. 0.0);
+        assert_eq!(viewport.depth_range, 1.0 .. 0.0);
+
+        let scissor = Scissor::fullscreen([640, 480]);
+        assert_eq!(scissor.origin, [0, 0]);
+        assert_eq!(scissor.dimensions, [640, 480]);
+    }
+
+    #[test]
+    fn viewport_fits_in() {
+        let viewport = Viewport {
+            origin: [10.0, 20.0],
+            dimensions: [630.0, 460.0],
+            depth_range: 0.0 .. 1.0,
+        };
+
+        assert!(viewport.fits_in([640, 480]));
+        assert!(!viewport.fits_in([639, 480]));
+        assert!(viewport.clone().flip_y().fits_in([640, 480]));
+        assert!(!viewport.flip_y().fits_in([640, 479]));
+    }
+
+    #[test]
+    fn scissor_overflow() {
+        assert!(Scissor::irrelevant().is_valid());
+
+        let scissor = Scissor { origin: [1, 0], dimensions: [0x7fffffff, 16] };
+        assert!(!scissor.is_valid());
+
+        let scissor = Scissor { origin: [-1, 0], dimensions: [16, 16] };
+        assert!(!scissor.is_valid());
+    }
+
+    #[test]
+    fn scissor_starts_in() {
+        assert!(Scissor::irrelevant().starts_in([640, 480]));
+
+        let scissor = Scissor { origin: [600, 470], dimensions: [100, 100] };
+        assert!(scissor.starts_in([640, 480]));
+        assert!(!scissor.starts_in([600, 480]));
+    }
+}
@@ -60,6 +60,25 @@
 //!
 //! Samplers that don't use `ClampToBorder` are not concerned by these restrictions.
 //!
+//! # Linear filtering
+//!
+//! A sampler that uses `Filter::Linear` or `MipmapMode::Linear` can only be used with image views
+//! whose format supports linear filtering, which is not the case of many integer, floating-point
+//! and depth formats. Use `Format::is_linear_filterable` to find out. Building a
+//! `SimpleDescriptorSet` that pairs such a sampler with an incompatible format returns
+//! `SimpleDescriptorSetError::LinearFilteringNotSupported`.
+//!
+//! # YCbCr conversion
+//!
+//! Sampling an image with a YCbCr format (as produced by video decoders) requires a
+//! `SamplerYcbcrConversion` object, which describes how to convert the YCbCr values into RGB.
+//! The conversion must be attached to the sampler with `Sampler::with_ycbcr_conversion`, and to
+//! the image view that is sampled. A sampler with a YCbCr conversion can only be used as an
+//! immutable sampler of a descriptor set layout.
+//!
+//! This requires enabling the `khr_sampler_ycbcr_conversion` extension and the
+//! `sampler_ycbcr_conversion` feature when creating the device.
+//!
 // FIXME: restrictions aren't checked yet
 use std::error;
 use std::fmt;
@@ -68,6 +87,9 @@ use std::ptr;
 use std::sync::Arc;
 
 use device::Device;
+use device::DeviceOwned;
+use format::Format;
+use image::Swizzle;
 use Error;
 use OomError;
 use VulkanObject;
@@ -85,6 +107,8 @@ pub struct Sampler {
     usable_with_float_formats: bool,
     usable_with_int_formats: bool,
     usable_with_swizzling: bool,
+    linear_filtering: bool,
+    ycbcr_conversion: Option<Arc<SamplerYcbcrConversion>>,
 }
 
 impl Sampler {
@@ -153,7 +177,7 @@ impl Sampler {
                -> Result<Arc<Sampler>, SamplerCreationError>
     {
         Sampler::new_impl(device, mag_filter, min_filter, mipmap_mode, address_u, address_v,
-                          address_w, mip_lod_bias, max_anisotropy, min_lod, max_lod, None)
+                          address_w, mip_lod_bias, max_anisotropy, min_lod, max_lod, None, None)
     }
 
     /// Creates a new `Sampler` with the given behavior.
@@ -180,13 +204,39 @@ impl Sampler {
                    -> Result<Arc<Sampler>, SamplerCreationError>
     {
         Sampler::new_impl(device, mag_filter, min_filter, mipmap_mode, address_u, address_v,
-                          address_w, mip_lod_bias, max_anisotropy, min_lod, max_lod, Some(compare))
+                          address_w, mip_lod_bias, max_anisotropy, min_lod, max_lod, Some(compare),
+                          None)
+    }
+
+    /// Creates a new `Sampler` that performs a YCbCr conversion.
+    ///
+    /// Such a sampler always uses the `ClampToEdge` address mode and no anisotropic filtering.
+    /// It can only be used as an immutable sampler of a descriptor set layout, and must be used
+    /// with an image view that has been created with the same conversion.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the conversion was not created with the same device.
+    /// - Panics if `min_lod > max_lod`.
+    ///
+    #[inline]
+    pub fn with_ycbcr_conversion(device: Arc<Device>, conversion: Arc<SamplerYcbcrConversion>,
+                                 filter: Filter, mipmap_mode: MipmapMode, min_lod: f32,
+                                 max_lod: f32)
+                                 -> Result<Arc<Sampler>, SamplerCreationError>
+    {
+        assert_eq!(device.internal_object(), conversion.device().internal_object());
+
+        Sampler::new_impl(device, filter, filter, mipmap_mode, SamplerAddressMode::ClampToEdge,
+                          SamplerAddressMode::ClampToEdge, SamplerAddressMode::ClampToEdge, 0.0,
+                          1.0, min_lod, max_lod, None, Some(conversion))
     }
 
     fn new_impl(device: Arc<Device>, mag_filter: Filter, min_filter: Filter,
                 mipmap_mode: MipmapMode, address_u: SamplerAddressMode,
                 address_v: SamplerAddressMode, address_w: SamplerAddressMode, mip_lod_bias: f32,
-                max_anisotropy: f32, min_lod: f32, max_lod: f32, compare: Option<Compare>)
+                max_anisotropy: f32, min_lod: f32, max_lod: f32, compare: Option<Compare>,
+                ycbcr_conversion: Option<Arc<SamplerYcbcrConversion>>)
                 -> Result<Arc<Sampler>, SamplerCreationError>
     {
         assert!(max_anisotropy >= 1.0);
@@ -242,9 +292,20 @@ impl Sampler {
 
         let vk = device.pointers();
         let sampler = unsafe {
+            let ycbcr_infos = ycbcr_conversion.as_ref().map(|conversion| {
+                vk::SamplerYcbcrConversionInfoKHR {
+                    sType: vk::STRUCTURE_TYPE_SAMPLER_YCBCR_CONVERSION_INFO_KHR,
+                    pNext: ptr::null(),
+                    conversion: conversion.internal_object(),
+                }
+            });
+
             let infos = vk::SamplerCreateInfo {
                 sType: vk::STRUCTURE_TYPE_SAMPLER_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: match ycbcr_infos {
+                    Some(ref infos) => infos as *const _ as *const _,
+                    None => ptr::null(),
+                },
                 flags: 0,   // reserved
                 magFilter: mag_filter as u32,
                 minFilter: min_filter as u32,
@@ -293,6 +354,12 @@ impl Sampler {
                 Some(BorderColor::IntOpaqueBlack) => false,
                 _ => true,
             },
+            // Samplers with a YCbCr conversion are checked against the features of the
+            // conversion instead.
+            linear_filtering: ycbcr_conversion.is_none() &&
+                              (mag_filter == Filter::Linear || min_filter == Filter::Linear ||
+                               mipmap_mode == MipmapMode::Linear),
+            ycbcr_conversion: ycbcr_conversion,
         }))
     }
 
@@ -375,6 +442,8 @@ impl Sampler {
                 Some(BorderColor::IntOpaqueBlack) => false,
                 _ => true,
             },
+            linear_filtering: filter == Filter::Linear,
+            ycbcr_conversion: None,
         }))
     }
 
@@ -410,6 +479,26 @@ impl Sampler {
     pub fn usable_with_swizzling(&self) -> bool {
         self.usable_with_swizzling
     }
+
+    /// Returns true if the sampler performs a linear filtering, either between texels or between
+    /// mipmaps. See the documentation of the `sampler` module for more info.
+    #[inline]
+    pub fn uses_linear_filtering(&self) -> bool {
+        self.linear_filtering
+    }
+
+    /// Returns the YCbCr conversion performed by the sampler, if any.
+    #[inline]
+    pub fn ycbcr_conversion(&self) -> Option<&Arc<SamplerYcbcrConversion>> {
+        self.ycbcr_conversion.as_ref()
+    }
+}
+
+unsafe impl DeviceOwned for Sampler {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
 }
 
 unsafe impl VulkanObject for Sampler {
@@ -438,6 +527,111 @@ impl Drop for Sampler {
     }
 }
 
+/// Describes how to convert the YCbCr values of an image into RGB values when sampling it.
+///
+/// See the documentation of the `sampler` module for more info.
+pub struct SamplerYcbcrConversion {
+    conversion: vk::SamplerYcbcrConversionKHR,
+    device: Arc<Device>,
+    format: Format,
+}
+
+impl SamplerYcbcrConversion {
+    /// Creates a new `SamplerYcbcrConversion`.
+    ///
+    /// `format` is the format of the images that are going to be sampled. `model` and `range`
+    /// describe how the YCbCr values have been encoded, and `components` describes in which
+    /// component of the image each value is stored.
+    ///
+    /// `x_chroma_offset` and `y_chroma_offset` describe where the chroma samples are located
+    /// relative to the luma samples, for formats whose chroma components are subsampled.
+    /// `chroma_filter` is the filter used to reconstruct the chroma components.
+    ///
+    /// Requires the `khr_sampler_ycbcr_conversion` extension and the `sampler_ycbcr_conversion`
+    /// feature to be enabled on the device.
+    pub fn new(device: Arc<Device>, format: Format, model: SamplerYcbcrModelConversion,
+               range: SamplerYcbcrRange, components: Swizzle, x_chroma_offset: ChromaLocation,
+               y_chroma_offset: ChromaLocation, chroma_filter: Filter)
+               -> Result<Arc<SamplerYcbcrConversion>, SamplerYcbcrConversionCreationError>
+    {
+        if !device.loaded_extensions().khr_sampler_ycbcr_conversion {
+            return Err(SamplerYcbcrConversionCreationError::ExtensionNotEnabled);
+        }
+
+        if !device.enabled_features().sampler_ycbcr_conversion {
+            return Err(SamplerYcbcrConversionCreationError::FeatureNotEnabled);
+        }
+
+        let vk = device.pointers();
+        let conversion = unsafe {
+            let infos = vk::SamplerYcbcrConversionCreateInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SAMPLER_YCBCR_CONVERSION_CREATE_INFO_KHR,
+                pNext: ptr::null(),
+                format: format as u32,
+                ycbcrModel: model as u32,
+                ycbcrRange: range as u32,
+                components: components.into(),
+                xChromaOffset: x_chroma_offset as u32,
+                yChromaOffset: y_chroma_offset as u32,
+                chromaFilter: chroma_filter as u32,
+                forceExplicitReconstruction: vk::FALSE,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateSamplerYcbcrConversionKHR(device.internal_object(),
+                                                                 &infos, ptr::null(),
+                                                                 &mut output)));
+            output
+        };
+
+        Ok(Arc::new(SamplerYcbcrConversion {
+            conversion: conversion,
+            device: device,
+            format: format,
+        }))
+    }
+
+    /// Returns the format of the images that this conversion applies to.
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+}
+
+unsafe impl DeviceOwned for SamplerYcbcrConversion {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+unsafe impl VulkanObject for SamplerYcbcrConversion {
+    type Object = vk::SamplerYcbcrConversionKHR;
+
+    #[inline]
+    fn internal_object(&self) -> vk::SamplerYcbcrConversionKHR {
+        self.conversion
+    }
+}
+
+impl fmt::Debug for SamplerYcbcrConversion {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "<Vulkan sampler YCbCr conversion {:?}>", self.conversion)
+    }
+}
+
+impl Drop for SamplerYcbcrConversion {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let vk = self.device.pointers();
+            vk.DestroySamplerYcbcrConversionKHR(self.device.internal_object(), self.conversion,
+                                                ptr::null());
+        }
+    }
+}
+
 /// Describes how the color of each pixel should be determined.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u32)]
@@ -579,6 +773,49 @@ pub enum BorderColor {
     IntOpaqueWhite = vk::BORDER_COLOR_INT_OPAQUE_WHITE,
 }
 
+/// The color model of the YCbCr values, which describes how to convert them into RGB.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum SamplerYcbcrModelConversion {
+    /// The values are already RGB. Only the range expansion is performed.
+    RgbIdentity = vk::SAMPLER_YCBCR_MODEL_CONVERSION_RGB_IDENTITY_KHR,
+
+    /// The values are YCbCr, but no model conversion is performed.
+    YcbcrIdentity = vk::SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_IDENTITY_KHR,
+
+    /// The color model defined by ITU-R BT.709.
+    Ycbcr709 = vk::SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_709_KHR,
+
+    /// The color model defined by ITU-R BT.601.
+    Ycbcr601 = vk::SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_601_KHR,
+
+    /// The color model defined by ITU-R BT.2020.
+    Ycbcr2020 = vk::SAMPLER_YCBCR_MODEL_CONVERSION_YCBCR_2020_KHR,
+}
+
+/// The range of the encoded YCbCr values.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum SamplerYcbcrRange {
+    /// The values use the full range of the format.
+    ItuFull = vk::SAMPLER_YCBCR_RANGE_ITU_FULL_KHR,
+
+    /// The values use the headroom and footroom reserved by the ITU specifications. For 8-bit
+    /// formats, luma values range from 16 to 235 and chroma values from 16 to 240.
+    ItuNarrow = vk::SAMPLER_YCBCR_RANGE_ITU_NARROW_KHR,
+}
+
+/// Position of the subsampled chroma samples relative to the luma samples.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ChromaLocation {
+    /// The chroma samples are located at the same position as the even luma samples.
+    CositedEven = vk::CHROMA_LOCATION_COSITED_EVEN_KHR,
+
+    /// The chroma samples are located halfway between each even luma sample and the next one.
+    Midpoint = vk::CHROMA_LOCATION_MIDPOINT_KHR,
+}
+
 /// Error that can happen when creating an instance.
 #[derive(Clone, Debug, PartialEq)]
 pub enum SamplerCreationError {
@@ -664,8 +901,75 @@ impl From<Error> for SamplerCreationError {
     }
 }
 
+/// Error that can happen when creating a `SamplerYcbcrConversion`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SamplerYcbcrConversionCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The `VK_KHR_sampler_ycbcr_conversion` extension was not enabled when creating the device.
+    ExtensionNotEnabled,
+
+    /// The `sampler_ycbcr_conversion` feature was not enabled when creating the device.
+    FeatureNotEnabled,
+}
+
+impl error::Error for SamplerYcbcrConversionCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SamplerYcbcrConversionCreationError::OomError(_) => "not enough memory available",
+            SamplerYcbcrConversionCreationError::ExtensionNotEnabled => {
+                "the device extension `VK_KHR_sampler_ycbcr_conversion` is not enabled"
+            },
+            SamplerYcbcrConversionCreationError::FeatureNotEnabled => {
+                "the `sampler_ycbcr_conversion` feature is not enabled"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            SamplerYcbcrConversionCreationError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for SamplerYcbcrConversionCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for SamplerYcbcrConversionCreationError {
+    #[inline]
+    fn from(err: OomError) -> SamplerYcbcrConversionCreationError {
+        SamplerYcbcrConversionCreationError::OomError(err)
+    }
+}
+
+impl From<Error> for SamplerYcbcrConversionCreationError {
+    #[inline]
+    fn from(err: Error) -> SamplerYcbcrConversionCreationError {
+        match err {
+            err @ Error::OutOfHostMemory => {
+                SamplerYcbcrConversionCreationError::OomError(OomError::from(err))
+            },
+            err @ Error::OutOfDeviceMemory => {
+                SamplerYcbcrConversionCreationError::OomError(OomError::from(err))
+            },
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use format::Format;
+    use image::Swizzle;
     use sampler;
 
     #[test]
@@ -680,6 +984,20 @@ mod tests {
                                       0.0, 2.0).unwrap();
         assert!(!s.compare_mode());
         assert!(!s.is_unnormalized());
+        assert!(s.uses_linear_filtering());
+    }
+
+    #[test]
+    fn nearest_filtering() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let s = sampler::Sampler::new(device, sampler::Filter::Nearest, sampler::Filter::Nearest,
+                                      sampler::MipmapMode::Nearest,
+                                      sampler::SamplerAddressMode::Repeat,
+                                      sampler::SamplerAddressMode::Repeat,
+                                      sampler::SamplerAddressMode::Repeat, 1.0, 1.0,
+                                      0.0, 2.0).unwrap();
+        assert!(!s.uses_linear_filtering());
     }
 
     #[test]
@@ -827,4 +1145,22 @@ mod tests {
             _ => panic!()
         }
     }
+
+    #[test]
+    fn ycbcr_conversion_extension() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let r = sampler::SamplerYcbcrConversion::new(device, Format::G8_B8R8_2Plane420Unorm,
+                                                     sampler::SamplerYcbcrModelConversion::Ycbcr709,
+                                                     sampler::SamplerYcbcrRange::ItuNarrow,
+                                                     Swizzle::default(),
+                                                     sampler::ChromaLocation::CositedEven,
+                                                     sampler::ChromaLocation::CositedEven,
+                                                     sampler::Filter::Linear);
+
+        match r {
+            Err(sampler::SamplerYcbcrConversionCreationError::ExtensionNotEnabled) => (),
+            _ => panic!()
+        }
+    }
 }
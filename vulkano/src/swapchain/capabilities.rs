@@ -50,7 +50,11 @@ pub struct Capabilities {
     /// the `color_attachment` usage is guaranteed to be supported.
     pub supported_usage_flags: ImageUsage,
 
-    /// List of formats supported for the swapchain.
+    /// List of formats supported for the swapchain, with the color space that goes with each of
+    /// them. The same format can appear multiple times with different color spaces.
+    ///
+    /// Color spaces other than `SrgbNonLinear` are only reported if the
+    /// `ext_swapchain_colorspace` instance extension is enabled.
     pub supported_formats: Vec<(Format, ColorSpace)>,       // TODO: https://github.com/KhronosGroup/Vulkan-Docs/issues/207
 
     /// List of present modes that are supported. `Fifo` is always guaranteed to be supported.
@@ -180,6 +184,48 @@ pub enum SurfaceTransform {
     Inherit = vk::SURFACE_TRANSFORM_INHERIT_BIT_KHR,
 }
 
+impl SurfaceTransform {
+    /// Returns true if the transform rotates the image by 90 or 270 degrees, in which case the
+    /// width and the height of the image are swapped when it is shown on the screen.
+    #[inline]
+    pub fn swaps_dimensions(&self) -> bool {
+        match *self {
+            SurfaceTransform::Rotate90 | SurfaceTransform::Rotate270 |
+            SurfaceTransform::HorizontalMirrorRotate90 |
+            SurfaceTransform::HorizontalMirrorRotate270 => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the matrix that must be applied to clip-space positions in order to pre-rotate
+    /// the rendering for this transform, or `None` for `Inherit`.
+    ///
+    /// The matrix is column-major, and is meant to be multiplied on the left of your projection
+    /// matrix. See the documentation of the `swapchain` module for more information.
+    #[inline]
+    pub fn pre_rotation_matrix(&self) -> Option<[[f32; 4]; 4]> {
+        // First two columns of the matrix. Mirroring is applied before rotating.
+        let (x, y) = match *self {
+            SurfaceTransform::Identity => ([1.0, 0.0], [0.0, 1.0]),
+            SurfaceTransform::Rotate90 => ([0.0, 1.0], [-1.0, 0.0]),
+            SurfaceTransform::Rotate180 => ([-1.0, 0.0], [0.0, -1.0]),
+            SurfaceTransform::Rotate270 => ([0.0, -1.0], [1.0, 0.0]),
+            SurfaceTransform::HorizontalMirror => ([-1.0, 0.0], [0.0, 1.0]),
+            SurfaceTransform::HorizontalMirrorRotate90 => ([0.0, -1.0], [-1.0, 0.0]),
+            SurfaceTransform::HorizontalMirrorRotate180 => ([1.0, 0.0], [0.0, -1.0]),
+            SurfaceTransform::HorizontalMirrorRotate270 => ([0.0, 1.0], [1.0, 0.0]),
+            SurfaceTransform::Inherit => return None,
+        };
+
+        Some([
+            [x[0], x[1], 0.0, 0.0],
+            [y[0], y[1], 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
 /// How the alpha values of the pixels of the window are treated.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -461,40 +507,79 @@ impl Default for SurfaceTransform {
 /// Additionally you can try detect whether the implementation supports any additional color space
 /// and perform a manual conversion to that color space from inside your shader.
 ///
+/// # HDR output
+///
+/// Color spaces other than `SrgbNonLinear`, such as `Hdr10St2084`, require the
+/// `ext_swapchain_colorspace` instance extension. Once it is enabled, check that the
+/// `(format, color_space)` pair appears in `Capabilities::supported_formats` and pass it to
+/// `Swapchain::with_color_space`.
+///
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum ColorSpace {
+    /// The sRGB color space, with the sRGB transfer function.
+    ///
+    /// This is the only color space that is available without the `ext_swapchain_colorspace`
+    /// instance extension.
     SrgbNonLinear = vk::COLOR_SPACE_SRGB_NONLINEAR_KHR,
-    DisplayP3Linear = vk::COLOR_SPACE_DISPLAY_P3_LINEAR_EXT,
+    /// The Display-P3 color space, with the sRGB transfer function.
     DisplayP3NonLinear = vk::COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT,
-    ScrgbLinear = vk::COLOR_SPACE_SCRGB_LINEAR_EXT,
-    ScrgbNonLinear = vk::COLOR_SPACE_SCRGB_NONLINEAR_EXT,
+    /// The extended sRGB color space (scRGB), with a linear transfer function.
+    ExtendedSrgbLinear = vk::COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT,
+    /// The extended sRGB color space (scRGB), with the sRGB transfer function.
+    ExtendedSrgbNonLinear = vk::COLOR_SPACE_EXTENDED_SRGB_NONLINEAR_EXT,
+    /// The DCI-P3 color space, with a linear transfer function.
     DciP3Linear = vk::COLOR_SPACE_DCI_P3_LINEAR_EXT,
+    /// The DCI-P3 color space, with the DCI-P3 transfer function.
     DciP3NonLinear = vk::COLOR_SPACE_DCI_P3_NONLINEAR_EXT,
+    /// The BT.709 color space, with a linear transfer function.
     Bt709Linear = vk::COLOR_SPACE_BT709_LINEAR_EXT,
+    /// The BT.709 color space, with the BT.709 transfer function.
     Bt709NonLinear = vk::COLOR_SPACE_BT709_NONLINEAR_EXT,
+    /// The BT.2020 color space, with a linear transfer function.
     Bt2020Linear = vk::COLOR_SPACE_BT2020_LINEAR_EXT,
-    Bt2020NonLinear = vk::COLOR_SPACE_BT2020_NONLINEAR_EXT,
+    /// The HDR10 color space: BT.2020 primaries with the SMPTE ST2084 (PQ) transfer function.
+    Hdr10St2084 = vk::COLOR_SPACE_HDR10_ST2084_EXT,
+    /// The Dolby Vision color space: BT.2020 primaries with the SMPTE ST2084 transfer function.
+    DolbyVision = vk::COLOR_SPACE_DOLBYVISION_EXT,
+    /// BT.2020 primaries with the Hybrid Log Gamma transfer function.
+    Hdr10Hlg = vk::COLOR_SPACE_HDR10_HLG_EXT,
+    /// The Adobe RGB color space, with a linear transfer function.
     AdobeRgbLinear = vk::COLOR_SPACE_ADOBERGB_LINEAR_EXT,
+    /// The Adobe RGB color space, with the Adobe RGB transfer function.
     AdobeRgbNonLinear = vk::COLOR_SPACE_ADOBERGB_NONLINEAR_EXT,
+    /// The color components are passed through to the display as they are.
+    PassThrough = vk::COLOR_SPACE_PASS_THROUGH_EXT,
 }
 
+impl ColorSpace {
+    /// Returns true if using this color space requires the `ext_swapchain_colorspace` instance
+    /// extension.
+    #[inline]
+    pub fn requires_extension(&self) -> bool {
+        *self != ColorSpace::SrgbNonLinear
+    }
+}
+
+// Returns `None` for color spaces that vulkano doesn't know about.
 #[inline]
-pub fn color_space_from_num(val: u32) -> ColorSpace {
-    match val {
+pub fn color_space_from_num(val: u32) -> Option<ColorSpace> {
+    Some(match val {
         vk::COLOR_SPACE_SRGB_NONLINEAR_KHR => ColorSpace::SrgbNonLinear,
-        vk::COLOR_SPACE_DISPLAY_P3_LINEAR_EXT => ColorSpace::DisplayP3Linear,
         vk::COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT => ColorSpace::DisplayP3NonLinear,
-        vk::COLOR_SPACE_SCRGB_LINEAR_EXT => ColorSpace::ScrgbLinear,
-        vk::COLOR_SPACE_SCRGB_NONLINEAR_EXT => ColorSpace::ScrgbNonLinear,
+        vk::COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT => ColorSpace::ExtendedSrgbLinear,
+        vk::COLOR_SPACE_EXTENDED_SRGB_NONLINEAR_EXT => ColorSpace::ExtendedSrgbNonLinear,
         vk::COLOR_SPACE_DCI_P3_LINEAR_EXT => ColorSpace::DciP3Linear,
         vk::COLOR_SPACE_DCI_P3_NONLINEAR_EXT => ColorSpace::DciP3NonLinear,
         vk::COLOR_SPACE_BT709_LINEAR_EXT => ColorSpace::Bt709Linear,
         vk::COLOR_SPACE_BT709_NONLINEAR_EXT => ColorSpace::Bt709NonLinear,
         vk::COLOR_SPACE_BT2020_LINEAR_EXT => ColorSpace::Bt2020Linear,
-        vk::COLOR_SPACE_BT2020_NONLINEAR_EXT => ColorSpace::Bt2020NonLinear,
+        vk::COLOR_SPACE_HDR10_ST2084_EXT => ColorSpace::Hdr10St2084,
+        vk::COLOR_SPACE_DOLBYVISION_EXT => ColorSpace::DolbyVision,
+        vk::COLOR_SPACE_HDR10_HLG_EXT => ColorSpace::Hdr10Hlg,
         vk::COLOR_SPACE_ADOBERGB_LINEAR_EXT => ColorSpace::AdobeRgbLinear,
         vk::COLOR_SPACE_ADOBERGB_NONLINEAR_EXT => ColorSpace::AdobeRgbNonLinear,
-        _ => panic!("Wrong value for color space enum")
-    }
+        vk::COLOR_SPACE_PASS_THROUGH_EXT => ColorSpace::PassThrough,
+        _ => return None
+    })
 }
@@ -31,7 +31,7 @@
 //! 
 //! ## Creating a surface from a window
 //! 
-//! There are 6 extensions that each allow you to create a surface from a type of window:
+//! There are 7 extensions that each allow you to create a surface from a type of window:
 //! 
 //! - `VK_KHR_xlib_surface`
 //! - `VK_KHR_xcb_surface`
@@ -39,11 +39,15 @@
 //! - `VK_KHR_mir_surface`
 //! - `VK_KHR_android_surface`
 //! - `VK_KHR_win32_surface`
+//! - `VK_EXT_metal_surface`
 //!
 //! For example if you want to create a surface from an Android surface, you will have to enable
 //! the `VK_KHR_android_surface` extension and use `Surface::from_anativewindow`.
 //! See the documentation of `Surface` for all the possible constructors.
 //!
+//! If you integrate a windowing library, `Surface::from_window_handle` takes the raw handles of
+//! a window as a `WindowHandle` and calls the right constructor for the current platform.
+//!
 //! Trying to use one of these functions without enabling the proper extension will result in an
 //! error.
 //!
@@ -104,21 +108,47 @@
 //!  - How the alpha of the final output will be interpreted.
 //!  - How to perform the cycling between images in regard to vsync.
 //!
-//! You can query the supported values of all these properties with `Surface::get_capabilities()`.
+//! You can query the supported values of all these properties with `Surface::capabilities()`.
+//! The supported present modes can also be queried alone with
+//! `Surface::supported_present_modes()`.
 //!
 //! ## Creating a swapchain
 //!
 //! In order to create a swapchain, you will first have to enable the `VK_KHR_swapchain` extension
 //! on the device (and not on the instance like `VK_KHR_surface`).
 //!
-//! Then, you should query the capabilities of the surface with `Surface::get_capabilities()` and
+//! Then, you should query the capabilities of the surface with `Surface::capabilities()` and
 //! choose which values you are going to use. Then, call `Swapchain::new`.
 //!
+//! Not all queue families can present images on a given surface. When choosing the queue that is
+//! going to present the images, use `QueueFamily::supports_surface` to find a family that
+//! supports the surface. `Swapchain::new` returns an error if none of the queue families that use
+//! the images support it.
+//!
 //! TODO: add example here
 //!
 //! Creating a swapchain not only returns the swapchain object, but also all the images that belong
 //! to it.
 //!
+//! ## Pre-rotation
+//!
+//! On some platforms, most notably on mobile devices, the surface may be rotated compared to the
+//! natural orientation of the screen. If the swapchain is created with a transform that doesn't
+//! match `Capabilities::current_transform`, the presentation engine will have to rotate each
+//! image before showing it, which is costly.
+//!
+//! The recommended pattern is:
+//!
+//!  - Pass `current_transform` as the `transform` parameter of `Swapchain::new`.
+//!  - Use `current_extent` as the dimensions of the swapchain. If you compute the dimensions
+//!    yourself from the size of the window, swap the width and the height when
+//!    `SurfaceTransform::swaps_dimensions` returns true.
+//!  - Multiply your projection matrix by `swapchain.transform().pre_rotation_matrix()` and
+//!    apply the result in your vertex shader, so that what you draw is already rotated.
+//!
+//! When the surface is rotated, the swapchain should be recreated with the new
+//! `current_transform`.
+//!
 //! ## Acquiring and presenting images
 //!
 //! Once you created a swapchain and retreived all the images that belong to it (see previous
@@ -136,11 +166,15 @@
 //!
 //! TODO: add example here
 //! loop {
-//!     let index = swapchain::acquire_next_image(Duration::from_millis(500)).unwrap();
-//!     draw(images[index]);
-//!     swapchain::present(queue, index).unwrap();
+//!     let (index, future) = swapchain::acquire_next_image(swapchain.clone(), timeout).unwrap();
+//!     let future = draw(future, images[index]);
+//!     swapchain::present(swapchain.clone(), future, queue.clone(), index).unwrap();
 //! }
 //!
+//! When drawing on multiple windows at once, use `present_multiple` instead to present the images
+//! of all the swapchains with a single submission. The result of the presentation is then
+//! available for each swapchain individually.
+//!
 //! ## Recreating a swapchain
 //!
 //! In some situations, the swapchain will become invalid by itself. This includes for example when
@@ -152,12 +186,25 @@
 //! rendering, you will need to *recreate* the swapchain by creating a new swapchain and passing
 //! as last parameter the old swapchain.
 //!
-//! TODO: suboptimal stuff
+//! The swapchain can also become *suboptimal*, which means that its images can still be presented
+//! but no longer match the surface exactly, for example after a change of DPI. The presentation
+//! engine then has to scale the images. The status of the swapchain is given by
+//! `Swapchain::status()`, based on the results of the latest acquire and present operations:
+//!
+//!  - `SwapchainStatus::Optimal`: keep rendering as usual.
+//!  - `SwapchainStatus::Suboptimal`: finish drawing and presenting the current image, then
+//!    recreate the swapchain before the next frame. There is no need to recreate it immediately.
+//!  - `SwapchainStatus::OutOfDate`: `acquire_next_image` returns `AcquireError::OutOfDate` and
+//!    flushing a present returns `FlushError::OutOfDate`. The swapchain must be recreated before
+//!    anything can be presented again.
+//!
+//! The `Renderer` helper follows these rules automatically.
 //!
 //! ```
 //! # use std::time::Duration;
 //! use vulkano::swapchain;
 //! use vulkano::swapchain::AcquireError;
+//! use vulkano::swapchain::SwapchainStatus;
 //! use vulkano::sync::GpuFuture;
 //!
 //! // let mut swapchain = Swapchain::new(...);
@@ -183,10 +230,15 @@
 //!
 //!     let final_future = acq_future
 //!         // .then_execute(...)
-//!         .then_swapchain_present(queue.clone(), swapchain.clone(), index)
+//!         .then_swapchain_present(queue.clone(), swapchain.clone(), index).unwrap()
 //!         .then_signal_fence();
 //!
 //!     final_future.flush().unwrap();      // TODO: PresentError?
+//!
+//!     // Recreating lazily, after the image has been presented.
+//!     if swapchain.status() == SwapchainStatus::Suboptimal {
+//!         recreate_swapchain = true;
+//!     }
 //! }
 //! ```
 //!
@@ -204,18 +256,28 @@ pub use self::capabilities::SupportedCompositeAlphaIter;
 pub use self::capabilities::ColorSpace;
 pub use self::capabilities::SupportedSurfaceTransforms;
 pub use self::capabilities::SupportedSurfaceTransformsIter;
+pub use self::renderer::Frame;
+pub use self::renderer::Renderer;
+pub use self::renderer::RendererError;
 pub use self::surface::Surface;
 pub use self::surface::SurfaceCreationError;
+pub use self::surface::WindowHandle;
 pub use self::surface::CapabilitiesError;
 pub use self::swapchain::AcquireError;
+pub use self::swapchain::MultiPresentFuture;
+pub use self::swapchain::PresentError;
 pub use self::swapchain::PresentFuture;
 pub use self::swapchain::Swapchain;
 pub use self::swapchain::SwapchainAcquireFuture;
+pub use self::swapchain::SwapchainCreationError;
+pub use self::swapchain::SwapchainStatus;
 pub use self::swapchain::acquire_next_image;
 pub use self::swapchain::present;
+pub use self::swapchain::present_multiple;
 
 mod capabilities;
 pub mod display;
+mod renderer;
 mod surface;
 mod swapchain;
 
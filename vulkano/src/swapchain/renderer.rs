@@ -0,0 +1,641 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::mem;
+use std::sync::Arc;
+use std::time::Duration;
+
+use command_buffer::CommandBuffer;
+use command_buffer::CommandBufferExecError;
+use device::DeviceOwned;
+use device::Queue;
+use image::SwapchainImage;
+use swapchain::AcquireError;
+use swapchain::CapabilitiesError;
+use swapchain::PresentError;
+use swapchain::Swapchain;
+use swapchain::SwapchainCreationError;
+use swapchain::SwapchainStatus;
+use swapchain::acquire_next_image;
+use sync::FlushError;
+use sync::GpuFuture;
+use sync::now;
+use VulkanObject;
+
+/// Helper that runs the acquire, submit and present steps of a render loop.
+///
+/// Each call to `render` acquires an image from the swapchain, calls a closure that builds the
+/// command buffer that draws on that image, executes the command buffer after the previous frame
+/// and presents the image. The submission of the previous frame is kept alive until the GPU has
+/// finished it, so that the CPU can record the next frame while the GPU is still busy.
+///
+/// If the swapchain turns out to be out of date, the frame is skipped and the swapchain is
+/// recreated at the start of the next call to `render`. If it is suboptimal, the frame is
+/// presented normally and the swapchain is recreated before the next frame.
+///
+/// The closure is told through `Frame::swapchain_recreated` when the images are new, so that
+/// it can rebuild the objects that depend on them, such as the framebuffers.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use vulkano::command_buffer::CommandBuffer;
+/// # use vulkano::image::SwapchainImage;
+/// # use vulkano::swapchain::Renderer;
+/// # fn example<Cb>(mut renderer: Renderer, draw: &Fn(&Arc<SwapchainImage>) -> Cb)
+/// #     where Cb: CommandBuffer + 'static {
+/// loop {
+///     // `draw` builds a command buffer that draws on the image.
+///     renderer.render(|frame| draw(frame.image())).unwrap();
+/// }
+/// # }
+/// ```
+pub struct Renderer {
+    // Queue where the command buffers are executed and the images presented.
+    queue: Arc<Queue>,
+    // The current swapchain.
+    swapchain: Arc<Swapchain>,
+    // The images of the current swapchain.
+    images: Vec<Arc<SwapchainImage>>,
+    // Submission of the previous frame.
+    previous_frame_end: Box<GpuFuture>,
+    // If `Some`, the swapchain must be recreated with these dimensions before the next frame. The
+    // inner `None` means that the dimensions are those of the surface.
+    recreate: Option<Option<[u32; 2]>>,
+    // True if the swapchain has been recreated and no frame has been rendered with it yet.
+    recreated: bool,
+    // Timeout passed to `acquire_next_image`.
+    acquire_timeout: Duration,
+}
+
+impl Renderer {
+    /// Builds a `Renderer` that draws on `swapchain`, with `images` being the images that were
+    /// returned when creating the swapchain.
+    ///
+    /// The command buffers are executed and the images are presented on `queue`, whose family
+    /// must be able to present on the surface of the swapchain.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the queue and the swapchain don't belong to the same device.
+    ///
+    pub fn new(queue: Arc<Queue>, swapchain: Arc<Swapchain>, images: Vec<Arc<SwapchainImage>>)
+               -> Renderer
+    {
+        assert_eq!(queue.device().internal_object(), swapchain.device().internal_object());
+
+        Renderer {
+            previous_frame_end: Box::new(now(queue.device().clone())),
+            queue: queue,
+            swapchain: swapchain,
+            images: images,
+            recreate: None,
+            recreated: true,
+            acquire_timeout: Duration::new(1, 0),
+        }
+    }
+
+    /// Returns the queue where the frames are submitted.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns the current swapchain.
+    #[inline]
+    pub fn swapchain(&self) -> &Arc<Swapchain> {
+        &self.swapchain
+    }
+
+    /// Returns the images of the current swapchain.
+    #[inline]
+    pub fn images(&self) -> &[Arc<SwapchainImage>] {
+        &self.images
+    }
+
+    /// Sets the timeout of the acquisition of the swapchain images. The default is one second.
+    #[inline]
+    pub fn set_acquire_timeout(&mut self, timeout: Duration) {
+        self.acquire_timeout = timeout;
+    }
+
+    /// Requests the swapchain to be recreated with new dimensions before the next frame.
+    ///
+    /// You should call this when the window is resized. If `dimensions` is `None`, the current
+    /// extent of the surface is used.
+    #[inline]
+    pub fn resize(&mut self, dimensions: Option<[u32; 2]>) {
+        self.recreate = Some(dimensions);
+    }
+
+    /// Renders and presents a frame.
+    ///
+    /// The closure receives the image to draw on, and must return the command buffer that draws
+    /// it. The command buffer is executed on the queue of the `Renderer`, after the acquisition of
+    /// the image and after the previous frame.
+    ///
+    /// Returns `Ok(false)` if the frame was skipped because the swapchain is out of date. In that
+    /// situation the closure may or may not have been called, and the swapchain will be recreated
+    /// during the next call.
+    pub fn render<F, Cb>(&mut self, build: F) -> Result<bool, RendererError>
+        where F: FnOnce(&Frame) -> Cb,
+              Cb: CommandBuffer + 'static
+    {
+        // Frees the resources of the frames that the GPU has finished.
+        self.previous_frame_end.cleanup_finished();
+
+        if let Some(dimensions) = self.recreate.take() {
+            if let Err(err) = self.recreate_swapchain(dimensions) {
+                self.recreate = Some(dimensions);
+                return Err(err);
+            }
+        }
+
+        let (image_num, acquire_future) = match acquire_next_image(self.swapchain.clone(),
+                                                                   self.acquire_timeout)
+        {
+            Ok(r) => r,
+            Err(AcquireError::OutOfDate) => {
+                self.recreate = Some(None);
+                return Ok(false);
+            },
+            Err(err) => return Err(RendererError::AcquireError(err)),
+        };
+
+        let command_buffer = {
+            let frame = Frame {
+                image_num: image_num,
+                images: &self.images,
+                swapchain: &self.swapchain,
+                recreated: self.recreated,
+            };
+
+            build(&frame)
+        };
+        self.recreated = false;
+
+        let previous = mem::replace(&mut self.previous_frame_end,
+                                    Box::new(now(self.queue.device().clone())));
+
+        let after_execute = match command_buffer.try_execute_after(previous.join(acquire_future),
+                                                                   self.queue.clone())
+        {
+            Ok(future) => future,
+            Err((err, joined)) => {
+                // The image has been acquired, so it must be presented even though nothing has
+                // been drawn on it. Otherwise the swapchain would run out of images.
+                let present = joined.then_swapchain_present(self.queue.clone(),
+                                                            self.swapchain.clone(), image_num);
+
+                if let Ok(present) = present {
+                    match present.then_signal_fence_and_flush() {
+                        Ok(future) => self.previous_frame_end = Box::new(future),
+                        Err(FlushError::OutOfDate) => self.recreate = Some(None),
+                        Err(_) => (),
+                    }
+                }
+
+                return Err(RendererError::CommandBufferExecError(err));
+            },
+        };
+
+        let present = try!(after_execute.then_swapchain_present(self.queue.clone(),
+                                                                self.swapchain.clone(),
+                                                                image_num));
+        let result = present.then_signal_fence_and_flush();
+
+        match result {
+            Ok(future) => {
+                self.previous_frame_end = Box::new(future);
+                if self.swapchain.status() == SwapchainStatus::Suboptimal &&
+                   self.recreate.is_none()
+                {
+                    self.recreate = Some(None);
+                }
+                Ok(true)
+            },
+            Err(FlushError::OutOfDate) => {
+                self.recreate = Some(None);
+                Ok(false)
+            },
+            Err(err) => Err(RendererError::FlushError(err)),
+        }
+    }
+
+    // Recreates the swapchain and its images.
+    fn recreate_swapchain(&mut self, dimensions: Option<[u32; 2]>)
+                          -> Result<(), RendererError>
+    {
+        let dimensions = match dimensions {
+            Some(d) => d,
+            None => {
+                let physical = self.queue.device().physical_device();
+                let capabilities = self.swapchain.surface().capabilities(physical)?;
+                capabilities.current_extent.unwrap_or(self.swapchain.dimensions())
+            },
+        };
+
+        let (swapchain, images) = self.swapchain.recreate_with_dimension(dimensions)?;
+        self.swapchain = swapchain;
+        self.images = images;
+        self.recreated = true;
+        Ok(())
+    }
+}
+
+/// Image being drawn by `Renderer::render`.
+pub struct Frame<'a> {
+    image_num: usize,
+    images: &'a [Arc<SwapchainImage>],
+    swapchain: &'a Arc<Swapchain>,
+    recreated: bool,
+}
+
+impl<'a> Frame<'a> {
+    /// Returns the index of the image to draw on, within `images()`.
+    #[inline]
+    pub fn image_num(&self) -> usize {
+        self.image_num
+    }
+
+    /// Returns the image to draw on.
+    #[inline]
+    pub fn image(&self) -> &'a Arc<SwapchainImage> {
+        &self.images[self.image_num]
+    }
+
+    /// Returns all the images of the swapchain.
+    #[inline]
+    pub fn images(&self) -> &'a [Arc<SwapchainImage>] {
+        self.images
+    }
+
+    /// Returns the swapchain the image belongs to.
+    #[inline]
+    pub fn swapchain(&self) -> &'a Arc<Swapchain> {
+        self.swapchain
+    }
+
+    /// Returns true if this is the first frame drawn on the swapchain, either because the
+    /// `Renderer` has just been created or because the swapchain has been recreated.
+    ///
+    /// The images are different from the ones of the previous frames, and the objects that were
+    /// built from them, such as the framebuffers, must be rebuilt.
+    #[inline]
+    pub fn swapchain_recreated(&self) -> bool {
+        self.recreated
+    }
+}
+
+/// Error that can happen when rendering a frame with a `Renderer`.
+#[derive(Debug, Clone)]
+pub enum RendererError {
+    /// Error while acquiring an image from the swapchain.
+    AcquireError(AcquireError),
+
+    /// Error while querying the capabilities of the surface.
+    CapabilitiesError(CapabilitiesError),
+
+    /// Error while recreating the swapchain.
+    SwapchainCreationError(SwapchainCreationError),
+
+    /// Error while executing the command buffer.
+    CommandBufferExecError(CommandBufferExecError),
+
+    /// Error while presenting the image.
+    PresentError(PresentError),
+
+    /// Error while submitting the frame.
+    FlushError(FlushError),
+}
+
+impl error::Error for RendererError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            RendererError::AcquireError(_) => {
+                "error while acquiring an image from the swapchain"
+            },
+            RendererError::CapabilitiesError(_) => {
+                "error while querying the capabilities of the surface"
+            },
+            RendererError::SwapchainCreationError(_) => {
+                "error while recreating the swapchain"
+            },
+            RendererError::CommandBufferExecError(_) => {
+                "error while executing the command buffer"
+            },
+            RendererError::PresentError(_) => {
+                "error while presenting the image"
+            },
+            RendererError::FlushError(_) => {
+                "error while submitting the frame"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            RendererError::AcquireError(ref err) => Some(err),
+            RendererError::CapabilitiesError(ref err) => Some(err),
+            RendererError::SwapchainCreationError(ref err) => Some(err),
+            RendererError::CommandBufferExecError(ref err) => Some(err),
+            RendererError::PresentError(ref err) => Some(err),
+            RendererError::FlushError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for RendererError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<AcquireError> for RendererError {
+    #[inline]
+    fn from(err: AcquireError) -> RendererError {
+        RendererError::AcquireError(err)
+    }
+}
+
+impl From<CapabilitiesError> for RendererError {
+    #[inline]
+    fn from(err: CapabilitiesError) -> RendererError {
+        RendererError::CapabilitiesError(err)
+    }
+}
+
+impl From<SwapchainCreationError> for RendererError {
+    #[inline]
+    fn from(err: SwapchainCreationError) -> RendererError {
+        RendererError::SwapchainCreationError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for RendererError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> RendererError {
+        RendererError::CommandBufferExecError(err)
+    }
+}
+
+impl From<PresentError> for RendererError {
+    #[inline]
+    fn from(err: PresentError) -> RendererError {
+        RendererError::PresentError(err)
+    }
+}
+
+impl From<FlushError> for RendererError {
+    #[inline]
+    fn from(err: FlushError) -> RendererError {
+        RendererError::FlushError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buffer::BufferAccess;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use command_buffer::CommandBufferBuild;
+    use command_buffer::CommandBufferExecError;
+    use command_buffer::cb::UnsafeCommandBuffer;
+    use device::Device;
+    use device::DeviceOwned;
+    use device::Queue;
+    use image::ImageAccess;
+    use image::ImageLayout;
+    use sync::AccessCheckError;
+    use sync::AccessError;
+    use sync::AccessFlagBits;
+    use sync::GpuFuture;
+    use sync::PipelineStages;
+    use super::Renderer;
+    use super::RendererError;
+
+    // Creates a `Renderer` that presents on the first display of the first physical device, or
+    // returns from the test if the implementation can't provide one.
+    macro_rules! renderer {
+        () => ({
+            use device::DeviceExtensions;
+            use features::Features;
+            use instance::Instance;
+            use instance::InstanceExtensions;
+            use instance::PhysicalDevice;
+            use swapchain::PresentMode;
+            use swapchain::Surface;
+            use swapchain::Swapchain;
+            use swapchain::display::Display;
+            use swapchain::display::DisplayPlane;
+
+            let extensions = InstanceExtensions {
+                khr_surface: true,
+                khr_display: true,
+                .. InstanceExtensions::none()
+            };
+
+            let instance = match Instance::new(None, &extensions, None) {
+                Ok(i) => i,
+                Err(_) => return
+            };
+
+            let physical = match PhysicalDevice::enumerate(&instance).next() {
+                Some(p) => p,
+                None => return
+            };
+
+            let display = match Display::enumerate(&physical).next() {
+                Some(d) => d,
+                None => return
+            };
+
+            let mode = match display.display_modes().next() {
+                Some(m) => m,
+                None => return
+            };
+
+            let plane = match DisplayPlane::enumerate(&physical).find(|p| p.supports(&display)) {
+                Some(p) => p,
+                None => return
+            };
+
+            let surface = match Surface::from_display_mode(&mode, &plane) {
+                Ok(s) => s,
+                Err(_) => return
+            };
+
+            let queue = physical.queue_families().find(|q| {
+                q.supports_graphics() && q.supports_surface(&surface).unwrap_or(false)
+            });
+            let queue = match queue {
+                Some(q) => q,
+                None => return
+            };
+
+            let extensions = DeviceExtensions {
+                khr_swapchain: true,
+                .. DeviceExtensions::none()
+            };
+
+            let (device, mut queues) = match Device::new(&physical, &Features::none(),
+                                                         &extensions,
+                                                         [(queue, 0.5)].iter().cloned())
+            {
+                Ok(r) => r,
+                Err(_) => return
+            };
+            let queue = queues.next().unwrap();
+
+            let caps = match surface.capabilities(physical) {
+                Ok(c) => c,
+                Err(_) => return
+            };
+
+            let alpha = caps.supported_composite_alpha.iter().next().unwrap();
+            let dimensions = caps.current_extent.unwrap_or(mode.visible_region());
+
+            let (swapchain, images) = match Swapchain::new(device, surface, caps.min_image_count,
+                                                           caps.supported_formats[0].0,
+                                                           dimensions, 1,
+                                                           caps.supported_usage_flags, &queue,
+                                                           caps.current_transform, alpha,
+                                                           PresentMode::Fifo, true, None)
+            {
+                Ok(r) => r,
+                Err(_) => return
+            };
+
+            Renderer::new(queue, swapchain, images)
+        })
+    }
+
+    // Command buffer that forwards to another one, except that its execution is always denied.
+    struct RejectedCommandBuffer<C>(C);
+
+    unsafe impl<C> CommandBuffer for RejectedCommandBuffer<C> where C: CommandBuffer {
+        type Pool = C::Pool;
+
+        #[inline]
+        fn inner(&self) -> &UnsafeCommandBuffer<C::Pool> {
+            self.0.inner()
+        }
+
+        #[inline]
+        fn prepare_submit(&self, _: &GpuFuture, _: &Queue)
+                          -> Result<(), CommandBufferExecError>
+        {
+            Err(CommandBufferExecError::AccessError(AccessError::AlreadyInUse))
+        }
+
+        #[inline]
+        fn check_buffer_access(&self, buffer: &BufferAccess, exclusive: bool, queue: &Queue)
+                               -> Result<Option<(PipelineStages, AccessFlagBits)>,
+                                         AccessCheckError>
+        {
+            self.0.check_buffer_access(buffer, exclusive, queue)
+        }
+
+        #[inline]
+        fn check_image_access(&self, image: &ImageAccess, layout: ImageLayout, exclusive: bool,
+                              queue: &Queue)
+                              -> Result<Option<(PipelineStages, AccessFlagBits)>,
+                                        AccessCheckError>
+        {
+            self.0.check_image_access(image, layout, exclusive, queue)
+        }
+    }
+
+    unsafe impl<C> DeviceOwned for RejectedCommandBuffer<C> where C: DeviceOwned {
+        #[inline]
+        fn device(&self) -> &Arc<Device> {
+            self.0.device()
+        }
+    }
+
+    #[test]
+    fn render_frames() {
+        let mut renderer = renderer!();
+        let queue = renderer.queue().clone();
+
+        let mut first = None;
+        let result = renderer.render(|frame| {
+            first = Some(frame.swapchain_recreated());
+            AutoCommandBufferBuilder::new(queue.device().clone(), queue.family()).unwrap()
+                .build().unwrap()
+        });
+
+        if let Ok(true) = result {
+            assert_eq!(first, Some(true));
+
+            let mut second = None;
+            renderer.render(|frame| {
+                second = Some(frame.swapchain_recreated());
+                AutoCommandBufferBuilder::new(queue.device().clone(), queue.family()).unwrap()
+                    .build().unwrap()
+            }).unwrap();
+            assert_eq!(second, Some(false));
+        }
+    }
+
+    #[test]
+    fn rejected_command_buffer() {
+        let mut renderer = renderer!();
+        let queue = renderer.queue().clone();
+
+        let result = renderer.render(|_| {
+            let cb = AutoCommandBufferBuilder::new(queue.device().clone(), queue.family())
+                .unwrap().build().unwrap();
+            RejectedCommandBuffer(cb)
+        });
+
+        match result {
+            Err(RendererError::CommandBufferExecError(CommandBufferExecError::AccessError(
+                AccessError::AlreadyInUse))) => (),
+            Ok(false) => (),        // The swapchain was out of date.
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn rejected_command_buffer_still_presents() {
+        let mut renderer = renderer!();
+        let queue = renderer.queue().clone();
+
+        // If the images of the failed frames weren't presented, the swapchain would run out of
+        // images and the acquisition would time out.
+        for _ in 0 .. renderer.images().len() * 2 {
+            let result = renderer.render(|_| {
+                let cb = AutoCommandBufferBuilder::new(queue.device().clone(), queue.family())
+                    .unwrap().build().unwrap();
+                RejectedCommandBuffer(cb)
+            });
+
+            match result {
+                Err(RendererError::CommandBufferExecError(_)) => (),
+                Ok(false) => (),
+                Err(err) => panic!("{:?}", err),
+                Ok(true) => panic!()
+            }
+        }
+
+        renderer.render(|_| {
+            AutoCommandBufferBuilder::new(queue.device().clone(), queue.family()).unwrap()
+                .build().unwrap()
+        }).unwrap();
+    }
+}
@@ -7,10 +7,12 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::any::Any;
 use std::error;
 use std::fmt;
 use std::mem;
 use std::os::raw::c_ulong;
+use std::os::raw::c_void;
 use std::ptr;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -22,6 +24,7 @@ use instance::PhysicalDevice;
 use instance::QueueFamily;
 use swapchain::capabilities;
 use swapchain::Capabilities;
+use swapchain::PresentMode;
 use swapchain::SurfaceSwapchainLock;
 use swapchain::display::DisplayMode;
 use swapchain::display::DisplayPlane;
@@ -35,6 +38,17 @@ use vk;
 /// Represents a surface on the screen.
 ///
 /// Creating a `Surface` is platform-specific.
+///
+/// # Window ownership
+///
+/// A surface created from a window references the window without owning it, and the window must
+/// outlive the surface. Since the surface is also kept alive by the swapchains and the images
+/// that use it, this is easy to get wrong during a resize or at shutdown.
+///
+/// To avoid that, the surface can be created with `from_window`, which takes an `Arc` of the
+/// object that owns the window in addition to its raw handles. The surface then holds this `Arc`
+/// and only drops it after the Vulkan surface has been destroyed. The object can be retrieved
+/// with `window()`.
 pub struct Surface {
     instance: Arc<Instance>,
     surface: vk::SurfaceKHR,
@@ -42,6 +56,9 @@ pub struct Surface {
     // If true, a swapchain has been associated to this surface, and that any new swapchain
     // creation should be forbidden.
     has_swapchain: AtomicBool,
+
+    // Object that owns the window, if any. Dropped after the surface is destroyed.
+    window: Option<Arc<Any + Send + Sync>>,
 }
 
 impl Surface {
@@ -93,6 +110,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -132,6 +150,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -171,6 +190,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -210,6 +230,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -249,6 +270,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -289,6 +311,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -325,6 +348,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -362,6 +386,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -399,6 +424,7 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
@@ -435,13 +461,140 @@ impl Surface {
             instance: instance.clone(),
             surface: surface,
             has_swapchain: AtomicBool::new(false),
+            window: None,
         }))
     }
 
+    /// Creates a `Surface` from a `CAMetalLayer`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the `layer` is correct and stays alive for the entire
+    /// lifetime of the surface.
+    pub unsafe fn from_metal_layer<T>(instance: Arc<Instance>, layer: *const T)
+                                      -> Result<Arc<Surface>, SurfaceCreationError>
+    {
+        let vk = instance.pointers();
+
+        if !instance.loaded_extensions().ext_metal_surface {
+            return Err(SurfaceCreationError::MissingExtension { name: "VK_EXT_metal_surface" });
+        }
+
+        let surface = {
+            let infos = vk::MetalSurfaceCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_METAL_SURFACE_CREATE_INFO_EXT,
+                pNext: ptr::null(),
+                flags: 0,   // reserved
+                pLayer: layer as *const _,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateMetalSurfaceEXT(instance.internal_object(), &infos,
+                                                       ptr::null(), &mut output)));
+            output
+        };
+
+        Ok(Arc::new(Surface {
+            instance: instance.clone(),
+            surface: surface,
+            has_swapchain: AtomicBool::new(false),
+            window: None,
+        }))
+    }
+
+    /// Creates a `Surface` from the raw handles of a window.
+    ///
+    /// This calls the constructor that corresponds to the variant of `handle`, and is meant to be
+    /// used by the code that integrates a windowing library with vulkano. Only the variants of
+    /// the current platform are available.
+    ///
+    /// Returns an error if the instance extension required by the variant is not enabled.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the handles are correct and stay alive for the entire
+    /// lifetime of the surface.
+    pub unsafe fn from_window_handle(instance: Arc<Instance>, handle: WindowHandle)
+                                     -> Result<Arc<Surface>, SurfaceCreationError>
+    {
+        match handle {
+            #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+                      not(target_os = "ios")))]
+            WindowHandle::Xlib { display, window } => {
+                Surface::from_xlib(instance, display, window)
+            },
+            #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+                      not(target_os = "ios")))]
+            WindowHandle::Xcb { connection, window } => {
+                Surface::from_xcb(instance, connection, window)
+            },
+            #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+                      not(target_os = "ios")))]
+            WindowHandle::Wayland { display, surface } => {
+                Surface::from_wayland(instance, display, surface)
+            },
+            #[cfg(windows)]
+            WindowHandle::Win32 { hinstance, hwnd } => {
+                Surface::from_hwnd(instance, hinstance, hwnd)
+            },
+            #[cfg(target_os = "android")]
+            WindowHandle::Android { window } => {
+                Surface::from_anativewindow(instance, window)
+            },
+            #[cfg(target_os = "macos")]
+            WindowHandle::MacOS { view } => {
+                Surface::from_macos_moltenvk(instance, view)
+            },
+            #[cfg(target_os = "ios")]
+            WindowHandle::Ios { view } => {
+                Surface::from_ios_moltenvk(instance, view)
+            },
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            WindowHandle::Metal { layer } => {
+                Surface::from_metal_layer(instance, layer)
+            },
+        }
+    }
+
+    /// Same as `from_window_handle`, but the surface also keeps `window` alive until it is
+    /// destroyed.
+    ///
+    /// `window` is typically the object of the windowing library that owns the window that
+    /// `handle` refers to. See the "Window ownership" section of the documentation of `Surface`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the handles are correct, and that they stay valid as long as
+    /// `window` is alive.
+    pub unsafe fn from_window<W>(instance: Arc<Instance>, handle: WindowHandle, window: Arc<W>)
+                                 -> Result<Arc<Surface>, SurfaceCreationError>
+        where W: Any + Send + Sync
+    {
+        let mut surface = try!(Surface::from_window_handle(instance, handle));
+        // The surface has just been created, therefore nobody else can hold a reference to it.
+        Arc::get_mut(&mut surface).unwrap().window = Some(window as Arc<Any + Send + Sync>);
+        Ok(surface)
+    }
+
+    /// Returns the object that owns the window of the surface, if it was created with
+    /// `from_window`.
+    #[inline]
+    pub fn window(&self) -> Option<&Arc<Any + Send + Sync>> {
+        self.window.as_ref()
+    }
+
     /// Returns true if the given queue family can draw on this surface.
-    // FIXME: vulkano doesn't check this for the moment!
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the queue family and the surface don't belong to the same instance.
+    ///
     pub fn is_supported(&self, queue: QueueFamily) -> Result<bool, CapabilitiesError> {
         unsafe {
+            assert_eq!(&*self.instance as *const _,
+                       &**queue.physical_device().instance() as *const _,
+                       "Instance mismatch in Surface::is_supported");
+
             let vk = self.instance.pointers();
 
             let mut output = mem::uninitialized();
@@ -500,22 +653,7 @@ impl Surface {
             };
 
             let modes = {
-                let mut num = 0;
-                try!(check_errors(
-                    vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
-                                                               self.surface, &mut num,
-                                                               ptr::null_mut())
-                ));
-
-                let mut modes = Vec::with_capacity(num as usize);
-                try!(check_errors(
-                    vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
-                                                               self.surface, &mut num,
-                                                               modes.as_mut_ptr())
-                ));
-                modes.set_len(num as usize);
-                debug_assert!(modes.iter().find(|&&m| m == vk::PRESENT_MODE_FIFO_KHR).is_some());
-                debug_assert!(modes.iter().count() > 0);
+                let modes = try!(self.raw_present_modes(device));
                 capabilities::supported_present_modes_from_list(modes.into_iter())
             };
 
@@ -541,13 +679,77 @@ impl Surface {
                     debug_assert!(usage.color_attachment);  // specs say that this must be true
                     usage
                 },
-                supported_formats: formats.into_iter().map(|f| {
-                    (Format::from_num(f.format).unwrap(), capabilities::color_space_from_num(f.colorSpace))
+                supported_formats: formats.into_iter().filter_map(|f| {
+                    // Color spaces that vulkano doesn't know about are skipped.
+                    let color_space = match capabilities::color_space_from_num(f.colorSpace) {
+                        Some(c) => c,
+                        None => return None,
+                    };
+                    Some((Format::from_num(f.format).unwrap(), color_space))
                 }).collect(),
                 present_modes: modes,
             })
         }
     }
+
+    /// Returns the list of present modes that are supported when presenting on this surface
+    /// with the given device.
+    ///
+    /// The `Fifo` mode is always part of the list.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the device and the surface don't belong to the same instance.
+    ///
+    #[inline]
+    pub fn supported_present_modes(&self, device: PhysicalDevice)
+                                   -> Result<Vec<PresentMode>, CapabilitiesError>
+    {
+        assert_eq!(&*self.instance as *const _, &**device.instance() as *const _,
+                   "Instance mismatch in Surface::supported_present_modes");
+
+        let modes = unsafe { try!(self.raw_present_modes(device)) };
+        Ok(capabilities::supported_present_modes_from_list(modes.into_iter()).iter().collect())
+    }
+
+    /// Returns true if the given present mode can be used when presenting on this surface with
+    /// the given device.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the device and the surface don't belong to the same instance.
+    ///
+    #[inline]
+    pub fn supports_present_mode(&self, device: PhysicalDevice, mode: PresentMode)
+                                 -> Result<bool, CapabilitiesError>
+    {
+        Ok(try!(self.supported_present_modes(device)).into_iter().any(|m| m == mode))
+    }
+
+    // Calls `vkGetPhysicalDeviceSurfacePresentModesKHR`.
+    unsafe fn raw_present_modes(&self, device: PhysicalDevice)
+                                -> Result<Vec<vk::PresentModeKHR>, CapabilitiesError>
+    {
+        let vk = self.instance.pointers();
+
+        let mut num = 0;
+        try!(check_errors(
+            vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
+                                                       self.surface, &mut num,
+                                                       ptr::null_mut())
+        ));
+
+        let mut modes = Vec::with_capacity(num as usize);
+        try!(check_errors(
+            vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
+                                                       self.surface, &mut num,
+                                                       modes.as_mut_ptr())
+        ));
+        modes.set_len(num as usize);
+        debug_assert!(modes.iter().find(|&&m| m == vk::PRESENT_MODE_FIFO_KHR).is_some());
+        debug_assert!(modes.iter().count() > 0);
+        Ok(modes)
+    }
 }
 
 unsafe impl SurfaceSwapchainLock for Surface {
@@ -583,6 +785,80 @@ impl Drop for Surface {
     }
 }
 
+/// Raw handles of a window, passed to `Surface::from_window_handle`.
+///
+/// Only the variants of the current platform are available. The pointers are not checked in any
+/// way.
+#[derive(Debug, Copy, Clone)]
+pub enum WindowHandle {
+    /// An Xlib window. Requires the `VK_KHR_xlib_surface` extension.
+    #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+              not(target_os = "ios")))]
+    Xlib {
+        /// Pointer to the `Display`.
+        display: *const c_void,
+        /// The `Window`.
+        window: c_ulong,
+    },
+
+    /// An XCB window. Requires the `VK_KHR_xcb_surface` extension.
+    #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+              not(target_os = "ios")))]
+    Xcb {
+        /// Pointer to the `xcb_connection_t`.
+        connection: *const c_void,
+        /// The `xcb_window_t`.
+        window: u32,
+    },
+
+    /// A Wayland surface. Requires the `VK_KHR_wayland_surface` extension.
+    #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+              not(target_os = "ios")))]
+    Wayland {
+        /// Pointer to the `wl_display`.
+        display: *const c_void,
+        /// Pointer to the `wl_surface`.
+        surface: *const c_void,
+    },
+
+    /// A Win32 window. Requires the `VK_KHR_win32_surface` extension.
+    #[cfg(windows)]
+    Win32 {
+        /// The `HINSTANCE` of the application.
+        hinstance: *const c_void,
+        /// The `HWND` of the window.
+        hwnd: *const c_void,
+    },
+
+    /// An Android window. Requires the `VK_KHR_android_surface` extension.
+    #[cfg(target_os = "android")]
+    Android {
+        /// Pointer to the `ANativeWindow`.
+        window: *const c_void,
+    },
+
+    /// A macOS view. Requires the `VK_MVK_macos_surface` extension.
+    #[cfg(target_os = "macos")]
+    MacOS {
+        /// Pointer to the `NSView`, which must be backed by a `CAMetalLayer`.
+        view: *const c_void,
+    },
+
+    /// An iOS view. Requires the `VK_MVK_ios_surface` extension.
+    #[cfg(target_os = "ios")]
+    Ios {
+        /// Pointer to the `UIView`, which must be backed by a `CAMetalLayer`.
+        view: *const c_void,
+    },
+
+    /// A Metal layer. Requires the `VK_EXT_metal_surface` extension.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    Metal {
+        /// Pointer to the `CAMetalLayer`.
+        layer: *const c_void,
+    },
+}
+
 /// Error that can happen when creating a debug callback.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SurfaceCreationError {
@@ -698,8 +974,10 @@ impl From<Error> for CapabilitiesError {
 #[cfg(test)]
 mod tests {
     use std::ptr;
+    use std::sync::Arc;
     use swapchain::Surface;
     use swapchain::SurfaceCreationError;
+    use swapchain::WindowHandle;
 
     #[test]
     fn khr_win32_surface_ext_missing() {
@@ -746,6 +1024,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ext_metal_surface_ext_missing() {
+        let instance = instance!();
+        match unsafe { Surface::from_metal_layer(instance, ptr::null::<u8>()) } {
+            Err(SurfaceCreationError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+              not(target_os = "ios")))]
+    fn window_handle_ext_missing() {
+        let instance = instance!();
+        let handle = WindowHandle::Xlib { display: ptr::null(), window: 0 };
+        match unsafe { Surface::from_window_handle(instance, handle) } {
+            Err(SurfaceCreationError::MissingExtension { name: "VK_KHR_xlib_surface" }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "android"), not(target_os = "macos"),
+              not(target_os = "ios")))]
+    fn window_released_on_error() {
+        let instance = instance!();
+        let window = Arc::new(5u32);
+        let handle = WindowHandle::Xlib { display: ptr::null(), window: 0 };
+        assert!(unsafe { Surface::from_window(instance, handle, window.clone()) }.is_err());
+        assert_eq!(Arc::strong_count(&window), 1);
+    }
+
     #[test]
     fn khr_android_surface_ext_missing() {
         let instance = instance!();
@@ -16,12 +16,15 @@ use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
+use smallvec::SmallVec;
 
 use buffer::BufferAccess;
 use command_buffer::submit::SubmitAnyBuilder;
 use command_buffer::submit::SubmitPresentBuilder;
+use command_buffer::submit::SubmitPresentError;
 use command_buffer::submit::SubmitSemaphoresWaitBuilder;
 use device::Device;
+use device::DeviceWaitError;
 use device::DeviceOwned;
 use device::Queue;
 use format::Format;
@@ -32,6 +35,8 @@ use image::ImageLayout;
 use image::ImageUsage;
 use image::sys::UnsafeImage;
 use image::swapchain::SwapchainImage;
+use instance::QueueFamily;
+use swapchain::CapabilitiesError;
 use swapchain::ColorSpace;
 use swapchain::CompositeAlpha;
 use swapchain::PresentMode;
@@ -45,6 +50,7 @@ use sync::FlushError;
 use sync::GpuFuture;
 use sync::PipelineStages;
 use sync::Semaphore;
+use sync::Sharing;
 use sync::SharingMode;
 
 use check_errors;
@@ -77,22 +83,26 @@ pub fn acquire_next_image(swapchain: Arc<Swapchain>, timeout: Duration)
             return Err(AcquireError::OutOfDate);
         }
 
+        if swapchain.device.is_lost() {
+            return Err(AcquireError::DeviceLost);
+        }
+
         let vk = swapchain.device.pointers();
 
-        let semaphore = try!(Semaphore::new(swapchain.device.clone()));
+        let semaphore = try!(Semaphore::from_pool(swapchain.device.clone()));
 
         let timeout_ns = timeout.as_secs().saturating_mul(1_000_000_000)
                                           .saturating_add(timeout.subsec_nanos() as u64);
 
         let mut out = mem::uninitialized();
-        let r = try!(check_errors(vk.AcquireNextImageKHR(swapchain.device.internal_object(),
-                                                         swapchain.swapchain, timeout_ns,
-                                                         semaphore.internal_object(), 0,
-                                                         &mut out)));
-
-        let id = match r {
-            Success::Success => out as usize,
-            Success::Suboptimal => out as usize,        // TODO: give that info to the user
+        let r = check_errors(vk.AcquireNextImageKHR(swapchain.device.internal_object(),
+                                                    swapchain.swapchain, timeout_ns,
+                                                    semaphore.internal_object(), 0, &mut out));
+        let r = try!(swapchain.update_status(swapchain.device.track_lost(r)));
+
+        let (id, suboptimal) = match r {
+            Success::Success => (out as usize, false),
+            Success::Suboptimal => (out as usize, true),
             Success::NotReady => return Err(AcquireError::Timeout),
             Success::Timeout => return Err(AcquireError::Timeout),
             s => panic!("unexpected success value: {:?}", s)
@@ -102,6 +112,7 @@ pub fn acquire_next_image(swapchain: Arc<Swapchain>, timeout: Duration)
             swapchain: swapchain.clone(),      // TODO: don't clone
             semaphore: semaphore,
             image_id: id,
+            suboptimal: suboptimal,
             finished: AtomicBool::new(false),
         }))
     }
@@ -114,12 +125,22 @@ pub fn acquire_next_image(swapchain: Arc<Swapchain>, timeout: Duration)
 ///
 /// The actual behavior depends on the present mode that you passed when creating the
 /// swapchain.
+///
+/// Returns `PresentError::UnsupportedQueueFamily` if the family of `queue` can't present images
+/// on the surface of the swapchain. Use `QueueFamily::supports_surface` to check beforehand.
+///
+/// # Panic
+///
+/// - Panics if `index` is out of range.
+///
 pub fn present<F>(swapchain: Arc<Swapchain>, before: F, queue: Arc<Queue>, index: usize)
-                  -> PresentFuture<F>
+                  -> Result<PresentFuture<F>, PresentError>
     where F: GpuFuture
 {
     assert!(index < swapchain.images.len());
 
+    try!(check_queue_support(Some(swapchain.supports_queue_family(queue.family()))));
+
     // TODO: restore this check with a dummy ImageAccess implementation
     /*let swapchain_image = me.images.lock().unwrap().get(index).unwrap().0.upgrade().unwrap();       // TODO: return error instead
     // Normally if `check_image_access` returns false we're supposed to call the `gpu_access`
@@ -127,14 +148,91 @@ pub fn present<F>(swapchain: Arc<Swapchain>, before: F, queue: Arc<Queue>, index
     // always returns false anyway (by design), we don't need to do it.
     assert!(before.check_image_access(&swapchain_image, ImageLayout::PresentSrc, true, &queue).is_ok());         // TODO: return error instead*/
 
-    PresentFuture {
+    Ok(PresentFuture {
+        inner: MultiPresentFuture {
+            previous: before,
+            queue: queue,
+            swapchains: vec![(swapchain, index)],
+            results: Mutex::new(None),
+            flushed: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        },
+    })
+}
+
+/// Presents images of multiple swapchains on the screen with a single submission.
+///
+/// Each element of `swapchains` is a swapchain and the index of the image to present, which is
+/// the same index as what `acquire_next_image` returned. This is the same as `present`, except
+/// that all the images are presented at once after the same future. Use this when you draw on
+/// multiple windows at the same time.
+///
+/// After the future has been flushed, its `results()` method tells whether the presentation of
+/// each swapchain has succeeded. Errors that are specific to one swapchain, such as being out of
+/// date, don't make the whole flush fail.
+///
+/// Returns `PresentError::UnsupportedQueueFamily` if the family of `queue` can't present images
+/// on the surface of one of the swapchains. Use `QueueFamily::supports_surface` to check
+/// beforehand.
+///
+/// Returns `PresentError::DuplicateSwapchain` if the same swapchain appears multiple times in
+/// `swapchains`.
+///
+/// # Panic
+///
+/// - Panics if `swapchains` is empty.
+/// - Panics if one of the image indices is out of range.
+/// - Panics if the swapchains and the queue don't all belong to the same device.
+///
+pub fn present_multiple<F>(swapchains: Vec<(Arc<Swapchain>, usize)>, before: F, queue: Arc<Queue>)
+                           -> Result<MultiPresentFuture<F>, PresentError>
+    where F: GpuFuture
+{
+    assert!(!swapchains.is_empty());
+
+    for &(ref swapchain, index) in swapchains.iter() {
+        assert!(index < swapchain.images.len());
+        assert_eq!(swapchain.device().internal_object(), queue.device().internal_object());
+    }
+
+    if has_duplicates(swapchains.iter().map(|&(ref sc, _)| sc.internal_object())) {
+        return Err(PresentError::DuplicateSwapchain);
+    }
+
+    try!(check_queue_support(swapchains.iter().map(|&(ref sc, _)| {
+        sc.supports_queue_family(queue.family())
+    })));
+
+    Ok(MultiPresentFuture {
         previous: before,
         queue: queue,
-        swapchain: swapchain,
-        image_id: index,
+        swapchains: swapchains,
+        results: Mutex::new(None),
         flushed: AtomicBool::new(false),
         finished: AtomicBool::new(false),
+    })
+}
+
+// Turns whether the surface of each swapchain supports the queue family into an error if one of
+// them can't be presented to from the queue.
+fn check_queue_support<I>(support: I) -> Result<(), PresentError>
+    where I: IntoIterator<Item = Result<bool, CapabilitiesError>>
+{
+    for supported in support {
+        if !try!(supported) {
+            return Err(PresentError::UnsupportedQueueFamily);
+        }
     }
+
+    Ok(())
+}
+
+// Returns true if the same swapchain appears multiple times in the list.
+fn has_duplicates<I>(swapchains: I) -> bool
+    where I: IntoIterator<Item = vk::SwapchainKHR>
+{
+    let swapchains: SmallVec<[vk::SwapchainKHR; 4]> = swapchains.into_iter().collect();
+    swapchains.iter().enumerate().any(|(n, sc)| swapchains[.. n].contains(sc))
 }
 
 /// Contains the swapping system and the images that can be shown on a surface.
@@ -156,8 +254,17 @@ pub struct Swapchain {
     // we acquire the image.
     stale: Mutex<bool>,
 
+    // True if the latest successful acquire or present operation returned `VK_SUBOPTIMAL_KHR`.
+    suboptimal: AtomicBool,
+    // True if acquiring or presenting an image returned `VK_ERROR_OUT_OF_DATE_KHR`.
+    out_of_date: AtomicBool,
+
+    // Results of `Surface::is_supported` for the queue families that have presented images, so
+    // that we don't query the surface every time we present.
+    queue_family_support: Mutex<SmallVec<[(u32, bool); 4]>>,
+
     // Parameters passed to the constructor.
-    num_images: u32,
+    min_image_count: u32,
     format: Format,
     color_space: ColorSpace,
     dimensions: [u32; 2],
@@ -179,10 +286,29 @@ struct ImageEntry {
 impl Swapchain {
     /// Builds a new swapchain. Allocates images who content can be made visible on a surface.
     ///
-    /// See also the `Surface::get_capabilities` function which returns the values that are
+    /// See also the `Surface::capabilities` function which returns the values that are
     /// supported by the implementation. All the parameters that you pass to `Swapchain::new`
     /// must be supported. 
     ///
+    /// Returns `SwapchainCreationError::UnsupportedPresentMode` if `mode` isn't supported by the
+    /// surface. Use `Surface::supports_present_mode` to check beforehand.
+    ///
+    /// Returns `SwapchainCreationError::UnsupportedQueueFamily` if none of the queue families of
+    /// `sharing` can present images on the surface. Use `QueueFamily::supports_surface` to check
+    /// beforehand.
+    ///
+    /// The `usage` parameter indicates how the images are going to be used, and must be a subset
+    /// of the `supported_usage_flags` of the surface. Otherwise,
+    /// `SwapchainCreationError::UnsupportedUsageFlags` is returned. Pass
+    /// `ImageUsage::color_attachment()` if the images are only drawn to by render passes. Adding
+    /// `transfer_dest` or `storage` lets you blit to the images or write them from a compute
+    /// shader.
+    ///
+    /// The `transform` parameter is the transform that the content of the images is assumed to
+    /// already have, and must be one of the `supported_transforms` of the surface. Passing the
+    /// `current_transform` of the surface lets the presentation engine avoid a rotation. See the
+    /// documentation of the `swapchain` module.
+    ///
     /// The `clipped` parameter indicates whether the implementation is allowed to discard 
     /// rendering operations that affect regions of the surface which aren't visible. This is
     /// important to take into account if your fragment shader has side-effects or if you want to
@@ -192,20 +318,22 @@ impl Swapchain {
     /// swapchain. The order in which the images are returned is important for the
     /// `acquire_next_image` and `present` functions.
     ///
+    /// The `num_images` parameter is only a minimum, and the implementation is free to create
+    /// more images. Use the length of the returned list or `Swapchain::num_images()` to size
+    /// the objects that you need per image, such as the framebuffers.
+    ///
     /// # Panic
     ///
     /// - Panics if the device and the surface don't belong to the same instance.
-    /// - Panics if `color_attachment` is false in `usage`.
     ///
     // TODO: remove `old_swapchain` parameter and add another function `with_old_swapchain`.
-    // TODO: add `ColorSpace` parameter
     // TODO: isn't it unsafe to take the surface through an Arc when it comes to vulkano-win?
     #[inline]
     pub fn new<F, S>(device: Arc<Device>, surface: Arc<Surface>, num_images: u32, format: F,
                      dimensions: [u32; 2], layers: u32, usage: ImageUsage, sharing: S,
                      transform: SurfaceTransform, alpha: CompositeAlpha, mode: PresentMode,
                      clipped: bool, old_swapchain: Option<&Arc<Swapchain>>)
-                     -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), OomError>
+                     -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
         where F: FormatDesc, S: Into<SharingMode>
     {
         Swapchain::new_inner(device, surface, num_images, format.format(),
@@ -213,11 +341,37 @@ impl Swapchain {
                              transform, alpha, mode, clipped, old_swapchain.map(|s| &**s))
     }
 
+    /// Same as `new`, but allows choosing the color space of the swapchain images.
+    ///
+    /// The `(format, color_space)` pair must be one of the entries of
+    /// `Capabilities::supported_formats`, otherwise `SwapchainCreationError::UnsupportedFormat`
+    /// is returned. Color spaces other than `SrgbNonLinear` require the
+    /// `ext_swapchain_colorspace` instance extension to be enabled.
+    ///
+    /// # Panic
+    ///
+    /// Same as `new`.
+    ///
+    #[inline]
+    pub fn with_color_space<F, S>(device: Arc<Device>, surface: Arc<Surface>, num_images: u32,
+                                  format: F, color_space: ColorSpace, dimensions: [u32; 2],
+                                  layers: u32, usage: ImageUsage, sharing: S,
+                                  transform: SurfaceTransform, alpha: CompositeAlpha,
+                                  mode: PresentMode, clipped: bool,
+                                  old_swapchain: Option<&Arc<Swapchain>>)
+                                  -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
+        where F: FormatDesc, S: Into<SharingMode>
+    {
+        Swapchain::new_inner(device, surface, num_images, format.format(), color_space,
+                             dimensions, layers, usage, sharing.into(), transform, alpha, mode,
+                             clipped, old_swapchain.map(|s| &**s))
+    }
+
      /// Recreates the swapchain with new dimensions.
     pub fn recreate_with_dimension(&self, dimensions: [u32; 2])
-                                   -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), OomError>
+                                   -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
     {
-        Swapchain::new_inner(self.device.clone(), self.surface.clone(), self.num_images,
+        Swapchain::new_inner(self.device.clone(), self.surface.clone(), self.min_image_count,
                              self.format, self.color_space, dimensions, self.layers, self.usage,
                              self.sharing.clone(), self.transform, self.alpha, self.mode,
                              self.clipped, Some(self))
@@ -227,23 +381,69 @@ impl Swapchain {
                  color_space: ColorSpace, dimensions: [u32; 2], layers: u32, usage: ImageUsage,
                  sharing: SharingMode, transform: SurfaceTransform, alpha: CompositeAlpha,
                  mode: PresentMode, clipped: bool, old_swapchain: Option<&Swapchain>)
-                 -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), OomError>
+                 -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
     {
         // Checking that the requested parameters match the capabilities.
-        let capabilities = try!(surface.get_capabilities(&device.physical_device()));
+        let capabilities = try!(surface.capabilities(device.physical_device()));
+        if color_space.requires_extension() &&
+           !device.instance().loaded_extensions().ext_swapchain_colorspace
+        {
+            return Err(SwapchainCreationError::ColorSpaceExtensionNotEnabled);
+        }
+        if !capabilities.supported_formats.iter().any(|&(f, c)| f == format && c == color_space) {
+            return Err(SwapchainCreationError::UnsupportedFormat);
+        }
+        if !capabilities.supported_transforms.supports(transform) {
+            return Err(SwapchainCreationError::UnsupportedTransform);
+        }
+        if !capabilities.present_modes.supports(mode) {
+            return Err(SwapchainCreationError::UnsupportedPresentMode);
+        }
+        {
+            let supported_usage = capabilities.supported_usage_flags.to_usage_bits();
+            let unsupported = usage.to_usage_bits() & !supported_usage;
+            if unsupported != 0 {
+                let unsupported = ImageUsage::from_bits(unsupported);
+                return Err(SwapchainCreationError::UnsupportedUsageFlags(unsupported));
+            }
+        }
+
+        // Checking that the images can be presented by one of the queue families that use them.
+        {
+            let supports_surface = |id: u32| -> Result<bool, CapabilitiesError> {
+                match device.physical_device().queue_family_by_id(id) {
+                    Some(family) => family.supports_surface(&surface),
+                    None => Ok(false),
+                }
+            };
+
+            let supported = match sharing {
+                SharingMode::Exclusive(id) => try!(supports_surface(id)),
+                SharingMode::Concurrent(ref ids) => {
+                    let mut supported = false;
+                    for &id in ids.iter() {
+                        if try!(supports_surface(id)) {
+                            supported = true;
+                            break;
+                        }
+                    }
+                    supported
+                },
+            };
+
+            if !supported {
+                return Err(SwapchainCreationError::UnsupportedQueueFamily);
+            }
+        }
         // TODO: return errors instead
         assert!(num_images >= capabilities.min_image_count);
         if let Some(c) = capabilities.max_image_count { assert!(num_images <= c) };
-        assert!(capabilities.supported_formats.iter().any(|&(f, c)| f == format && c == color_space));
         assert!(dimensions[0] >= capabilities.min_image_extent[0]);
         assert!(dimensions[1] >= capabilities.min_image_extent[1]);
         assert!(dimensions[0] <= capabilities.max_image_extent[0]);
         assert!(dimensions[1] <= capabilities.max_image_extent[1]);
         assert!(layers >= 1 && layers <= capabilities.max_image_array_layers);
-        assert!((usage.to_usage_bits() & capabilities.supported_usage_flags.to_usage_bits()) == usage.to_usage_bits());
-        assert!(capabilities.supported_transforms.supports(transform));
         assert!(capabilities.supported_composite_alpha.supports(alpha));
-        assert!(capabilities.present_modes.supports(mode));
 
         // If we recreate a swapchain, make sure that the surface is the same.
         if let Some(sc) = old_swapchain {
@@ -262,8 +462,6 @@ impl Swapchain {
         let vk = device.pointers();
         assert!(device.loaded_extensions().khr_swapchain);     // TODO: return error instead
 
-        assert!(usage.color_attachment);
-
         if let Some(ref old_swapchain) = old_swapchain {
             *old_swapchain.stale.lock().unwrap() = false;
         }
@@ -328,8 +526,13 @@ impl Swapchain {
                 cubemap_compatible: false,
             };
 
+            let img_sharing = match sharing {
+                SharingMode::Exclusive(_) => Sharing::Exclusive,
+                SharingMode::Concurrent(ref ids) => Sharing::Concurrent(ids.iter().cloned()),
+            };
+
             let img = UnsafeImage::from_raw(device.clone(), image, usage.to_usage_bits(), format,
-                                            dims, 1, 1);
+                                            dims, 1, 1, img_sharing);
 
             ImageEntry {
                 image: img,
@@ -343,7 +546,10 @@ impl Swapchain {
             swapchain: swapchain,
             images: images,
             stale: Mutex::new(false),
-            num_images: num_images,
+            suboptimal: AtomicBool::new(false),
+            out_of_date: AtomicBool::new(false),
+            queue_family_support: Mutex::new(SmallVec::new()),
+            min_image_count: num_images,
             format: format,
             color_space: color_space,
             dimensions: dimensions,
@@ -373,12 +579,22 @@ impl Swapchain {
 
     /// Returns the number of images of the swapchain.
     ///
-    /// See the documentation of `Swapchain::new`. 
+    /// This is the number of images that were actually created, which can be larger than the
+    /// `num_images` that was passed to `Swapchain::new`. It is also the length of the list of
+    /// images returned when creating the swapchain.
     #[inline]
     pub fn num_images(&self) -> u32 {
         self.images.len() as u32
     }
 
+    /// Returns the minimum number of images that was requested when creating the swapchain.
+    ///
+    /// See the documentation of `Swapchain::new`.
+    #[inline]
+    pub fn min_image_count(&self) -> u32 {
+        self.min_image_count
+    }
+
     /// Returns the format of the images of the swapchain.
     ///
     /// See the documentation of `Swapchain::new`. 
@@ -387,6 +603,14 @@ impl Swapchain {
         self.format
     }
 
+    /// Returns the color space of the images of the swapchain.
+    ///
+    /// See the documentation of `Swapchain::with_color_space`.
+    #[inline]
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
     /// Returns the dimensions of the images of the swapchain.
     ///
     /// See the documentation of `Swapchain::new`. 
@@ -405,6 +629,10 @@ impl Swapchain {
 
     /// Returns the transform that was passed when creating the swapchain.
     ///
+    /// This is the transform that the presentation engine assumes has already been applied to the
+    /// content of the images. Use `SurfaceTransform::pre_rotation_matrix` to get the matrix to
+    /// apply in your vertex shader.
+    ///
     /// See the documentation of `Swapchain::new`. 
     #[inline]
     pub fn transform(&self) -> SurfaceTransform {
@@ -427,6 +655,64 @@ impl Swapchain {
         self.mode
     }
 
+    /// Returns the usage of the images of the swapchain.
+    ///
+    /// See the documentation of `Swapchain::new`.
+    #[inline]
+    pub fn usage(&self) -> ImageUsage {
+        self.usage
+    }
+
+    /// Returns whether the swapchain still matches the surface, according to the results of the
+    /// latest acquire and present operations.
+    ///
+    /// See the documentation of `SwapchainStatus` for how to handle each value.
+    #[inline]
+    pub fn status(&self) -> SwapchainStatus {
+        if self.out_of_date.load(Ordering::Acquire) || *self.stale.lock().unwrap() {
+            SwapchainStatus::OutOfDate
+        } else if self.suboptimal.load(Ordering::Acquire) {
+            SwapchainStatus::Suboptimal
+        } else {
+            SwapchainStatus::Optimal
+        }
+    }
+
+    // Updates the status of the swapchain from the result of an acquire or present operation,
+    // and returns the result unchanged.
+    pub(crate) fn update_status(&self, result: Result<Success, Error>) -> Result<Success, Error> {
+        // An out of date swapchain stays out of date, but a suboptimal one can become optimal
+        // again, for example if the window is resized back.
+        match result {
+            Ok(Success::Suboptimal) => self.suboptimal.store(true, Ordering::Release),
+            Ok(_) => self.suboptimal.store(false, Ordering::Release),
+            Err(Error::OutOfDate) => self.out_of_date.store(true, Ordering::Release),
+            Err(_) => ()
+        }
+
+        result
+    }
+
+    // Returns true if queues of `family` can present images on the surface. The result doesn't
+    // change during the lifetime of the surface, therefore it is only queried once per family.
+    fn supports_queue_family(&self, family: QueueFamily) -> Result<bool, CapabilitiesError> {
+        let mut cache = self.queue_family_support.lock().unwrap();
+
+        if let Some(&(_, supported)) = cache.iter().find(|&&(id, _)| id == family.id()) {
+            return Ok(supported);
+        }
+
+        let supported = try!(self.surface.is_supported(family));
+        cache.push((family.id(), supported));
+        Ok(supported)
+    }
+
+    /// Returns the surface the swapchain presents on.
+    #[inline]
+    pub fn surface(&self) -> &Arc<Surface> {
+        &self.surface
+    }
+
     /// Returns the value of `clipped` that was passed when creating the swapchain.
     ///
     /// See the documentation of `Swapchain::new`. 
@@ -436,6 +722,13 @@ impl Swapchain {
     }
 }
 
+unsafe impl DeviceOwned for Swapchain {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
 unsafe impl VulkanObject for Swapchain {
     type Object = vk::SwapchainKHR;
 
@@ -469,6 +762,8 @@ pub struct SwapchainAcquireFuture {
     swapchain: Arc<Swapchain>,
     image_id: usize,
     semaphore: Semaphore,
+    // True if the acquisition returned `VK_SUBOPTIMAL_KHR`.
+    suboptimal: bool,
     finished: AtomicBool,
 }
 
@@ -484,6 +779,20 @@ impl SwapchainAcquireFuture {
     pub fn swapchain(&self) -> &Arc<Swapchain> {
         &self.swapchain
     }
+
+    /// Returns `Suboptimal` if the image was acquired but the swapchain no longer matches the
+    /// surface exactly, and `Optimal` otherwise.
+    ///
+    /// An out of date swapchain is reported by `acquire_next_image` returning
+    /// `AcquireError::OutOfDate` instead.
+    #[inline]
+    pub fn status(&self) -> SwapchainStatus {
+        if self.suboptimal {
+            SwapchainStatus::Suboptimal
+        } else {
+            SwapchainStatus::Optimal
+        }
+    }
 }
 
 unsafe impl GpuFuture for SwapchainAcquireFuture {
@@ -575,6 +884,148 @@ impl Drop for SwapchainAcquireFuture {
     }
 }
 
+/// Whether a swapchain still matches the properties of its surface.
+///
+/// The status is returned by `SwapchainAcquireFuture::status` for a single acquisition, and by
+/// `Swapchain::status` for the latest acquire and present operations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwapchainStatus {
+    /// The swapchain matches the surface. Keep rendering as usual.
+    Optimal,
+
+    /// The images can still be presented, but the swapchain no longer matches the surface
+    /// exactly, for example because the window has been resized or moved to a screen with a
+    /// different DPI. The presentation engine may have to scale the images.
+    ///
+    /// The image that was acquired should still be drawn and presented. The swapchain should
+    /// then be recreated when convenient, for example before the next frame, rather than
+    /// immediately.
+    Suboptimal,
+
+    /// The swapchain can no longer be used to present, and acquiring or presenting an image
+    /// returns an `OutOfDate` error. It must be recreated before the next frame.
+    OutOfDate,
+}
+
+/// Error that can happen when creating a swapchain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapchainCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The surface is no longer accessible and must be recreated.
+    SurfaceLost,
+
+    /// The window of the surface is already used by another API.
+    NativeWindowInUse,
+
+    /// The requested present mode is not supported by the surface.
+    UnsupportedPresentMode,
+
+    /// The requested combination of format and color space is not supported by the surface.
+    UnsupportedFormat,
+
+    /// The requested pre-transform is not part of the transforms supported by the surface.
+    UnsupportedTransform,
+
+    /// A color space other than `SrgbNonLinear` was requested, but the
+    /// `ext_swapchain_colorspace` instance extension was not enabled.
+    ColorSpaceExtensionNotEnabled,
+
+    /// None of the queue families that are going to use the swapchain images can present them on
+    /// the surface. Use `QueueFamily::supports_surface` to check beforehand.
+    UnsupportedQueueFamily,
+
+    /// Some of the requested image usages are not part of the `supported_usage_flags` of the
+    /// surface. Contains the usages that are not supported.
+    UnsupportedUsageFlags(ImageUsage),
+}
+
+impl error::Error for SwapchainCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SwapchainCreationError::OomError(_) => "not enough memory available",
+            SwapchainCreationError::DeviceLost => "the connection to the device has been lost",
+            SwapchainCreationError::SurfaceLost => "the surface is no longer valid",
+            SwapchainCreationError::NativeWindowInUse => {
+                "the window of the surface is already used by another API"
+            },
+            SwapchainCreationError::UnsupportedPresentMode => {
+                "the requested present mode is not supported by the surface"
+            },
+            SwapchainCreationError::UnsupportedFormat => {
+                "the requested combination of format and color space is not supported by the \
+                 surface"
+            },
+            SwapchainCreationError::UnsupportedTransform => {
+                "the requested pre-transform is not supported by the surface"
+            },
+            SwapchainCreationError::ColorSpaceExtensionNotEnabled => {
+                "the `ext_swapchain_colorspace` extension must be enabled to use this color space"
+            },
+            SwapchainCreationError::UnsupportedQueueFamily => {
+                "none of the queue families that use the swapchain images can present on the \
+                 surface"
+            },
+            SwapchainCreationError::UnsupportedUsageFlags(_) => {
+                "the requested image usage is not supported by the surface"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            SwapchainCreationError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for SwapchainCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for SwapchainCreationError {
+    #[inline]
+    fn from(err: OomError) -> SwapchainCreationError {
+        SwapchainCreationError::OomError(err)
+    }
+}
+
+impl From<CapabilitiesError> for SwapchainCreationError {
+    #[inline]
+    fn from(err: CapabilitiesError) -> SwapchainCreationError {
+        match err {
+            CapabilitiesError::OomError(err) => SwapchainCreationError::OomError(err),
+            CapabilitiesError::SurfaceLost => SwapchainCreationError::SurfaceLost,
+        }
+    }
+}
+
+impl From<Error> for SwapchainCreationError {
+    #[inline]
+    fn from(err: Error) -> SwapchainCreationError {
+        match err {
+            err @ Error::OutOfHostMemory => SwapchainCreationError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => {
+                SwapchainCreationError::OomError(OomError::from(err))
+            },
+            Error::DeviceLost => SwapchainCreationError::DeviceLost,
+            Error::SurfaceLost => SwapchainCreationError::SurfaceLost,
+            Error::NativeWindowInUse => SwapchainCreationError::NativeWindowInUse,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
 /// Error that can happen when calling `acquire_next_image`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -645,13 +1096,161 @@ impl From<Error> for AcquireError {
     }
 }
 
+/// Error that can happen when calling `present` or `present_multiple`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PresentError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The surface of one of the swapchains is no longer accessible and must be recreated.
+    SurfaceLost,
+
+    /// The family of the queue can't present images on the surface of one of the swapchains.
+    /// Use `QueueFamily::supports_surface` to check beforehand.
+    UnsupportedQueueFamily,
+
+    /// The same swapchain appears multiple times in the list of swapchains to present.
+    DuplicateSwapchain,
+}
+
+impl error::Error for PresentError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            PresentError::OomError(_) => "not enough memory",
+            PresentError::SurfaceLost => "the surface of a swapchain is no longer valid",
+            PresentError::UnsupportedQueueFamily => {
+                "the queue family can't present images on the surface of a swapchain"
+            },
+            PresentError::DuplicateSwapchain => {
+                "the same swapchain appears multiple times in the list of swapchains to present"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            PresentError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for PresentError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<CapabilitiesError> for PresentError {
+    #[inline]
+    fn from(err: CapabilitiesError) -> PresentError {
+        match err {
+            CapabilitiesError::OomError(err) => PresentError::OomError(err),
+            CapabilitiesError::SurfaceLost => PresentError::SurfaceLost,
+        }
+    }
+}
+
 /// Represents a swapchain image being presented on the screen.
 #[must_use = "Dropping this object will immediately block the thread until the GPU has finished processing the submission"]
 pub struct PresentFuture<P> where P: GpuFuture {
+    // Presenting a single swapchain is the same as presenting multiple swapchains, except that
+    // the error of the swapchain is the error of the whole flush.
+    inner: MultiPresentFuture<P>,
+}
+
+impl<P> PresentFuture<P> where P: GpuFuture {
+    /// Returns the index of the image in the list of images returned when creating the swapchain.
+    #[inline]
+    pub fn image_id(&self) -> usize {
+        self.inner.swapchains[0].1
+    }
+
+    /// Returns the corresponding swapchain.
+    #[inline]
+    pub fn swapchain(&self) -> &Arc<Swapchain> {
+        &self.inner.swapchains[0].0
+    }
+}
+
+unsafe impl<P> GpuFuture for PresentFuture<P> where P: GpuFuture {
+    #[inline]
+    fn cleanup_finished(&mut self) {
+        self.inner.cleanup_finished();
+    }
+
+    #[inline]
+    unsafe fn build_submission(&self) -> Result<SubmitAnyBuilder, FlushError> {
+        self.inner.build_submission()
+    }
+
+    #[inline]
+    fn flush(&self) -> Result<(), FlushError> {
+        try!(self.inner.flush());
+
+        // The results are only written by the flush that actually submitted the present command,
+        // so that the error is only reported once.
+        if let Some(results) = self.inner.results.lock().unwrap().take() {
+            for result in results {
+                try!(result);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn signal_finished(&self) {
+        self.inner.signal_finished();
+    }
+
+    #[inline]
+    fn queue_change_allowed(&self) -> bool {
+        self.inner.queue_change_allowed()
+    }
+
+    #[inline]
+    fn queue(&self) -> Option<Arc<Queue>> {
+        self.inner.queue()
+    }
+
+    #[inline]
+    fn check_buffer_access(&self, buffer: &BufferAccess, exclusive: bool, queue: &Queue)
+                           -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError>
+    {
+        self.inner.check_buffer_access(buffer, exclusive, queue)
+    }
+
+    #[inline]
+    fn check_image_access(&self, image: &ImageAccess, layout: ImageLayout, exclusive: bool, queue: &Queue)
+                          -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError>
+    {
+        self.inner.check_image_access(image, layout, exclusive, queue)
+    }
+}
+
+unsafe impl<P> DeviceOwned for PresentFuture<P> where P: GpuFuture {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
+
+/// Represents images of multiple swapchains being presented on the screen.
+///
+/// Returned by `present_multiple`.
+#[must_use = "Dropping this object will immediately block the thread until the GPU has finished processing the submission"]
+pub struct MultiPresentFuture<P> where P: GpuFuture {
     previous: P,
     queue: Arc<Queue>,
-    swapchain: Arc<Swapchain>,
-    image_id: usize,
+    swapchains: Vec<(Arc<Swapchain>, usize)>,
+    // Result of the presentation of each swapchain, or `None` if the future hasn't been flushed
+    // by itself yet.
+    results: Mutex<Option<Vec<Result<(), SubmitPresentError>>>>,
     // True if `flush()` has been called on the future, which means that the present command has
     // been submitted.
     flushed: AtomicBool,
@@ -660,21 +1259,34 @@ pub struct PresentFuture<P> where P: GpuFuture {
     finished: AtomicBool,
 }
 
-impl<P> PresentFuture<P> where P: GpuFuture {
-    /// Returns the index of the image in the list of images returned when creating the swapchain.
+impl<P> MultiPresentFuture<P> where P: GpuFuture {
+    /// Returns the swapchains and the indices of the images that are presented.
     #[inline]
-    pub fn image_id(&self) -> usize {
-        self.image_id
+    pub fn swapchains(&self) -> &[(Arc<Swapchain>, usize)] {
+        &self.swapchains
     }
 
-    /// Returns the corresponding swapchain.
+    /// Returns the result of the presentation of each swapchain, in the same order as
+    /// `swapchains()`.
+    ///
+    /// Returns `None` if the future hasn't been flushed yet, or if it has been submitted as part
+    /// of a later future, for example by `then_signal_fence_and_flush`. In that situation you can
+    /// still use `Swapchain::status()` to know whether each swapchain needs to be recreated.
     #[inline]
-    pub fn swapchain(&self) -> &Arc<Swapchain> {
-        &self.swapchain
+    pub fn results(&self) -> Option<Vec<Result<(), SubmitPresentError>>> {
+        self.results.lock().unwrap().clone()
+    }
+
+    fn add_swapchains<'a>(&'a self, builder: &mut SubmitPresentBuilder<'a>) {
+        for &(ref swapchain, index) in self.swapchains.iter() {
+            unsafe {
+                builder.add_swapchain(swapchain, index as u32);
+            }
+        }
     }
 }
 
-unsafe impl<P> GpuFuture for PresentFuture<P> where P: GpuFuture {
+unsafe impl<P> GpuFuture for MultiPresentFuture<P> where P: GpuFuture {
     #[inline]
     fn cleanup_finished(&mut self) {
         self.previous.cleanup_finished();
@@ -686,36 +1298,24 @@ unsafe impl<P> GpuFuture for PresentFuture<P> where P: GpuFuture {
             return Ok(SubmitAnyBuilder::Empty);
         }
 
-        let queue = self.previous.queue().map(|q| q.clone());
-
         // TODO: if the swapchain image layout is not PRESENT, should add a transition command
         // buffer
 
-        Ok(match try!(self.previous.build_submission()) {
-            SubmitAnyBuilder::Empty => {
-                let mut builder = SubmitPresentBuilder::new();
-                builder.add_swapchain(&self.swapchain, self.image_id as u32);
-                SubmitAnyBuilder::QueuePresent(builder)
-            },
-            SubmitAnyBuilder::SemaphoresWait(sem) => {
-                let mut builder: SubmitPresentBuilder = sem.into();
-                builder.add_swapchain(&self.swapchain, self.image_id as u32);
-                SubmitAnyBuilder::QueuePresent(builder)
-            },
-            SubmitAnyBuilder::CommandBuffer(cb) => {
-                try!(cb.submit(&queue.unwrap()));        // FIXME: wrong because build_submission can be called multiple times
-                let mut builder = SubmitPresentBuilder::new();
-                builder.add_swapchain(&self.swapchain, self.image_id as u32);
-                SubmitAnyBuilder::QueuePresent(builder)
+        let mut builder = match try!(self.previous.build_submission()) {
+            SubmitAnyBuilder::Empty => SubmitPresentBuilder::new(),
+            SubmitAnyBuilder::SemaphoresWait(sem) => sem.into(),
+            SubmitAnyBuilder::CommandBuffer(_) | SubmitAnyBuilder::QueuePresent(_) => {
+                // The previous submission can't be merged with a present command, so we submit it
+                // first. We go through `flush()` instead of submitting the builder ourselves,
+                // because the previous future remembers that it has been flushed. That way,
+                // calling `build_submission` multiple times doesn't submit it multiple times.
+                try!(self.previous.flush());
+                SubmitPresentBuilder::new()
             },
-            SubmitAnyBuilder::QueuePresent(present) => {
-                unimplemented!()        // TODO:
-                /*present.submit();
-                let mut builder = SubmitPresentBuilder::new();
-                builder.add_swapchain(self.command_buffer.inner(), self.image_id);
-                SubmitAnyBuilder::CommandBuffer(builder)*/
-            },
-        })
+        };
+
+        self.add_swapchains(&mut builder);
+        Ok(SubmitAnyBuilder::QueuePresent(builder))
     }
 
     #[inline]
@@ -726,7 +1326,8 @@ unsafe impl<P> GpuFuture for PresentFuture<P> where P: GpuFuture {
             match self.build_submission()? {
                 SubmitAnyBuilder::Empty => {}
                 SubmitAnyBuilder::QueuePresent(present) => {
-                    present.submit(&self.queue)?;
+                    let results = present.submit_with_results(&self.queue)?;
+                    *self.results.lock().unwrap() = Some(results.into_iter().collect());
                 }
                 _ => unreachable!()
             }
@@ -769,36 +1370,84 @@ unsafe impl<P> GpuFuture for PresentFuture<P> where P: GpuFuture {
     fn check_image_access(&self, image: &ImageAccess, layout: ImageLayout, exclusive: bool, queue: &Queue)
                           -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError>
     {
-        let swapchain_image = self.swapchain.raw_image(self.image_id).unwrap();
-        if swapchain_image.internal_object() == image.inner().internal_object() {
-            // This future presents the swapchain image, which "unlocks" it. Therefore any attempt 
-            // to use this swapchain image afterwards shouldn't get granted automatic access.
-            // Instead any attempt to access the image afterwards should get an authorization from
-            // a later swapchain acquire future. Hence why we return `Unknown` here.
-            Err(AccessCheckError::Unknown)
-        } else {
-            self.previous.check_image_access(image, layout, exclusive, queue)
+        for &(ref swapchain, index) in self.swapchains.iter() {
+            let swapchain_image = swapchain.raw_image(index).unwrap();
+            if swapchain_image.internal_object() == image.inner().internal_object() {
+                // This future presents the swapchain image, which "unlocks" it. Therefore any
+                // attempt to use this swapchain image afterwards shouldn't get granted automatic
+                // access. Instead any attempt to access the image afterwards should get an
+                // authorization from a later swapchain acquire future. Hence why we return
+                // `Unknown` here.
+                return Err(AccessCheckError::Unknown);
+            }
         }
+
+        self.previous.check_image_access(image, layout, exclusive, queue)
     }
 }
 
-unsafe impl<P> DeviceOwned for PresentFuture<P> where P: GpuFuture {
+unsafe impl<P> DeviceOwned for MultiPresentFuture<P> where P: GpuFuture {
     #[inline]
     fn device(&self) -> &Arc<Device> {
         self.queue.device()
     }
 }
 
-impl<P> Drop for PresentFuture<P> where P: GpuFuture {
+impl<P> Drop for MultiPresentFuture<P> where P: GpuFuture {
     fn drop(&mut self) {
         unsafe {
             if !*self.finished.get_mut() {
-                // TODO: handle errors?
-                self.flush().unwrap();
+                // If the device has been lost, nothing is executing anymore and there is no need
+                // to wait.
+                // TODO: handle other errors?
+                match self.flush() {
+                    Ok(()) | Err(FlushError::DeviceLost) => (),
+                    Err(err) => panic!("{:?}", err),
+                }
                 // Block until the queue finished.
-                self.queue().unwrap().wait().unwrap();
+                // Note that this returns immediately if the device has been lost.
+                match self.queue().unwrap().wait() {
+                    Ok(()) | Err(DeviceWaitError::DeviceLost) => (),
+                    Err(err) => panic!("{:?}", err),
+                }
                 self.previous.signal_finished();
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use swapchain::CapabilitiesError;
+    use swapchain::PresentError;
+    use super::check_queue_support;
+    use super::has_duplicates;
+
+    #[test]
+    fn no_duplicates() {
+        assert!(!has_duplicates(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn duplicates() {
+        assert!(has_duplicates(vec![1, 2, 1]));
+        assert!(has_duplicates(vec![5, 5]));
+    }
+
+    #[test]
+    fn queue_supported() {
+        assert_eq!(check_queue_support(vec![Ok(true), Ok(true)]), Ok(()));
+    }
+
+    #[test]
+    fn queue_unsupported() {
+        assert_eq!(check_queue_support(vec![Ok(true), Ok(false)]),
+                   Err(PresentError::UnsupportedQueueFamily));
+    }
+
+    #[test]
+    fn queue_support_surface_lost() {
+        assert_eq!(check_queue_support(vec![Err(CapabilitiesError::SurfaceLost), Ok(false)]),
+                   Err(PresentError::SurfaceLost));
+    }
+}
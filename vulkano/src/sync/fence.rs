@@ -42,9 +42,38 @@ pub struct Fence<D = Arc<Device>> where D: SafeDeref<Target = Device> {
     // This variable exists so that we don't need to call `vkGetFenceStatus` or `vkWaitForFences`
     // multiple times.
     signaled: AtomicBool,
+
+    // If true, the fence is reset and put back in the device's fence pool when destroyed.
+    pooled: bool,
 }
 
 impl<D> Fence<D> where D: SafeDeref<Target = Device> {
+    /// Takes an unsignaled fence from the fence pool of the device, or builds a new one if the
+    /// pool is empty.
+    ///
+    /// The fence is returned to the pool when it is destroyed, provided that it is signaled at
+    /// that point.
+    pub fn from_pool(device: D) -> Result<Fence<D>, OomError> {
+        let fence = match device.fence_pool().take() {
+            Some(fence) => {
+                Fence {
+                    fence: fence,
+                    device: device,
+                    signaled: AtomicBool::new(false),
+                    pooled: true,
+                }
+            },
+            None => {
+                let mut fence = try!(Fence::new_impl(device, false));
+                fence.device.fence_pool().record_creation();
+                fence.pooled = true;
+                fence
+            },
+        };
+
+        Ok(fence)
+    }
+
     /// Builds a new fence.
     #[inline]
     pub fn new(device: D) -> Result<Fence<D>, OomError> {
@@ -76,25 +105,35 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
             fence: fence,
             device: device,
             signaled: AtomicBool::new(signaled),
+            pooled: false,
         })
     }
 
     /// Returns true if the fence is signaled.
+    ///
+    /// If the device has been lost, returns true as well since nothing executes anymore. Use
+    /// `wait()` with a timeout of 0 or `Device::is_lost()` if you need to tell the difference.
     #[inline]
     pub fn ready(&self) -> Result<bool, OomError> {
         unsafe {
             if self.signaled.load(Ordering::Relaxed) { return Ok(true); }
 
+            if self.device.is_lost() {
+                return Ok(true);
+            }
+
             let vk = self.device.pointers();
-            let result = try!(check_errors(vk.GetFenceStatus(self.device.internal_object(),
-                                                             self.fence)));
-            match result {
-                Success::Success => {
+            let result = check_errors(vk.GetFenceStatus(self.device.internal_object(),
+                                                        self.fence));
+            match self.device.track_lost(result) {
+                Ok(Success::Success) => {
                     self.signaled.store(true, Ordering::Relaxed);
                     Ok(true)
                 },
-                Success::NotReady => Ok(false),
-                _ => unreachable!()
+                Ok(Success::NotReady) => Ok(false),
+                Ok(_) => unreachable!(),
+                Err(Error::DeviceLost) => Ok(true),
+                Err(err) => Err(err.into()),
             }
         }
     }
@@ -104,10 +143,17 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
     /// Returns `Ok` if the fence is now signaled. Returns `Err` if the timeout was reached instead.
     ///
     /// If you pass a duration of 0, then the function will return without blocking.
+    ///
+    /// Returns `FenceWaitError::DeviceLostError` if the device has been lost, either before or
+    /// during the wait.
     pub fn wait(&self, timeout: Option<Duration>) -> Result<(), FenceWaitError> {
         unsafe {
             if self.signaled.load(Ordering::Relaxed) { return Ok(()); }
 
+            if self.device.is_lost() {
+                return Err(FenceWaitError::DeviceLostError);
+            }
+
             let timeout_ns = if let Some(timeout) = timeout {
                 timeout.as_secs().saturating_mul(1_000_000_000)
                                  .saturating_add(timeout.subsec_nanos() as u64)
@@ -116,8 +162,9 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
             };
 
             let vk = self.device.pointers();
-            let r = try!(check_errors(vk.WaitForFences(self.device.internal_object(), 1,
-                                                       &self.fence, vk::TRUE, timeout_ns)));
+            let r = check_errors(vk.WaitForFences(self.device.internal_object(), 1,
+                                                  &self.fence, vk::TRUE, timeout_ns));
+            let r = try!(self.device.track_lost(r));
 
             match r {
                 Success::Success => {
@@ -165,10 +212,16 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
         };
 
         let r = if let Some(device) = device {
+            if device.is_lost() {
+                return Err(FenceWaitError::DeviceLostError);
+            }
+
             unsafe {
                 let vk = device.pointers();
-                try!(check_errors(vk.WaitForFences(device.internal_object(), fences.len() as u32,
-                                                   fences.as_ptr(), vk::TRUE, timeout_ns)))
+                let r = check_errors(vk.WaitForFences(device.internal_object(),
+                                                      fences.len() as u32, fences.as_ptr(),
+                                                      vk::TRUE, timeout_ns));
+                try!(device.track_lost(r))
             }
         } else {
             return Ok(());
@@ -244,8 +297,17 @@ impl<D> Drop for Fence<D> where D: SafeDeref<Target = Device> {
     #[inline]
     fn drop(&mut self) {
         unsafe {
-            let vk = self.device.pointers();
-            vk.DestroyFence(self.device.internal_object(), self.fence, ptr::null());
+            // A fence can only be reused if we know that the GPU is done with it, which is the
+            // case if it is signaled. `ready()` also returns true if the device has been lost,
+            // in which case nothing tells us that the fence will ever be signaled, so we destroy
+            // it instead. We check `is_lost()` afterwards, as `ready()` can detect the loss.
+            if self.pooled && self.ready().unwrap_or(false) && !self.device.is_lost() {
+                self.reset();
+                self.device.fence_pool().recycle(self.fence);
+            } else {
+                let vk = self.device.pointers();
+                vk.DestroyFence(self.device.internal_object(), self.fence, ptr::null());
+            }
         }
     }
 }
@@ -289,6 +351,13 @@ impl fmt::Display for FenceWaitError {
     }
 }
 
+impl From<OomError> for FenceWaitError {
+    #[inline]
+    fn from(err: OomError) -> FenceWaitError {
+        FenceWaitError::OomError(err)
+    }
+}
+
 impl From<Error> for FenceWaitError {
     #[inline]
     fn from(err: Error) -> FenceWaitError {
@@ -305,6 +374,7 @@ impl From<Error> for FenceWaitError {
 mod tests {
     use std::time::Duration;
     use sync::Fence;
+    use Error;
 
     #[test]
     fn fence_create() {
@@ -339,6 +409,18 @@ mod tests {
         assert!(!fence.ready().unwrap());
     }
 
+    #[test]
+    fn fence_not_recycled_after_device_lost() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let fence = Fence::from_pool(device.clone()).unwrap();
+        let _ = device.track_lost::<()>(Err(Error::DeviceLost));
+        assert!(fence.ready().unwrap());
+        drop(fence);
+
+        assert_eq!(device.fence_pool().num_available(), 0);
+    }
+
     #[test]
     #[should_panic(expected = "Tried to wait for multiple fences that didn't belong to the same device")]
     fn multiwait_different_devices() {
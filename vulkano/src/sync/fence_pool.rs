@@ -0,0 +1,113 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::fmt;
+use std::ptr;
+
+use sync::handle_pool::HandlePool;
+use vk;
+
+/// Pool of fences that are reused instead of being destroyed.
+///
+/// Each `Device` owns a pool, which can be accessed with `Device::fence_pool()`. Fences built
+/// with `Fence::from_pool` are taken from this pool, and are reset and put back in it when they
+/// are destroyed. This is what `then_signal_fence` and `FramesInFlight` use.
+///
+/// A fence is only returned to the pool if it is signaled when it is destroyed, which guarantees
+/// that the GPU no longer uses it. Otherwise it is destroyed as usual.
+pub struct FencePool {
+    // Fences that are unsignaled, not in use, and that can be handed out.
+    pool: HandlePool<vk::Fence>,
+}
+
+impl FencePool {
+    /// Builds a new empty pool.
+    #[inline]
+    pub(crate) fn new() -> FencePool {
+        FencePool {
+            pool: HandlePool::new(),
+        }
+    }
+
+    /// Returns the total number of fences that have been created for this pool.
+    #[inline]
+    pub fn num_created(&self) -> usize {
+        self.pool.num_created()
+    }
+
+    /// Returns the number of fences that are currently unused and waiting in the pool.
+    #[inline]
+    pub fn num_available(&self) -> usize {
+        self.pool.num_available()
+    }
+
+    // Takes an unused fence from the pool, if there is one.
+    #[inline]
+    pub(crate) fn take(&self) -> Option<vk::Fence> {
+        self.pool.take()
+    }
+
+    // Must be called whenever a fence has been created for this pool.
+    #[inline]
+    pub(crate) fn record_creation(&self) {
+        self.pool.record_creation();
+    }
+
+    // Puts back a fence in the pool. It must be unsignaled and not used by the GPU.
+    #[inline]
+    pub(crate) unsafe fn recycle(&self, fence: vk::Fence) {
+        self.pool.recycle(fence);
+    }
+
+    // Destroys all the fences of the pool. Called when the device is destroyed.
+    pub(crate) unsafe fn destroy_all(&self, vk: &vk::DevicePointers, device: vk::Device) {
+        self.pool.destroy_all(|fence| vk.DestroyFence(device, fence, ptr::null()));
+    }
+}
+
+impl fmt::Debug for FencePool {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "<Vulkan fence pool ({} created)>", self.num_created())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::submit::SubmitCommandBufferBuilder;
+    use sync::Fence;
+    use VulkanObject;
+
+    #[test]
+    fn unsignaled_not_recycled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        drop(Fence::from_pool(device.clone()).unwrap());
+        assert_eq!(device.fence_pool().num_available(), 0);
+    }
+
+    #[test]
+    fn signaled_recycled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let fence = Fence::from_pool(device.clone()).unwrap();
+        let raw = fence.internal_object();
+        unsafe {
+            let mut builder = SubmitCommandBufferBuilder::new();
+            builder.set_fence_signal(&fence);
+            builder.submit(&queue).unwrap();
+        }
+        fence.wait(None).unwrap();
+        drop(fence);
+
+        let fence = Fence::from_pool(device.clone()).unwrap();
+        assert_eq!(fence.internal_object(), raw);
+        assert!(!fence.ready().unwrap());
+    }
+}
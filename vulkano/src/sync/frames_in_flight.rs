@@ -0,0 +1,183 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use device::Device;
+use device::DeviceOwned;
+use sync::Fence;
+use sync::FenceWaitError;
+
+/// Helper that limits the number of frames that are processed by the GPU at the same time.
+///
+/// When rendering, the CPU usually prepares the next frame while the GPU is still working on the
+/// previous ones. The resources that are modified by the CPU for a frame (uniform buffers,
+/// command buffers, ...) must therefore be duplicated for each frame in flight, and the CPU must
+/// not touch a copy before the GPU has finished the frame that last used it.
+///
+/// A `FramesInFlight` holds one fence per frame slot. Each call to `next_frame()` moves to the
+/// next slot in a ring, waits for the fence of the frame that previously used this slot, resets
+/// it, and returns the index of the slot and the fence. The index can be used to pick the copy
+/// of your per-frame resources, and the fence must be signaled by the last submission of the
+/// frame.
+///
+/// ```
+/// use vulkano::sync::FramesInFlight;
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// let mut frames = FramesInFlight::new(device.clone(), 2);
+///
+/// loop {
+///     let (index, fence) = frames.next_frame(None).unwrap();
+///     // The resources of slot `index` are no longer in use by the GPU.
+///     // ...
+///     // Submit the frame, and make the submission signal `fence`.
+///     # break;
+/// }
+/// ```
+///
+/// > **Note**: If you don't submit anything that signals the fence of a frame, the next call to
+/// > `next_frame()` that reaches this slot will wait until the timeout is reached.
+#[derive(Debug)]
+pub struct FramesInFlight {
+    device: Arc<Device>,
+    // One entry per slot. `None` if the slot has never been handed out.
+    fences: Vec<Option<Fence>>,
+    // Index of the slot that was handed out last. Equal to `fences.len()` before the first frame.
+    current: usize,
+}
+
+impl FramesInFlight {
+    /// Builds a new `FramesInFlight` with the given number of frame slots.
+    ///
+    /// Fences are lazily taken from the fence pool of the device.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `num_frames` is 0.
+    ///
+    pub fn new(device: Arc<Device>, num_frames: usize) -> FramesInFlight {
+        assert!(num_frames >= 1);
+
+        FramesInFlight {
+            device: device,
+            fences: (0 .. num_frames).map(|_| None).collect(),
+            current: num_frames,
+        }
+    }
+
+    /// Returns the number of frame slots.
+    #[inline]
+    pub fn num_frames(&self) -> usize {
+        self.fences.len()
+    }
+
+    /// Returns the index of the slot that was returned by the last call to `next_frame()`, or
+    /// `None` if it was never called.
+    #[inline]
+    pub fn current_frame(&self) -> Option<usize> {
+        if self.current < self.fences.len() {
+            Some(self.current)
+        } else {
+            None
+        }
+    }
+
+    /// Moves to the next frame slot.
+    ///
+    /// Blocks until the frame that previously used this slot has finished executing, then resets
+    /// the fence of the slot. Returns the index of the slot and the fence that the submission of
+    /// the new frame must signal.
+    ///
+    /// If `timeout` is `None`, the wait is infinite. If the timeout is reached, an error is
+    /// returned and the current slot doesn't change.
+    pub fn next_frame(&mut self, timeout: Option<Duration>)
+                      -> Result<(usize, &Fence), FenceWaitError>
+    {
+        let next = (self.current + 1) % self.fences.len();
+
+        {
+            let slot = &mut self.fences[next];
+
+            match *slot {
+                Some(ref mut fence) => {
+                    try!(fence.wait(timeout));
+                    fence.reset();
+                },
+                None => {
+                    *slot = Some(try!(Fence::from_pool(self.device.clone())));
+                },
+            }
+        }
+
+        self.current = next;
+        Ok((next, self.fences[next].as_ref().unwrap()))
+    }
+
+    /// Waits until all the frames that are in flight have finished executing.
+    pub fn wait_idle(&self, timeout: Option<Duration>) -> Result<(), FenceWaitError> {
+        let fences = self.fences.iter().filter_map(|f| f.as_ref());
+        Fence::multi_wait(fences, timeout)
+    }
+}
+
+unsafe impl DeviceOwned for FramesInFlight {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use command_buffer::submit::SubmitCommandBufferBuilder;
+    use sync::FramesInFlight;
+    use VulkanObject;
+
+    #[test]
+    fn fences_reused_after_signaled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let mut frames = FramesInFlight::new(device.clone(), 3);
+        let mut handed_out = Vec::new();
+
+        for frame in 0 .. 10 {
+            let (index, fence) = frames.next_frame(None).unwrap();
+            assert_eq!(index, frame % 3);
+            // The fence is either new or has been waited upon and reset.
+            assert!(!fence.ready().unwrap());
+            handed_out.push(fence.internal_object());
+
+            unsafe {
+                let mut builder = SubmitCommandBufferBuilder::new();
+                builder.set_fence_signal(fence);
+                builder.submit(&queue).unwrap();
+            }
+        }
+
+        for frame in 3 .. 10 {
+            assert_eq!(handed_out[frame], handed_out[frame - 3]);
+        }
+
+        frames.wait_idle(None).unwrap();
+    }
+
+    #[test]
+    fn zero_frames() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        // Catching the panic ourselves, as `#[should_panic]` would fail if there is no device to
+        // run the test on.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| FramesInFlight::new(device, 0)));
+        assert!(result.is_err());
+    }
+}
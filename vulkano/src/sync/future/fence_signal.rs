@@ -25,6 +25,7 @@ use sync::AccessCheckError;
 use sync::AccessFlagBits;
 use sync::FlushError;
 use sync::Fence;
+use sync::FenceWaitError;
 use sync::GpuFuture;
 use sync::PipelineStages;
 
@@ -37,10 +38,11 @@ pub fn then_signal_fence<F>(future: F, behavior: FenceSignalFutureBehavior) -> F
 
     assert!(future.queue().is_some());        // TODO: document
 
-    let fence = Fence::new(device.clone()).unwrap();
+    let fence = Fence::from_pool(device.clone()).unwrap();
     FenceSignalFuture {
         device: device,
         state: Mutex::new(FenceSignalFutureState::Pending(future, fence)),
+        submission_id: Mutex::new(None),
         behavior: behavior,
     }
 }
@@ -91,6 +93,8 @@ pub enum FenceSignalFutureBehavior {
 pub struct FenceSignalFuture<F> where F: GpuFuture {
     // Current state. See the docs of `FenceSignalFutureState`.
     state: Mutex<FenceSignalFutureState<F>>,
+    // ID of the submission that signals the fence, once it has been flushed.
+    submission_id: Mutex<Option<u64>>,
     // The device of the future.
     device: Arc<Device>,
     behavior: FenceSignalFutureBehavior,
@@ -121,6 +125,16 @@ enum FenceSignalFutureState<F> {
 }
 
 impl<F> FenceSignalFuture<F> where F: GpuFuture {
+    /// Returns the ID that the queue assigned to the submission that signals the fence, or
+    /// `None` if the future hasn't been flushed yet.
+    ///
+    /// Once the fence is known to be signaled, for example after `wait()` returned successfully,
+    /// `Queue::last_completed_id()` is at least this value.
+    #[inline]
+    pub fn submission_id(&self) -> Option<u64> {
+        *self.submission_id.lock().unwrap()
+    }
+
     /// Blocks the current thread until the fence is signaled by the GPU. Performs a flush if
     /// necessary.
     ///
@@ -136,9 +150,19 @@ impl<F> FenceSignalFuture<F> where F: GpuFuture {
 
         match mem::replace(&mut *state, FenceSignalFutureState::Cleaned) {
             FenceSignalFutureState::Flushed(previous, fence) => {
-                fence.wait(timeout)?;
-                unsafe { previous.signal_finished(); }
-                Ok(())
+                match fence.wait(timeout) {
+                    Ok(()) => {
+                        self.mark_completed(&previous);
+                        unsafe { previous.signal_finished(); }
+                        Ok(())
+                    },
+                    Err(err) => {
+                        // The submission is still in progress, so we must keep the previous
+                        // future and the fence alive.
+                        *state = FenceSignalFutureState::Flushed(previous, fence);
+                        Err(err.into())
+                    },
+                }
             },
             FenceSignalFutureState::Cleaned => Ok(()),
             _ => unreachable!()
@@ -157,6 +181,7 @@ impl<F> FenceSignalFuture<F> where F: GpuFuture {
             FenceSignalFutureState::Flushed(ref mut prev, ref fence) => {
                 match fence.wait(Some(Duration::from_secs(0))) {
                     Ok(()) => unsafe {
+                        self.mark_completed(prev);
                         prev.signal_finished()
                     },
                     Err(_) => {
@@ -181,6 +206,14 @@ impl<F> FenceSignalFuture<F> where F: GpuFuture {
         *state = FenceSignalFutureState::Cleaned;
     }
 
+    // Informs the queue that the submission that signals the fence is finished. Must only be
+    // called once the fence is signaled.
+    fn mark_completed(&self, previous: &F) {
+        if let (Some(queue), Some(id)) = (previous.queue(), self.submission_id()) {
+            queue.mark_completed(id);
+        }
+    }
+
     // Implementation of `flush`. You must lock the state and pass the mutex guard here.
     fn flush_impl(&self, state: &mut MutexGuard<FenceSignalFutureState<F>>)
                   -> Result<(), FlushError>
@@ -258,7 +291,8 @@ impl<F> FenceSignalFuture<F> where F: GpuFuture {
 
             // Restore the state before returning.
             match result {
-                Ok(()) => {
+                Ok(id) => {
+                    *self.submission_id.lock().unwrap() = Some(id);
                     **state = FenceSignalFutureState::Flushed(previous, fence);
                     Ok(())
                 },
@@ -401,8 +435,14 @@ impl<F> Drop for FenceSignalFuture<F> where F: GpuFuture {
         match mem::replace(&mut *state, FenceSignalFutureState::Cleaned) {
             FenceSignalFutureState::Flushed(previous, fence) => {
                 // This is a normal situation. Submitting worked.
-                // TODO: handle errors?
-                fence.wait(None).unwrap();
+                // If the device has been lost, nothing is executing anymore and we can consider
+                // the submission finished.
+                // TODO: handle other errors?
+                match fence.wait(None) {
+                    Ok(()) | Err(FenceWaitError::DeviceLostError) => (),
+                    Err(err) => panic!("{:?}", err),
+                }
+                self.mark_completed(&previous);
                 unsafe { previous.signal_finished(); }
             },
             FenceSignalFutureState::Cleaned => {
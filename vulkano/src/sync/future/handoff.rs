@@ -0,0 +1,389 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use command_buffer::AutoCommandBufferBuilder;
+use command_buffer::CommandAddError;
+use command_buffer::CommandBufferBuild;
+use command_buffer::CommandBufferExecError;
+use command_buffer::cb::AddCommand;
+use command_buffer::commands_raw::CmdBufferMemoryBarrier;
+use command_buffer::commands_raw::CmdBufferOwnershipTransfer;
+use command_buffer::commands_raw::CmdImageMemoryBarrier;
+use command_buffer::commands_raw::CmdImageOwnershipTransfer;
+use command_buffer::commands_raw::CmdQueueOwnershipTransferError;
+use device::Queue;
+use image::ImageAccess;
+use sync::AccessFlagBits;
+use sync::GpuFuture;
+use sync::PipelineStages;
+use OomError;
+
+/// Describes how a resource written on a queue must be handed off to another queue.
+///
+/// This is what the `then_handoff_buffer` and `then_handoff_image` methods of `GpuFuture` use
+/// to decide which synchronization primitives to insert.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QueueHandoff {
+    /// Both queues are the same. A pipeline barrier is enough to make the writes visible.
+    Barrier,
+
+    /// The queues are different but the resource can be accessed by both of them, either because
+    /// they belong to the same family or because the resource uses the `Concurrent` sharing mode.
+    /// The memory dependency of a semaphore wait is enough to make the writes visible.
+    Semaphore,
+
+    /// The queues belong to different families and the resource uses the `Exclusive` sharing
+    /// mode. A release barrier must be executed on the source queue family, followed with a
+    /// semaphore, followed with an acquire barrier on the destination queue family.
+    OwnershipTransfer {
+        /// Family of the queue that wrote the resource.
+        source_family: u32,
+        /// Family of the queue that is going to use the resource.
+        destination_family: u32,
+    },
+}
+
+impl QueueHandoff {
+    /// Determines what is needed to hand off a resource from `producer` to `consumer`.
+    ///
+    /// `concurrent` must be true if the resource was created with the `Concurrent` sharing mode.
+    #[inline]
+    pub fn new(producer: &Queue, consumer: &Queue, concurrent: bool) -> QueueHandoff {
+        QueueHandoff::from_ids(producer.is_same(consumer), producer.family().id(),
+                               consumer.family().id(), concurrent)
+    }
+
+    // Same as `new`, but with the identifiers of the queues.
+    fn from_ids(same_queue: bool, producer_family: u32, consumer_family: u32, concurrent: bool)
+                -> QueueHandoff
+    {
+        if same_queue {
+            QueueHandoff::Barrier
+        } else if concurrent || producer_family == consumer_family {
+            QueueHandoff::Semaphore
+        } else {
+            QueueHandoff::OwnershipTransfer {
+                source_family: producer_family,
+                destination_family: consumer_family,
+            }
+        }
+    }
+}
+
+// Operation submitted by a handoff.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum HandoffStep {
+    // Pipeline barrier executed on the producer queue.
+    Barrier,
+    // Barrier that releases the ownership of the resource, executed on the producer queue.
+    Release,
+    // Semaphore signaled after the previous operations and waited upon by the consumer queue.
+    Semaphore,
+    // Barrier that acquires the ownership of the resource, executed on the consumer queue.
+    Acquire,
+}
+
+impl QueueHandoff {
+    // Returns the operations that are submitted for this handoff, in submission order.
+    fn steps(&self) -> &'static [HandoffStep] {
+        match *self {
+            QueueHandoff::Barrier => &[HandoffStep::Barrier],
+            QueueHandoff::Semaphore => &[HandoffStep::Semaphore],
+            QueueHandoff::OwnershipTransfer { .. } => {
+                &[HandoffStep::Release, HandoffStep::Semaphore, HandoffStep::Acquire]
+            },
+        }
+    }
+}
+
+/// Builds the future returned by `GpuFuture::then_handoff_buffer`.
+pub fn then_handoff_buffer<F, B>(future: F, buffer: B, src_stages: PipelineStages,
+                                 src_access: AccessFlagBits, queue: Arc<Queue>,
+                                 dst_stages: PipelineStages, dst_access: AccessFlagBits)
+                                 -> Result<Box<GpuFuture>, QueueHandoffError>
+    where F: GpuFuture + 'static, B: BufferAccess + Send + Sync + Clone + 'static
+{
+    let producer = match future.queue() {
+        Some(q) => q,
+        None => return Err(QueueHandoffError::UnknownProducerQueue),
+    };
+
+    let device = future.device().clone();
+    let concurrent = buffer.inner().buffer.concurrent_sharing();
+
+    let mut future: Box<GpuFuture> = Box::new(future);
+
+    for step in QueueHandoff::new(&producer, &queue, concurrent).steps() {
+        future = match *step {
+            HandoffStep::Barrier => {
+                let barrier = CmdBufferMemoryBarrier::new(buffer.clone(), src_stages, src_access,
+                                                          dst_stages, dst_access);
+                let cb = AutoCommandBufferBuilder::new(device.clone(), producer.family())?;
+                let cb = AddCommand::add(cb, barrier)?.build()?;
+                Box::new(future.then_execute(producer.clone(), cb)?)
+            },
+            HandoffStep::Release => {
+                let release = CmdBufferOwnershipTransfer::release(buffer.clone(),
+                                                                  producer.family(),
+                                                                  queue.family(), src_stages,
+                                                                  src_access)?;
+                let cb = AutoCommandBufferBuilder::new(device.clone(), producer.family())?;
+                let cb = AddCommand::add(cb, release)?.build()?;
+                Box::new(future.then_execute(producer.clone(), cb)?)
+            },
+            HandoffStep::Semaphore => {
+                Box::new(future.then_signal_semaphore())
+            },
+            HandoffStep::Acquire => {
+                let acquire = CmdBufferOwnershipTransfer::acquire(buffer.clone(),
+                                                                  producer.family(),
+                                                                  queue.family(), dst_stages,
+                                                                  dst_access)?;
+                let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family())?;
+                let cb = AddCommand::add(cb, acquire)?.build()?;
+                Box::new(future.then_execute(queue.clone(), cb)?)
+            },
+        };
+    }
+
+    Ok(future)
+}
+
+/// Builds the future returned by `GpuFuture::then_handoff_image`.
+pub fn then_handoff_image<F, I>(future: F, image: I, src_stages: PipelineStages,
+                                src_access: AccessFlagBits, queue: Arc<Queue>,
+                                dst_stages: PipelineStages, dst_access: AccessFlagBits)
+                                -> Result<Box<GpuFuture>, QueueHandoffError>
+    where F: GpuFuture + 'static, I: ImageAccess + Send + Sync + Clone + 'static
+{
+    let producer = match future.queue() {
+        Some(q) => q,
+        None => return Err(QueueHandoffError::UnknownProducerQueue),
+    };
+
+    let device = future.device().clone();
+    let concurrent = image.inner().concurrent_sharing();
+
+    let mut future: Box<GpuFuture> = Box::new(future);
+
+    for step in QueueHandoff::new(&producer, &queue, concurrent).steps() {
+        future = match *step {
+            HandoffStep::Barrier => {
+                let barrier = CmdImageMemoryBarrier::new(image.clone(), src_stages, src_access,
+                                                         dst_stages, dst_access);
+                let cb = AutoCommandBufferBuilder::new(device.clone(), producer.family())?;
+                let cb = AddCommand::add(cb, barrier)?.build()?;
+                Box::new(future.then_execute(producer.clone(), cb)?)
+            },
+            HandoffStep::Release => {
+                let release = CmdImageOwnershipTransfer::release(image.clone(), producer.family(),
+                                                                 queue.family(), src_stages,
+                                                                 src_access)?;
+                let cb = AutoCommandBufferBuilder::new(device.clone(), producer.family())?;
+                let cb = AddCommand::add(cb, release)?.build()?;
+                Box::new(future.then_execute(producer.clone(), cb)?)
+            },
+            HandoffStep::Semaphore => {
+                Box::new(future.then_signal_semaphore())
+            },
+            HandoffStep::Acquire => {
+                let acquire = CmdImageOwnershipTransfer::acquire(image.clone(), producer.family(),
+                                                                 queue.family(), dst_stages,
+                                                                 dst_access)?;
+                let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family())?;
+                let cb = AddCommand::add(cb, acquire)?.build()?;
+                Box::new(future.then_execute(queue.clone(), cb)?)
+            },
+        };
+    }
+
+    Ok(future)
+}
+
+/// Error that can happen when handing off a resource to another queue.
+#[derive(Debug, Clone)]
+pub enum QueueHandoffError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The future doesn't know on which queue the resource was written.
+    UnknownProducerQueue,
+
+    /// Error while building one of the ownership transfer commands.
+    OwnershipTransferError(CmdQueueOwnershipTransferError),
+
+    /// Error while adding a command to a command buffer.
+    CommandAddError(CommandAddError),
+
+    /// Error while executing a command buffer after the future.
+    CommandBufferExecError(CommandBufferExecError),
+}
+
+impl error::Error for QueueHandoffError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            QueueHandoffError::OomError(_) => "not enough memory available",
+            QueueHandoffError::UnknownProducerQueue => {
+                "the future doesn't know on which queue the resource was written"
+            },
+            QueueHandoffError::OwnershipTransferError(_) => {
+                "error while building one of the ownership transfer commands"
+            },
+            QueueHandoffError::CommandAddError(_) => {
+                "error while adding a command to a command buffer"
+            },
+            QueueHandoffError::CommandBufferExecError(_) => {
+                "error while executing a command buffer after the future"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            QueueHandoffError::OomError(ref err) => Some(err),
+            QueueHandoffError::OwnershipTransferError(ref err) => Some(err),
+            QueueHandoffError::CommandAddError(ref err) => Some(err),
+            QueueHandoffError::CommandBufferExecError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for QueueHandoffError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for QueueHandoffError {
+    #[inline]
+    fn from(err: OomError) -> QueueHandoffError {
+        QueueHandoffError::OomError(err)
+    }
+}
+
+impl From<CmdQueueOwnershipTransferError> for QueueHandoffError {
+    #[inline]
+    fn from(err: CmdQueueOwnershipTransferError) -> QueueHandoffError {
+        QueueHandoffError::OwnershipTransferError(err)
+    }
+}
+
+impl From<CommandAddError> for QueueHandoffError {
+    #[inline]
+    fn from(err: CommandAddError) -> QueueHandoffError {
+        QueueHandoffError::CommandAddError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for QueueHandoffError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> QueueHandoffError {
+        QueueHandoffError::CommandBufferExecError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBufferBuild;
+    use command_buffer::submit::SubmitAnyBuilder;
+    use sync::now;
+    use sync::AccessFlagBits;
+    use sync::GpuFuture;
+    use sync::PipelineStages;
+    use sync::QueueHandoff;
+    use sync::QueueHandoffError;
+    use sync::future::handoff::HandoffStep;
+
+    #[test]
+    fn same_queue_is_barrier() {
+        assert_eq!(QueueHandoff::from_ids(true, 0, 0, false), QueueHandoff::Barrier);
+        assert_eq!(QueueHandoff::from_ids(true, 0, 0, true), QueueHandoff::Barrier);
+    }
+
+    #[test]
+    fn same_family_is_semaphore() {
+        assert_eq!(QueueHandoff::from_ids(false, 1, 1, false), QueueHandoff::Semaphore);
+    }
+
+    #[test]
+    fn different_families() {
+        assert_eq!(QueueHandoff::from_ids(false, 0, 2, false),
+                   QueueHandoff::OwnershipTransfer { source_family: 0, destination_family: 2 });
+        assert_eq!(QueueHandoff::from_ids(false, 0, 2, true), QueueHandoff::Semaphore);
+    }
+
+    #[test]
+    fn handoff_steps() {
+        assert_eq!(QueueHandoff::Barrier.steps(), &[HandoffStep::Barrier]);
+        assert_eq!(QueueHandoff::Semaphore.steps(), &[HandoffStep::Semaphore]);
+
+        let transfer = QueueHandoff::OwnershipTransfer { source_family: 0, destination_family: 2 };
+        assert_eq!(transfer.steps(),
+                   &[HandoffStep::Release, HandoffStep::Semaphore, HandoffStep::Acquire]);
+    }
+
+    #[test]
+    fn same_queue_submits_barrier() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        let stages = PipelineStages { compute_shader: true, .. PipelineStages::none() };
+        let access = AccessFlagBits { shader_write: true, .. AccessFlagBits::none() };
+
+        let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+                                                                             .build().unwrap();
+        let future = now(device).then_execute(queue.clone(), cb).unwrap();
+
+        let future = future.then_handoff_buffer(buffer, stages, access, queue.clone(), stages,
+                                                access).unwrap();
+
+        // The barrier is submitted on the same queue, without any semaphore.
+        assert!(future.queue().unwrap().is_same(&queue));
+        match unsafe { future.build_submission() }.unwrap() {
+            SubmitAnyBuilder::CommandBuffer(_) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn same_queue_object() {
+        let (_, queue) = gfx_dev_and_queue!();
+        assert_eq!(QueueHandoff::new(&queue, &queue, false), QueueHandoff::Barrier);
+    }
+
+    #[test]
+    fn unknown_producer_queue() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(),
+                                                    Some(queue.family()), 0u32).unwrap();
+
+        let stages = PipelineStages { compute_shader: true, .. PipelineStages::none() };
+        let access = AccessFlagBits { shader_write: true, .. AccessFlagBits::none() };
+
+        match now(device).then_handoff_buffer(buffer, stages, access, queue, stages, access) {
+            Err(QueueHandoffError::UnknownProducerQueue) => (),
+            _ => panic!()
+        }
+    }
+}
@@ -10,6 +10,7 @@
 use std::error;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 
 use buffer::BufferAccess;
 use command_buffer::CommandBuffer;
@@ -24,6 +25,8 @@ use image::ImageAccess;
 use image::ImageLayout;
 use swapchain;
 use swapchain::Swapchain;
+use swapchain::MultiPresentFuture;
+use swapchain::PresentError;
 use swapchain::PresentFuture;
 use sync::AccessFlagBits;
 use sync::FenceWaitError;
@@ -33,10 +36,12 @@ use OomError;
 pub use self::now::{now, NowFuture};
 pub use self::fence_signal::{FenceSignalFuture, FenceSignalFutureBehavior};
 pub use self::join::JoinFuture;
+pub use self::handoff::{QueueHandoff, QueueHandoffError};
 pub use self::semaphore_signal::SemaphoreSignalFuture;
 
 mod now;
 mod fence_signal;
+mod handoff;
 mod join;
 mod semaphore_signal;
 
@@ -215,6 +220,64 @@ pub unsafe trait GpuFuture: DeviceOwned {
         Ok(f)
     }
 
+    /// Makes the content of `buffer`, written by this future on its queue, available to the
+    /// operations that are going to be submitted after the returned future on `queue`.
+    ///
+    /// `src_stages` and `src_access` describe how this future wrote the buffer, and `dst_stages`
+    /// and `dst_access` describe how the operations submitted on `queue` are going to use it.
+    ///
+    /// Depending on the queues and on the sharing mode of the buffer, this inserts a pipeline
+    /// barrier, a semaphore, or a semaphore surrounded with a queue family ownership transfer.
+    /// See the documentation of `QueueHandoff` for more information.
+    ///
+    /// Returns an error if `queue()` returns `None`.
+    #[inline]
+    fn then_handoff_buffer<B>(self, buffer: B, src_stages: PipelineStages,
+                              src_access: AccessFlagBits, queue: Arc<Queue>,
+                              dst_stages: PipelineStages, dst_access: AccessFlagBits)
+                              -> Result<Box<GpuFuture>, QueueHandoffError>
+        where Self: Sized + 'static, B: BufferAccess + Send + Sync + Clone + 'static
+    {
+        handoff::then_handoff_buffer(self, buffer, src_stages, src_access, queue, dst_stages,
+                                     dst_access)
+    }
+
+    /// Same as `then_handoff_buffer`, but for an image. The layout of the image is left unchanged.
+    #[inline]
+    fn then_handoff_image<I>(self, image: I, src_stages: PipelineStages,
+                             src_access: AccessFlagBits, queue: Arc<Queue>,
+                             dst_stages: PipelineStages, dst_access: AccessFlagBits)
+                             -> Result<Box<GpuFuture>, QueueHandoffError>
+        where Self: Sized + 'static, I: ImageAccess + Send + Sync + Clone + 'static
+    {
+        handoff::then_handoff_image(self, image, src_stages, src_access, queue, dst_stages,
+                                    dst_access)
+    }
+
+    /// Signals a fence after this future, flushes it, and blocks the current thread until the
+    /// GPU has finished executing it.
+    ///
+    /// This is a shortcut for `then_signal_fence_and_flush()` followed with `wait()`. The fence
+    /// is taken from the fence pool of the device and returned to it once signaled.
+    ///
+    /// If `timeout` is `None`, then the wait is infinite. If the timeout elapses before the GPU
+    /// has finished, the future is returned in a `FlushAndWaitError::Timeout` so that you can
+    /// wait on it again later. Dropping it blocks until the submission is finished.
+    fn flush_and_wait(self, timeout: Option<Duration>) -> Result<(), FlushAndWaitError<Self>>
+        where Self: Sized
+    {
+        let future = match self.then_signal_fence_and_flush() {
+            Ok(f) => f,
+            Err(err) => return Err(FlushAndWaitError::FlushError(err)),
+        };
+
+        match future.wait(timeout) {
+            Ok(()) => Ok(()),
+            Err(FlushError::Timeout) => Err(FlushAndWaitError::Timeout(future)),
+            Err(err) => Err(FlushAndWaitError::FlushError(err)),
+        }
+    }
+
     /// Presents a swapchain image after this future.
     ///
     /// You should only ever do this indirectly after a `SwapchainAcquireFuture` of the same image,
@@ -223,11 +286,22 @@ pub unsafe trait GpuFuture: DeviceOwned {
     /// > **Note**: This is just a shortcut for the `Swapchain::present()` function.
     #[inline]
     fn then_swapchain_present(self, queue: Arc<Queue>, swapchain: Arc<Swapchain>,
-                              image_index: usize) -> PresentFuture<Self>
+                              image_index: usize) -> Result<PresentFuture<Self>, PresentError>
         where Self: Sized
     {
         swapchain::present(swapchain, self, queue, image_index)
     }
+
+    /// Presents images of multiple swapchains after this future, with a single submission.
+    ///
+    /// > **Note**: This is just a shortcut for the `swapchain::present_multiple()` function.
+    #[inline]
+    fn then_swapchains_present(self, queue: Arc<Queue>, swapchains: Vec<(Arc<Swapchain>, usize)>)
+                               -> Result<MultiPresentFuture<Self>, PresentError>
+        where Self: Sized
+    {
+        swapchain::present_multiple(swapchains, self, queue)
+    }
 }
 
 unsafe impl<F: ?Sized> GpuFuture for Box<F> where F: GpuFuture {
@@ -458,6 +532,55 @@ impl From<SubmitCommandBufferError> for FlushError {
     }
 }
 
+/// Error that can happen when calling `flush_and_wait()`.
+pub enum FlushAndWaitError<F> where F: GpuFuture {
+    /// Error while flushing or waiting. `DeviceLost` is returned if the device was lost while
+    /// waiting.
+    FlushError(FlushError),
+
+    /// The timeout has elapsed before the GPU finished executing the submission. Contains the
+    /// future, which can be waited upon again.
+    Timeout(FenceSignalFuture<F>),
+}
+
+impl<F> error::Error for FlushAndWaitError<F> where F: GpuFuture {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            FlushAndWaitError::FlushError(_) => "error while flushing or waiting",
+            FlushAndWaitError::Timeout(_) => {
+                "the timeout has elapsed before the submission was finished"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            FlushAndWaitError::FlushError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl<F> fmt::Display for FlushAndWaitError<F> where F: GpuFuture {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl<F> fmt::Debug for FlushAndWaitError<F> where F: GpuFuture {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            FlushAndWaitError::FlushError(ref err) => {
+                fmt.debug_tuple("FlushError").field(err).finish()
+            },
+            FlushAndWaitError::Timeout(_) => write!(fmt, "Timeout"),
+        }
+    }
+}
+
 impl From<FenceWaitError> for FlushError {
     #[inline]
     fn from(err: FenceWaitError) -> FlushError {
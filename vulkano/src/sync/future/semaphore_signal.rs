@@ -17,6 +17,7 @@ use command_buffer::submit::SubmitAnyBuilder;
 use command_buffer::submit::SubmitCommandBufferBuilder;
 use command_buffer::submit::SubmitSemaphoresWaitBuilder;
 use device::Device;
+use device::DeviceWaitError;
 use device::DeviceOwned;
 use device::Queue;
 use image::ImageAccess;
@@ -37,7 +38,7 @@ pub fn then_signal_semaphore<F>(future: F) -> SemaphoreSignalFuture<F> where F:
 
     SemaphoreSignalFuture {
         previous: future,
-        semaphore: Semaphore::new(device).unwrap(),
+        semaphore: Semaphore::from_pool(device).unwrap(),
         wait_submitted: Mutex::new(false),
         finished: AtomicBool::new(false),
     }
@@ -154,11 +155,23 @@ impl<F> Drop for SemaphoreSignalFuture<F> where F: GpuFuture {
     fn drop(&mut self) {
         unsafe {
             if !*self.finished.get_mut() {
-                // TODO: handle errors?
-                self.flush().unwrap();
+                // If the device has been lost, nothing is executing anymore and there is no need
+                // to wait.
+                // TODO: handle other errors?
+                match self.flush() {
+                    Ok(()) | Err(FlushError::DeviceLost) => (),
+                    Err(err) => panic!("{:?}", err),
+                }
                 // Block until the queue finished.
-                self.queue().unwrap().wait().unwrap();
+                // Note that this returns immediately if the device has been lost.
+                match self.queue().unwrap().wait() {
+                    Ok(()) | Err(DeviceWaitError::DeviceLost) => (),
+                    Err(err) => panic!("{:?}", err),
+                }
                 self.previous.signal_finished();
+                // Nothing guarantees that the semaphore has been waited upon, in which case it
+                // stays signaled and can't be reused.
+                self.semaphore.disable_recycling();
             }
         }
     }
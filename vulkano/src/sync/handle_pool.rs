@@ -0,0 +1,73 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+// List of Vulkan handles that are reused instead of being destroyed.
+//
+// This is the common implementation of `FencePool` and `SemaphorePool`. It doesn't know how to
+// create or destroy the handles, this is the job of the users of the pool.
+pub(crate) struct HandlePool<H> {
+    // Handles that are not in use and that can be handed out.
+    available: Mutex<Vec<H>>,
+    // Total number of handles that have been created for this pool.
+    num_created: AtomicUsize,
+}
+
+impl<H> HandlePool<H> {
+    // Builds a new empty pool.
+    #[inline]
+    pub fn new() -> HandlePool<H> {
+        HandlePool {
+            available: Mutex::new(Vec::new()),
+            num_created: AtomicUsize::new(0),
+        }
+    }
+
+    // Returns the total number of handles that have been created for this pool.
+    #[inline]
+    pub fn num_created(&self) -> usize {
+        self.num_created.load(Ordering::Relaxed)
+    }
+
+    // Returns the number of handles that are currently unused and waiting in the pool.
+    #[inline]
+    pub fn num_available(&self) -> usize {
+        self.available.lock().unwrap().len()
+    }
+
+    // Takes an unused handle from the pool, if there is one.
+    #[inline]
+    pub fn take(&self) -> Option<H> {
+        self.available.lock().unwrap().pop()
+    }
+
+    // Must be called whenever a handle has been created for this pool.
+    #[inline]
+    pub fn record_creation(&self) {
+        self.num_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Puts back a handle in the pool.
+    #[inline]
+    pub fn recycle(&self, handle: H) {
+        self.available.lock().unwrap().push(handle);
+    }
+
+    // Removes all the handles from the pool and passes them to `destroy`.
+    pub fn destroy_all<F>(&self, mut destroy: F)
+        where F: FnMut(H)
+    {
+        for handle in self.available.lock().unwrap().drain(..) {
+            destroy(handle);
+        }
+    }
+}
@@ -109,6 +109,8 @@ use device::Queue;
 pub use self::event::Event;
 pub use self::fence::Fence;
 pub use self::fence::FenceWaitError;
+pub use self::fence_pool::FencePool;
+pub use self::frames_in_flight::FramesInFlight;
 pub use self::future::now;
 pub use self::future::NowFuture;
 pub use self::future::GpuFuture;
@@ -118,15 +120,28 @@ pub use self::future::JoinFuture;
 pub use self::future::AccessError;
 pub use self::future::AccessCheckError;
 pub use self::future::FlushError;
+pub use self::future::FlushAndWaitError;
+pub use self::future::QueueHandoff;
+pub use self::future::QueueHandoffError;
 pub use self::pipeline::AccessFlagBits;
 pub use self::pipeline::PipelineStages;
+pub use self::progress::ProgressCounter;
+pub use self::semaphore::ExternalSemaphoreError;
+pub use self::semaphore::ExternalSemaphoreHandleType;
+pub use self::semaphore::ExternalSemaphoreProperties;
 pub use self::semaphore::Semaphore;
+pub use self::semaphore_pool::SemaphorePool;
 
 mod event;
 mod fence;
+mod fence_pool;
+mod frames_in_flight;
 mod future;
+mod handle_pool;
 mod pipeline;
+mod progress;
 mod semaphore;
+mod semaphore_pool;
 
 /// Declares in which queue(s) a resource can be used.
 ///
@@ -7,17 +7,30 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::fmt;
 use std::ops;
+
+use image::ImageLayout;
 use vk;
 
 macro_rules! pipeline_stages {
-    ($($elem:ident => $val:expr,)+) => (
-        #[derive(Debug, Copy, Clone)]
+    ($($elem:ident => $val:expr,)+ ; $($ext_elem:ident => $ext_val:expr,)*) => (
+        /// List of pipeline stages.
+        ///
+        /// Each field corresponds to a stage of the pipeline. The struct can be converted to and
+        /// from the raw Vulkan bitmask, and combined with the `|` and `&` operators.
+        ///
+        /// The stages that are provided by device extensions, such as `conditional_rendering`,
+        /// can only be used if the corresponding extension is enabled on the device.
+        #[derive(Copy, Clone, PartialEq, Eq)]
         #[allow(missing_docs)]
         pub struct PipelineStages {
             $(
                 pub $elem: bool,
             )+
+            $(
+                pub $ext_elem: bool,
+            )*
         }
 
         impl PipelineStages {
@@ -27,10 +40,72 @@ macro_rules! pipeline_stages {
                     $(
                         $elem: false,
                     )+
+                    $(
+                        $ext_elem: false,
+                    )*
+                }
+            }
+
+            /// Returns true if none of the stages are set.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                $(
+                    !self.$elem &&
+                )+
+                $(
+                    !self.$ext_elem &&
+                )* true
+            }
+
+            /// Returns the stages that are set in `self` or in `other`.
+            #[inline]
+            pub fn union(&self, other: &PipelineStages) -> PipelineStages {
+                PipelineStages {
+                    $(
+                        $elem: self.$elem || other.$elem,
+                    )+
+                    $(
+                        $ext_elem: self.$ext_elem || other.$ext_elem,
+                    )*
+                }
+            }
+
+            /// Returns the stages that are set in both `self` and `other`.
+            #[inline]
+            pub fn intersection(&self, other: &PipelineStages) -> PipelineStages {
+                PipelineStages {
+                    $(
+                        $elem: self.$elem && other.$elem,
+                    )+
+                    $(
+                        $ext_elem: self.$ext_elem && other.$ext_elem,
+                    )*
                 }
             }
         }
 
+        impl fmt::Debug for PipelineStages {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                try!(write!(fmt, "PipelineStages {{"));
+                let mut first = true;
+                $(
+                    if self.$elem {
+                        try!(write!(fmt, "{}{}", if first { " " } else { " | " },
+                                    stringify!($elem)));
+                        first = false;
+                    }
+                )+
+                $(
+                    if self.$ext_elem {
+                        try!(write!(fmt, "{}{}", if first { " " } else { " | " },
+                                    stringify!($ext_elem)));
+                        first = false;
+                    }
+                )*
+                write!(fmt, "{}}}", if first { "" } else { " " })
+            }
+        }
+
         impl ops::BitOr for PipelineStages {
             type Output = PipelineStages;
 
@@ -40,6 +115,9 @@ macro_rules! pipeline_stages {
                     $(
                         $elem: self.$elem || rhs.$elem,
                     )+
+                    $(
+                        $ext_elem: self.$ext_elem || rhs.$ext_elem,
+                    )*
                 }
             }
         }
@@ -50,6 +128,25 @@ macro_rules! pipeline_stages {
                 $(
                     self.$elem = self.$elem || rhs.$elem;
                 )+
+                $(
+                    self.$ext_elem = self.$ext_elem || rhs.$ext_elem;
+                )*
+            }
+        }
+
+        impl ops::BitAnd for PipelineStages {
+            type Output = PipelineStages;
+
+            #[inline]
+            fn bitand(self, rhs: PipelineStages) -> PipelineStages {
+                self.intersection(&rhs)
+            }
+        }
+
+        impl ops::BitAndAssign for PipelineStages {
+            #[inline]
+            fn bitand_assign(&mut self, rhs: PipelineStages) {
+                *self = self.intersection(&rhs);
             }
         }
 
@@ -61,9 +158,27 @@ macro_rules! pipeline_stages {
                 $(
                     if self.$elem { result |= $val }
                 )+
+                $(
+                    if self.$ext_elem { result |= $ext_val }
+                )*
                 result
             }
         }
+
+        #[doc(hidden)]
+        impl From<vk::PipelineStageFlagBits> for PipelineStages {
+            #[inline]
+            fn from(val: vk::PipelineStageFlagBits) -> PipelineStages {
+                PipelineStages {
+                    $(
+                        $elem: (val & $val) != 0,
+                    )+
+                    $(
+                        $ext_elem: (val & $ext_val) != 0,
+                    )*
+                }
+            }
+        }
     );
 }
 
@@ -85,25 +200,56 @@ pipeline_stages!{
     host => vk::PIPELINE_STAGE_HOST_BIT,
     all_graphics => vk::PIPELINE_STAGE_ALL_GRAPHICS_BIT,
     all_commands => vk::PIPELINE_STAGE_ALL_COMMANDS_BIT,
+    ;
+    // Stages provided by device extensions.
+    conditional_rendering => vk::PIPELINE_STAGE_CONDITIONAL_RENDERING_BIT_EXT,
+}
+
+impl PipelineStages {
+    /// Builds a `PipelineStages` struct with only the `all_commands` stage set.
+    ///
+    /// This covers every stage of every command, and is the most conservative value to use when
+    /// the exact stages are unknown.
+    #[inline]
+    pub fn all_commands() -> PipelineStages {
+        PipelineStages {
+            all_commands: true,
+            .. PipelineStages::none()
+        }
+    }
 }
 
 macro_rules! access_flags {
-    ($($elem:ident => $val:expr,)+) => (
-        #[derive(Debug, Copy, Clone)]
+    ($($elem:ident => $val:expr,)+ ; $($ext_elem:ident => $ext_val:expr,)*) => (
+        /// List of memory accesses.
+        ///
+        /// Used with `PipelineStages` to describe the memory dependencies of a barrier. The struct
+        /// can be converted to and from the raw Vulkan bitmask, and combined with the `|` and `&`
+        /// operators.
+        #[derive(Copy, Clone, PartialEq, Eq)]
         #[allow(missing_docs)]
         pub struct AccessFlagBits {
             $(
                 pub $elem: bool,
             )+
+            $(
+                pub $ext_elem: bool,
+            )*
         }
 
         impl AccessFlagBits {
-            /// Builds an `AccessFlagBits` struct with all bits set.
+            /// Builds an `AccessFlagBits` struct with all the core bits set.
+            ///
+            /// The bits that are provided by device extensions, such as
+            /// `conditional_rendering_read`, are not set.
             pub fn all() -> AccessFlagBits {
                 AccessFlagBits {
                     $(
                         $elem: true,
                     )+
+                    $(
+                        $ext_elem: false,
+                    )*
                 }
             }
 
@@ -113,10 +259,72 @@ macro_rules! access_flags {
                     $(
                         $elem: false,
                     )+
+                    $(
+                        $ext_elem: false,
+                    )*
+                }
+            }
+
+            /// Returns true if none of the bits are set.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                $(
+                    !self.$elem &&
+                )+
+                $(
+                    !self.$ext_elem &&
+                )* true
+            }
+
+            /// Returns the bits that are set in `self` or in `other`.
+            #[inline]
+            pub fn union(&self, other: &AccessFlagBits) -> AccessFlagBits {
+                AccessFlagBits {
+                    $(
+                        $elem: self.$elem || other.$elem,
+                    )+
+                    $(
+                        $ext_elem: self.$ext_elem || other.$ext_elem,
+                    )*
+                }
+            }
+
+            /// Returns the bits that are set in both `self` and `other`.
+            #[inline]
+            pub fn intersection(&self, other: &AccessFlagBits) -> AccessFlagBits {
+                AccessFlagBits {
+                    $(
+                        $elem: self.$elem && other.$elem,
+                    )+
+                    $(
+                        $ext_elem: self.$ext_elem && other.$ext_elem,
+                    )*
                 }
             }
         }
 
+        impl fmt::Debug for AccessFlagBits {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                try!(write!(fmt, "AccessFlagBits {{"));
+                let mut first = true;
+                $(
+                    if self.$elem {
+                        try!(write!(fmt, "{}{}", if first { " " } else { " | " },
+                                    stringify!($elem)));
+                        first = false;
+                    }
+                )+
+                $(
+                    if self.$ext_elem {
+                        try!(write!(fmt, "{}{}", if first { " " } else { " | " },
+                                    stringify!($ext_elem)));
+                        first = false;
+                    }
+                )*
+                write!(fmt, "{}}}", if first { "" } else { " " })
+            }
+        }
+
         impl ops::BitOr for AccessFlagBits {
             type Output = AccessFlagBits;
 
@@ -126,6 +334,9 @@ macro_rules! access_flags {
                     $(
                         $elem: self.$elem || rhs.$elem,
                     )+
+                    $(
+                        $ext_elem: self.$ext_elem || rhs.$ext_elem,
+                    )*
                 }
             }
         }
@@ -136,6 +347,9 @@ macro_rules! access_flags {
                 $(
                     self.$elem = self.$elem || rhs.$elem;
                 )+
+                $(
+                    self.$ext_elem = self.$ext_elem || rhs.$ext_elem;
+                )*
             }
         }
 
@@ -147,9 +361,43 @@ macro_rules! access_flags {
                 $(
                     if self.$elem { result |= $val }
                 )+
+                $(
+                    if self.$ext_elem { result |= $ext_val }
+                )*
                 result
             }
         }
+
+        impl ops::BitAnd for AccessFlagBits {
+            type Output = AccessFlagBits;
+
+            #[inline]
+            fn bitand(self, rhs: AccessFlagBits) -> AccessFlagBits {
+                self.intersection(&rhs)
+            }
+        }
+
+        impl ops::BitAndAssign for AccessFlagBits {
+            #[inline]
+            fn bitand_assign(&mut self, rhs: AccessFlagBits) {
+                *self = self.intersection(&rhs);
+            }
+        }
+
+        #[doc(hidden)]
+        impl From<vk::AccessFlagBits> for AccessFlagBits {
+            #[inline]
+            fn from(val: vk::AccessFlagBits) -> AccessFlagBits {
+                AccessFlagBits {
+                    $(
+                        $elem: (val & $val) != 0,
+                    )+
+                    $(
+                        $ext_elem: (val & $ext_val) != 0,
+                    )*
+                }
+            }
+        }
     );
 }
 
@@ -171,4 +419,157 @@ access_flags!{
     host_write => vk::ACCESS_HOST_WRITE_BIT,
     memory_read => vk::ACCESS_MEMORY_READ_BIT,
     memory_write => vk::ACCESS_MEMORY_WRITE_BIT,
+    ;
+    // Bits provided by device extensions.
+    conditional_rendering_read => vk::ACCESS_CONDITIONAL_RENDERING_READ_BIT_EXT,
+}
+
+impl AccessFlagBits {
+    /// Returns the accesses that are usually performed on an image that is in `layout`.
+    ///
+    /// For example an image in the `TransferDstOptimal` layout is usually written by transfer
+    /// commands, so this returns `transfer_write`.
+    pub fn for_layout(layout: ImageLayout) -> AccessFlagBits {
+        match layout {
+            ImageLayout::Undefined | ImageLayout::PresentSrc => AccessFlagBits::none(),
+            ImageLayout::General => AccessFlagBits {
+                memory_read: true,
+                memory_write: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::ColorAttachmentOptimal => AccessFlagBits {
+                color_attachment_read: true,
+                color_attachment_write: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::DepthStencilAttachmentOptimal => AccessFlagBits {
+                depth_stencil_attachment_read: true,
+                depth_stencil_attachment_write: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::DepthStencilReadOnlyOptimal => AccessFlagBits {
+                depth_stencil_attachment_read: true,
+                shader_read: true,
+                input_attachment_read: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::ShaderReadOnlyOptimal => AccessFlagBits {
+                shader_read: true,
+                input_attachment_read: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::TransferSrcOptimal => AccessFlagBits {
+                transfer_read: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::TransferDstOptimal => AccessFlagBits {
+                transfer_write: true,
+                .. AccessFlagBits::none()
+            },
+            ImageLayout::Preinitialized => AccessFlagBits {
+                host_write: true,
+                .. AccessFlagBits::none()
+            },
+        }
+    }
+
+    /// Returns the default source and destination accesses of a barrier that transitions an
+    /// image from `before` to `after`.
+    ///
+    /// The source accesses are the writes that are usually performed in the `before` layout, as
+    /// reads don't need to be made available. The destination accesses are all the accesses that
+    /// are usually performed in the `after` layout.
+    #[inline]
+    pub fn for_layout_transition(before: ImageLayout, after: ImageLayout)
+                                 -> (AccessFlagBits, AccessFlagBits)
+    {
+        let src = AccessFlagBits::for_layout(before).intersection(&AccessFlagBits::writes());
+        let dst = AccessFlagBits::for_layout(after);
+        (src, dst)
+    }
+
+    // Returns all the bits that correspond to a write.
+    #[inline]
+    fn writes() -> AccessFlagBits {
+        AccessFlagBits {
+            shader_write: true,
+            color_attachment_write: true,
+            depth_stencil_attachment_write: true,
+            transfer_write: true,
+            host_write: true,
+            memory_write: true,
+            .. AccessFlagBits::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::ImageLayout;
+    use sync::AccessFlagBits;
+    use sync::PipelineStages;
+    use vk;
+
+    #[test]
+    fn vk_round_trip() {
+        let stages = PipelineStages {
+            vertex_shader: true,
+            transfer: true,
+            .. PipelineStages::none()
+        };
+
+        let bits: vk::PipelineStageFlagBits = stages.into();
+        assert_eq!(bits, vk::PIPELINE_STAGE_VERTEX_SHADER_BIT | vk::PIPELINE_STAGE_TRANSFER_BIT);
+        assert_eq!(PipelineStages::from(bits), stages);
+    }
+
+    #[test]
+    fn union_intersection() {
+        let a = PipelineStages { vertex_shader: true, transfer: true, .. PipelineStages::none() };
+        let b = PipelineStages { transfer: true, host: true, .. PipelineStages::none() };
+
+        assert_eq!(a.union(&b), a | b);
+        assert_eq!(a.intersection(&b), PipelineStages { transfer: true, .. PipelineStages::none() });
+        assert_eq!(a & b, a.intersection(&b));
+        assert!(PipelineStages::none().is_empty());
+        assert!(!PipelineStages::all_commands().is_empty());
+    }
+
+    #[test]
+    fn debug_only_set_stages() {
+        let stages = PipelineStages { vertex_shader: true, transfer: true, .. PipelineStages::none() };
+        assert_eq!(format!("{:?}", stages), "PipelineStages { vertex_shader | transfer }");
+        assert_eq!(format!("{:?}", PipelineStages::none()), "PipelineStages {}");
+    }
+
+    #[test]
+    fn access_vk_round_trip() {
+        let access = AccessFlagBits {
+            shader_read: true,
+            conditional_rendering_read: true,
+            .. AccessFlagBits::none()
+        };
+
+        let bits: vk::AccessFlagBits = access.into();
+        assert_eq!(bits, vk::ACCESS_SHADER_READ_BIT | vk::ACCESS_CONDITIONAL_RENDERING_READ_BIT_EXT);
+        assert_eq!(AccessFlagBits::from(bits), access);
+        assert_eq!(format!("{:?}", access),
+                   "AccessFlagBits { shader_read | conditional_rendering_read }");
+    }
+
+    #[test]
+    fn layout_transition_access() {
+        let (src, dst) = AccessFlagBits::for_layout_transition(ImageLayout::TransferDstOptimal,
+                                                              ImageLayout::ShaderReadOnlyOptimal);
+        assert_eq!(src, AccessFlagBits { transfer_write: true, .. AccessFlagBits::none() });
+        assert_eq!(dst, AccessFlagBits {
+            shader_read: true,
+            input_attachment_read: true,
+            .. AccessFlagBits::none()
+        });
+
+        let (src, _) = AccessFlagBits::for_layout_transition(ImageLayout::TransferSrcOptimal,
+                                                            ImageLayout::General);
+        assert!(src.is_empty());
+    }
 }
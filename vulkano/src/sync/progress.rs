@@ -0,0 +1,193 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::mem;
+use std::sync::Arc;
+
+use buffer::BufferUsage;
+use buffer::CpuAccessibleBuffer;
+use device::Device;
+use instance::QueueFamily;
+use memory::MemoryAllocError;
+use memory::MemoryPreference;
+use memory::MemoryProperties;
+use sync::Event;
+use OomError;
+
+/// Helper that reports the progress of a long GPU job to the CPU without waiting on a fence.
+///
+/// A `ProgressCounter` holds a buffer that contains a single `u32`, and optionally one event per
+/// chunk of the job. The buffer is meant to be bound as a storage buffer and incremented by the
+/// shader with an atomic operation, for example once per workgroup. The events are meant to be
+/// set by the command buffer after each chunk, with `set_event`. The CPU can then look at
+/// `poll_progress()` or `chunks_done()` at any time, for example to update a progress bar.
+///
+/// ```
+/// use vulkano::sync::ProgressCounter;
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+/// let progress = ProgressCounter::new(device.clone(), Some(queue.family()), 16).unwrap();
+/// // Bind `progress.counter()` to the shader, and after each chunk `i` of the job record
+/// // `set_event(progress.chunk_event(i).clone(), ...)`.
+/// // ...
+/// println!("{} workgroups and {} chunks done", progress.poll_progress(),
+///          progress.chunks_done().unwrap());
+/// ```
+///
+/// # Memory coherence
+///
+/// The counter is allocated in host-visible and host-coherent memory, so that the CPU doesn't
+/// need to invalidate the mapped range before reading it. However, Vulkan only guarantees that
+/// the writes of a shader are visible to the host after a memory dependency whose destination is
+/// the host, such as a fence wait or a pipeline barrier with `host_read` access. The values read
+/// while the job is running are therefore only a hint: in practice they lag slightly behind the
+/// GPU, but they must not be used to decide whether the results of the job can be read.
+///
+/// The shader must increment the counter with an atomic operation, as workgroups run in
+/// parallel. Because host-coherent memory is often uncached, reading the counter is slow and it
+/// shouldn't be polled in a tight loop.
+///
+/// The counter and the events are never reset. Create a new `ProgressCounter` for each job, or
+/// reset the counter manually with `fill_buffer` at the start of the job.
+#[derive(Debug)]
+pub struct ProgressCounter {
+    // Buffer incremented by the shader.
+    counter: Arc<CpuAccessibleBuffer<u32>>,
+    // One event per chunk of the job.
+    events: Vec<Arc<Event>>,
+}
+
+impl ProgressCounter {
+    /// Builds a new `ProgressCounter` for a job split into `chunks` chunks.
+    ///
+    /// The counter is usable from the given queue families. `chunks` can be 0 if you don't need
+    /// the events. Returns `NoSuitableMemoryType` if no host-visible and host-coherent memory
+    /// type supports the buffer.
+    pub fn new<'a, I>(device: Arc<Device>, queue_families: I, chunks: usize)
+                      -> Result<ProgressCounter, MemoryAllocError>
+        where I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        let usage = BufferUsage {
+            storage_buffer: true,
+            transfer_dest: true,
+            .. BufferUsage::none()
+        };
+
+        let preference = MemoryPreference {
+            required: MemoryProperties {
+                host_visible: true,
+                host_coherent: true,
+                .. MemoryProperties::none()
+            },
+            preferred: MemoryProperties::none(),
+        };
+
+        let counter = unsafe {
+            try!(CpuAccessibleBuffer::raw_with_memory_preference(device.clone(),
+                                                                 mem::size_of::<u32>(), usage,
+                                                                 queue_families, preference))
+        };
+
+        // The buffer isn't used by the GPU yet, therefore it can't be locked.
+        *counter.write().unwrap() = 0;
+
+        let mut events = Vec::with_capacity(chunks);
+        for _ in 0 .. chunks {
+            events.push(Arc::new(try!(Event::raw(device.clone()))));
+        }
+
+        Ok(ProgressCounter {
+            counter: counter,
+            events: events,
+        })
+    }
+
+    /// Returns the buffer that the shader must increment.
+    #[inline]
+    pub fn counter(&self) -> &Arc<CpuAccessibleBuffer<u32>> {
+        &self.counter
+    }
+
+    /// Returns the number of chunks of the job.
+    #[inline]
+    pub fn num_chunks(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns the event that must be set after the chunk `chunk` of the job.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `chunk` is out of range.
+    ///
+    #[inline]
+    pub fn chunk_event(&self, chunk: usize) -> &Arc<Event> {
+        &self.events[chunk]
+    }
+
+    /// Returns the current value of the counter, without blocking.
+    ///
+    /// See the documentation of `ProgressCounter` for the guarantees about this value. The
+    /// counter is read with a volatile read and without locking it, so this never fails even if
+    /// the counter is currently used by the GPU.
+    #[inline]
+    pub fn poll_progress(&self) -> u32 {
+        // The memory of the counter is host-coherent, see `new`.
+        unsafe { self.counter.read_volatile() }
+    }
+
+    /// Returns the number of chunks whose event is set, without blocking.
+    ///
+    /// Contrary to the counter, an event that is seen as set guarantees that the commands
+    /// recorded before `set_event` have finished executing the stages passed to it.
+    pub fn chunks_done(&self) -> Result<usize, OomError> {
+        let mut done = 0;
+        for event in self.events.iter() {
+            if try!(event.signaled()) {
+                done += 1;
+            }
+        }
+        Ok(done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sync::ProgressCounter;
+
+    #[test]
+    fn initial_state() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let progress = ProgressCounter::new(device, Some(queue.family()), 4).unwrap();
+        assert_eq!(progress.num_chunks(), 4);
+        assert_eq!(progress.poll_progress(), 0);
+        assert_eq!(progress.chunks_done().unwrap(), 0);
+    }
+
+    #[test]
+    fn host_written_counter() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let progress = ProgressCounter::new(device, Some(queue.family()), 0).unwrap();
+        *progress.counter().write().unwrap() = 12;
+        assert_eq!(progress.poll_progress(), 12);
+    }
+
+    #[test]
+    fn poll_while_locked() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let progress = ProgressCounter::new(device, Some(queue.family()), 0).unwrap();
+        let lock = progress.counter().write().unwrap();
+        assert_eq!(progress.poll_progress(), 0);
+        drop(lock);
+    }
+}
@@ -7,12 +7,19 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
 use std::mem;
+#[cfg(windows)]
+use std::os::raw::c_void;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
 use std::ptr;
 use std::sync::Arc;
 
 use device::Device;
 use device::DeviceOwned;
+use Error;
 use OomError;
 use SafeDeref;
 use VulkanObject;
@@ -27,13 +34,52 @@ use vk;
 pub struct Semaphore<D = Arc<Device>> where D: SafeDeref<Target = Device> {
     semaphore: vk::Semaphore,
     device: D,
+    // Handle type the semaphore can be exported as, if any.
+    export_handle_type: Option<ExternalSemaphoreHandleType>,
+    // If true, the semaphore is put back in the device's semaphore pool when destroyed.
+    pooled: bool,
 }
 
 impl<D> Semaphore<D> where D: SafeDeref<Target = Device> {
+    /// Takes a semaphore from the semaphore pool of the device, or builds a new one if the pool
+    /// is empty.
+    ///
+    /// The semaphore is returned to the pool when it is destroyed, provided that it is known to
+    /// be unsignaled at that point.
+    pub fn from_pool(device: D) -> Result<Semaphore<D>, OomError> {
+        let semaphore = match device.semaphore_pool().take() {
+            Some(semaphore) => semaphore,
+            None => {
+                let semaphore = try!(Semaphore::create_raw(&device));
+                device.semaphore_pool().record_creation();
+                semaphore
+            },
+        };
+
+        Ok(Semaphore {
+            device: device,
+            semaphore: semaphore,
+            export_handle_type: None,
+            pooled: true,
+        })
+    }
+
     /// Builds a new semaphore.
     #[inline]
     pub fn new(device: D) -> Result<Semaphore<D>, OomError> {
-        let semaphore = unsafe {
+        let semaphore = try!(Semaphore::create_raw(&device));
+
+        Ok(Semaphore {
+            device: device,
+            semaphore: semaphore,
+            export_handle_type: None,
+            pooled: false,
+        })
+    }
+
+    // Creates a new raw semaphore object.
+    fn create_raw(device: &D) -> Result<vk::Semaphore, OomError> {
+        unsafe {
             // since the creation is constant, we use a `static` instead of a struct on the stack
             static mut INFOS: vk::SemaphoreCreateInfo = vk::SemaphoreCreateInfo {
                 sType: vk::STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
@@ -45,14 +91,402 @@ impl<D> Semaphore<D> where D: SafeDeref<Target = Device> {
             let mut output = mem::uninitialized();
             try!(check_errors(vk.CreateSemaphore(device.internal_object(), &INFOS,
                                                  ptr::null(), &mut output)));
+            Ok(output)
+        }
+    }
+
+    /// Prevents the semaphore from being returned to the pool when it is destroyed.
+    ///
+    /// Must be called if the semaphore may be signaled or have pending operations when it is
+    /// destroyed.
+    #[inline]
+    pub(crate) fn disable_recycling(&mut self) {
+        self.pooled = false;
+    }
+
+    /// Builds a new semaphore whose payload can be exported as an operating system handle of
+    /// the given type.
+    ///
+    /// This requires the `khr_external_semaphore` device extension, plus either
+    /// `khr_external_semaphore_fd` or `khr_external_semaphore_win32` depending on the handle
+    /// type. If the `khr_external_semaphore_capabilities` instance extension is enabled, the
+    /// handle type is also checked against what the physical device supports.
+    pub fn new_exportable(device: D, handle_type: ExternalSemaphoreHandleType)
+                          -> Result<Semaphore<D>, ExternalSemaphoreError>
+    {
+        try!(check_handle_type_extensions(&device, handle_type));
+
+        match device.physical_device().external_semaphore_properties(handle_type) {
+            Some(ref props) if !props.exportable => {
+                return Err(ExternalSemaphoreError::UnsupportedHandleType);
+            },
+            _ => ()
+        }
+
+        let semaphore = unsafe {
+            let export = vk::ExportSemaphoreCreateInfoKHR {
+                sType: vk::STRUCTURE_TYPE_EXPORT_SEMAPHORE_CREATE_INFO_KHR,
+                pNext: ptr::null(),
+                handleTypes: handle_type as u32,
+            };
+
+            let infos = vk::SemaphoreCreateInfo {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
+                pNext: &export as *const _ as *const _,
+                flags: 0,   // reserved
+            };
+
+            let vk = device.pointers();
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateSemaphore(device.internal_object(), &infos,
+                                                 ptr::null(), &mut output)));
             output
         };
 
         Ok(Semaphore {
             device: device,
             semaphore: semaphore,
+            export_handle_type: Some(handle_type),
+            pooled: false,
         })
     }
+
+    /// Returns the handle type this semaphore can be exported as, if it was created with
+    /// `new_exportable`.
+    #[inline]
+    pub fn export_handle_type(&self) -> Option<ExternalSemaphoreHandleType> {
+        self.export_handle_type
+    }
+
+    /// Exports the payload of the semaphore as a POSIX file descriptor.
+    ///
+    /// The semaphore must have been created with `new_exportable` and either the `OpaqueFd` or
+    /// the `SyncFd` handle type. The ownership of the returned file descriptor belongs to the
+    /// caller.
+    ///
+    /// Note that exporting a `SyncFd` requires the semaphore to be signaled, or to have a
+    /// signal operation pending, and resets the semaphore to the unsignaled state.
+    #[cfg(unix)]
+    pub fn export_fd(&self) -> Result<RawFd, ExternalSemaphoreError> {
+        let handle_type = match self.export_handle_type {
+            Some(ty @ ExternalSemaphoreHandleType::OpaqueFd) => ty,
+            Some(ty @ ExternalSemaphoreHandleType::SyncFd) => ty,
+            _ => return Err(ExternalSemaphoreError::NotExportable),
+        };
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::SemaphoreGetFdInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_GET_FD_INFO_KHR,
+                pNext: ptr::null(),
+                semaphore: self.semaphore,
+                handleType: handle_type as u32,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.GetSemaphoreFdKHR(self.device.internal_object(), &infos,
+                                                   &mut output)));
+            Ok(output)
+        }
+    }
+
+    /// Replaces the payload of the semaphore with the one referred to by a POSIX file
+    /// descriptor, for example one that has been exported by OpenGL or CUDA.
+    ///
+    /// If `temporary` is true, the imported payload is only used until the next wait operation
+    /// on the semaphore, after which the semaphore goes back to its original payload. `SyncFd`
+    /// handles can only be imported temporarily, and importing one with `temporary` set to false
+    /// returns `ExternalSemaphoreError::TemporaryImportRequired`.
+    ///
+    /// On success, the ownership of the file descriptor is transferred to the Vulkan
+    /// implementation, and you must not use or close it afterwards.
+    ///
+    /// # Safety
+    ///
+    /// - `fd` must be a valid file descriptor of type `handle_type`.
+    /// - The semaphore must not be in use by the GPU.
+    ///
+    #[cfg(unix)]
+    pub unsafe fn import_fd(&mut self, handle_type: ExternalSemaphoreHandleType, fd: RawFd,
+                            temporary: bool)
+                            -> Result<(), ExternalSemaphoreError>
+    {
+        match handle_type {
+            ExternalSemaphoreHandleType::OpaqueFd | ExternalSemaphoreHandleType::SyncFd => (),
+            _ => return Err(ExternalSemaphoreError::UnsupportedHandleType),
+        }
+
+        if handle_type == ExternalSemaphoreHandleType::SyncFd && !temporary {
+            return Err(ExternalSemaphoreError::TemporaryImportRequired);
+        }
+
+        try!(check_import_support(&self.device, handle_type));
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ImportSemaphoreFdInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_SEMAPHORE_FD_INFO_KHR,
+            pNext: ptr::null(),
+            semaphore: self.semaphore,
+            flags: if temporary { vk::SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR } else { 0 },
+            handleType: handle_type as u32,
+            fd: fd,
+        };
+
+        try!(check_errors(vk.ImportSemaphoreFdKHR(self.device.internal_object(), &infos)));
+        // The payload no longer belongs to the pool.
+        self.pooled = false;
+        Ok(())
+    }
+
+    /// Exports the payload of the semaphore as a Win32 handle.
+    ///
+    /// The semaphore must have been created with `new_exportable` and one of the Win32 handle
+    /// types. The ownership of the returned handle belongs to the caller, which must close it
+    /// with `CloseHandle` if it is an NT handle.
+    #[cfg(windows)]
+    pub fn export_win32_handle(&self) -> Result<*mut c_void, ExternalSemaphoreError> {
+        let handle_type = match self.export_handle_type {
+            Some(ty @ ExternalSemaphoreHandleType::OpaqueWin32) => ty,
+            Some(ty @ ExternalSemaphoreHandleType::OpaqueWin32Kmt) => ty,
+            Some(ty @ ExternalSemaphoreHandleType::D3d12Fence) => ty,
+            _ => return Err(ExternalSemaphoreError::NotExportable),
+        };
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::SemaphoreGetWin32HandleInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_GET_WIN32_HANDLE_INFO_KHR,
+                pNext: ptr::null(),
+                semaphore: self.semaphore,
+                handleType: handle_type as u32,
+            };
+
+            let mut output = ptr::null_mut();
+            try!(check_errors(vk.GetSemaphoreWin32HandleKHR(self.device.internal_object(),
+                                                            &infos, &mut output)));
+            Ok(output)
+        }
+    }
+
+    /// Replaces the payload of the semaphore with the one referred to by a Win32 handle.
+    ///
+    /// If `temporary` is true, the imported payload is only used until the next wait operation
+    /// on the semaphore. Contrary to file descriptors, importing a Win32 handle doesn't transfer
+    /// its ownership.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid handle of type `handle_type`.
+    /// - The semaphore must not be in use by the GPU.
+    ///
+    #[cfg(windows)]
+    pub unsafe fn import_win32_handle(&mut self, handle_type: ExternalSemaphoreHandleType,
+                                      handle: *mut c_void, temporary: bool)
+                                      -> Result<(), ExternalSemaphoreError>
+    {
+        match handle_type {
+            ExternalSemaphoreHandleType::OpaqueFd | ExternalSemaphoreHandleType::SyncFd => {
+                return Err(ExternalSemaphoreError::UnsupportedHandleType);
+            },
+            _ => ()
+        }
+
+        try!(check_import_support(&self.device, handle_type));
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ImportSemaphoreWin32HandleInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_SEMAPHORE_WIN32_HANDLE_INFO_KHR,
+            pNext: ptr::null(),
+            semaphore: self.semaphore,
+            flags: if temporary { vk::SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR } else { 0 },
+            handleType: handle_type as u32,
+            handle: handle,
+            name: ptr::null(),
+        };
+
+        try!(check_errors(vk.ImportSemaphoreWin32HandleKHR(self.device.internal_object(),
+                                                           &infos)));
+        // The payload no longer belongs to the pool.
+        self.pooled = false;
+        Ok(())
+    }
+}
+
+// Checks that the device extensions required by a handle type are enabled.
+fn check_handle_type_extensions(device: &Device, handle_type: ExternalSemaphoreHandleType)
+                                -> Result<(), ExternalSemaphoreError>
+{
+    let extensions = device.loaded_extensions();
+
+    if !extensions.khr_external_semaphore {
+        return Err(ExternalSemaphoreError::ExtensionNotEnabled);
+    }
+
+    let supported = match handle_type {
+        ExternalSemaphoreHandleType::OpaqueFd |
+        ExternalSemaphoreHandleType::SyncFd => extensions.khr_external_semaphore_fd,
+        _ => extensions.khr_external_semaphore_win32,
+    };
+
+    if !supported {
+        return Err(ExternalSemaphoreError::ExtensionNotEnabled);
+    }
+
+    Ok(())
+}
+
+// Checks that a handle of the given type can be imported into a semaphore of this device.
+fn check_import_support(device: &Device, handle_type: ExternalSemaphoreHandleType)
+                        -> Result<(), ExternalSemaphoreError>
+{
+    try!(check_handle_type_extensions(device, handle_type));
+
+    match device.physical_device().external_semaphore_properties(handle_type) {
+        Some(ref props) if !props.importable => {
+            Err(ExternalSemaphoreError::UnsupportedHandleType)
+        },
+        _ => Ok(())
+    }
+}
+
+/// Type of an operating system handle that refers to the payload of a semaphore.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ExternalSemaphoreHandleType {
+    /// A POSIX file descriptor whose only valid use is to be imported in a Vulkan implementation
+    /// (or in an API that supports this file descriptor type).
+    ///
+    /// Requires the `khr_external_semaphore_fd` device extension.
+    OpaqueFd = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR,
+
+    /// A Win32 NT handle whose only valid use is to be imported in a Vulkan implementation (or in
+    /// an API that supports this handle type).
+    ///
+    /// Requires the `khr_external_semaphore_win32` device extension.
+    OpaqueWin32 = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR,
+
+    /// Same as `OpaqueWin32`, but the handle is a global share handle instead of an NT handle.
+    OpaqueWin32Kmt = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR,
+
+    /// An NT handle returned by `ID3D12Device::CreateSharedHandle` for a Direct3D 12 fence.
+    D3d12Fence = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR,
+
+    /// A POSIX file descriptor for a Linux sync file. Can only be imported temporarily.
+    ///
+    /// Requires the `khr_external_semaphore_fd` device extension.
+    SyncFd = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR,
+}
+
+/// Capabilities of the implementation regarding a type of external semaphore handle.
+///
+/// Obtained by calling `PhysicalDevice::external_semaphore_properties`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalSemaphoreProperties {
+    /// A semaphore payload can be exported as a handle of this type.
+    pub exportable: bool,
+    /// A handle of this type can be imported into a semaphore.
+    pub importable: bool,
+}
+
+#[doc(hidden)]
+impl From<vk::ExternalSemaphorePropertiesKHR> for ExternalSemaphoreProperties {
+    #[inline]
+    fn from(props: vk::ExternalSemaphorePropertiesKHR) -> ExternalSemaphoreProperties {
+        let features = props.externalSemaphoreFeatures;
+        ExternalSemaphoreProperties {
+            exportable: (features & vk::EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR) != 0,
+            importable: (features & vk::EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR) != 0,
+        }
+    }
+}
+
+/// Error that can happen when exporting or importing the payload of a semaphore.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExternalSemaphoreError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The device extension required by the handle type was not enabled.
+    ExtensionNotEnabled,
+
+    /// The physical device doesn't support exporting or importing this handle type.
+    UnsupportedHandleType,
+
+    /// The semaphore was not created with the requested handle type as an export type.
+    NotExportable,
+
+    /// The handle to import is not valid, or is not of the given type.
+    InvalidExternalHandle,
+
+    /// The handle type can only be imported temporarily.
+    TemporaryImportRequired,
+
+    /// Too many objects have been created.
+    TooManyObjects,
+}
+
+impl error::Error for ExternalSemaphoreError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ExternalSemaphoreError::OomError(_) => "not enough memory available",
+            ExternalSemaphoreError::ExtensionNotEnabled => {
+                "the device extension required by the handle type was not enabled"
+            },
+            ExternalSemaphoreError::UnsupportedHandleType => {
+                "the physical device doesn't support exporting or importing this handle type"
+            },
+            ExternalSemaphoreError::NotExportable => {
+                "the semaphore was not created with the requested handle type as an export type"
+            },
+            ExternalSemaphoreError::InvalidExternalHandle => {
+                "the handle to import is not valid"
+            },
+            ExternalSemaphoreError::TemporaryImportRequired => {
+                "the handle type can only be imported temporarily"
+            },
+            ExternalSemaphoreError::TooManyObjects => "too many objects have been created",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ExternalSemaphoreError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ExternalSemaphoreError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ExternalSemaphoreError {
+    #[inline]
+    fn from(err: OomError) -> ExternalSemaphoreError {
+        ExternalSemaphoreError::OomError(err)
+    }
+}
+
+impl From<Error> for ExternalSemaphoreError {
+    #[inline]
+    fn from(err: Error) -> ExternalSemaphoreError {
+        match err {
+            err @ Error::OutOfHostMemory => ExternalSemaphoreError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => ExternalSemaphoreError::OomError(OomError::from(err)),
+            Error::InvalidExternalHandle => ExternalSemaphoreError::InvalidExternalHandle,
+            Error::TooManyObjects => ExternalSemaphoreError::TooManyObjects,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
 }
 
 unsafe impl DeviceOwned for Semaphore {
@@ -75,14 +509,20 @@ impl<D> Drop for Semaphore<D> where D: SafeDeref<Target = Device> {
     #[inline]
     fn drop(&mut self) {
         unsafe {
-            let vk = self.device.pointers();
-            vk.DestroySemaphore(self.device.internal_object(), self.semaphore, ptr::null());
+            if self.pooled {
+                self.device.semaphore_pool().recycle(self.semaphore);
+            } else {
+                let vk = self.device.pointers();
+                vk.DestroySemaphore(self.device.internal_object(), self.semaphore, ptr::null());
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use sync::ExternalSemaphoreError;
+    use sync::ExternalSemaphoreHandleType;
     use sync::Semaphore;
 
     #[test]
@@ -90,4 +530,24 @@ mod tests {
         let (device, _) = gfx_dev_and_queue!();
         let _ = Semaphore::new(device.clone());
     }
+
+    #[test]
+    fn exportable_missing_extension() {
+        let (device, _) = gfx_dev_and_queue!();
+        match Semaphore::new_exportable(device.clone(), ExternalSemaphoreHandleType::OpaqueFd) {
+            Err(ExternalSemaphoreError::ExtensionNotEnabled) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn sync_fd_permanent_import() {
+        let (device, _) = gfx_dev_and_queue!();
+        let mut semaphore = Semaphore::new(device.clone()).unwrap();
+        match unsafe { semaphore.import_fd(ExternalSemaphoreHandleType::SyncFd, -1, false) } {
+            Err(ExternalSemaphoreError::TemporaryImportRequired) => (),
+            _ => panic!()
+        }
+    }
 }
@@ -0,0 +1,128 @@
+// Copyright (c) 2017 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::fmt;
+use std::ptr;
+
+use sync::handle_pool::HandlePool;
+use vk;
+
+/// Pool of semaphores that are reused instead of being destroyed.
+///
+/// Each `Device` owns a pool, which can be accessed with `Device::semaphore_pool()`. Semaphores
+/// built with `Semaphore::from_pool` are taken from this pool, and are put back in it when they
+/// are destroyed. This is what the futures of the `sync` module and `acquire_next_image` use, so
+/// that drawing a frame doesn't have to create new semaphores once the pool is warm.
+///
+/// A semaphore is only returned to the pool if it is known to be unsignaled with no pending
+/// operation. Otherwise it is destroyed as usual.
+pub struct SemaphorePool {
+    // Semaphores that are not in use and that can be handed out.
+    pool: HandlePool<vk::Semaphore>,
+}
+
+impl SemaphorePool {
+    /// Builds a new empty pool.
+    #[inline]
+    pub(crate) fn new() -> SemaphorePool {
+        SemaphorePool {
+            pool: HandlePool::new(),
+        }
+    }
+
+    /// Returns the total number of semaphores that have been created for this pool.
+    ///
+    /// This number stops growing once enough semaphores are in circulation.
+    #[inline]
+    pub fn num_created(&self) -> usize {
+        self.pool.num_created()
+    }
+
+    /// Returns the number of semaphores that are currently unused and waiting in the pool.
+    #[inline]
+    pub fn num_available(&self) -> usize {
+        self.pool.num_available()
+    }
+
+    // Takes an unused semaphore from the pool, if there is one.
+    #[inline]
+    pub(crate) fn take(&self) -> Option<vk::Semaphore> {
+        self.pool.take()
+    }
+
+    // Must be called whenever a semaphore has been created for this pool.
+    #[inline]
+    pub(crate) fn record_creation(&self) {
+        self.pool.record_creation();
+    }
+
+    // Puts back a semaphore in the pool. It must be unsignaled and not used by the GPU.
+    #[inline]
+    pub(crate) unsafe fn recycle(&self, semaphore: vk::Semaphore) {
+        self.pool.recycle(semaphore);
+    }
+
+    // Destroys all the semaphores of the pool. Called when the device is destroyed.
+    pub(crate) unsafe fn destroy_all(&self, vk: &vk::DevicePointers, device: vk::Device) {
+        self.pool.destroy_all(|semaphore| vk.DestroySemaphore(device, semaphore, ptr::null()));
+    }
+}
+
+impl fmt::Debug for SemaphorePool {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "<Vulkan semaphore pool ({} created)>", self.num_created())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use command_buffer::CommandBufferBuilder;
+    use sync::GpuFuture;
+    use sync::Semaphore;
+    use VulkanObject;
+
+    #[test]
+    fn recycled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let first = Semaphore::from_pool(device.clone()).unwrap();
+        let raw = first.internal_object();
+        drop(first);
+
+        let second = Semaphore::from_pool(device.clone()).unwrap();
+        assert_eq!(second.internal_object(), raw);
+        assert_eq!(device.semaphore_pool().num_created(), 1);
+    }
+
+    #[test]
+    fn frames_plateau() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let mut created_after_warmup = 0;
+
+        for frame in 0 .. 64 {
+            let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap()
+                .build().unwrap();
+
+            cb.execute(queue.clone()).unwrap()
+                .then_signal_semaphore()
+                .then_signal_fence_and_flush().unwrap()
+                .wait(None).unwrap();
+
+            if frame == 3 {
+                created_after_warmup = device.semaphore_pool().num_created();
+            }
+        }
+
+        assert_eq!(device.semaphore_pool().num_created(), created_after_warmup);
+    }
+}